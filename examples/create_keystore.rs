@@ -0,0 +1,27 @@
+//! Create an encrypted signing keystore.
+//!
+//! Reads the secret seed and password from stdin (one per line) so neither
+//! ends up in shell history or the environment:
+//!
+//! ```text
+//! cargo run --example create_keystore -- /etc/aframp/hot-wallet.json
+//! ```
+use std::io::BufRead;
+use Bitmesh_backend::chains::stellar::keystore::EncryptedKeystore;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let path = std::env::args()
+        .nth(1)
+        .ok_or("usage: create_keystore <output-path>")?;
+
+    eprintln!("Enter secret seed, then password, one per line:");
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    let secret_seed = lines.next().ok_or("missing secret seed")??;
+    let password = lines.next().ok_or("missing password")??;
+
+    let keystore = EncryptedKeystore::encrypt(secret_seed.trim(), &password)?;
+    keystore.save(&path)?;
+    println!("Wrote keystore for {} to {}", keystore.public_key, path);
+    Ok(())
+}
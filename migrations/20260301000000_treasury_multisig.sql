@@ -0,0 +1,63 @@
+-- migrate:up
+-- Treasury multisig: M-of-N signed transactions for treasury operations.
+-- Notes:
+-- - Unsigned envelopes are built once and never mutated; signatures are collected separately.
+-- - Threshold validation happens against live Horizon signer data at submission time.
+
+CREATE TABLE IF NOT EXISTS treasury_transactions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    transaction_id UUID REFERENCES transactions(transaction_id) ON DELETE SET NULL,
+    purpose TEXT NOT NULL DEFAULT 'manual' CHECK (purpose IN ('manual', 'offramp_refund', 'payout')),
+    source_account VARCHAR(255) NOT NULL,
+    destination VARCHAR(255) NOT NULL,
+    amount NUMERIC(36, 18) NOT NULL CHECK (amount > 0),
+    asset_code TEXT NOT NULL,
+    asset_issuer TEXT NOT NULL,
+    memo JSONB NOT NULL DEFAULT '{"type": "none"}'::jsonb,
+    sequence BIGINT NOT NULL,
+    transaction_hash TEXT NOT NULL UNIQUE,
+    unsigned_envelope_xdr TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'submitted', 'failed', 'expired', 'cancelled')),
+    submitted_hash TEXT,
+    error_message TEXT,
+    requested_by TEXT,
+    metadata JSONB NOT NULL DEFAULT '{}'::jsonb,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE treasury_transactions IS 'Treasury transactions that require M-of-N signatures before submission.';
+COMMENT ON COLUMN treasury_transactions.transaction_id IS 'Optional link to the business transaction this treasury movement settles.';
+COMMENT ON COLUMN treasury_transactions.purpose IS 'Why the treasury movement was proposed; drives post-submission bookkeeping.';
+COMMENT ON COLUMN treasury_transactions.transaction_hash IS 'Network-specific hash of the unsigned transaction that signers sign.';
+COMMENT ON COLUMN treasury_transactions.status IS 'pending: collecting signatures; submitted: accepted by Horizon; failed/expired/cancelled: terminal.';
+COMMENT ON COLUMN treasury_transactions.expires_at IS 'Upper time bound of the envelope; signatures collected after this are useless.';
+
+CREATE INDEX IF NOT EXISTS idx_treasury_transactions_status
+    ON treasury_transactions(status, expires_at);
+CREATE INDEX IF NOT EXISTS idx_treasury_transactions_transaction
+    ON treasury_transactions(transaction_id);
+
+CREATE TABLE IF NOT EXISTS treasury_signatures (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    treasury_transaction_id UUID NOT NULL REFERENCES treasury_transactions(id) ON DELETE CASCADE,
+    signer_public_key VARCHAR(56) NOT NULL,
+    signature TEXT NOT NULL,
+    source TEXT NOT NULL DEFAULT 'api' CHECK (source IN ('api', 'presigned_envelope')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (treasury_transaction_id, signer_public_key)
+);
+
+COMMENT ON TABLE treasury_signatures IS 'Partial signatures collected for treasury transactions.';
+COMMENT ON COLUMN treasury_signatures.signature IS 'Hex-encoded ed25519 signature over the transaction hash.';
+COMMENT ON COLUMN treasury_signatures.source IS 'Whether the signature was posted directly or extracted from a pre-signed envelope.';
+
+CREATE TRIGGER set_updated_at_treasury_transactions
+    BEFORE UPDATE ON treasury_transactions
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+-- migrate:down
+DROP TRIGGER IF EXISTS set_updated_at_treasury_transactions ON treasury_transactions;
+DROP TABLE IF EXISTS treasury_signatures;
+DROP TABLE IF EXISTS treasury_transactions;
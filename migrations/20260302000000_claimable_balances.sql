@@ -0,0 +1,45 @@
+-- migrate:up
+-- Claimable balances created when a cNGN payout targets a wallet without a trustline.
+-- Notes:
+-- - The recipient can claim until reclaim_after; after that only the source account can.
+-- - balance_id is derived from the creating transaction, so it is known before submission.
+
+CREATE TABLE IF NOT EXISTS claimable_balances (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    transaction_id UUID REFERENCES transactions(transaction_id) ON DELETE SET NULL,
+    balance_id TEXT NOT NULL UNIQUE,
+    source_account VARCHAR(255) NOT NULL,
+    claimant VARCHAR(255) NOT NULL,
+    asset_code TEXT NOT NULL,
+    asset_issuer TEXT NOT NULL,
+    amount NUMERIC(36, 18) NOT NULL CHECK (amount > 0),
+    status TEXT NOT NULL DEFAULT 'unsubmitted' CHECK (status IN ('unsubmitted', 'pending', 'claimed', 'reclaimed', 'failed')),
+    creation_tx_hash TEXT NOT NULL,
+    resolution_tx_hash TEXT,
+    error_message TEXT,
+    reclaim_after TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE claimable_balances IS 'cNGN payouts parked in Stellar claimable balances because the recipient had no trustline.';
+COMMENT ON COLUMN claimable_balances.transaction_id IS 'Business transaction the payout belongs to, if any.';
+COMMENT ON COLUMN claimable_balances.balance_id IS 'Horizon hex claimable balance id.';
+COMMENT ON COLUMN claimable_balances.status IS 'unsubmitted: built, not yet on ledger; pending: claimable; claimed/reclaimed/failed: terminal.';
+COMMENT ON COLUMN claimable_balances.reclaim_after IS 'From this time the recipient can no longer claim and the source may reclaim.';
+COMMENT ON COLUMN claimable_balances.resolution_tx_hash IS 'Hash of the reclaim transaction when reclaimed by us.';
+
+CREATE INDEX IF NOT EXISTS idx_claimable_balances_claimant
+    ON claimable_balances(claimant, status);
+CREATE INDEX IF NOT EXISTS idx_claimable_balances_status
+    ON claimable_balances(status, reclaim_after);
+CREATE INDEX IF NOT EXISTS idx_claimable_balances_transaction
+    ON claimable_balances(transaction_id);
+
+CREATE TRIGGER set_updated_at_claimable_balances
+    BEFORE UPDATE ON claimable_balances
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+-- migrate:down
+DROP TRIGGER IF EXISTS set_updated_at_claimable_balances ON claimable_balances;
+DROP TABLE IF EXISTS claimable_balances;
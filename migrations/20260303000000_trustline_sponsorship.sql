@@ -0,0 +1,38 @@
+-- migrate:up
+-- Sponsored trustline reserves for users who cannot cover the XLM reserve themselves.
+-- Notes:
+-- - A sponsored 'create' operation records the sponsor and how many reserves it pays for.
+-- - A sponsorship counts against the user's limit while it is completed and not revoked.
+-- - Revocations are logged as 'revoke_sponsorship' operations.
+
+ALTER TABLE trustline_operations
+    ADD COLUMN IF NOT EXISTS sponsor_account VARCHAR(255),
+    ADD COLUMN IF NOT EXISTS sponsored_reserves INTEGER NOT NULL DEFAULT 0 CHECK (sponsored_reserves >= 0),
+    ADD COLUMN IF NOT EXISTS sponsorship_revoked_at TIMESTAMPTZ;
+
+ALTER TABLE trustline_operations
+    DROP CONSTRAINT IF EXISTS trustline_operations_operation_type_check;
+ALTER TABLE trustline_operations
+    ADD CONSTRAINT trustline_operations_operation_type_check
+    CHECK (operation_type IN ('create', 'update', 'remove', 'verify', 'revoke_sponsorship'));
+
+COMMENT ON COLUMN trustline_operations.sponsor_account IS 'Platform account paying the reserve, for sponsored operations.';
+COMMENT ON COLUMN trustline_operations.sponsored_reserves IS 'Number of base reserves paid by the sponsor.';
+COMMENT ON COLUMN trustline_operations.sponsorship_revoked_at IS 'When the sponsor stopped paying the reserve.';
+
+CREATE INDEX IF NOT EXISTS idx_trustline_operations_active_sponsorships
+    ON trustline_operations(wallet_address, asset_code)
+    WHERE sponsor_account IS NOT NULL AND sponsorship_revoked_at IS NULL;
+
+-- migrate:down
+DROP INDEX IF EXISTS idx_trustline_operations_active_sponsorships;
+DELETE FROM trustline_operations WHERE operation_type = 'revoke_sponsorship';
+ALTER TABLE trustline_operations
+    DROP CONSTRAINT IF EXISTS trustline_operations_operation_type_check;
+ALTER TABLE trustline_operations
+    ADD CONSTRAINT trustline_operations_operation_type_check
+    CHECK (operation_type IN ('create', 'update', 'remove', 'verify'));
+ALTER TABLE trustline_operations
+    DROP COLUMN IF EXISTS sponsorship_revoked_at,
+    DROP COLUMN IF EXISTS sponsored_reserves,
+    DROP COLUMN IF EXISTS sponsor_account;
@@ -0,0 +1,66 @@
+-- migrate:up
+-- Append-only exchange rate history with validity intervals.
+-- Notes:
+-- - exchange_rates keeps only the latest rate per pair; every change is also appended here.
+-- - A row is in effect over [valid_from, valid_until); valid_until is NULL for the current rate.
+-- - Rows are never updated except to close the open interval once, and never deleted.
+
+CREATE TABLE IF NOT EXISTS exchange_rate_history (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    from_currency TEXT NOT NULL,
+    to_currency TEXT NOT NULL,
+    rate NUMERIC(36, 18) NOT NULL CHECK (rate > 0),
+    source TEXT,
+    valid_from TIMESTAMPTZ NOT NULL,
+    valid_until TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CHECK (valid_until IS NULL OR valid_until > valid_from)
+);
+
+COMMENT ON TABLE exchange_rate_history IS 'Every exchange rate ever in effect, for point-in-time lookups and audits.';
+COMMENT ON COLUMN exchange_rate_history.valid_from IS 'Start of the interval in which this rate was in effect (inclusive).';
+COMMENT ON COLUMN exchange_rate_history.valid_until IS 'End of the interval (exclusive); NULL while the rate is current.';
+COMMENT ON COLUMN exchange_rate_history.source IS 'Provider or operator that set the rate.';
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_exchange_rate_history_open
+    ON exchange_rate_history(from_currency, to_currency)
+    WHERE valid_until IS NULL;
+CREATE INDEX IF NOT EXISTS idx_exchange_rate_history_pair_valid_from
+    ON exchange_rate_history(from_currency, to_currency, valid_from DESC);
+
+CREATE OR REPLACE FUNCTION exchange_rate_history_append_only()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        RAISE EXCEPTION 'exchange_rate_history is append-only';
+    END IF;
+    IF OLD.valid_until IS NOT NULL
+        OR NEW.valid_until IS NULL
+        OR NEW.id <> OLD.id
+        OR NEW.from_currency <> OLD.from_currency
+        OR NEW.to_currency <> OLD.to_currency
+        OR NEW.rate <> OLD.rate
+        OR NEW.source IS DISTINCT FROM OLD.source
+        OR NEW.valid_from <> OLD.valid_from
+        OR NEW.created_at <> OLD.created_at THEN
+        RAISE EXCEPTION 'exchange_rate_history rows can only have their open interval closed';
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER exchange_rate_history_append_only
+    BEFORE UPDATE OR DELETE ON exchange_rate_history
+    FOR EACH ROW EXECUTE FUNCTION exchange_rate_history_append_only();
+
+-- Seed with the rates currently in effect
+INSERT INTO exchange_rate_history (from_currency, to_currency, rate, source, valid_from)
+SELECT from_currency, to_currency, rate::NUMERIC, source, updated_at
+FROM exchange_rates
+WHERE rate ~ '^[0-9]+(\.[0-9]+)?$' AND rate::NUMERIC > 0
+ON CONFLICT DO NOTHING;
+
+-- migrate:down
+DROP TRIGGER IF EXISTS exchange_rate_history_append_only ON exchange_rate_history;
+DROP FUNCTION IF EXISTS exchange_rate_history_append_only();
+DROP TABLE IF EXISTS exchange_rate_history;
@@ -0,0 +1,24 @@
+-- migrate:up
+-- Staleness markers for exchange rates maintained by the rate refresher.
+-- Notes:
+-- - A pair is marked stale when refreshes keep failing past the staleness window, or when a
+--   new rate jumps further from the previous one than the circuit breaker allows.
+-- - Recording a new rate clears the marker.
+-- - Quotes are refused while any rate they depend on is marked stale.
+
+ALTER TABLE exchange_rates
+    ADD COLUMN IF NOT EXISTS stale_since TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS stale_reason TEXT;
+
+COMMENT ON COLUMN exchange_rates.stale_since IS 'When the rate was marked stale; NULL while it is fresh.';
+COMMENT ON COLUMN exchange_rates.stale_reason IS 'Why the rate was marked stale (refresh failures or a rejected jump).';
+
+CREATE INDEX IF NOT EXISTS idx_exchange_rates_stale
+    ON exchange_rates(from_currency, to_currency)
+    WHERE stale_since IS NOT NULL;
+
+-- migrate:down
+DROP INDEX IF EXISTS idx_exchange_rates_stale;
+ALTER TABLE exchange_rates
+    DROP COLUMN IF EXISTS stale_reason,
+    DROP COLUMN IF EXISTS stale_since;
@@ -0,0 +1,39 @@
+-- migrate:up
+-- Inventory held back for issued quotes so the same hot wallet or provider float is not promised twice.
+-- Notes:
+-- - inventory_pool names the balance being drawn on, e.g. 'stellar:cngn' or 'flutterwave:NGN'.
+-- - reference is the quote id (onramp) or transaction id (offramp payout) holding the reservation.
+-- - Only 'reserved' rows whose expires_at is in the future count against available inventory.
+-- - Expired reservations are moved to 'released' the next time the pool is reserved against.
+
+CREATE TABLE IF NOT EXISTS liquidity_reservations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    inventory_pool TEXT NOT NULL,
+    reference TEXT NOT NULL,
+    amount NUMERIC(36, 18) NOT NULL CHECK (amount > 0),
+    status TEXT NOT NULL DEFAULT 'reserved' CHECK (status IN ('reserved', 'consumed', 'released')),
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (inventory_pool, reference)
+);
+
+COMMENT ON TABLE liquidity_reservations IS 'Hot wallet and provider float earmarked for outstanding quotes and payouts.';
+COMMENT ON COLUMN liquidity_reservations.inventory_pool IS 'Balance the reservation draws on: stellar:cngn or <provider>:<currency>.';
+COMMENT ON COLUMN liquidity_reservations.reference IS 'Quote id or transaction id that owns the reservation.';
+COMMENT ON COLUMN liquidity_reservations.status IS 'reserved: held; consumed: the quote was used; released: expired or cancelled.';
+COMMENT ON COLUMN liquidity_reservations.expires_at IS 'After this time the reservation no longer counts against inventory.';
+
+CREATE INDEX IF NOT EXISTS idx_liquidity_reservations_active
+    ON liquidity_reservations(inventory_pool, expires_at)
+    WHERE status = 'reserved';
+CREATE INDEX IF NOT EXISTS idx_liquidity_reservations_reference
+    ON liquidity_reservations(reference);
+
+CREATE TRIGGER set_updated_at_liquidity_reservations
+    BEFORE UPDATE ON liquidity_reservations
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+-- migrate:down
+DROP TRIGGER IF EXISTS set_updated_at_liquidity_reservations ON liquidity_reservations;
+DROP TABLE IF EXISTS liquidity_reservations;
@@ -0,0 +1,72 @@
+-- migrate:up
+-- Pricing rules evaluated on top of the matched fee_structures tier.
+-- Notes:
+-- - rule_type selects which columns apply:
+--   promo_code: promo_code, discount_percent/discount_flat, budget_total (total discount the promo may grant)
+--   first_n_free: free_transactions (platform fee waived for a wallet's first N completed transactions)
+--   volume_discount: min_monthly_volume, discount_percent (wallet volume in the current calendar month)
+--   partner_override: partner_key_hash, platform_fee_percent (replaces the tier's platform fee)
+-- - Rules run highest priority first. A non-stackable rule applies only if nothing applied before it,
+--   and nothing applies after it.
+-- - Discounts only ever reduce the platform fee; provider fees are a pass-through cost.
+
+CREATE TABLE IF NOT EXISTS fee_rules (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL,
+    rule_type TEXT NOT NULL CHECK (rule_type IN ('promo_code', 'first_n_free', 'volume_discount', 'partner_override')),
+    transaction_type TEXT CHECK (transaction_type IN ('onramp', 'offramp', 'bill_payment', 'exchange', 'transfer')),
+    priority INTEGER NOT NULL DEFAULT 0,
+    stackable BOOLEAN NOT NULL DEFAULT FALSE,
+    promo_code TEXT UNIQUE,
+    partner_key_hash TEXT,
+    free_transactions INTEGER CHECK (free_transactions IS NULL OR free_transactions > 0),
+    min_monthly_volume NUMERIC(36, 18) CHECK (min_monthly_volume IS NULL OR min_monthly_volume >= 0),
+    discount_percent NUMERIC(10, 4) CHECK (discount_percent IS NULL OR (discount_percent >= 0 AND discount_percent <= 100)),
+    discount_flat NUMERIC(36, 18) CHECK (discount_flat IS NULL OR discount_flat >= 0),
+    platform_fee_percent NUMERIC(10, 4) CHECK (platform_fee_percent IS NULL OR (platform_fee_percent >= 0 AND platform_fee_percent <= 100)),
+    budget_total NUMERIC(36, 18) CHECK (budget_total IS NULL OR budget_total >= 0),
+    budget_used NUMERIC(36, 18) NOT NULL DEFAULT 0 CHECK (budget_used >= 0),
+    starts_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at TIMESTAMPTZ,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CONSTRAINT chk_fee_rules_budget CHECK (budget_total IS NULL OR budget_used <= budget_total),
+    CONSTRAINT chk_fee_rules_promo CHECK (rule_type <> 'promo_code' OR promo_code IS NOT NULL),
+    CONSTRAINT chk_fee_rules_partner CHECK (rule_type <> 'partner_override' OR (partner_key_hash IS NOT NULL AND platform_fee_percent IS NOT NULL)),
+    CONSTRAINT chk_fee_rules_first_n CHECK (rule_type <> 'first_n_free' OR free_transactions IS NOT NULL),
+    CONSTRAINT chk_fee_rules_volume CHECK (rule_type <> 'volume_discount' OR (min_monthly_volume IS NOT NULL AND discount_percent IS NOT NULL))
+);
+
+COMMENT ON TABLE fee_rules IS 'Promotions, volume discounts and partner pricing applied on top of fee_structures.';
+COMMENT ON COLUMN fee_rules.transaction_type IS 'Transaction type the rule applies to; NULL applies to all.';
+COMMENT ON COLUMN fee_rules.priority IS 'Higher priority rules are evaluated first.';
+COMMENT ON COLUMN fee_rules.stackable IS 'Whether the rule combines with other applied rules.';
+COMMENT ON COLUMN fee_rules.partner_key_hash IS 'Hex SHA-256 of the partner API key; the key itself is never stored.';
+COMMENT ON COLUMN fee_rules.budget_total IS 'Total platform fee the promo may waive; NULL is unlimited.';
+COMMENT ON COLUMN fee_rules.budget_used IS 'Platform fee already waived by charged transactions.';
+
+CREATE INDEX IF NOT EXISTS idx_fee_rules_active
+    ON fee_rules(transaction_type, priority DESC)
+    WHERE is_active = TRUE;
+CREATE INDEX IF NOT EXISTS idx_fee_rules_partner
+    ON fee_rules(partner_key_hash)
+    WHERE partner_key_hash IS NOT NULL;
+
+CREATE TRIGGER set_updated_at_fee_rules
+    BEFORE UPDATE ON fee_rules
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+ALTER TABLE fee_calculation_logs
+    ADD COLUMN IF NOT EXISTS applied_rule_ids UUID[] NOT NULL DEFAULT '{}';
+
+COMMENT ON COLUMN fee_calculation_logs.applied_rule_ids IS 'fee_rules applied to this calculation; details are in calculation_metadata.applied_rules.';
+
+CREATE INDEX IF NOT EXISTS idx_fee_calculation_logs_applied_rules
+    ON fee_calculation_logs USING GIN (applied_rule_ids);
+
+-- migrate:down
+DROP INDEX IF EXISTS idx_fee_calculation_logs_applied_rules;
+ALTER TABLE fee_calculation_logs DROP COLUMN IF EXISTS applied_rule_ids;
+DROP TRIGGER IF EXISTS set_updated_at_fee_rules ON fee_rules;
+DROP TABLE IF EXISTS fee_rules;
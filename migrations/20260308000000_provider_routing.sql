@@ -0,0 +1,42 @@
+-- migrate:up
+-- Persisted provider outcomes and daily caps for cost- and reliability-based routing.
+-- Notes:
+-- - provider_routing_stats holds one row per provider per UTC day. Routing reads a rolling window of
+--   rows for success rate and average latency, and today's row for volume against the daily cap.
+-- - Latency is recorded for every initiation attempt; routed_volume only counts successful ones.
+-- - payment_provider_configs.daily_volume_cap is in the routed amount's currency; NULL is uncapped.
+
+CREATE TABLE IF NOT EXISTS provider_routing_stats (
+    provider TEXT NOT NULL REFERENCES payment_provider_configs(provider),
+    stat_date DATE NOT NULL,
+    success_count BIGINT NOT NULL DEFAULT 0 CHECK (success_count >= 0),
+    failure_count BIGINT NOT NULL DEFAULT 0 CHECK (failure_count >= 0),
+    total_latency_ms BIGINT NOT NULL DEFAULT 0 CHECK (total_latency_ms >= 0),
+    routed_volume NUMERIC(36, 18) NOT NULL DEFAULT 0 CHECK (routed_volume >= 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (provider, stat_date)
+);
+
+COMMENT ON TABLE provider_routing_stats IS 'Daily per-provider initiation outcomes used by the routing engine.';
+COMMENT ON COLUMN provider_routing_stats.stat_date IS 'UTC day the outcomes were recorded on.';
+COMMENT ON COLUMN provider_routing_stats.total_latency_ms IS 'Sum of initiation latencies; divide by success_count + failure_count for the average.';
+COMMENT ON COLUMN provider_routing_stats.routed_volume IS 'Amount successfully routed to the provider on stat_date.';
+
+CREATE INDEX IF NOT EXISTS idx_provider_routing_stats_date
+    ON provider_routing_stats(stat_date DESC);
+
+CREATE TRIGGER set_updated_at_provider_routing_stats
+    BEFORE UPDATE ON provider_routing_stats
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+ALTER TABLE payment_provider_configs
+    ADD COLUMN IF NOT EXISTS daily_volume_cap NUMERIC(36, 18) CHECK (daily_volume_cap IS NULL OR daily_volume_cap >= 0);
+
+COMMENT ON COLUMN payment_provider_configs.daily_volume_cap IS 'Maximum amount routed to the provider per UTC day; NULL is uncapped.';
+
+-- migrate:down
+ALTER TABLE payment_provider_configs DROP COLUMN IF EXISTS daily_volume_cap;
+DROP TRIGGER IF EXISTS set_updated_at_provider_routing_stats ON provider_routing_stats;
+DROP INDEX IF EXISTS idx_provider_routing_stats_date;
+DROP TABLE IF EXISTS provider_routing_stats;
@@ -0,0 +1,48 @@
+-- migrate:up
+-- Raw webhook bodies and a Postgres-backed processing queue for webhook_events.
+-- Notes:
+-- - raw_body is the request body exactly as received; signatures are verified against it and
+--   processing re-parses it, so key order and whitespace are never lost to JSONB normalisation.
+--   payload stays as the parsed JSONB copy for querying. Rows written before this migration have
+--   no raw_body and fall back to payload.
+-- - Workers claim pending rows whose next_attempt_at has passed with FOR UPDATE SKIP LOCKED and
+--   lease them until locked_until; a processing row whose lease ran out is claimed again.
+-- - Events that keep failing, or can never be processed, move to dead_lettered and stay there
+--   until replayed from the admin API. Existing failed rows become dead_lettered.
+
+ALTER TABLE webhook_events
+    ADD COLUMN IF NOT EXISTS raw_body BYTEA,
+    ADD COLUMN IF NOT EXISTS next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    ADD COLUMN IF NOT EXISTS locked_until TIMESTAMPTZ;
+
+ALTER TABLE webhook_events DROP CONSTRAINT IF EXISTS webhook_events_status_check;
+UPDATE webhook_events SET status = 'dead_lettered' WHERE status = 'failed';
+ALTER TABLE webhook_events
+    ADD CONSTRAINT webhook_events_status_check
+    CHECK (status IN ('pending', 'processing', 'completed', 'dead_lettered'));
+
+COMMENT ON COLUMN webhook_events.raw_body IS 'Request body exactly as received from the provider; the signed bytes.';
+COMMENT ON COLUMN webhook_events.next_attempt_at IS 'Earliest time a worker may process the event again.';
+COMMENT ON COLUMN webhook_events.locked_until IS 'Lease held by the worker processing the event; expired leases are reclaimed.';
+COMMENT ON COLUMN webhook_events.status IS 'pending, processing (leased by a worker), completed or dead_lettered (gave up; replayable).';
+
+CREATE INDEX IF NOT EXISTS idx_webhook_events_due
+    ON webhook_events(next_attempt_at)
+    WHERE status IN ('pending', 'processing');
+
+CREATE INDEX IF NOT EXISTS idx_webhook_events_dead_lettered
+    ON webhook_events(provider, updated_at DESC)
+    WHERE status = 'dead_lettered';
+
+-- migrate:down
+DROP INDEX IF EXISTS idx_webhook_events_dead_lettered;
+DROP INDEX IF EXISTS idx_webhook_events_due;
+ALTER TABLE webhook_events DROP CONSTRAINT IF EXISTS webhook_events_status_check;
+UPDATE webhook_events SET status = 'failed' WHERE status = 'dead_lettered';
+ALTER TABLE webhook_events
+    ADD CONSTRAINT webhook_events_status_check
+    CHECK (status IN ('pending', 'processing', 'completed', 'failed'));
+ALTER TABLE webhook_events
+    DROP COLUMN IF EXISTS locked_until,
+    DROP COLUMN IF EXISTS next_attempt_at,
+    DROP COLUMN IF EXISTS raw_body;
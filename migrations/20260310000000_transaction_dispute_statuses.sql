@@ -0,0 +1,22 @@
+-- migrate:up
+-- Transaction statuses for refunds, reversals and disputes driven by provider webhooks.
+-- Notes:
+-- - transactions.status references transaction_statuses, which only seeded pending, processing,
+--   completed and failed. The orchestrator also writes created, payment_confirmed,
+--   refund_initiated and refunded; disputed and charged_back are new.
+-- - A completed transaction can move to refund_initiated (payout reversed), refunded (provider
+--   refund) or disputed; a disputed one returns to completed when won or ends charged_back when lost.
+
+INSERT INTO transaction_statuses (code, description) VALUES
+  ('created', 'Created, not yet sent to a provider'),
+  ('payment_confirmed', 'Payment confirmed by the provider, settlement pending'),
+  ('refund_initiated', 'Refund owed to the user'),
+  ('refunded', 'Refunded to the user'),
+  ('disputed', 'Customer disputed the payment with their bank'),
+  ('charged_back', 'Dispute lost and the payment charged back')
+ON CONFLICT (code) DO NOTHING;
+
+-- migrate:down
+DELETE FROM transaction_statuses
+WHERE code IN ('disputed', 'charged_back')
+  AND NOT EXISTS (SELECT 1 FROM transactions t WHERE t.status = transaction_statuses.code);
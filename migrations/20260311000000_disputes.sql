@@ -0,0 +1,120 @@
+-- migrate:up
+-- Card payment disputes (chargebacks), their evidence, the ledger adjustments posted when one is
+-- lost and the wallet risk flags raised against the wallet that received the cNGN.
+-- Notes:
+-- - A dispute is keyed by the provider's own dispute ID; webhooks and the dispute poller both
+--   upsert into the same row, so whichever sees a change first wins and the other is a no-op.
+-- - transaction_id links the dispute to the collection it concerns. The transaction itself moves
+--   to disputed and then back to completed (won) or to charged_back (lost).
+-- - Evidence files live in object storage; dispute_evidence records what ops uploaded and when it
+--   was sent to the provider.
+-- - ledger_adjustments has one row per dispute and kind, so posting a loss twice is a no-op.
+-- - wallet_risk_flags stay active until cleared; a wallet with an active freeze cannot quote.
+
+CREATE TABLE IF NOT EXISTS disputes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    transaction_id UUID NOT NULL REFERENCES transactions(transaction_id) ON DELETE RESTRICT,
+    provider TEXT NOT NULL CHECK (provider IN ('flutterwave', 'paystack', 'mpesa')),
+    provider_dispute_id TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'open'
+        CHECK (status IN ('open', 'under_review', 'won', 'lost')),
+    reason TEXT,
+    amount NUMERIC(36, 18) NOT NULL CHECK (amount >= 0),
+    currency TEXT NOT NULL,
+    evidence_due_at TIMESTAMPTZ,
+    ops_action TEXT CHECK (ops_action IN ('accepted', 'contested')),
+    ops_action_by TEXT,
+    ops_action_at TIMESTAMPTZ,
+    ops_note TEXT,
+    resolved_at TIMESTAMPTZ,
+    provider_data JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (provider, provider_dispute_id)
+);
+
+COMMENT ON TABLE disputes IS 'Chargebacks raised against card collections, one row per provider dispute.';
+COMMENT ON COLUMN disputes.status IS 'open (awaiting our response), under_review (contested, bank deciding), won or lost.';
+COMMENT ON COLUMN disputes.evidence_due_at IS 'Provider deadline for accepting or contesting; silence after it loses the dispute.';
+COMMENT ON COLUMN disputes.ops_action IS 'What ops did: accepted (conceded) or contested with evidence.';
+COMMENT ON COLUMN disputes.provider_data IS 'Dispute object as last reported by the provider.';
+
+CREATE INDEX IF NOT EXISTS idx_disputes_transaction ON disputes(transaction_id);
+CREATE INDEX IF NOT EXISTS idx_disputes_open_due
+    ON disputes(evidence_due_at)
+    WHERE status = 'open';
+
+CREATE TRIGGER set_updated_at_disputes
+    BEFORE UPDATE ON disputes
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+CREATE TABLE IF NOT EXISTS dispute_evidence (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    dispute_id UUID NOT NULL REFERENCES disputes(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL
+        CHECK (kind IN ('receipt', 'delivery_proof', 'customer_communication', 'identity', 'other')),
+    file_name TEXT NOT NULL,
+    content_type TEXT,
+    storage_url TEXT NOT NULL,
+    description TEXT,
+    uploaded_by TEXT NOT NULL,
+    submitted_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE dispute_evidence IS 'Evidence files uploaded by ops for a dispute.';
+COMMENT ON COLUMN dispute_evidence.storage_url IS 'Where the file is stored; the provider receives this reference.';
+COMMENT ON COLUMN dispute_evidence.submitted_at IS 'When the file was sent to the provider with a contest; NULL until then.';
+
+CREATE INDEX IF NOT EXISTS idx_dispute_evidence_dispute ON dispute_evidence(dispute_id);
+
+CREATE TABLE IF NOT EXISTS ledger_adjustments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    transaction_id UUID NOT NULL REFERENCES transactions(transaction_id) ON DELETE RESTRICT,
+    dispute_id UUID REFERENCES disputes(id) ON DELETE RESTRICT,
+    wallet_address VARCHAR(255) NOT NULL,
+    kind TEXT NOT NULL CHECK (kind IN ('chargeback')),
+    amount NUMERIC(36, 18) NOT NULL,
+    currency TEXT NOT NULL,
+    description TEXT,
+    metadata JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (dispute_id, kind)
+);
+
+COMMENT ON TABLE ledger_adjustments IS 'Manual and automatic corrections to platform balances, such as funds lost to chargebacks.';
+COMMENT ON COLUMN ledger_adjustments.amount IS 'Signed amount; negative is a loss to the platform.';
+COMMENT ON COLUMN ledger_adjustments.metadata IS 'Context such as the cNGN already delivered for the transaction.';
+
+CREATE INDEX IF NOT EXISTS idx_ledger_adjustments_wallet ON ledger_adjustments(wallet_address, created_at DESC);
+
+CREATE TABLE IF NOT EXISTS wallet_risk_flags (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    wallet_address VARCHAR(255) NOT NULL
+        REFERENCES wallets(wallet_address) ON UPDATE CASCADE ON DELETE CASCADE,
+    action TEXT NOT NULL CHECK (action IN ('flag', 'freeze')),
+    reason TEXT NOT NULL,
+    dispute_id UUID REFERENCES disputes(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    cleared_at TIMESTAMPTZ,
+    cleared_by TEXT
+);
+
+COMMENT ON TABLE wallet_risk_flags IS 'Risk actions against a wallet; flag marks it for review, freeze blocks new quotes.';
+COMMENT ON COLUMN wallet_risk_flags.cleared_at IS 'When ops lifted the flag; NULL while active.';
+
+CREATE INDEX IF NOT EXISTS idx_wallet_risk_flags_active
+    ON wallet_risk_flags(wallet_address)
+    WHERE cleared_at IS NULL;
+
+-- migrate:down
+DROP INDEX IF EXISTS idx_wallet_risk_flags_active;
+DROP TABLE IF EXISTS wallet_risk_flags;
+DROP INDEX IF EXISTS idx_ledger_adjustments_wallet;
+DROP TABLE IF EXISTS ledger_adjustments;
+DROP INDEX IF EXISTS idx_dispute_evidence_dispute;
+DROP TABLE IF EXISTS dispute_evidence;
+DROP TRIGGER IF EXISTS set_updated_at_disputes ON disputes;
+DROP INDEX IF EXISTS idx_disputes_open_due;
+DROP INDEX IF EXISTS idx_disputes_transaction;
+DROP TABLE IF EXISTS disputes;
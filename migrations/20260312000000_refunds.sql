@@ -0,0 +1,52 @@
+-- migrate:up
+-- Fiat refunds of onramp collections whose cNGN could not be delivered (missing trustline,
+-- invalid wallet, no liquidity), sent back through the provider that collected the payment.
+-- Notes:
+-- - A refund moves its transaction to refund_initiated, and to refunded once the provider
+--   confirms it (immediately or via a refund webhook).
+-- - amount is what goes back to the customer; provider_fee is the provider's collection fee kept
+--   back from a full refund when the fee is not refundable.
+-- - At most one refund per transaction may be in flight or processed; a failed refund can be
+--   retried and the new attempt gets its own row.
+
+CREATE TABLE IF NOT EXISTS refunds (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    transaction_id UUID NOT NULL REFERENCES transactions(transaction_id) ON DELETE RESTRICT,
+    provider TEXT NOT NULL CHECK (provider IN ('flutterwave', 'paystack', 'mpesa')),
+    provider_refund_id TEXT,
+    status TEXT NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'processing', 'processed', 'failed')),
+    reason TEXT NOT NULL
+        CHECK (reason IN ('missing_trustline', 'invalid_wallet', 'insufficient_liquidity', 'other')),
+    amount NUMERIC(36, 18) NOT NULL CHECK (amount > 0),
+    provider_fee NUMERIC(36, 18) NOT NULL DEFAULT 0 CHECK (provider_fee >= 0),
+    currency TEXT NOT NULL,
+    requested_by TEXT NOT NULL,
+    note TEXT,
+    failure_reason TEXT,
+    provider_data JSONB NOT NULL DEFAULT '{}'::jsonb,
+    processed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE refunds IS 'Fiat refunds of onramp payments, one row per attempt.';
+COMMENT ON COLUMN refunds.status IS 'pending (not yet accepted by the provider), processing, processed or failed.';
+COMMENT ON COLUMN refunds.amount IS 'Amount returned to the customer, in the collection currency.';
+COMMENT ON COLUMN refunds.provider_fee IS 'Provider collection fee withheld from the refund.';
+COMMENT ON COLUMN refunds.requested_by IS 'Operator or worker that asked for the refund.';
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_refunds_active_transaction
+    ON refunds(transaction_id)
+    WHERE status <> 'failed';
+CREATE INDEX IF NOT EXISTS idx_refunds_status ON refunds(status, created_at);
+
+CREATE TRIGGER set_updated_at_refunds
+    BEFORE UPDATE ON refunds
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+-- migrate:down
+DROP TRIGGER IF EXISTS set_updated_at_refunds ON refunds;
+DROP INDEX IF EXISTS idx_refunds_status;
+DROP INDEX IF EXISTS idx_refunds_active_transaction;
+DROP TABLE IF EXISTS refunds;
@@ -0,0 +1,97 @@
+-- migrate:up
+-- Durable background jobs and the recurring schedules that enqueue them.
+-- Notes:
+-- - Runners claim queued jobs whose run_at has passed with FOR UPDATE SKIP LOCKED and hold a
+--   lease (lease_expires_at) that they extend by heartbeat while the job runs. A running job whose
+--   lease ran out is claimed again, or buried once it has used all of its attempts.
+-- - attempts counts claims. A failed attempt goes back to queued with run_at pushed out by
+--   exponential backoff from backoff_seconds; the last one moves the job to dead.
+-- - unique_key deduplicates work: at most one queued or running job may hold a given key.
+--   Scheduled jobs use schedule:<name>, so a slow run is never overlapped by the next.
+-- - job_schedules.schedule is either an interval ('@every 30s', '@every 5m') or a five-field
+--   cron expression evaluated in UTC.
+
+CREATE TABLE IF NOT EXISTS jobs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    kind TEXT NOT NULL,
+    payload JSONB NOT NULL DEFAULT '{}'::jsonb,
+    status TEXT NOT NULL DEFAULT 'queued'
+        CHECK (status IN ('queued', 'running', 'succeeded', 'dead', 'cancelled')),
+    attempts INTEGER NOT NULL DEFAULT 0 CHECK (attempts >= 0),
+    max_attempts INTEGER NOT NULL DEFAULT 5 CHECK (max_attempts > 0),
+    backoff_seconds INTEGER NOT NULL DEFAULT 10 CHECK (backoff_seconds >= 0),
+    run_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    unique_key TEXT,
+    schedule_name TEXT,
+    locked_by TEXT,
+    lease_expires_at TIMESTAMPTZ,
+    heartbeat_at TIMESTAMPTZ,
+    last_error TEXT,
+    started_at TIMESTAMPTZ,
+    finished_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE jobs IS 'Durable background jobs run by the job runners.';
+COMMENT ON COLUMN jobs.kind IS 'Job type, e.g. offramp.process; selects the handler.';
+COMMENT ON COLUMN jobs.status IS 'queued, running (leased by a runner), succeeded, dead (out of attempts; retryable from the admin API) or cancelled.';
+COMMENT ON COLUMN jobs.attempts IS 'Number of times the job has been claimed.';
+COMMENT ON COLUMN jobs.backoff_seconds IS 'Delay after the first failed attempt; doubles with each further failure.';
+COMMENT ON COLUMN jobs.unique_key IS 'At most one queued or running job may hold a key.';
+COMMENT ON COLUMN jobs.lease_expires_at IS 'Lease held by the runner executing the job; extended by heartbeat.';
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_jobs_active_unique_key
+    ON jobs(unique_key)
+    WHERE status IN ('queued', 'running') AND unique_key IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_jobs_due
+    ON jobs(kind, run_at)
+    WHERE status = 'queued';
+
+CREATE INDEX IF NOT EXISTS idx_jobs_leases
+    ON jobs(lease_expires_at)
+    WHERE status = 'running';
+
+CREATE INDEX IF NOT EXISTS idx_jobs_status ON jobs(status, updated_at DESC);
+
+CREATE TRIGGER set_updated_at_jobs
+    BEFORE UPDATE ON jobs
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+CREATE TABLE IF NOT EXISTS job_schedules (
+    name TEXT PRIMARY KEY,
+    kind TEXT NOT NULL,
+    payload JSONB NOT NULL DEFAULT '{}'::jsonb,
+    schedule TEXT NOT NULL,
+    max_attempts INTEGER NOT NULL DEFAULT 3 CHECK (max_attempts > 0),
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    next_run_at TIMESTAMPTZ NOT NULL,
+    last_enqueued_at TIMESTAMPTZ,
+    last_job_id UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE job_schedules IS 'Recurring jobs; each run is enqueued as a jobs row.';
+COMMENT ON COLUMN job_schedules.schedule IS '@every <n>s|m|h, or a five-field cron expression in UTC.';
+COMMENT ON COLUMN job_schedules.enabled IS 'Paused schedules keep their definition but enqueue nothing.';
+
+CREATE INDEX IF NOT EXISTS idx_job_schedules_due
+    ON job_schedules(next_run_at)
+    WHERE enabled;
+
+CREATE TRIGGER set_updated_at_job_schedules
+    BEFORE UPDATE ON job_schedules
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+-- migrate:down
+DROP TRIGGER IF EXISTS set_updated_at_job_schedules ON job_schedules;
+DROP INDEX IF EXISTS idx_job_schedules_due;
+DROP TABLE IF EXISTS job_schedules;
+DROP TRIGGER IF EXISTS set_updated_at_jobs ON jobs;
+DROP INDEX IF EXISTS idx_jobs_status;
+DROP INDEX IF EXISTS idx_jobs_leases;
+DROP INDEX IF EXISTS idx_jobs_due;
+DROP INDEX IF EXISTS idx_jobs_active_unique_key;
+DROP TABLE IF EXISTS jobs;
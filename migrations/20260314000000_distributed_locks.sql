@@ -0,0 +1,32 @@
+-- migrate:up
+-- Named leases for leader election and other locks shared between backend replicas.
+-- Notes:
+-- - A lock is held until expires_at; the holder renews it well before then and a crashed holder
+--   simply stops renewing, so another replica takes over once it lapses.
+-- - fencing_token goes up by one on every change of holder and never goes down. A holder passes
+--   its token along with guarded work so anything done under a lease it has since lost can be
+--   told apart from the new holder's work.
+-- - Releasing a lock expires it but keeps the row, so the token sequence is never reset.
+
+CREATE TABLE IF NOT EXISTS distributed_locks (
+    name TEXT PRIMARY KEY,
+    holder TEXT NOT NULL,
+    fencing_token BIGINT NOT NULL DEFAULT 1 CHECK (fencing_token > 0),
+    acquired_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE distributed_locks IS 'Leases shared between replicas; one row per lock name.';
+COMMENT ON COLUMN distributed_locks.holder IS 'Instance that holds or last held the lock.';
+COMMENT ON COLUMN distributed_locks.fencing_token IS 'Incremented on every change of holder.';
+COMMENT ON COLUMN distributed_locks.expires_at IS 'The lock is free once this has passed.';
+
+CREATE TRIGGER set_updated_at_distributed_locks
+    BEFORE UPDATE ON distributed_locks
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+-- migrate:down
+DROP TRIGGER IF EXISTS set_updated_at_distributed_locks ON distributed_locks;
+DROP TABLE IF EXISTS distributed_locks;
@@ -0,0 +1,49 @@
+-- migrate:up
+-- Manual review queue for offramp payouts the transfer reconciliation loop cannot settle on its
+-- own: transfers reversed after the provider reported success, statuses we cannot interpret, and
+-- transfers still unconfirmed after the retry timeout.
+-- Notes:
+-- - While a review is open its transaction stays in transfer_pending and the offramp processor
+--   stops polling it; metadata.payout_review_id points at the review.
+-- - Resolving a review completes the payout, refunds the cNGN (refund_initiated) or resumes
+--   polling.
+-- - At most one open review per transaction; a resumed payout that stalls again gets a new row.
+
+CREATE TABLE IF NOT EXISTS payout_reviews (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    transaction_id UUID NOT NULL REFERENCES transactions(transaction_id) ON DELETE RESTRICT,
+    provider TEXT NOT NULL,
+    provider_reference TEXT,
+    reason TEXT NOT NULL
+        CHECK (reason IN ('reversed_after_success', 'unknown_status', 'stuck_pending')),
+    provider_status TEXT,
+    details JSONB NOT NULL DEFAULT '{}'::jsonb,
+    status TEXT NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'resolved')),
+    resolution TEXT CHECK (resolution IN ('completed', 'refunded', 'resumed')),
+    resolved_by TEXT,
+    note TEXT,
+    resolved_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CHECK ((status = 'resolved') = (resolution IS NOT NULL))
+);
+
+COMMENT ON TABLE payout_reviews IS 'Offramp payouts escalated to ops because the provider gave no definitive answer.';
+COMMENT ON COLUMN payout_reviews.reason IS 'reversed_after_success, unknown_status or stuck_pending.';
+COMMENT ON COLUMN payout_reviews.provider_status IS 'Transfer status last reported by the provider, if any.';
+COMMENT ON COLUMN payout_reviews.resolution IS 'completed, refunded or resumed, once ops have resolved the review.';
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_payout_reviews_open_transaction
+    ON payout_reviews(transaction_id)
+    WHERE status = 'open';
+CREATE INDEX IF NOT EXISTS idx_payout_reviews_status ON payout_reviews(status, created_at);
+
+CREATE TRIGGER set_updated_at_payout_reviews
+    BEFORE UPDATE ON payout_reviews
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+-- migrate:down
+DROP TRIGGER IF EXISTS set_updated_at_payout_reviews ON payout_reviews;
+DROP INDEX IF EXISTS idx_payout_reviews_status;
+DROP INDEX IF EXISTS idx_payout_reviews_open_transaction;
+DROP TABLE IF EXISTS payout_reviews;
@@ -0,0 +1,16 @@
+-- migrate:up
+-- Transaction status for offramp deposits that fell short of the quote and wait for the user to
+-- top up.
+-- Notes:
+-- - With OFFRAMP_UNDERPAYMENT_POLICY=top_up an underpaid offramp moves from cngn_received to
+--   awaiting_top_up; a further cNGN payment with the same memo returns it to cngn_received, and
+--   the window closing moves it to refund_initiated.
+
+INSERT INTO transaction_statuses (code, description) VALUES
+  ('awaiting_top_up', 'Offramp deposit short of the quote, waiting for the user to top up')
+ON CONFLICT (code) DO NOTHING;
+
+-- migrate:down
+DELETE FROM transaction_statuses
+WHERE code = 'awaiting_top_up'
+  AND NOT EXISTS (SELECT 1 FROM transactions t WHERE t.status = transaction_statuses.code);
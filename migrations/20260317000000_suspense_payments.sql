@@ -0,0 +1,55 @@
+-- migrate:up
+-- Suspense ledger for cNGN payments into the system wallet that the transaction monitor could not
+-- attribute to a transaction: no memo, a memo naming no known transaction, or a memo naming a
+-- transaction no longer waiting for a deposit (expired, failed, cancelled or already paid).
+-- Notes:
+-- - The monitor records each payment once (keyed by its Stellar hash); a rescan is a no-op.
+-- - Ops resolve a payment by matching it to a transaction still waiting for funds, or by sending
+--   the cNGN back to the sender from the system wallet.
+-- - A refund moves the row to refunding with the hash of the signed payment before submission, so
+--   a failed or interrupted submission can be checked on Horizon before anything is sent again.
+-- - Unresolved and refunding rows feed the weekly aging report.
+
+CREATE TABLE IF NOT EXISTS suspense_payments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    stellar_tx_hash TEXT NOT NULL UNIQUE,
+    ledger BIGINT,
+    sender TEXT NOT NULL,
+    amount NUMERIC(36, 18) NOT NULL CHECK (amount > 0),
+    asset_code TEXT NOT NULL,
+    asset_issuer TEXT,
+    memo TEXT,
+    reason TEXT NOT NULL CHECK (reason IN ('no_memo', 'unknown_memo', 'transaction_closed')),
+    status TEXT NOT NULL DEFAULT 'unresolved'
+        CHECK (status IN ('unresolved', 'matched', 'refunding', 'refunded')),
+    matched_transaction_id UUID REFERENCES transactions(transaction_id) ON DELETE RESTRICT,
+    refund_tx_hash TEXT,
+    refund_expires_at TIMESTAMPTZ,
+    resolved_by TEXT,
+    note TEXT,
+    resolved_at TIMESTAMPTZ,
+    received_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CHECK ((status = 'matched') = (matched_transaction_id IS NOT NULL)),
+    CHECK (status <> 'refunded' OR refund_tx_hash IS NOT NULL)
+);
+
+COMMENT ON TABLE suspense_payments IS 'Incoming cNGN payments the monitor could not match to a transaction, awaiting ops resolution.';
+COMMENT ON COLUMN suspense_payments.reason IS 'no_memo, unknown_memo or transaction_closed.';
+COMMENT ON COLUMN suspense_payments.status IS 'unresolved, matched, refunding (refund signed, not yet confirmed) or refunded.';
+COMMENT ON COLUMN suspense_payments.refund_expires_at IS 'Time bound of the refund envelope; after it a refund that never landed can be rebuilt.';
+COMMENT ON COLUMN suspense_payments.received_at IS 'Ledger close time of the payment, used for aging.';
+
+CREATE INDEX IF NOT EXISTS idx_suspense_payments_status ON suspense_payments(status, received_at);
+CREATE INDEX IF NOT EXISTS idx_suspense_payments_sender ON suspense_payments(sender);
+
+CREATE TRIGGER set_updated_at_suspense_payments
+    BEFORE UPDATE ON suspense_payments
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+-- migrate:down
+DROP TRIGGER IF EXISTS set_updated_at_suspense_payments ON suspense_payments;
+DROP INDEX IF EXISTS idx_suspense_payments_sender;
+DROP INDEX IF EXISTS idx_suspense_payments_status;
+DROP TABLE IF EXISTS suspense_payments;
@@ -0,0 +1,22 @@
+-- migrate:up
+-- Sponsored trustline creations are claimed before they are submitted.
+-- Notes:
+-- - 'submitting' marks a sponsored creation whose envelope is on its way to Horizon.
+-- - It counts against the user's sponsorship limit like a completed one, so concurrent
+--   submissions for the same user cannot both fit under the limit.
+-- - An operation left in 'submitting' (e.g. the process died mid-submit) keeps counting until
+--   an operator checks its hash on the ledger and resolves it.
+
+ALTER TABLE trustline_operations
+    DROP CONSTRAINT IF EXISTS trustline_operations_status_check;
+ALTER TABLE trustline_operations
+    ADD CONSTRAINT trustline_operations_status_check
+    CHECK (status IN ('pending', 'submitting', 'completed', 'failed'));
+
+-- migrate:down
+UPDATE trustline_operations SET status = 'failed' WHERE status = 'submitting';
+ALTER TABLE trustline_operations
+    DROP CONSTRAINT IF EXISTS trustline_operations_status_check;
+ALTER TABLE trustline_operations
+    ADD CONSTRAINT trustline_operations_status_check
+    CHECK (status IN ('pending', 'completed', 'failed'));
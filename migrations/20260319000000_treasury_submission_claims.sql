@@ -0,0 +1,23 @@
+-- migrate:up
+-- Treasury transactions are claimed before their signed envelope is submitted.
+-- Notes:
+-- - 'submitting' marks a treasury transaction whose envelope is on its way to Horizon, so two
+--   callers cannot submit it at once and a late failure cannot overwrite a success.
+-- - A submission whose outcome is unknown returns to 'pending'; resubmitting the same envelope
+--   cannot move funds twice because its sequence number is fixed.
+-- - A transaction left in 'submitting' (e.g. the process died mid-submit) is not expired; an
+--   operator checks its hash on the ledger and resolves it.
+
+ALTER TABLE treasury_transactions
+    DROP CONSTRAINT IF EXISTS treasury_transactions_status_check;
+ALTER TABLE treasury_transactions
+    ADD CONSTRAINT treasury_transactions_status_check
+    CHECK (status IN ('pending', 'submitting', 'submitted', 'failed', 'expired', 'cancelled'));
+
+-- migrate:down
+UPDATE treasury_transactions SET status = 'pending' WHERE status = 'submitting';
+ALTER TABLE treasury_transactions
+    DROP CONSTRAINT IF EXISTS treasury_transactions_status_check;
+ALTER TABLE treasury_transactions
+    ADD CONSTRAINT treasury_transactions_status_check
+    CHECK (status IN ('pending', 'submitted', 'failed', 'expired', 'cancelled'));
@@ -0,0 +1,16 @@
+-- migrate:up
+-- Webhook event leases record which worker holds them.
+-- Notes:
+-- - locked_by names the queue worker holding the lease; the worker renews the lease before each
+--   event it processes and only that worker can complete or fail the event.
+-- - Reclaiming an event whose lease ran out counts as a failed attempt, so an event that keeps
+--   crashing its worker is dead-lettered like one that keeps failing.
+
+ALTER TABLE webhook_events
+    ADD COLUMN IF NOT EXISTS locked_by TEXT;
+
+COMMENT ON COLUMN webhook_events.locked_by IS 'Queue worker holding the lease in locked_until.';
+
+-- migrate:down
+ALTER TABLE webhook_events
+    DROP COLUMN IF EXISTS locked_by;
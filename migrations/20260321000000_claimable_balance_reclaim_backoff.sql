@@ -0,0 +1,18 @@
+-- migrate:up
+-- Failed reclaims of expired claimable balances back off instead of being retried every sweep.
+-- Notes:
+-- - reclaim_attempts counts failed reclaims; the last error is kept in error_message.
+-- - The sweep skips a balance until next_reclaim_at, so one that keeps failing does not hold
+--   up the balances behind it.
+
+ALTER TABLE claimable_balances
+    ADD COLUMN IF NOT EXISTS reclaim_attempts INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS next_reclaim_at TIMESTAMPTZ;
+
+COMMENT ON COLUMN claimable_balances.reclaim_attempts IS 'Failed attempts to reclaim the balance after its deadline.';
+COMMENT ON COLUMN claimable_balances.next_reclaim_at IS 'Earliest time the sweep retries a failed reclaim.';
+
+-- migrate:down
+ALTER TABLE claimable_balances
+    DROP COLUMN IF EXISTS next_reclaim_at,
+    DROP COLUMN IF EXISTS reclaim_attempts;
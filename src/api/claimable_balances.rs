@@ -0,0 +1,63 @@
+//! Claimable balance API
+//!
+//! Lets a wallet see cNGN payouts waiting for it in claimable balances and
+//! build the transaction that claims one (adding the trustline if needed).
+
+use crate::chains::stellar::payment::UnsignedClaimTransaction;
+use crate::database::claimable_balance_repository::ClaimableBalance;
+use crate::error::AppError;
+use crate::services::claimable_balance::ClaimableBalanceService;
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct ClaimableBalanceState {
+    pub service: Arc<ClaimableBalanceService>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListClaimableBalancesQuery {
+    /// Defaults to `pending`; pass `all` for every status
+    #[serde(default = "default_status")]
+    pub status: String,
+}
+
+fn default_status() -> String {
+    "pending".to_string()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ClaimRequest {
+    pub wallet_address: String,
+}
+
+/// GET /api/wallets/{address}/claimable-balances?status=pending
+pub async fn list_for_wallet(
+    State(state): State<ClaimableBalanceState>,
+    Path(address): Path<String>,
+    Query(params): Query<ListClaimableBalancesQuery>,
+) -> Result<Json<Vec<ClaimableBalance>>, AppError> {
+    let status = (params.status != "all").then_some(params.status.as_str());
+    state
+        .service
+        .list_for_wallet(address.trim(), status)
+        .await
+        .map(Json)
+}
+
+/// POST /api/claimable-balances/{balance_id}/claim
+pub async fn build_claim(
+    State(state): State<ClaimableBalanceState>,
+    Path(balance_id): Path<String>,
+    Json(payload): Json<ClaimRequest>,
+) -> Result<Json<UnsignedClaimTransaction>, AppError> {
+    state
+        .service
+        .build_claim(balance_id.trim(), payload.wallet_address.trim())
+        .await
+        .map(Json)
+}
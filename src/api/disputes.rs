@@ -0,0 +1,119 @@
+//! Dispute admin API
+//!
+//! Lets ops review chargebacks, upload evidence, accept or contest them, and
+//! review or lift the wallet risk flags raised when a dispute is lost.
+
+use crate::database::dispute_repository::{Dispute, DisputeEvidenceFile, NewDisputeEvidence};
+use crate::database::wallet_risk_repository::WalletRiskFlag;
+use crate::error::AppError;
+use crate::services::dispute::{ContestRequest, DisputeDetail, DisputeService};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct DisputesState {
+    pub dispute_service: Arc<DisputeService>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListDisputesQuery {
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AcceptDisputeRequest {
+    pub operator: String,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ClearRiskFlagRequest {
+    pub operator: String,
+}
+
+/// GET /api/admin/disputes?status=open&limit=50
+pub async fn list_disputes(
+    State(state): State<DisputesState>,
+    Query(query): Query<ListDisputesQuery>,
+) -> Result<Json<Vec<Dispute>>, AppError> {
+    state
+        .dispute_service
+        .list(
+            query.status.as_deref(),
+            query.limit.unwrap_or(50).clamp(1, 500),
+        )
+        .await
+        .map(Json)
+}
+
+/// GET /api/admin/disputes/{id}
+pub async fn get_dispute(
+    State(state): State<DisputesState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<DisputeDetail>, AppError> {
+    state.dispute_service.get(id).await.map(Json)
+}
+
+/// POST /api/admin/disputes/{id}/evidence
+pub async fn add_evidence(
+    State(state): State<DisputesState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<NewDisputeEvidence>,
+) -> Result<(StatusCode, Json<DisputeEvidenceFile>), AppError> {
+    let file = state.dispute_service.add_evidence(id, payload).await?;
+    Ok((StatusCode::CREATED, Json(file)))
+}
+
+/// POST /api/admin/disputes/{id}/accept
+pub async fn accept_dispute(
+    State(state): State<DisputesState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<AcceptDisputeRequest>,
+) -> Result<Json<Dispute>, AppError> {
+    state
+        .dispute_service
+        .accept(id, payload.operator.trim(), payload.note.as_deref())
+        .await
+        .map(Json)
+}
+
+/// POST /api/admin/disputes/{id}/contest
+pub async fn contest_dispute(
+    State(state): State<DisputesState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ContestRequest>,
+) -> Result<Json<Dispute>, AppError> {
+    state.dispute_service.contest(id, payload).await.map(Json)
+}
+
+/// GET /api/admin/wallets/{wallet_address}/risk-flags
+pub async fn list_wallet_risk_flags(
+    State(state): State<DisputesState>,
+    Path(wallet_address): Path<String>,
+) -> Result<Json<Vec<WalletRiskFlag>>, AppError> {
+    state
+        .dispute_service
+        .wallet_risk_flags(&wallet_address)
+        .await
+        .map(Json)
+}
+
+/// POST /api/admin/risk-flags/{id}/clear
+pub async fn clear_risk_flag(
+    State(state): State<DisputesState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ClearRiskFlagRequest>,
+) -> Result<Json<WalletRiskFlag>, AppError> {
+    state
+        .dispute_service
+        .clear_risk_flag(id, payload.operator.trim())
+        .await
+        .map(Json)
+}
@@ -0,0 +1,30 @@
+//! Fee administration API
+//!
+//! What-if simulation of proposed fee structures against recent transaction
+//! history. Simulations never change live pricing.
+
+use crate::error::AppError;
+use crate::services::fee_simulation::{
+    FeeSimulationReport, FeeSimulationRequest, FeeSimulationService,
+};
+use axum::{extract::State, Json};
+use std::sync::Arc;
+use tracing::info;
+
+#[derive(Clone)]
+pub struct FeesState {
+    pub simulation_service: Arc<FeeSimulationService>,
+}
+
+/// POST /api/admin/fees/simulate
+pub async fn simulate(
+    State(state): State<FeesState>,
+    Json(payload): Json<FeeSimulationRequest>,
+) -> Result<Json<FeeSimulationReport>, AppError> {
+    info!(
+        days = ?payload.days,
+        structures = payload.fee_structures.len(),
+        "Fee simulation requested"
+    );
+    state.simulation_service.simulate(payload).await.map(Json)
+}
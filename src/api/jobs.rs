@@ -0,0 +1,133 @@
+//! Background job admin API
+//!
+//! Lets ops see what the job queue is doing, retry dead jobs, cancel queued
+//! ones, and pause, resume or trigger recurring schedules.
+
+use crate::database::job_repository::{Job, JobCount, JobSchedule};
+use crate::error::AppError;
+use crate::services::jobs::JobQueue;
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct JobsState {
+    pub queue: Arc<JobQueue>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListJobsQuery {
+    pub status: Option<String>,
+    pub kind: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JobActionRequest {
+    pub operator: String,
+}
+
+/// GET /api/admin/jobs?status=dead&kind=offramp.process&limit=50
+pub async fn list_jobs(
+    State(state): State<JobsState>,
+    Query(query): Query<ListJobsQuery>,
+) -> Result<Json<Vec<Job>>, AppError> {
+    state
+        .queue
+        .list(
+            query.status.as_deref(),
+            query.kind.as_deref(),
+            query.limit.unwrap_or(50).clamp(1, 500),
+        )
+        .await
+        .map(Json)
+}
+
+/// GET /api/admin/jobs/stats
+pub async fn job_stats(State(state): State<JobsState>) -> Result<Json<Vec<JobCount>>, AppError> {
+    state.queue.counts().await.map(Json)
+}
+
+/// GET /api/admin/jobs/{id}
+pub async fn get_job(
+    State(state): State<JobsState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Job>, AppError> {
+    state.queue.get(id).await.map(Json)
+}
+
+/// POST /api/admin/jobs/{id}/retry
+pub async fn retry_job(
+    State(state): State<JobsState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<JobActionRequest>,
+) -> Result<Json<Job>, AppError> {
+    state
+        .queue
+        .retry(id, payload.operator.trim())
+        .await
+        .map(Json)
+}
+
+/// POST /api/admin/jobs/{id}/cancel
+pub async fn cancel_job(
+    State(state): State<JobsState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<JobActionRequest>,
+) -> Result<Json<Job>, AppError> {
+    state
+        .queue
+        .cancel(id, payload.operator.trim())
+        .await
+        .map(Json)
+}
+
+/// GET /api/admin/job-schedules
+pub async fn list_schedules(
+    State(state): State<JobsState>,
+) -> Result<Json<Vec<JobSchedule>>, AppError> {
+    state.queue.schedules().await.map(Json)
+}
+
+/// POST /api/admin/job-schedules/{name}/pause
+pub async fn pause_schedule(
+    State(state): State<JobsState>,
+    Path(name): Path<String>,
+    Json(payload): Json<JobActionRequest>,
+) -> Result<Json<JobSchedule>, AppError> {
+    state
+        .queue
+        .set_schedule_enabled(&name, false, payload.operator.trim())
+        .await
+        .map(Json)
+}
+
+/// POST /api/admin/job-schedules/{name}/resume
+pub async fn resume_schedule(
+    State(state): State<JobsState>,
+    Path(name): Path<String>,
+    Json(payload): Json<JobActionRequest>,
+) -> Result<Json<JobSchedule>, AppError> {
+    state
+        .queue
+        .set_schedule_enabled(&name, true, payload.operator.trim())
+        .await
+        .map(Json)
+}
+
+/// POST /api/admin/job-schedules/{name}/run
+pub async fn run_schedule(
+    State(state): State<JobsState>,
+    Path(name): Path<String>,
+    Json(payload): Json<JobActionRequest>,
+) -> Result<Json<JobSchedule>, AppError> {
+    state
+        .queue
+        .trigger_schedule(&name, payload.operator.trim())
+        .await
+        .map(Json)
+}
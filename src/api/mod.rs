@@ -1,3 +1,14 @@
 pub mod wallet;
 pub mod webhooks;
 pub mod bills;
+pub mod treasury;
+pub mod claimable_balances;
+pub mod rates;
+pub mod fees;
+pub mod payment_providers;
+pub mod quotes;
+pub mod disputes;
+pub mod refunds;
+pub mod jobs;
+pub mod payout_reviews;
+pub mod suspense_payments;
@@ -0,0 +1,19 @@
+//! Payment provider administration API
+//!
+//! Circuit breaker state per provider, as shared across instances.
+
+use crate::payments::circuit_breaker::{CircuitSnapshot, ProviderCircuitBreaker};
+use crate::payments::types::ProviderName;
+use axum::{extract::State, Json};
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct PaymentProvidersState {
+    pub circuit_breaker: Arc<ProviderCircuitBreaker>,
+    pub providers: Vec<ProviderName>,
+}
+
+/// GET /api/admin/payment-providers/circuits
+pub async fn circuits(State(state): State<PaymentProvidersState>) -> Json<Vec<CircuitSnapshot>> {
+    Json(state.circuit_breaker.snapshots(&state.providers).await)
+}
@@ -0,0 +1,62 @@
+//! Payout review admin API
+//!
+//! Lets ops work through offramp payouts the transfer reconciliation loop
+//! could not settle: mark them completed, refund the cNGN, or resume polling.
+
+use crate::database::payout_review_repository::PayoutReview;
+use crate::error::AppError;
+use crate::services::payout_review::{PayoutReviewService, ResolvePayoutReviewRequest};
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct PayoutReviewsState {
+    pub payout_review_service: Arc<PayoutReviewService>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListPayoutReviewsQuery {
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// GET /api/admin/payout-reviews?status=open&limit=50
+pub async fn list_payout_reviews(
+    State(state): State<PayoutReviewsState>,
+    Query(query): Query<ListPayoutReviewsQuery>,
+) -> Result<Json<Vec<PayoutReview>>, AppError> {
+    state
+        .payout_review_service
+        .list(
+            query.status.as_deref(),
+            query.limit.unwrap_or(50).clamp(1, 500),
+        )
+        .await
+        .map(Json)
+}
+
+/// GET /api/admin/payout-reviews/{id}
+pub async fn get_payout_review(
+    State(state): State<PayoutReviewsState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<PayoutReview>, AppError> {
+    state.payout_review_service.get(id).await.map(Json)
+}
+
+/// POST /api/admin/payout-reviews/{id}/resolve
+pub async fn resolve_payout_review(
+    State(state): State<PayoutReviewsState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ResolvePayoutReviewRequest>,
+) -> Result<Json<PayoutReview>, AppError> {
+    state
+        .payout_review_service
+        .resolve(id, payload)
+        .await
+        .map(Json)
+}
@@ -0,0 +1,178 @@
+//! Quote API
+//!
+//! Onramp, offramp and bill payment quotes for a send or receive amount,
+//! plus the original onramp quote endpoint kept for existing clients.
+
+use crate::chains::traits::ChainType;
+use crate::error::{AppError, AppErrorKind, ValidationError};
+use crate::payments::types::ProviderName;
+use crate::services::quote::{QuoteFlow, QuoteRequest, QuoteResponse, QuoteService};
+use axum::{extract::State, http::HeaderMap, Json};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct QuotesState {
+    pub quote_service: Arc<QuoteService>,
+}
+
+/// Header partners send their API key in, for partner pricing
+const PARTNER_API_KEY_HEADER: &str = "x-api-key";
+
+/// POST /api/quotes
+pub async fn create_quote(
+    State(state): State<QuotesState>,
+    headers: HeaderMap,
+    Json(payload): Json<QuoteRequest>,
+) -> Result<Json<QuoteResponse>, AppError> {
+    let request = QuoteRequest {
+        partner_api_key: partner_api_key(&headers),
+        ..payload
+    };
+    state.quote_service.create_quote(request).await.map(Json)
+}
+
+/// Request body of `POST /api/onramp/quote`
+#[derive(Debug, Clone, Deserialize)]
+pub struct OnrampQuoteRequest {
+    /// Amount in NGN; used when `source_amount` is not given
+    #[serde(default)]
+    pub amount_ngn: i64,
+    pub wallet_address: String,
+    pub provider: String,
+    pub chain: Option<String>,
+    /// Fiat currency the user pays in (defaults to NGN)
+    #[serde(default)]
+    pub source_currency: Option<String>,
+    /// Decimal amount in `source_currency`
+    #[serde(default)]
+    pub source_amount: Option<String>,
+    #[serde(default)]
+    pub promo_code: Option<String>,
+}
+
+/// Response body of `POST /api/onramp/quote`
+#[derive(Debug, Clone, Serialize)]
+pub struct OnrampQuoteResponse {
+    pub quote_id: String,
+    pub expires_at: String,
+    pub expires_in_seconds: u64,
+    pub input: OnrampQuoteInput,
+    pub fees: OnrampQuoteFees,
+    pub output: OnrampQuoteOutput,
+    pub trustline_required: bool,
+    /// The requested amount exceeded available liquidity and was reduced
+    pub repriced: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OnrampQuoteInput {
+    /// NGN equivalent of the input amount
+    pub amount_ngn: i64,
+    pub amount: String,
+    pub currency: String,
+    pub provider: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OnrampQuoteFees {
+    pub platform_fee_ngn: i64,
+    pub provider_fee_ngn: i64,
+    pub total_fee_ngn: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OnrampQuoteOutput {
+    pub amount_ngn_after_fees: i64,
+    /// Source currency to cNGN rate applied, spread included
+    pub rate: f64,
+    pub rate_path: Vec<String>,
+    pub amount_cngn: i64,
+    pub chain: String,
+}
+
+/// POST /api/onramp/quote
+pub async fn create_onramp_quote(
+    State(state): State<QuotesState>,
+    headers: HeaderMap,
+    Json(payload): Json<OnrampQuoteRequest>,
+) -> Result<Json<OnrampQuoteResponse>, AppError> {
+    let provider = ProviderName::from_str(&payload.provider).map_err(AppError::from)?;
+    let chain = match payload.chain.as_deref() {
+        Some(chain) => Some(ChainType::from_str(chain).ok_or_else(|| {
+            AppError::new(AppErrorKind::Validation(
+                ValidationError::InvalidWalletAddress {
+                    address: payload.wallet_address.clone(),
+                    reason: format!("quotes are not available on {}", chain),
+                },
+            ))
+        })?),
+        None => None,
+    };
+    let send_amount = payload
+        .source_amount
+        .clone()
+        .unwrap_or_else(|| payload.amount_ngn.to_string());
+
+    let quote = state
+        .quote_service
+        .create_quote(QuoteRequest {
+            flow: QuoteFlow::Onramp,
+            wallet_address: payload.wallet_address,
+            provider: Some(provider.clone()),
+            chain,
+            fiat_currency: payload.source_currency,
+            send_amount: Some(send_amount),
+            receive_amount: None,
+            biller: None,
+            promo_code: payload.promo_code,
+            partner_api_key: partner_api_key(&headers),
+        })
+        .await?;
+
+    Ok(Json(OnrampQuoteResponse {
+        quote_id: quote.quote_id,
+        expires_at: quote.expires_at.to_rfc3339(),
+        expires_in_seconds: quote.expires_in_seconds,
+        input: OnrampQuoteInput {
+            amount_ngn: whole_units(&quote.amount_ngn),
+            amount: quote.send.amount,
+            currency: quote.send.currency,
+            provider: provider.to_string(),
+        },
+        fees: OnrampQuoteFees {
+            platform_fee_ngn: whole_units(&quote.fees.platform_fee),
+            provider_fee_ngn: whole_units(&quote.fees.provider_fee),
+            total_fee_ngn: whole_units(&quote.fees.total_fee),
+        },
+        output: OnrampQuoteOutput {
+            // cNGN is pegged 1:1
+            amount_ngn_after_fees: whole_units(&quote.receive.amount),
+            rate: quote.rate.parse().unwrap_or(1.0),
+            rate_path: quote.rate_path,
+            amount_cngn: whole_units(&quote.receive.amount),
+            chain: quote.chain.to_string(),
+        },
+        trustline_required: quote.trustline_required,
+        repriced: quote.repriced,
+    }))
+}
+
+/// Integer part of a decimal amount, as the original onramp response reported
+fn whole_units(amount: &str) -> i64 {
+    amount
+        .split('.')
+        .next()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0)
+}
+
+fn partner_api_key(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(PARTNER_API_KEY_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(str::to_string)
+}
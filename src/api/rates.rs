@@ -0,0 +1,101 @@
+//! Exchange rate history API
+//!
+//! Point-in-time rate history for audits: the rates in effect over a time
+//! range and, optionally, OHLC candles per interval.
+
+use crate::database::exchange_rate_repository::ExchangeRateHistoryEntry;
+use crate::error::{AppError, ValidationError};
+use crate::services::exchange_rate::{ExchangeRateService, RateCandle, RateInterval};
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+const DEFAULT_HISTORY_WINDOW_HOURS: i64 = 24;
+
+#[derive(Clone)]
+pub struct RatesState {
+    pub service: Arc<ExchangeRateService>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RateHistoryQuery {
+    /// Currency pair as `FROM/TO` or `FROM-TO`, e.g. `USD/NGN`
+    pub pair: String,
+    /// Defaults to 24 hours before `to`
+    pub from: Option<DateTime<Utc>>,
+    /// Defaults to now
+    pub to: Option<DateTime<Utc>>,
+    /// Candle width (1m, 5m, 15m, 1h, 4h, 1d); omit for raw history only
+    pub interval: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RateHistoryResponse {
+    pub pair: String,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub interval: Option<RateInterval>,
+    /// Every rate in effect during the range with its validity interval, up
+    /// to the first `MAX_HISTORY_ENTRIES`
+    pub history: Vec<ExchangeRateHistoryEntry>,
+    /// More rates were in effect than `history` holds; narrow the range to
+    /// see the rest. Candles always cover the whole range.
+    pub history_truncated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub candles: Option<Vec<RateCandle>>,
+}
+
+/// GET /api/rates/history?pair=USD/NGN&from=&to=&interval=1h
+pub async fn get_history(
+    State(state): State<RatesState>,
+    Query(params): Query<RateHistoryQuery>,
+) -> Result<Json<RateHistoryResponse>, AppError> {
+    let (from_currency, to_currency) = parse_pair(&params.pair)?;
+    let to = params.to.unwrap_or_else(Utc::now);
+    let from = params
+        .from
+        .unwrap_or(to - chrono::Duration::hours(DEFAULT_HISTORY_WINDOW_HOURS));
+    let interval = params
+        .interval
+        .as_deref()
+        .map(str::parse::<RateInterval>)
+        .transpose()?;
+
+    let history = state
+        .service
+        .get_rate_history(from_currency, to_currency, from, to)
+        .await?;
+    let candles = match interval {
+        Some(interval) => Some(
+            state
+                .service
+                .get_rate_candles(from_currency, to_currency, from, to, interval)
+                .await?,
+        ),
+        None => None,
+    };
+
+    Ok(Json(RateHistoryResponse {
+        pair: format!("{}/{}", from_currency, to_currency),
+        from,
+        to,
+        interval,
+        history: history.entries,
+        history_truncated: history.truncated,
+        candles,
+    }))
+}
+
+fn parse_pair(pair: &str) -> Result<(&str, &str), ValidationError> {
+    pair.trim()
+        .split_once(['/', '-'])
+        .filter(|(from, to)| !from.is_empty() && !to.is_empty())
+        .ok_or_else(|| ValidationError::InvalidCurrency {
+            currency: pair.to_string(),
+            reason: "pair must look like FROM/TO".to_string(),
+        })
+}
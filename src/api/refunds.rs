@@ -0,0 +1,38 @@
+//! Refund admin API
+//!
+//! Lets ops refund onramp payments whose cNGN could not be delivered and
+//! follow the refund attempts made for a transaction.
+
+use crate::database::refund_repository::Refund;
+use crate::error::AppError;
+use crate::services::refund::{OnrampRefundRequest, RefundService};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct RefundsState {
+    pub refund_service: Arc<RefundService>,
+}
+
+/// POST /api/admin/transactions/{id}/refund
+pub async fn refund_onramp(
+    State(state): State<RefundsState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<OnrampRefundRequest>,
+) -> Result<(StatusCode, Json<Refund>), AppError> {
+    let refund = state.refund_service.refund_onramp(id, payload).await?;
+    Ok((StatusCode::ACCEPTED, Json(refund)))
+}
+
+/// GET /api/admin/transactions/{id}/refunds
+pub async fn list_refunds(
+    State(state): State<RefundsState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<Refund>>, AppError> {
+    state.refund_service.list(id).await.map(Json)
+}
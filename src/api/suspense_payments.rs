@@ -0,0 +1,89 @@
+//! Suspense payment admin API
+//!
+//! Lets ops resolve cNGN payments the transaction monitor could not match:
+//! credit them to the transaction they were meant for, or refund the sender.
+
+use crate::database::suspense_payment_repository::SuspensePayment;
+use crate::error::AppError;
+use crate::services::suspense_payment::{
+    MatchSuspensePaymentRequest, RefundSuspensePaymentRequest, SuspenseAgingReport,
+    SuspensePaymentService,
+};
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct SuspensePaymentsState {
+    pub suspense_payment_service: Arc<SuspensePaymentService>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListSuspensePaymentsQuery {
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// GET /api/admin/suspense-payments?status=unresolved&limit=50
+pub async fn list_suspense_payments(
+    State(state): State<SuspensePaymentsState>,
+    Query(query): Query<ListSuspensePaymentsQuery>,
+) -> Result<Json<Vec<SuspensePayment>>, AppError> {
+    state
+        .suspense_payment_service
+        .list(
+            query.status.as_deref(),
+            query.limit.unwrap_or(50).clamp(1, 500),
+        )
+        .await
+        .map(Json)
+}
+
+/// GET /api/admin/suspense-payments/aging
+pub async fn suspense_aging_report(
+    State(state): State<SuspensePaymentsState>,
+) -> Result<Json<SuspenseAgingReport>, AppError> {
+    state
+        .suspense_payment_service
+        .aging_report()
+        .await
+        .map(Json)
+}
+
+/// GET /api/admin/suspense-payments/{id}
+pub async fn get_suspense_payment(
+    State(state): State<SuspensePaymentsState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuspensePayment>, AppError> {
+    state.suspense_payment_service.get(id).await.map(Json)
+}
+
+/// POST /api/admin/suspense-payments/{id}/match
+pub async fn match_suspense_payment(
+    State(state): State<SuspensePaymentsState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<MatchSuspensePaymentRequest>,
+) -> Result<Json<SuspensePayment>, AppError> {
+    state
+        .suspense_payment_service
+        .match_payment(id, payload)
+        .await
+        .map(Json)
+}
+
+/// POST /api/admin/suspense-payments/{id}/refund
+pub async fn refund_suspense_payment(
+    State(state): State<SuspensePaymentsState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<RefundSuspensePaymentRequest>,
+) -> Result<Json<SuspensePayment>, AppError> {
+    state
+        .suspense_payment_service
+        .refund(id, payload)
+        .await
+        .map(Json)
+}
@@ -0,0 +1,118 @@
+//! Treasury multisig API
+//!
+//! Endpoints for proposing treasury payments, collecting signatures from the
+//! treasury signers (detached or as pre-signed envelopes) and submitting once
+//! the account threshold is met.
+
+use crate::database::treasury_repository::TreasuryTransaction;
+use crate::error::AppError;
+use crate::services::treasury::{TreasuryApprovalStatus, TreasuryPaymentRequest, TreasuryService};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::info;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct TreasuryState {
+    pub treasury_service: Arc<TreasuryService>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListTransactionsQuery {
+    #[serde(default = "default_status")]
+    pub status: String,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+}
+
+fn default_status() -> String {
+    "pending".to_string()
+}
+
+fn default_limit() -> i64 {
+    50
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddSignatureRequest {
+    pub public_key: String,
+    /// Hex-encoded ed25519 signature over the transaction hash
+    pub signature: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PresignedEnvelopeRequest {
+    pub signed_envelope_xdr: String,
+}
+
+/// POST /api/treasury/transactions
+pub async fn propose_transaction(
+    State(state): State<TreasuryState>,
+    Json(payload): Json<TreasuryPaymentRequest>,
+) -> Result<(StatusCode, Json<TreasuryTransaction>), AppError> {
+    info!(destination = %payload.destination, amount = %payload.amount, "Treasury payment proposed");
+    let created = state
+        .treasury_service
+        .propose_payment("manual", payload)
+        .await?;
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+/// GET /api/treasury/transactions?status=pending&limit=50
+pub async fn list_transactions(
+    State(state): State<TreasuryState>,
+    Query(params): Query<ListTransactionsQuery>,
+) -> Result<Json<Vec<TreasuryTransaction>>, AppError> {
+    state
+        .treasury_service
+        .list(&params.status, params.limit.clamp(1, 200))
+        .await
+        .map(Json)
+}
+
+/// GET /api/treasury/transactions/{id}
+pub async fn get_transaction(
+    State(state): State<TreasuryState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<TreasuryApprovalStatus>, AppError> {
+    state.treasury_service.approval_status(id).await.map(Json)
+}
+
+/// POST /api/treasury/transactions/{id}/signatures
+pub async fn add_signature(
+    State(state): State<TreasuryState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<AddSignatureRequest>,
+) -> Result<Json<TreasuryApprovalStatus>, AppError> {
+    state
+        .treasury_service
+        .add_signature(id, payload.public_key.trim(), &payload.signature)
+        .await
+        .map(Json)
+}
+
+/// POST /api/treasury/transactions/{id}/presigned
+pub async fn add_presigned_envelope(
+    State(state): State<TreasuryState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<PresignedEnvelopeRequest>,
+) -> Result<Json<TreasuryApprovalStatus>, AppError> {
+    state
+        .treasury_service
+        .add_presigned_envelope(id, payload.signed_envelope_xdr.trim())
+        .await
+        .map(Json)
+}
+
+/// POST /api/treasury/transactions/{id}/submit
+pub async fn submit_transaction(
+    State(state): State<TreasuryState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<TreasuryTransaction>, AppError> {
+    state.treasury_service.submit(id).await.map(Json)
+}
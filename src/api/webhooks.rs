@@ -1,25 +1,33 @@
 use axum::{
-    extract::{Path, State},
+    body::Bytes,
+    extract::{Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
     Json,
 };
+use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use std::sync::Arc;
 use tracing::{error, info, warn};
+use uuid::Uuid;
 
-use crate::services::webhook_processor::{WebhookProcessor, WebhookProcessorError};
+use crate::database::webhook_repository::WebhookEvent;
+use crate::error::{AppError, AppErrorKind, DomainError};
+use crate::services::webhook_processor::{IngestOutcome, WebhookProcessor, WebhookProcessorError};
 
 pub struct WebhookState {
     pub processor: Arc<WebhookProcessor>,
 }
 
-/// POST /webhooks/:provider
+/// POST /webhooks/{provider}
+///
+/// Verifies the signature over the raw body, queues the event and returns;
+/// processing happens on the webhook queue workers.
 pub async fn handle_webhook(
     State(state): State<Arc<WebhookState>>,
     Path(provider): Path<String>,
     headers: axum::http::HeaderMap,
-    body: String,
+    body: Bytes,
 ) -> impl IntoResponse {
     info!(provider = %provider, "Received webhook");
 
@@ -41,36 +49,104 @@ pub async fn handle_webhook(
         return (StatusCode::UNAUTHORIZED, "Missing signature").into_response();
     }
 
-    // Parse payload
-    let payload: JsonValue = match serde_json::from_str(&body) {
-        Ok(p) => p,
-        Err(e) => {
-            error!(provider = %provider, error = %e, "Invalid JSON payload");
-            return (StatusCode::BAD_REQUEST, "Invalid JSON").into_response();
-        }
-    };
-
-    // Process webhook
     match state
         .processor
-        .process_webhook(&provider, signature.as_deref(), &payload)
+        .ingest(&provider, signature.as_deref(), &body)
         .await
     {
-        Ok(_) => {
-            info!(provider = %provider, "Webhook processed successfully");
+        Ok(IngestOutcome::Queued) | Ok(IngestOutcome::Duplicate) => {
             (StatusCode::OK, Json(serde_json::json!({"status": "ok"}))).into_response()
         }
         Err(WebhookProcessorError::InvalidSignature) => {
             warn!(provider = %provider, "Invalid webhook signature");
             (StatusCode::UNAUTHORIZED, "Invalid signature").into_response()
         }
-        Err(WebhookProcessorError::AlreadyProcessed) => {
-            info!(provider = %provider, "Webhook already processed");
-            (StatusCode::OK, Json(serde_json::json!({"status": "ok"}))).into_response()
+        Err(WebhookProcessorError::UnknownProvider(_)) => {
+            (StatusCode::NOT_FOUND, "Unknown provider").into_response()
+        }
+        Err(WebhookProcessorError::InvalidPayload(e)) => {
+            error!(provider = %provider, error = %e, "Invalid webhook payload");
+            (StatusCode::BAD_REQUEST, "Invalid payload").into_response()
         }
         Err(e) => {
-            error!(provider = %provider, error = %e, "Webhook processing failed");
-            (StatusCode::OK, Json(serde_json::json!({"status": "ok"}))).into_response()
+            // Not acknowledged, so the provider redelivers
+            error!(provider = %provider, error = %e, "Failed to queue webhook");
+            (StatusCode::SERVICE_UNAVAILABLE, "Webhook not accepted").into_response()
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeadLetterQuery {
+    pub provider: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// Dead-lettered event as shown to operators
+#[derive(Debug, Serialize)]
+pub struct DeadLetteredWebhook {
+    pub id: Uuid,
+    pub event_id: String,
+    pub provider: String,
+    pub event_type: String,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub payload: JsonValue,
+    /// Body as received, when kept
+    pub raw_body: Option<String>,
+    pub received_at: chrono::DateTime<chrono::Utc>,
+    pub dead_lettered_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<WebhookEvent> for DeadLetteredWebhook {
+    fn from(event: WebhookEvent) -> Self {
+        Self {
+            id: event.id,
+            event_id: event.event_id,
+            provider: event.provider,
+            event_type: event.event_type,
+            attempts: event.retry_count,
+            last_error: event.error_message,
+            payload: event.payload,
+            raw_body: event
+                .raw_body
+                .map(|raw| String::from_utf8_lossy(&raw).into_owned()),
+            received_at: event.created_at,
+            dead_lettered_at: event.processed_at,
+        }
+    }
+}
+
+/// GET /api/admin/webhooks/dead-letters
+pub async fn list_dead_letters(
+    State(state): State<Arc<WebhookState>>,
+    Query(query): Query<DeadLetterQuery>,
+) -> Result<Json<Vec<DeadLetteredWebhook>>, AppError> {
+    let events = state
+        .processor
+        .repository()
+        .get_dead_lettered(
+            query.provider.as_deref(),
+            query.limit.unwrap_or(50).clamp(1, 500),
+        )
+        .await?;
+    Ok(Json(events.into_iter().map(Into::into).collect()))
+}
+
+/// POST /api/admin/webhooks/{id}/replay
+pub async fn replay_dead_letter(
+    State(state): State<Arc<WebhookState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<JsonValue>, AppError> {
+    match state.processor.repository().replay(id).await? {
+        Some(event) => {
+            info!(id = %id, event_id = %event.event_id, "Dead-lettered webhook replayed");
+            Ok(Json(serde_json::json!({"status": "queued", "id": id})))
         }
+        None => Err(AppError::new(AppErrorKind::Domain(
+            DomainError::TransactionNotFound {
+                transaction_id: id.to_string(),
+            },
+        ))),
     }
 }
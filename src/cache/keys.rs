@@ -294,11 +294,12 @@ pub mod bill_payment {
     }
 }
 
-pub mod onramp {
+pub mod quote {
     use super::*;
 
-    pub const NAMESPACE: &str = "onramp";
+    pub const NAMESPACE: &str = "quote";
 
+    /// Locked onramp, offramp or bill payment quote
     #[derive(Debug, Clone)]
     pub struct QuoteKey {
         pub quote_id: String,
@@ -314,7 +315,7 @@ pub mod onramp {
 
     impl fmt::Display for QuoteKey {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            write!(f, "{}:{}:quote:{}", VERSION, NAMESPACE, self.quote_id)
+            write!(f, "{}:{}:{}", VERSION, NAMESPACE, self.quote_id)
         }
     }
 }
@@ -344,6 +345,32 @@ pub mod fee {
     }
 }
 
+pub mod payment_provider {
+    use super::*;
+
+    pub const NAMESPACE: &str = "payment_provider";
+
+    /// Circuit breaker state shared across instances
+    #[derive(Debug, Clone)]
+    pub struct CircuitKey {
+        pub provider: String,
+    }
+
+    impl CircuitKey {
+        pub fn new(provider: impl Into<String>) -> Self {
+            Self {
+                provider: provider.into(),
+            }
+        }
+    }
+
+    impl fmt::Display for CircuitKey {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}:{}:circuit:{}", VERSION, NAMESPACE, self.provider)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -377,4 +404,16 @@ mod tests {
         let key = auth::RateLimitKey::new("user_123", "login");
         assert_eq!(key.to_string(), "v1:auth:rate_limit:user_123:login");
     }
+
+    #[test]
+    fn test_payment_provider_circuit_key() {
+        let key = payment_provider::CircuitKey::new("paystack");
+        assert_eq!(key.to_string(), "v1:payment_provider:circuit:paystack");
+    }
+
+    #[test]
+    fn test_quote_key() {
+        let key = quote::QuoteKey::new("q_123");
+        assert_eq!(key.to_string(), "v1:quote:q_123");
+    }
 }
@@ -3,7 +3,8 @@ use crate::chains::stellar::{
     errors::{StellarError, StellarResult},
     types::{
         extract_afri_balance, extract_asset_balance, extract_cngn_balance,
-        is_valid_stellar_address, HealthStatus, HorizonAccount, StellarAccountInfo,
+        is_valid_stellar_address, HealthStatus, HorizonAccount, HorizonOrderBook,
+        HorizonPaymentPath, StellarAccountInfo, StellarAsset,
     },
 };
 use reqwest::Client;
@@ -476,6 +477,177 @@ impl StellarClient {
             .cloned()
             .unwrap_or_default())
     }
+
+    /// Fetch a claimable balance by its hex id. `None` once it has been
+    /// claimed (Horizon drops claimed balances).
+    pub async fn get_claimable_balance(
+        &self,
+        balance_id: &str,
+    ) -> StellarResult<Option<JsonValue>> {
+        let url = format!(
+            "{}/claimable_balances/{}",
+            self.config.horizon_url(),
+            encode_form_component(balance_id)
+        );
+        let response = timeout(
+            self.config.request_timeout,
+            self.http_client.get(&url).send(),
+        )
+        .await
+        .map_err(|_| StellarError::timeout_error(self.config.request_timeout.as_secs()))?
+        .map_err(|e| {
+            if e.status() == Some(reqwest::StatusCode::TOO_MANY_REQUESTS) {
+                StellarError::RateLimitError
+            } else {
+                StellarError::network_error(format!("Horizon claimable balance error: {}", e))
+            }
+        })?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let response = response.error_for_status().map_err(|e| {
+            if e.status() == Some(reqwest::StatusCode::TOO_MANY_REQUESTS) {
+                StellarError::RateLimitError
+            } else {
+                StellarError::network_error(format!("Horizon claimable balance error: {}", e))
+            }
+        })?;
+
+        response
+            .json::<JsonValue>()
+            .await
+            .map(Some)
+            .map_err(|e| StellarError::serialization_error(format!("JSON parsing error: {}", e)))
+    }
+
+    /// Paths that convert exactly `source_amount` of `source_asset` into one
+    /// of `destination_assets`, best first as ranked by Horizon.
+    pub async fn find_strict_send_paths(
+        &self,
+        source_asset: &StellarAsset,
+        source_amount: &str,
+        destination_assets: &[StellarAsset],
+    ) -> StellarResult<Vec<HorizonPaymentPath>> {
+        let mut url = format!(
+            "{}/paths/strict-send?source_amount={}&destination_assets={}",
+            self.config.horizon_url(),
+            encode_form_component(source_amount),
+            encode_form_component(&join_assets(destination_assets)),
+        );
+        push_asset_query(&mut url, "source", source_asset);
+        self.fetch_paths(&url).await
+    }
+
+    /// Paths that deliver exactly `destination_amount` of `destination_asset`
+    /// funded by one of `source_assets`.
+    pub async fn find_strict_receive_paths(
+        &self,
+        source_assets: &[StellarAsset],
+        destination_asset: &StellarAsset,
+        destination_amount: &str,
+    ) -> StellarResult<Vec<HorizonPaymentPath>> {
+        let mut url = format!(
+            "{}/paths/strict-receive?destination_amount={}&source_assets={}",
+            self.config.horizon_url(),
+            encode_form_component(destination_amount),
+            encode_form_component(&join_assets(source_assets)),
+        );
+        push_asset_query(&mut url, "destination", destination_asset);
+        self.fetch_paths(&url).await
+    }
+
+    /// Order book for `selling` priced in `buying`, limited to `limit` levels
+    /// per side.
+    pub async fn get_order_book(
+        &self,
+        selling: &StellarAsset,
+        buying: &StellarAsset,
+        limit: u32,
+    ) -> StellarResult<HorizonOrderBook> {
+        let mut url = format!("{}/order_book?limit={}", self.config.horizon_url(), limit);
+        push_asset_query(&mut url, "selling", selling);
+        push_asset_query(&mut url, "buying", buying);
+
+        let response = timeout(
+            self.config.request_timeout,
+            self.http_client.get(&url).send(),
+        )
+        .await
+        .map_err(|_| StellarError::timeout_error(self.config.request_timeout.as_secs()))?
+        .map_err(|e| StellarError::network_error(format!("Horizon order book error: {}", e)))?
+        .error_for_status()
+        .map_err(|e| {
+            if e.status() == Some(reqwest::StatusCode::TOO_MANY_REQUESTS) {
+                StellarError::RateLimitError
+            } else {
+                StellarError::network_error(format!("Horizon order book error: {}", e))
+            }
+        })?;
+
+        response
+            .json::<HorizonOrderBook>()
+            .await
+            .map_err(|e| StellarError::serialization_error(format!("JSON parsing error: {}", e)))
+    }
+
+    async fn fetch_paths(&self, url: &str) -> StellarResult<Vec<HorizonPaymentPath>> {
+        let response = timeout(
+            self.config.request_timeout,
+            self.http_client.get(url).send(),
+        )
+        .await
+        .map_err(|_| StellarError::timeout_error(self.config.request_timeout.as_secs()))?
+        .map_err(|e| {
+            if e.status() == Some(reqwest::StatusCode::TOO_MANY_REQUESTS) {
+                StellarError::RateLimitError
+            } else {
+                StellarError::network_error(format!("Horizon path finding error: {}", e))
+            }
+        })?
+        .error_for_status()
+        .map_err(|e| {
+            if e.status() == Some(reqwest::StatusCode::TOO_MANY_REQUESTS) {
+                StellarError::RateLimitError
+            } else {
+                StellarError::network_error(format!("Horizon path finding error: {}", e))
+            }
+        })?;
+
+        let body = response
+            .json::<JsonValue>()
+            .await
+            .map_err(|e| StellarError::serialization_error(format!("JSON parsing error: {}", e)))?;
+
+        Ok(body
+            .get("_embedded")
+            .and_then(|v| v.get("records"))
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|record| serde_json::from_value::<HorizonPaymentPath>(record).ok())
+            .collect())
+    }
+}
+
+fn join_assets(assets: &[StellarAsset]) -> String {
+    assets
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn push_asset_query(url: &mut String, prefix: &str, asset: &StellarAsset) {
+    url.push_str(&format!("&{}_asset_type={}", prefix, asset.asset_type()));
+    if let StellarAsset::Credit { code, issuer } = asset {
+        url.push_str(&format!(
+            "&{}_asset_code={}&{}_asset_issuer={}",
+            prefix, code, prefix, issuer
+        ));
+    }
 }
 
 fn encode_form_component(input: &str) -> String {
@@ -0,0 +1,252 @@
+//! Encrypted signing keystore
+//!
+//! A keystore file holds one Stellar secret seed encrypted with
+//! ChaCha20-Poly1305 under a key derived from a password with Argon2id. The
+//! public key is stored in clear so the file can be matched to an account
+//! without unlocking it.
+
+use crate::chains::stellar::errors::{StellarError, StellarResult};
+use crate::chains::stellar::signer::{public_key_of, TransactionSigner};
+use argon2::{Algorithm, Argon2, Params, Version};
+use async_trait::async_trait;
+use chacha20poly1305::aead::{rand_core::RngCore, Aead, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use ed25519_dalek::{Signer, SigningKey};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use stellar_strkey::ed25519::PrivateKey as StrkeyPrivateKey;
+
+const KEYSTORE_VERSION: u32 = 1;
+const KDF_ALGORITHM: &str = "argon2id";
+const CIPHER_ALGORITHM: &str = "chacha20poly1305";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// Argon2id cost parameters stored alongside the ciphertext.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct KdfParams {
+    pub algorithm: String,
+    /// Memory cost in KiB
+    pub m_cost: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+    /// Hex-encoded salt
+    pub salt: String,
+}
+
+impl KdfParams {
+    fn new_random() -> Self {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        Self {
+            algorithm: KDF_ALGORITHM.to_string(),
+            m_cost: Params::DEFAULT_M_COST,
+            t_cost: Params::DEFAULT_T_COST,
+            p_cost: Params::DEFAULT_P_COST,
+            salt: hex::encode(salt),
+        }
+    }
+
+    fn derive_key(&self, password: &str) -> StellarResult<Key> {
+        if self.algorithm != KDF_ALGORITHM {
+            return Err(StellarError::config_error(format!(
+                "unsupported keystore kdf {}",
+                self.algorithm
+            )));
+        }
+        let salt = hex::decode(&self.salt)
+            .map_err(|_| StellarError::config_error("keystore salt must be hex encoded"))?;
+        let params = Params::new(self.m_cost, self.t_cost, self.p_cost, Some(32)).map_err(|e| {
+            StellarError::config_error(format!("invalid keystore kdf params: {}", e))
+        })?;
+
+        let mut key = [0u8; 32];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(password.as_bytes(), &salt, &mut key)
+            .map_err(|e| {
+                StellarError::config_error(format!("keystore key derivation failed: {}", e))
+            })?;
+        Ok(Key::from(key))
+    }
+}
+
+/// On-disk keystore format.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedKeystore {
+    pub version: u32,
+    pub public_key: String,
+    pub kdf: KdfParams,
+    pub cipher: String,
+    /// Hex-encoded nonce
+    pub nonce: String,
+    /// Hex-encoded ciphertext of the raw 32-byte ed25519 seed
+    pub ciphertext: String,
+}
+
+impl EncryptedKeystore {
+    /// Encrypt a Stellar secret seed (S...) under `password`
+    pub fn encrypt(secret_seed: &str, password: &str) -> StellarResult<Self> {
+        if password.is_empty() {
+            return Err(StellarError::config_error(
+                "keystore password must not be empty",
+            ));
+        }
+        let private_key = StrkeyPrivateKey::from_string(secret_seed.trim())
+            .map_err(|_| StellarError::signing_error("invalid Stellar secret seed"))?;
+        let public_key = public_key_of(&SigningKey::from_bytes(&private_key.0));
+
+        let kdf = KdfParams::new_random();
+        let cipher = ChaCha20Poly1305::new(&kdf.derive_key(password)?);
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), private_key.0.as_slice())
+            .map_err(|_| StellarError::signing_error("keystore encryption failed"))?;
+
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            public_key,
+            kdf,
+            cipher: CIPHER_ALGORITHM.to_string(),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        })
+    }
+
+    /// Decrypt the signing key. Fails on a wrong password or tampered file.
+    pub fn decrypt(&self, password: &str) -> StellarResult<SigningKey> {
+        if self.version != KEYSTORE_VERSION || self.cipher != CIPHER_ALGORITHM {
+            return Err(StellarError::config_error(format!(
+                "unsupported keystore version {} / cipher {}",
+                self.version, self.cipher
+            )));
+        }
+        let nonce = hex::decode(&self.nonce)
+            .ok()
+            .filter(|n| n.len() == NONCE_LEN)
+            .ok_or_else(|| StellarError::config_error("keystore nonce is malformed"))?;
+        let ciphertext = hex::decode(&self.ciphertext)
+            .map_err(|_| StellarError::config_error("keystore ciphertext must be hex encoded"))?;
+
+        let cipher = ChaCha20Poly1305::new(&self.kdf.derive_key(password)?);
+        let seed = cipher
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+            .map_err(|_| {
+                StellarError::signing_error("wrong keystore password or corrupted keystore")
+            })?;
+        let seed: [u8; 32] = seed
+            .try_into()
+            .map_err(|_| StellarError::signing_error("keystore seed must be 32 bytes"))?;
+
+        let signing_key = SigningKey::from_bytes(&seed);
+        if public_key_of(&signing_key) != self.public_key {
+            return Err(StellarError::signing_error(
+                "keystore public key does not match decrypted seed",
+            ));
+        }
+        Ok(signing_key)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> StellarResult<Self> {
+        let path = path.as_ref();
+        let raw = std::fs::read_to_string(path).map_err(|e| {
+            StellarError::config_error(format!("cannot read keystore {}: {}", path.display(), e))
+        })?;
+        serde_json::from_str(&raw)
+            .map_err(|e| StellarError::config_error(format!("invalid keystore file: {}", e)))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> StellarResult<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| StellarError::serialization_error(e.to_string()))?;
+        std::fs::write(path.as_ref(), json).map_err(|e| {
+            StellarError::config_error(format!(
+                "cannot write keystore {}: {}",
+                path.as_ref().display(),
+                e
+            ))
+        })
+    }
+}
+
+/// Signer backed by a decrypted keystore. The key stays in memory for the
+/// process lifetime and is zeroized on drop.
+pub struct KeystoreSigner {
+    signing_key: SigningKey,
+    public_key: String,
+}
+
+impl KeystoreSigner {
+    /// Load and unlock a keystore file
+    pub fn open(path: impl AsRef<Path>, password: &str) -> StellarResult<Self> {
+        let keystore = EncryptedKeystore::load(path)?;
+        Self::unlock(&keystore, password)
+    }
+
+    pub fn unlock(keystore: &EncryptedKeystore, password: &str) -> StellarResult<Self> {
+        let signing_key = keystore.decrypt(password)?;
+        Ok(Self {
+            signing_key,
+            public_key: keystore.public_key.clone(),
+        })
+    }
+}
+
+#[async_trait]
+impl TransactionSigner for KeystoreSigner {
+    fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    async fn sign_hash(&self, tx_hash: &[u8; 32]) -> StellarResult<[u8; 64]> {
+        Ok(self.signing_key.sign(tx_hash).to_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secret_seed() -> String {
+        StrkeyPrivateKey([9u8; 32]).to_string().as_str().to_owned()
+    }
+
+    #[test]
+    fn keystore_roundtrip() {
+        let keystore = EncryptedKeystore::encrypt(&secret_seed(), "correct horse").unwrap();
+        let signing_key = keystore.decrypt("correct horse").unwrap();
+        assert_eq!(signing_key.to_bytes(), [9u8; 32]);
+        assert!(keystore.public_key.starts_with('G'));
+    }
+
+    #[test]
+    fn wrong_password_is_rejected() {
+        let keystore = EncryptedKeystore::encrypt(&secret_seed(), "correct horse").unwrap();
+        assert!(keystore.decrypt("battery staple").is_err());
+    }
+
+    #[test]
+    fn tampered_ciphertext_is_rejected() {
+        let mut keystore = EncryptedKeystore::encrypt(&secret_seed(), "pw").unwrap();
+        let mut bytes = hex::decode(&keystore.ciphertext).unwrap();
+        bytes[0] ^= 0xff;
+        keystore.ciphertext = hex::encode(bytes);
+        assert!(keystore.decrypt("pw").is_err());
+    }
+
+    #[tokio::test]
+    async fn keystore_signer_uses_stored_public_key() {
+        let keystore = EncryptedKeystore::encrypt(&secret_seed(), "pw").unwrap();
+        let signer = KeystoreSigner::unlock(&keystore, "pw").unwrap();
+        assert_eq!(signer.public_key(), keystore.public_key);
+
+        let hash = [5u8; 32];
+        let signature = signer.sign_hash(&hash).await.unwrap();
+        assert!(crate::chains::stellar::multisig::verify_signature(
+            &hash,
+            signer.public_key(),
+            &hex::encode(signature)
+        )
+        .is_ok());
+    }
+}
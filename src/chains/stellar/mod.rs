@@ -1,8 +1,11 @@
 pub mod client;
 pub mod config;
 pub mod errors;
+pub mod keystore;
+pub mod multisig;
 pub mod payment;
 pub mod service;
+pub mod signer;
 pub mod trustline;
 pub mod types;
 
@@ -0,0 +1,376 @@
+//! Stellar multisig helpers
+//!
+//! Pure functions for M-of-N signing: hashing an unsigned envelope, verifying
+//! detached signatures, pulling signatures out of pre-signed envelopes and
+//! checking collected weight against the account's signers and thresholds.
+
+use crate::chains::stellar::errors::{StellarError, StellarResult};
+use crate::chains::stellar::payment::network_id;
+use crate::chains::stellar::types::{StellarAccountInfo, Thresholds};
+use ed25519_dalek::{Signature as Ed25519Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use stellar_strkey::ed25519::PublicKey as StrkeyPublicKey;
+use stellar_xdr::next::{
+    DecoratedSignature, Limits, ReadXdr, Signature, SignatureHint, Transaction,
+    TransactionEnvelope, TransactionV1Envelope, VecM, WriteXdr,
+};
+
+/// Stellar rejects envelopes carrying more than 20 signatures.
+pub const MAX_ENVELOPE_SIGNATURES: usize = 20;
+
+const SIGNER_TYPE_ED25519: &str = "ed25519_public_key";
+const SIGNER_TYPE_PREAUTH_TX: &str = "preauth_tx";
+
+/// Threshold category an operation falls into on Stellar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThresholdLevel {
+    Low,
+    Medium,
+    High,
+}
+
+impl ThresholdLevel {
+    /// Weight required by the account for this level. A zero threshold still
+    /// needs at least one non-zero weight signature to be accepted.
+    pub fn required_weight(&self, thresholds: &Thresholds) -> u32 {
+        let raw = match self {
+            ThresholdLevel::Low => thresholds.low_threshold,
+            ThresholdLevel::Medium => thresholds.med_threshold,
+            ThresholdLevel::High => thresholds.high_threshold,
+        };
+        u32::from(raw).max(1)
+    }
+}
+
+/// A detached ed25519 signature over a transaction hash.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CollectedSignature {
+    pub public_key: String,
+    /// Hex-encoded 64-byte signature
+    pub signature: String,
+}
+
+/// Outcome of checking collected signatures against the account's signer set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThresholdEvaluation {
+    pub level: ThresholdLevel,
+    pub required_weight: u32,
+    pub collected_weight: u32,
+    pub counted_signers: Vec<String>,
+    pub ignored_signers: Vec<String>,
+    pub preauthorized: bool,
+    pub is_met: bool,
+}
+
+/// Decode an unsigned v1 envelope and return its transaction.
+pub fn envelope_transaction(envelope_xdr: &str) -> StellarResult<Transaction> {
+    let envelope = TransactionEnvelope::from_xdr_base64(envelope_xdr, Limits::none())
+        .map_err(|e| StellarError::serialization_error(format!("invalid envelope xdr: {}", e)))?;
+    match envelope {
+        TransactionEnvelope::Tx(v1) => Ok(v1.tx),
+        _ => Err(StellarError::signing_error(
+            "only v1 transaction envelopes are supported for multisig",
+        )),
+    }
+}
+
+/// Hash the transaction inside an envelope for the given network.
+pub fn transaction_hash(envelope_xdr: &str, network_passphrase: &str) -> StellarResult<[u8; 32]> {
+    envelope_transaction(envelope_xdr)?
+        .hash(network_id(network_passphrase))
+        .map_err(|e| StellarError::serialization_error(e.to_string()))
+}
+
+/// Verify a hex-encoded signature from `public_key` over `tx_hash`.
+pub fn verify_signature(
+    tx_hash: &[u8; 32],
+    public_key: &str,
+    signature_hex: &str,
+) -> StellarResult<()> {
+    let verifying_key = verifying_key(public_key)?;
+    let bytes = hex::decode(signature_hex.trim())
+        .map_err(|_| StellarError::signing_error("signature must be hex encoded"))?;
+    let signature = Ed25519Signature::from_slice(&bytes)
+        .map_err(|_| StellarError::signing_error("signature must be 64 bytes"))?;
+    verifying_key.verify(tx_hash, &signature).map_err(|_| {
+        StellarError::signing_error(format!("signature from {} does not verify", public_key))
+    })
+}
+
+/// Pull every signature out of a pre-signed envelope that belongs to one of
+/// `candidates` and verifies against `tx_hash`. Signatures whose hint does not
+/// match a candidate, or that fail verification, are skipped.
+pub fn extract_signatures(
+    signed_envelope_xdr: &str,
+    tx_hash: &[u8; 32],
+    candidates: &[String],
+) -> StellarResult<Vec<CollectedSignature>> {
+    let envelope = TransactionEnvelope::from_xdr_base64(signed_envelope_xdr, Limits::none())
+        .map_err(|e| StellarError::signing_error(format!("invalid xdr: {}", e)))?;
+    let signatures = match envelope {
+        TransactionEnvelope::Tx(v1) => v1.signatures,
+        _ => {
+            return Err(StellarError::signing_error(
+                "only v1 transaction envelopes are supported for multisig",
+            ))
+        }
+    };
+
+    let mut collected = Vec::new();
+    for decorated in signatures.iter() {
+        for candidate in candidates {
+            let Ok(hint) = signature_hint(candidate) else {
+                continue;
+            };
+            if hint != decorated.hint {
+                continue;
+            }
+            let signature_hex = hex::encode(decorated.signature.as_slice());
+            if verify_signature(tx_hash, candidate, &signature_hex).is_ok() {
+                collected.push(CollectedSignature {
+                    public_key: candidate.clone(),
+                    signature: signature_hex,
+                });
+                break;
+            }
+        }
+    }
+
+    Ok(collected)
+}
+
+/// Attach `signatures` to an unsigned envelope, returning the signed XDR.
+pub fn assemble_signed_envelope(
+    unsigned_envelope_xdr: &str,
+    signatures: &[CollectedSignature],
+) -> StellarResult<String> {
+    if signatures.is_empty() {
+        return Err(StellarError::signing_error("no signatures to attach"));
+    }
+    if signatures.len() > MAX_ENVELOPE_SIGNATURES {
+        return Err(StellarError::signing_error(format!(
+            "envelope cannot carry more than {} signatures",
+            MAX_ENVELOPE_SIGNATURES
+        )));
+    }
+
+    let tx = envelope_transaction(unsigned_envelope_xdr)?;
+    let mut decorated = Vec::with_capacity(signatures.len());
+    for sig in signatures {
+        let bytes = hex::decode(&sig.signature)
+            .map_err(|_| StellarError::signing_error("signature must be hex encoded"))?;
+        decorated.push(DecoratedSignature {
+            hint: signature_hint(&sig.public_key)?,
+            signature: Signature::try_from(bytes)
+                .map_err(|e| StellarError::serialization_error(e.to_string()))?,
+        });
+    }
+
+    TransactionEnvelope::Tx(TransactionV1Envelope {
+        tx,
+        signatures: VecM::try_from(decorated)
+            .map_err(|e| StellarError::serialization_error(e.to_string()))?,
+    })
+    .to_xdr_base64(Limits::none())
+    .map_err(|e| StellarError::serialization_error(e.to_string()))
+}
+
+/// Sum the weight of `signer_keys` against the account's current signer set.
+///
+/// Keys that are not ed25519 signers on the account are reported in
+/// `ignored_signers`. A `preauth_tx` signer matching `tx_hash` authorizes the
+/// transaction on its own weight, which is how pre-signed transactions are
+/// honoured.
+pub fn evaluate_threshold(
+    account: &StellarAccountInfo,
+    level: ThresholdLevel,
+    signer_keys: &[String],
+    tx_hash: &[u8; 32],
+) -> ThresholdEvaluation {
+    let required_weight = level.required_weight(&account.thresholds);
+    let mut collected_weight = 0u32;
+    let mut counted_signers = Vec::new();
+    let mut ignored_signers = Vec::new();
+
+    for key in signer_keys {
+        if counted_signers.contains(key) {
+            continue;
+        }
+        let weight = account
+            .signers
+            .iter()
+            .find(|s| s.r#type == SIGNER_TYPE_ED25519 && &s.key == key)
+            .map(|s| u32::from(s.weight))
+            .unwrap_or(0);
+        if weight == 0 {
+            ignored_signers.push(key.clone());
+        } else {
+            collected_weight += weight;
+            counted_signers.push(key.clone());
+        }
+    }
+
+    let preauth_key = stellar_strkey::PreAuthTx(*tx_hash).to_string();
+    let preauthorized = account
+        .signers
+        .iter()
+        .find(|s| s.r#type == SIGNER_TYPE_PREAUTH_TX && s.key == preauth_key.as_str())
+        .map(|s| {
+            collected_weight += u32::from(s.weight);
+            s.weight > 0
+        })
+        .unwrap_or(false);
+
+    ThresholdEvaluation {
+        level,
+        required_weight,
+        collected_weight,
+        counted_signers,
+        ignored_signers,
+        preauthorized,
+        is_met: collected_weight >= required_weight,
+    }
+}
+
+/// ed25519 signer keys registered on the account with non-zero weight.
+pub fn weighted_signer_keys(account: &StellarAccountInfo) -> Vec<String> {
+    account
+        .signers
+        .iter()
+        .filter(|s| s.r#type == SIGNER_TYPE_ED25519 && s.weight > 0)
+        .map(|s| s.key.clone())
+        .collect()
+}
+
+fn verifying_key(public_key: &str) -> StellarResult<VerifyingKey> {
+    let decoded = StrkeyPublicKey::from_string(public_key)
+        .map_err(|_| StellarError::invalid_address(public_key))?;
+    VerifyingKey::from_bytes(&decoded.0)
+        .map_err(|_| StellarError::signing_error(format!("invalid ed25519 key {}", public_key)))
+}
+
+fn signature_hint(public_key: &str) -> StellarResult<SignatureHint> {
+    let decoded = StrkeyPublicKey::from_string(public_key)
+        .map_err(|_| StellarError::invalid_address(public_key))?;
+    SignatureHint::try_from(&decoded.0[28..])
+        .map_err(|e| StellarError::serialization_error(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chains::stellar::types::{AccountFlags, Signer};
+    use ed25519_dalek::{Signer as _, SigningKey};
+    use std::collections::HashMap;
+
+    fn key(seed: u8) -> (SigningKey, String) {
+        let signing_key = SigningKey::from_bytes(&[seed; 32]);
+        let address = StrkeyPublicKey(signing_key.verifying_key().to_bytes())
+            .to_string()
+            .as_str()
+            .to_owned();
+        (signing_key, address)
+    }
+
+    fn account(signers: Vec<Signer>, med_threshold: u8) -> StellarAccountInfo {
+        StellarAccountInfo {
+            account_id: signers[0].key.clone(),
+            sequence: 1,
+            subentry_count: 0,
+            thresholds: Thresholds {
+                low_threshold: 1,
+                med_threshold,
+                high_threshold: med_threshold,
+            },
+            flags: AccountFlags {
+                auth_required: false,
+                auth_revocable: false,
+                auth_immutable: false,
+                auth_clawback_enabled: false,
+            },
+            balances: vec![],
+            signers,
+            data: HashMap::new(),
+            last_modified_ledger: 1,
+            created_at: String::new(),
+        }
+    }
+
+    fn ed25519_signer(key: &str, weight: u8) -> Signer {
+        Signer {
+            key: key.to_string(),
+            weight,
+            r#type: SIGNER_TYPE_ED25519.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_verify_signature_roundtrip() {
+        let (signing_key, address) = key(7);
+        let hash = [9u8; 32];
+        let signature = hex::encode(signing_key.sign(&hash).to_bytes());
+
+        assert!(verify_signature(&hash, &address, &signature).is_ok());
+        assert!(verify_signature(&[1u8; 32], &address, &signature).is_err());
+        assert!(verify_signature(&hash, &address, "zz").is_err());
+    }
+
+    #[test]
+    fn test_evaluate_threshold_two_of_three() {
+        let (_, a) = key(1);
+        let (_, b) = key(2);
+        let (_, c) = key(3);
+        let (_, outsider) = key(4);
+        let acct = account(
+            vec![
+                ed25519_signer(&a, 1),
+                ed25519_signer(&b, 1),
+                ed25519_signer(&c, 1),
+            ],
+            2,
+        );
+        let hash = [0u8; 32];
+
+        let one = evaluate_threshold(&acct, ThresholdLevel::Medium, &[a.clone()], &hash);
+        assert_eq!(one.required_weight, 2);
+        assert!(!one.is_met);
+
+        let two = evaluate_threshold(
+            &acct,
+            ThresholdLevel::Medium,
+            &[a.clone(), a.clone(), outsider.clone(), b.clone()],
+            &hash,
+        );
+        assert_eq!(two.collected_weight, 2);
+        assert_eq!(two.ignored_signers, vec![outsider]);
+        assert!(two.is_met);
+    }
+
+    #[test]
+    fn test_evaluate_threshold_honours_preauth_signer() {
+        let (_, a) = key(1);
+        let hash = [5u8; 32];
+        let mut signers = vec![ed25519_signer(&a, 1)];
+        signers.push(Signer {
+            key: stellar_strkey::PreAuthTx(hash).to_string().as_str().to_owned(),
+            weight: 2,
+            r#type: SIGNER_TYPE_PREAUTH_TX.to_string(),
+        });
+        let acct = account(signers, 2);
+
+        let eval = evaluate_threshold(&acct, ThresholdLevel::Medium, &[], &hash);
+        assert!(eval.preauthorized);
+        assert!(eval.is_met);
+    }
+
+    #[test]
+    fn test_zero_threshold_still_requires_a_signature() {
+        let thresholds = Thresholds {
+            low_threshold: 0,
+            med_threshold: 0,
+            high_threshold: 0,
+        };
+        assert_eq!(ThresholdLevel::Low.required_weight(&thresholds), 1);
+    }
+}
@@ -1,25 +1,35 @@
 use crate::chains::stellar::client::StellarClient;
 use crate::chains::stellar::errors::{StellarError, StellarResult};
+use crate::chains::stellar::multisig;
+use crate::chains::stellar::signer::{
+    ensure_signer_matches_source, sign_envelope, SignedEnvelope, TransactionSigner,
+};
 use crate::chains::stellar::trustline::CngnAssetConfig;
-use crate::chains::stellar::types::{extract_asset_balance, is_valid_stellar_address};
-use ed25519_dalek::{Signer, SigningKey};
+use crate::chains::stellar::types::{
+    extract_asset_balance, is_valid_stellar_address, HorizonPaymentPath, StellarAsset,
+};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
-use stellar_strkey::ed25519::{
-    MuxedAccount as StrkeyMuxedAccount, PrivateKey as StrkeyPrivateKey,
-    PublicKey as StrkeyPublicKey,
-};
+use stellar_strkey::ed25519::{MuxedAccount as StrkeyMuxedAccount, PublicKey as StrkeyPublicKey};
 use stellar_xdr::next::{
-    AccountId, AlphaNum12, AlphaNum4, Asset, AssetCode12, AssetCode4, DecoratedSignature, Hash,
-    Limits, Memo, MuxedAccount, MuxedAccountMed25519, Operation, OperationBody, PaymentOp,
-    Preconditions, PublicKey, ReadXdr, SequenceNumber, Signature, SignatureHint, StringM,
+    AccountId, AlphaNum12, AlphaNum4, Asset, AssetCode12, AssetCode4, ChangeTrustAsset,
+    ChangeTrustOp, ClaimClaimableBalanceOp, ClaimPredicate, ClaimableBalanceId, Claimant,
+    ClaimantV0, CreateClaimableBalanceOp, DecoratedSignature, Hash, HashIdPreimage,
+    HashIdPreimageOperationId, Limits, Memo, MuxedAccount, MuxedAccountMed25519, Operation,
+    OperationBody,
+    PathPaymentStrictReceiveOp, PathPaymentStrictSendOp, PaymentOp, Preconditions, PublicKey,
+    SequenceNumber, StringM,
     TimeBounds, TimePoint, Transaction, TransactionEnvelope, TransactionExt, TransactionV1Envelope,
     Uint256, VecM, WriteXdr,
 };
 
 const DEFAULT_BASE_FEE_STROOPS: u32 = 100;
 const DEFAULT_TIMEOUT_SECONDS: u64 = 300;
+const DEFAULT_SLIPPAGE_BPS: u32 = 50;
+const DEFAULT_MAX_SLIPPAGE_BPS: u32 = 500;
+/// Path payment operations allow at most five intermediate assets
+const MAX_PATH_HOPS: usize = 5;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "value", rename_all = "snake_case")]
@@ -44,6 +54,13 @@ pub struct CngnPaymentDraft {
     pub transaction_hash: String,
     pub unsigned_envelope_xdr: String,
     pub memo: CngnMemo,
+    /// Set when the destination had no trustline and the funds are parked in
+    /// a claimable balance instead (Horizon hex id)
+    #[serde(default)]
+    pub claimable_balance_id: Option<String>,
+    /// After this time only the source can claim the balance back
+    #[serde(default)]
+    pub reclaim_after: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,12 +70,91 @@ pub struct SignedCngnPayment {
     pub signed_envelope_xdr: String,
 }
 
+/// What a server-built payment envelope must do, checked against the
+/// decoded XDR before a server-side key signs it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpectedCngnPayment {
+    pub source: String,
+    pub destination: String,
+    pub asset_code: String,
+    pub asset_issuer: String,
+    pub amount: String,
+}
+
+impl From<&CngnPaymentDraft> for ExpectedCngnPayment {
+    fn from(draft: &CngnPaymentDraft) -> Self {
+        Self {
+            source: draft.source.clone(),
+            destination: draft.destination.clone(),
+            asset_code: draft.asset_code.clone(),
+            asset_issuer: draft.asset_issuer.clone(),
+            amount: draft.amount.clone(),
+        }
+    }
+}
+
+/// Which side of a path payment is fixed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PathPaymentMode {
+    /// Spend exactly `send_amount`; receive at least `dest_min`
+    StrictSend,
+    /// Receive exactly `dest_amount`; spend at most `send_max`
+    StrictReceive,
+}
+
+/// Best DEX path found by Horizon with slippage bounds applied.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathPaymentQuote {
+    pub mode: PathPaymentMode,
+    pub send_asset: StellarAsset,
+    pub dest_asset: StellarAsset,
+    /// Amounts Horizon expects at quote time
+    pub send_amount: String,
+    pub dest_amount: String,
+    /// Destination units per source unit
+    pub rate: String,
+    pub path: Vec<StellarAsset>,
+    pub slippage_bps: u32,
+    pub send_max: String,
+    pub dest_min: String,
+    pub quoted_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathPaymentDraft {
+    pub source: String,
+    pub destination: String,
+    pub quote: PathPaymentQuote,
+    pub sequence: i64,
+    pub fee_stroops: u32,
+    pub timeout_seconds: u64,
+    pub created_at: String,
+    pub transaction_hash: String,
+    pub unsigned_envelope_xdr: String,
+    pub memo: CngnMemo,
+}
+
+/// Unsigned transaction that claims a claimable balance, adding the cNGN
+/// trustline first when the claimant does not have one yet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnsignedClaimTransaction {
+    pub account_id: String,
+    pub balance_id: String,
+    pub adds_trustline: bool,
+    pub sequence: i64,
+    pub fee_stroops: u32,
+    pub transaction_hash: String,
+    pub unsigned_envelope_xdr: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct CngnPaymentBuilder {
     stellar_client: StellarClient,
     config: CngnAssetConfig,
     base_fee_stroops: u32,
     timeout: Duration,
+    max_slippage_bps: u32,
 }
 
 impl CngnPaymentBuilder {
@@ -68,6 +164,7 @@ impl CngnPaymentBuilder {
             config: CngnAssetConfig::from_env(),
             base_fee_stroops: DEFAULT_BASE_FEE_STROOPS,
             timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECONDS),
+            max_slippage_bps: DEFAULT_MAX_SLIPPAGE_BPS,
         }
     }
 
@@ -81,6 +178,21 @@ impl CngnPaymentBuilder {
         self
     }
 
+    pub fn with_max_slippage_bps(mut self, max_slippage_bps: u32) -> Self {
+        self.max_slippage_bps = max_slippage_bps;
+        self
+    }
+
+    /// The cNGN asset on the client's network
+    pub fn cngn_asset(&self) -> StellarAsset {
+        StellarAsset::credit(
+            self.config.asset_code.clone(),
+            self.config
+                .issuer_for_network(self.stellar_client.network())
+                .to_string(),
+        )
+    }
+
     pub async fn build_payment(
         &self,
         source: &str,
@@ -88,6 +200,42 @@ impl CngnPaymentBuilder {
         amount: &str,
         memo: CngnMemo,
         fee_stroops: Option<u32>,
+    ) -> StellarResult<CngnPaymentDraft> {
+        self.build_transfer(source, destination, amount, memo, fee_stroops, None)
+            .await
+    }
+
+    /// Like `build_payment`, but when the destination has no cNGN trustline
+    /// the funds go into a claimable balance instead. The recipient can claim
+    /// it until `reclaim_after` elapses; after that only `source` can.
+    pub async fn build_payout(
+        &self,
+        source: &str,
+        destination: &str,
+        amount: &str,
+        memo: CngnMemo,
+        fee_stroops: Option<u32>,
+        reclaim_after: Duration,
+    ) -> StellarResult<CngnPaymentDraft> {
+        self.build_transfer(
+            source,
+            destination,
+            amount,
+            memo,
+            fee_stroops,
+            Some(reclaim_after),
+        )
+        .await
+    }
+
+    async fn build_transfer(
+        &self,
+        source: &str,
+        destination: &str,
+        amount: &str,
+        memo: CngnMemo,
+        fee_stroops: Option<u32>,
+        claimable_fallback: Option<Duration>,
     ) -> StellarResult<CngnPaymentDraft> {
         validate_address(source)?;
         validate_address(destination)?;
@@ -101,7 +249,17 @@ impl CngnPaymentBuilder {
             .to_string();
         let asset_code = self.config.asset_code.clone();
 
-        ensure_destination_has_trustline(&destination_account.balances, &asset_code, &issuer)?;
+        let reclaim_after = match ensure_destination_has_trustline(
+            &destination_account.balances,
+            &asset_code,
+            &issuer,
+        ) {
+            Ok(()) => None,
+            Err(e) => match claimable_fallback {
+                Some(window) => Some(unix_time() + window.as_secs()),
+                None => return Err(e),
+            },
+        };
 
         let amount_stroops = decimal_to_stroops(amount)?;
         ensure_source_has_cngn_balance(
@@ -115,17 +273,36 @@ impl CngnPaymentBuilder {
         ensure_source_has_xlm_for_fee(&source_account.balances, fee)?;
 
         let sequence = source_account.sequence + 1;
-        let (tx, envelope) = build_unsigned_transaction(
-            source,
-            destination,
-            amount_stroops,
-            sequence,
-            fee,
-            self.timeout,
-            &memo,
-            &asset_code,
-            &issuer,
-        )?;
+        let (tx, envelope, claimable_balance_id) = match reclaim_after {
+            None => {
+                let (tx, envelope) = build_unsigned_transaction(
+                    source,
+                    destination,
+                    amount_stroops,
+                    sequence,
+                    fee,
+                    self.timeout,
+                    &memo,
+                    &asset_code,
+                    &issuer,
+                )?;
+                (tx, envelope, None)
+            }
+            Some(deadline) => {
+                let (tx, envelope) = build_claimable_balance_transaction(
+                    source,
+                    destination,
+                    amount_stroops,
+                    sequence,
+                    fee,
+                    self.timeout,
+                    &memo,
+                    build_asset(&asset_code, &issuer)?,
+                    deadline,
+                )?;
+                (tx, envelope, Some(claimable_balance_id(source, sequence, 0)?))
+            }
+        };
 
         let network_id = network_id(self.stellar_client.network().network_passphrase());
         let tx_hash = tx
@@ -149,60 +326,267 @@ impl CngnPaymentBuilder {
             transaction_hash: hex::encode(tx_hash),
             unsigned_envelope_xdr,
             memo,
+            claimable_balance_id,
+            reclaim_after: reclaim_after.and_then(|t| {
+                chrono::DateTime::from_timestamp(t as i64, 0).map(|d| d.to_rfc3339())
+            }),
+        })
+    }
+
+    /// Build a transaction for `account` that claims a cNGN claimable
+    /// balance. The cNGN trustline is added in the same transaction when
+    /// missing, so a recipient without one can claim in a single step.
+    pub async fn build_claim(
+        &self,
+        account: &str,
+        balance_id: &str,
+        fee_stroops: Option<u32>,
+    ) -> StellarResult<UnsignedClaimTransaction> {
+        validate_address(account)?;
+        let balance_id_xdr = parse_claimable_balance_id(balance_id)?;
+        let account_info = self.stellar_client.get_account(account).await?;
+
+        let issuer = self
+            .config
+            .issuer_for_network(self.stellar_client.network())
+            .to_string();
+        let asset_code = self.config.asset_code.clone();
+        let adds_trustline =
+            extract_asset_balance(&account_info.balances, &asset_code, Some(&issuer)).is_none();
+
+        let mut operations = Vec::with_capacity(2);
+        if adds_trustline {
+            operations.push(change_trust_operation(build_asset(&asset_code, &issuer)?)?);
+        }
+        operations.push(Operation {
+            source_account: None,
+            body: OperationBody::ClaimClaimableBalance(ClaimClaimableBalanceOp {
+                balance_id: balance_id_xdr,
+            }),
+        });
+
+        let fee_per_op = fee_stroops.unwrap_or(self.base_fee_stroops);
+        let fee = fee_per_op.saturating_mul(operations.len() as u32);
+        ensure_source_has_xlm_for_fee(&account_info.balances, fee)?;
+
+        let sequence = account_info.sequence + 1;
+        let (tx, envelope) = assemble_transaction(
+            account,
+            operations,
+            sequence,
+            fee,
+            self.timeout,
+            &CngnMemo::None,
+        )?;
+
+        let network_id = network_id(self.stellar_client.network().network_passphrase());
+        let tx_hash = tx
+            .hash(network_id)
+            .map_err(|e| StellarError::serialization_error(e.to_string()))?;
+        let unsigned_envelope_xdr = envelope
+            .to_xdr_base64(Limits::none())
+            .map_err(|e| StellarError::serialization_error(e.to_string()))?;
+
+        Ok(UnsignedClaimTransaction {
+            account_id: account.to_string(),
+            balance_id: balance_id.to_string(),
+            adds_trustline,
+            sequence,
+            fee_stroops: fee,
+            transaction_hash: hex::encode(tx_hash),
+            unsigned_envelope_xdr,
         })
     }
 
-    pub fn sign_payment(
+    /// Sign a draft with `signer`, whose key must be the draft's source
+    /// account. The envelope is decoded first and must pay exactly what the
+    /// draft says.
+    pub async fn sign_payment(
         &self,
         draft: CngnPaymentDraft,
-        secret_seed: &str,
+        signer: &dyn TransactionSigner,
     ) -> StellarResult<SignedCngnPayment> {
-        let signing_key = decode_signing_key(secret_seed)?;
-        ensure_signing_key_matches_source(&signing_key, &draft.source)?;
+        let signed = self
+            .sign_verified_envelope(
+                &draft.unsigned_envelope_xdr,
+                &ExpectedCngnPayment::from(&draft),
+                signer,
+            )
+            .await?;
 
-        let envelope =
-            TransactionEnvelope::from_xdr_base64(&draft.unsigned_envelope_xdr, Limits::none())
-                .map_err(|e| StellarError::serialization_error(e.to_string()))?;
+        Ok(SignedCngnPayment {
+            draft,
+            signature: signed.signature,
+            signed_envelope_xdr: signed.signed_envelope_xdr,
+        })
+    }
 
-        let tx = match envelope {
-            TransactionEnvelope::Tx(v1) => v1.tx,
-            _ => {
-                return Err(StellarError::signing_error(
-                    "unsupported envelope type for cNGN payment",
-                ))
+    /// Sign an envelope the server built earlier, once its decoded XDR is
+    /// confirmed to hold a single payment (or claimable balance) of
+    /// `expected` and nothing else.
+    pub async fn sign_verified_envelope(
+        &self,
+        unsigned_envelope_xdr: &str,
+        expected: &ExpectedCngnPayment,
+        signer: &dyn TransactionSigner,
+    ) -> StellarResult<SignedEnvelope> {
+        ensure_signer_matches_source(signer, &expected.source)?;
+        let tx = multisig::envelope_transaction(unsigned_envelope_xdr)?;
+        verify_payment_envelope(&tx, expected)?;
+
+        sign_envelope(
+            signer,
+            unsigned_envelope_xdr,
+            self.stellar_client.network().network_passphrase(),
+        )
+        .await
+    }
+
+    /// Find the best DEX path between two assets. `amount` is the send
+    /// amount for strict-send and the destination amount for strict-receive.
+    pub async fn quote_path_payment(
+        &self,
+        mode: PathPaymentMode,
+        send_asset: &StellarAsset,
+        dest_asset: &StellarAsset,
+        amount: &str,
+        slippage_bps: Option<u32>,
+    ) -> StellarResult<PathPaymentQuote> {
+        if send_asset == dest_asset {
+            return Err(StellarError::transaction_failed(
+                "send and destination assets are the same; use a plain payment",
+            ));
+        }
+        let slippage_bps = slippage_bps.unwrap_or(DEFAULT_SLIPPAGE_BPS);
+        self.ensure_slippage_allowed(slippage_bps)?;
+        let amount_stroops = decimal_to_stroops(amount)?;
+        if amount_stroops <= 0 {
+            return Err(StellarError::transaction_failed(
+                "amount must be greater than zero",
+            ));
+        }
+        let amount = decimal_from_stroops(amount_stroops);
+
+        let records = match mode {
+            PathPaymentMode::StrictSend => {
+                self.stellar_client
+                    .find_strict_send_paths(send_asset, &amount, std::slice::from_ref(dest_asset))
+                    .await?
+            }
+            PathPaymentMode::StrictReceive => {
+                self.stellar_client
+                    .find_strict_receive_paths(std::slice::from_ref(send_asset), dest_asset, &amount)
+                    .await?
             }
         };
 
+        let (send_stroops, dest_stroops, path) =
+            select_best_path(mode, &records, send_asset, dest_asset).ok_or_else(|| {
+                StellarError::transaction_failed(format!(
+                    "no DEX path from {} to {} for amount {}",
+                    send_asset, dest_asset, amount
+                ))
+            })?;
+
+        path_payment_quote(
+            mode,
+            send_asset.clone(),
+            dest_asset.clone(),
+            send_stroops,
+            dest_stroops,
+            path,
+            slippage_bps,
+        )
+    }
+
+    /// Build an unsigned path payment from `source` to `destination` using a
+    /// quote. The slippage bounds are recomputed from the quoted amounts, so
+    /// a caller cannot widen them past the configured maximum.
+    pub async fn build_path_payment(
+        &self,
+        source: &str,
+        destination: &str,
+        quote: PathPaymentQuote,
+        memo: CngnMemo,
+        fee_stroops: Option<u32>,
+    ) -> StellarResult<PathPaymentDraft> {
+        validate_address(source)?;
+        validate_address(destination)?;
+        if quote.path.len() > MAX_PATH_HOPS {
+            return Err(StellarError::transaction_failed(format!(
+                "path has {} hops; at most {} are allowed",
+                quote.path.len(),
+                MAX_PATH_HOPS
+            )));
+        }
+        let quote = path_payment_quote(
+            quote.mode,
+            quote.send_asset,
+            quote.dest_asset,
+            decimal_to_stroops(&quote.send_amount)?,
+            decimal_to_stroops(&quote.dest_amount)?,
+            quote.path,
+            quote.slippage_bps,
+        )?;
+        self.ensure_slippage_allowed(quote.slippage_bps)?;
+
+        let source_account = self.stellar_client.get_account(source).await?;
+        let destination_account = self.stellar_client.get_account(destination).await?;
+
+        ensure_destination_accepts_asset(&destination_account.balances, &quote.dest_asset)?;
+
+        let fee = fee_stroops.unwrap_or(self.base_fee_stroops);
+        let send_max_stroops = decimal_to_stroops(&quote.send_max)?;
+        ensure_source_has_asset_balance(
+            &source_account.balances,
+            &quote.send_asset,
+            send_max_stroops,
+        )?;
+        ensure_source_has_xlm_for_fee(&source_account.balances, fee)?;
+
+        let sequence = source_account.sequence + 1;
+        let (tx, envelope) = build_path_payment_transaction(
+            source,
+            destination,
+            &quote,
+            sequence,
+            fee,
+            self.timeout,
+            &memo,
+        )?;
+
         let network_id = network_id(self.stellar_client.network().network_passphrase());
-        let hash = tx
+        let tx_hash = tx
             .hash(network_id)
             .map_err(|e| StellarError::serialization_error(e.to_string()))?;
-
-        let signature_bytes = signing_key
-            .try_sign(&hash)
-            .map_err(|_| StellarError::signing_error("failed to sign transaction hash"))?
-            .to_bytes()
-            .to_vec();
-        let hint = signature_hint(&signing_key)?;
-        let signature = Signature::try_from(signature_bytes.clone())
-            .map_err(|e| StellarError::serialization_error(e.to_string()))?;
-        let decorated = DecoratedSignature { hint, signature };
-        let signed_env = TransactionEnvelope::Tx(TransactionV1Envelope {
-            tx,
-            signatures: VecM::try_from(vec![decorated])
-                .map_err(|e| StellarError::serialization_error(e.to_string()))?,
-        });
-        let signed_envelope_xdr = signed_env
+        let unsigned_envelope_xdr = envelope
             .to_xdr_base64(Limits::none())
             .map_err(|e| StellarError::serialization_error(e.to_string()))?;
 
-        Ok(SignedCngnPayment {
-            draft,
-            signature: hex::encode(signature_bytes),
-            signed_envelope_xdr,
+        Ok(PathPaymentDraft {
+            source: source.to_string(),
+            destination: destination.to_string(),
+            quote,
+            sequence,
+            fee_stroops: fee,
+            timeout_seconds: self.timeout.as_secs(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            transaction_hash: hex::encode(tx_hash),
+            unsigned_envelope_xdr,
+            memo,
         })
     }
 
+    fn ensure_slippage_allowed(&self, slippage_bps: u32) -> StellarResult<()> {
+        if slippage_bps > self.max_slippage_bps {
+            return Err(StellarError::transaction_failed(format!(
+                "slippage {} bps exceeds the maximum of {} bps",
+                slippage_bps, self.max_slippage_bps
+            )));
+        }
+        Ok(())
+    }
+
     pub async fn submit_signed_payment(
         &self,
         signed_envelope_xdr: &str,
@@ -276,6 +660,197 @@ fn ensure_source_has_cngn_balance(
     }
 }
 
+fn ensure_destination_accepts_asset(
+    balances: &[crate::chains::stellar::types::AssetBalance],
+    asset: &StellarAsset,
+) -> StellarResult<()> {
+    match asset {
+        StellarAsset::Native => Ok(()),
+        StellarAsset::Credit { code, issuer } => {
+            if extract_asset_balance(balances, code, Some(issuer)).is_some() {
+                Ok(())
+            } else {
+                Err(StellarError::transaction_failed(format!(
+                    "recipient has no {} trustline (op_no_trust)",
+                    code
+                )))
+            }
+        }
+    }
+}
+
+fn ensure_source_has_asset_balance(
+    balances: &[crate::chains::stellar::types::AssetBalance],
+    asset: &StellarAsset,
+    amount_stroops: i64,
+) -> StellarResult<()> {
+    let balance = match asset {
+        StellarAsset::Native => balances
+            .iter()
+            .find(|b| b.asset_type == "native")
+            .map(|b| b.balance.clone()),
+        StellarAsset::Credit { code, issuer } => {
+            extract_asset_balance(balances, code, Some(issuer))
+        }
+    }
+    .unwrap_or_else(|| "0".to_string());
+    let available_stroops = decimal_to_stroops(&balance)?;
+    if available_stroops >= amount_stroops {
+        Ok(())
+    } else {
+        Err(StellarError::transaction_failed(format!(
+            "insufficient {} balance: available={}, required={}",
+            asset.code(),
+            balance,
+            decimal_from_stroops(amount_stroops)
+        )))
+    }
+}
+
+/// Pick the cheapest usable path: most received for strict-send, least
+/// spent for strict-receive. Returns (send stroops, dest stroops, hops).
+fn select_best_path(
+    mode: PathPaymentMode,
+    records: &[HorizonPaymentPath],
+    send_asset: &StellarAsset,
+    dest_asset: &StellarAsset,
+) -> Option<(i64, i64, Vec<StellarAsset>)> {
+    let candidates = records.iter().filter_map(|record| {
+        if record.source_asset().as_ref() != Some(send_asset)
+            || record.destination_asset().as_ref() != Some(dest_asset)
+        {
+            return None;
+        }
+        let hops = record.hops()?;
+        if hops.len() > MAX_PATH_HOPS {
+            return None;
+        }
+        let send = decimal_to_stroops(&record.source_amount).ok()?;
+        let dest = decimal_to_stroops(&record.destination_amount).ok()?;
+        (send > 0 && dest > 0).then_some((send, dest, hops))
+    });
+
+    match mode {
+        PathPaymentMode::StrictSend => candidates.max_by_key(|(_, dest, _)| *dest),
+        PathPaymentMode::StrictReceive => candidates.min_by_key(|(send, _, _)| *send),
+    }
+}
+
+fn path_payment_quote(
+    mode: PathPaymentMode,
+    send_asset: StellarAsset,
+    dest_asset: StellarAsset,
+    send_stroops: i64,
+    dest_stroops: i64,
+    path: Vec<StellarAsset>,
+    slippage_bps: u32,
+) -> StellarResult<PathPaymentQuote> {
+    if send_stroops <= 0 || dest_stroops <= 0 {
+        return Err(StellarError::transaction_failed(
+            "quoted amounts must be greater than zero",
+        ));
+    }
+    let (send_max, dest_min) = apply_slippage(mode, send_stroops, dest_stroops, slippage_bps)?;
+    let rate = (dest_stroops as i128 * 10_000_000) / send_stroops as i128;
+
+    Ok(PathPaymentQuote {
+        mode,
+        send_asset,
+        dest_asset,
+        send_amount: decimal_from_stroops(send_stroops),
+        dest_amount: decimal_from_stroops(dest_stroops),
+        rate: decimal_from_stroops(i64::try_from(rate).unwrap_or(i64::MAX)),
+        path,
+        slippage_bps,
+        send_max: decimal_from_stroops(send_max),
+        dest_min: decimal_from_stroops(dest_min),
+        quoted_at: chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+/// Returns (send_max, dest_min). Only the floating side gets the slippage
+/// allowance; the fixed side is exact.
+fn apply_slippage(
+    mode: PathPaymentMode,
+    send_stroops: i64,
+    dest_stroops: i64,
+    slippage_bps: u32,
+) -> StellarResult<(i64, i64)> {
+    if slippage_bps >= 10_000 {
+        return Err(StellarError::transaction_failed(
+            "slippage must be below 10000 bps",
+        ));
+    }
+    let bps = slippage_bps as i128;
+    match mode {
+        PathPaymentMode::StrictSend => {
+            let dest_min = (dest_stroops as i128 * (10_000 - bps)) / 10_000;
+            Ok((send_stroops, dest_min.max(1) as i64))
+        }
+        PathPaymentMode::StrictReceive => {
+            let send_max = (send_stroops as i128 * (10_000 + bps) + 9_999) / 10_000;
+            let send_max = i64::try_from(send_max)
+                .map_err(|_| StellarError::transaction_failed("amount overflow"))?;
+            Ok((send_max, dest_stroops))
+        }
+    }
+}
+
+fn build_path_payment_transaction(
+    source: &str,
+    destination: &str,
+    quote: &PathPaymentQuote,
+    sequence: i64,
+    fee_stroops: u32,
+    timeout: Duration,
+    memo: &CngnMemo,
+) -> StellarResult<(Transaction, TransactionEnvelope)> {
+    let destination_account = parse_muxed_account(destination)?;
+    let send_asset = asset_to_xdr(&quote.send_asset)?;
+    let dest_asset = asset_to_xdr(&quote.dest_asset)?;
+    let path = VecM::try_from(
+        quote
+            .path
+            .iter()
+            .map(asset_to_xdr)
+            .collect::<StellarResult<Vec<_>>>()?,
+    )
+    .map_err(|e| StellarError::serialization_error(e.to_string()))?;
+
+    let body = match quote.mode {
+        PathPaymentMode::StrictSend => OperationBody::PathPaymentStrictSend(PathPaymentStrictSendOp {
+            send_asset,
+            send_amount: decimal_to_stroops(&quote.send_amount)?,
+            destination: destination_account,
+            dest_asset,
+            dest_min: decimal_to_stroops(&quote.dest_min)?,
+            path,
+        }),
+        PathPaymentMode::StrictReceive => {
+            OperationBody::PathPaymentStrictReceive(PathPaymentStrictReceiveOp {
+                send_asset,
+                send_max: decimal_to_stroops(&quote.send_max)?,
+                destination: destination_account,
+                dest_asset,
+                dest_amount: decimal_to_stroops(&quote.dest_amount)?,
+                path,
+            })
+        }
+    };
+
+    assemble_transaction(
+        source,
+        vec![Operation {
+            source_account: None,
+            body,
+        }],
+        sequence,
+        fee_stroops,
+        timeout,
+        memo,
+    )
+}
+
 fn build_unsigned_transaction(
     source: &str,
     destination: &str,
@@ -287,7 +862,6 @@ fn build_unsigned_transaction(
     asset_code: &str,
     issuer: &str,
 ) -> StellarResult<(Transaction, TransactionEnvelope)> {
-    let source_account = parse_muxed_account(source)?;
     let destination_account = parse_muxed_account(destination)?;
     let asset = build_asset(asset_code, issuer)?;
 
@@ -300,9 +874,168 @@ fn build_unsigned_transaction(
         }),
     };
 
+    assemble_transaction(source, vec![op], sequence, fee_stroops, timeout, memo)
+}
+
+/// Lock `amount_stroops` in a claimable balance for `destination` until
+/// `reclaim_after` (unix seconds); from then on only `source` can claim it.
+#[allow(clippy::too_many_arguments)]
+fn build_claimable_balance_transaction(
+    source: &str,
+    destination: &str,
+    amount_stroops: i64,
+    sequence: i64,
+    fee_stroops: u32,
+    timeout: Duration,
+    memo: &CngnMemo,
+    asset: Asset,
+    reclaim_after: u64,
+) -> StellarResult<(Transaction, TransactionEnvelope)> {
+    let deadline = ClaimPredicate::BeforeAbsoluteTime(reclaim_after as i64);
+    let claimants = vec![
+        Claimant::ClaimantTypeV0(ClaimantV0 {
+            destination: account_id_of(destination)?,
+            predicate: deadline.clone(),
+        }),
+        Claimant::ClaimantTypeV0(ClaimantV0 {
+            destination: account_id_of(source)?,
+            predicate: ClaimPredicate::Not(Some(Box::new(deadline))),
+        }),
+    ];
+
+    let op = Operation {
+        source_account: None,
+        body: OperationBody::CreateClaimableBalance(CreateClaimableBalanceOp {
+            asset,
+            amount: amount_stroops,
+            claimants: VecM::try_from(claimants)
+                .map_err(|e| StellarError::serialization_error(e.to_string()))?,
+        }),
+    };
+
+    assemble_transaction(source, vec![op], sequence, fee_stroops, timeout, memo)
+}
+
+/// Check a decoded envelope pays `expected` from its source in a single
+/// payment or claimable balance operation
+fn verify_payment_envelope(tx: &Transaction, expected: &ExpectedCngnPayment) -> StellarResult<()> {
+    let reject = |reason: &str| {
+        Err(StellarError::signing_error(format!(
+            "envelope does not match the payment: {}",
+            reason
+        )))
+    };
+
+    if tx.source_account != parse_muxed_account(&expected.source)? {
+        return reject("transaction source differs");
+    }
+    let [op] = tx.operations.as_slice() else {
+        return reject("expected exactly 1 operation");
+    };
+    if op.source_account.is_some() {
+        return reject("operation source must not be overridden");
+    }
+    let asset = build_asset(&expected.asset_code, &expected.asset_issuer)?;
+    let amount = decimal_to_stroops(&expected.amount)?;
+    match &op.body {
+        OperationBody::Payment(payment) => {
+            if payment.destination != parse_muxed_account(&expected.destination)? {
+                return reject("destination differs");
+            }
+            if payment.asset != asset {
+                return reject("asset differs");
+            }
+            if payment.amount != amount {
+                return reject("amount differs");
+            }
+        }
+        OperationBody::CreateClaimableBalance(balance) => {
+            let destination = account_id_of(&expected.destination)?;
+            let source = account_id_of(&expected.source)?;
+            let claimants_ok = balance.claimants.iter().all(|claimant| {
+                let Claimant::ClaimantTypeV0(claimant) = claimant;
+                claimant.destination == destination || claimant.destination == source
+            }) && balance.claimants.iter().any(|claimant| {
+                let Claimant::ClaimantTypeV0(claimant) = claimant;
+                claimant.destination == destination
+            });
+            if !claimants_ok {
+                return reject("claimable balance is not for the destination");
+            }
+            if balance.asset != asset {
+                return reject("asset differs");
+            }
+            if balance.amount != amount {
+                return reject("amount differs");
+            }
+        }
+        _ => return reject("operation is not a payment"),
+    }
+
+    Ok(())
+}
+
+fn change_trust_operation(asset: Asset) -> StellarResult<Operation> {
+    let line = match asset {
+        Asset::CreditAlphanum4(a) => ChangeTrustAsset::CreditAlphanum4(a),
+        Asset::CreditAlphanum12(a) => ChangeTrustAsset::CreditAlphanum12(a),
+        _ => {
+            return Err(StellarError::config_error(
+                "trustlines can only be created for credit assets",
+            ))
+        }
+    };
+    Ok(Operation {
+        source_account: None,
+        body: OperationBody::ChangeTrust(ChangeTrustOp {
+            line,
+            limit: i64::MAX,
+        }),
+    })
+}
+
+/// Claimable balance id created by operation `op_index` of the transaction
+/// with `sequence` from `source`, in Horizon's hex form.
+pub(crate) fn claimable_balance_id(
+    source: &str,
+    sequence: i64,
+    op_index: u32,
+) -> StellarResult<String> {
+    let preimage = HashIdPreimage::OpId(HashIdPreimageOperationId {
+        source_account: account_id_of(source)?,
+        seq_num: SequenceNumber(sequence),
+        op_num: op_index,
+    });
+    let bytes = preimage
+        .to_xdr(Limits::none())
+        .map_err(|e| StellarError::serialization_error(e.to_string()))?;
+    let hash: [u8; 32] = Sha256::digest(bytes).into();
+    ClaimableBalanceId::ClaimableBalanceIdTypeV0(Hash(hash))
+        .to_xdr(Limits::none())
+        .map(hex::encode)
+        .map_err(|e| StellarError::serialization_error(e.to_string()))
+}
+
+fn parse_claimable_balance_id(balance_id: &str) -> StellarResult<ClaimableBalanceId> {
+    use stellar_xdr::next::ReadXdr;
+    let bytes = hex::decode(balance_id.trim())
+        .map_err(|_| StellarError::transaction_failed("claimable balance id must be hex"))?;
+    ClaimableBalanceId::from_xdr(bytes, Limits::none())
+        .map_err(|_| StellarError::transaction_failed("invalid claimable balance id"))
+}
+
+fn assemble_transaction(
+    source: &str,
+    operations: Vec<Operation>,
+    sequence: i64,
+    fee_stroops: u32,
+    timeout: Duration,
+    memo: &CngnMemo,
+) -> StellarResult<(Transaction, TransactionEnvelope)> {
+    let source_account = parse_muxed_account(source)?;
     let now = unix_time();
     let tx = Transaction {
-        source_account: source_account,
+        source_account,
         fee: fee_stroops,
         seq_num: SequenceNumber(sequence),
         cond: Preconditions::Time(TimeBounds {
@@ -310,7 +1043,7 @@ fn build_unsigned_transaction(
             max_time: TimePoint(now + timeout.as_secs()),
         }),
         memo: memo_to_xdr(memo)?,
-        operations: VecM::try_from(vec![op])
+        operations: VecM::try_from(operations)
             .map_err(|e| StellarError::serialization_error(e.to_string()))?,
         ext: TransactionExt::V0,
     };
@@ -338,6 +1071,16 @@ fn parse_muxed_account(address: &str) -> StellarResult<MuxedAccount> {
     }
 }
 
+/// Account id of a G... or M... address (muxed ids are dropped)
+fn account_id_of(address: &str) -> StellarResult<AccountId> {
+    match parse_muxed_account(address)? {
+        MuxedAccount::Ed25519(key) => Ok(AccountId(PublicKey::PublicKeyTypeEd25519(key))),
+        MuxedAccount::MuxedEd25519(muxed) => {
+            Ok(AccountId(PublicKey::PublicKeyTypeEd25519(muxed.ed25519)))
+        }
+    }
+}
+
 fn parse_account_id(address: &str) -> StellarResult<AccountId> {
     let public_key = StrkeyPublicKey::from_string(address)
         .map_err(|_| StellarError::invalid_address(address))?;
@@ -373,6 +1116,13 @@ fn build_asset(asset_code: &str, issuer: &str) -> StellarResult<Asset> {
     }
 }
 
+fn asset_to_xdr(asset: &StellarAsset) -> StellarResult<Asset> {
+    match asset {
+        StellarAsset::Native => Ok(Asset::Native),
+        StellarAsset::Credit { code, issuer } => build_asset(code, issuer),
+    }
+}
+
 fn memo_to_xdr(memo: &CngnMemo) -> StellarResult<Memo> {
     match memo {
         CngnMemo::None => Ok(Memo::None),
@@ -449,40 +1199,7 @@ fn decimal_from_stroops(stroops: i64) -> String {
     format!("{whole}.{frac:07}")
 }
 
-fn decode_signing_key(secret_seed: &str) -> StellarResult<SigningKey> {
-    let private = StrkeyPrivateKey::from_string(secret_seed)
-        .map_err(|_| StellarError::signing_error("invalid secret seed"))?;
-    Ok(SigningKey::from_bytes(&private.0))
-}
-
-fn ensure_signing_key_matches_source(signing_key: &SigningKey, source: &str) -> StellarResult<()> {
-    let public_key_bytes = signing_key.verifying_key().to_bytes();
-    let expected = if source.starts_with('M') {
-        StrkeyMuxedAccount::from_string(source)
-            .map(|m| m.ed25519)
-            .map_err(|_| StellarError::invalid_address(source))?
-    } else {
-        StrkeyPublicKey::from_string(source)
-            .map(|p| p.0)
-            .map_err(|_| StellarError::invalid_address(source))?
-    };
-
-    if public_key_bytes == expected {
-        Ok(())
-    } else {
-        Err(StellarError::signing_error(
-            "secret seed does not match source account",
-        ))
-    }
-}
-
-fn signature_hint(signing_key: &SigningKey) -> StellarResult<SignatureHint> {
-    let bytes = signing_key.verifying_key().to_bytes();
-    SignatureHint::try_from(&bytes[bytes.len() - 4..])
-        .map_err(|e| StellarError::serialization_error(e.to_string()))
-}
-
-fn network_id(passphrase: &str) -> [u8; 32] {
+pub(crate) fn network_id(passphrase: &str) -> [u8; 32] {
     Sha256::digest(passphrase.as_bytes()).into()
 }
 
@@ -527,4 +1244,134 @@ mod tests {
         assert!(decimal_to_stroops("1.12345678").is_err());
         assert!(decimal_to_stroops("abc").is_err());
     }
+
+    #[test]
+    fn test_apply_slippage_bounds_floating_side() {
+        let (send_max, dest_min) =
+            apply_slippage(PathPaymentMode::StrictSend, 100_000_000, 50_000_000, 100).unwrap();
+        assert_eq!(send_max, 100_000_000);
+        assert_eq!(dest_min, 49_500_000);
+
+        let (send_max, dest_min) =
+            apply_slippage(PathPaymentMode::StrictReceive, 100_000_001, 50_000_000, 100).unwrap();
+        assert_eq!(send_max, 101_000_002);
+        assert_eq!(dest_min, 50_000_000);
+
+        assert!(apply_slippage(PathPaymentMode::StrictSend, 1, 1, 10_000).is_err());
+    }
+
+    #[test]
+    fn test_select_best_path_strict_send_prefers_most_received() {
+        let issuer = StrkeyPublicKey([7u8; 32]).to_string().as_str().to_owned();
+        let usdc = StellarAsset::credit("USDC", issuer.clone());
+        let cngn = StellarAsset::credit("cNGN", issuer.clone());
+        let record = |dest: &str, hops: usize| HorizonPaymentPath {
+            source_asset_type: "credit_alphanum4".to_string(),
+            source_asset_code: Some("USDC".to_string()),
+            source_asset_issuer: Some(issuer.clone()),
+            source_amount: "10.0000000".to_string(),
+            destination_asset_type: "credit_alphanum4".to_string(),
+            destination_asset_code: Some("cNGN".to_string()),
+            destination_asset_issuer: Some(issuer.clone()),
+            destination_amount: dest.to_string(),
+            path: (0..hops)
+                .map(|_| crate::chains::stellar::types::HorizonPathAsset {
+                    asset_type: "native".to_string(),
+                    asset_code: None,
+                    asset_issuer: None,
+                })
+                .collect(),
+        };
+
+        let records = vec![record("15000", 1), record("15200", 0), record("16000", 6)];
+        let (send, dest, path) =
+            select_best_path(PathPaymentMode::StrictSend, &records, &usdc, &cngn).unwrap();
+        assert_eq!(send, 100_000_000);
+        assert_eq!(dest, 152_000_000_000);
+        assert!(path.is_empty());
+    }
+
+    #[test]
+    fn test_claimable_balance_id_roundtrips() {
+        let source = StrkeyPublicKey([3u8; 32]).to_string().as_str().to_owned();
+        let id = claimable_balance_id(&source, 42, 0).unwrap();
+        assert_eq!(id.len(), 72);
+        assert!(id.starts_with("00000000"));
+        assert_ne!(id, claimable_balance_id(&source, 43, 0).unwrap());
+        assert!(parse_claimable_balance_id(&id).is_ok());
+        assert!(parse_claimable_balance_id("zz").is_err());
+    }
+
+    #[test]
+    fn test_verify_payment_envelope() {
+        let account = |seed: u8| StrkeyPublicKey([seed; 32]).to_string().as_str().to_owned();
+        let (source, destination, issuer) = (account(1), account(2), account(3));
+        const AMOUNT: i64 = 125_000_000;
+        let expected = ExpectedCngnPayment {
+            source: source.clone(),
+            destination: destination.clone(),
+            asset_code: "cNGN".to_string(),
+            asset_issuer: issuer.clone(),
+            amount: "12.5".to_string(),
+        };
+        let payment = |to: &str, stroops: i64, code: &str| {
+            build_unsigned_transaction(
+                &source,
+                to,
+                stroops,
+                1,
+                100,
+                Duration::from_secs(30),
+                &CngnMemo::None,
+                code,
+                &issuer,
+            )
+            .unwrap()
+            .0
+        };
+
+        let accepts = |tx: Transaction, expected: &ExpectedCngnPayment| {
+            verify_payment_envelope(&tx, expected).is_ok()
+        };
+        assert!(accepts(payment(&destination, AMOUNT, "cNGN"), &expected));
+        assert!(!accepts(payment(&account(4), AMOUNT, "cNGN"), &expected));
+        assert!(!accepts(
+            payment(&destination, AMOUNT + 1, "cNGN"),
+            &expected
+        ));
+        assert!(!accepts(payment(&destination, AMOUNT, "USDC"), &expected));
+
+        let other_source = ExpectedCngnPayment {
+            source: account(4),
+            ..expected.clone()
+        };
+        assert!(!accepts(
+            payment(&destination, AMOUNT, "cNGN"),
+            &other_source
+        ));
+
+        let mut extra = payment(&destination, AMOUNT, "cNGN");
+        let mut ops = extra.operations.to_vec();
+        ops.push(ops[0].clone());
+        extra.operations = ops.try_into().unwrap();
+        assert!(!accepts(extra, &expected));
+
+        let balance = |to: &str| {
+            build_claimable_balance_transaction(
+                &source,
+                to,
+                AMOUNT,
+                1,
+                100,
+                Duration::from_secs(30),
+                &CngnMemo::None,
+                build_asset("cNGN", &issuer).unwrap(),
+                1_000,
+            )
+            .unwrap()
+            .0
+        };
+        assert!(accepts(balance(&destination), &expected));
+        assert!(!accepts(balance(&account(4)), &expected));
+    }
 }
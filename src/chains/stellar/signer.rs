@@ -0,0 +1,546 @@
+//! Transaction signing backends
+//!
+//! Every path that signs a Stellar transaction goes through [`TransactionSigner`]
+//! so that raw secret seeds never travel through request bodies or environment
+//! variables. Backends:
+//! - [`KeystoreSigner`](crate::chains::stellar::keystore::KeystoreSigner): an
+//!   encrypted keystore file unlocked with a password read from disk
+//! - [`RemoteSigner`]: an HTTP signing service holding the key
+//! - [`MockSigner`]: in-process key for tests and local development
+
+use crate::chains::stellar::errors::{StellarError, StellarResult};
+use crate::chains::stellar::keystore::KeystoreSigner;
+use crate::chains::stellar::multisig;
+use crate::chains::stellar::payment::network_id;
+use async_trait::async_trait;
+use ed25519_dalek::{Signer, SigningKey};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use stellar_strkey::ed25519::{
+    MuxedAccount as StrkeyMuxedAccount, PrivateKey as StrkeyPrivateKey,
+    PublicKey as StrkeyPublicKey,
+};
+use stellar_xdr::next::{
+    DecoratedSignature, Limits, ReadXdr, Signature, SignatureHint, TransactionEnvelope,
+    TransactionV1Envelope, VecM, WriteXdr,
+};
+
+const DEFAULT_REMOTE_TIMEOUT_SECONDS: u64 = 10;
+
+/// Signs Stellar transaction hashes on behalf of a single account key.
+#[async_trait]
+pub trait TransactionSigner: Send + Sync {
+    /// Stellar public key (G...) whose signatures this signer produces
+    fn public_key(&self) -> &str;
+
+    /// Sign a 32-byte transaction hash, returning the 64-byte ed25519 signature
+    async fn sign_hash(&self, tx_hash: &[u8; 32]) -> StellarResult<[u8; 64]>;
+}
+
+/// Envelope after a signer has added its signature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedEnvelope {
+    pub transaction_hash: String,
+    /// Hex-encoded signature added by this signer
+    pub signature: String,
+    pub signed_envelope_xdr: String,
+}
+
+/// Add `signer`'s signature to a v1 envelope, keeping any signatures already
+/// present so that multisig envelopes can be built up incrementally.
+pub async fn sign_envelope(
+    signer: &dyn TransactionSigner,
+    envelope_xdr: &str,
+    network_passphrase: &str,
+) -> StellarResult<SignedEnvelope> {
+    let envelope = TransactionEnvelope::from_xdr_base64(envelope_xdr, Limits::none())
+        .map_err(|e| StellarError::serialization_error(e.to_string()))?;
+    let (tx, existing) = match envelope {
+        TransactionEnvelope::Tx(v1) => (v1.tx, v1.signatures),
+        _ => {
+            return Err(StellarError::signing_error(
+                "only v1 transaction envelopes can be signed",
+            ))
+        }
+    };
+
+    let hash = tx
+        .hash(network_id(network_passphrase))
+        .map_err(|e| StellarError::serialization_error(e.to_string()))?;
+    let signature = signer.sign_hash(&hash).await?;
+    let signature_hex = hex::encode(signature);
+    multisig::verify_signature(&hash, signer.public_key(), &signature_hex)?;
+
+    let mut signatures: Vec<DecoratedSignature> = existing.to_vec();
+    if signatures.len() >= multisig::MAX_ENVELOPE_SIGNATURES {
+        return Err(StellarError::signing_error(format!(
+            "envelope cannot carry more than {} signatures",
+            multisig::MAX_ENVELOPE_SIGNATURES
+        )));
+    }
+    signatures.push(DecoratedSignature {
+        hint: signature_hint(signer.public_key())?,
+        signature: Signature::try_from(signature.to_vec())
+            .map_err(|e| StellarError::serialization_error(e.to_string()))?,
+    });
+
+    let signed_envelope_xdr = TransactionEnvelope::Tx(TransactionV1Envelope {
+        tx,
+        signatures: VecM::try_from(signatures)
+            .map_err(|e| StellarError::serialization_error(e.to_string()))?,
+    })
+    .to_xdr_base64(Limits::none())
+    .map_err(|e| StellarError::serialization_error(e.to_string()))?;
+
+    Ok(SignedEnvelope {
+        transaction_hash: hex::encode(hash),
+        signature: signature_hex,
+        signed_envelope_xdr,
+    })
+}
+
+/// Reject signing when the signer's key is not the transaction source.
+/// Muxed (M...) sources are compared by their underlying ed25519 key.
+pub fn ensure_signer_matches_source(
+    signer: &dyn TransactionSigner,
+    source: &str,
+) -> StellarResult<()> {
+    let expected = if source.starts_with('M') {
+        StrkeyMuxedAccount::from_string(source)
+            .map(|m| m.ed25519)
+            .map_err(|_| StellarError::invalid_address(source))?
+    } else {
+        StrkeyPublicKey::from_string(source)
+            .map(|p| p.0)
+            .map_err(|_| StellarError::invalid_address(source))?
+    };
+    let actual = StrkeyPublicKey::from_string(signer.public_key())
+        .map_err(|_| StellarError::invalid_address(signer.public_key()))?;
+
+    if actual.0 == expected {
+        Ok(())
+    } else {
+        Err(StellarError::signing_error(format!(
+            "signer {} does not match source account {}",
+            signer.public_key(),
+            source
+        )))
+    }
+}
+
+fn signature_hint(public_key: &str) -> StellarResult<SignatureHint> {
+    let decoded = StrkeyPublicKey::from_string(public_key)
+        .map_err(|_| StellarError::invalid_address(public_key))?;
+    SignatureHint::try_from(&decoded.0[28..])
+        .map_err(|e| StellarError::serialization_error(e.to_string()))
+}
+
+pub(crate) fn public_key_of(signing_key: &SigningKey) -> String {
+    StrkeyPublicKey(signing_key.verifying_key().to_bytes())
+        .to_string()
+        .as_str()
+        .to_owned()
+}
+
+// ---------------------------------------------------------------------------
+// Mock signer
+// ---------------------------------------------------------------------------
+
+/// In-process signer for tests and local development. Never configure this
+/// against mainnet funds.
+pub struct MockSigner {
+    signing_key: SigningKey,
+    public_key: String,
+}
+
+impl MockSigner {
+    pub fn new(signing_key: SigningKey) -> Self {
+        let public_key = public_key_of(&signing_key);
+        Self {
+            signing_key,
+            public_key,
+        }
+    }
+
+    /// Deterministic signer derived from a 32-byte seed
+    pub fn from_seed_bytes(seed: [u8; 32]) -> Self {
+        Self::new(SigningKey::from_bytes(&seed))
+    }
+
+    pub fn from_secret_seed(secret_seed: &str) -> StellarResult<Self> {
+        let private_key = StrkeyPrivateKey::from_string(secret_seed.trim())
+            .map_err(|_| StellarError::signing_error("invalid Stellar secret seed"))?;
+        Ok(Self::new(SigningKey::from_bytes(&private_key.0)))
+    }
+}
+
+#[async_trait]
+impl TransactionSigner for MockSigner {
+    fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    async fn sign_hash(&self, tx_hash: &[u8; 32]) -> StellarResult<[u8; 64]> {
+        Ok(self.signing_key.sign(tx_hash).to_bytes())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Remote signer
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Serialize)]
+struct RemoteSignRequest<'a> {
+    key_id: &'a str,
+    public_key: &'a str,
+    transaction_hash: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RemoteSignResponse {
+    signature: String,
+}
+
+/// Signer backed by an HTTP signing service.
+///
+/// The service receives only the transaction hash and key identifier and
+/// returns a hex-encoded ed25519 signature, which is verified locally before
+/// use.
+pub struct RemoteSigner {
+    http: reqwest::Client,
+    url: String,
+    key_id: String,
+    public_key: String,
+    auth_token: Option<String>,
+    timeout: Duration,
+}
+
+impl RemoteSigner {
+    pub fn new(
+        url: impl Into<String>,
+        key_id: impl Into<String>,
+        public_key: impl Into<String>,
+        auth_token: Option<String>,
+        timeout: Duration,
+    ) -> StellarResult<Self> {
+        let public_key = public_key.into();
+        StrkeyPublicKey::from_string(&public_key)
+            .map_err(|_| StellarError::invalid_address(&public_key))?;
+        let http = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| StellarError::config_error(e.to_string()))?;
+
+        Ok(Self {
+            http,
+            url: url.into().trim_end_matches('/').to_string(),
+            key_id: key_id.into(),
+            public_key,
+            auth_token,
+            timeout,
+        })
+    }
+}
+
+#[async_trait]
+impl TransactionSigner for RemoteSigner {
+    fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    async fn sign_hash(&self, tx_hash: &[u8; 32]) -> StellarResult<[u8; 64]> {
+        let mut request = self
+            .http
+            .post(format!("{}/sign", self.url))
+            .json(&RemoteSignRequest {
+                key_id: &self.key_id,
+                public_key: &self.public_key,
+                transaction_hash: hex::encode(tx_hash),
+            });
+        if let Some(token) = &self.auth_token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request.send().await.map_err(|e| {
+            if e.is_timeout() {
+                StellarError::timeout_error(self.timeout.as_secs())
+            } else {
+                StellarError::network_error(format!("remote signer unreachable: {}", e))
+            }
+        })?;
+        if !response.status().is_success() {
+            return Err(StellarError::signing_error(format!(
+                "remote signer returned {}",
+                response.status()
+            )));
+        }
+
+        let body: RemoteSignResponse = response.json().await.map_err(|e| {
+            StellarError::signing_error(format!("invalid remote signer response: {}", e))
+        })?;
+        multisig::verify_signature(tx_hash, &self.public_key, &body.signature)?;
+
+        let bytes = hex::decode(&body.signature)
+            .map_err(|_| StellarError::signing_error("signature must be hex encoded"))?;
+        bytes
+            .try_into()
+            .map_err(|_| StellarError::signing_error("signature must be 64 bytes"))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Configuration
+// ---------------------------------------------------------------------------
+
+/// Which signer backend to use for a given role (e.g. the hot wallet).
+#[derive(Debug, Clone)]
+pub enum SignerConfig {
+    Keystore {
+        path: String,
+        password_file: String,
+    },
+    Remote {
+        url: String,
+        key_id: String,
+        public_key: String,
+        auth_token_file: Option<String>,
+        timeout: Duration,
+    },
+}
+
+impl SignerConfig {
+    /// Read signer settings for `prefix` (e.g. `HOT_WALLET`):
+    /// `{prefix}_SIGNER=keystore|remote`, then either
+    /// `{prefix}_KEYSTORE_PATH` + `{prefix}_KEYSTORE_PASSWORD_FILE`, or
+    /// `{prefix}_REMOTE_SIGNER_URL`, `{prefix}_REMOTE_SIGNER_KEY_ID`,
+    /// `{prefix}_PUBLIC_KEY` and optionally `{prefix}_REMOTE_SIGNER_TOKEN_FILE`.
+    ///
+    /// Only file paths are read from the environment, never key material.
+    pub fn from_env(prefix: &str) -> Option<Self> {
+        let var = |name: &str| std::env::var(format!("{}_{}", prefix, name)).ok();
+        match var("SIGNER")?.to_lowercase().as_str() {
+            "keystore" => Some(SignerConfig::Keystore {
+                path: var("KEYSTORE_PATH")?,
+                password_file: var("KEYSTORE_PASSWORD_FILE")?,
+            }),
+            "remote" => Some(SignerConfig::Remote {
+                url: var("REMOTE_SIGNER_URL")?,
+                key_id: var("REMOTE_SIGNER_KEY_ID")?,
+                public_key: var("PUBLIC_KEY")?,
+                auth_token_file: var("REMOTE_SIGNER_TOKEN_FILE"),
+                timeout: Duration::from_secs(
+                    var("REMOTE_SIGNER_TIMEOUT_SECONDS")
+                        .and_then(|v| v.parse::<u64>().ok())
+                        .unwrap_or(DEFAULT_REMOTE_TIMEOUT_SECONDS),
+                ),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Instantiate the configured backend
+    pub fn build(&self) -> StellarResult<Arc<dyn TransactionSigner>> {
+        match self {
+            SignerConfig::Keystore {
+                path,
+                password_file,
+            } => {
+                let password = read_secret_file(password_file)?;
+                Ok(Arc::new(KeystoreSigner::open(path, &password)?))
+            }
+            SignerConfig::Remote {
+                url,
+                key_id,
+                public_key,
+                auth_token_file,
+                timeout,
+            } => {
+                let auth_token = auth_token_file
+                    .as_deref()
+                    .map(read_secret_file)
+                    .transpose()?;
+                Ok(Arc::new(RemoteSigner::new(
+                    url.clone(),
+                    key_id.clone(),
+                    public_key.clone(),
+                    auth_token,
+                    *timeout,
+                )?))
+            }
+        }
+    }
+}
+
+fn read_secret_file(path: &str) -> StellarResult<String> {
+    std::fs::read_to_string(path)
+        .map(|s| s.trim_end_matches(['\r', '\n']).to_string())
+        .map_err(|e| StellarError::config_error(format!("cannot read {}: {}", path, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chains::stellar::multisig::transaction_hash;
+    use stellar_xdr::next::{
+        Memo, MuxedAccount, Preconditions, SequenceNumber, Transaction, TransactionExt, Uint256,
+    };
+
+    const PASSPHRASE: &str = "Test SDF Network ; September 2015";
+
+    fn unsigned_envelope(source: &str) -> String {
+        let key = StrkeyPublicKey::from_string(source).unwrap();
+        let tx = Transaction {
+            source_account: MuxedAccount::Ed25519(Uint256(key.0)),
+            fee: 100,
+            seq_num: SequenceNumber(1),
+            cond: Preconditions::None,
+            memo: Memo::None,
+            operations: VecM::default(),
+            ext: TransactionExt::V0,
+        };
+        TransactionEnvelope::Tx(TransactionV1Envelope {
+            tx,
+            signatures: VecM::default(),
+        })
+        .to_xdr_base64(Limits::none())
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn mock_signer_signature_verifies() {
+        let signer = MockSigner::from_seed_bytes([7u8; 32]);
+        let xdr = unsigned_envelope(signer.public_key());
+
+        let signed = sign_envelope(&signer, &xdr, PASSPHRASE).await.unwrap();
+        let hash = transaction_hash(&xdr, PASSPHRASE).unwrap();
+
+        assert_eq!(signed.transaction_hash, hex::encode(hash));
+        assert!(multisig::verify_signature(&hash, signer.public_key(), &signed.signature).is_ok());
+    }
+
+    #[tokio::test]
+    async fn signatures_accumulate_across_signers() {
+        let first = MockSigner::from_seed_bytes([1u8; 32]);
+        let second = MockSigner::from_seed_bytes([2u8; 32]);
+        let xdr = unsigned_envelope(first.public_key());
+
+        let once = sign_envelope(&first, &xdr, PASSPHRASE).await.unwrap();
+        let twice = sign_envelope(&second, &once.signed_envelope_xdr, PASSPHRASE)
+            .await
+            .unwrap();
+
+        match TransactionEnvelope::from_xdr_base64(&twice.signed_envelope_xdr, Limits::none())
+            .unwrap()
+        {
+            TransactionEnvelope::Tx(v1) => assert_eq!(v1.signatures.len(), 2),
+            _ => panic!("expected v1 envelope"),
+        }
+    }
+
+    #[test]
+    fn signer_must_match_source() {
+        let signer = MockSigner::from_seed_bytes([3u8; 32]);
+        let other = MockSigner::from_seed_bytes([4u8; 32]);
+        assert!(ensure_signer_matches_source(&signer, signer.public_key()).is_ok());
+        assert!(ensure_signer_matches_source(&signer, other.public_key()).is_err());
+    }
+
+    /// Serve one HTTP response and hand back the raw request
+    async fn spawn_remote_signer(
+        status: &'static str,
+        body: String,
+    ) -> (String, tokio::sync::oneshot::Receiver<String>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (request_tx, request_rx) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0_u8; 8192];
+            let n = socket.read(&mut buf).await.unwrap();
+            let _ = request_tx.send(String::from_utf8_lossy(&buf[..n]).to_string());
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+        (format!("http://{}", addr), request_rx)
+    }
+
+    fn remote_signer(url: &str, key: &MockSigner) -> RemoteSigner {
+        RemoteSigner::new(
+            url,
+            "hot-wallet",
+            key.public_key(),
+            Some("secret-token".to_string()),
+            Duration::from_secs(5),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn remote_signer_returns_verified_signature() {
+        let key = MockSigner::from_seed_bytes([5u8; 32]);
+        let hash = [9u8; 32];
+        let signature = key.sign_hash(&hash).await.unwrap();
+        let (url, request) = spawn_remote_signer(
+            "200 OK",
+            format!(r#"{{"signature":"{}"}}"#, hex::encode(signature)),
+        )
+        .await;
+
+        let signed = remote_signer(&url, &key).sign_hash(&hash).await.unwrap();
+        assert_eq!(signed, signature);
+
+        let request = request.await.unwrap();
+        assert!(request.starts_with("POST /sign "));
+        assert!(request.contains("Bearer secret-token"));
+        assert!(request.contains(r#""key_id":"hot-wallet""#));
+        assert!(request.contains(&hex::encode(hash)));
+    }
+
+    #[tokio::test]
+    async fn remote_signer_rejects_signature_from_other_key() {
+        let key = MockSigner::from_seed_bytes([5u8; 32]);
+        let other = MockSigner::from_seed_bytes([6u8; 32]);
+        let hash = [9u8; 32];
+        let signature = other.sign_hash(&hash).await.unwrap();
+        let (url, _request) = spawn_remote_signer(
+            "200 OK",
+            format!(r#"{{"signature":"{}"}}"#, hex::encode(signature)),
+        )
+        .await;
+
+        assert!(remote_signer(&url, &key).sign_hash(&hash).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn remote_signer_surfaces_service_errors() {
+        let key = MockSigner::from_seed_bytes([5u8; 32]);
+        let (url, _request) =
+            spawn_remote_signer("503 Service Unavailable", "{}".to_string()).await;
+
+        let err = remote_signer(&url, &key)
+            .sign_hash(&[9u8; 32])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("503"));
+    }
+
+    #[test]
+    fn remote_signer_requires_valid_public_key() {
+        assert!(RemoteSigner::new(
+            "http://localhost",
+            "hot-wallet",
+            "not-a-key",
+            None,
+            Duration::from_secs(5)
+        )
+        .is_err());
+    }
+}
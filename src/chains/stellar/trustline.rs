@@ -1,15 +1,20 @@
 use crate::chains::stellar::client::StellarClient;
 use crate::chains::stellar::config::StellarNetwork;
 use crate::chains::stellar::errors::{StellarError, StellarResult};
+use crate::chains::stellar::multisig;
+use crate::chains::stellar::signer::{
+    ensure_signer_matches_source, sign_envelope, TransactionSigner,
+};
 use crate::chains::stellar::types::{is_valid_stellar_address, AssetBalance};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use stellar_strkey::ed25519::PublicKey as StrkeyPublicKey;
 use stellar_xdr::next::{
-    AccountId, AlphaNum12, AlphaNum4, AssetCode12, AssetCode4, ChangeTrustAsset, ChangeTrustOp,
-    Limits, MuxedAccount, Operation, OperationBody, Preconditions, PublicKey, SequenceNumber,
-    Transaction, TransactionEnvelope, TransactionExt, TransactionV1Envelope, Uint256, VecM,
-    WriteXdr,
+    AccountId, AlphaNum12, AlphaNum4, AssetCode12, AssetCode4, BeginSponsoringFutureReservesOp,
+    ChangeTrustAsset, ChangeTrustOp, LedgerKey, LedgerKeyTrustLine, Limits, MuxedAccount,
+    Operation, OperationBody, Preconditions, PublicKey, RevokeSponsorshipOp, SequenceNumber,
+    Transaction, TransactionEnvelope, TransactionExt, TransactionV1Envelope, TrustLineAsset,
+    Uint256, VecM, WriteXdr,
 };
 
 const BASE_RESERVE_XLM: f64 = 0.5;
@@ -76,6 +81,10 @@ pub struct UnsignedTrustlineTransaction {
     pub transaction_hash: String,
     pub unsigned_envelope_xdr: String,
     pub limit: Option<String>,
+    /// Platform account paying the trustline reserve, for sponsored drafts.
+    /// The transaction source is then the sponsor, not `account_id`.
+    #[serde(default)]
+    pub sponsor: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -212,13 +221,216 @@ impl CngnTrustlineManager {
             }),
         };
 
+        let (transaction_hash, unsigned_envelope_xdr) =
+            self.encode_unsigned(source, fee, sequence, vec![op])?;
+
+        Ok(UnsignedTrustlineTransaction {
+            account_id: account_id.to_string(),
+            asset_code: self.asset_code().to_string(),
+            issuer: self.issuer().to_string(),
+            fee_stroops: fee,
+            sequence,
+            transaction_hash,
+            unsigned_envelope_xdr,
+            limit: selected_limit,
+            sponsor: None,
+        })
+    }
+
+    /// Build a trustline transaction whose reserve is paid by `sponsor`.
+    ///
+    /// The sponsor is the transaction source and pays the fee, so the user
+    /// needs no spare XLM. The envelope needs both the user's signature (for
+    /// the ChangeTrust and EndSponsoring operations) and the sponsor's.
+    pub async fn build_sponsored_trustline_transaction(
+        &self,
+        account_id: &str,
+        sponsor: &str,
+        limit: Option<&str>,
+        fee_stroops: Option<u32>,
+    ) -> StellarResult<UnsignedTrustlineTransaction> {
+        if !is_valid_stellar_address(account_id) {
+            return Err(StellarError::invalid_address(account_id));
+        }
+        if !is_valid_stellar_address(sponsor) {
+            return Err(StellarError::invalid_address(sponsor));
+        }
+        if account_id == sponsor {
+            return Err(StellarError::transaction_failed(
+                "an account cannot sponsor its own trustline",
+            ));
+        }
+
+        let status = self.check_trustline(account_id).await?;
+        if status.has_trustline {
+            return Err(StellarError::trustline_already_exists(
+                account_id,
+                self.asset_code(),
+            ));
+        }
+
+        let sponsor_account = self.stellar_client.get_account(sponsor).await?;
+        let available_xlm = account_xlm_balance(&sponsor_account.balances);
+        let required_xlm = required_xlm_for_trustline(sponsor_account.subentry_count);
+        if available_xlm < required_xlm {
+            return Err(StellarError::insufficient_xlm(
+                format!("{:.7}", available_xlm),
+                format!("{:.7}", required_xlm),
+            ));
+        }
+
+        // 3 operations, so the minimum fee is three times the base fee
+        let fee = fee_stroops.unwrap_or(DEFAULT_BASE_FEE_STROOPS * 3);
+        let sequence = sponsor_account.sequence + 1;
+        let selected_limit = limit
+            .map(|v| v.to_string())
+            .or_else(|| self.config.default_limit.clone());
+        let limit_i64 = match selected_limit.as_deref() {
+            Some(raw_limit) => decimal_to_int64_stroops(raw_limit)?,
+            None => i64::MAX,
+        };
+
+        let asset = build_change_trust_asset(self.asset_code(), self.issuer())?;
+        let operations = sponsored_trustline_operations(account_id, asset, limit_i64)?;
+        let (transaction_hash, unsigned_envelope_xdr) =
+            self.encode_unsigned(parse_muxed_account(sponsor)?, fee, sequence, operations)?;
+
+        Ok(UnsignedTrustlineTransaction {
+            account_id: account_id.to_string(),
+            asset_code: self.asset_code().to_string(),
+            issuer: self.issuer().to_string(),
+            fee_stroops: fee,
+            sequence,
+            transaction_hash,
+            unsigned_envelope_xdr,
+            limit: selected_limit,
+            sponsor: Some(sponsor.to_string()),
+        })
+    }
+
+    /// Build the transaction that stops `sponsor` sponsoring the cNGN
+    /// trustline of `account_id`. The reserve moves to the user, so Horizon
+    /// rejects it with `op_low_reserve` unless the user holds enough XLM; a
+    /// user without XLM has to remove the trustline instead, which releases
+    /// the sponsor's reserve as well.
+    pub async fn build_revoke_trustline_sponsorship(
+        &self,
+        account_id: &str,
+        sponsor: &str,
+        fee_stroops: Option<u32>,
+    ) -> StellarResult<UnsignedTrustlineTransaction> {
+        if !is_valid_stellar_address(account_id) {
+            return Err(StellarError::invalid_address(account_id));
+        }
+
+        let sponsor_account = self.stellar_client.get_account(sponsor).await?;
+        let fee = fee_stroops.unwrap_or(DEFAULT_BASE_FEE_STROOPS);
+        let sequence = sponsor_account.sequence + 1;
+        let asset = build_change_trust_asset(self.asset_code(), self.issuer())?;
+        let op = Operation {
+            source_account: None,
+            body: OperationBody::RevokeSponsorship(RevokeSponsorshipOp::LedgerEntry(
+                LedgerKey::Trustline(LedgerKeyTrustLine {
+                    account_id: parse_account_id(account_id)?,
+                    asset: trustline_asset(asset)?,
+                }),
+            )),
+        };
+        let (transaction_hash, unsigned_envelope_xdr) =
+            self.encode_unsigned(parse_muxed_account(sponsor)?, fee, sequence, vec![op])?;
+
+        Ok(UnsignedTrustlineTransaction {
+            account_id: account_id.to_string(),
+            asset_code: self.asset_code().to_string(),
+            issuer: self.issuer().to_string(),
+            fee_stroops: fee,
+            sequence,
+            transaction_hash,
+            unsigned_envelope_xdr,
+            limit: None,
+            sponsor: Some(sponsor.to_string()),
+        })
+    }
+
+    /// Add the sponsor's signature to a sponsored trustline envelope the user
+    /// has already signed. The envelope must be exactly the one produced by
+    /// [`Self::build_sponsored_trustline_transaction`] for `account_id`, whose
+    /// hash is `expected_hash`, so the user cannot change its fee, sequence,
+    /// time bounds or memo and the sponsor key authorises nothing else.
+    pub async fn cosign_sponsored_trustline(
+        &self,
+        envelope_xdr: &str,
+        expected_hash: &str,
+        account_id: &str,
+        sponsor_signer: &dyn TransactionSigner,
+    ) -> StellarResult<String> {
+        let network_passphrase = self.stellar_client.network().network_passphrase();
+        let hash = hex::encode(multisig::transaction_hash(
+            envelope_xdr,
+            network_passphrase,
+        )?);
+        if !hash.eq_ignore_ascii_case(expected_hash) {
+            return Err(StellarError::signing_error(
+                "envelope does not match the sponsored trustline draft",
+            ));
+        }
+        let tx = multisig::envelope_transaction(envelope_xdr)?;
+        let asset = build_change_trust_asset(self.asset_code(), self.issuer())?;
+        validate_sponsored_trustline(&tx, account_id, sponsor_signer.public_key(), &asset)?;
+
+        let signed = sign_envelope(sponsor_signer, envelope_xdr, network_passphrase).await?;
+        Ok(signed.signed_envelope_xdr)
+    }
+
+    /// Sign a trustline envelope the server built for `account_id`, an
+    /// account the platform controls. The envelope must be sourced from that
+    /// account, which must be the signer's, and hold nothing but its cNGN
+    /// ChangeTrust.
+    pub async fn sign_trustline_transaction(
+        &self,
+        unsigned_envelope_xdr: &str,
+        account_id: &str,
+        signer: &dyn TransactionSigner,
+    ) -> StellarResult<String> {
+        ensure_signer_matches_source(signer, account_id)?;
+        let tx = multisig::envelope_transaction(unsigned_envelope_xdr)?;
+        let asset = build_change_trust_asset(self.asset_code(), self.issuer())?;
+        validate_platform_trustline(&tx, account_id, &asset)?;
+
+        let signed = sign_envelope(
+            signer,
+            unsigned_envelope_xdr,
+            self.stellar_client.network().network_passphrase(),
+        )
+        .await?;
+        Ok(signed.signed_envelope_xdr)
+    }
+
+    pub async fn submit_signed_trustline_xdr(
+        &self,
+        signed_envelope_xdr: &str,
+    ) -> StellarResult<serde_json::Value> {
+        validate_signed_envelope_has_signatures(signed_envelope_xdr)?;
+        self.stellar_client
+            .submit_transaction_xdr(signed_envelope_xdr)
+            .await
+    }
+
+    /// Hash and encode an unsigned v1 envelope, returning `(hash_hex, xdr)`
+    fn encode_unsigned(
+        &self,
+        source: MuxedAccount,
+        fee: u32,
+        sequence: i64,
+        operations: Vec<Operation>,
+    ) -> StellarResult<(String, String)> {
         let tx = Transaction {
             source_account: source,
             fee,
             seq_num: SequenceNumber(sequence),
             cond: Preconditions::None,
             memo: stellar_xdr::next::Memo::None,
-            operations: VecM::try_from(vec![op])
+            operations: VecM::try_from(operations)
                 .map_err(|e| StellarError::serialization_error(e.to_string()))?,
             ext: TransactionExt::V0,
         };
@@ -242,27 +454,108 @@ impl CngnTrustlineManager {
             .to_xdr_base64(Limits::none())
             .map_err(|e| StellarError::serialization_error(e.to_string()))?;
 
-        Ok(UnsignedTrustlineTransaction {
-            account_id: account_id.to_string(),
-            asset_code: self.asset_code().to_string(),
-            issuer: self.issuer().to_string(),
-            fee_stroops: fee,
-            sequence,
-            transaction_hash: hex::encode(hash),
-            unsigned_envelope_xdr: xdr,
-            limit: selected_limit,
-        })
+        Ok((hex::encode(hash), xdr))
     }
+}
 
-    pub async fn submit_signed_trustline_xdr(
-        &self,
-        signed_envelope_xdr: &str,
-    ) -> StellarResult<serde_json::Value> {
-        validate_signed_envelope_has_signatures(signed_envelope_xdr)?;
-        self.stellar_client
-            .submit_transaction_xdr(signed_envelope_xdr)
-            .await
+/// BeginSponsoring (sponsor, as tx source) / ChangeTrust (user) /
+/// EndSponsoring (user)
+fn sponsored_trustline_operations(
+    account_id: &str,
+    asset: ChangeTrustAsset,
+    limit: i64,
+) -> StellarResult<Vec<Operation>> {
+    let user = parse_muxed_account(account_id)?;
+    Ok(vec![
+        Operation {
+            source_account: None,
+            body: OperationBody::BeginSponsoringFutureReserves(BeginSponsoringFutureReservesOp {
+                sponsored_id: parse_account_id(account_id)?,
+            }),
+        },
+        Operation {
+            source_account: Some(user.clone()),
+            body: OperationBody::ChangeTrust(ChangeTrustOp { line: asset, limit }),
+        },
+        Operation {
+            source_account: Some(user),
+            body: OperationBody::EndSponsoringFutureReserves,
+        },
+    ])
+}
+
+fn validate_sponsored_trustline(
+    tx: &Transaction,
+    account_id: &str,
+    sponsor: &str,
+    asset: &ChangeTrustAsset,
+) -> StellarResult<()> {
+    let sponsor_account = parse_muxed_account(sponsor)?;
+    let user = parse_muxed_account(account_id)?;
+    let user_id = parse_account_id(account_id)?;
+    let reject = |reason: &str| {
+        Err(StellarError::signing_error(format!(
+            "not a sponsored trustline envelope: {}",
+            reason
+        )))
+    };
+
+    if tx.source_account != sponsor_account {
+        return reject("transaction source is not the sponsor");
+    }
+    let [begin, change, end] = tx.operations.as_slice() else {
+        return reject("expected exactly 3 operations");
+    };
+    match &begin.body {
+        OperationBody::BeginSponsoringFutureReserves(op)
+            if op.sponsored_id == user_id
+                && begin
+                    .source_account
+                    .as_ref()
+                    .is_none_or(|s| *s == sponsor_account) => {}
+        _ => return reject("first operation must begin sponsoring the user"),
+    }
+    match &change.body {
+        OperationBody::ChangeTrust(op)
+            if op.line == *asset && change.source_account.as_ref() == Some(&user) => {}
+        _ => return reject("second operation must be the user's cNGN ChangeTrust"),
+    }
+    match &end.body {
+        OperationBody::EndSponsoringFutureReserves
+            if end.source_account.as_ref() == Some(&user) => {}
+        _ => return reject("third operation must end sponsoring from the user"),
+    }
+
+    Ok(())
+}
+
+fn validate_platform_trustline(
+    tx: &Transaction,
+    account_id: &str,
+    asset: &ChangeTrustAsset,
+) -> StellarResult<()> {
+    let account = parse_muxed_account(account_id)?;
+    let reject = |reason: &str| {
+        Err(StellarError::signing_error(format!(
+            "not a platform trustline envelope: {}",
+            reason
+        )))
+    };
+
+    if tx.source_account != account {
+        return reject("transaction source is not the account");
+    }
+    let [change] = tx.operations.as_slice() else {
+        return reject("expected exactly 1 operation");
+    };
+    match &change.body {
+        OperationBody::ChangeTrust(op)
+            if op.line == *asset
+                && change.source_account.as_ref().is_none_or(|s| *s == account) => {}
+        _ => return reject("operation must be the account's cNGN ChangeTrust"),
     }
+
+    Ok(())
 }
 
 fn validate_signed_envelope_has_signatures(xdr: &str) -> StellarResult<()> {
@@ -359,6 +652,16 @@ fn build_change_trust_asset(asset_code: &str, issuer: &str) -> StellarResult<Cha
     }
 }
 
+fn trustline_asset(asset: ChangeTrustAsset) -> StellarResult<TrustLineAsset> {
+    match asset {
+        ChangeTrustAsset::CreditAlphanum4(a) => Ok(TrustLineAsset::CreditAlphanum4(a)),
+        ChangeTrustAsset::CreditAlphanum12(a) => Ok(TrustLineAsset::CreditAlphanum12(a)),
+        _ => Err(StellarError::config_error(
+            "only credit assets have trustlines",
+        )),
+    }
+}
+
 fn decimal_to_int64_stroops(amount: &str) -> StellarResult<i64> {
     let value = amount.trim();
     if value.is_empty() {
@@ -426,4 +729,143 @@ mod tests {
         assert_eq!(decimal_to_int64_stroops("1.5").unwrap(), 15_000_000);
         assert!(decimal_to_int64_stroops("1.12345678").is_err());
     }
+
+    #[test]
+    fn test_validate_sponsored_trustline() {
+        let user = StrkeyPublicKey([1u8; 32]).to_string().as_str().to_owned();
+        let sponsor = StrkeyPublicKey([2u8; 32]).to_string().as_str().to_owned();
+        let issuer = StrkeyPublicKey([3u8; 32]).to_string().as_str().to_owned();
+        let asset = build_change_trust_asset("cNGN", &issuer).unwrap();
+        let tx = |source: &str, operations: Vec<Operation>| Transaction {
+            source_account: parse_muxed_account(source).unwrap(),
+            fee: 300,
+            seq_num: SequenceNumber(1),
+            cond: Preconditions::None,
+            memo: stellar_xdr::next::Memo::None,
+            operations: VecM::try_from(operations).unwrap(),
+            ext: TransactionExt::V0,
+        };
+
+        let ops = sponsored_trustline_operations(&user, asset.clone(), i64::MAX).unwrap();
+        assert!(
+            validate_sponsored_trustline(&tx(&sponsor, ops.clone()), &user, &sponsor, &asset)
+                .is_ok()
+        );
+
+        // Sponsor key must not sign a transaction sourced from another account
+        assert!(
+            validate_sponsored_trustline(&tx(&user, ops.clone()), &user, &sponsor, &asset).is_err()
+        );
+
+        // Sponsoring a different user than the one asked for is rejected
+        let other = StrkeyPublicKey([4u8; 32]).to_string().as_str().to_owned();
+        assert!(
+            validate_sponsored_trustline(&tx(&sponsor, ops.clone()), &other, &sponsor, &asset)
+                .is_err()
+        );
+
+        // Extra operations cannot be smuggled in
+        let mut extra = ops;
+        extra.push(extra[1].clone());
+        assert!(
+            validate_sponsored_trustline(&tx(&sponsor, extra), &user, &sponsor, &asset).is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cosign_only_the_sponsored_draft() {
+        use crate::chains::stellar::config::StellarConfig;
+        use crate::chains::stellar::signer::MockSigner;
+        use stellar_xdr::next::{TransactionV1Envelope, WriteXdr};
+
+        let client = StellarClient::new(StellarConfig::default()).unwrap();
+        let passphrase = client.network().network_passphrase().to_string();
+        let issuer = StrkeyPublicKey([3u8; 32]).to_string().as_str().to_owned();
+        let manager = CngnTrustlineManager::with_config(
+            client,
+            CngnAssetConfig {
+                asset_code: "cNGN".to_string(),
+                issuer_testnet: issuer.clone(),
+                issuer_mainnet: issuer,
+                default_limit: None,
+            },
+        );
+        let sponsor = MockSigner::from_seed_bytes([2u8; 32]);
+        let user = StrkeyPublicKey([1u8; 32]).to_string().as_str().to_owned();
+        let asset = build_change_trust_asset(manager.asset_code(), manager.issuer()).unwrap();
+        let envelope = |fee: u32| {
+            TransactionEnvelope::Tx(TransactionV1Envelope {
+                tx: Transaction {
+                    source_account: parse_muxed_account(sponsor.public_key()).unwrap(),
+                    fee,
+                    seq_num: SequenceNumber(1),
+                    cond: Preconditions::None,
+                    memo: stellar_xdr::next::Memo::None,
+                    operations: VecM::try_from(
+                        sponsored_trustline_operations(&user, asset.clone(), i64::MAX).unwrap(),
+                    )
+                    .unwrap(),
+                    ext: TransactionExt::V0,
+                },
+                signatures: VecM::default(),
+            })
+            .to_xdr_base64(Limits::none())
+            .unwrap()
+        };
+        let draft = envelope(300);
+        let draft_hash = hex::encode(multisig::transaction_hash(&draft, &passphrase).unwrap());
+
+        assert!(manager
+            .cosign_sponsored_trustline(&draft, &draft_hash, &user, &sponsor)
+            .await
+            .is_ok());
+
+        // Same operations with the fee raised on the sponsor's account
+        assert!(manager
+            .cosign_sponsored_trustline(&envelope(1_000_000), &draft_hash, &user, &sponsor)
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_validate_platform_trustline() {
+        let account = StrkeyPublicKey([1u8; 32]).to_string().as_str().to_owned();
+        let issuer = StrkeyPublicKey([3u8; 32]).to_string().as_str().to_owned();
+        let asset = build_change_trust_asset("cNGN", &issuer).unwrap();
+        let tx = |source: &str, operations: Vec<Operation>| Transaction {
+            source_account: parse_muxed_account(source).unwrap(),
+            fee: 100,
+            seq_num: SequenceNumber(1),
+            cond: Preconditions::None,
+            memo: stellar_xdr::next::Memo::None,
+            operations: VecM::try_from(operations).unwrap(),
+            ext: TransactionExt::V0,
+        };
+        let change_trust = |line: ChangeTrustAsset| Operation {
+            source_account: None,
+            body: OperationBody::ChangeTrust(ChangeTrustOp {
+                line,
+                limit: i64::MAX,
+            }),
+        };
+
+        let ops = vec![change_trust(asset.clone())];
+        assert!(validate_platform_trustline(&tx(&account, ops.clone()), &account, &asset).is_ok());
+
+        // Another account's envelope is not signed for it
+        let other = StrkeyPublicKey([4u8; 32]).to_string().as_str().to_owned();
+        assert!(validate_platform_trustline(&tx(&other, ops.clone()), &account, &asset).is_err());
+
+        // Only the cNGN trustline, and nothing alongside it
+        let other_asset = build_change_trust_asset("USDC", &issuer).unwrap();
+        assert!(validate_platform_trustline(
+            &tx(&account, vec![change_trust(other_asset)]),
+            &account,
+            &asset
+        )
+        .is_err());
+        let mut extra = ops;
+        extra.push(extra[0].clone());
+        assert!(validate_platform_trustline(&tx(&account, extra), &account, &asset).is_err());
+    }
 }
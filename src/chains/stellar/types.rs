@@ -93,6 +93,175 @@ pub struct HealthStatus {
     pub error_message: Option<String>,
 }
 
+/// Asset identifier in the canonical Horizon form: `native` or `CODE:ISSUER`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum StellarAsset {
+    Native,
+    Credit { code: String, issuer: String },
+}
+
+impl StellarAsset {
+    pub fn credit(code: impl Into<String>, issuer: impl Into<String>) -> Self {
+        Self::Credit {
+            code: code.into(),
+            issuer: issuer.into(),
+        }
+    }
+
+    /// Horizon `asset_type` value for this asset
+    pub fn asset_type(&self) -> &'static str {
+        match self {
+            Self::Native => "native",
+            Self::Credit { code, .. } if code.len() <= 4 => "credit_alphanum4",
+            Self::Credit { .. } => "credit_alphanum12",
+        }
+    }
+
+    pub fn code(&self) -> &str {
+        match self {
+            Self::Native => "XLM",
+            Self::Credit { code, .. } => code,
+        }
+    }
+
+    pub fn issuer(&self) -> Option<&str> {
+        match self {
+            Self::Native => None,
+            Self::Credit { issuer, .. } => Some(issuer),
+        }
+    }
+
+    /// Build from the `asset_type` / `asset_code` / `asset_issuer` triple
+    /// Horizon uses in balances, operations and path records.
+    pub fn from_horizon(
+        asset_type: &str,
+        asset_code: Option<&str>,
+        asset_issuer: Option<&str>,
+    ) -> Option<Self> {
+        match (asset_type, asset_code, asset_issuer) {
+            ("native", _, _) => Some(Self::Native),
+            (_, Some(code), Some(issuer)) => Some(Self::credit(code, issuer)),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for StellarAsset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Native => write!(f, "native"),
+            Self::Credit { code, issuer } => write!(f, "{}:{}", code, issuer),
+        }
+    }
+}
+
+impl std::str::FromStr for StellarAsset {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if value.eq_ignore_ascii_case("native") || value.eq_ignore_ascii_case("xlm") {
+            return Ok(Self::Native);
+        }
+        let (code, issuer) = value
+            .split_once(':')
+            .ok_or_else(|| format!("asset must be 'native' or CODE:ISSUER, got '{}'", value))?;
+        if code.is_empty() || code.len() > 12 || !code.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(format!("invalid asset code '{}'", code));
+        }
+        if !is_valid_stellar_address(issuer) {
+            return Err(format!("invalid asset issuer '{}'", issuer));
+        }
+        Ok(Self::credit(code, issuer))
+    }
+}
+
+impl TryFrom<String> for StellarAsset {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<StellarAsset> for String {
+    fn from(asset: StellarAsset) -> Self {
+        asset.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HorizonPathAsset {
+    pub asset_type: String,
+    pub asset_code: Option<String>,
+    pub asset_issuer: Option<String>,
+}
+
+/// A record from Horizon's `/paths/strict-send` or `/paths/strict-receive`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HorizonPaymentPath {
+    pub source_asset_type: String,
+    pub source_asset_code: Option<String>,
+    pub source_asset_issuer: Option<String>,
+    pub source_amount: String,
+    pub destination_asset_type: String,
+    pub destination_asset_code: Option<String>,
+    pub destination_asset_issuer: Option<String>,
+    pub destination_amount: String,
+    #[serde(default)]
+    pub path: Vec<HorizonPathAsset>,
+}
+
+impl HorizonPaymentPath {
+    pub fn source_asset(&self) -> Option<StellarAsset> {
+        StellarAsset::from_horizon(
+            &self.source_asset_type,
+            self.source_asset_code.as_deref(),
+            self.source_asset_issuer.as_deref(),
+        )
+    }
+
+    pub fn destination_asset(&self) -> Option<StellarAsset> {
+        StellarAsset::from_horizon(
+            &self.destination_asset_type,
+            self.destination_asset_code.as_deref(),
+            self.destination_asset_issuer.as_deref(),
+        )
+    }
+
+    /// Intermediate hops, or `None` if Horizon returned an unparseable asset
+    pub fn hops(&self) -> Option<Vec<StellarAsset>> {
+        self.path
+            .iter()
+            .map(|a| {
+                StellarAsset::from_horizon(
+                    &a.asset_type,
+                    a.asset_code.as_deref(),
+                    a.asset_issuer.as_deref(),
+                )
+            })
+            .collect()
+    }
+}
+
+/// One price level of a Horizon `/order_book` side.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HorizonOrderBookLevel {
+    /// Units of the buying asset per unit of the selling asset
+    pub price: String,
+    pub amount: String,
+}
+
+/// Horizon `/order_book` snapshot, best levels first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HorizonOrderBook {
+    #[serde(default)]
+    pub bids: Vec<HorizonOrderBookLevel>,
+    #[serde(default)]
+    pub asks: Vec<HorizonOrderBookLevel>,
+}
+
 impl From<HorizonAccount> for StellarAccountInfo {
     fn from(account: HorizonAccount) -> Self {
         Self {
@@ -182,3 +351,12 @@ pub fn extract_afri_balance(balances: &[AssetBalance]) -> Option<String> {
 pub fn extract_cngn_balance(balances: &[AssetBalance], issuer: Option<&str>) -> Option<String> {
     extract_asset_balance(balances, "cNGN", issuer)
 }
+
+/// Horizon operation types that credit the destination with `amount` of
+/// `asset_code`/`asset_issuer`, including DEX conversions.
+pub fn is_payment_operation(op_type: &str) -> bool {
+    matches!(
+        op_type,
+        "payment" | "path_payment_strict_send" | "path_payment_strict_receive"
+    )
+}
@@ -100,6 +100,27 @@ impl ConversionAuditRepository {
         .map_err(DatabaseError::from_sqlx)
     }
 
+    /// Move a `quoted` audit to `executed`, linking the transaction. Returns
+    /// false if the quote was already executed, failed or expired, so a
+    /// quote can only ever be used once.
+    pub async fn lock_quote(
+        &self,
+        id: Uuid,
+        transaction_id: Option<Uuid>,
+    ) -> Result<bool, DatabaseError> {
+        let result = sqlx::query(
+            "UPDATE conversion_audits
+             SET status = 'executed', transaction_id = COALESCE($2, transaction_id), updated_at = NOW()
+             WHERE id = $1 AND status = 'quoted'",
+        )
+        .bind(id)
+        .bind(transaction_id)
+        .execute(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)?;
+        Ok(result.rows_affected() > 0)
+    }
+
     /// Find audits by user
     pub async fn find_by_user_id(
         &self,
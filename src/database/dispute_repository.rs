@@ -0,0 +1,270 @@
+use crate::database::error::DatabaseError;
+use serde::{Deserialize, Serialize};
+use sqlx::{types::BigDecimal, FromRow, PgPool};
+use uuid::Uuid;
+
+/// A chargeback raised against one of our collections
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Dispute {
+    pub id: Uuid,
+    pub transaction_id: Uuid,
+    pub provider: String,
+    pub provider_dispute_id: String,
+    /// open, under_review, won or lost
+    pub status: String,
+    pub reason: Option<String>,
+    pub amount: BigDecimal,
+    pub currency: String,
+    pub evidence_due_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// accepted or contested, once ops have responded
+    pub ops_action: Option<String>,
+    pub ops_action_by: Option<String>,
+    pub ops_action_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub ops_note: Option<String>,
+    pub resolved_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub provider_data: serde_json::Value,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// An evidence file uploaded for a dispute
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct DisputeEvidenceFile {
+    pub id: Uuid,
+    pub dispute_id: Uuid,
+    pub kind: String,
+    pub file_name: String,
+    pub content_type: Option<String>,
+    pub storage_url: String,
+    pub description: Option<String>,
+    pub uploaded_by: String,
+    pub submitted_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// An evidence file ops uploaded to storage
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewDisputeEvidence {
+    /// receipt, delivery_proof, customer_communication, identity or other
+    pub kind: String,
+    pub file_name: String,
+    pub content_type: Option<String>,
+    pub storage_url: String,
+    pub description: Option<String>,
+    pub uploaded_by: String,
+}
+
+/// A dispute as last reported by the provider
+#[derive(Debug, Clone)]
+pub struct DisputeUpsert {
+    pub transaction_id: Uuid,
+    pub provider: String,
+    pub provider_dispute_id: String,
+    pub status: String,
+    pub reason: Option<String>,
+    pub amount: BigDecimal,
+    pub currency: String,
+    pub evidence_due_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub provider_data: serde_json::Value,
+}
+
+const DISPUTE_COLUMNS: &str = "id, transaction_id, provider, provider_dispute_id, status, reason, \
+     amount, currency, evidence_due_at, ops_action, ops_action_by, ops_action_at, ops_note, \
+     resolved_at, provider_data, created_at, updated_at";
+
+const DISPUTE_EVIDENCE_COLUMNS: &str = "id, dispute_id, kind, file_name, content_type, \
+     storage_url, description, uploaded_by, submitted_at, created_at";
+
+/// Repository for disputes and their evidence
+pub struct DisputeRepository {
+    pool: PgPool,
+}
+
+impl DisputeRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Insert or refresh a dispute from a provider report. A resolved
+    /// dispute keeps its outcome and one under review does not reopen, so
+    /// stale reports arriving late change nothing.
+    pub async fn upsert(&self, dispute: &DisputeUpsert) -> Result<Dispute, DatabaseError> {
+        sqlx::query_as::<_, Dispute>(&format!(
+            "INSERT INTO disputes
+                (transaction_id, provider, provider_dispute_id, status, reason, amount, currency,
+                 evidence_due_at, provider_data, resolved_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9,
+                     CASE WHEN $4 IN ('won', 'lost') THEN NOW() END)
+             ON CONFLICT (provider, provider_dispute_id) DO UPDATE SET
+                status = CASE
+                    WHEN disputes.status IN ('won', 'lost') THEN disputes.status
+                    WHEN disputes.status = 'under_review' AND EXCLUDED.status = 'open'
+                        THEN disputes.status
+                    ELSE EXCLUDED.status
+                END,
+                reason = COALESCE(EXCLUDED.reason, disputes.reason),
+                amount = EXCLUDED.amount,
+                evidence_due_at = COALESCE(EXCLUDED.evidence_due_at, disputes.evidence_due_at),
+                provider_data = EXCLUDED.provider_data,
+                resolved_at = COALESCE(disputes.resolved_at, EXCLUDED.resolved_at)
+             RETURNING {}",
+            DISPUTE_COLUMNS
+        ))
+        .bind(dispute.transaction_id)
+        .bind(&dispute.provider)
+        .bind(&dispute.provider_dispute_id)
+        .bind(&dispute.status)
+        .bind(&dispute.reason)
+        .bind(&dispute.amount)
+        .bind(&dispute.currency)
+        .bind(dispute.evidence_due_at)
+        .bind(&dispute.provider_data)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    pub async fn find_by_id(&self, id: Uuid) -> Result<Option<Dispute>, DatabaseError> {
+        sqlx::query_as::<_, Dispute>(&format!(
+            "SELECT {} FROM disputes WHERE id = $1",
+            DISPUTE_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Disputes, optionally in one status, soonest deadline first
+    pub async fn list(
+        &self,
+        status: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<Dispute>, DatabaseError> {
+        sqlx::query_as::<_, Dispute>(&format!(
+            "SELECT {} FROM disputes
+             WHERE $1::TEXT IS NULL OR status = $1
+             ORDER BY evidence_due_at ASC NULLS LAST, created_at DESC
+             LIMIT $2",
+            DISPUTE_COLUMNS
+        ))
+        .bind(status)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Open disputes whose evidence deadline falls before `deadline`
+    pub async fn find_open_due_before(
+        &self,
+        deadline: chrono::DateTime<chrono::Utc>,
+        limit: i64,
+    ) -> Result<Vec<Dispute>, DatabaseError> {
+        sqlx::query_as::<_, Dispute>(&format!(
+            "SELECT {} FROM disputes
+             WHERE status = 'open' AND evidence_due_at < $1
+             ORDER BY evidence_due_at ASC
+             LIMIT $2",
+            DISPUTE_COLUMNS
+        ))
+        .bind(deadline)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Record how ops responded to an open dispute and move it to `status`.
+    /// Returns `None` if the dispute is no longer open.
+    pub async fn record_ops_action(
+        &self,
+        id: Uuid,
+        action: &str,
+        actor: &str,
+        note: Option<&str>,
+        status: &str,
+    ) -> Result<Option<Dispute>, DatabaseError> {
+        sqlx::query_as::<_, Dispute>(&format!(
+            "UPDATE disputes SET
+                ops_action = $2, ops_action_by = $3, ops_action_at = NOW(), ops_note = $4,
+                status = $5,
+                resolved_at = CASE WHEN $5 IN ('won', 'lost') THEN NOW() ELSE resolved_at END
+             WHERE id = $1 AND status = 'open'
+             RETURNING {}",
+            DISPUTE_COLUMNS
+        ))
+        .bind(id)
+        .bind(action)
+        .bind(actor)
+        .bind(note)
+        .bind(status)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Disputes lost on transactions credited to `wallet_address`
+    pub async fn count_lost_for_wallet(&self, wallet_address: &str) -> Result<i64, DatabaseError> {
+        sqlx::query_scalar(
+            "SELECT COUNT(*) FROM disputes d
+             JOIN transactions t ON t.transaction_id = d.transaction_id
+             WHERE t.wallet_address = $1 AND d.status = 'lost'",
+        )
+        .bind(wallet_address)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    pub async fn add_evidence(
+        &self,
+        dispute_id: Uuid,
+        evidence: &NewDisputeEvidence,
+    ) -> Result<DisputeEvidenceFile, DatabaseError> {
+        sqlx::query_as::<_, DisputeEvidenceFile>(&format!(
+            "INSERT INTO dispute_evidence
+                (dispute_id, kind, file_name, content_type, storage_url, description, uploaded_by)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             RETURNING {}",
+            DISPUTE_EVIDENCE_COLUMNS
+        ))
+        .bind(dispute_id)
+        .bind(&evidence.kind)
+        .bind(&evidence.file_name)
+        .bind(&evidence.content_type)
+        .bind(&evidence.storage_url)
+        .bind(&evidence.description)
+        .bind(&evidence.uploaded_by)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    pub async fn list_evidence(
+        &self,
+        dispute_id: Uuid,
+    ) -> Result<Vec<DisputeEvidenceFile>, DatabaseError> {
+        sqlx::query_as::<_, DisputeEvidenceFile>(&format!(
+            "SELECT {} FROM dispute_evidence WHERE dispute_id = $1 ORDER BY created_at",
+            DISPUTE_EVIDENCE_COLUMNS
+        ))
+        .bind(dispute_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Stamp every unsent evidence file of a dispute as sent to the provider
+    pub async fn mark_evidence_submitted(&self, dispute_id: Uuid) -> Result<u64, DatabaseError> {
+        let result = sqlx::query(
+            "UPDATE dispute_evidence SET submitted_at = NOW()
+             WHERE dispute_id = $1 AND submitted_at IS NULL",
+        )
+        .bind(dispute_id)
+        .execute(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,105 @@
+use crate::database::error::DatabaseError;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+
+/// A lease on a named lock
+#[derive(Debug, Clone, PartialEq, Eq, FromRow, Serialize, Deserialize)]
+pub struct LockLease {
+    pub name: String,
+    pub holder: String,
+    /// Incremented on every change of holder
+    pub fencing_token: i64,
+    pub acquired_at: chrono::DateTime<chrono::Utc>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+const LOCK_COLUMNS: &str = "name, holder, fencing_token, acquired_at, expires_at";
+
+/// Repository for distributed locks
+pub struct DistributedLockRepository {
+    pool: PgPool,
+}
+
+impl DistributedLockRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Take the lock for `holder` if it is free or has lapsed, with the next
+    /// fencing token. Returns `None` while someone holds it.
+    pub async fn try_acquire(
+        &self,
+        name: &str,
+        holder: &str,
+        ttl: std::time::Duration,
+    ) -> Result<Option<LockLease>, DatabaseError> {
+        sqlx::query_as::<_, LockLease>(&format!(
+            "INSERT INTO distributed_locks (name, holder, fencing_token, acquired_at, expires_at)
+             VALUES ($1, $2, 1, NOW(), NOW() + make_interval(secs => $3))
+             ON CONFLICT (name) DO UPDATE SET
+                holder = EXCLUDED.holder,
+                fencing_token = distributed_locks.fencing_token + 1,
+                acquired_at = NOW(),
+                expires_at = EXCLUDED.expires_at
+             WHERE distributed_locks.expires_at < NOW()
+             RETURNING {}",
+            LOCK_COLUMNS
+        ))
+        .bind(name)
+        .bind(holder)
+        .bind(ttl.as_secs_f64())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Extend a lease. Returns `None` if another holder has taken the lock
+    /// since, in which case the lease is lost for good.
+    pub async fn renew(
+        &self,
+        lease: &LockLease,
+        ttl: std::time::Duration,
+    ) -> Result<Option<LockLease>, DatabaseError> {
+        sqlx::query_as::<_, LockLease>(&format!(
+            "UPDATE distributed_locks SET expires_at = NOW() + make_interval(secs => $4)
+             WHERE name = $1 AND holder = $2 AND fencing_token = $3
+             RETURNING {}",
+            LOCK_COLUMNS
+        ))
+        .bind(&lease.name)
+        .bind(&lease.holder)
+        .bind(lease.fencing_token)
+        .bind(ttl.as_secs_f64())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Give a lease up so another replica can take the lock straight away
+    pub async fn release(&self, lease: &LockLease) -> Result<bool, DatabaseError> {
+        let result = sqlx::query(
+            "UPDATE distributed_locks SET expires_at = NOW() - INTERVAL '1 millisecond'
+             WHERE name = $1 AND holder = $2 AND fencing_token = $3",
+        )
+        .bind(&lease.name)
+        .bind(&lease.holder)
+        .bind(lease.fencing_token)
+        .execute(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// The unexpired lease on a lock, if any
+    pub async fn current(&self, name: &str) -> Result<Option<LockLease>, DatabaseError> {
+        sqlx::query_as::<_, LockLease>(&format!(
+            "SELECT {} FROM distributed_locks WHERE name = $1 AND expires_at >= NOW()",
+            LOCK_COLUMNS
+        ))
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+}
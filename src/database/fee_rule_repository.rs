@@ -0,0 +1,129 @@
+use crate::database::error::DatabaseError;
+use serde::{Deserialize, Serialize};
+use sqlx::{types::BigDecimal, FromRow, PgPool};
+use uuid::Uuid;
+
+/// Pricing rule applied on top of a fee structure tier
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct FeeRule {
+    pub id: Uuid,
+    pub name: String,
+    pub rule_type: String,
+    pub transaction_type: Option<String>,
+    pub priority: i32,
+    pub stackable: bool,
+    pub promo_code: Option<String>,
+    pub partner_key_hash: Option<String>,
+    pub free_transactions: Option<i32>,
+    pub min_monthly_volume: Option<BigDecimal>,
+    pub discount_percent: Option<BigDecimal>,
+    pub discount_flat: Option<BigDecimal>,
+    pub platform_fee_percent: Option<BigDecimal>,
+    pub budget_total: Option<BigDecimal>,
+    pub budget_used: BigDecimal,
+    pub starts_at: chrono::DateTime<chrono::Utc>,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub is_active: bool,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// A wallet's history as seen by volume and first-N rules
+#[derive(Debug, Clone, FromRow)]
+pub struct WalletFeeActivity {
+    pub completed_transactions: i64,
+    pub monthly_volume: BigDecimal,
+}
+
+const FEE_RULE_COLUMNS: &str = "id, name, rule_type, transaction_type, priority, stackable, \
+     promo_code, partner_key_hash, free_transactions, min_monthly_volume, discount_percent, \
+     discount_flat, platform_fee_percent, budget_total, budget_used, starts_at, expires_at, \
+     is_active, created_at, updated_at";
+
+/// Repository for fee rules
+pub struct FeeRuleRepository {
+    pool: PgPool,
+}
+
+impl FeeRuleRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Active, in-window rules that could apply to a calculation, highest
+    /// priority first. Promo and partner rules are only returned when the
+    /// caller presented their code or key.
+    pub async fn list_candidates(
+        &self,
+        transaction_type: &str,
+        promo_code: Option<&str>,
+        partner_key_hash: Option<&str>,
+    ) -> Result<Vec<FeeRule>, DatabaseError> {
+        sqlx::query_as::<_, FeeRule>(&format!(
+            "SELECT {} FROM fee_rules
+             WHERE is_active = TRUE
+               AND starts_at <= NOW()
+               AND (expires_at IS NULL OR expires_at > NOW())
+               AND (transaction_type IS NULL OR transaction_type = $1)
+               AND (rule_type <> 'promo_code' OR UPPER(promo_code) = UPPER($2))
+               AND (rule_type <> 'partner_override' OR partner_key_hash = $3)
+             ORDER BY priority DESC, created_at ASC",
+            FEE_RULE_COLUMNS
+        ))
+        .bind(transaction_type)
+        .bind(promo_code)
+        .bind(partner_key_hash)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Completed transactions overall, and completed volume (in the source
+    /// amount) since `month_start`
+    pub async fn wallet_activity(
+        &self,
+        wallet_address: &str,
+        month_start: chrono::DateTime<chrono::Utc>,
+    ) -> Result<WalletFeeActivity, DatabaseError> {
+        sqlx::query_as::<_, WalletFeeActivity>(
+            "SELECT COUNT(*) AS completed_transactions,
+                    COALESCE(SUM(from_amount) FILTER (WHERE created_at >= $2), 0) AS monthly_volume
+             FROM transactions
+             WHERE wallet_address = $1 AND status = 'completed'",
+        )
+        .bind(wallet_address)
+        .bind(month_start)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Charge `amount` against a promo's budget. Returns false when the
+    /// remaining budget cannot cover it.
+    pub async fn consume_budget(
+        &self,
+        rule_id: Uuid,
+        amount: &BigDecimal,
+    ) -> Result<bool, DatabaseError> {
+        let result = sqlx::query(
+            "UPDATE fee_rules SET budget_used = budget_used + $2
+             WHERE id = $1 AND (budget_total IS NULL OR budget_used + $2 <= budget_total)",
+        )
+        .bind(rule_id)
+        .bind(amount)
+        .execute(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn set_active(&self, rule_id: Uuid, is_active: bool) -> Result<bool, DatabaseError> {
+        let result = sqlx::query("UPDATE fee_rules SET is_active = $2 WHERE id = $1")
+            .bind(rule_id)
+            .bind(is_active)
+            .execute(&self.pool)
+            .await
+            .map_err(DatabaseError::from_sqlx)?;
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,496 @@
+use crate::database::error::DatabaseError;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+/// A durable background job
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Job {
+    pub id: Uuid,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    /// queued, running, succeeded, dead or cancelled
+    pub status: String,
+    /// Number of times the job has been claimed
+    pub attempts: i32,
+    pub max_attempts: i32,
+    /// Delay after the first failed attempt, doubling after each further one
+    pub backoff_seconds: i32,
+    pub run_at: chrono::DateTime<chrono::Utc>,
+    pub unique_key: Option<String>,
+    /// Schedule that enqueued the job, if any
+    pub schedule_name: Option<String>,
+    pub locked_by: Option<String>,
+    pub lease_expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub heartbeat_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub last_error: Option<String>,
+    pub started_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Job {
+    /// Deserialize the payload into the job's typed form
+    pub fn payload_as<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_value(self.payload.clone())
+    }
+
+    /// Whether this claim is the job's last allowed attempt
+    pub fn is_last_attempt(&self) -> bool {
+        self.attempts >= self.max_attempts
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewJob {
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub max_attempts: i32,
+    pub backoff_seconds: i32,
+    pub run_at: chrono::DateTime<chrono::Utc>,
+    pub unique_key: Option<String>,
+    pub schedule_name: Option<String>,
+}
+
+/// A recurring job
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct JobSchedule {
+    pub name: String,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    /// `@every <n>s|m|h` or a five-field cron expression in UTC
+    pub schedule: String,
+    pub max_attempts: i32,
+    pub enabled: bool,
+    pub next_run_at: chrono::DateTime<chrono::Utc>,
+    pub last_enqueued_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub last_job_id: Option<Uuid>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewJobSchedule {
+    pub name: String,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub schedule: String,
+    pub max_attempts: i32,
+    /// First run, used when the schedule is new or its timing changed
+    pub next_run_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Number of jobs of one kind in one status
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct JobCount {
+    pub kind: String,
+    pub status: String,
+    pub count: i64,
+}
+
+const JOB_COLUMNS: &str = "id, kind, payload, status, attempts, max_attempts, backoff_seconds, \
+     run_at, unique_key, schedule_name, locked_by, lease_expires_at, heartbeat_at, last_error, \
+     started_at, finished_at, created_at, updated_at";
+
+const JOB_SCHEDULE_COLUMNS: &str = "name, kind, payload, schedule, max_attempts, enabled, \
+     next_run_at, last_enqueued_at, last_job_id, created_at, updated_at";
+
+/// Repository for jobs and job schedules
+pub struct JobRepository {
+    pool: PgPool,
+}
+
+impl JobRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Queue a job. Returns `None` if a queued or running job already holds
+    /// its unique key.
+    pub async fn enqueue(&self, job: &NewJob) -> Result<Option<Job>, DatabaseError> {
+        sqlx::query_as::<_, Job>(&format!(
+            "INSERT INTO jobs
+                (kind, payload, max_attempts, backoff_seconds, run_at, unique_key, schedule_name)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             ON CONFLICT (unique_key)
+                WHERE status IN ('queued', 'running') AND unique_key IS NOT NULL
+                DO NOTHING
+             RETURNING {}",
+            JOB_COLUMNS
+        ))
+        .bind(&job.kind)
+        .bind(&job.payload)
+        .bind(job.max_attempts)
+        .bind(job.backoff_seconds)
+        .bind(job.run_at)
+        .bind(&job.unique_key)
+        .bind(&job.schedule_name)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Lease up to `limit` due jobs of the given kinds to `worker` until
+    /// `lease` has passed. Rows other runners hold are skipped, and jobs
+    /// whose lease ran out mid-run are claimed again while they have
+    /// attempts left.
+    pub async fn claim_due(
+        &self,
+        kinds: &[String],
+        worker: &str,
+        limit: i64,
+        lease: std::time::Duration,
+    ) -> Result<Vec<Job>, DatabaseError> {
+        sqlx::query_as::<_, Job>(&format!(
+            "UPDATE jobs
+             SET status = 'running',
+                 attempts = attempts + 1,
+                 locked_by = $2,
+                 lease_expires_at = NOW() + make_interval(secs => $4),
+                 heartbeat_at = NOW(),
+                 started_at = NOW()
+             WHERE id IN (
+                 SELECT id FROM jobs
+                 WHERE kind = ANY($1)
+                   AND ((status = 'queued' AND run_at <= NOW())
+                        OR (status = 'running' AND lease_expires_at < NOW()
+                            AND attempts < max_attempts))
+                 ORDER BY run_at ASC
+                 LIMIT $3
+                 FOR UPDATE SKIP LOCKED
+             )
+             RETURNING {}",
+            JOB_COLUMNS
+        ))
+        .bind(kinds)
+        .bind(worker)
+        .bind(limit)
+        .bind(lease.as_secs_f64())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Bury running jobs whose lease ran out on their last attempt. Returns
+    /// how many were buried.
+    pub async fn bury_expired(&self) -> Result<u64, DatabaseError> {
+        let result = sqlx::query(
+            "UPDATE jobs
+             SET status = 'dead',
+                 last_error = COALESCE(last_error || '; ', '') || 'lease expired on final attempt',
+                 locked_by = NULL,
+                 lease_expires_at = NULL,
+                 finished_at = NOW()
+             WHERE status = 'running'
+               AND lease_expires_at < NOW()
+               AND attempts >= max_attempts",
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Extend the lease `worker` holds on a running job. Returns `false` if
+    /// the job is no longer leased to `worker`.
+    pub async fn heartbeat(
+        &self,
+        id: Uuid,
+        worker: &str,
+        lease: std::time::Duration,
+    ) -> Result<bool, DatabaseError> {
+        let result = sqlx::query(
+            "UPDATE jobs
+             SET lease_expires_at = NOW() + make_interval(secs => $3),
+                 heartbeat_at = NOW()
+             WHERE id = $1 AND status = 'running' AND locked_by = $2",
+        )
+        .bind(id)
+        .bind(worker)
+        .bind(lease.as_secs_f64())
+        .execute(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Mark a job `worker` holds as succeeded. Returns `None` if the lease
+    /// was lost to another runner.
+    pub async fn complete(&self, id: Uuid, worker: &str) -> Result<Option<Job>, DatabaseError> {
+        sqlx::query_as::<_, Job>(&format!(
+            "UPDATE jobs
+             SET status = 'succeeded',
+                 locked_by = NULL,
+                 lease_expires_at = NULL,
+                 finished_at = NOW()
+             WHERE id = $1 AND status = 'running' AND locked_by = $2
+             RETURNING {}",
+            JOB_COLUMNS
+        ))
+        .bind(id)
+        .bind(worker)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Record a failed attempt, queueing the job again at `run_at` or
+    /// burying it. Returns `None` if the lease was lost to another runner.
+    pub async fn record_failure(
+        &self,
+        id: Uuid,
+        worker: &str,
+        error: &str,
+        run_at: chrono::DateTime<chrono::Utc>,
+        dead: bool,
+    ) -> Result<Option<Job>, DatabaseError> {
+        sqlx::query_as::<_, Job>(&format!(
+            "UPDATE jobs
+             SET status = CASE WHEN $5 THEN 'dead' ELSE 'queued' END,
+                 last_error = $3,
+                 run_at = $4,
+                 locked_by = NULL,
+                 lease_expires_at = NULL,
+                 finished_at = CASE WHEN $5 THEN NOW() ELSE finished_at END
+             WHERE id = $1 AND status = 'running' AND locked_by = $2
+             RETURNING {}",
+            JOB_COLUMNS
+        ))
+        .bind(id)
+        .bind(worker)
+        .bind(error)
+        .bind(run_at)
+        .bind(dead)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Queue a job `worker` holds again at `run_at` without counting the
+    /// attempt, for handlers waiting on something outside their control.
+    /// Returns `None` if the lease was lost to another runner.
+    pub async fn snooze(
+        &self,
+        id: Uuid,
+        worker: &str,
+        run_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Option<Job>, DatabaseError> {
+        sqlx::query_as::<_, Job>(&format!(
+            "UPDATE jobs
+             SET status = 'queued',
+                 attempts = GREATEST(attempts - 1, 0),
+                 run_at = $3,
+                 locked_by = NULL,
+                 lease_expires_at = NULL
+             WHERE id = $1 AND status = 'running' AND locked_by = $2
+             RETURNING {}",
+            JOB_COLUMNS
+        ))
+        .bind(id)
+        .bind(worker)
+        .bind(run_at)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    pub async fn find(&self, id: Uuid) -> Result<Option<Job>, DatabaseError> {
+        sqlx::query_as::<_, Job>(&format!("SELECT {} FROM jobs WHERE id = $1", JOB_COLUMNS))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Jobs, optionally filtered by status and kind, most recently updated first
+    pub async fn list(
+        &self,
+        status: Option<&str>,
+        kind: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<Job>, DatabaseError> {
+        sqlx::query_as::<_, Job>(&format!(
+            "SELECT {} FROM jobs
+             WHERE ($1::TEXT IS NULL OR status = $1)
+               AND ($2::TEXT IS NULL OR kind = $2)
+             ORDER BY updated_at DESC LIMIT $3",
+            JOB_COLUMNS
+        ))
+        .bind(status)
+        .bind(kind)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Job counts by kind and status
+    pub async fn counts(&self) -> Result<Vec<JobCount>, DatabaseError> {
+        sqlx::query_as::<_, JobCount>(
+            "SELECT kind, status, COUNT(*) AS count FROM jobs
+             GROUP BY kind, status ORDER BY kind, status",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Put a dead or cancelled job back on the queue with a fresh attempt
+    /// budget. Returns `None` if the job is in another status or another job
+    /// now holds its unique key.
+    pub async fn retry(&self, id: Uuid) -> Result<Option<Job>, DatabaseError> {
+        sqlx::query_as::<_, Job>(&format!(
+            "UPDATE jobs
+             SET status = 'queued',
+                 attempts = 0,
+                 run_at = NOW(),
+                 finished_at = NULL
+             WHERE id = $1 AND status IN ('dead', 'cancelled')
+               AND (unique_key IS NULL OR NOT EXISTS (
+                   SELECT 1 FROM jobs other
+                   WHERE other.unique_key = jobs.unique_key
+                     AND other.status IN ('queued', 'running')))
+             RETURNING {}",
+            JOB_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Cancel a queued job. Returns `None` if it is not queued.
+    pub async fn cancel(&self, id: Uuid) -> Result<Option<Job>, DatabaseError> {
+        sqlx::query_as::<_, Job>(&format!(
+            "UPDATE jobs SET status = 'cancelled', finished_at = NOW()
+             WHERE id = $1 AND status = 'queued'
+             RETURNING {}",
+            JOB_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Create or update a schedule. An existing schedule keeps its next run
+    /// and enabled flag unless its timing changed.
+    pub async fn upsert_schedule(
+        &self,
+        schedule: &NewJobSchedule,
+    ) -> Result<JobSchedule, DatabaseError> {
+        sqlx::query_as::<_, JobSchedule>(&format!(
+            "INSERT INTO job_schedules (name, kind, payload, schedule, max_attempts, next_run_at)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             ON CONFLICT (name) DO UPDATE SET
+                kind = EXCLUDED.kind,
+                payload = EXCLUDED.payload,
+                max_attempts = EXCLUDED.max_attempts,
+                next_run_at = CASE WHEN job_schedules.schedule <> EXCLUDED.schedule
+                                   THEN EXCLUDED.next_run_at
+                                   ELSE job_schedules.next_run_at END,
+                schedule = EXCLUDED.schedule
+             RETURNING {}",
+            JOB_SCHEDULE_COLUMNS
+        ))
+        .bind(&schedule.name)
+        .bind(&schedule.kind)
+        .bind(&schedule.payload)
+        .bind(&schedule.schedule)
+        .bind(schedule.max_attempts)
+        .bind(schedule.next_run_at)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Enabled schedules of the given kinds whose next run has come
+    pub async fn due_schedules(&self, kinds: &[String]) -> Result<Vec<JobSchedule>, DatabaseError> {
+        sqlx::query_as::<_, JobSchedule>(&format!(
+            "SELECT {} FROM job_schedules
+             WHERE enabled AND next_run_at <= NOW() AND kind = ANY($1)
+             ORDER BY next_run_at",
+            JOB_SCHEDULE_COLUMNS
+        ))
+        .bind(kinds)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Move a schedule from `expected_run_at` to `next_run_at`. Returns
+    /// `false` if another runner advanced it first, so each run is enqueued
+    /// once.
+    pub async fn advance_schedule(
+        &self,
+        name: &str,
+        expected_run_at: chrono::DateTime<chrono::Utc>,
+        next_run_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool, DatabaseError> {
+        let result = sqlx::query(
+            "UPDATE job_schedules SET next_run_at = $3, last_enqueued_at = NOW()
+             WHERE name = $1 AND next_run_at = $2",
+        )
+        .bind(name)
+        .bind(expected_run_at)
+        .bind(next_run_at)
+        .execute(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Remember the job a schedule last enqueued
+    pub async fn set_last_job(&self, name: &str, job_id: Uuid) -> Result<(), DatabaseError> {
+        sqlx::query("UPDATE job_schedules SET last_job_id = $2 WHERE name = $1")
+            .bind(name)
+            .bind(job_id)
+            .execute(&self.pool)
+            .await
+            .map_err(DatabaseError::from_sqlx)?;
+        Ok(())
+    }
+
+    pub async fn list_schedules(&self) -> Result<Vec<JobSchedule>, DatabaseError> {
+        sqlx::query_as::<_, JobSchedule>(&format!(
+            "SELECT {} FROM job_schedules ORDER BY name",
+            JOB_SCHEDULE_COLUMNS
+        ))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Pause or resume a schedule. Returns `None` if there is no such schedule.
+    pub async fn set_schedule_enabled(
+        &self,
+        name: &str,
+        enabled: bool,
+    ) -> Result<Option<JobSchedule>, DatabaseError> {
+        sqlx::query_as::<_, JobSchedule>(&format!(
+            "UPDATE job_schedules SET enabled = $2 WHERE name = $1 RETURNING {}",
+            JOB_SCHEDULE_COLUMNS
+        ))
+        .bind(name)
+        .bind(enabled)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Make a schedule due now. Returns `None` if there is no such schedule.
+    pub async fn trigger_schedule(&self, name: &str) -> Result<Option<JobSchedule>, DatabaseError> {
+        sqlx::query_as::<_, JobSchedule>(&format!(
+            "UPDATE job_schedules SET next_run_at = NOW() WHERE name = $1 RETURNING {}",
+            JOB_SCHEDULE_COLUMNS
+        ))
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+}
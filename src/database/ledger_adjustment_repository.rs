@@ -0,0 +1,88 @@
+use crate::database::error::DatabaseError;
+use serde::{Deserialize, Serialize};
+use sqlx::{types::BigDecimal, FromRow, PgPool};
+use uuid::Uuid;
+
+/// A correction to platform balances, e.g. funds lost to a chargeback
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct LedgerAdjustment {
+    pub id: Uuid,
+    pub transaction_id: Uuid,
+    pub dispute_id: Option<Uuid>,
+    pub wallet_address: String,
+    pub kind: String,
+    /// Signed; negative is a loss to the platform
+    pub amount: BigDecimal,
+    pub currency: String,
+    pub description: Option<String>,
+    pub metadata: serde_json::Value,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewLedgerAdjustment {
+    pub transaction_id: Uuid,
+    pub dispute_id: Option<Uuid>,
+    pub wallet_address: String,
+    pub kind: String,
+    pub amount: BigDecimal,
+    pub currency: String,
+    pub description: Option<String>,
+    pub metadata: serde_json::Value,
+}
+
+const LEDGER_ADJUSTMENT_COLUMNS: &str = "id, transaction_id, dispute_id, wallet_address, kind, \
+     amount, currency, description, metadata, created_at";
+
+/// Repository for ledger adjustments
+pub struct LedgerAdjustmentRepository {
+    pool: PgPool,
+}
+
+impl LedgerAdjustmentRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Post an adjustment. Returns `None` if one of the same kind was already
+    /// posted for the dispute.
+    pub async fn post(
+        &self,
+        adjustment: &NewLedgerAdjustment,
+    ) -> Result<Option<LedgerAdjustment>, DatabaseError> {
+        sqlx::query_as::<_, LedgerAdjustment>(&format!(
+            "INSERT INTO ledger_adjustments
+                (transaction_id, dispute_id, wallet_address, kind, amount, currency, description,
+                 metadata)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+             ON CONFLICT (dispute_id, kind) DO NOTHING
+             RETURNING {}",
+            LEDGER_ADJUSTMENT_COLUMNS
+        ))
+        .bind(adjustment.transaction_id)
+        .bind(adjustment.dispute_id)
+        .bind(&adjustment.wallet_address)
+        .bind(&adjustment.kind)
+        .bind(&adjustment.amount)
+        .bind(&adjustment.currency)
+        .bind(&adjustment.description)
+        .bind(&adjustment.metadata)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    pub async fn find_by_transaction(
+        &self,
+        transaction_id: Uuid,
+    ) -> Result<Vec<LedgerAdjustment>, DatabaseError> {
+        sqlx::query_as::<_, LedgerAdjustment>(&format!(
+            "SELECT {} FROM ledger_adjustments WHERE transaction_id = $1 ORDER BY created_at",
+            LEDGER_ADJUSTMENT_COLUMNS
+        ))
+        .bind(transaction_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+}
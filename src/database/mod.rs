@@ -1,20 +1,33 @@
 // This module requires std library (not available in WASM)
 
 pub mod bill_payment_repository;
+pub mod claimable_balance_repository;
 pub mod conversion_audit_repository;
+pub mod dispute_repository;
+pub mod distributed_lock_repository;
 pub mod error;
 pub mod exchange_rate_repository;
+pub mod fee_rule_repository;
 pub mod fee_structure_repository;
+pub mod job_repository;
+pub mod ledger_adjustment_repository;
+pub mod liquidity_reservation_repository;
 pub mod payment_method_repository;
 pub mod payment_repository;
+pub mod payout_review_repository;
 pub mod provider_config_repository;
+pub mod provider_routing_repository;
+pub mod refund_repository;
 pub mod repository;
+pub mod suspense_payment_repository;
 pub mod transaction;
 pub mod transaction_repository;
+pub mod treasury_repository;
 pub mod onramp_quote_repository;
 pub mod trustline_operation_repository;
 pub mod trustline_repository;
 pub mod wallet_repository;
+pub mod wallet_risk_repository;
 pub mod webhook_repository;
 
 use sqlx::postgres::PgPoolOptions;
@@ -0,0 +1,104 @@
+use crate::database::error::DatabaseError;
+use sqlx::{types::BigDecimal, FromRow, PgPool};
+
+/// A provider's routing inputs: its config row plus outcomes over the
+/// rolling window
+#[derive(Debug, Clone, FromRow)]
+pub struct ProviderRoutingStats {
+    pub provider: String,
+    pub is_enabled: bool,
+    pub daily_volume_cap: Option<BigDecimal>,
+    pub success_count: i64,
+    pub failure_count: i64,
+    pub total_latency_ms: i64,
+    /// Amount successfully routed to the provider since UTC midnight
+    pub volume_today: BigDecimal,
+}
+
+/// Repository for persisted provider routing metrics
+pub struct ProviderRoutingRepository {
+    pool: PgPool,
+}
+
+impl ProviderRoutingRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Stats for every configured provider over the last `window_days` UTC
+    /// days, including today
+    pub async fn stats(
+        &self,
+        window_days: i32,
+    ) -> Result<Vec<ProviderRoutingStats>, DatabaseError> {
+        sqlx::query_as::<_, ProviderRoutingStats>(
+            "SELECT c.provider, c.is_enabled, c.daily_volume_cap,
+                    COALESCE(SUM(s.success_count), 0)::BIGINT AS success_count,
+                    COALESCE(SUM(s.failure_count), 0)::BIGINT AS failure_count,
+                    COALESCE(SUM(s.total_latency_ms), 0)::BIGINT AS total_latency_ms,
+                    COALESCE(SUM(s.routed_volume)
+                        FILTER (WHERE s.stat_date = (NOW() AT TIME ZONE 'UTC')::DATE), 0) AS volume_today
+             FROM payment_provider_configs c
+             LEFT JOIN provider_routing_stats s
+               ON s.provider = c.provider
+              AND s.stat_date > (NOW() AT TIME ZONE 'UTC')::DATE - $1
+             GROUP BY c.provider, c.is_enabled, c.daily_volume_cap
+             ORDER BY c.provider",
+        )
+        .bind(window_days)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Add one initiation outcome to today's row for `provider`
+    pub async fn record_outcome(
+        &self,
+        provider: &str,
+        succeeded: bool,
+        latency_ms: i64,
+        amount: &BigDecimal,
+    ) -> Result<(), DatabaseError> {
+        let routed = if succeeded {
+            amount.clone()
+        } else {
+            BigDecimal::from(0)
+        };
+        sqlx::query(
+            "INSERT INTO provider_routing_stats
+                (provider, stat_date, success_count, failure_count, total_latency_ms, routed_volume)
+             VALUES ($1, (NOW() AT TIME ZONE 'UTC')::DATE, $2, $3, $4, $5)
+             ON CONFLICT (provider, stat_date) DO UPDATE SET
+                success_count = provider_routing_stats.success_count + EXCLUDED.success_count,
+                failure_count = provider_routing_stats.failure_count + EXCLUDED.failure_count,
+                total_latency_ms = provider_routing_stats.total_latency_ms + EXCLUDED.total_latency_ms,
+                routed_volume = provider_routing_stats.routed_volume + EXCLUDED.routed_volume",
+        )
+        .bind(provider)
+        .bind(i64::from(succeeded))
+        .bind(i64::from(!succeeded))
+        .bind(latency_ms.max(0))
+        .bind(routed)
+        .execute(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)?;
+        Ok(())
+    }
+
+    /// Set or clear (`None`) a provider's daily volume cap
+    pub async fn set_daily_cap(
+        &self,
+        provider: &str,
+        cap: Option<&BigDecimal>,
+    ) -> Result<bool, DatabaseError> {
+        let result = sqlx::query(
+            "UPDATE payment_provider_configs SET daily_volume_cap = $2 WHERE provider = $1",
+        )
+        .bind(provider)
+        .bind(cap)
+        .execute(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)?;
+        Ok(result.rows_affected() > 0)
+    }
+}
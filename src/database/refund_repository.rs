@@ -0,0 +1,169 @@
+use crate::database::error::DatabaseError;
+use serde::{Deserialize, Serialize};
+use sqlx::{types::BigDecimal, FromRow, PgPool};
+use uuid::Uuid;
+
+/// A fiat refund of an onramp payment
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Refund {
+    pub id: Uuid,
+    pub transaction_id: Uuid,
+    pub provider: String,
+    pub provider_refund_id: Option<String>,
+    /// pending, processing, processed or failed
+    pub status: String,
+    /// missing_trustline, invalid_wallet, insufficient_liquidity or other
+    pub reason: String,
+    pub amount: BigDecimal,
+    /// Provider fee withheld from the refund
+    pub provider_fee: BigDecimal,
+    pub currency: String,
+    pub requested_by: String,
+    pub note: Option<String>,
+    pub failure_reason: Option<String>,
+    pub provider_data: serde_json::Value,
+    pub processed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewRefund {
+    pub transaction_id: Uuid,
+    pub provider: String,
+    pub reason: String,
+    pub amount: BigDecimal,
+    pub provider_fee: BigDecimal,
+    pub currency: String,
+    pub requested_by: String,
+    pub note: Option<String>,
+}
+
+const REFUND_COLUMNS: &str = "id, transaction_id, provider, provider_refund_id, status, reason, \
+     amount, provider_fee, currency, requested_by, note, failure_reason, provider_data, \
+     processed_at, created_at, updated_at";
+
+/// Repository for refunds
+pub struct RefundRepository {
+    pool: PgPool,
+}
+
+impl RefundRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Record a refund attempt. Returns `None` if the transaction already has
+    /// one that has not failed.
+    pub async fn create(&self, refund: &NewRefund) -> Result<Option<Refund>, DatabaseError> {
+        sqlx::query_as::<_, Refund>(&format!(
+            "INSERT INTO refunds
+                (transaction_id, provider, reason, amount, provider_fee, currency, requested_by,
+                 note)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+             ON CONFLICT (transaction_id) WHERE status <> 'failed' DO NOTHING
+             RETURNING {}",
+            REFUND_COLUMNS
+        ))
+        .bind(refund.transaction_id)
+        .bind(&refund.provider)
+        .bind(&refund.reason)
+        .bind(&refund.amount)
+        .bind(&refund.provider_fee)
+        .bind(&refund.currency)
+        .bind(&refund.requested_by)
+        .bind(&refund.note)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// The transaction's refund that is in flight or processed, if any
+    pub async fn find_active(&self, transaction_id: Uuid) -> Result<Option<Refund>, DatabaseError> {
+        sqlx::query_as::<_, Refund>(&format!(
+            "SELECT {} FROM refunds WHERE transaction_id = $1 AND status <> 'failed'",
+            REFUND_COLUMNS
+        ))
+        .bind(transaction_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Every refund attempt for a transaction, oldest first
+    pub async fn find_by_transaction(
+        &self,
+        transaction_id: Uuid,
+    ) -> Result<Vec<Refund>, DatabaseError> {
+        sqlx::query_as::<_, Refund>(&format!(
+            "SELECT {} FROM refunds WHERE transaction_id = $1 ORDER BY created_at",
+            REFUND_COLUMNS
+        ))
+        .bind(transaction_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Record the provider's acceptance of a refund and where it stands
+    pub async fn mark_submitted(
+        &self,
+        id: Uuid,
+        status: &str,
+        provider_refund_id: Option<&str>,
+        provider_data: &serde_json::Value,
+    ) -> Result<Refund, DatabaseError> {
+        sqlx::query_as::<_, Refund>(&format!(
+            "UPDATE refunds SET
+                status = $2,
+                provider_refund_id = COALESCE($3, provider_refund_id),
+                provider_data = $4,
+                processed_at = CASE WHEN $2 = 'processed' THEN NOW() ELSE processed_at END
+             WHERE id = $1
+             RETURNING {}",
+            REFUND_COLUMNS
+        ))
+        .bind(id)
+        .bind(status)
+        .bind(provider_refund_id)
+        .bind(provider_data)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Mark an in-flight refund processed. Returns `None` if it was already
+    /// processed or has failed.
+    pub async fn mark_processed(&self, id: Uuid) -> Result<Option<Refund>, DatabaseError> {
+        sqlx::query_as::<_, Refund>(&format!(
+            "UPDATE refunds SET status = 'processed', processed_at = NOW()
+             WHERE id = $1 AND status IN ('pending', 'processing')
+             RETURNING {}",
+            REFUND_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Mark an in-flight refund failed, freeing the transaction for another
+    /// attempt. Returns `None` if it was already processed or failed.
+    pub async fn mark_failed(
+        &self,
+        id: Uuid,
+        failure_reason: &str,
+    ) -> Result<Option<Refund>, DatabaseError> {
+        sqlx::query_as::<_, Refund>(&format!(
+            "UPDATE refunds SET status = 'failed', failure_reason = $2
+             WHERE id = $1 AND status IN ('pending', 'processing')
+             RETURNING {}",
+            REFUND_COLUMNS
+        ))
+        .bind(id)
+        .bind(failure_reason)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+}
@@ -0,0 +1,336 @@
+use crate::database::error::{DatabaseError, DatabaseErrorKind};
+use crate::database::repository::{Repository, TransactionalRepository};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sqlx::{types::BigDecimal, FromRow, PgPool};
+use uuid::Uuid;
+
+/// Treasury transaction awaiting or past multisig approval
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct TreasuryTransaction {
+    pub id: Uuid,
+    pub transaction_id: Option<Uuid>,
+    pub purpose: String,
+    pub source_account: String,
+    pub destination: String,
+    pub amount: BigDecimal,
+    pub asset_code: String,
+    pub asset_issuer: String,
+    pub memo: serde_json::Value,
+    pub sequence: i64,
+    pub transaction_hash: String,
+    pub unsigned_envelope_xdr: String,
+    pub status: String,
+    pub submitted_hash: Option<String>,
+    pub error_message: Option<String>,
+    pub requested_by: Option<String>,
+    pub metadata: serde_json::Value,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// A single signer's contribution to a treasury transaction
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct TreasurySignature {
+    pub id: Uuid,
+    pub treasury_transaction_id: Uuid,
+    pub signer_public_key: String,
+    pub signature: String,
+    pub source: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Fields needed to record a new treasury transaction
+#[derive(Debug, Clone)]
+pub struct NewTreasuryTransaction {
+    pub transaction_id: Option<Uuid>,
+    pub purpose: String,
+    pub source_account: String,
+    pub destination: String,
+    pub amount: BigDecimal,
+    pub asset_code: String,
+    pub asset_issuer: String,
+    pub memo: serde_json::Value,
+    pub sequence: i64,
+    pub transaction_hash: String,
+    pub unsigned_envelope_xdr: String,
+    pub requested_by: Option<String>,
+    pub metadata: serde_json::Value,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+const TREASURY_COLUMNS: &str = "id, transaction_id, purpose, source_account, destination, amount, \
+     asset_code, asset_issuer, memo, sequence, transaction_hash, unsigned_envelope_xdr, status, \
+     submitted_hash, error_message, requested_by, metadata, expires_at, created_at, updated_at";
+
+/// Repository for treasury multisig transactions and their signatures
+pub struct TreasuryRepository {
+    pool: PgPool,
+}
+
+impl TreasuryRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Record a freshly built treasury transaction in `pending` status
+    pub async fn create(
+        &self,
+        new: NewTreasuryTransaction,
+    ) -> Result<TreasuryTransaction, DatabaseError> {
+        sqlx::query_as::<_, TreasuryTransaction>(&format!(
+            "INSERT INTO treasury_transactions
+             (transaction_id, purpose, source_account, destination, amount, asset_code,
+              asset_issuer, memo, sequence, transaction_hash, unsigned_envelope_xdr,
+              requested_by, metadata, expires_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+             RETURNING {}",
+            TREASURY_COLUMNS
+        ))
+        .bind(new.transaction_id)
+        .bind(new.purpose)
+        .bind(new.source_account)
+        .bind(new.destination)
+        .bind(new.amount)
+        .bind(new.asset_code)
+        .bind(new.asset_issuer)
+        .bind(new.memo)
+        .bind(new.sequence)
+        .bind(new.transaction_hash)
+        .bind(new.unsigned_envelope_xdr)
+        .bind(new.requested_by)
+        .bind(new.metadata)
+        .bind(new.expires_at)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Find the open treasury transaction linked to a business transaction
+    pub async fn find_pending_by_transaction_id(
+        &self,
+        transaction_id: Uuid,
+    ) -> Result<Option<TreasuryTransaction>, DatabaseError> {
+        sqlx::query_as::<_, TreasuryTransaction>(&format!(
+            "SELECT {} FROM treasury_transactions
+             WHERE transaction_id = $1 AND status IN ('pending', 'submitting')
+             ORDER BY created_at DESC LIMIT 1",
+            TREASURY_COLUMNS
+        ))
+        .bind(transaction_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// List treasury transactions by status, newest first
+    pub async fn list_by_status(
+        &self,
+        status: &str,
+        limit: i64,
+    ) -> Result<Vec<TreasuryTransaction>, DatabaseError> {
+        sqlx::query_as::<_, TreasuryTransaction>(&format!(
+            "SELECT {} FROM treasury_transactions
+             WHERE status = $1
+             ORDER BY created_at DESC LIMIT $2",
+            TREASURY_COLUMNS
+        ))
+        .bind(status)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Claim a pending treasury transaction for submission. Returns `None` if
+    /// it is no longer pending, is being submitted elsewhere or has expired.
+    pub async fn claim_submission(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<TreasuryTransaction>, DatabaseError> {
+        sqlx::query_as::<_, TreasuryTransaction>(&format!(
+            "UPDATE treasury_transactions
+             SET status = 'submitting', error_message = NULL
+             WHERE id = $1 AND status = 'pending' AND expires_at > NOW()
+             RETURNING {}",
+            TREASURY_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Record how a claimed submission ended. Returns `None` if the
+    /// transaction was not being submitted.
+    pub async fn finish_submission(
+        &self,
+        id: Uuid,
+        status: &str,
+        submitted_hash: Option<&str>,
+        error_message: Option<&str>,
+    ) -> Result<Option<TreasuryTransaction>, DatabaseError> {
+        sqlx::query_as::<_, TreasuryTransaction>(&format!(
+            "UPDATE treasury_transactions
+             SET status = $2, submitted_hash = COALESCE($3, submitted_hash), error_message = $4
+             WHERE id = $1 AND status = 'submitting'
+             RETURNING {}",
+            TREASURY_COLUMNS
+        ))
+        .bind(id)
+        .bind(status)
+        .bind(submitted_hash)
+        .bind(error_message)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Expire pending treasury transactions whose envelope time bound has passed
+    pub async fn mark_expired(&self) -> Result<u64, DatabaseError> {
+        let result = sqlx::query(
+            "UPDATE treasury_transactions SET status = 'expired'
+             WHERE status = 'pending' AND expires_at < NOW()",
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)?;
+        Ok(result.rows_affected())
+    }
+
+    /// Store a signer's signature, replacing any earlier one from the same key
+    pub async fn upsert_signature(
+        &self,
+        treasury_transaction_id: Uuid,
+        signer_public_key: &str,
+        signature: &str,
+        source: &str,
+    ) -> Result<TreasurySignature, DatabaseError> {
+        sqlx::query_as::<_, TreasurySignature>(
+            "INSERT INTO treasury_signatures
+             (treasury_transaction_id, signer_public_key, signature, source)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (treasury_transaction_id, signer_public_key)
+             DO UPDATE SET signature = EXCLUDED.signature, source = EXCLUDED.source
+             RETURNING id, treasury_transaction_id, signer_public_key, signature, source, created_at",
+        )
+        .bind(treasury_transaction_id)
+        .bind(signer_public_key)
+        .bind(signature)
+        .bind(source)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// All signatures collected so far for a treasury transaction
+    pub async fn list_signatures(
+        &self,
+        treasury_transaction_id: Uuid,
+    ) -> Result<Vec<TreasurySignature>, DatabaseError> {
+        sqlx::query_as::<_, TreasurySignature>(
+            "SELECT id, treasury_transaction_id, signer_public_key, signature, source, created_at
+             FROM treasury_signatures
+             WHERE treasury_transaction_id = $1
+             ORDER BY created_at ASC",
+        )
+        .bind(treasury_transaction_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+}
+
+#[async_trait]
+impl Repository for TreasuryRepository {
+    type Entity = TreasuryTransaction;
+
+    async fn find_by_id(&self, id: &str) -> Result<Option<Self::Entity>, DatabaseError> {
+        let uuid = Uuid::parse_str(id).map_err(|e| {
+            DatabaseError::new(DatabaseErrorKind::Unknown {
+                message: format!("Invalid UUID: {}", e),
+            })
+        })?;
+        sqlx::query_as::<_, TreasuryTransaction>(&format!(
+            "SELECT {} FROM treasury_transactions WHERE id = $1",
+            TREASURY_COLUMNS
+        ))
+        .bind(uuid)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    async fn find_all(&self) -> Result<Vec<Self::Entity>, DatabaseError> {
+        sqlx::query_as::<_, TreasuryTransaction>(&format!(
+            "SELECT {} FROM treasury_transactions ORDER BY created_at DESC",
+            TREASURY_COLUMNS
+        ))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    async fn insert(&self, entity: &Self::Entity) -> Result<Self::Entity, DatabaseError> {
+        self.create(NewTreasuryTransaction {
+            transaction_id: entity.transaction_id,
+            purpose: entity.purpose.clone(),
+            source_account: entity.source_account.clone(),
+            destination: entity.destination.clone(),
+            amount: entity.amount.clone(),
+            asset_code: entity.asset_code.clone(),
+            asset_issuer: entity.asset_issuer.clone(),
+            memo: entity.memo.clone(),
+            sequence: entity.sequence,
+            transaction_hash: entity.transaction_hash.clone(),
+            unsigned_envelope_xdr: entity.unsigned_envelope_xdr.clone(),
+            requested_by: entity.requested_by.clone(),
+            metadata: entity.metadata.clone(),
+            expires_at: entity.expires_at,
+        })
+        .await
+    }
+
+    async fn update(&self, id: &str, entity: &Self::Entity) -> Result<Self::Entity, DatabaseError> {
+        let uuid = Uuid::parse_str(id).map_err(|e| {
+            DatabaseError::new(DatabaseErrorKind::Unknown {
+                message: format!("Invalid UUID: {}", e),
+            })
+        })?;
+        sqlx::query_as::<_, TreasuryTransaction>(&format!(
+            "UPDATE treasury_transactions
+             SET status = $2, submitted_hash = COALESCE($3, submitted_hash), error_message = $4
+             WHERE id = $1
+             RETURNING {}",
+            TREASURY_COLUMNS
+        ))
+        .bind(uuid)
+        .bind(&entity.status)
+        .bind(entity.submitted_hash.as_deref())
+        .bind(entity.error_message.as_deref())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    async fn delete(&self, id: &str) -> Result<bool, DatabaseError> {
+        let uuid = Uuid::parse_str(id).map_err(|e| {
+            DatabaseError::new(DatabaseErrorKind::Unknown {
+                message: format!("Invalid UUID: {}", e),
+            })
+        })?;
+        let result = sqlx::query("DELETE FROM treasury_transactions WHERE id = $1")
+            .bind(uuid)
+            .execute(&self.pool)
+            .await
+            .map_err(DatabaseError::from_sqlx)?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+impl TransactionalRepository for TreasuryRepository {
+    fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+}
@@ -0,0 +1,115 @@
+use crate::database::error::DatabaseError;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+/// A risk action against a wallet: `flag` marks it for review, `freeze`
+/// blocks new quotes until cleared
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct WalletRiskFlag {
+    pub id: Uuid,
+    pub wallet_address: String,
+    pub action: String,
+    pub reason: String,
+    pub dispute_id: Option<Uuid>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub cleared_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub cleared_by: Option<String>,
+}
+
+const WALLET_RISK_FLAG_COLUMNS: &str =
+    "id, wallet_address, action, reason, dispute_id, created_at, cleared_at, cleared_by";
+
+/// Repository for wallet risk flags
+pub struct WalletRiskRepository {
+    pool: PgPool,
+}
+
+impl WalletRiskRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn raise(
+        &self,
+        wallet_address: &str,
+        action: &str,
+        reason: &str,
+        dispute_id: Option<Uuid>,
+    ) -> Result<WalletRiskFlag, DatabaseError> {
+        sqlx::query_as::<_, WalletRiskFlag>(&format!(
+            "INSERT INTO wallet_risk_flags (wallet_address, action, reason, dispute_id)
+             VALUES ($1, $2, $3, $4)
+             RETURNING {}",
+            WALLET_RISK_FLAG_COLUMNS
+        ))
+        .bind(wallet_address)
+        .bind(action)
+        .bind(reason)
+        .bind(dispute_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Whether a flag was already raised for `dispute_id`
+    pub async fn exists_for_dispute(&self, dispute_id: Uuid) -> Result<bool, DatabaseError> {
+        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM wallet_risk_flags WHERE dispute_id = $1)")
+            .bind(dispute_id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Every flag raised against a wallet, newest first
+    pub async fn find_by_wallet(
+        &self,
+        wallet_address: &str,
+    ) -> Result<Vec<WalletRiskFlag>, DatabaseError> {
+        sqlx::query_as::<_, WalletRiskFlag>(&format!(
+            "SELECT {} FROM wallet_risk_flags WHERE wallet_address = $1 ORDER BY created_at DESC",
+            WALLET_RISK_FLAG_COLUMNS
+        ))
+        .bind(wallet_address)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// The active freeze on a wallet, if any
+    pub async fn active_freeze(
+        &self,
+        wallet_address: &str,
+    ) -> Result<Option<WalletRiskFlag>, DatabaseError> {
+        sqlx::query_as::<_, WalletRiskFlag>(&format!(
+            "SELECT {} FROM wallet_risk_flags
+             WHERE wallet_address = $1 AND action = 'freeze' AND cleared_at IS NULL
+             ORDER BY created_at DESC
+             LIMIT 1",
+            WALLET_RISK_FLAG_COLUMNS
+        ))
+        .bind(wallet_address)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Lift a flag. Returns `None` if it does not exist or was already cleared.
+    pub async fn clear(
+        &self,
+        id: Uuid,
+        cleared_by: &str,
+    ) -> Result<Option<WalletRiskFlag>, DatabaseError> {
+        sqlx::query_as::<_, WalletRiskFlag>(&format!(
+            "UPDATE wallet_risk_flags SET cleared_at = NOW(), cleared_by = $2
+             WHERE id = $1 AND cleared_at IS NULL
+             RETURNING {}",
+            WALLET_RISK_FLAG_COLUMNS
+        ))
+        .bind(id)
+        .bind(cleared_by)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+}
@@ -20,8 +20,18 @@ pub struct WebhookEvent {
     pub error_message: Option<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
+    /// Request body exactly as received; `None` for events logged internally
+    pub raw_body: Option<Vec<u8>>,
+    pub next_attempt_at: chrono::DateTime<chrono::Utc>,
+    pub locked_until: Option<chrono::DateTime<chrono::Utc>>,
+    /// Queue worker holding the lease
+    pub locked_by: Option<String>,
 }
 
+const WEBHOOK_EVENT_COLUMNS: &str = "id, event_id, provider, event_type, payload, signature, status, \
+    transaction_id, processed_at, retry_count, error_message, created_at, updated_at, raw_body, \
+    next_attempt_at, locked_until, locked_by";
+
 /// Webhook Repository for webhook event storage and tracking
 pub struct WebhookRepository {
     pool: PgPool,
@@ -32,7 +42,8 @@ impl WebhookRepository {
         Self { pool }
     }
 
-    /// Log a new webhook event
+    /// Log an event observed outside a provider webhook, e.g. by the
+    /// transaction monitor
     pub async fn log_event(
         &self,
         event_id: &str,
@@ -42,12 +53,13 @@ impl WebhookRepository {
         signature: Option<&str>,
         transaction_id: Option<Uuid>,
     ) -> Result<WebhookEvent, DatabaseError> {
-        let inserted = sqlx::query_as::<_, WebhookEvent>(
+        let inserted = sqlx::query_as::<_, WebhookEvent>(&format!(
             "INSERT INTO webhook_events (event_id, provider, event_type, payload, signature, transaction_id, status) 
              VALUES ($1, $2, $3, $4, $5, $6, 'pending') 
              ON CONFLICT (provider, event_id) DO NOTHING
-             RETURNING id, event_id, provider, event_type, payload, signature, status, transaction_id, processed_at, retry_count, error_message, created_at, updated_at",
-        )
+             RETURNING {}",
+            WEBHOOK_EVENT_COLUMNS
+        ))
         .bind(event_id)
         .bind(provider)
         .bind(event_type)
@@ -62,76 +74,219 @@ impl WebhookRepository {
             return Ok(event);
         }
 
-        sqlx::query_as::<_, WebhookEvent>(
-            "SELECT id, event_id, provider, event_type, payload, signature, status, transaction_id, processed_at, retry_count, error_message, created_at, updated_at 
-             FROM webhook_events 
-             WHERE provider = $1 AND event_id = $2",
-        )
+        self.find_by_event_id(provider, event_id)
+            .await?
+            .ok_or_else(|| DatabaseError::new(DatabaseErrorKind::NotFound {
+                entity: "webhook_event".to_string(),
+                id: event_id.to_string(),
+            }))
+    }
+
+    /// Queue a verified provider webhook for processing. Returns `None` if
+    /// the provider already delivered this event.
+    pub async fn enqueue(
+        &self,
+        event_id: &str,
+        provider: &str,
+        event_type: &str,
+        payload: serde_json::Value,
+        raw_body: &[u8],
+        signature: Option<&str>,
+    ) -> Result<Option<WebhookEvent>, DatabaseError> {
+        sqlx::query_as::<_, WebhookEvent>(&format!(
+            "INSERT INTO webhook_events (event_id, provider, event_type, payload, raw_body, signature, status, next_attempt_at) 
+             VALUES ($1, $2, $3, $4, $5, $6, 'pending', NOW()) 
+             ON CONFLICT (provider, event_id) DO NOTHING
+             RETURNING {}",
+            WEBHOOK_EVENT_COLUMNS
+        ))
+        .bind(event_id)
+        .bind(provider)
+        .bind(event_type)
+        .bind(payload)
+        .bind(raw_body)
+        .bind(signature)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    pub async fn find_by_event_id(
+        &self,
+        provider: &str,
+        event_id: &str,
+    ) -> Result<Option<WebhookEvent>, DatabaseError> {
+        sqlx::query_as::<_, WebhookEvent>(&format!(
+            "SELECT {} FROM webhook_events WHERE provider = $1 AND event_id = $2",
+            WEBHOOK_EVENT_COLUMNS
+        ))
         .bind(provider)
         .bind(event_id)
-        .fetch_one(&self.pool)
+        .fetch_optional(&self.pool)
         .await
         .map_err(DatabaseError::from_sqlx)
     }
 
-    /// Get pending webhook events
-    pub async fn get_pending_events(&self, limit: i64) -> Result<Vec<WebhookEvent>, DatabaseError> {
-        sqlx::query_as::<_, WebhookEvent>(
-            "SELECT id, event_id, provider, event_type, payload, signature, status, transaction_id, processed_at, retry_count, error_message, created_at, updated_at 
-             FROM webhook_events 
-             WHERE status = 'pending' AND retry_count < 5 
-             ORDER BY created_at ASC LIMIT $1",
-        )
+    /// Lease up to `limit` due events from `providers` to `worker` until
+    /// `lease` has passed. Rows other workers hold are skipped, and events
+    /// whose lease ran out mid-processing are claimed again, counting the
+    /// lost attempt, while they have attempts left out of `max_attempts`.
+    pub async fn claim_due(
+        &self,
+        providers: &[String],
+        worker: &str,
+        limit: i64,
+        lease: std::time::Duration,
+        max_attempts: i32,
+    ) -> Result<Vec<WebhookEvent>, DatabaseError> {
+        sqlx::query_as::<_, WebhookEvent>(&format!(
+            "UPDATE webhook_events
+             SET retry_count = retry_count + CASE WHEN status = 'processing' THEN 1 ELSE 0 END,
+                 status = 'processing',
+                 locked_by = $2,
+                 locked_until = NOW() + make_interval(secs => $4)
+             WHERE id IN (
+                 SELECT id FROM webhook_events
+                 WHERE provider = ANY($1)
+                   AND ((status = 'pending' AND next_attempt_at <= NOW())
+                        OR (status = 'processing' AND locked_until < NOW()
+                            AND retry_count + 1 < $5))
+                 ORDER BY next_attempt_at ASC
+                 LIMIT $3
+                 FOR UPDATE SKIP LOCKED
+             )
+             RETURNING {}",
+            WEBHOOK_EVENT_COLUMNS
+        ))
+        .bind(providers)
+        .bind(worker)
         .bind(limit)
+        .bind(lease.as_secs_f64())
+        .bind(max_attempts)
         .fetch_all(&self.pool)
         .await
         .map_err(DatabaseError::from_sqlx)
     }
 
-    /// Mark webhook event as processing
-    pub async fn mark_processing(&self, id: Uuid) -> Result<WebhookEvent, DatabaseError> {
-        sqlx::query_as::<_, WebhookEvent>(
-            "UPDATE webhook_events 
-             SET status = 'processing' 
-             WHERE id = $1 AND status = 'pending'
-             RETURNING id, event_id, provider, event_type, payload, signature, status, transaction_id, processed_at, retry_count, error_message, created_at, updated_at",
+    /// Dead-letter events whose lease ran out on their last of
+    /// `max_attempts` attempts
+    pub async fn dead_letter_expired(&self, max_attempts: i32) -> Result<u64, DatabaseError> {
+        let result = sqlx::query(
+            "UPDATE webhook_events
+             SET status = 'dead_lettered',
+                 retry_count = retry_count + 1,
+                 error_message = COALESCE(error_message || '; ', '') || 'lease expired on final attempt',
+                 locked_by = NULL,
+                 locked_until = NULL,
+                 processed_at = NOW()
+             WHERE status = 'processing'
+               AND locked_until < NOW()
+               AND retry_count + 1 >= $1",
         )
-        .bind(id)
-        .fetch_one(&self.pool)
+        .bind(max_attempts)
+        .execute(&self.pool)
         .await
-        .map_err(DatabaseError::from_sqlx)
+        .map_err(DatabaseError::from_sqlx)?;
+
+        Ok(result.rows_affected())
     }
 
-    /// Mark webhook event as processed
-    pub async fn mark_processed(&self, id: Uuid) -> Result<WebhookEvent, DatabaseError> {
-        sqlx::query_as::<_, WebhookEvent>(
-            "UPDATE webhook_events SET status = 'completed', processed_at = NOW() WHERE id = $1 
-             RETURNING id, event_id, provider, event_type, payload, signature, status, transaction_id, processed_at, retry_count, error_message, created_at, updated_at",
+    /// Extend the lease `worker` holds on an event. Returns `false` if the
+    /// event is no longer leased to `worker`.
+    pub async fn renew_lease(
+        &self,
+        id: Uuid,
+        worker: &str,
+        lease: std::time::Duration,
+    ) -> Result<bool, DatabaseError> {
+        let result = sqlx::query(
+            "UPDATE webhook_events
+             SET locked_until = NOW() + make_interval(secs => $3)
+             WHERE id = $1 AND status = 'processing' AND locked_by = $2",
         )
         .bind(id)
-        .fetch_one(&self.pool)
+        .bind(worker)
+        .bind(lease.as_secs_f64())
+        .execute(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Mark an event `worker` holds as processed. Returns `None` if the
+    /// lease was lost to another worker.
+    pub async fn mark_processed(
+        &self,
+        id: Uuid,
+        worker: &str,
+    ) -> Result<Option<WebhookEvent>, DatabaseError> {
+        sqlx::query_as::<_, WebhookEvent>(&format!(
+            "UPDATE webhook_events
+             SET status = 'completed', processed_at = NOW(), locked_by = NULL, locked_until = NULL,
+                 error_message = NULL
+             WHERE id = $1 AND status = 'processing' AND locked_by = $2
+             RETURNING {}",
+            WEBHOOK_EVENT_COLUMNS
+        ))
+        .bind(id)
+        .bind(worker)
+        .fetch_optional(&self.pool)
         .await
         .map_err(DatabaseError::from_sqlx)
     }
 
-    /// Record webhook processing failure
+    /// Record a processing failure of an event `worker` holds, scheduling the
+    /// next attempt or moving the event to the dead-letter status. Returns
+    /// `None` if the lease was lost to another worker.
     pub async fn record_failure(
         &self,
         id: Uuid,
+        worker: &str,
         error: &str,
-    ) -> Result<WebhookEvent, DatabaseError> {
-        sqlx::query_as::<_, WebhookEvent>(
+        next_attempt_at: chrono::DateTime<chrono::Utc>,
+        dead_letter: bool,
+    ) -> Result<Option<WebhookEvent>, DatabaseError> {
+        sqlx::query_as::<_, WebhookEvent>(&format!(
             "UPDATE webhook_events 
              SET retry_count = retry_count + 1, 
-                 error_message = $2, 
-                 status = CASE WHEN retry_count + 1 >= 5 THEN 'failed' ELSE 'pending' END,
-                 processed_at = CASE WHEN retry_count + 1 >= 5 THEN NOW() ELSE processed_at END
-             WHERE id = $1 
-             RETURNING id, event_id, provider, event_type, payload, signature, status, transaction_id, processed_at, retry_count, error_message, created_at, updated_at",
-        )
+                 error_message = $3, 
+                 status = CASE WHEN $5 THEN 'dead_lettered' ELSE 'pending' END,
+                 next_attempt_at = $4,
+                 locked_by = NULL,
+                 locked_until = NULL,
+                 processed_at = CASE WHEN $5 THEN NOW() ELSE processed_at END
+             WHERE id = $1 AND status = 'processing' AND locked_by = $2
+             RETURNING {}",
+            WEBHOOK_EVENT_COLUMNS
+        ))
         .bind(id)
+        .bind(worker)
         .bind(error)
-        .fetch_one(&self.pool)
+        .bind(next_attempt_at)
+        .bind(dead_letter)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::from_sqlx)
+    }
+
+    /// Put a dead-lettered event back on the queue with a fresh attempt
+    /// budget. Returns `None` if the event is not dead-lettered.
+    pub async fn replay(&self, id: Uuid) -> Result<Option<WebhookEvent>, DatabaseError> {
+        sqlx::query_as::<_, WebhookEvent>(&format!(
+            "UPDATE webhook_events
+             SET status = 'pending',
+                 retry_count = 0,
+                 next_attempt_at = NOW(),
+                 locked_by = NULL,
+                 locked_until = NULL,
+                 processed_at = NULL
+             WHERE id = $1 AND status = 'dead_lettered'
+             RETURNING {}",
+            WEBHOOK_EVENT_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
         .await
         .map_err(DatabaseError::from_sqlx)
     }
@@ -142,12 +297,12 @@ impl WebhookRepository {
         provider: &str,
         limit: i64,
     ) -> Result<Vec<WebhookEvent>, DatabaseError> {
-        sqlx::query_as::<_, WebhookEvent>(
-            "SELECT id, event_id, provider, event_type, payload, signature, status, transaction_id, processed_at, retry_count, error_message, created_at, updated_at 
-             FROM webhook_events 
+        sqlx::query_as::<_, WebhookEvent>(&format!(
+            "SELECT {} FROM webhook_events 
              WHERE provider = $1 
              ORDER BY created_at DESC LIMIT $2",
-        )
+            WEBHOOK_EVENT_COLUMNS
+        ))
         .bind(provider)
         .bind(limit)
         .fetch_all(&self.pool)
@@ -155,14 +310,19 @@ impl WebhookRepository {
         .map_err(DatabaseError::from_sqlx)
     }
 
-    /// Get failed webhook events
-    pub async fn get_failed_events(&self, limit: i64) -> Result<Vec<WebhookEvent>, DatabaseError> {
-        sqlx::query_as::<_, WebhookEvent>(
-            "SELECT id, event_id, provider, event_type, payload, signature, status, transaction_id, processed_at, retry_count, error_message, created_at, updated_at 
-             FROM webhook_events 
-             WHERE status = 'failed' 
-             ORDER BY created_at DESC LIMIT $1",
-        )
+    /// Dead-lettered events, most recently given up on first
+    pub async fn get_dead_lettered(
+        &self,
+        provider: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<WebhookEvent>, DatabaseError> {
+        sqlx::query_as::<_, WebhookEvent>(&format!(
+            "SELECT {} FROM webhook_events 
+             WHERE status = 'dead_lettered' AND ($1::TEXT IS NULL OR provider = $1)
+             ORDER BY updated_at DESC LIMIT $2",
+            WEBHOOK_EVENT_COLUMNS
+        ))
+        .bind(provider)
         .bind(limit)
         .fetch_all(&self.pool)
         .await
@@ -180,10 +340,11 @@ impl Repository for WebhookRepository {
                 message: format!("Invalid UUID: {}", e),
             })
         })?;
-        sqlx::query_as::<_, WebhookEvent>(
-             "SELECT id, event_id, provider, event_type, payload, signature, status, transaction_id, processed_at, retry_count, error_message, created_at, updated_at 
+        sqlx::query_as::<_, WebhookEvent>(&format!(
+             "SELECT {} 
              FROM webhook_events WHERE id = $1",
-        )
+            WEBHOOK_EVENT_COLUMNS
+        ))
         .bind(uuid)
         .fetch_optional(&self.pool)
         .await
@@ -191,21 +352,23 @@ impl Repository for WebhookRepository {
     }
 
     async fn find_all(&self) -> Result<Vec<Self::Entity>, DatabaseError> {
-        sqlx::query_as::<_, WebhookEvent>(
-            "SELECT id, event_id, provider, event_type, payload, signature, status, transaction_id, processed_at, retry_count, error_message, created_at, updated_at 
+        sqlx::query_as::<_, WebhookEvent>(&format!(
+            "SELECT {} 
              FROM webhook_events ORDER BY created_at DESC",
-        )
+            WEBHOOK_EVENT_COLUMNS
+        ))
         .fetch_all(&self.pool)
         .await
         .map_err(DatabaseError::from_sqlx)
     }
 
     async fn insert(&self, entity: &Self::Entity) -> Result<Self::Entity, DatabaseError> {
-        sqlx::query_as::<_, WebhookEvent>(
+        sqlx::query_as::<_, WebhookEvent>(&format!(
             "INSERT INTO webhook_events (event_id, provider, event_type, payload, signature, status, transaction_id, processed_at, retry_count, error_message, created_at) 
              VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) 
-             RETURNING id, event_id, provider, event_type, payload, signature, status, transaction_id, processed_at, retry_count, error_message, created_at, updated_at",
-        )
+             RETURNING {}",
+            WEBHOOK_EVENT_COLUMNS
+        ))
         .bind(&entity.event_id)
         .bind(&entity.provider)
         .bind(&entity.event_type)
@@ -228,12 +391,13 @@ impl Repository for WebhookRepository {
                 message: format!("Invalid UUID: {}", e),
             })
         })?;
-        sqlx::query_as::<_, WebhookEvent>(
+        sqlx::query_as::<_, WebhookEvent>(&format!(
             "UPDATE webhook_events 
              SET event_id = $1, provider = $2, event_type = $3, payload = $4, signature = $5, status = $6, transaction_id = $7, processed_at = $8, retry_count = $9, error_message = $10 
              WHERE id = $11 
-             RETURNING id, event_id, provider, event_type, payload, signature, status, transaction_id, processed_at, retry_count, error_message, created_at, updated_at",
-        )
+             RETURNING {}",
+            WEBHOOK_EVENT_COLUMNS
+        ))
         .bind(&entity.event_id)
         .bind(&entity.provider)
         .bind(&entity.event_type)
@@ -43,8 +43,20 @@ pub enum ErrorCode {
     InvalidWallet,
     #[serde(rename = "DUPLICATE_TRANSACTION")]
     DuplicateTransaction,
-    #[serde(rename = "INSUFFICIENT_LIQUIDITY")]
-    InsufficientLiquidity,
+    #[serde(rename = "WALLET_FROZEN")]
+    WalletFrozen,
+    #[serde(rename = "DISPUTE_CLOSED")]
+    DisputeClosed,
+    #[serde(rename = "REFUND_NOT_ALLOWED")]
+    RefundNotAllowed,
+    #[serde(rename = "INVALID_JOB_STATE")]
+    InvalidJobState,
+    #[serde(rename = "PAYOUT_REVIEW_CLOSED")]
+    PayoutReviewClosed,
+    #[serde(rename = "SUSPENSE_PAYMENT_CLOSED")]
+    SuspensePaymentClosed,
+    #[serde(rename = "TRANSACTION_NOT_AWAITING_PAYMENT")]
+    TransactionNotAwaitingPayment,
 
     // Infrastructure errors (5xx)
     #[serde(rename = "DATABASE_ERROR")]
@@ -101,6 +113,33 @@ pub enum DomainError {
     },
     /// Insufficient cNGN liquidity on Stellar for onramp
     InsufficientLiquidity { amount: String },
+    /// Wallet is frozen by risk controls
+    WalletFrozen {
+        wallet_address: String,
+        reason: String,
+    },
+    /// Dispute has already been responded to or resolved
+    DisputeClosed { dispute_id: String, status: String },
+    /// Transaction is not in a state that can be refunded
+    RefundNotAllowed {
+        transaction_id: String,
+        status: String,
+    },
+    /// Background job is not in a state that allows the requested action
+    InvalidJobState {
+        job_id: String,
+        status: String,
+        action: String,
+    },
+    /// Payout review has already been resolved
+    PayoutReviewClosed { review_id: String, status: String },
+    /// Suspense payment has already been matched or refunded
+    SuspensePaymentClosed { payment_id: String, status: String },
+    /// Transaction is not waiting for an incoming cNGN payment
+    TransactionNotAwaitingPayment {
+        transaction_id: String,
+        status: String,
+    },
 }
 
 /// Infrastructure-level errors (database, cache, configuration)
@@ -209,6 +248,13 @@ impl AppError {
                 DomainError::DuplicateTransaction { .. } => 409, // Conflict
                 DomainError::TrustlineCreationFailed { .. } => 422,
                 DomainError::InsufficientLiquidity { .. } => 409, // Conflict
+                DomainError::WalletFrozen { .. } => 403,
+                DomainError::DisputeClosed { .. } => 409,
+                DomainError::RefundNotAllowed { .. } => 409,
+                DomainError::InvalidJobState { .. } => 409,
+                DomainError::PayoutReviewClosed { .. } => 409,
+                DomainError::SuspensePaymentClosed { .. } => 409,
+                DomainError::TransactionNotAwaitingPayment { .. } => 409,
             },
             AppErrorKind::Infrastructure(err) => match err {
                 InfrastructureError::Database { .. } => 500,
@@ -245,6 +291,15 @@ impl AppError {
                 DomainError::TrustlineCreationFailed { .. } => ErrorCode::TrustlineCreationFailed,
                 DomainError::InsufficientLiquidity { .. } => ErrorCode::InsufficientLiquidity,
                 DomainError::AmountTooLow { .. } => ErrorCode::AmountTooLow,
+                DomainError::WalletFrozen { .. } => ErrorCode::WalletFrozen,
+                DomainError::DisputeClosed { .. } => ErrorCode::DisputeClosed,
+                DomainError::RefundNotAllowed { .. } => ErrorCode::RefundNotAllowed,
+                DomainError::InvalidJobState { .. } => ErrorCode::InvalidJobState,
+                DomainError::PayoutReviewClosed { .. } => ErrorCode::PayoutReviewClosed,
+                DomainError::SuspensePaymentClosed { .. } => ErrorCode::SuspensePaymentClosed,
+                DomainError::TransactionNotAwaitingPayment { .. } => {
+                    ErrorCode::TransactionNotAwaitingPayment
+                }
             },
             AppErrorKind::Infrastructure(err) => match err {
                 InfrastructureError::Database { .. } => ErrorCode::DatabaseError,
@@ -321,6 +376,27 @@ impl AppError {
                 DomainError::AmountTooLow { .. } => {
                     "Minimum onramp amount is ₦1,000.".to_string()
                 }
+                DomainError::WalletFrozen { .. } => {
+                    "This wallet is temporarily restricted. Please contact support".to_string()
+                }
+                DomainError::DisputeClosed { dispute_id, status } => {
+                    format!("Dispute '{}' is already {}", dispute_id, status)
+                }
+                DomainError::RefundNotAllowed { transaction_id, status } => {
+                    format!("Transaction '{}' cannot be refunded while {}", transaction_id, status)
+                }
+                DomainError::InvalidJobState { job_id, status, action } => {
+                    format!("Job '{}' cannot be {} while {}", job_id, action, status)
+                }
+                DomainError::PayoutReviewClosed { review_id, status } => {
+                    format!("Payout review '{}' is already {}", review_id, status)
+                }
+                DomainError::SuspensePaymentClosed { payment_id, status } => {
+                    format!("Suspense payment '{}' is already {}", payment_id, status)
+                }
+                DomainError::TransactionNotAwaitingPayment { transaction_id, status } => {
+                    format!("Transaction '{}' is not awaiting payment while {}", transaction_id, status)
+                }
             },
             AppErrorKind::Infrastructure(_) => {
                 "Service temporarily unavailable. Please try again later".to_string()
@@ -436,6 +512,13 @@ impl std::error::Error for AppError {}
 // Conversions from specific error types
 // Note: From<DatabaseError> is implemented in database/error.rs to avoid circular dependency
 
+#[cfg(feature = "database")]
+impl From<ValidationError> for AppError {
+    fn from(err: ValidationError) -> Self {
+        AppError::new(AppErrorKind::Validation(err))
+    }
+}
+
 #[cfg(feature = "database")]
 impl From<StellarError> for AppError {
     fn from(err: StellarError) -> Self {
@@ -496,6 +579,54 @@ impl From<StellarError> for AppError {
     }
 }
 
+#[cfg(feature = "database")]
+impl From<crate::services::exchange_rate::ExchangeRateError> for AppError {
+    fn from(err: crate::services::exchange_rate::ExchangeRateError) -> Self {
+        use crate::services::exchange_rate::ExchangeRateError as ER;
+
+        let kind = match err {
+            ER::Database(e) => return e.into(),
+            ER::RateNotFound { from, to } => {
+                AppErrorKind::Validation(ValidationError::InvalidCurrency {
+                    currency: format!("{}/{}", from, to),
+                    reason: "No exchange rate available for this pair".to_string(),
+                })
+            }
+            ER::InvalidRate(message) | ER::InvalidAmount(message) => {
+                AppErrorKind::Validation(ValidationError::InvalidAmount {
+                    amount: String::new(),
+                    reason: message,
+                })
+            }
+            ER::InvalidHistoryQuery { field, reason } => {
+                return AppError::new(AppErrorKind::Validation(ValidationError::OutOfRange {
+                    field,
+                    min: None,
+                    max: None,
+                }))
+                .with_context(reason)
+            }
+            ER::ProviderError(message) => AppErrorKind::External(ExternalError::PaymentProvider {
+                provider: "rate_provider".to_string(),
+                message,
+                is_retryable: true,
+            }),
+            ER::StaleRate { from, to, reason } => {
+                AppErrorKind::External(ExternalError::PaymentProvider {
+                    provider: "rate_provider".to_string(),
+                    message: format!("Rate for {}/{} is stale: {}", from, to, reason),
+                    is_retryable: true,
+                })
+            }
+            ER::FeeCalculationError(message) => {
+                AppErrorKind::Infrastructure(InfrastructureError::Configuration { message })
+            }
+        };
+
+        AppError::new(kind)
+    }
+}
+
 /// Result type for operations that can fail with AppError
 #[cfg(feature = "database")]
 pub type AppResult<T> = Result<T, AppError>;
@@ -3,12 +3,16 @@
 
 use serde::Serialize;
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::time::timeout;
 use tracing::{error, info};
 
 use crate::cache::RedisCache;
 use crate::chains::stellar::client::StellarClient;
+use crate::database::exchange_rate_repository::ExchangeRateRepository;
+use crate::payments::circuit_breaker::{CircuitState, ProviderCircuitBreaker};
+use crate::payments::types::ProviderName;
 
 /// Health status response
 #[derive(Debug, Serialize, Clone)]
@@ -88,6 +92,10 @@ pub struct HealthChecker {
     db_pool: Option<sqlx::PgPool>,
     cache: Option<RedisCache>,
     stellar_client: Option<StellarClient>,
+    /// Report exchange rates not refreshed within this window
+    rate_stale_after: Option<Duration>,
+    /// Report payment providers whose circuit is not closed
+    payment_circuits: Option<(Arc<ProviderCircuitBreaker>, Vec<ProviderName>)>,
 }
 
 impl HealthChecker {
@@ -100,14 +108,33 @@ impl HealthChecker {
             db_pool,
             cache,
             stellar_client,
+            rate_stale_after: None,
+            payment_circuits: None,
         }
     }
 
+    /// Include exchange rate freshness in health checks
+    pub fn with_rate_freshness(mut self, stale_after: Duration) -> Self {
+        self.rate_stale_after = Some(stale_after);
+        self
+    }
+
+    /// Include payment provider circuit states in health checks
+    pub fn with_payment_circuits(
+        mut self,
+        breaker: Arc<ProviderCircuitBreaker>,
+        providers: Vec<ProviderName>,
+    ) -> Self {
+        self.payment_circuits = Some((breaker, providers));
+        self
+    }
+
     /// Perform comprehensive health check
     pub async fn check_health(&self) -> HealthStatus {
         let mut health_status = HealthStatus::new();
         let mut overall_healthy = true;
         let mut any_disabled = false;
+        let mut any_degraded = false;
 
         // Check database health
         if let Some(db_pool) = &self.db_pool {
@@ -225,9 +252,84 @@ impl HealthChecker {
             );
         }
 
+        // Check exchange rate freshness. Stale rates stop quoting but do not
+        // make the service unhealthy.
+        if let (Some(db_pool), Some(stale_after)) = (&self.db_pool, self.rate_stale_after) {
+            match timeout(
+                Duration::from_secs(5),
+                check_rate_freshness(db_pool, stale_after),
+            )
+            .await
+            {
+                Ok(Ok((response_time, stale))) if stale.is_empty() => {
+                    health_status.checks.insert(
+                        "exchange_rates".to_string(),
+                        ComponentHealth::up(Some(response_time)),
+                    );
+                }
+                Ok(Ok((response_time, stale))) => {
+                    any_degraded = true;
+                    health_status.checks.insert(
+                        "exchange_rates".to_string(),
+                        ComponentHealth::warning(
+                            Some(response_time),
+                            Some(format!("Stale rates: {}", stale.join(", "))),
+                        ),
+                    );
+                    error!("Exchange rate freshness check: stale {:?}", stale);
+                }
+                Ok(Err(e)) => {
+                    any_degraded = true;
+                    health_status.checks.insert(
+                        "exchange_rates".to_string(),
+                        ComponentHealth::warning(None, Some(e.to_string())),
+                    );
+                    error!("Exchange rate freshness check failed: {}", e);
+                }
+                Err(_) => {
+                    any_degraded = true;
+                    health_status.checks.insert(
+                        "exchange_rates".to_string(),
+                        ComponentHealth::warning(None, Some("Timeout".to_string())),
+                    );
+                    error!("Exchange rate freshness check timed out");
+                }
+            }
+        }
+
+        // Check payment provider circuits. Traffic fails over to providers
+        // with closed circuits, so an open one only degrades the service.
+        if let Some((breaker, providers)) = &self.payment_circuits {
+            let start = Instant::now();
+            let tripped: Vec<String> = breaker
+                .snapshots(providers)
+                .await
+                .into_iter()
+                .filter(|c| c.record.state != CircuitState::Closed)
+                .map(|c| format!("{} ({})", c.provider, c.record.state))
+                .collect();
+            let response_time = start.elapsed().as_millis();
+            if tripped.is_empty() {
+                health_status.checks.insert(
+                    "payment_providers".to_string(),
+                    ComponentHealth::up(Some(response_time)),
+                );
+            } else {
+                any_degraded = true;
+                health_status.checks.insert(
+                    "payment_providers".to_string(),
+                    ComponentHealth::warning(
+                        Some(response_time),
+                        Some(format!("Circuits not closed: {}", tripped.join(", "))),
+                    ),
+                );
+                error!("Payment provider circuits not closed: {:?}", tripped);
+            }
+        }
+
         // Set overall status
         health_status.status = if overall_healthy {
-            if any_disabled {
+            if any_disabled || any_degraded {
                 HealthState::Degraded
             } else {
                 HealthState::Healthy
@@ -253,6 +355,24 @@ pub async fn check_database_health(
     }
 }
 
+// Add a function to list stale exchange rates
+pub async fn check_rate_freshness(
+    pool: &sqlx::PgPool,
+    stale_after: Duration,
+) -> Result<(u128, Vec<String>), Box<dyn std::error::Error + Send + Sync>> {
+    let start = Instant::now();
+    let cutoff = chrono::Utc::now()
+        - chrono::Duration::from_std(stale_after).unwrap_or_else(|_| chrono::Duration::zero());
+
+    let stale = ExchangeRateRepository::new(pool.clone())
+        .list_stale(cutoff)
+        .await?
+        .into_iter()
+        .map(|rate| format!("{}/{}", rate.from_currency, rate.to_currency))
+        .collect();
+    Ok((start.elapsed().as_millis(), stale))
+}
+
 // Add a function to check cache health
 pub async fn check_cache_health(
     cache: &RedisCache,
@@ -35,7 +35,7 @@ use tokio::signal;
 use tokio::sync::watch;
 use tower::ServiceBuilder;
 use tower_http::request_id::{PropagateRequestIdLayer, SetRequestIdLayer};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use uuid::Uuid;
 
 /// Graceful shutdown signal handler
@@ -228,33 +228,322 @@ async fn main() -> anyhow::Result<()> {
     // Initialize health checker
     info!("🏥 Initializing health checker...");
     let health_checker =
-        HealthChecker::new(db_pool.clone(), redis_cache.clone(), stellar_client.clone());
+        HealthChecker::new(db_pool.clone(), redis_cache.clone(), stellar_client.clone())
+            .with_rate_freshness(workers::rate_refresher::RateRefresherConfig::from_env().stale_after);
     // Initialize notification service
     let notification_service = std::sync::Arc::new(services::notification::NotificationService::new());
 
+    // Manual review queue for payouts the offramp processor cannot settle
+    let payout_review_service = db_pool.clone().map(|pool| {
+        std::sync::Arc::new(services::payout_review::PayoutReviewService::new(
+            pool,
+            notification_service.clone(),
+        ))
+    });
+
+    // Provider routing by fee tier, persisted reliability and daily caps
+    let provider_routing_enabled = std::env::var("PROVIDER_ROUTING_ENABLED")
+        .unwrap_or_else(|_| "true".to_string())
+        .to_lowercase()
+        != "false";
+    let provider_routing = match db_pool.clone() {
+        Some(pool) if provider_routing_enabled => {
+            let config = services::provider_routing::ProviderRoutingConfig::from_env();
+            info!(
+                cost_weight = config.weights.cost,
+                reliability_weight = config.weights.reliability,
+                latency_weight = config.weights.latency,
+                window_days = config.window_days,
+                "Provider routing enabled"
+            );
+            Some(std::sync::Arc::new(
+                services::provider_routing::ProviderRoutingEngine::new(pool, config),
+            ))
+        }
+        Some(_) => {
+            info!("Provider routing disabled (PROVIDER_ROUTING_ENABLED=false)");
+            None
+        }
+        None => None,
+    };
+
+    // Per-provider circuit breaker, shared across instances through Redis
+    let payment_circuits_enabled = std::env::var("PAYMENT_CIRCUIT_BREAKER_ENABLED")
+        .unwrap_or_else(|_| "true".to_string())
+        .to_lowercase()
+        != "false";
+    let payment_circuit_breaker = if db_pool.is_some() && payment_circuits_enabled {
+        let config = payments::circuit_breaker::CircuitBreakerConfig::from_env();
+        info!(
+            failure_rate = config.failure_rate_threshold,
+            min_requests = config.min_requests,
+            consecutive_timeouts = config.consecutive_timeouts,
+            open_secs = config.open_duration.as_secs(),
+            shared = redis_cache.is_some(),
+            "Payment provider circuit breaker enabled"
+        );
+        Some(std::sync::Arc::new(
+            payments::circuit_breaker::ProviderCircuitBreaker::new(redis_cache.clone(), config),
+        ))
+    } else {
+        if db_pool.is_some() {
+            info!("Payment provider circuit breaker disabled (PAYMENT_CIRCUIT_BREAKER_ENABLED=false)");
+        }
+        None
+    };
+
     // Initialize payment provider factory
     let provider_factory = if db_pool.is_some() {
         info!("💳 Initializing payment provider factory...");
-        let factory = std::sync::Arc::new(PaymentProviderFactory::from_env().unwrap_or_else(|e| {
+        let factory = PaymentProviderFactory::from_env().unwrap_or_else(|e| {
             error!("Failed to initialize payment provider factory: {}", e);
             panic!("Cannot start without payment providers");
-        }));
+        });
+        let factory = match payment_circuit_breaker.clone() {
+            Some(breaker) => factory.with_circuit_breaker(breaker),
+            None => factory,
+        };
         info!("✅ Payment provider factory initialized");
-        Some(factory)
+        Some(std::sync::Arc::new(factory))
+    } else {
+        None
+    };
+
+    let health_checker = match (payment_circuit_breaker.clone(), provider_factory.as_ref()) {
+        (Some(breaker), Some(factory)) => {
+            health_checker.with_payment_circuits(breaker, factory.list_available_providers())
+        }
+        _ => health_checker,
+    };
+
+    // Initialize hot wallet signer (keystore file or remote signer; never a raw seed)
+    let hot_wallet_signer = match chains::stellar::signer::SignerConfig::from_env("HOT_WALLET") {
+        Some(signer_config) => match signer_config.build() {
+            Ok(signer) => {
+                info!(public_key = %signer.public_key(), "🔑 Hot wallet signer initialized");
+                Some(signer)
+            }
+            Err(e) => {
+                error!(error = %e, "Failed to initialize hot wallet signer");
+                None
+            }
+        },
+        None => {
+            info!("⏭️  No hot wallet signer configured (HOT_WALLET_SIGNER unset)");
+            None
+        }
+    };
+
+    // Suspense ledger for incoming cNGN the monitor cannot match; refunds
+    // are sent from the hot wallet
+    let suspense_payment_service = if let (Some(pool), Some(client)) = (db_pool.clone(), stellar_client.clone()) {
+        let service = services::suspense_payment::SuspensePaymentService::new(pool, client);
+        let service = match hot_wallet_signer.clone() {
+            Some(signer) => service.with_refund_signer(signer),
+            None => service,
+        };
+        Some(std::sync::Arc::new(service))
+    } else {
+        None
+    };
+
+    // Initialize treasury multisig service
+    let treasury_service = if let (Some(pool), Some(client)) = (db_pool.clone(), stellar_client.clone()) {
+        let treasury_config = services::treasury::TreasuryConfig::from_env();
+        info!(
+            multisig_limit = %treasury_config.multisig_limit,
+            signing_window_secs = treasury_config.signing_window.as_secs(),
+            "🔐 Treasury multisig enabled"
+        );
+        Some(std::sync::Arc::new(services::treasury::TreasuryService::new(
+            database::treasury_repository::TreasuryRepository::new(pool.clone()),
+            database::transaction_repository::TransactionRepository::new(pool),
+            client,
+            treasury_config,
+        )))
+    } else {
+        None
+    };
+
+    // Initialize claimable balance fallback for payouts to wallets without a trustline
+    let claimable_balance_service = if let (Some(pool), Some(client)) = (db_pool.clone(), stellar_client.clone()) {
+        let config = services::claimable_balance::ClaimableBalanceConfig::from_env();
+        info!(
+            enabled = config.enabled,
+            reclaim_after_secs = config.reclaim_after.as_secs(),
+            "Claimable balance fallback configured"
+        );
+        let service = services::claimable_balance::ClaimableBalanceService::new(
+            database::claimable_balance_repository::ClaimableBalanceRepository::new(pool.clone()),
+            database::transaction_repository::TransactionRepository::new(pool),
+            client,
+            config,
+        );
+        let service = match hot_wallet_signer.clone() {
+            Some(signer) => service.with_reclaim_signer(signer),
+            None => service,
+        };
+        Some(std::sync::Arc::new(service))
+    } else {
+        None
+    };
+
+    // Initialize trustline reserve sponsorship
+    let trustline_sponsorship_service = if let (Some(pool), Some(client)) = (db_pool.clone(), stellar_client.clone()) {
+        let config = services::trustline_sponsorship::TrustlineSponsorshipConfig::from_env();
+        let sponsor_signer = if config.enabled {
+            chains::stellar::signer::SignerConfig::from_env("TRUSTLINE_SPONSOR")
+                .map(|signer_config| signer_config.build())
+        } else {
+            None
+        };
+        match sponsor_signer {
+            Some(Ok(signer)) => {
+                info!(
+                    sponsor = %signer.public_key(),
+                    max_per_user = config.max_reserves_per_user,
+                    "Trustline sponsorship enabled"
+                );
+                Some(std::sync::Arc::new(
+                    services::trustline_sponsorship::TrustlineSponsorshipService::new(
+                        database::trustline_operation_repository::TrustlineOperationRepository::new(pool),
+                        client,
+                        signer,
+                        config,
+                    ),
+                ))
+            }
+            Some(Err(e)) => {
+                error!(error = %e, "Failed to initialize trustline sponsor signer");
+                None
+            }
+            None => {
+                if config.enabled {
+                    warn!("Trustline sponsorship enabled but TRUSTLINE_SPONSOR_SIGNER is not configured");
+                }
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    // Exchange rate service shared by onramp quotes and the rate refresher
+    let fee_structure_service = db_pool.clone().map(|pool| {
+        std::sync::Arc::new(services::fee_structure::FeeStructureService::new(
+            database::fee_structure_repository::FeeStructureRepository::new(pool),
+        ))
+    });
+    let exchange_rate_service = if let (Some(pool), Some(fee_service)) =
+        (db_pool.clone(), fee_structure_service.clone())
+    {
+        let mut service = services::exchange_rate::ExchangeRateService::new(
+            database::exchange_rate_repository::ExchangeRateRepository::new(pool),
+            services::exchange_rate::ExchangeRateServiceConfig::from_env(),
+        )
+        .add_provider(std::sync::Arc::new(
+            services::rate_providers::FixedRateProvider::new(),
+        ))
+        .with_fee_service(fee_service);
+        if let Some(cache) = redis_cache.clone() {
+            service = service.with_cache(cache);
+        }
+        if let Some(external_rates) =
+            services::rate_sources::RateSourcesConfig::from_env().build(stellar_client.clone())
+        {
+            info!(
+                sources = external_rates.provider_count(),
+                "External rate sources configured"
+            );
+            service = service.add_provider(std::sync::Arc::new(external_rates));
+        }
+        Some(std::sync::Arc::new(service))
+    } else {
+        None
+    };
+
+    // Liquidity reservations for onramp quotes (hot wallet cNGN) and offramp
+    // payouts (provider float)
+    let liquidity_enabled = std::env::var("LIQUIDITY_RESERVATIONS_ENABLED")
+        .unwrap_or_else(|_| "true".to_string())
+        .to_lowercase()
+        != "false";
+    let liquidity_service = if !liquidity_enabled {
+        info!("Liquidity reservations disabled (LIQUIDITY_RESERVATIONS_ENABLED=false)");
+        None
+    } else if let (Some(pool), Some(client)) = (db_pool.clone(), stellar_client.clone()) {
+        let mut config = services::liquidity::LiquidityConfig::from_env();
+        if config.hot_wallet.is_empty() {
+            if let Some(signer) = hot_wallet_signer.as_ref() {
+                config.hot_wallet = signer.public_key().to_string();
+            }
+        }
+        if config.hot_wallet.is_empty() {
+            info!("Skipping liquidity reservations (no hot wallet address configured)");
+            None
+        } else {
+            info!(
+                hot_wallet = %config.hot_wallet,
+                shortfall_policy = ?config.shortfall_policy,
+                "Liquidity reservations enabled"
+            );
+            let cngn_issuer = std::env::var("CNGN_ISSUER_ADDRESS")
+                .or_else(|_| std::env::var("CNGN_ISSUER_MAINNET"))
+                .unwrap_or_else(|_| "GXXXXDEFAULTISSUERXXXX".to_string());
+            let service =
+                services::liquidity::LiquidityService::new(pool, client, cngn_issuer, config);
+            let service = match provider_factory.clone() {
+                Some(factory) => service.with_provider_factory(factory),
+                None => service,
+            };
+            Some(std::sync::Arc::new(service))
+        }
     } else {
         None
     };
 
     let (worker_shutdown_tx, worker_shutdown_rx) = watch::channel(false);
     
+    // Durable job queue; the workers below register handlers and schedules
+    // on it and the job runners are started once all have done so
+    let job_queue = db_pool.clone().map(|pool| {
+        std::sync::Arc::new(services::jobs::JobQueue::new(
+            pool,
+            services::jobs::JobsConfig::from_env(),
+        ))
+    });
+    let mut job_handlers: Vec<std::sync::Arc<dyn services::jobs::JobHandler>> = Vec::new();
+
+    // Singleton workers only do their work on the replica that holds their
+    // lock; without a database every worker runs unconditionally
+    let lock_store = db_pool.clone().map(|pool| {
+        std::sync::Arc::new(services::distributed_lock::PgLockStore::new(pool))
+            as std::sync::Arc<dyn services::distributed_lock::LockStore>
+    });
+    let leader_config = services::distributed_lock::LeaderElectionConfig::from_env();
+    let instance_id = services::distributed_lock::instance_id();
+    let mut leader_election_handles = Vec::new();
+    let mut elect_leader = |name: &str| {
+        lock_store.clone().map(|store| {
+            let election = services::distributed_lock::LeaderElection::new(
+                store,
+                name,
+                &instance_id,
+                leader_config.clone(),
+            );
+            let leadership = election.leadership();
+            leader_election_handles.push(tokio::spawn(election.run(worker_shutdown_rx.clone())));
+            leadership
+        })
+    };
+
     // Start Transaction Monitor Worker
     let monitor_enabled = std::env::var("TX_MONITOR_ENABLED")
         .unwrap_or_else(|_| "true".to_string())
         .to_lowercase()
         != "false";
-    let mut monitor_handle = None;
     if monitor_enabled {
-        if let (Some(pool), Some(client)) = (db_pool.clone(), stellar_client.clone()) {
+        if let (Some(pool), Some(client), Some(queue)) = (db_pool.clone(), stellar_client.clone(), job_queue.clone()) {
             let monitor_config = workers::transaction_monitor::TransactionMonitorConfig::from_env();
             info!(
                 poll_interval_secs = monitor_config.poll_interval.as_secs(),
@@ -262,12 +551,35 @@ async fn main() -> anyhow::Result<()> {
                 max_retries = monitor_config.max_retries,
                 "Starting Stellar transaction monitoring worker"
             );
-            let worker = workers::transaction_monitor::TransactionMonitorWorker::new(
-                pool,
-                client,
-                monitor_config,
+            if let Err(e) = queue
+                .schedule(
+                    "stellar.monitor",
+                    &format!("@every {}s", monitor_config.poll_interval.as_secs().max(1)),
+                    &workers::transaction_monitor::MonitorScanJob {},
+                    1,
+                )
+                .await
+            {
+                error!(error = %e, "Failed to schedule Stellar transaction monitor");
+            }
+            let monitor = workers::transaction_monitor::TransactionMonitorWorker::new(
+                pool.clone(),
+                client.clone(),
+                queue,
+                monitor_config.clone(),
             );
-            monitor_handle = Some(tokio::spawn(worker.run(worker_shutdown_rx.clone())));
+            let monitor = match elect_leader("stellar.incoming_scan") {
+                Some(leadership) => monitor.with_leadership(leadership),
+                None => monitor,
+            };
+            job_handlers.push(std::sync::Arc::new(monitor));
+            job_handlers.push(std::sync::Arc::new(
+                workers::transaction_monitor::TransactionConfirmationWorker::new(
+                    pool,
+                    client,
+                    monitor_config,
+                ),
+            ));
         } else {
             info!(
                 "Skipping Stellar transaction monitor worker (missing db pool or stellar client)"
@@ -277,30 +589,132 @@ async fn main() -> anyhow::Result<()> {
         info!("Stellar transaction monitor worker disabled (TX_MONITOR_ENABLED=false)");
     }
 
+    // Weekly aging report of unresolved suspense payments
+    if let (Some(service), Some(queue)) = (suspense_payment_service.clone(), job_queue.clone()) {
+        let schedule = std::env::var("SUSPENSE_AGING_REPORT_SCHEDULE")
+            .unwrap_or_else(|_| "0 8 * * 1".to_string());
+        if let Err(e) = queue
+            .schedule(
+                "suspense.aging_report",
+                &schedule,
+                &workers::suspense_report::SuspenseAgingReportJob {},
+                3,
+            )
+            .await
+        {
+            error!(error = %e, "Failed to schedule suspense aging report");
+        }
+        job_handlers.push(std::sync::Arc::new(
+            workers::suspense_report::SuspenseAgingReporter::new(service),
+        ));
+    }
+
+    // Quotes for onramp, offramp and bill payments; the offramp processor also
+    // re-quotes overpaid deposits with it
+    let quote_service = if let (Some(cache), Some(client), Some(exchange_rate_service), Some(fee_service)) = (
+        redis_cache.clone(),
+        stellar_client.clone(),
+        exchange_rate_service.clone(),
+        fee_structure_service.clone(),
+    ) {
+        let cngn_issuer = std::env::var("CNGN_ISSUER_ADDRESS")
+            .or_else(|_| std::env::var("CNGN_ISSUER_MAINNET"))
+            .unwrap_or_else(|_| "GXXXXDEFAULTISSUERXXXX".to_string());
+
+        let quote_service = services::quote::QuoteService::new(
+            exchange_rate_service,
+            fee_service,
+            client,
+            cache,
+            cngn_issuer,
+            services::quote::QuoteConfig::from_env(),
+        );
+        let quote_service = match liquidity_service.clone() {
+            Some(liquidity) => quote_service.with_liquidity(liquidity),
+            None => quote_service,
+        };
+        let quote_service = match db_pool.clone() {
+            Some(pool) => quote_service.with_audit(std::sync::Arc::new(
+                services::conversion_audit::ConversionAuditService::new(
+                    database::conversion_audit_repository::ConversionAuditRepository::new(pool),
+                ),
+            )),
+            None => quote_service,
+        };
+        let quote_service = match db_pool.clone() {
+            Some(pool) => quote_service.with_wallet_risk(std::sync::Arc::new(
+                database::wallet_risk_repository::WalletRiskRepository::new(pool),
+            )),
+            None => quote_service,
+        };
+        let quote_service = match db_pool.clone() {
+            Some(pool) => quote_service.with_fee_rules(std::sync::Arc::new(
+                services::fee_calculation::FeeCalculationService::new(pool),
+            )),
+            None => quote_service,
+        };
+        Some(std::sync::Arc::new(quote_service))
+    } else {
+        None
+    };
+
     // Start Offramp Processor Worker
     let offramp_enabled = std::env::var("OFFRAMP_PROCESSOR_ENABLED")
         .unwrap_or_else(|_| "true".to_string())
         .to_lowercase() != "false";
-    let mut offramp_handle = None;
     if offramp_enabled {
-        if let (Some(pool), Some(client), Some(factory)) = (db_pool.clone(), stellar_client.clone(), provider_factory.clone()) {
+        if let (Some(pool), Some(client), Some(factory), Some(queue)) = (db_pool.clone(), stellar_client.clone(), provider_factory.clone(), job_queue.clone()) {
             let config = workers::offramp_processor::OfframpProcessorConfig::from_env();
             if let Err(e) = config.validate() {
                 error!(error = %e, "Invalid offramp processor configuration, skipping worker");
-            } else {
+            } else if let Some(signer) = hot_wallet_signer.clone() {
                 info!(
                     poll_interval_secs = config.poll_interval.as_secs(),
                     batch_size = config.batch_size,
                     "Starting offramp processor worker"
                 );
+                if let Err(e) = queue
+                    .schedule(
+                        "offramp.process",
+                        &format!("@every {}s", config.poll_interval.as_secs().max(1)),
+                        &workers::offramp_processor::OfframpCycleJob {},
+                        1,
+                    )
+                    .await
+                {
+                    error!(error = %e, "Failed to schedule offramp processor");
+                }
                 let worker = workers::offramp_processor::OfframpProcessorWorker::new(
                     pool,
                     client,
                     factory,
                     notification_service.clone(),
+                    signer,
                     config,
                 );
-                offramp_handle = Some(tokio::spawn(worker.run(worker_shutdown_rx.clone())));
+                let worker = match treasury_service.clone() {
+                    Some(treasury) => worker.with_treasury(treasury),
+                    None => worker,
+                };
+                let worker = match liquidity_service.clone() {
+                    Some(liquidity) => worker.with_liquidity(liquidity),
+                    None => worker,
+                };
+                let worker = match provider_routing.clone() {
+                    Some(routing) => worker.with_routing(routing),
+                    None => worker,
+                };
+                let worker = match payout_review_service.clone() {
+                    Some(reviews) => worker.with_payout_reviews(reviews),
+                    None => worker,
+                };
+                let worker = match quote_service.clone() {
+                    Some(quotes) => worker.with_quotes(quotes),
+                    None => worker,
+                };
+                job_handlers.push(std::sync::Arc::new(worker));
+            } else {
+                error!("Hot wallet signer unavailable, skipping offramp processor worker");
             }
         } else {
             info!("Skipping offramp processor worker (missing db pool, stellar client, or provider factory)");
@@ -309,7 +723,77 @@ async fn main() -> anyhow::Result<()> {
         info!("Offramp processor worker disabled (OFFRAMP_PROCESSOR_ENABLED=false)");
     }
 
-    // Initialize webhook processor and retry worker
+    // Start job runners for the handlers registered above
+    let mut job_runner_handles = Vec::new();
+    if let Some(queue) = job_queue.clone() {
+        if job_handlers.is_empty() {
+            info!("No job handlers registered; job runners not started");
+        } else {
+            for runner_id in 0..queue.config().workers {
+                let runner = job_handlers.iter().fold(
+                    workers::job_runner::JobRunner::new(
+                        queue.clone(),
+                        format!("{}-{}", queue.config().worker_id, runner_id),
+                    ),
+                    |runner, handler| runner.with_handler(handler.clone()),
+                );
+                job_runner_handles.push(tokio::spawn(runner.run(worker_shutdown_rx.clone())));
+            }
+            info!(
+                workers = queue.config().workers,
+                handlers = job_handlers.len(),
+                "✅ Job runners started"
+            );
+        }
+    }
+
+    // Start Claimable Balance Reclaimer Worker
+    let reclaimer_enabled = std::env::var("CLAIMABLE_BALANCE_RECLAIMER_ENABLED")
+        .unwrap_or_else(|_| "true".to_string())
+        .to_lowercase() != "false";
+    let mut reclaimer_handle = None;
+    if reclaimer_enabled {
+        if let Some(service) = claimable_balance_service.clone() {
+            let config = workers::claimable_balance_reclaimer::ClaimableBalanceReclaimerConfig::from_env();
+            let worker = workers::claimable_balance_reclaimer::ClaimableBalanceReclaimer::new(service, config);
+            let worker = match elect_leader("claimable_balance_reclaimer") {
+                Some(leadership) => worker.with_leadership(leadership),
+                None => worker,
+            };
+            reclaimer_handle = Some(tokio::spawn(worker.run(worker_shutdown_rx.clone())));
+        } else {
+            info!("Skipping claimable balance reclaimer (missing db pool or stellar client)");
+        }
+    } else {
+        info!("Claimable balance reclaimer disabled (CLAIMABLE_BALANCE_RECLAIMER_ENABLED=false)");
+    }
+
+    // Start Rate Refresher Worker
+    let rate_refresher_enabled = std::env::var("RATE_REFRESHER_ENABLED")
+        .unwrap_or_else(|_| "true".to_string())
+        .to_lowercase() != "false";
+    let mut rate_refresher_handle = None;
+    if rate_refresher_enabled {
+        if let Some(service) = exchange_rate_service.clone() {
+            let config = workers::rate_refresher::RateRefresherConfig::from_env();
+            let worker = workers::rate_refresher::RateRefresher::new(service, config);
+            let worker = match elect_leader("rate_refresher") {
+                Some(leadership) => worker.with_leadership(leadership),
+                None => worker,
+            };
+            rate_refresher_handle = Some(tokio::spawn(worker.run(worker_shutdown_rx.clone())));
+        } else {
+            info!("Skipping rate refresher (missing db pool)");
+        }
+    } else {
+        info!("Rate refresher disabled (RATE_REFRESHER_ENABLED=false)");
+    }
+
+    // Initialize webhook processor and queue workers
+    let mut webhook_queue_handles = Vec::new();
+    let mut dispute_service = None;
+    let mut refund_service = None;
+    let mut dispute_poller_handle = None;
     let webhook_routes = if let (Some(pool), Some(provider_factory)) = (db_pool.clone(), provider_factory.clone()) {
         let webhook_repo = std::sync::Arc::new(database::webhook_repository::WebhookRepository::new(pool.clone()));
         
@@ -325,32 +809,84 @@ async fn main() -> anyhow::Result<()> {
             }
         }
         
-        let orchestrator = std::sync::Arc::new(services::payment_orchestrator::PaymentOrchestrator::new(
+        let orchestrator = services::payment_orchestrator::PaymentOrchestrator::new(
             providers,
             transaction_repo,
             orchestrator_config,
-        ));
+        );
+        let orchestrator = match provider_routing.clone() {
+            Some(routing) => orchestrator.with_routing(routing),
+            None => orchestrator,
+        };
+        let orchestrator = match payment_circuit_breaker.clone() {
+            Some(breaker) => orchestrator.with_circuit_breaker(breaker),
+            None => orchestrator,
+        };
+        let orchestrator = std::sync::Arc::new(orchestrator.with_fee_calculation(std::sync::Arc::new(
+            services::fee_calculation::FeeCalculationService::new(pool.clone()),
+        )));
         
-        let webhook_processor = std::sync::Arc::new(services::webhook_processor::WebhookProcessor::new(
-            webhook_repo,
-            provider_factory,
-            orchestrator,
+        let disputes = std::sync::Arc::new(services::dispute::DisputeService::new(
+            pool.clone(),
+            provider_factory.clone(),
+            orchestrator.clone(),
+            services::dispute::DisputeConfig::from_env(),
         ));
+        dispute_service = Some(disputes.clone());
+
+        let refunds = std::sync::Arc::new(services::refund::RefundService::new(
+            pool.clone(),
+            provider_factory.clone(),
+            orchestrator.clone(),
+            notification_service.clone(),
+            services::refund::RefundConfig::from_env(),
+        ));
+        refund_service = Some(refunds.clone());
+
+        let dispute_poller_enabled = std::env::var("DISPUTE_POLLER_ENABLED")
+            .unwrap_or_else(|_| "true".to_string())
+            .to_lowercase() != "false";
+        if dispute_poller_enabled {
+            let worker = workers::dispute_poller::DisputePoller::new(disputes.clone());
+            let worker = match elect_leader("dispute_poller") {
+                Some(leadership) => worker.with_leadership(leadership),
+                None => worker,
+            };
+            dispute_poller_handle = Some(tokio::spawn(worker.run(worker_shutdown_rx.clone())));
+            info!("✅ Dispute poller started");
+        } else {
+            info!("Dispute poller disabled (DISPUTE_POLLER_ENABLED=false)");
+        }
+
+        let webhook_processor = std::sync::Arc::new(
+            services::webhook_processor::WebhookProcessor::new(
+                webhook_repo,
+                provider_factory,
+                orchestrator,
+                services::webhook_processor::WebhookQueueConfig::from_env(),
+            )
+            .with_disputes(disputes)
+            .with_refunds(refunds),
+        );
         
-        // Start webhook retry worker
-        let webhook_retry_enabled = std::env::var("WEBHOOK_RETRY_ENABLED")
+        // Start webhook queue workers
+        let webhook_queue_enabled = std::env::var("WEBHOOK_QUEUE_ENABLED")
+            .or_else(|_| std::env::var("WEBHOOK_RETRY_ENABLED"))
             .unwrap_or_else(|_| "true".to_string())
             .to_lowercase() != "false";
         
-        if webhook_retry_enabled {
-            let retry_worker = workers::webhook_retry::WebhookRetryWorker::new(
-                webhook_processor.clone(),
-                60, // Check every 60 seconds
+        if webhook_queue_enabled {
+            for worker_id in 0..webhook_processor.config().workers {
+                let worker = workers::webhook_queue::WebhookQueueWorker::new(
+                    webhook_processor.clone(),
+                    worker_id,
+                );
+                webhook_queue_handles.push(tokio::spawn(worker.run(worker_shutdown_rx.clone())));
+            }
+            info!(
+                workers = webhook_processor.config().workers,
+                "✅ Webhook queue workers started"
             );
-            tokio::spawn(async move {
-                retry_worker.run().await;
-            });
-            info!("✅ Webhook retry worker started");
         }
         
         let webhook_state = api::webhooks::WebhookState {
@@ -358,55 +894,155 @@ async fn main() -> anyhow::Result<()> {
         };
         
         Router::new()
-            .route("/webhooks/:provider", post(api::webhooks::handle_webhook))
+            .route("/webhooks/{provider}", post(api::webhooks::handle_webhook))
+            .route(
+                "/api/admin/webhooks/dead-letters",
+                get(api::webhooks::list_dead_letters),
+            )
+            .route(
+                "/api/admin/webhooks/{id}/replay",
+                post(api::webhooks::replay_dead_letter),
+            )
             .with_state(std::sync::Arc::new(webhook_state))
     } else {
         info!("⏭️  Skipping webhook routes (no database)");
         Router::new()
     };
 
-    // Create the application router with logging middleware
-    info!("🛣️  Setting up application routes...");
-    
-    // Setup onramp routes (quote service)
-    let onramp_routes = if let (Some(pool), Some(cache), Some(client)) = (
-        db_pool.clone(),
-        redis_cache.clone(),
-        stellar_client.clone(),
-    ) {
-        let cngn_issuer = std::env::var("CNGN_ISSUER_ADDRESS")
-            .or_else(|_| std::env::var("CNGN_ISSUER_MAINNET"))
-            .unwrap_or_else(|_| "GXXXXDEFAULTISSUERXXXX".to_string());
+    let dispute_routes = if let Some(service) = dispute_service.clone() {
+        Router::new()
+            .route("/api/admin/disputes", get(api::disputes::list_disputes))
+            .route("/api/admin/disputes/{id}", get(api::disputes::get_dispute))
+            .route(
+                "/api/admin/disputes/{id}/evidence",
+                post(api::disputes::add_evidence),
+            )
+            .route(
+                "/api/admin/disputes/{id}/accept",
+                post(api::disputes::accept_dispute),
+            )
+            .route(
+                "/api/admin/disputes/{id}/contest",
+                post(api::disputes::contest_dispute),
+            )
+            .route(
+                "/api/admin/wallets/{wallet_address}/risk-flags",
+                get(api::disputes::list_wallet_risk_flags),
+            )
+            .route(
+                "/api/admin/risk-flags/{id}/clear",
+                post(api::disputes::clear_risk_flag),
+            )
+            .with_state(api::disputes::DisputesState {
+                dispute_service: service,
+            })
+    } else {
+        Router::new()
+    };
 
-        let rate_repo = database::exchange_rate_repository::ExchangeRateRepository::new(pool.clone());
-        let fee_repo = database::fee_structure_repository::FeeStructureRepository::new(pool.clone());
-        let fee_service = std::sync::Arc::new(services::fee_structure::FeeStructureService::new(
-            fee_repo,
-        ));
+    let refund_routes = if let Some(service) = refund_service.clone() {
+        Router::new()
+            .route(
+                "/api/admin/transactions/{id}/refund",
+                post(api::refunds::refund_onramp),
+            )
+            .route(
+                "/api/admin/transactions/{id}/refunds",
+                get(api::refunds::list_refunds),
+            )
+            .with_state(api::refunds::RefundsState {
+                refund_service: service,
+            })
+    } else {
+        Router::new()
+    };
 
-        let exchange_rate_service = std::sync::Arc::new(
-            services::exchange_rate::ExchangeRateService::new(
-                rate_repo,
-                services::exchange_rate::ExchangeRateServiceConfig::default(),
+    let payout_review_routes = if let Some(service) = payout_review_service.clone() {
+        Router::new()
+            .route(
+                "/api/admin/payout-reviews",
+                get(api::payout_reviews::list_payout_reviews),
             )
-            .with_cache(cache.clone())
-            .add_provider(std::sync::Arc::new(
-                services::rate_providers::FixedRateProvider::new(),
-            ))
-            .with_fee_service(fee_service.clone()),
-        );
+            .route(
+                "/api/admin/payout-reviews/{id}",
+                get(api::payout_reviews::get_payout_review),
+            )
+            .route(
+                "/api/admin/payout-reviews/{id}/resolve",
+                post(api::payout_reviews::resolve_payout_review),
+            )
+            .with_state(api::payout_reviews::PayoutReviewsState {
+                payout_review_service: service,
+            })
+    } else {
+        Router::new()
+    };
 
-        let quote_service = std::sync::Arc::new(services::onramp_quote::OnrampQuoteService::new(
-            exchange_rate_service,
-            fee_service,
-            client,
-            cache,
-            cngn_issuer,
-        ));
+    let suspense_payment_routes = if let Some(service) = suspense_payment_service.clone() {
+        Router::new()
+            .route(
+                "/api/admin/suspense-payments",
+                get(api::suspense_payments::list_suspense_payments),
+            )
+            .route(
+                "/api/admin/suspense-payments/aging",
+                get(api::suspense_payments::suspense_aging_report),
+            )
+            .route(
+                "/api/admin/suspense-payments/{id}",
+                get(api::suspense_payments::get_suspense_payment),
+            )
+            .route(
+                "/api/admin/suspense-payments/{id}/match",
+                post(api::suspense_payments::match_suspense_payment),
+            )
+            .route(
+                "/api/admin/suspense-payments/{id}/refund",
+                post(api::suspense_payments::refund_suspense_payment),
+            )
+            .with_state(api::suspense_payments::SuspensePaymentsState {
+                suspense_payment_service: service,
+            })
+    } else {
+        Router::new()
+    };
+
+    let job_routes = if let Some(queue) = job_queue.clone() {
+        Router::new()
+            .route("/api/admin/jobs", get(api::jobs::list_jobs))
+            .route("/api/admin/jobs/stats", get(api::jobs::job_stats))
+            .route("/api/admin/jobs/{id}", get(api::jobs::get_job))
+            .route("/api/admin/jobs/{id}/retry", post(api::jobs::retry_job))
+            .route("/api/admin/jobs/{id}/cancel", post(api::jobs::cancel_job))
+            .route("/api/admin/job-schedules", get(api::jobs::list_schedules))
+            .route(
+                "/api/admin/job-schedules/{name}/pause",
+                post(api::jobs::pause_schedule),
+            )
+            .route(
+                "/api/admin/job-schedules/{name}/resume",
+                post(api::jobs::resume_schedule),
+            )
+            .route(
+                "/api/admin/job-schedules/{name}/run",
+                post(api::jobs::run_schedule),
+            )
+            .with_state(api::jobs::JobsState { queue })
+    } else {
+        Router::new()
+    };
+
+    // Create the application router with logging middleware
+    info!("🛣️  Setting up application routes...");
+    
+    // Setup quote routes (onramp, offramp and bill payment quotes)
+    let quote_routes = if let Some(quote_service) = quote_service.clone() {
+        let state = api::quotes::QuotesState { quote_service };
 
         Router::new()
-            .route("/api/onramp/quote", post(create_onramp_quote))
-            .with_state(quote_service)
+            .route("/api/quotes", post(api::quotes::create_quote))
+            .route("/api/onramp/quote", post(api::quotes::create_onramp_quote))
+            .with_state(state)
     } else {
         Router::new()
     };
@@ -431,6 +1067,104 @@ async fn main() -> anyhow::Result<()> {
         Router::new()
     };
     
+    // Treasury multisig routes
+    let treasury_routes = if let Some(treasury_service) = treasury_service.clone() {
+        let treasury_state = api::treasury::TreasuryState { treasury_service };
+
+        Router::new()
+            .route(
+                "/api/treasury/transactions",
+                post(api::treasury::propose_transaction).get(api::treasury::list_transactions),
+            )
+            .route(
+                "/api/treasury/transactions/{id}",
+                get(api::treasury::get_transaction),
+            )
+            .route(
+                "/api/treasury/transactions/{id}/signatures",
+                post(api::treasury::add_signature),
+            )
+            .route(
+                "/api/treasury/transactions/{id}/presigned",
+                post(api::treasury::add_presigned_envelope),
+            )
+            .route(
+                "/api/treasury/transactions/{id}/submit",
+                post(api::treasury::submit_transaction),
+            )
+            .with_state(treasury_state)
+    } else {
+        Router::new()
+    };
+
+    // Claimable balance routes
+    let claimable_balance_routes = if let Some(service) = claimable_balance_service.clone() {
+        let state = api::claimable_balances::ClaimableBalanceState { service };
+
+        Router::new()
+            .route(
+                "/api/wallets/{address}/claimable-balances",
+                get(api::claimable_balances::list_for_wallet),
+            )
+            .route(
+                "/api/claimable-balances/{balance_id}/claim",
+                post(api::claimable_balances::build_claim),
+            )
+            .with_state(state)
+    } else {
+        Router::new()
+    };
+
+    // Exchange rate history routes
+    let rates_routes = if let Some(pool) = db_pool.clone() {
+        let state = api::rates::RatesState {
+            service: std::sync::Arc::new(services::exchange_rate::ExchangeRateService::new(
+                database::exchange_rate_repository::ExchangeRateRepository::new(pool),
+                services::exchange_rate::ExchangeRateServiceConfig::from_env(),
+            )),
+        };
+
+        Router::new()
+            .route("/api/rates/history", get(api::rates::get_history))
+            .with_state(state)
+    } else {
+        Router::new()
+    };
+
+    // Fee administration routes
+    let fees_routes = if let Some(pool) = db_pool.clone() {
+        let state = api::fees::FeesState {
+            simulation_service: std::sync::Arc::new(
+                services::fee_simulation::FeeSimulationService::new(pool),
+            ),
+        };
+
+        Router::new()
+            .route("/api/admin/fees/simulate", post(api::fees::simulate))
+            .with_state(state)
+    } else {
+        Router::new()
+    };
+
+    // Payment provider administration routes
+    let payment_provider_routes = if let (Some(breaker), Some(factory)) =
+        (payment_circuit_breaker.clone(), provider_factory.clone())
+    {
+        let state = api::payment_providers::PaymentProvidersState {
+            circuit_breaker: breaker,
+            providers: factory.list_available_providers(),
+        };
+
+        Router::new()
+            .route(
+                "/api/admin/payment-providers/circuits",
+                get(api::payment_providers::circuits),
+            )
+            .with_state(state)
+    } else {
+        Router::new()
+    };
+
     // Bill payment providers routes (public endpoint - no auth required)
     let bills_routes = Router::new()
         .route("/api/bills/providers", get(api::bills::get_providers));
@@ -465,19 +1199,37 @@ async fn main() -> anyhow::Result<()> {
             "/api/cngn/trustlines/retry/{id}",
             post(retry_cngn_trustline),
         )
+        .route(
+            "/api/cngn/trustlines/sponsorship/revoke",
+            post(revoke_cngn_trustline_sponsorship),
+        )
         .route("/api/cngn/payments/build", post(build_cngn_payment))
-        .route("/api/cngn/payments/sign", post(sign_cngn_payment))
         .route("/api/cngn/payments/submit", post(submit_cngn_payment))
+        .route("/api/stellar/paths/quote", post(quote_path_payment))
+        .route("/api/stellar/paths/build", post(build_path_payment))
         .route("/api/payments/initiate", post(initiate_payment))
-        .merge(onramp_routes)
+        .merge(quote_routes)
         .merge(wallet_routes)
         .merge(webhook_routes)
+        .merge(dispute_routes)
+        .merge(refund_routes)
+        .merge(payout_review_routes)
+        .merge(suspense_payment_routes)
+        .merge(job_routes)
         .merge(bills_routes)
+        .merge(treasury_routes)
+        .merge(claimable_balance_routes)
+        .merge(rates_routes)
+        .merge(fees_routes)
+        .merge(payment_provider_routes)
         .with_state(AppState {
             db_pool,
             redis_cache,
             stellar_client,
             health_checker,
+            hot_wallet_signer,
+            claimable_balance_service,
+            trustline_sponsorship_service,
         })
         .layer(
             ServiceBuilder::new()
@@ -552,14 +1304,34 @@ async fn main() -> anyhow::Result<()> {
         .unwrap();
 
     let _ = worker_shutdown_tx.send(true);
-    if let Some(handle) = monitor_handle {
+    for handle in job_runner_handles {
+        if let Err(e) = tokio::time::timeout(std::time::Duration::from_secs(5), handle).await {
+            error!(error = %e, "Timed out waiting for job runner shutdown");
+        }
+    }
+    if let Some(handle) = reclaimer_handle {
+        if let Err(e) = tokio::time::timeout(std::time::Duration::from_secs(5), handle).await {
+            error!(error = %e, "Timed out waiting for claimable balance reclaimer shutdown");
+        }
+    }
+    if let Some(handle) = rate_refresher_handle {
+        if let Err(e) = tokio::time::timeout(std::time::Duration::from_secs(5), handle).await {
+            error!(error = %e, "Timed out waiting for rate refresher shutdown");
+        }
+    }
+    for handle in webhook_queue_handles {
         if let Err(e) = tokio::time::timeout(std::time::Duration::from_secs(5), handle).await {
-            error!(error = %e, "Timed out waiting for monitor worker shutdown");
+            error!(error = %e, "Timed out waiting for webhook queue worker shutdown");
         }
     }
-    if let Some(handle) = offramp_handle {
+    if let Some(handle) = dispute_poller_handle {
         if let Err(e) = tokio::time::timeout(std::time::Duration::from_secs(5), handle).await {
-            error!(error = %e, "Timed out waiting for offramp worker shutdown");
+            error!(error = %e, "Timed out waiting for dispute poller shutdown");
+        }
+    }
+    for handle in leader_election_handles {
+        if let Err(e) = tokio::time::timeout(std::time::Duration::from_secs(5), handle).await {
+            error!(error = %e, "Timed out waiting for leader election shutdown");
         }
     }
 
@@ -575,6 +1347,11 @@ struct AppState {
     redis_cache: Option<RedisCache>,
     stellar_client: Option<StellarClient>,
     health_checker: HealthChecker,
+    hot_wallet_signer: Option<std::sync::Arc<dyn chains::stellar::signer::TransactionSigner>>,
+    claimable_balance_service:
+        Option<std::sync::Arc<services::claimable_balance::ClaimableBalanceService>>,
+    trustline_sponsorship_service:
+        Option<std::sync::Arc<services::trustline_sponsorship::TrustlineSponsorshipService>>,
 }
 
 // Handlers
@@ -799,10 +1576,16 @@ struct CngnTrustlineBuildRequest {
     account_id: String,
     limit: Option<String>,
     fee_stroops: Option<u32>,
+    /// Have the platform sponsor pay the trustline reserve
+    #[serde(default)]
+    sponsored: bool,
 }
 
 #[derive(Debug, Deserialize)]
 struct CngnTrustlineSubmitRequest {
+    /// When empty, the envelope built for `operation_id` is signed
+    /// server-side; only done for the account the configured signer holds.
+    #[serde(default)]
     signed_envelope_xdr: String,
     account_id: Option<String>,
     operation_id: Option<Uuid>,
@@ -820,6 +1603,8 @@ struct CngnTrustlineSubmitResponse {
     operation_id: Option<Uuid>,
 }
 
+/// The returned draft is signed by the payer in their own wallet and
+/// submitted through `/api/cngn/payments/submit`; the server never signs it.
 #[derive(Debug, Deserialize)]
 struct CngnPaymentBuildRequest {
     source: String,
@@ -829,12 +1614,6 @@ struct CngnPaymentBuildRequest {
     fee_stroops: Option<u32>,
 }
 
-#[derive(Debug, Deserialize)]
-struct CngnPaymentSignRequest {
-    draft: crate::chains::stellar::payment::CngnPaymentDraft,
-    secret_seed: String,
-}
-
 #[derive(Debug, Deserialize)]
 struct CngnPaymentSubmitRequest {
     signed_envelope_xdr: String,
@@ -853,6 +1632,27 @@ struct CngnPaymentSubmitResponse {
     transaction_id: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct PathPaymentQuoteRequest {
+    mode: crate::chains::stellar::payment::PathPaymentMode,
+    send_asset: crate::chains::stellar::types::StellarAsset,
+    /// Defaults to cNGN
+    dest_asset: Option<crate::chains::stellar::types::StellarAsset>,
+    amount: String,
+    slippage_bps: Option<u32>,
+}
+
+/// The returned draft is signed by the payer and submitted through
+/// `/api/cngn/payments/submit`.
+#[derive(Debug, Deserialize)]
+struct PathPaymentBuildRequest {
+    source: String,
+    destination: String,
+    quote: crate::chains::stellar::payment::PathPaymentQuote,
+    memo: Option<crate::chains::stellar::payment::CngnMemo>,
+    fee_stroops: Option<u32>,
+}
+
 #[derive(Debug, Deserialize)]
 struct InitiatePaymentApiRequest {
     amount: String,
@@ -1144,26 +1944,6 @@ async fn list_trustline_operations_by_wallet(
         })
 }
 
-async fn create_onramp_quote(
-    axum::extract::State(quote_service): axum::extract::State<std::sync::Arc<services::onramp_quote::OnrampQuoteService>>,
-    headers: axum::http::HeaderMap,
-    Json(payload): Json<services::onramp_quote::OnrampQuoteRequest>,
-) -> Result<
-    Json<services::onramp_quote::OnrampQuoteResponse>,
-    (
-        axum::http::StatusCode,
-        Json<middleware::error::ErrorResponse>,
-    ),
-> {
-    let request_id = middleware::error::get_request_id_from_headers(&headers);
-
-    quote_service
-        .create_quote(payload)
-        .await
-        .map(Json)
-        .map_err(|e| app_error_response(e, request_id))
-}
-
 async fn calculate_fee(
     axum::extract::State(state): axum::extract::State<AppState>,
     headers: axum::http::HeaderMap,
@@ -1365,6 +2145,24 @@ async fn build_cngn_trustline(
         ));
     }
 
+    if payload.sponsored {
+        let Some(service) = state.trustline_sponsorship_service.as_ref() else {
+            return Err(crate::middleware::error::json_error_response(
+                axum::http::StatusCode::SERVICE_UNAVAILABLE,
+                "Trustline sponsorship disabled by configuration",
+                request_id,
+            ));
+        };
+        let (draft, operation) = service
+            .build(&payload.account_id, payload.limit.as_deref())
+            .await
+            .map_err(|e| app_error_response(e, request_id.clone()))?;
+        return Ok(Json(CngnTrustlineBuildResponse {
+            draft,
+            operation_id: Some(operation.id),
+        }));
+    }
+
     let manager =
         crate::chains::stellar::trustline::CngnTrustlineManager::new(stellar_client.clone());
     let draft = manager
@@ -1438,18 +2236,81 @@ async fn submit_cngn_trustline(
         }
     };
 
-    if payload.signed_envelope_xdr.trim().is_empty() {
-        return Err(crate::middleware::error::json_error_response(
-            axum::http::StatusCode::BAD_REQUEST,
-            "signed_envelope_xdr is required",
-            request_id,
-        ));
+    // Sponsored envelopes are sourced from the sponsor and need its co-signature
+    if let Some(service) = state.trustline_sponsorship_service.as_ref() {
+        if service.is_sponsored_envelope(&payload.signed_envelope_xdr) {
+            let Some(operation_id) = payload.operation_id else {
+                return Err(crate::middleware::error::json_error_response(
+                    axum::http::StatusCode::BAD_REQUEST,
+                    "operation_id is required for sponsored trustlines",
+                    request_id,
+                ));
+            };
+            let horizon_response = service
+                .submit(operation_id, &payload.signed_envelope_xdr)
+                .await
+                .map_err(|e| app_error_response(e, request_id))?;
+            return Ok(Json(CngnTrustlineSubmitResponse {
+                horizon_response,
+                operation_id: Some(operation_id),
+            }));
+        }
     }
 
     let manager =
         crate::chains::stellar::trustline::CngnTrustlineManager::new(stellar_client.clone());
+
+    let signed_envelope_xdr = if !payload.signed_envelope_xdr.trim().is_empty() {
+        payload.signed_envelope_xdr.clone()
+    } else {
+        // Never sign caller-supplied XDR; only the envelope stored when the
+        // server built this trustline
+        let (Some(pool), Some(signer), Some(operation_id)) = (
+            state.db_pool.as_ref(),
+            state.hot_wallet_signer.as_ref(),
+            payload.operation_id,
+        ) else {
+            return Err(crate::middleware::error::json_error_response(
+                axum::http::StatusCode::BAD_REQUEST,
+                "signed_envelope_xdr is required",
+                request_id,
+            ));
+        };
+        use crate::database::repository::Repository;
+        let repo = crate::database::trustline_operation_repository::TrustlineOperationRepository::new(pool.clone());
+        let operation = repo
+            .find_by_id(&operation_id.to_string())
+            .await
+            .map_err(|e| {
+                crate::middleware::error::json_error_response(
+                    axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                    e.to_string(),
+                    request_id.clone(),
+                )
+            })?
+            .filter(|operation| operation.status == "pending");
+        let Some((operation, unsigned)) = operation.and_then(|operation| {
+            let unsigned = operation
+                .metadata
+                .get("unsigned_envelope_xdr")
+                .and_then(|v| v.as_str())?
+                .to_string();
+            Some((operation, unsigned))
+        }) else {
+            return Err(crate::middleware::error::json_error_response(
+                axum::http::StatusCode::NOT_FOUND,
+                format!("no pending trustline operation {} to sign", operation_id),
+                request_id,
+            ));
+        };
+        manager
+            .sign_trustline_transaction(&unsigned, &operation.wallet_address, signer.as_ref())
+            .await
+            .map_err(|e| app_error_response(e.into(), request_id.clone()))?
+    };
+
     let result = manager
-        .submit_signed_trustline_xdr(&payload.signed_envelope_xdr)
+        .submit_signed_trustline_xdr(&signed_envelope_xdr)
         .await;
 
     match result {
@@ -1514,6 +2375,33 @@ async fn retry_cngn_trustline(
         })
 }
 
+async fn revoke_cngn_trustline_sponsorship(
+    axum::extract::State(state): axum::extract::State<AppState>,
+    headers: axum::http::HeaderMap,
+    Json(payload): Json<TrustlineAccountRequest>,
+) -> Result<
+    Json<crate::database::trustline_operation_repository::TrustlineOperation>,
+    (
+        axum::http::StatusCode,
+        Json<crate::middleware::error::ErrorResponse>,
+    ),
+> {
+    let request_id = crate::middleware::error::get_request_id_from_headers(&headers);
+    let Some(service) = state.trustline_sponsorship_service.as_ref() else {
+        return Err(crate::middleware::error::json_error_response(
+            axum::http::StatusCode::SERVICE_UNAVAILABLE,
+            "Trustline sponsorship disabled by configuration",
+            request_id,
+        ));
+    };
+
+    service
+        .revoke(&payload.account_id)
+        .await
+        .map(Json)
+        .map_err(|e| app_error_response(e, request_id))
+}
+
 async fn build_cngn_payment(
     axum::extract::State(state): axum::extract::State<AppState>,
     headers: axum::http::HeaderMap,
@@ -1548,19 +2436,73 @@ async fn build_cngn_payment(
         ));
     }
 
+    // The hot wallet only pays out through server-built payouts; drafts
+    // from it are never handed to callers.
+    if state
+        .hot_wallet_signer
+        .as_ref()
+        .is_some_and(|signer| signer.public_key() == payload.source)
+    {
+        return Err(crate::middleware::error::json_error_response(
+            axum::http::StatusCode::FORBIDDEN,
+            "payments from the platform hot wallet cannot be built through this endpoint",
+            request_id,
+        ));
+    }
+
+    // Large treasury transfers must be approved by multiple signers.
+    let treasury_config = crate::services::treasury::TreasuryConfig::from_env();
+    if !treasury_config.treasury_account.is_empty() && payload.source == treasury_config.treasury_account {
+        use std::str::FromStr;
+        let amount = sqlx::types::BigDecimal::from_str(&payload.amount)
+            .unwrap_or_else(|_| sqlx::types::BigDecimal::from(0));
+        if amount > treasury_config.multisig_limit {
+            return Err(crate::middleware::error::json_error_response(
+                axum::http::StatusCode::UNPROCESSABLE_ENTITY,
+                format!(
+                    "treasury transfers above {} require multisig approval via /api/treasury/transactions",
+                    treasury_config.multisig_limit
+                ),
+                request_id,
+            ));
+        }
+    }
+
     let builder = crate::chains::stellar::payment::CngnPaymentBuilder::new(stellar_client.clone());
-    let draft = builder
-        .build_payment(
-            &payload.source,
-            &payload.destination,
-            &payload.amount,
-            payload
-                .memo
-                .unwrap_or(crate::chains::stellar::payment::CngnMemo::None),
-            payload.fee_stroops,
-        )
-        .await
-        .map_err(|e| app_error_response(e.into(), request_id.clone()))?;
+    let memo = payload
+        .memo
+        .unwrap_or(crate::chains::stellar::payment::CngnMemo::None);
+    // Recipients without a trustline get a claimable balance instead of a failure.
+    let draft = match state
+        .claimable_balance_service
+        .as_ref()
+        .filter(|service| service.config().enabled)
+    {
+        Some(service) => {
+            builder
+                .build_payout(
+                    &payload.source,
+                    &payload.destination,
+                    &payload.amount,
+                    memo,
+                    payload.fee_stroops,
+                    service.config().reclaim_after,
+                )
+                .await
+        }
+        None => {
+            builder
+                .build_payment(
+                    &payload.source,
+                    &payload.destination,
+                    &payload.amount,
+                    memo,
+                    payload.fee_stroops,
+                )
+                .await
+        }
+    }
+    .map_err(|e| app_error_response(e.into(), request_id.clone()))?;
 
     let mut transaction_id = None;
     if let Some(pool) = state.db_pool.as_ref() {
@@ -1598,7 +2540,8 @@ async fn build_cngn_payment(
                     "destination": payload.destination,
                     "memo": draft.memo,
                     "stellar_tx_hash": draft.transaction_hash,
-                    "unsigned_envelope_xdr": draft.unsigned_envelope_xdr
+                    "unsigned_envelope_xdr": draft.unsigned_envelope_xdr,
+                    "claimable_balance_id": draft.claimable_balance_id
                 }),
             )
             .await
@@ -1610,6 +2553,13 @@ async fn build_cngn_payment(
                 )
             })?;
         transaction_id = Some(tx.transaction_id.to_string());
+
+        if let Some(service) = state.claimable_balance_service.as_ref() {
+            service
+                .record_payout(Some(tx.transaction_id), &draft)
+                .await
+                .map_err(|e| app_error_response(e, request_id.clone()))?;
+        }
     }
 
     Ok(Json(CngnPaymentBuildResponse {
@@ -1618,36 +2568,6 @@ async fn build_cngn_payment(
     }))
 }
 
-async fn sign_cngn_payment(
-    axum::extract::State(state): axum::extract::State<AppState>,
-    headers: axum::http::HeaderMap,
-    Json(payload): Json<CngnPaymentSignRequest>,
-) -> Result<
-    Json<crate::chains::stellar::payment::SignedCngnPayment>,
-    (
-        axum::http::StatusCode,
-        Json<crate::middleware::error::ErrorResponse>,
-    ),
-> {
-    let request_id = crate::middleware::error::get_request_id_from_headers(&headers);
-    let stellar_client = match state.stellar_client.as_ref() {
-        Some(client) => client,
-        None => {
-            return Err(crate::middleware::error::json_error_response(
-                axum::http::StatusCode::SERVICE_UNAVAILABLE,
-                "Stellar client disabled by configuration",
-                request_id,
-            ))
-        }
-    };
-
-    let builder = crate::chains::stellar::payment::CngnPaymentBuilder::new(stellar_client.clone());
-    builder
-        .sign_payment(payload.draft, &payload.secret_seed)
-        .map(Json)
-        .map_err(|e| app_error_response(e.into(), request_id))
-}
-
 async fn submit_cngn_payment(
     axum::extract::State(state): axum::extract::State<AppState>,
     headers: axum::http::HeaderMap,
@@ -1706,6 +2626,12 @@ async fn submit_cngn_payment(
                 let _ = repo
                     .update_status_with_metadata(tx_id, "processing", metadata)
                     .await;
+                if let (Some(service), Ok(tx_uuid)) = (
+                    state.claimable_balance_service.as_ref(),
+                    Uuid::parse_str(tx_id),
+                ) {
+                    let _ = service.mark_submitted(tx_uuid, None).await;
+                }
             }
             Ok(Json(CngnPaymentSubmitResponse {
                 horizon_response,
@@ -1720,8 +2646,107 @@ async fn submit_cngn_payment(
                     pool.clone(),
                 );
                 let _ = repo.update_status(tx_id, "failed").await;
+                if let (Some(service), Ok(tx_uuid)) = (
+                    state.claimable_balance_service.as_ref(),
+                    Uuid::parse_str(tx_id),
+                ) {
+                    let _ = service.mark_submitted(tx_uuid, Some(&e.to_string())).await;
+                }
             }
             Err(app_error_response(e.into(), request_id))
         }
     }
 }
+
+async fn quote_path_payment(
+    axum::extract::State(state): axum::extract::State<AppState>,
+    headers: axum::http::HeaderMap,
+    Json(payload): Json<PathPaymentQuoteRequest>,
+) -> Result<
+    Json<crate::chains::stellar::payment::PathPaymentQuote>,
+    (
+        axum::http::StatusCode,
+        Json<crate::middleware::error::ErrorResponse>,
+    ),
+> {
+    let request_id = crate::middleware::error::get_request_id_from_headers(&headers);
+    let stellar_client = match state.stellar_client.as_ref() {
+        Some(client) => client,
+        None => {
+            return Err(crate::middleware::error::json_error_response(
+                axum::http::StatusCode::SERVICE_UNAVAILABLE,
+                "Stellar client disabled by configuration",
+                request_id,
+            ))
+        }
+    };
+
+    if payload.amount.trim().is_empty() {
+        return Err(crate::middleware::error::json_error_response(
+            axum::http::StatusCode::BAD_REQUEST,
+            "amount is required",
+            request_id,
+        ));
+    }
+
+    let builder = crate::chains::stellar::payment::CngnPaymentBuilder::new(stellar_client.clone());
+    let dest_asset = payload.dest_asset.unwrap_or_else(|| builder.cngn_asset());
+    builder
+        .quote_path_payment(
+            payload.mode,
+            &payload.send_asset,
+            &dest_asset,
+            &payload.amount,
+            payload.slippage_bps,
+        )
+        .await
+        .map(Json)
+        .map_err(|e| app_error_response(e.into(), request_id))
+}
+
+async fn build_path_payment(
+    axum::extract::State(state): axum::extract::State<AppState>,
+    headers: axum::http::HeaderMap,
+    Json(payload): Json<PathPaymentBuildRequest>,
+) -> Result<
+    Json<crate::chains::stellar::payment::PathPaymentDraft>,
+    (
+        axum::http::StatusCode,
+        Json<crate::middleware::error::ErrorResponse>,
+    ),
+> {
+    let request_id = crate::middleware::error::get_request_id_from_headers(&headers);
+    let stellar_client = match state.stellar_client.as_ref() {
+        Some(client) => client,
+        None => {
+            return Err(crate::middleware::error::json_error_response(
+                axum::http::StatusCode::SERVICE_UNAVAILABLE,
+                "Stellar client disabled by configuration",
+                request_id,
+            ))
+        }
+    };
+
+    if payload.source.trim().is_empty() || payload.destination.trim().is_empty() {
+        return Err(crate::middleware::error::json_error_response(
+            axum::http::StatusCode::BAD_REQUEST,
+            "source and destination are required",
+            request_id,
+        ));
+    }
+
+    let builder = crate::chains::stellar::payment::CngnPaymentBuilder::new(stellar_client.clone());
+    builder
+        .build_path_payment(
+            payload.source.trim(),
+            payload.destination.trim(),
+            payload.quote,
+            payload
+                .memo
+                .unwrap_or(crate::chains::stellar::payment::CngnMemo::None),
+            payload.fee_stroops,
+        )
+        .await
+        .map(Json)
+        .map_err(|e| app_error_response(e.into(), request_id))
+}
@@ -0,0 +1,524 @@
+//! Payment provider circuit breaker
+//!
+//! Tracks each provider's recent HTTP outcomes and stops sending it traffic
+//! once its error rate or run of timeouts crosses a threshold. After a
+//! cool-down a few half-open probe requests decide whether the circuit closes
+//! again. State lives in Redis so every instance sees the same circuit;
+//! without Redis (or while it is unreachable) each instance keeps its own.
+
+use crate::cache::keys::payment_provider::CircuitKey;
+use crate::cache::{CacheError, RedisCache};
+use crate::payments::types::ProviderName;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// Optimistic update attempts before falling back to local state
+const MAX_SHARED_UPDATE_ATTEMPTS: usize = 5;
+/// Idle circuits are dropped from Redis after this long
+const CIRCUIT_RECORD_TTL_SECS: u64 = 86_400;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Traffic flows; outcomes are counted
+    #[default]
+    Closed,
+    /// Traffic is refused until the cool-down passes
+    Open,
+    /// A limited number of probe requests are let through
+    HalfOpen,
+}
+
+impl std::fmt::Display for CircuitState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CircuitState::Closed => write!(f, "closed"),
+            CircuitState::Open => write!(f, "open"),
+            CircuitState::HalfOpen => write!(f, "half_open"),
+        }
+    }
+}
+
+/// How a provider call ended, as far as the breaker is concerned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallOutcome {
+    /// The provider answered, even if it rejected the request
+    Success,
+    /// Server error, rate limit or connection failure
+    Failure,
+    Timeout,
+}
+
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerConfig {
+    /// Failure share of a window that opens the circuit
+    pub failure_rate_threshold: f64,
+    /// Requests a window needs before its failure rate counts
+    pub min_requests: u64,
+    pub window: Duration,
+    /// Timeouts in a row that open the circuit regardless of rate
+    pub consecutive_timeouts: u64,
+    /// How long an open circuit refuses traffic before probing
+    pub open_duration: Duration,
+    /// Successful probes needed to close a half-open circuit
+    pub half_open_probes: u64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_rate_threshold: 0.5,
+            min_requests: 10,
+            window: Duration::from_secs(60),
+            consecutive_timeouts: 5,
+            open_duration: Duration::from_secs(30),
+            half_open_probes: 3,
+        }
+    }
+}
+
+impl CircuitBreakerConfig {
+    pub fn from_env() -> Self {
+        fn env_u64(name: &str, default: u64) -> u64 {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(default)
+        }
+
+        let mut cfg = Self::default();
+        cfg.failure_rate_threshold = std::env::var("PAYMENT_CIRCUIT_FAILURE_RATE")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|v| *v > 0.0 && *v <= 1.0)
+            .unwrap_or(cfg.failure_rate_threshold);
+        cfg.min_requests = env_u64("PAYMENT_CIRCUIT_MIN_REQUESTS", cfg.min_requests);
+        cfg.window = Duration::from_secs(env_u64(
+            "PAYMENT_CIRCUIT_WINDOW_SECONDS",
+            cfg.window.as_secs(),
+        ));
+        cfg.consecutive_timeouts = env_u64(
+            "PAYMENT_CIRCUIT_CONSECUTIVE_TIMEOUTS",
+            cfg.consecutive_timeouts,
+        );
+        cfg.open_duration = Duration::from_secs(env_u64(
+            "PAYMENT_CIRCUIT_OPEN_SECONDS",
+            cfg.open_duration.as_secs(),
+        ));
+        cfg.half_open_probes = env_u64("PAYMENT_CIRCUIT_HALF_OPEN_PROBES", cfg.half_open_probes);
+        cfg
+    }
+}
+
+/// A state change, for logs and callers that react to it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CircuitTransition {
+    pub from: CircuitState,
+    pub to: CircuitState,
+}
+
+/// One provider's circuit, as stored in Redis. Times are unix seconds.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CircuitRecord {
+    pub state: CircuitState,
+    pub window_started_at: i64,
+    pub window_requests: u64,
+    pub window_failures: u64,
+    pub consecutive_timeouts: u64,
+    /// When the circuit last changed state
+    pub state_changed_at: i64,
+    /// Probes let through and probes that succeeded since going half-open
+    pub probes_admitted: u64,
+    pub probe_successes: u64,
+    /// How many times the circuit has opened
+    pub times_opened: u64,
+}
+
+impl CircuitRecord {
+    fn cool_down_over(&self, now: i64, config: &CircuitBreakerConfig) -> bool {
+        now - self.state_changed_at >= config.open_duration.as_secs() as i64
+    }
+
+    /// Whether the provider would be offered traffic now. Unlike `admit`
+    /// this claims no probe slot.
+    pub fn is_available(&self, now: i64, config: &CircuitBreakerConfig) -> bool {
+        match self.state {
+            CircuitState::Closed => true,
+            CircuitState::Open => self.cool_down_over(now, config),
+            CircuitState::HalfOpen => {
+                self.probes_admitted < config.half_open_probes || self.cool_down_over(now, config)
+            }
+        }
+    }
+
+    /// Decide whether a request may go to the provider. An open circuit past
+    /// its cool-down goes half-open, and requests admitted while half-open
+    /// count as probes.
+    pub fn admit(
+        &mut self,
+        now: i64,
+        config: &CircuitBreakerConfig,
+    ) -> (bool, Option<CircuitTransition>) {
+        match self.state {
+            CircuitState::Closed => (true, None),
+            CircuitState::Open if !self.cool_down_over(now, config) => (false, None),
+            CircuitState::Open => {
+                let transition = self.transition(CircuitState::HalfOpen, now);
+                self.probes_admitted = 1;
+                (true, Some(transition))
+            }
+            CircuitState::HalfOpen => {
+                // Probes that never reported back (crashed instance) must not
+                // wedge the circuit half-open forever
+                if self.cool_down_over(now, config) {
+                    self.state_changed_at = now;
+                    self.probes_admitted = 0;
+                    self.probe_successes = 0;
+                }
+                if self.probes_admitted < config.half_open_probes {
+                    self.probes_admitted += 1;
+                    (true, None)
+                } else {
+                    (false, None)
+                }
+            }
+        }
+    }
+
+    /// Count a finished call, opening or closing the circuit as needed
+    pub fn record(
+        &mut self,
+        outcome: CallOutcome,
+        now: i64,
+        config: &CircuitBreakerConfig,
+    ) -> Option<CircuitTransition> {
+        if outcome == CallOutcome::Timeout {
+            self.consecutive_timeouts += 1;
+        } else {
+            self.consecutive_timeouts = 0;
+        }
+
+        match self.state {
+            CircuitState::Closed => {
+                if now - self.window_started_at >= config.window.as_secs() as i64 {
+                    self.window_started_at = now;
+                    self.window_requests = 0;
+                    self.window_failures = 0;
+                }
+                self.window_requests += 1;
+                if outcome != CallOutcome::Success {
+                    self.window_failures += 1;
+                }
+
+                let rate_tripped = self.window_requests >= config.min_requests
+                    && self.window_failures as f64 / self.window_requests as f64
+                        >= config.failure_rate_threshold;
+                let timeouts_tripped = self.consecutive_timeouts >= config.consecutive_timeouts;
+                (rate_tripped || timeouts_tripped).then(|| self.transition(CircuitState::Open, now))
+            }
+            CircuitState::HalfOpen if outcome == CallOutcome::Success => {
+                self.probe_successes += 1;
+                (self.probe_successes >= config.half_open_probes)
+                    .then(|| self.transition(CircuitState::Closed, now))
+            }
+            CircuitState::HalfOpen => Some(self.transition(CircuitState::Open, now)),
+            // A call admitted before the circuit opened; nothing to learn
+            CircuitState::Open => None,
+        }
+    }
+
+    fn transition(&mut self, to: CircuitState, now: i64) -> CircuitTransition {
+        let transition = CircuitTransition {
+            from: self.state,
+            to,
+        };
+        self.state = to;
+        self.state_changed_at = now;
+        self.probes_admitted = 0;
+        self.probe_successes = 0;
+        match to {
+            CircuitState::Open => self.times_opened += 1,
+            CircuitState::Closed => {
+                self.window_started_at = now;
+                self.window_requests = 0;
+                self.window_failures = 0;
+                self.consecutive_timeouts = 0;
+            }
+            CircuitState::HalfOpen => {}
+        }
+        transition
+    }
+}
+
+/// A provider's circuit for health and metrics output
+#[derive(Debug, Clone, Serialize)]
+pub struct CircuitSnapshot {
+    pub provider: ProviderName,
+    pub available: bool,
+    #[serde(flatten)]
+    pub record: CircuitRecord,
+}
+
+pub struct ProviderCircuitBreaker {
+    cache: Option<RedisCache>,
+    local: Mutex<HashMap<ProviderName, CircuitRecord>>,
+    config: CircuitBreakerConfig,
+}
+
+impl ProviderCircuitBreaker {
+    pub fn new(cache: Option<RedisCache>, config: CircuitBreakerConfig) -> Self {
+        Self {
+            cache,
+            local: Mutex::new(HashMap::new()),
+            config,
+        }
+    }
+
+    /// Claim permission to call `provider`; false while its circuit is open
+    pub async fn allow(&self, provider: &ProviderName) -> bool {
+        let config = self.config.clone();
+        let (allowed, transition) = self
+            .update(provider, move |record, now| record.admit(now, &config))
+            .await;
+        if let Some(transition) = transition {
+            log_transition(provider, transition);
+        }
+        allowed
+    }
+
+    /// Report how a call to `provider` ended
+    pub async fn record(&self, provider: &ProviderName, outcome: CallOutcome) {
+        let config = self.config.clone();
+        let transition = self
+            .update(provider, move |record, now| {
+                record.record(outcome, now, &config)
+            })
+            .await;
+        if let Some(transition) = transition {
+            log_transition(provider, transition);
+        }
+    }
+
+    /// Whether `provider` should be offered traffic right now
+    pub async fn is_available(&self, provider: &ProviderName) -> bool {
+        self.current(provider)
+            .await
+            .is_available(chrono::Utc::now().timestamp(), &self.config)
+    }
+
+    pub async fn state(&self, provider: &ProviderName) -> CircuitState {
+        self.current(provider).await.state
+    }
+
+    pub async fn snapshots(&self, providers: &[ProviderName]) -> Vec<CircuitSnapshot> {
+        let now = chrono::Utc::now().timestamp();
+        let mut snapshots = Vec::with_capacity(providers.len());
+        for provider in providers {
+            let record = self.current(provider).await;
+            snapshots.push(CircuitSnapshot {
+                provider: provider.clone(),
+                available: record.is_available(now, &self.config),
+                record,
+            });
+        }
+        snapshots
+    }
+
+    async fn current(&self, provider: &ProviderName) -> CircuitRecord {
+        if let Some(cache) = &self.cache {
+            match read_shared(cache, &CircuitKey::new(provider.as_str()).to_string()).await {
+                Ok(record) => return record,
+                Err(e) => {
+                    warn!(provider = %provider, error = %e, "circuit state unavailable in Redis, using local state");
+                }
+            }
+        }
+        self.local
+            .lock()
+            .await
+            .get(provider)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Apply `f` to the provider's record, atomically across instances when
+    /// Redis is available
+    async fn update<R>(
+        &self,
+        provider: &ProviderName,
+        f: impl Fn(&mut CircuitRecord, i64) -> R,
+    ) -> R {
+        if let Some(cache) = &self.cache {
+            let key = CircuitKey::new(provider.as_str()).to_string();
+            match update_shared(cache, &key, &f).await {
+                Ok(result) => return result,
+                Err(e) => {
+                    warn!(provider = %provider, error = %e, "circuit state update failed in Redis, using local state");
+                }
+            }
+        }
+        let mut local = self.local.lock().await;
+        let record = local.entry(provider.clone()).or_default();
+        f(record, chrono::Utc::now().timestamp())
+    }
+}
+
+fn log_transition(provider: &ProviderName, transition: CircuitTransition) {
+    match transition.to {
+        CircuitState::Open => warn!(
+            provider = %provider,
+            from = %transition.from,
+            "payment provider circuit opened"
+        ),
+        _ => info!(
+            provider = %provider,
+            from = %transition.from,
+            to = %transition.to,
+            "payment provider circuit changed state"
+        ),
+    }
+}
+
+async fn read_shared(cache: &RedisCache, key: &str) -> Result<CircuitRecord, CacheError> {
+    let mut conn = cache.get_connection().await?;
+    let raw: Option<String> = conn.get(key).await?;
+    Ok(raw
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default())
+}
+
+/// Read-modify-write under WATCH, retrying when another instance wrote the
+/// record in between
+async fn update_shared<R>(
+    cache: &RedisCache,
+    key: &str,
+    f: &impl Fn(&mut CircuitRecord, i64) -> R,
+) -> Result<R, CacheError> {
+    let mut conn = cache.get_connection().await?;
+    for _ in 0..MAX_SHARED_UPDATE_ATTEMPTS {
+        let _: () = redis::cmd("WATCH").arg(key).query_async(&mut *conn).await?;
+        let raw: Option<String> = conn.get(key).await?;
+        let mut record: CircuitRecord = raw
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+        let result = f(&mut record, chrono::Utc::now().timestamp());
+
+        let committed: Option<(String,)> = redis::pipe()
+            .atomic()
+            .set_ex(
+                key,
+                serde_json::to_string(&record)?,
+                CIRCUIT_RECORD_TTL_SECS,
+            )
+            .query_async(&mut *conn)
+            .await?;
+        if committed.is_some() {
+            return Ok(result);
+        }
+    }
+    let _: () = redis::cmd("UNWATCH").query_async(&mut *conn).await?;
+    Err(CacheError::OperationError(format!(
+        "circuit record {} kept changing during update",
+        key
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> CircuitBreakerConfig {
+        CircuitBreakerConfig {
+            failure_rate_threshold: 0.5,
+            min_requests: 4,
+            window: Duration::from_secs(60),
+            consecutive_timeouts: 3,
+            open_duration: Duration::from_secs(30),
+            half_open_probes: 2,
+        }
+    }
+
+    #[test]
+    fn test_opens_on_failure_rate() {
+        let config = config();
+        let mut record = CircuitRecord::default();
+        assert_eq!(record.record(CallOutcome::Success, 100, &config), None);
+        assert_eq!(record.record(CallOutcome::Failure, 101, &config), None);
+        assert_eq!(record.record(CallOutcome::Success, 102, &config), None);
+        let transition = record.record(CallOutcome::Failure, 103, &config);
+        assert_eq!(
+            transition,
+            Some(CircuitTransition {
+                from: CircuitState::Closed,
+                to: CircuitState::Open
+            })
+        );
+        assert_eq!(record.times_opened, 1);
+        assert_eq!(record.admit(110, &config), (false, None));
+        assert!(!record.is_available(110, &config));
+    }
+
+    #[test]
+    fn test_opens_on_consecutive_timeouts() {
+        let config = config();
+        let mut record = CircuitRecord::default();
+        for _ in 0..10 {
+            record.record(CallOutcome::Success, 100, &config);
+        }
+        record.record(CallOutcome::Timeout, 101, &config);
+        record.record(CallOutcome::Timeout, 102, &config);
+        assert_eq!(record.state, CircuitState::Closed);
+        record.record(CallOutcome::Timeout, 103, &config);
+        assert_eq!(record.state, CircuitState::Open);
+    }
+
+    #[test]
+    fn test_old_failures_age_out_of_the_window() {
+        let config = config();
+        let mut record = CircuitRecord::default();
+        record.record(CallOutcome::Failure, 100, &config);
+        record.record(CallOutcome::Failure, 101, &config);
+        record.record(CallOutcome::Failure, 102, &config);
+        // A new window starts before the fourth request is counted
+        record.record(CallOutcome::Failure, 200, &config);
+        assert_eq!(record.state, CircuitState::Closed);
+        assert_eq!(record.window_requests, 1);
+    }
+
+    #[test]
+    fn test_half_open_probes_close_or_reopen() {
+        let config = config();
+        let mut record = CircuitRecord::default();
+        for t in 0..4 {
+            record.record(CallOutcome::Failure, t, &config);
+        }
+        assert_eq!(record.state, CircuitState::Open);
+
+        // After the cool-down two probes are admitted, a third is not
+        let (allowed, transition) = record.admit(40, &config);
+        assert!(allowed);
+        assert_eq!(transition.unwrap().to, CircuitState::HalfOpen);
+        assert_eq!(record.admit(41, &config), (true, None));
+        assert_eq!(record.admit(42, &config), (false, None));
+
+        assert_eq!(record.record(CallOutcome::Success, 43, &config), None);
+        let closed = record.record(CallOutcome::Success, 44, &config);
+        assert_eq!(closed.unwrap().to, CircuitState::Closed);
+        assert_eq!(record.window_requests, 0);
+
+        // A failed probe reopens the circuit for another cool-down
+        for t in 50..54 {
+            record.record(CallOutcome::Failure, t, &config);
+        }
+        record.admit(90, &config);
+        let reopened = record.record(CallOutcome::Failure, 91, &config);
+        assert_eq!(reopened.unwrap().to, CircuitState::Open);
+        assert_eq!(record.times_opened, 3);
+        assert_eq!(record.admit(100, &config), (false, None));
+    }
+}
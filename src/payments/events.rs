@@ -0,0 +1,156 @@
+//! Provider-agnostic webhook event taxonomy
+//!
+//! Each provider maps its own event names (and, where the name alone is
+//! ambiguous, the payload status) onto [`PaymentEventKind`], and derives a
+//! stable event ID so redelivered webhooks deduplicate.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentEventKind {
+    /// Collection settled
+    ChargeSucceeded,
+    ChargeFailed,
+    /// Customer left the checkout without paying
+    ChargeAbandoned,
+    /// Payout delivered to the recipient
+    TransferSucceeded,
+    TransferFailed,
+    /// Payout came back after being reported successful
+    TransferReversed,
+    /// Money returned to the customer
+    RefundProcessed,
+    RefundFailed,
+    /// Customer disputed a collection with their bank
+    DisputeOpened,
+    /// Dispute closed in our favour; the funds stay with us
+    DisputeWon,
+    /// Dispute closed against us; the funds were charged back
+    DisputeLost,
+    SubscriptionCreated,
+    SubscriptionCancelled,
+    SubscriptionPaymentFailed,
+    /// Event with no meaning to us; recorded but not acted on
+    Unknown,
+}
+
+impl PaymentEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaymentEventKind::ChargeSucceeded => "charge_succeeded",
+            PaymentEventKind::ChargeFailed => "charge_failed",
+            PaymentEventKind::ChargeAbandoned => "charge_abandoned",
+            PaymentEventKind::TransferSucceeded => "transfer_succeeded",
+            PaymentEventKind::TransferFailed => "transfer_failed",
+            PaymentEventKind::TransferReversed => "transfer_reversed",
+            PaymentEventKind::RefundProcessed => "refund_processed",
+            PaymentEventKind::RefundFailed => "refund_failed",
+            PaymentEventKind::DisputeOpened => "dispute_opened",
+            PaymentEventKind::DisputeWon => "dispute_won",
+            PaymentEventKind::DisputeLost => "dispute_lost",
+            PaymentEventKind::SubscriptionCreated => "subscription_created",
+            PaymentEventKind::SubscriptionCancelled => "subscription_cancelled",
+            PaymentEventKind::SubscriptionPaymentFailed => "subscription_payment_failed",
+            PaymentEventKind::Unknown => "unknown",
+        }
+    }
+
+    pub fn is_dispute(&self) -> bool {
+        matches!(
+            self,
+            PaymentEventKind::DisputeOpened
+                | PaymentEventKind::DisputeWon
+                | PaymentEventKind::DisputeLost
+        )
+    }
+}
+
+impl std::fmt::Display for PaymentEventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Look an event name up in a provider's mapping table
+pub fn lookup_kind(
+    table: &[(&str, PaymentEventKind)],
+    event_type: &str,
+) -> Option<PaymentEventKind> {
+    table
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(event_type))
+        .map(|(_, kind)| *kind)
+}
+
+/// Stable ID for a webhook event: the event name, the provider's ID for the
+/// object it concerns and that object's status, so a charge moving from
+/// pending to successful is two events but a redelivery is one. Without an
+/// object ID the body's SHA-256 is used instead.
+pub fn derive_event_id(
+    event_type: &str,
+    object_id: Option<&str>,
+    status: Option<&str>,
+    raw_body: &[u8],
+) -> String {
+    match object_id.map(str::trim).filter(|id| !id.is_empty()) {
+        Some(id) => match status.map(str::trim).filter(|s| !s.is_empty()) {
+            Some(status) => format!("{}:{}:{}", event_type, id, status.to_lowercase()),
+            None => format!("{}:{}", event_type, id),
+        },
+        None => format!(
+            "{}:sha256:{}",
+            event_type,
+            hex::encode(Sha256::digest(raw_body))
+        ),
+    }
+}
+
+/// String form of an ID that providers send as either a number or a string
+pub fn json_id(value: Option<&serde_json::Value>) -> Option<String> {
+    match value? {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_id_is_stable_and_status_sensitive() {
+        let body = br#"{"event":"charge.completed"}"#;
+        let pending = derive_event_id("charge.completed", Some("42"), Some("pending"), body);
+        let successful = derive_event_id("charge.completed", Some("42"), Some("Successful"), body);
+        assert_eq!(pending, "charge.completed:42:pending");
+        assert_eq!(successful, "charge.completed:42:successful");
+        assert_eq!(
+            derive_event_id("charge.completed", Some("42"), Some("Successful"), b"{}"),
+            successful
+        );
+    }
+
+    #[test]
+    fn event_id_falls_back_to_body_hash() {
+        let first = derive_event_id("unknown", None, None, b"{\"a\":1}");
+        assert_eq!(
+            first,
+            derive_event_id("unknown", Some(" "), None, b"{\"a\":1}")
+        );
+        assert_ne!(first, derive_event_id("unknown", None, None, b"{\"a\":2}"));
+        assert!(first.starts_with("unknown:sha256:"));
+    }
+
+    #[test]
+    fn lookup_is_case_insensitive() {
+        let table = [("charge.success", PaymentEventKind::ChargeSucceeded)];
+        assert_eq!(
+            lookup_kind(&table, "CHARGE.SUCCESS"),
+            Some(PaymentEventKind::ChargeSucceeded)
+        );
+        assert_eq!(lookup_kind(&table, "charge.failed"), None);
+    }
+}
@@ -1,9 +1,11 @@
+use crate::payments::circuit_breaker::ProviderCircuitBreaker;
 use crate::payments::error::{PaymentError, PaymentResult};
 use crate::payments::provider::PaymentProvider;
 use crate::payments::providers::{FlutterwaveProvider, MpesaProvider, PaystackProvider};
 use crate::payments::types::ProviderName;
 use std::collections::HashMap;
 use std::str::FromStr;
+use std::sync::Arc;
 
 #[derive(Debug, Clone)]
 pub struct PaymentFactoryConfig {
@@ -69,16 +71,27 @@ impl PaymentFactoryConfig {
 
 pub struct PaymentProviderFactory {
     config: PaymentFactoryConfig,
+    circuit_breaker: Option<Arc<ProviderCircuitBreaker>>,
 }
 
 impl PaymentProviderFactory {
     pub fn from_env() -> PaymentResult<Self> {
         let config = PaymentFactoryConfig::from_env()?;
-        Ok(Self { config })
+        Ok(Self::with_config(config))
     }
 
     pub fn with_config(config: PaymentFactoryConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            circuit_breaker: None,
+        }
+    }
+
+    /// Gate provider API calls on their circuits and keep providers with an
+    /// open circuit out of routing
+    pub fn with_circuit_breaker(mut self, breaker: Arc<ProviderCircuitBreaker>) -> Self {
+        self.circuit_breaker = Some(breaker);
+        self
     }
 
     pub fn get_provider(&self, provider: ProviderName) -> PaymentResult<Box<dyn PaymentProvider>> {
@@ -89,9 +102,22 @@ impl PaymentProviderFactory {
             });
         }
 
+        let breaker = self.circuit_breaker.clone();
         match provider {
-            ProviderName::Paystack => Ok(Box::new(PaystackProvider::from_env()?)),
-            ProviderName::Flutterwave => Ok(Box::new(FlutterwaveProvider::from_env()?)),
+            ProviderName::Paystack => {
+                let provider = PaystackProvider::from_env()?;
+                Ok(match breaker {
+                    Some(breaker) => Box::new(provider.with_circuit_breaker(breaker)),
+                    None => Box::new(provider),
+                })
+            }
+            ProviderName::Flutterwave => {
+                let provider = FlutterwaveProvider::from_env()?;
+                Ok(match breaker {
+                    Some(breaker) => Box::new(provider.with_circuit_breaker(breaker)),
+                    None => Box::new(provider),
+                })
+            }
             ProviderName::Mpesa => Ok(Box::new(MpesaProvider::from_env()?)),
         }
     }
@@ -113,29 +139,23 @@ impl PaymentProviderFactory {
         self.get_provider(provider)
     }
 
-    pub fn get_cheapest_provider_for_amount(
-        &self,
-        _amount_minor_units: i64,
-    ) -> PaymentResult<Box<dyn PaymentProvider>> {
-        let cheapest = self
-            .config
-            .enabled_providers
-            .iter()
-            .min_by_key(|p| {
-                self.config
-                    .provider_fee_bps
-                    .get(*p)
-                    .copied()
-                    .unwrap_or(u32::MAX)
-            })
-            .cloned()
-            .unwrap_or(self.config.default_provider.clone());
-        self.get_provider(cheapest)
-    }
-
     pub fn list_available_providers(&self) -> Vec<ProviderName> {
         self.config.enabled_providers.clone()
     }
+
+    /// Enabled providers whose circuit is not open
+    pub async fn list_routable_providers(&self) -> Vec<ProviderName> {
+        let Some(breaker) = &self.circuit_breaker else {
+            return self.list_available_providers();
+        };
+        let mut routable = Vec::with_capacity(self.config.enabled_providers.len());
+        for provider in &self.config.enabled_providers {
+            if breaker.is_available(provider).await {
+                routable.push(provider.clone());
+            }
+        }
+        routable
+    }
 }
 
 #[cfg(test)]
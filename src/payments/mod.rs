@@ -3,9 +3,13 @@
 //! This module provides a unified interface for payment providers (Paystack, Flutterwave, M-Pesa)
 //! to support fiat transactions in African markets.
 
+#[cfg(feature = "database")]
+pub mod circuit_breaker;
 #[cfg(feature = "database")]
 pub mod error;
 #[cfg(feature = "database")]
+pub mod events;
+#[cfg(feature = "database")]
 pub mod factory;
 #[cfg(feature = "database")]
 pub mod provider;
@@ -1,7 +1,8 @@
-use crate::payments::error::PaymentResult;
+use crate::payments::error::{PaymentError, PaymentResult};
 use crate::payments::types::{
-    PaymentRequest, PaymentResponse, ProviderName, StatusRequest, StatusResponse, WebhookEvent,
-    WebhookVerificationResult, WithdrawalRequest, WithdrawalResponse,
+    BulkWithdrawalResponse, DisputeEvidence, Money, PaymentRequest, PaymentResponse,
+    ProviderDispute, ProviderName, RefundRequest, RefundResponse, StatusRequest, StatusResponse,
+    WebhookEvent, WebhookVerificationResult, WithdrawalRequest, WithdrawalResponse,
 };
 use async_trait::async_trait;
 
@@ -18,6 +19,83 @@ pub trait PaymentProvider: Send + Sync {
 
     async fn get_payment_status(&self, request: StatusRequest) -> PaymentResult<StatusResponse>;
 
+    /// Most transfers one bulk withdrawal may carry, or `None` if the
+    /// provider has no bulk transfer API
+    fn max_bulk_withdrawals(&self) -> Option<usize> {
+        None
+    }
+
+    /// Pay out several withdrawals in one call. Transfers are rejected or
+    /// accepted individually, so a successful call can still carry failed
+    /// items.
+    async fn process_bulk_withdrawal(
+        &self,
+        _requests: Vec<WithdrawalRequest>,
+    ) -> PaymentResult<BulkWithdrawalResponse> {
+        Err(unsupported(self.name(), "bulk transfers are"))
+    }
+
+    /// Status of a transfer we initiated, looked up by our reference.
+    /// `None` if the provider has no transfer with that reference. Providers
+    /// without a dedicated transfer lookup answer through `get_payment_status`.
+    async fn get_transfer_status(
+        &self,
+        transaction_reference: &str,
+    ) -> PaymentResult<Option<StatusResponse>> {
+        self.get_payment_status(StatusRequest {
+            transaction_reference: Some(transaction_reference.to_string()),
+            provider_reference: None,
+        })
+        .await
+        .map(Some)
+    }
+
+    /// Available payout float held with the provider in `currency`.
+    /// Providers without a balance endpoint report an error.
+    async fn get_balance(&self, currency: &str) -> PaymentResult<Money> {
+        Err(PaymentError::ProviderError {
+            provider: self.name().to_string(),
+            message: format!("balance lookup for {} is not supported", currency),
+            provider_code: None,
+            retryable: false,
+        })
+    }
+
+    /// Refund a collected payment, in full or in part. Providers without a
+    /// refund API report an error.
+    async fn refund(&self, _request: RefundRequest) -> PaymentResult<RefundResponse> {
+        Err(unsupported(self.name(), "refunds are"))
+    }
+
+    /// Disputes opened or updated since `since`. Providers without a
+    /// dispute API report an error.
+    async fn list_disputes(
+        &self,
+        _since: chrono::DateTime<chrono::Utc>,
+    ) -> PaymentResult<Vec<ProviderDispute>> {
+        Err(unsupported(self.name(), "dispute management is"))
+    }
+
+    /// Concede a dispute, letting the provider refund `amount`
+    async fn accept_dispute(
+        &self,
+        _dispute_id: &str,
+        _amount: &Money,
+        _message: &str,
+    ) -> PaymentResult<()> {
+        Err(unsupported(self.name(), "dispute management is"))
+    }
+
+    /// Contest a dispute with evidence
+    async fn contest_dispute(
+        &self,
+        _dispute_id: &str,
+        _amount: &Money,
+        _evidence: &DisputeEvidence,
+    ) -> PaymentResult<()> {
+        Err(unsupported(self.name(), "dispute management is"))
+    }
+
     fn name(&self) -> ProviderName;
 
     fn supported_currencies(&self) -> &'static [&'static str];
@@ -31,13 +109,27 @@ pub trait PaymentProvider: Send + Sync {
     ) -> PaymentResult<WebhookVerificationResult>;
 
     fn parse_webhook_event(&self, payload: &[u8]) -> PaymentResult<WebhookEvent>;
+
+    /// The dispute a dispute webhook concerns, when the payload carries one
+    fn dispute_from_webhook(&self, _event: &WebhookEvent) -> Option<ProviderDispute> {
+        None
+    }
+}
+
+fn unsupported(provider: ProviderName, feature: &str) -> PaymentError {
+    PaymentError::ProviderError {
+        provider: provider.to_string(),
+        message: format!("{} not supported", feature),
+        provider_code: None,
+        retryable: false,
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::payments::types::{
-        Money, PaymentMethod, PaymentState, ProviderName, WithdrawalMethod, WithdrawalRecipient,
+        PaymentMethod, PaymentState, ProviderName, WithdrawalMethod, WithdrawalRecipient,
     };
 
     struct MockProvider;
@@ -121,6 +213,8 @@ mod tests {
             Ok(WebhookEvent {
                 provider: ProviderName::Paystack,
                 event_type: "mock".to_string(),
+                kind: crate::payments::events::PaymentEventKind::Unknown,
+                event_id: "mock".to_string(),
                 transaction_reference: None,
                 provider_reference: None,
                 status: Some(PaymentState::Success),
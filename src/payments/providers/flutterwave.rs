@@ -1,14 +1,18 @@
+use crate::payments::circuit_breaker::ProviderCircuitBreaker;
 use crate::payments::error::{PaymentError, PaymentResult};
+use crate::payments::events::{derive_event_id, json_id, lookup_kind, PaymentEventKind};
 use crate::payments::provider::PaymentProvider;
 use crate::payments::types::{
-    Money, PaymentMethod, PaymentRequest, PaymentResponse, PaymentState, ProviderName,
-    StatusRequest, StatusResponse, WebhookEvent, WebhookVerificationResult, WithdrawalMethod,
-    WithdrawalRequest, WithdrawalResponse,
+    BulkWithdrawalItem, BulkWithdrawalResponse, DisputeEvidence, DisputeState, Money, PaymentMethod, PaymentRequest, PaymentResponse,
+    PaymentState, ProviderDispute, ProviderName, RefundRequest, RefundResponse, StatusRequest,
+    StatusResponse, WebhookEvent, WebhookVerificationResult, WithdrawalMethod, WithdrawalRequest,
+    WithdrawalResponse,
 };
 use crate::payments::utils::{secure_eq, PaymentHttpClient};
 use async_trait::async_trait;
 use serde::Deserialize;
 use serde_json::Value as JsonValue;
+use std::sync::Arc;
 use std::time::Duration;
 use tracing::info;
 
@@ -69,6 +73,12 @@ impl FlutterwaveProvider {
         Self::new(FlutterwaveConfig::from_env()?)
     }
 
+    /// Route this provider's API calls through its circuit
+    pub fn with_circuit_breaker(mut self, breaker: Arc<ProviderCircuitBreaker>) -> Self {
+        self.http = self.http.with_circuit_breaker(breaker, ProviderName::Flutterwave);
+        self
+    }
+
     fn endpoint(&self, path: &str) -> String {
         format!("{}{}", self.config.base_url, path)
     }
@@ -85,6 +95,134 @@ impl FlutterwaveProvider {
             })
     }
 
+    /// Available balance from a `/balances/{currency}` response body
+    fn balance_from_data(data: Option<&JsonValue>, currency: &str) -> PaymentResult<Money> {
+        data.and_then(|d| d.get("available_balance"))
+            .and_then(|v| {
+                v.as_str()
+                    .map(|s| s.to_string())
+                    .or_else(|| v.as_f64().map(|n| n.to_string()))
+            })
+            .map(|amount| Money {
+                amount,
+                currency: currency.to_string(),
+            })
+            .ok_or_else(|| PaymentError::ProviderError {
+                provider: "flutterwave".to_string(),
+                message: format!("balance response has no available_balance for {}", currency),
+                provider_code: None,
+                retryable: true,
+            })
+    }
+
+    /// Accept or decline a chargeback
+    async fn update_chargeback(
+        &self,
+        dispute_id: &str,
+        action: &str,
+        comment: &str,
+    ) -> PaymentResult<()> {
+        let body = serde_json::json!({ "action": action, "comment": comment });
+        let raw: FlutterwaveEnvelope = self
+            .http
+            .request_json(
+                reqwest::Method::PUT,
+                &self.endpoint(&format!("/chargebacks/{}", dispute_id)),
+                Some(&self.config.secret_key),
+                Some(&body),
+                &[("Content-Type", "application/json")],
+            )
+            .await
+            .map_err(|e| match e {
+                PaymentError::ProviderError { message, .. } => Self::map_message_error(message),
+                other => other,
+            })?;
+        if raw.status.to_lowercase() != "success" {
+            return Err(Self::map_message_error(raw.message));
+        }
+        info!(dispute_id = %dispute_id, action = %action, "flutterwave chargeback updated");
+        Ok(())
+    }
+
+    /// Flutterwave's numeric id for a payment, which its refund endpoint
+    /// takes instead of our reference
+    async fn transaction_id(&self, request: &RefundRequest) -> PaymentResult<String> {
+        if let Some(id) = request
+            .provider_reference
+            .as_deref()
+            .filter(|r| !r.is_empty() && r.chars().all(|c| c.is_ascii_digit()))
+        {
+            return Ok(id.to_string());
+        }
+        let url = format!(
+            "{}?tx_ref={}",
+            self.endpoint("/transactions/verify_by_reference"),
+            request.transaction_reference
+        );
+        let raw: FlutterwaveEnvelope = self
+            .http
+            .request_json(
+                reqwest::Method::GET,
+                &url,
+                Some(&self.config.secret_key),
+                None,
+                &[],
+            )
+            .await
+            .map_err(|e| match e {
+                PaymentError::ProviderError { message, .. } => Self::map_message_error(message),
+                other => other,
+            })?;
+        if raw.status.to_lowercase() != "success" {
+            return Err(Self::map_message_error(raw.message));
+        }
+        json_id(raw.data.as_ref().and_then(|d| d.get("id"))).ok_or_else(|| {
+            PaymentError::ProviderError {
+                provider: "flutterwave".to_string(),
+                message: format!("no transaction found for {}", request.transaction_reference),
+                provider_code: None,
+                retryable: false,
+            }
+        })
+    }
+
+    /// One entry of a `/bulk-transfers` request
+    fn bulk_entry(request: &WithdrawalRequest) -> PaymentResult<JsonValue> {
+        request.amount.validate_positive("amount")?;
+        if !matches!(request.withdrawal_method, WithdrawalMethod::BankTransfer) {
+            return Err(PaymentError::ValidationError {
+                message: "flutterwave currently supports bank transfer withdrawals only"
+                    .to_string(),
+                field: Some("withdrawal_method".to_string()),
+            });
+        }
+        let account_number = request.recipient.account_number.clone().ok_or(
+            PaymentError::ValidationError {
+                message: "recipient.account_number is required".to_string(),
+                field: Some("recipient.account_number".to_string()),
+            },
+        )?;
+        let bank_code =
+            request
+                .recipient
+                .bank_code
+                .clone()
+                .ok_or(PaymentError::ValidationError {
+                    message: "recipient.bank_code is required".to_string(),
+                    field: Some("recipient.bank_code".to_string()),
+                })?;
+
+        Ok(serde_json::json!({
+            "bank_code": bank_code,
+            "account_number": account_number,
+            "amount": request.amount.amount,
+            "currency": request.amount.currency,
+            "narration": request.reason.clone().unwrap_or_else(|| "Aframp payout".to_string()),
+            "reference": request.transaction_reference,
+            "meta": request.metadata,
+        }))
+    }
+
     fn map_message_error(message: String) -> PaymentError {
         let lowered = message.to_lowercase();
         if lowered.contains("insufficient") || lowered.contains("low balance") {
@@ -384,17 +522,9 @@ impl PaymentProvider for FlutterwaveProvider {
         }
 
         let data = raw.data.unwrap_or_else(|| serde_json::json!({}));
-        let transfer_status = data
-            .get("status")
-            .and_then(|v| v.as_str())
-            .unwrap_or("unknown")
-            .to_lowercase();
-        let status = match transfer_status.as_str() {
-            "successful" | "success" | "completed" => PaymentState::Success,
-            "new" | "pending" | "processing" => PaymentState::Processing,
-            "failed" | "cancelled" => PaymentState::Failed,
-            _ => PaymentState::Unknown,
-        };
+        let status = flutterwave_transfer_state(
+            data.get("status").and_then(|v| v.as_str()).unwrap_or("unknown"),
+        );
 
         let provider_reference = data
             .get("reference")
@@ -421,6 +551,232 @@ impl PaymentProvider for FlutterwaveProvider {
         self.verify_payment(request).await
     }
 
+    fn max_bulk_withdrawals(&self) -> Option<usize> {
+        Some(MAX_BULK_TRANSFERS)
+    }
+
+    async fn process_bulk_withdrawal(
+        &self,
+        requests: Vec<WithdrawalRequest>,
+    ) -> PaymentResult<BulkWithdrawalResponse> {
+        let mut items = Vec::new();
+        let mut bulk_data = Vec::new();
+        let mut submitted = Vec::new();
+        for request in &requests {
+            match Self::bulk_entry(request) {
+                Ok(entry) => {
+                    bulk_data.push(entry);
+                    submitted.push(request.transaction_reference.clone());
+                }
+                Err(e) => items.push(BulkWithdrawalItem {
+                    transaction_reference: request.transaction_reference.clone(),
+                    status: PaymentState::Failed,
+                    provider_reference: None,
+                    failure_reason: Some(e.to_string()),
+                    retryable: e.is_retryable(),
+                }),
+            }
+        }
+        if bulk_data.is_empty() {
+            return Ok(BulkWithdrawalResponse {
+                batch_reference: None,
+                items,
+                provider_data: None,
+            });
+        }
+
+        let payload = serde_json::json!({
+            "title": format!("Aframp payouts {}", chrono::Utc::now().format("%Y-%m-%d %H:%M:%S")),
+            "bulk_data": bulk_data,
+        });
+        let raw: FlutterwaveEnvelope = self
+            .http
+            .request_json(
+                reqwest::Method::POST,
+                &self.endpoint("/bulk-transfers"),
+                Some(&self.config.secret_key),
+                Some(&payload),
+                &[("Content-Type", "application/json")],
+            )
+            .await
+            .map_err(|e| match e {
+                PaymentError::ProviderError { message, .. } => Self::map_message_error(message),
+                other => other,
+            })?;
+        if raw.status.to_lowercase() != "success" {
+            return Err(Self::map_message_error(raw.message));
+        }
+
+        let data = raw.data.unwrap_or_else(|| serde_json::json!({}));
+        let batch_reference = json_id(data.get("id"));
+        info!(batch = ?batch_reference, transfers = submitted.len(), "flutterwave bulk transfer queued");
+        items.extend(flutterwave_queued_items(submitted));
+        Ok(BulkWithdrawalResponse {
+            batch_reference,
+            items,
+            provider_data: Some(data),
+        })
+    }
+
+    async fn get_transfer_status(
+        &self,
+        transaction_reference: &str,
+    ) -> PaymentResult<Option<StatusResponse>> {
+        let url = format!(
+            "{}?reference={}",
+            self.endpoint("/transfers"),
+            transaction_reference
+        );
+        let raw: FlutterwaveEnvelope = self
+            .http
+            .request_json(
+                reqwest::Method::GET,
+                &url,
+                Some(&self.config.secret_key),
+                None,
+                &[],
+            )
+            .await
+            .map_err(|e| match e {
+                PaymentError::ProviderError { message, .. } => Self::map_message_error(message),
+                other => other,
+            })?;
+        if raw.status.to_lowercase() != "success" {
+            return Err(Self::map_message_error(raw.message));
+        }
+
+        Ok(raw
+            .data
+            .as_ref()
+            .and_then(|d| d.as_array())
+            .and_then(|transfers| transfers.first())
+            .map(flutterwave_transfer_status))
+    }
+
+    async fn get_balance(&self, currency: &str) -> PaymentResult<Money> {
+        let currency = currency.to_uppercase();
+        let raw: FlutterwaveEnvelope = self
+            .http
+            .request_json(
+                reqwest::Method::GET,
+                &self.endpoint(&format!("/balances/{}", currency)),
+                Some(&self.config.secret_key),
+                None,
+                &[],
+            )
+            .await
+            .map_err(|e| match e {
+                PaymentError::ProviderError { message, .. } => Self::map_message_error(message),
+                other => other,
+            })?;
+
+        if raw.status.to_lowercase() != "success" {
+            return Err(Self::map_message_error(raw.message));
+        }
+
+        Self::balance_from_data(raw.data.as_ref(), &currency)
+    }
+
+    async fn refund(&self, request: RefundRequest) -> PaymentResult<RefundResponse> {
+        let id = self.transaction_id(&request).await?;
+        let mut body = serde_json::json!({});
+        if let Some(amount) = &request.amount {
+            amount.validate_positive("amount")?;
+            body["amount"] = serde_json::json!(amount.amount);
+        }
+        if let Some(reason) = &request.reason {
+            body["comments"] = serde_json::json!(reason);
+        }
+
+        let raw: FlutterwaveEnvelope = self
+            .http
+            .request_json(
+                reqwest::Method::POST,
+                &self.endpoint(&format!("/transactions/{}/refund", id)),
+                Some(&self.config.secret_key),
+                Some(&body),
+                &[("Content-Type", "application/json")],
+            )
+            .await
+            .map_err(|e| match e {
+                PaymentError::ProviderError { message, .. } => Self::map_message_error(message),
+                other => other,
+            })?;
+        if raw.status.to_lowercase() != "success" {
+            return Err(Self::map_message_error(raw.message));
+        }
+        let refund = flutterwave_refund(&raw.data.unwrap_or_else(|| serde_json::json!({})));
+        info!(transaction_id = %id, refund = ?refund.refund_reference, "flutterwave refund initiated");
+        Ok(refund)
+    }
+
+    async fn list_disputes(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> PaymentResult<Vec<ProviderDispute>> {
+        let mut disputes = Vec::new();
+        for page in 1..=DISPUTE_PAGE_LIMIT {
+            let raw: FlutterwaveEnvelope = self
+                .http
+                .request_json(
+                    reqwest::Method::GET,
+                    &self.endpoint(&format!(
+                        "/chargebacks?from={}&page={}",
+                        since.format("%Y-%m-%d"),
+                        page
+                    )),
+                    Some(&self.config.secret_key),
+                    None,
+                    &[],
+                )
+                .await
+                .map_err(|e| match e {
+                    PaymentError::ProviderError { message, .. } => Self::map_message_error(message),
+                    other => other,
+                })?;
+            if raw.status.to_lowercase() != "success" {
+                return Err(Self::map_message_error(raw.message));
+            }
+            let items = raw
+                .data
+                .as_ref()
+                .and_then(|d| d.as_array())
+                .cloned()
+                .unwrap_or_default();
+            if items.is_empty() {
+                break;
+            }
+            disputes.extend(items.iter().filter_map(flutterwave_dispute));
+        }
+        Ok(disputes)
+    }
+
+    async fn accept_dispute(
+        &self,
+        dispute_id: &str,
+        _amount: &Money,
+        message: &str,
+    ) -> PaymentResult<()> {
+        self.update_chargeback(dispute_id, "accept", message).await
+    }
+
+    async fn contest_dispute(
+        &self,
+        dispute_id: &str,
+        _amount: &Money,
+        evidence: &DisputeEvidence,
+    ) -> PaymentResult<()> {
+        // Chargebacks take a comment only, so the evidence goes in it
+        let mut comment = format!("{}\n\n{}", evidence.message, evidence.service_details);
+        if let Some(date) = &evidence.delivery_date {
+            comment.push_str(&format!("\nDelivered: {}", date));
+        }
+        if !evidence.files.is_empty() {
+            comment.push_str(&format!("\nEvidence: {}", evidence.files.join(", ")));
+        }
+        self.update_chargeback(dispute_id, "decline", &comment).await
+    }
+
     fn name(&self) -> ProviderName {
         ProviderName::Flutterwave
     }
@@ -496,8 +852,13 @@ impl PaymentProvider for FlutterwaveProvider {
                     .map(|s| s.to_string())
             });
 
+        let data_status = data.get("status").and_then(|v| v.as_str());
+        let object_id = json_id(data.get("id")).or_else(|| provider_reference.clone());
+
         Ok(WebhookEvent {
             provider: ProviderName::Flutterwave,
+            kind: flutterwave_event_kind(&event_type, data_status),
+            event_id: derive_event_id(&event_type, object_id.as_deref(), data_status, payload),
             event_type,
             transaction_reference: data
                 .get("tx_ref")
@@ -514,6 +875,191 @@ impl PaymentProvider for FlutterwaveProvider {
             received_at: chrono::Utc::now().to_rfc3339(),
         })
     }
+
+    fn dispute_from_webhook(&self, event: &WebhookEvent) -> Option<ProviderDispute> {
+        if !event.kind.is_dispute() {
+            return None;
+        }
+        event.payload.get("data").and_then(flutterwave_dispute)
+    }
+}
+
+/// Most chargeback pages read in one poll
+const DISPUTE_PAGE_LIMIT: usize = 10;
+
+/// Flutterwave does not document a cap; batches are kept to a size its
+/// dashboard handles comfortably
+const MAX_BULK_TRANSFERS: usize = 100;
+
+/// Transfer status as reported by the transfer endpoints and webhooks
+fn flutterwave_transfer_state(status: &str) -> PaymentState {
+    match status.to_lowercase().as_str() {
+        "successful" | "success" | "completed" => PaymentState::Success,
+        "new" | "pending" | "processing" | "queued" => PaymentState::Processing,
+        "failed" | "cancelled" => PaymentState::Failed,
+        "reversed" => PaymentState::Reversed,
+        _ => PaymentState::Unknown,
+    }
+}
+
+/// Items for a queued bulk transfer. Flutterwave reports each transfer's
+/// outcome later, so all are processing until then.
+fn flutterwave_queued_items(references: Vec<String>) -> Vec<BulkWithdrawalItem> {
+    references
+        .into_iter()
+        .map(|reference| BulkWithdrawalItem {
+            provider_reference: Some(reference.clone()),
+            transaction_reference: reference,
+            status: PaymentState::Processing,
+            failure_reason: None,
+            retryable: false,
+        })
+        .collect()
+}
+
+/// A transfer object, as returned by `/transfers`
+fn flutterwave_transfer_status(data: &JsonValue) -> StatusResponse {
+    StatusResponse {
+        status: flutterwave_transfer_state(
+            data.get("status").and_then(|v| v.as_str()).unwrap_or("unknown"),
+        ),
+        transaction_reference: data
+            .get("reference")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string()),
+        provider_reference: json_id(data.get("id")),
+        amount: major_money(data, "amount"),
+        payment_method: Some(PaymentMethod::BankTransfer),
+        timestamp: data
+            .get("created_at")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string()),
+        failure_reason: data
+            .get("complete_message")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string()),
+        provider_data: Some(data.clone()),
+    }
+}
+
+/// A refund object, as returned by `/transactions/{id}/refund`
+fn flutterwave_refund(data: &JsonValue) -> RefundResponse {
+    let status = match data
+        .get("status")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_lowercase()
+        .as_str()
+    {
+        "completed" | "successful" => PaymentState::Success,
+        "failed" => PaymentState::Failed,
+        _ => PaymentState::Pending,
+    };
+    RefundResponse {
+        status,
+        refund_reference: json_id(data.get("id")),
+        amount_refunded: major_money(data, "amount_refunded"),
+        provider_data: Some(data.clone()),
+    }
+}
+
+/// `field` of `data` as money in major units, with the payload's currency
+fn major_money(data: &JsonValue, field: &str) -> Option<Money> {
+    data.get(field)
+        .and_then(|v| {
+            v.as_str()
+                .map(|s| s.to_string())
+                .or_else(|| v.as_f64().map(|n| n.to_string()))
+        })
+        .map(|amount| Money {
+            amount,
+            currency: data
+                .get("currency")
+                .and_then(|v| v.as_str())
+                .unwrap_or("NGN")
+                .to_string(),
+        })
+}
+
+/// A chargeback object, as returned by `/chargebacks` and sent in
+/// `chargeback.*` webhooks. Amounts are in major units.
+fn flutterwave_dispute(data: &JsonValue) -> Option<ProviderDispute> {
+    let dispute_id = json_id(data.get("id"))?;
+    let state = match flutterwave_event_kind(
+        "chargeback.updated",
+        data.get("status").and_then(|v| v.as_str()),
+    ) {
+        PaymentEventKind::DisputeLost => DisputeState::Lost,
+        PaymentEventKind::DisputeWon => DisputeState::Won,
+        _ => DisputeState::Open,
+    };
+    let amount = major_money(data, "amount");
+
+    Some(ProviderDispute {
+        provider: ProviderName::Flutterwave,
+        dispute_id,
+        transaction_reference: data
+            .get("tx_ref")
+            .or_else(|| data.get("meta").and_then(|m| m.get("tx_ref")))
+            .or_else(|| data.get("flw_ref"))
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string()),
+        state,
+        amount,
+        reason: data
+            .get("comment")
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string()),
+        evidence_due_at: data
+            .get("due_date")
+            .and_then(|v| v.as_str())
+            .and_then(|v| chrono::DateTime::parse_from_rfc3339(v).ok())
+            .map(|v| v.with_timezone(&chrono::Utc)),
+        provider_data: Some(data.clone()),
+    })
+}
+
+const FLUTTERWAVE_EVENT_KINDS: &[(&str, PaymentEventKind)] = &[
+    ("charge.completed", PaymentEventKind::ChargeSucceeded),
+    ("transfer.completed", PaymentEventKind::TransferSucceeded),
+    ("transfer.reversed", PaymentEventKind::TransferReversed),
+    ("refund.completed", PaymentEventKind::RefundProcessed),
+    ("chargeback.initiated", PaymentEventKind::DisputeOpened),
+    ("subscription.cancelled", PaymentEventKind::SubscriptionCancelled),
+];
+
+/// Flutterwave reports success and failure under one event name, so the
+/// payload status decides the kind
+fn flutterwave_event_kind(event_type: &str, status: Option<&str>) -> PaymentEventKind {
+    let status = status.unwrap_or_default().to_lowercase();
+    if event_type.to_lowercase().starts_with("chargeback.") {
+        return match status.as_str() {
+            "lost" | "accepted" => PaymentEventKind::DisputeLost,
+            "won" | "declined" => PaymentEventKind::DisputeWon,
+            _ => PaymentEventKind::DisputeOpened,
+        };
+    }
+
+    match lookup_kind(FLUTTERWAVE_EVENT_KINDS, event_type) {
+        Some(PaymentEventKind::ChargeSucceeded) => match status.as_str() {
+            "successful" | "success" | "completed" => PaymentEventKind::ChargeSucceeded,
+            "failed" => PaymentEventKind::ChargeFailed,
+            "cancelled" | "abandoned" => PaymentEventKind::ChargeAbandoned,
+            _ => PaymentEventKind::Unknown,
+        },
+        Some(PaymentEventKind::TransferSucceeded) => match status.as_str() {
+            "successful" | "success" => PaymentEventKind::TransferSucceeded,
+            "failed" => PaymentEventKind::TransferFailed,
+            "reversed" => PaymentEventKind::TransferReversed,
+            _ => PaymentEventKind::Unknown,
+        },
+        Some(PaymentEventKind::RefundProcessed) if status == "failed" => {
+            PaymentEventKind::RefundFailed
+        }
+        Some(kind) => kind,
+        None => PaymentEventKind::Unknown,
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -572,4 +1118,90 @@ mod tests {
         assert_eq!(event.provider_reference.as_deref(), Some("flw_1"));
         assert!(matches!(event.status, Some(PaymentState::Success)));
     }
+
+    #[test]
+    fn webhook_kind_follows_payload_status() {
+        let provider = provider();
+        let payload = br#"{"event":"charge.completed","data":{"id":77,"status":"failed","tx_ref":"tx_2"}}"#;
+        let event = provider.parse_webhook_event(payload).unwrap();
+        assert_eq!(event.kind, PaymentEventKind::ChargeFailed);
+        assert_eq!(event.event_id, "charge.completed:77:failed");
+
+        let payload = br#"{"event":"transfer.completed","data":{"id":9,"status":"REVERSED","reference":"po_1"}}"#;
+        let event = provider.parse_webhook_event(payload).unwrap();
+        assert_eq!(event.kind, PaymentEventKind::TransferReversed);
+    }
+
+    #[test]
+    fn refund_response_is_read() {
+        let data = serde_json::json!({
+            "id": 75923,
+            "tx_id": 1212441,
+            "amount_refunded": 2500,
+            "status": "completed",
+            "currency": "NGN"
+        });
+        let refund = flutterwave_refund(&data);
+        assert_eq!(refund.status, PaymentState::Success);
+        assert_eq!(refund.refund_reference.as_deref(), Some("75923"));
+        assert_eq!(refund.amount_refunded.unwrap().amount, "2500");
+
+        let pending = flutterwave_refund(&serde_json::json!({ "id": 1, "status": "pending" }));
+        assert_eq!(pending.status, PaymentState::Pending);
+    }
+
+    #[test]
+    fn chargeback_webhook_carries_dispute() {
+        let provider = provider();
+        let payload = br#"{"event":"chargeback.initiated","data":{"id":310,"amount":5000,"currency":"NGN","status":"initiated","flw_ref":"FLW-1","tx_ref":"tx_3","comment":"not received","due_date":"2026-03-22T09:00:00Z"}}"#;
+        let event = provider.parse_webhook_event(payload).unwrap();
+        let dispute = provider.dispute_from_webhook(&event).expect("dispute");
+        assert_eq!(dispute.dispute_id, "310");
+        assert_eq!(dispute.state, DisputeState::Open);
+        assert_eq!(dispute.transaction_reference.as_deref(), Some("tx_3"));
+        assert_eq!(dispute.amount.unwrap().amount, "5000");
+
+        let lost = flutterwave_dispute(&serde_json::json!({"id": 310, "status": "lost"})).unwrap();
+        assert_eq!(lost.state, DisputeState::Lost);
+    }
+
+    #[test]
+    fn bulk_transfer_items_wait_on_provider() {
+        let items = flutterwave_queued_items(vec!["tx_1".to_string(), "tx_2".to_string()]);
+        assert_eq!(items.len(), 2);
+        assert!(items.iter().all(|i| i.status == PaymentState::Processing));
+        assert_eq!(items[1].provider_reference.as_deref(), Some("tx_2"));
+    }
+
+    #[test]
+    fn transfer_status_is_read() {
+        let data = serde_json::json!({
+            "id": 190626,
+            "reference": "tx_1",
+            "status": "FAILED",
+            "amount": 5000,
+            "currency": "NGN",
+            "complete_message": "Account resolve failed",
+            "created_at": "2026-03-14T10:00:00.000Z"
+        });
+        let status = flutterwave_transfer_status(&data);
+        assert_eq!(status.status, PaymentState::Failed);
+        assert_eq!(status.transaction_reference.as_deref(), Some("tx_1"));
+        assert_eq!(status.provider_reference.as_deref(), Some("190626"));
+        assert_eq!(status.failure_reason.as_deref(), Some("Account resolve failed"));
+    }
+
+    #[test]
+    fn balance_reads_available_balance() {
+        let data = serde_json::json!({
+            "currency": "NGN",
+            "available_balance": 2500000.5,
+            "ledger_balance": 2600000
+        });
+        let balance = FlutterwaveProvider::balance_from_data(Some(&data), "NGN")
+            .expect("balance should parse");
+        assert_eq!(balance.amount, "2500000.5");
+        assert_eq!(balance.currency, "NGN");
+        assert!(FlutterwaveProvider::balance_from_data(None, "NGN").is_err());
+    }
 }
@@ -1,4 +1,5 @@
 use crate::payments::error::{PaymentError, PaymentResult};
+use crate::payments::events::{derive_event_id, PaymentEventKind};
 use crate::payments::provider::PaymentProvider;
 use crate::payments::types::{
     PaymentRequest, PaymentResponse, PaymentState, ProviderName, StatusRequest, StatusResponse,
@@ -109,6 +110,8 @@ impl PaymentProvider for MpesaProvider {
         Ok(WebhookEvent {
             provider: ProviderName::Mpesa,
             event_type: "unknown".to_string(),
+            kind: PaymentEventKind::Unknown,
+            event_id: derive_event_id("unknown", None, None, payload),
             transaction_reference: None,
             provider_reference: None,
             status: Some(PaymentState::Unknown),
@@ -1,14 +1,20 @@
+use crate::payments::circuit_breaker::ProviderCircuitBreaker;
 use crate::payments::error::{PaymentError, PaymentResult};
+use crate::payments::events::{derive_event_id, json_id, lookup_kind, PaymentEventKind};
 use crate::payments::provider::PaymentProvider;
 use crate::payments::types::{
-    Money, PaymentMethod, PaymentRequest, PaymentResponse, PaymentState, ProviderName,
-    StatusRequest, StatusResponse, WebhookEvent, WebhookVerificationResult, WithdrawalMethod,
-    WithdrawalRequest, WithdrawalResponse,
+    BulkWithdrawalItem, BulkWithdrawalResponse, DisputeEvidence, DisputeState, Money, PaymentMethod, PaymentRequest, PaymentResponse,
+    PaymentState, ProviderDispute, ProviderName, RefundRequest, RefundResponse, StatusRequest,
+    StatusResponse, WebhookEvent, WebhookVerificationResult, WithdrawalMethod, WithdrawalRequest,
+    WithdrawalResponse,
 };
 use crate::payments::utils::{verify_hmac_sha512_hex, PaymentHttpClient};
 use async_trait::async_trait;
+use bigdecimal::{BigDecimal, RoundingMode, ToPrimitive};
 use serde::Deserialize;
 use serde_json::Value as JsonValue;
+use std::str::FromStr;
+use std::sync::Arc;
 use std::time::Duration;
 use tracing::info;
 
@@ -77,10 +83,130 @@ impl PaystackProvider {
         Self::new(PaystackConfig::from_env()?)
     }
 
+    /// Route this provider's API calls through its circuit
+    pub fn with_circuit_breaker(mut self, breaker: Arc<ProviderCircuitBreaker>) -> Self {
+        self.http = self.http.with_circuit_breaker(breaker, ProviderName::Paystack);
+        self
+    }
+
     fn endpoint(&self, path: &str) -> String {
         format!("{}{}", self.config.base_url, path)
     }
 
+    /// Balance in `currency`, converted from the subunits (kobo) Paystack reports
+    fn balance_in(balances: &[PaystackBalanceData], currency: &str) -> Option<Money> {
+        balances
+            .iter()
+            .find(|b| b.currency.eq_ignore_ascii_case(currency))
+            .map(|b| Money {
+                amount: (bigdecimal::BigDecimal::from(b.balance) / bigdecimal::BigDecimal::from(100))
+                    .to_string(),
+                currency: b.currency.clone(),
+            })
+    }
+
+    /// Amount in subunits (kobo), as the dispute, refund and bulk transfer
+    /// endpoints take it
+    fn to_subunits(money: &Money) -> PaymentResult<i64> {
+        BigDecimal::from_str(money.amount.trim())
+            .ok()
+            .and_then(|amount| {
+                (amount * BigDecimal::from(100))
+                    .with_scale_round(0, RoundingMode::HalfUp)
+                    .to_i64()
+            })
+            .ok_or_else(|| PaymentError::ValidationError {
+                message: format!("invalid amount: {}", money.amount),
+                field: Some("amount".to_string()),
+            })
+    }
+
+    async fn resolve_dispute(&self, dispute_id: &str, body: JsonValue) -> PaymentResult<()> {
+        let raw: PaystackEnvelope<JsonValue> = self
+            .http
+            .request_json(
+                reqwest::Method::PUT,
+                &self.endpoint(&format!("/dispute/{}/resolve", dispute_id)),
+                Some(&self.config.secret_key),
+                Some(&body),
+                &[("Content-Type", "application/json")],
+            )
+            .await?;
+        if !raw.status {
+            return Err(PaymentError::ProviderError {
+                provider: "paystack".to_string(),
+                message: raw.message,
+                provider_code: None,
+                retryable: false,
+            });
+        }
+        Ok(())
+    }
+
+    /// Register a withdrawal's bank account as a transfer recipient,
+    /// returning its recipient code
+    async fn create_recipient(&self, request: &WithdrawalRequest) -> PaymentResult<String> {
+        if !matches!(request.withdrawal_method, WithdrawalMethod::BankTransfer) {
+            return Err(PaymentError::ValidationError {
+                message: "paystack currently supports bank transfer withdrawals only".to_string(),
+                field: Some("withdrawal_method".to_string()),
+            });
+        }
+
+        let account_name = request
+            .recipient
+            .account_name
+            .clone()
+            .unwrap_or_else(|| "Recipient".to_string());
+        let account_number =
+            request
+                .recipient
+                .account_number
+                .clone()
+                .ok_or(PaymentError::ValidationError {
+                    message: "recipient.account_number is required".to_string(),
+                    field: Some("recipient.account_number".to_string()),
+                })?;
+        let bank_code =
+            request
+                .recipient
+                .bank_code
+                .clone()
+                .ok_or(PaymentError::ValidationError {
+                    message: "recipient.bank_code is required".to_string(),
+                    field: Some("recipient.bank_code".to_string()),
+                })?;
+
+        let recipient_payload = serde_json::json!({
+            "type": "nuban",
+            "name": account_name,
+            "account_number": account_number,
+            "bank_code": bank_code,
+            "currency": request.amount.currency,
+        });
+
+        let recipient: PaystackEnvelope<PaystackRecipientData> = self
+            .http
+            .request_json(
+                reqwest::Method::POST,
+                &self.endpoint("/transferrecipient"),
+                Some(&self.config.secret_key),
+                Some(&recipient_payload),
+                &[("Content-Type", "application/json")],
+            )
+            .await?;
+        if !recipient.status {
+            return Err(PaymentError::ProviderError {
+                provider: "paystack".to_string(),
+                message: recipient.message,
+                provider_code: None,
+                retryable: false,
+            });
+        }
+
+        Ok(recipient.data.recipient_code)
+    }
+
     fn ensure_status_ref(request: &StatusRequest) -> PaymentResult<String> {
         request
             .provider_reference
@@ -213,68 +339,12 @@ impl PaymentProvider for PaystackProvider {
         request: WithdrawalRequest,
     ) -> PaymentResult<WithdrawalResponse> {
         request.amount.validate_positive("amount")?;
-        if !matches!(request.withdrawal_method, WithdrawalMethod::BankTransfer) {
-            return Err(PaymentError::ValidationError {
-                message: "paystack currently supports bank transfer withdrawals only".to_string(),
-                field: Some("withdrawal_method".to_string()),
-            });
-        }
-
-        let account_name = request
-            .recipient
-            .account_name
-            .clone()
-            .unwrap_or_else(|| "Recipient".to_string());
-        let account_number =
-            request
-                .recipient
-                .account_number
-                .clone()
-                .ok_or(PaymentError::ValidationError {
-                    message: "recipient.account_number is required".to_string(),
-                    field: Some("recipient.account_number".to_string()),
-                })?;
-        let bank_code =
-            request
-                .recipient
-                .bank_code
-                .clone()
-                .ok_or(PaymentError::ValidationError {
-                    message: "recipient.bank_code is required".to_string(),
-                    field: Some("recipient.bank_code".to_string()),
-                })?;
-
-        let recipient_payload = serde_json::json!({
-            "type": "nuban",
-            "name": account_name,
-            "account_number": account_number,
-            "bank_code": bank_code,
-            "currency": request.amount.currency,
-        });
-
-        let recipient: PaystackEnvelope<PaystackRecipientData> = self
-            .http
-            .request_json(
-                reqwest::Method::POST,
-                &self.endpoint("/transferrecipient"),
-                Some(&self.config.secret_key),
-                Some(&recipient_payload),
-                &[("Content-Type", "application/json")],
-            )
-            .await?;
-        if !recipient.status {
-            return Err(PaymentError::ProviderError {
-                provider: "paystack".to_string(),
-                message: recipient.message,
-                provider_code: None,
-                retryable: false,
-            });
-        }
+        let recipient_code = self.create_recipient(&request).await?;
 
         let transfer_payload = serde_json::json!({
             "source": "balance",
             "amount": request.amount.amount,
-            "recipient": recipient.data.recipient_code,
+            "recipient": recipient_code,
             "reference": request.transaction_reference,
             "reason": request.reason,
             "metadata": request.metadata,
@@ -299,16 +369,8 @@ impl PaymentProvider for PaystackProvider {
             });
         }
 
-        let status = match transfer.data.status.as_str() {
-            "success" => PaymentState::Success,
-            "pending" => PaymentState::Processing,
-            "failed" => PaymentState::Failed,
-            "reversed" => PaymentState::Reversed,
-            _ => PaymentState::Unknown,
-        };
-
         Ok(WithdrawalResponse {
-            status,
+            status: paystack_transfer_state(&transfer.data.status),
             transaction_reference: request.transaction_reference,
             provider_reference: Some(transfer.data.reference),
             amount_debited: Some(request.amount),
@@ -325,6 +387,295 @@ impl PaymentProvider for PaystackProvider {
         self.verify_payment(request).await
     }
 
+    fn max_bulk_withdrawals(&self) -> Option<usize> {
+        Some(MAX_BULK_TRANSFERS)
+    }
+
+    async fn process_bulk_withdrawal(
+        &self,
+        requests: Vec<WithdrawalRequest>,
+    ) -> PaymentResult<BulkWithdrawalResponse> {
+        let Some(currency) = requests.first().map(|r| r.amount.currency.clone()) else {
+            return Ok(BulkWithdrawalResponse {
+                batch_reference: None,
+                items: Vec::new(),
+                provider_data: None,
+            });
+        };
+
+        // Bulk transfers only take recipient codes, so recipients are created
+        // first; a transfer whose recipient cannot be created fails on its own
+        let mut items = Vec::new();
+        let mut transfers = Vec::new();
+        for request in &requests {
+            let transfer = async {
+                request.amount.validate_positive("amount")?;
+                if !request.amount.currency.eq_ignore_ascii_case(&currency) {
+                    return Err(PaymentError::ValidationError {
+                        message: format!("bulk transfers must all be in {}", currency),
+                        field: Some("amount.currency".to_string()),
+                    });
+                }
+                let recipient_code = self.create_recipient(request).await?;
+                Ok(serde_json::json!({
+                    "amount": Self::to_subunits(&request.amount)?,
+                    "recipient": recipient_code,
+                    "reference": request.transaction_reference,
+                    "reason": request.reason,
+                }))
+            }
+            .await;
+            match transfer {
+                Ok(transfer) => transfers.push(transfer),
+                Err(e) => items.push(BulkWithdrawalItem {
+                    transaction_reference: request.transaction_reference.clone(),
+                    status: PaymentState::Failed,
+                    provider_reference: None,
+                    failure_reason: Some(e.to_string()),
+                    retryable: e.is_retryable(),
+                }),
+            }
+        }
+        if transfers.is_empty() {
+            return Ok(BulkWithdrawalResponse {
+                batch_reference: None,
+                items,
+                provider_data: None,
+            });
+        }
+
+        let payload = serde_json::json!({
+            "currency": currency,
+            "source": "balance",
+            "transfers": transfers,
+        });
+        let raw: PaystackEnvelope<Vec<PaystackTransferData>> = self
+            .http
+            .request_json(
+                reqwest::Method::POST,
+                &self.endpoint("/transfer/bulk"),
+                Some(&self.config.secret_key),
+                Some(&payload),
+                &[("Content-Type", "application/json")],
+            )
+            .await?;
+        if !raw.status {
+            return Err(PaymentError::ProviderError {
+                provider: "paystack".to_string(),
+                message: raw.message,
+                provider_code: None,
+                retryable: false,
+            });
+        }
+
+        info!(transfers = transfers.len(), "paystack bulk transfer queued");
+        items.extend(paystack_bulk_items(raw.data));
+        Ok(BulkWithdrawalResponse {
+            batch_reference: None,
+            items,
+            provider_data: Some(serde_json::json!({ "message": raw.message })),
+        })
+    }
+
+    async fn get_transfer_status(
+        &self,
+        transaction_reference: &str,
+    ) -> PaymentResult<Option<StatusResponse>> {
+        let result: PaymentResult<PaystackEnvelope<PaystackTransferVerifyData>> = self
+            .http
+            .request_json(
+                reqwest::Method::GET,
+                &self.endpoint(&format!("/transfer/verify/{}", transaction_reference)),
+                Some(&self.config.secret_key),
+                None,
+                &[],
+            )
+            .await;
+        let raw = match result {
+            Ok(raw) => raw,
+            Err(PaymentError::ProviderError { provider_code, .. })
+                if provider_code.as_deref() == Some("404") =>
+            {
+                return Ok(None)
+            }
+            Err(e) => return Err(e),
+        };
+        if !raw.status {
+            return Err(PaymentError::ProviderError {
+                provider: "paystack".to_string(),
+                message: raw.message,
+                provider_code: None,
+                retryable: false,
+            });
+        }
+        Ok(Some(paystack_transfer_status(raw.data)))
+    }
+
+    async fn get_balance(&self, currency: &str) -> PaymentResult<Money> {
+        let raw: PaystackEnvelope<Vec<PaystackBalanceData>> = self
+            .http
+            .request_json(
+                reqwest::Method::GET,
+                &self.endpoint("/balance"),
+                Some(&self.config.secret_key),
+                None,
+                &[],
+            )
+            .await?;
+        if !raw.status {
+            return Err(PaymentError::ProviderError {
+                provider: "paystack".to_string(),
+                message: raw.message,
+                provider_code: None,
+                retryable: true,
+            });
+        }
+
+        Self::balance_in(&raw.data, currency).ok_or_else(|| PaymentError::ProviderError {
+            provider: "paystack".to_string(),
+            message: format!("no {} balance on the integration", currency),
+            provider_code: None,
+            retryable: false,
+        })
+    }
+
+    async fn refund(&self, request: RefundRequest) -> PaymentResult<RefundResponse> {
+        let transaction = request
+            .provider_reference
+            .clone()
+            .unwrap_or_else(|| request.transaction_reference.clone());
+        let mut body = serde_json::json!({ "transaction": transaction });
+        if let Some(amount) = &request.amount {
+            amount.validate_positive("amount")?;
+            body["amount"] = serde_json::json!(Self::to_subunits(amount)?);
+            body["currency"] = serde_json::json!(amount.currency);
+        }
+        if let Some(reason) = &request.reason {
+            body["merchant_note"] = serde_json::json!(reason);
+        }
+
+        let raw: PaystackEnvelope<JsonValue> = self
+            .http
+            .request_json(
+                reqwest::Method::POST,
+                &self.endpoint("/refund"),
+                Some(&self.config.secret_key),
+                Some(&body),
+                &[("Content-Type", "application/json")],
+            )
+            .await?;
+        if !raw.status {
+            return Err(PaymentError::ProviderError {
+                provider: "paystack".to_string(),
+                message: raw.message,
+                provider_code: None,
+                retryable: false,
+            });
+        }
+        let refund = paystack_refund(&raw.data);
+        info!(reference = %transaction, refund = ?refund.refund_reference, "paystack refund initiated");
+        Ok(refund)
+    }
+
+    async fn list_disputes(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> PaymentResult<Vec<ProviderDispute>> {
+        let mut disputes = Vec::new();
+        for page in 1..=DISPUTE_PAGE_LIMIT {
+            let raw: PaystackEnvelope<Vec<JsonValue>> = self
+                .http
+                .request_json(
+                    reqwest::Method::GET,
+                    &self.endpoint(&format!(
+                        "/dispute?from={}&perPage={}&page={}",
+                        since.format("%Y-%m-%d"),
+                        DISPUTE_PAGE_SIZE,
+                        page
+                    )),
+                    Some(&self.config.secret_key),
+                    None,
+                    &[],
+                )
+                .await?;
+            if !raw.status {
+                return Err(PaymentError::ProviderError {
+                    provider: "paystack".to_string(),
+                    message: raw.message,
+                    provider_code: None,
+                    retryable: true,
+                });
+            }
+            let last_page = raw.data.len() < DISPUTE_PAGE_SIZE;
+            disputes.extend(raw.data.iter().filter_map(paystack_dispute));
+            if last_page {
+                break;
+            }
+        }
+        Ok(disputes)
+    }
+
+    async fn accept_dispute(
+        &self,
+        dispute_id: &str,
+        amount: &Money,
+        message: &str,
+    ) -> PaymentResult<()> {
+        let body = serde_json::json!({
+            "resolution": "merchant-accepted",
+            "message": message,
+            "refund_amount": Self::to_subunits(amount)?,
+        });
+        self.resolve_dispute(dispute_id, body).await?;
+        info!(dispute_id = %dispute_id, "paystack dispute accepted");
+        Ok(())
+    }
+
+    async fn contest_dispute(
+        &self,
+        dispute_id: &str,
+        _amount: &Money,
+        evidence: &DisputeEvidence,
+    ) -> PaymentResult<()> {
+        let payload = serde_json::json!({
+            "customer_email": evidence.customer_email,
+            "customer_name": evidence.customer_name,
+            "customer_phone": evidence.customer_phone,
+            "service_details": evidence.service_details,
+            "delivery_date": evidence.delivery_date,
+        });
+        let raw: PaystackEnvelope<JsonValue> = self
+            .http
+            .request_json(
+                reqwest::Method::POST,
+                &self.endpoint(&format!("/dispute/{}/evidence", dispute_id)),
+                Some(&self.config.secret_key),
+                Some(&payload),
+                &[("Content-Type", "application/json")],
+            )
+            .await?;
+        if !raw.status {
+            return Err(PaymentError::ProviderError {
+                provider: "paystack".to_string(),
+                message: raw.message,
+                provider_code: None,
+                retryable: false,
+            });
+        }
+
+        // Declining refunds nothing; the evidence and files back the decline
+        let body = serde_json::json!({
+            "resolution": "declined",
+            "message": evidence.message,
+            "refund_amount": 0,
+            "uploaded_filename": evidence.files.first(),
+            "evidence": json_id(raw.data.get("id")),
+        });
+        self.resolve_dispute(dispute_id, body).await?;
+        info!(dispute_id = %dispute_id, "paystack dispute contested");
+        Ok(())
+    }
+
     fn name(&self) -> ProviderName {
         ProviderName::Paystack
     }
@@ -370,24 +721,32 @@ impl PaymentProvider for PaystackProvider {
             .and_then(|v| v.as_str())
             .unwrap_or("unknown")
             .to_string();
-        let provider_ref = parsed
-            .get("data")
-            .and_then(|v| v.get("reference"))
+        let data = parsed.get("data").cloned().unwrap_or_else(|| serde_json::json!({}));
+        let data_status = data.get("status").and_then(|v| v.as_str());
+
+        // Charges carry our reference directly; refunds and disputes point
+        // at the charge they concern
+        let provider_ref = data
+            .get("reference")
+            .or_else(|| data.get("transaction_reference"))
+            .or_else(|| data.get("transaction").and_then(|t| t.get("reference")))
             .and_then(|v| v.as_str())
             .map(|v| v.to_string());
-        let status = parsed
-            .get("data")
-            .and_then(|v| v.get("status"))
-            .and_then(|v| v.as_str())
-            .map(|v| match v {
-                "success" => PaymentState::Success,
-                "pending" => PaymentState::Pending,
-                "failed" => PaymentState::Failed,
-                _ => PaymentState::Unknown,
-            });
+        let status = data_status.map(|v| match v {
+            "success" => PaymentState::Success,
+            "pending" => PaymentState::Pending,
+            "failed" => PaymentState::Failed,
+            _ => PaymentState::Unknown,
+        });
+        let object_id = json_id(data.get("id"))
+            .or_else(|| json_id(data.get("subscription_code")))
+            .or_else(|| json_id(data.get("invoice_code")))
+            .or_else(|| provider_ref.clone());
 
         Ok(WebhookEvent {
             provider: ProviderName::Paystack,
+            kind: paystack_event_kind(&event_type, &data),
+            event_id: derive_event_id(&event_type, object_id.as_deref(), data_status, payload),
             event_type,
             transaction_reference: None,
             provider_reference: provider_ref,
@@ -396,6 +755,198 @@ impl PaymentProvider for PaystackProvider {
             received_at: chrono::Utc::now().to_rfc3339(),
         })
     }
+
+    fn dispute_from_webhook(&self, event: &WebhookEvent) -> Option<ProviderDispute> {
+        if !event.kind.is_dispute() {
+            return None;
+        }
+        event.payload.get("data").and_then(paystack_dispute)
+    }
+}
+
+/// Disputes requested per page, and the most pages read in one poll
+const DISPUTE_PAGE_SIZE: usize = 100;
+const DISPUTE_PAGE_LIMIT: usize = 10;
+
+/// Paystack caps a bulk transfer at 100 transfers
+const MAX_BULK_TRANSFERS: usize = 100;
+
+/// Transfer status as reported by the transfer endpoints
+fn paystack_transfer_state(status: &str) -> PaymentState {
+    match status {
+        "success" => PaymentState::Success,
+        "pending" | "received" | "queued" | "otp" => PaymentState::Processing,
+        "failed" | "abandoned" | "rejected" => PaymentState::Failed,
+        "reversed" => PaymentState::Reversed,
+        _ => PaymentState::Unknown,
+    }
+}
+
+/// Per-transfer results of `/transfer/bulk`, keyed by our references
+fn paystack_bulk_items(transfers: Vec<PaystackTransferData>) -> Vec<BulkWithdrawalItem> {
+    transfers
+        .into_iter()
+        .map(|transfer| BulkWithdrawalItem {
+            status: paystack_transfer_state(&transfer.status),
+            provider_reference: Some(transfer.reference.clone()),
+            transaction_reference: transfer.reference,
+            failure_reason: transfer.failure_reason,
+            retryable: false,
+        })
+        .collect()
+}
+
+/// A transfer, as returned by `/transfer/verify`. Amounts are in subunits.
+fn paystack_transfer_status(data: PaystackTransferVerifyData) -> StatusResponse {
+    StatusResponse {
+        status: paystack_transfer_state(&data.status),
+        transaction_reference: Some(data.reference.clone()),
+        provider_reference: Some(data.reference),
+        amount: Some(subunit_money(data.amount, data.currency)),
+        payment_method: Some(PaymentMethod::BankTransfer),
+        timestamp: data.updated_at,
+        failure_reason: data.failure_reason,
+        provider_data: Some(serde_json::json!({ "transfer_code": data.transfer_code })),
+    }
+}
+
+/// A refund object, as returned by `/refund`. Amounts are in subunits.
+fn paystack_refund(data: &JsonValue) -> RefundResponse {
+    let status = match data
+        .get("status")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+    {
+        "processed" => PaymentState::Success,
+        "failed" => PaymentState::Failed,
+        "processing" | "needs-attention" => PaymentState::Processing,
+        _ => PaymentState::Pending,
+    };
+    let currency = data
+        .get("currency")
+        .and_then(|v| v.as_str())
+        .unwrap_or("NGN")
+        .to_string();
+    RefundResponse {
+        status,
+        refund_reference: json_id(data.get("id")),
+        amount_refunded: data
+            .get("amount")
+            .and_then(|v| v.as_i64())
+            .map(|kobo| subunit_money(kobo, currency)),
+        provider_data: Some(data.clone()),
+    }
+}
+
+fn subunit_money(kobo: i64, currency: String) -> Money {
+    Money {
+        amount: (BigDecimal::from(kobo) / BigDecimal::from(100))
+            .with_scale(2)
+            .to_string(),
+        currency,
+    }
+}
+
+/// A dispute object, as returned by `/dispute` and sent in `charge.dispute.*`
+/// webhooks. Amounts are in subunits.
+fn paystack_dispute(data: &JsonValue) -> Option<ProviderDispute> {
+    let dispute_id = json_id(data.get("id"))?;
+    let transaction = data.get("transaction");
+    let resolution = data
+        .get("resolution")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    let state = if resolution.contains("accept") {
+        DisputeState::Lost
+    } else if resolution.contains("decline") {
+        DisputeState::Won
+    } else {
+        match data.get("status").and_then(|v| v.as_str()) {
+            Some("awaiting-bank-feedback") => DisputeState::UnderReview,
+            _ => DisputeState::Open,
+        }
+    };
+    let currency = data
+        .get("currency")
+        .or_else(|| transaction.and_then(|t| t.get("currency")))
+        .and_then(|v| v.as_str())
+        .unwrap_or("NGN")
+        .to_string();
+    let amount = data
+        .get("refund_amount")
+        .filter(|v| !v.is_null())
+        .or_else(|| transaction.and_then(|t| t.get("amount")))
+        .and_then(|v| v.as_i64())
+        .map(|kobo| subunit_money(kobo, currency));
+
+    Some(ProviderDispute {
+        provider: ProviderName::Paystack,
+        dispute_id,
+        transaction_reference: transaction
+            .and_then(|t| t.get("reference"))
+            .or_else(|| data.get("transaction_reference"))
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string()),
+        state,
+        amount,
+        reason: data
+            .get("category")
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string()),
+        evidence_due_at: data
+            .get("due_at")
+            .and_then(|v| v.as_str())
+            .and_then(|v| chrono::DateTime::parse_from_rfc3339(v).ok())
+            .map(|v| v.with_timezone(&chrono::Utc)),
+        provider_data: Some(data.clone()),
+    })
+}
+
+const PAYSTACK_EVENT_KINDS: &[(&str, PaymentEventKind)] = &[
+    ("charge.success", PaymentEventKind::ChargeSucceeded),
+    ("charge.failed", PaymentEventKind::ChargeFailed),
+    ("transfer.success", PaymentEventKind::TransferSucceeded),
+    ("transfer.failed", PaymentEventKind::TransferFailed),
+    ("transfer.reversed", PaymentEventKind::TransferReversed),
+    ("refund.processed", PaymentEventKind::RefundProcessed),
+    ("refund.failed", PaymentEventKind::RefundFailed),
+    ("charge.dispute.create", PaymentEventKind::DisputeOpened),
+    ("charge.dispute.remind", PaymentEventKind::DisputeOpened),
+    ("subscription.create", PaymentEventKind::SubscriptionCreated),
+    ("subscription.disable", PaymentEventKind::SubscriptionCancelled),
+    ("subscription.not_renew", PaymentEventKind::SubscriptionCancelled),
+    ("invoice.payment_failed", PaymentEventKind::SubscriptionPaymentFailed),
+];
+
+fn paystack_event_kind(event_type: &str, data: &JsonValue) -> PaymentEventKind {
+    if event_type.eq_ignore_ascii_case("charge.dispute.resolve") {
+        // The resolution is the customer's claim: accepting it loses the funds
+        let resolution = data
+            .get("resolution")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        return if resolution.contains("accept") {
+            PaymentEventKind::DisputeLost
+        } else if resolution.contains("decline") {
+            PaymentEventKind::DisputeWon
+        } else {
+            PaymentEventKind::Unknown
+        };
+    }
+
+    match lookup_kind(PAYSTACK_EVENT_KINDS, event_type) {
+        Some(PaymentEventKind::ChargeSucceeded) => {
+            match data.get("status").and_then(|v| v.as_str()) {
+                Some("abandoned") => PaymentEventKind::ChargeAbandoned,
+                Some("failed") => PaymentEventKind::ChargeFailed,
+                _ => PaymentEventKind::ChargeSucceeded,
+            }
+        }
+        Some(kind) => kind,
+        None => PaymentEventKind::Unknown,
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -424,6 +975,12 @@ struct PaystackVerifyData {
     gateway_response: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct PaystackBalanceData {
+    currency: String,
+    balance: i64,
+}
+
 #[derive(Debug, Deserialize)]
 struct PaystackRecipientData {
     recipient_code: String,
@@ -431,13 +988,28 @@ struct PaystackRecipientData {
 
 #[derive(Debug, Deserialize)]
 struct PaystackTransferData {
-    transfer_code: String,
+    #[serde(default)]
+    transfer_code: Option<String>,
     reference: String,
     status: String,
     #[serde(default)]
     failure_reason: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct PaystackTransferVerifyData {
+    reference: String,
+    status: String,
+    amount: i64,
+    currency: String,
+    #[serde(default)]
+    transfer_code: Option<String>,
+    #[serde(default)]
+    failure_reason: Option<String>,
+    #[serde(default)]
+    updated_at: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -464,9 +1036,110 @@ mod tests {
         assert!(!result.valid);
     }
 
+    #[test]
+    fn balance_is_converted_from_kobo() {
+        let raw: PaystackEnvelope<Vec<PaystackBalanceData>> = serde_json::from_str(
+            r#"{"status":true,"message":"Balances retrieved","data":[{"currency":"NGN","balance":123456789}]}"#,
+        )
+        .expect("balance envelope should parse");
+        let balance = PaystackProvider::balance_in(&raw.data, "ngn").expect("NGN balance");
+        assert_eq!(balance.amount, "1234567.89");
+        assert_eq!(balance.currency, "NGN");
+        assert!(PaystackProvider::balance_in(&raw.data, "GHS").is_none());
+    }
+
     #[test]
     fn secure_eq_works() {
         assert!(crate::payments::utils::secure_eq(b"abc", b"abc"));
         assert!(!crate::payments::utils::secure_eq(b"abc", b"abd"));
     }
+
+    #[test]
+    fn dispute_webhooks_map_to_kinds_and_charge_reference() {
+        let provider = provider();
+        let payload = br#"{"event":"charge.dispute.resolve","data":{"id":12,"status":"resolved","resolution":"merchant-accepted","transaction":{"id":5,"reference":"tx_9"}}}"#;
+        let event = provider.parse_webhook_event(payload).unwrap();
+        assert_eq!(event.kind, PaymentEventKind::DisputeLost);
+        assert_eq!(event.provider_reference.as_deref(), Some("tx_9"));
+        assert_eq!(event.event_id, "charge.dispute.resolve:12:resolved");
+
+        let payload = br#"{"event":"refund.processed","data":{"id":3,"status":"processed","transaction_reference":"tx_9"}}"#;
+        let event = provider.parse_webhook_event(payload).unwrap();
+        assert_eq!(event.kind, PaymentEventKind::RefundProcessed);
+        assert_eq!(event.provider_reference.as_deref(), Some("tx_9"));
+    }
+
+    #[test]
+    fn partial_refund_response_is_read_in_naira() {
+        let data = serde_json::json!({
+            "id": 3018284,
+            "status": "pending",
+            "amount": 250000,
+            "currency": "NGN",
+            "transaction": { "id": 5, "reference": "tx_9" }
+        });
+        let refund = paystack_refund(&data);
+        assert_eq!(refund.status, PaymentState::Pending);
+        assert_eq!(refund.refund_reference.as_deref(), Some("3018284"));
+        assert_eq!(refund.amount_refunded.unwrap().amount, "2500.00");
+
+        let processed = paystack_refund(&serde_json::json!({ "id": 1, "status": "processed" }));
+        assert_eq!(processed.status, PaymentState::Success);
+        assert!(processed.amount_refunded.is_none());
+    }
+
+    #[test]
+    fn dispute_is_read_from_webhook_payload() {
+        let provider = provider();
+        let payload = br#"{"event":"charge.dispute.create","data":{"id":12,"status":"awaiting-merchant-feedback","resolution":null,"category":"chargeback","currency":"NGN","refund_amount":null,"due_at":"2026-03-20T10:00:00.000Z","transaction":{"id":5,"reference":"tx_9","amount":1250050}}}"#;
+        let event = provider.parse_webhook_event(payload).unwrap();
+        let dispute = provider.dispute_from_webhook(&event).expect("dispute");
+        assert_eq!(dispute.dispute_id, "12");
+        assert_eq!(dispute.state, DisputeState::Open);
+        assert_eq!(dispute.transaction_reference.as_deref(), Some("tx_9"));
+        assert_eq!(dispute.amount.unwrap().amount, "12500.50");
+        assert!(dispute.evidence_due_at.is_some());
+
+        let payload = br#"{"event":"charge.success","data":{"id":5,"status":"success","reference":"tx_9"}}"#;
+        let event = provider.parse_webhook_event(payload).unwrap();
+        assert!(provider.dispute_from_webhook(&event).is_none());
+    }
+
+    #[test]
+    fn bulk_transfer_items_are_keyed_by_reference() {
+        let raw: PaystackEnvelope<Vec<PaystackTransferData>> = serde_json::from_str(
+            r#"{"status":true,"message":"2 transfers queued.","data":[
+                {"reference":"tx_1","recipient":"RCP_1","amount":500000,"transfer_code":"TRF_1","currency":"NGN","status":"received"},
+                {"reference":"tx_2","recipient":"RCP_2","amount":20000,"transfer_code":"TRF_2","currency":"NGN","status":"failed"}
+            ]}"#,
+        )
+        .expect("bulk transfer envelope should parse");
+        let items = paystack_bulk_items(raw.data);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].transaction_reference, "tx_1");
+        assert_eq!(items[0].status, PaymentState::Processing);
+        assert_eq!(items[1].status, PaymentState::Failed);
+        assert!(!items[1].retryable);
+    }
+
+    #[test]
+    fn transfer_status_is_read_in_naira() {
+        let raw: PaystackEnvelope<PaystackTransferVerifyData> = serde_json::from_str(
+            r#"{"status":true,"message":"Transfer retrieved","data":{"reference":"tx_1","status":"reversed","amount":500050,"currency":"NGN","transfer_code":"TRF_1","updated_at":"2026-03-14T10:00:00.000Z"}}"#,
+        )
+        .expect("transfer envelope should parse");
+        let status = paystack_transfer_status(raw.data);
+        assert_eq!(status.status, PaymentState::Reversed);
+        assert_eq!(status.transaction_reference.as_deref(), Some("tx_1"));
+        assert_eq!(status.amount.unwrap().amount, "5000.50");
+    }
+
+    #[test]
+    fn dispute_amount_is_sent_in_kobo() {
+        let money = Money {
+            amount: "12500.505".to_string(),
+            currency: "NGN".to_string(),
+        };
+        assert_eq!(PaystackProvider::to_subunits(&money).unwrap(), 1250051);
+    }
 }
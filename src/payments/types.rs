@@ -1,4 +1,5 @@
 use crate::payments::error::PaymentError;
+use crate::payments::events::PaymentEventKind;
 use bigdecimal::BigDecimal;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
@@ -20,6 +21,22 @@ impl ProviderName {
             ProviderName::Mpesa => "mpesa",
         }
     }
+
+    /// Fiat currencies the provider can collect and pay out
+    pub fn supports_currency(&self, currency: &str) -> bool {
+        let supported: &[&str] = match self {
+            ProviderName::Flutterwave => &["NGN", "GHS", "KES", "ZAR", "USD"],
+            ProviderName::Paystack => &["NGN", "GHS", "ZAR", "USD"],
+            ProviderName::Mpesa => &["KES", "TZS", "UGX"],
+        };
+        supported.iter().any(|c| c.eq_ignore_ascii_case(currency))
+    }
+
+    /// Card collectors, whose payments can be charged back and who expose a
+    /// dispute API
+    pub fn supports_disputes(&self) -> bool {
+        matches!(self, ProviderName::Paystack | ProviderName::Flutterwave)
+    }
 }
 
 impl std::fmt::Display for ProviderName {
@@ -84,6 +101,19 @@ pub enum PaymentMethod {
     Other,
 }
 
+impl PaymentMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaymentMethod::Card => "card",
+            PaymentMethod::BankTransfer => "bank_transfer",
+            PaymentMethod::MobileMoney => "mobile_money",
+            PaymentMethod::Ussd => "ussd",
+            PaymentMethod::Wallet => "wallet",
+            PaymentMethod::Other => "other",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum WithdrawalMethod {
@@ -91,6 +121,15 @@ pub enum WithdrawalMethod {
     MobileMoney,
 }
 
+impl WithdrawalMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WithdrawalMethod::BankTransfer => "bank_transfer",
+            WithdrawalMethod::MobileMoney => "mobile_money",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum PaymentState {
@@ -137,6 +176,27 @@ pub struct WithdrawalRequest {
     pub metadata: Option<JsonValue>,
 }
 
+/// How one transfer in a bulk withdrawal fared
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkWithdrawalItem {
+    pub transaction_reference: String,
+    pub status: PaymentState,
+    pub provider_reference: Option<String>,
+    /// Why the provider rejected the transfer
+    pub failure_reason: Option<String>,
+    /// Whether a rejected transfer may go through if submitted again
+    pub retryable: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkWithdrawalResponse {
+    /// Provider's ID for the batch
+    pub batch_reference: Option<String>,
+    /// Transfers the provider reported on; ones it did not are left out
+    pub items: Vec<BulkWithdrawalItem>,
+    pub provider_data: Option<JsonValue>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StatusRequest {
     pub transaction_reference: Option<String>,
@@ -186,7 +246,11 @@ pub struct WebhookVerificationResult {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebhookEvent {
     pub provider: ProviderName,
+    /// Provider's own event name, e.g. `charge.success`
     pub event_type: String,
+    pub kind: PaymentEventKind,
+    /// Stable across redeliveries of the same event
+    pub event_id: String,
     pub transaction_reference: Option<String>,
     pub provider_reference: Option<String>,
     pub status: Option<PaymentState>,
@@ -194,6 +258,66 @@ pub struct WebhookEvent {
     pub received_at: String,
 }
 
+/// Where a dispute stands at the provider
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DisputeState {
+    /// Awaiting our response
+    Open,
+    /// We contested; the issuing bank is deciding
+    UnderReview,
+    Won,
+    Lost,
+}
+
+/// A dispute (chargeback) as the provider reports it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderDispute {
+    pub provider: ProviderName,
+    /// Provider's ID for the dispute
+    pub dispute_id: String,
+    /// Reference of the disputed charge
+    pub transaction_reference: Option<String>,
+    pub state: DisputeState,
+    pub amount: Option<Money>,
+    pub reason: Option<String>,
+    /// Deadline for accepting or contesting
+    pub evidence_due_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub provider_data: Option<JsonValue>,
+}
+
+/// What we send the provider when contesting a dispute
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisputeEvidence {
+    pub message: String,
+    pub customer_email: Option<String>,
+    pub customer_name: Option<String>,
+    pub customer_phone: Option<String>,
+    /// What the customer paid for
+    pub service_details: String,
+    pub delivery_date: Option<String>,
+    /// Storage references of the uploaded evidence files
+    pub files: Vec<String>,
+}
+
+/// Return (part of) a collected payment through the provider that took it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefundRequest {
+    pub transaction_reference: String,
+    pub provider_reference: Option<String>,
+    /// `None` refunds the whole payment
+    pub amount: Option<Money>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefundResponse {
+    pub status: PaymentState,
+    pub refund_reference: Option<String>,
+    pub amount_refunded: Option<Money>,
+    pub provider_data: Option<JsonValue>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
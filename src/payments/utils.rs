@@ -1,7 +1,10 @@
+use crate::payments::circuit_breaker::{CallOutcome, ProviderCircuitBreaker};
 use crate::payments::error::{PaymentError, PaymentResult};
+use crate::payments::types::ProviderName;
 use reqwest::Client;
 use serde::de::DeserializeOwned;
 use serde_json::Value as JsonValue;
+use std::sync::Arc;
 use std::time::Duration;
 use tracing::warn;
 
@@ -10,6 +13,7 @@ pub struct PaymentHttpClient {
     client: Client,
     timeout: Duration,
     max_retries: u32,
+    circuit: Option<(Arc<ProviderCircuitBreaker>, ProviderName)>,
 }
 
 impl PaymentHttpClient {
@@ -26,9 +30,20 @@ impl PaymentHttpClient {
             client,
             timeout,
             max_retries,
+            circuit: None,
         })
     }
 
+    /// Gate requests on `provider`'s circuit and report their outcomes to it
+    pub fn with_circuit_breaker(
+        mut self,
+        breaker: Arc<ProviderCircuitBreaker>,
+        provider: ProviderName,
+    ) -> Self {
+        self.circuit = Some((breaker, provider));
+        self
+    }
+
     pub async fn request_json<T: DeserializeOwned>(
         &self,
         method: reqwest::Method,
@@ -37,7 +52,40 @@ impl PaymentHttpClient {
         body: Option<&JsonValue>,
         additional_headers: &[(&str, &str)],
     ) -> PaymentResult<T> {
+        let Some((breaker, provider)) = &self.circuit else {
+            return self
+                .send_with_retries(method, url, bearer_token, body, additional_headers)
+                .await
+                .0;
+        };
+
+        if !breaker.allow(provider).await {
+            return Err(PaymentError::ProviderError {
+                provider: provider.to_string(),
+                message: "circuit open: provider temporarily unavailable".to_string(),
+                provider_code: None,
+                retryable: true,
+            });
+        }
+        let (result, outcome) = self
+            .send_with_retries(method, url, bearer_token, body, additional_headers)
+            .await;
+        breaker.record(provider, outcome).await;
+        result
+    }
+
+    /// The request with retries, plus how the provider behaved for the
+    /// circuit breaker. Client errors other than 429 mean the provider is up.
+    async fn send_with_retries<T: DeserializeOwned>(
+        &self,
+        method: reqwest::Method,
+        url: &str,
+        bearer_token: Option<&str>,
+        body: Option<&JsonValue>,
+        additional_headers: &[(&str, &str)],
+    ) -> (PaymentResult<T>, CallOutcome) {
         let mut last_error = None;
+        let mut timed_out = false;
         for attempt in 0..=self.max_retries {
             let mut request = self.client.request(method.clone(), url);
             request = request.timeout(self.timeout);
@@ -52,19 +100,19 @@ impl PaymentHttpClient {
                 request = request.json(payload);
             }
 
-            let response = request
-                .send()
-                .await
-                .map_err(|e| PaymentError::NetworkError {
+            let response = request.send().await.map_err(|e| {
+                timed_out = e.is_timeout();
+                PaymentError::NetworkError {
                     message: format!("provider request failed: {}", e),
-                });
+                }
+            });
 
             match response {
                 Ok(resp) => {
                     let status = resp.status();
                     let text = resp.text().await.unwrap_or_default();
                     if status.is_success() {
-                        return serde_json::from_str::<T>(&text).map_err(|e| {
+                        let parsed = serde_json::from_str::<T>(&text).map_err(|e| {
                             PaymentError::ProviderError {
                                 provider: "http".to_string(),
                                 message: format!("invalid provider JSON response: {}", e),
@@ -72,6 +120,7 @@ impl PaymentHttpClient {
                                 retryable: false,
                             }
                         });
+                        return (parsed, CallOutcome::Success);
                     }
 
                     if status.as_u16() == 429 {
@@ -79,10 +128,13 @@ impl PaymentHttpClient {
                             tokio::time::sleep(Duration::from_secs(1 << attempt)).await;
                             continue;
                         }
-                        return Err(PaymentError::RateLimitError {
-                            message: "provider rate limit exceeded".to_string(),
-                            retry_after_seconds: None,
-                        });
+                        return (
+                            Err(PaymentError::RateLimitError {
+                                message: "provider rate limit exceeded".to_string(),
+                                retry_after_seconds: None,
+                            }),
+                            CallOutcome::Failure,
+                        );
                     }
 
                     if status.is_server_error() && attempt < self.max_retries {
@@ -95,12 +147,20 @@ impl PaymentHttpClient {
                         continue;
                     }
 
-                    return Err(PaymentError::ProviderError {
-                        provider: "http".to_string(),
-                        message: format!("HTTP {}: {}", status, text),
-                        provider_code: Some(status.as_u16().to_string()),
-                        retryable: status.is_server_error(),
-                    });
+                    let outcome = if status.is_server_error() {
+                        CallOutcome::Failure
+                    } else {
+                        CallOutcome::Success
+                    };
+                    return (
+                        Err(PaymentError::ProviderError {
+                            provider: "http".to_string(),
+                            message: format!("HTTP {}: {}", status, text),
+                            provider_code: Some(status.as_u16().to_string()),
+                            retryable: status.is_server_error(),
+                        }),
+                        outcome,
+                    );
                 }
                 Err(e) => {
                     last_error = Some(e);
@@ -112,9 +172,17 @@ impl PaymentHttpClient {
             }
         }
 
-        Err(last_error.unwrap_or(PaymentError::NetworkError {
-            message: "provider request failed".to_string(),
-        }))
+        let outcome = if timed_out {
+            CallOutcome::Timeout
+        } else {
+            CallOutcome::Failure
+        };
+        (
+            Err(last_error.unwrap_or(PaymentError::NetworkError {
+                message: "provider request failed".to_string(),
+            })),
+            outcome,
+        )
     }
 }
 
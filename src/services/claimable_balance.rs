@@ -0,0 +1,350 @@
+//! Claimable balance payouts
+//! Tracks cNGN payouts that were parked in claimable balances because the
+//! recipient had no trustline, builds claim transactions for recipients and
+//! reclaims balances nobody claimed before the deadline.
+
+use crate::chains::stellar::client::StellarClient;
+use crate::chains::stellar::errors::StellarError;
+use crate::chains::stellar::payment::{
+    CngnPaymentBuilder, CngnPaymentDraft, UnsignedClaimTransaction,
+};
+use crate::chains::stellar::signer::{
+    ensure_signer_matches_source, sign_envelope, TransactionSigner,
+};
+use crate::database::claimable_balance_repository::{
+    ClaimableBalance, ClaimableBalanceRepository, NewClaimableBalance,
+};
+use crate::database::transaction_repository::TransactionRepository;
+use crate::error::{AppError, AppErrorKind, DomainError, ValidationError};
+use serde::Serialize;
+use sqlx::types::BigDecimal;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+const DEFAULT_RECLAIM_DAYS: u64 = 30;
+/// Wait before retrying a failed reclaim, doubled per failure
+const RECLAIM_RETRY_AFTER: Duration = Duration::from_secs(5 * 60);
+/// Longest wait between reclaim retries
+const RECLAIM_RETRY_MAX: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Claimable balance fallback configuration
+#[derive(Debug, Clone)]
+pub struct ClaimableBalanceConfig {
+    /// Fall back to a claimable balance when the recipient has no trustline
+    pub enabled: bool,
+    /// How long the recipient has to claim before we may reclaim
+    pub reclaim_after: Duration,
+}
+
+impl ClaimableBalanceConfig {
+    pub fn from_env() -> Self {
+        let enabled = std::env::var("CLAIMABLE_BALANCE_FALLBACK_ENABLED")
+            .map(|v| v.to_lowercase() != "false")
+            .unwrap_or(true);
+        let reclaim_after = Duration::from_secs(
+            std::env::var("CLAIMABLE_BALANCE_RECLAIM_DAYS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(DEFAULT_RECLAIM_DAYS)
+                * 24
+                * 60
+                * 60,
+        );
+
+        Self {
+            enabled,
+            reclaim_after,
+        }
+    }
+}
+
+/// Outcome of one pass over pending claimable balances
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ClaimableBalanceSweep {
+    pub claimed: usize,
+    pub reclaimed: usize,
+    pub failed: usize,
+}
+
+/// Service for claimable balance payouts
+pub struct ClaimableBalanceService {
+    repo: ClaimableBalanceRepository,
+    transaction_repo: TransactionRepository,
+    stellar_client: StellarClient,
+    reclaim_signer: Option<Arc<dyn TransactionSigner>>,
+    config: ClaimableBalanceConfig,
+}
+
+impl ClaimableBalanceService {
+    pub fn new(
+        repo: ClaimableBalanceRepository,
+        transaction_repo: TransactionRepository,
+        stellar_client: StellarClient,
+        config: ClaimableBalanceConfig,
+    ) -> Self {
+        Self {
+            repo,
+            transaction_repo,
+            stellar_client,
+            reclaim_signer: None,
+            config,
+        }
+    }
+
+    /// Signer for the payout source account, used to reclaim expired balances
+    pub fn with_reclaim_signer(mut self, signer: Arc<dyn TransactionSigner>) -> Self {
+        self.reclaim_signer = Some(signer);
+        self
+    }
+
+    pub fn config(&self) -> &ClaimableBalanceConfig {
+        &self.config
+    }
+
+    /// Record the claimable balance a payout draft will create. Returns `None`
+    /// for drafts that are plain payments.
+    pub async fn record_payout(
+        &self,
+        transaction_id: Option<Uuid>,
+        draft: &CngnPaymentDraft,
+    ) -> Result<Option<ClaimableBalance>, AppError> {
+        let (Some(balance_id), Some(reclaim_after)) = (
+            draft.claimable_balance_id.as_ref(),
+            draft.reclaim_after.as_ref(),
+        ) else {
+            return Ok(None);
+        };
+
+        let reclaim_after = chrono::DateTime::parse_from_rfc3339(reclaim_after)
+            .map_err(|e| StellarError::serialization_error(e.to_string()))?
+            .with_timezone(&chrono::Utc);
+        let amount = BigDecimal::from_str(&draft.amount).map_err(|_| {
+            AppError::new(AppErrorKind::Validation(ValidationError::InvalidAmount {
+                amount: draft.amount.clone(),
+                reason: "amount must be a decimal number".to_string(),
+            }))
+        })?;
+
+        let created = self
+            .repo
+            .create(NewClaimableBalance {
+                transaction_id,
+                balance_id: balance_id.clone(),
+                source_account: draft.source.clone(),
+                claimant: draft.destination.clone(),
+                asset_code: draft.asset_code.clone(),
+                asset_issuer: draft.asset_issuer.clone(),
+                amount,
+                creation_tx_hash: draft.transaction_hash.clone(),
+                reclaim_after,
+            })
+            .await?;
+
+        info!(
+            balance_id = %created.balance_id,
+            claimant = %created.claimant,
+            amount = %created.amount,
+            "payout parked in claimable balance"
+        );
+        Ok(Some(created))
+    }
+
+    /// Update balances created by a transaction after its submission result
+    /// is known.
+    pub async fn mark_submitted(
+        &self,
+        transaction_id: Uuid,
+        error_message: Option<&str>,
+    ) -> Result<u64, AppError> {
+        let status = if error_message.is_some() {
+            "failed"
+        } else {
+            "pending"
+        };
+        Ok(self
+            .repo
+            .resolve_unsubmitted_for_transaction(transaction_id, status, error_message)
+            .await?)
+    }
+
+    /// Claimable balances for a wallet, optionally filtered by status
+    pub async fn list_for_wallet(
+        &self,
+        wallet: &str,
+        status: Option<&str>,
+    ) -> Result<Vec<ClaimableBalance>, AppError> {
+        Ok(self.repo.list_by_claimant(wallet, status).await?)
+    }
+
+    /// Build the transaction the recipient signs to claim a pending balance
+    pub async fn build_claim(
+        &self,
+        balance_id: &str,
+        wallet: &str,
+    ) -> Result<UnsignedClaimTransaction, AppError> {
+        let balance = self.load(balance_id).await?;
+        if balance.claimant != wallet {
+            return Err(AppError::new(AppErrorKind::Validation(
+                ValidationError::InvalidWalletAddress {
+                    address: wallet.to_string(),
+                    reason: "wallet is not the claimant of this balance".to_string(),
+                },
+            )));
+        }
+        if balance.status != "pending" {
+            return Err(StellarError::transaction_failed(format!(
+                "claimable balance is {}",
+                balance.status
+            ))
+            .into());
+        }
+        if balance.reclaim_after <= chrono::Utc::now() {
+            return Err(StellarError::transaction_failed(
+                "claimable balance can no longer be claimed by the recipient",
+            )
+            .into());
+        }
+
+        Ok(CngnPaymentBuilder::new(self.stellar_client.clone())
+            .build_claim(wallet, &balance.balance_id, None)
+            .await?)
+    }
+
+    /// Check pending balances against Horizon: mark the ones that are gone as
+    /// claimed and reclaim the ones past their deadline. Balances are taken
+    /// soonest deadline first, so a limited sweep never skips expired ones
+    /// for newer ones; a balance whose reclaim failed is retried with backoff
+    /// so it cannot hold the ones behind it up.
+    pub async fn sweep(&self, limit: i64) -> Result<ClaimableBalanceSweep, AppError> {
+        let mut sweep = ClaimableBalanceSweep::default();
+
+        for balance in self.repo.list_due_for_sweep(limit).await? {
+            let on_ledger = match self
+                .stellar_client
+                .get_claimable_balance(&balance.balance_id)
+                .await
+            {
+                Ok(v) => v.is_some(),
+                Err(e) => {
+                    warn!(balance_id = %balance.balance_id, error = %e, "claimable balance lookup failed");
+                    continue;
+                }
+            };
+
+            if !on_ledger {
+                self.repo
+                    .update_status(balance.id, "claimed", None, None)
+                    .await?;
+                self.note_on_transaction(&balance, "claimed", None).await;
+                sweep.claimed += 1;
+                continue;
+            }
+
+            if balance.reclaim_after > chrono::Utc::now() {
+                continue;
+            }
+
+            match self.reclaim(&balance).await {
+                Ok(hash) => {
+                    self.repo
+                        .update_status(balance.id, "reclaimed", Some(&hash), None)
+                        .await?;
+                    self.note_on_transaction(&balance, "reclaimed", Some(&hash))
+                        .await;
+                    info!(balance_id = %balance.balance_id, hash = %hash, "expired claimable balance reclaimed");
+                    sweep.reclaimed += 1;
+                }
+                Err(e) => {
+                    let failed = self
+                        .repo
+                        .record_reclaim_failure(
+                            balance.id,
+                            &e.to_string(),
+                            RECLAIM_RETRY_AFTER,
+                            RECLAIM_RETRY_MAX,
+                        )
+                        .await?;
+                    warn!(
+                        balance_id = %balance.balance_id,
+                        attempts = failed.reclaim_attempts,
+                        next_attempt = ?failed.next_reclaim_at,
+                        error = %e,
+                        "failed to reclaim claimable balance"
+                    );
+                    sweep.failed += 1;
+                }
+            }
+        }
+
+        Ok(sweep)
+    }
+
+    async fn reclaim(&self, balance: &ClaimableBalance) -> Result<String, AppError> {
+        let signer = self.reclaim_signer.as_ref().ok_or_else(|| {
+            StellarError::signing_error("no signer configured to reclaim claimable balances")
+        })?;
+        ensure_signer_matches_source(signer.as_ref(), &balance.source_account)?;
+
+        let draft = CngnPaymentBuilder::new(self.stellar_client.clone())
+            .build_claim(&balance.source_account, &balance.balance_id, None)
+            .await?;
+        let signed = sign_envelope(
+            signer.as_ref(),
+            &draft.unsigned_envelope_xdr,
+            self.stellar_client.network().network_passphrase(),
+        )
+        .await?;
+        let response = self
+            .stellar_client
+            .submit_transaction_xdr(&signed.signed_envelope_xdr)
+            .await?;
+
+        Ok(response
+            .get("hash")
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string())
+            .unwrap_or(signed.transaction_hash))
+    }
+
+    /// Record the balance outcome in the linked transaction's metadata,
+    /// leaving its status and other metadata alone.
+    async fn note_on_transaction(
+        &self,
+        balance: &ClaimableBalance,
+        outcome: &str,
+        hash: Option<&str>,
+    ) {
+        let Some(tx_id) = balance.transaction_id else {
+            return;
+        };
+        let tx_id = tx_id.to_string();
+        let result = self
+            .transaction_repo
+            .merge_metadata(
+                &tx_id,
+                serde_json::json!({
+                    "claimable_balance_status": outcome,
+                    "claimable_balance_resolved_at": chrono::Utc::now().to_rfc3339(),
+                    "claimable_balance_resolution_hash": hash,
+                }),
+            )
+            .await;
+        if let Err(e) = result {
+            warn!(transaction_id = %tx_id, error = %e, "failed to record claimable balance outcome");
+        }
+    }
+
+    async fn load(&self, balance_id: &str) -> Result<ClaimableBalance, AppError> {
+        self.repo
+            .find_by_balance_id(balance_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::new(AppErrorKind::Domain(DomainError::TransactionNotFound {
+                    transaction_id: balance_id.to_string(),
+                }))
+            })
+    }
+}
@@ -2,18 +2,15 @@
 //! Builds payment transaction drafts, calculates fees, supports memo, and signs payloads.
 
 use crate::chains::stellar::client::StellarClient;
+use crate::chains::stellar::signer::{ensure_signer_matches_source, sign_envelope, TransactionSigner};
 use crate::error::{AppError, AppErrorKind, ExternalError, ValidationError};
-use ed25519_dalek::{Signer, SigningKey};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use stellar_strkey::ed25519::{
-    MuxedAccount as StrkeyMuxedAccount, PrivateKey as StrkeyPrivateKey,
-    PublicKey as StrkeyPublicKey,
-};
+use stellar_strkey::ed25519::{MuxedAccount as StrkeyMuxedAccount, PublicKey as StrkeyPublicKey};
 use stellar_xdr::next::{
     AccountId, AlphaNum12, AlphaNum4, Asset, AssetCode12, AssetCode4, DecoratedSignature, Hash,
     Limits, Memo, MuxedAccount, MuxedAccountMed25519, Operation, OperationBody, PaymentOp,
-    Preconditions, PublicKey, SequenceNumber, Signature, SignatureHint, StringM, Transaction,
+    Preconditions, PublicKey, SequenceNumber, StringM, Transaction,
     TransactionEnvelope, TransactionExt, TransactionV1Envelope, Uint256, VecM, WriteXdr,
 };
 
@@ -114,24 +111,34 @@ impl CngnPaymentBuilder {
         })
     }
 
-    /// Sign a payment transaction draft with a Stellar secret seed
-    pub fn sign_transaction(
+    /// Sign a payment transaction draft with the source account's signer
+    pub async fn sign_transaction(
         &self,
         draft: PaymentTransactionDraft,
-        secret_seed: &str,
+        signer: &dyn TransactionSigner,
     ) -> Result<SignedPaymentTransaction, AppError> {
-        let signing_key = decode_signing_key(secret_seed)?;
-        let (envelope_xdr, tx_hash, signature) = build_signed_envelope_xdr(
-            &draft,
-            &signing_key,
-            self.stellar_client.network().network_passphrase(),
+        ensure_signer_matches_source(signer, &draft.operation.source)?;
+
+        // Rebuild from the draft fields so the signature covers exactly what
+        // the draft describes, not whatever XDR the caller passed back.
+        let (_, envelope) = build_transaction(
+            &draft.operation,
+            &draft.memo,
+            draft.fee_stroops,
+            draft.sequence,
         )?;
+        let signed = sign_envelope(
+            signer,
+            &envelope_to_xdr(&envelope)?,
+            self.stellar_client.network().network_passphrase(),
+        )
+        .await?;
 
         Ok(SignedPaymentTransaction {
             draft,
-            hash: tx_hash,
-            signature: hex::encode(signature),
-            envelope_xdr,
+            hash: signed.transaction_hash,
+            signature: signed.signature,
+            envelope_xdr: signed.signed_envelope_xdr,
         })
     }
 
@@ -188,18 +195,6 @@ fn validate_payment_operation(operation: &PaymentOperation) -> Result<(), AppErr
     Ok(())
 }
 
-fn decode_signing_key(secret_seed: &str) -> Result<SigningKey, AppError> {
-    let private_key = StrkeyPrivateKey::from_string(secret_seed).map_err(|_| {
-        AppError::new(AppErrorKind::Validation(
-            ValidationError::InvalidWalletAddress {
-                address: "secret_seed".to_string(),
-                reason: "invalid Stellar secret seed".to_string(),
-            },
-        ))
-    })?;
-    Ok(SigningKey::from_bytes(&private_key.0))
-}
-
 fn build_unsigned_envelope_xdr(
     operation: &PaymentOperation,
     memo: &PaymentMemo,
@@ -213,55 +208,6 @@ fn build_unsigned_envelope_xdr(
     Ok((xdr, tx_hash))
 }
 
-fn build_signed_envelope_xdr(
-    draft: &PaymentTransactionDraft,
-    signing_key: &SigningKey,
-    network_passphrase: &str,
-) -> Result<(String, String, Vec<u8>), AppError> {
-    let (tx, _) = build_transaction(
-        &draft.operation,
-        &draft.memo,
-        draft.fee_stroops,
-        draft.sequence,
-    )?;
-
-    let tx_hash_bytes = tx_hash_bytes(&tx, network_passphrase)?;
-    let signature = signing_key.try_sign(&tx_hash_bytes).map_err(|_| {
-        AppError::new(AppErrorKind::External(ExternalError::Blockchain {
-            message: "Failed to sign transaction hash".to_string(),
-            is_retryable: false,
-        }))
-    })?;
-
-    ensure_signing_key_matches_source(signing_key, &draft.operation.source)?;
-
-    let hint = signature_hint_from_signing_key(signing_key)?;
-    let decorated = DecoratedSignature {
-        hint,
-        signature: Signature::try_from(signature.to_vec()).map_err(|_| {
-            AppError::new(AppErrorKind::External(ExternalError::Blockchain {
-                message: "Failed to build decorated signature".to_string(),
-                is_retryable: false,
-            }))
-        })?,
-    };
-
-    let signatures = VecM::try_from(vec![decorated]).map_err(|_| {
-        AppError::new(AppErrorKind::External(ExternalError::Blockchain {
-            message: "Failed to build signature list".to_string(),
-            is_retryable: false,
-        }))
-    })?;
-
-    let envelope = TransactionEnvelope::Tx(TransactionV1Envelope { tx, signatures });
-    let envelope_xdr = envelope_to_xdr(&envelope)?;
-    Ok((
-        envelope_xdr,
-        hex::encode(tx_hash_bytes),
-        signature.to_bytes().to_vec(),
-    ))
-}
-
 fn build_transaction(
     operation: &PaymentOperation,
     memo: &PaymentMemo,
@@ -539,54 +485,3 @@ fn parse_muxed_account(address: &str) -> Result<MuxedAccount, AppError> {
     }
 }
 
-fn signature_hint_from_signing_key(signing_key: &SigningKey) -> Result<SignatureHint, AppError> {
-    let public_key_bytes = signing_key.verifying_key().to_bytes();
-    let hint = &public_key_bytes[public_key_bytes.len() - 4..];
-    SignatureHint::try_from(hint).map_err(|_| {
-        AppError::new(AppErrorKind::External(ExternalError::Blockchain {
-            message: "Failed to compute signature hint".to_string(),
-            is_retryable: false,
-        }))
-    })
-}
-
-fn ensure_signing_key_matches_source(
-    signing_key: &SigningKey,
-    source: &str,
-) -> Result<(), AppError> {
-    let public_key_bytes = signing_key.verifying_key().to_bytes();
-    let expected = if source.starts_with('M') {
-        StrkeyMuxedAccount::from_string(source)
-            .map(|muxed| muxed.ed25519)
-            .map_err(|_| {
-                AppError::new(AppErrorKind::Validation(
-                    ValidationError::InvalidWalletAddress {
-                        address: source.to_string(),
-                        reason: "invalid muxed source address".to_string(),
-                    },
-                ))
-            })?
-    } else {
-        StrkeyPublicKey::from_string(source)
-            .map(|pk| pk.0)
-            .map_err(|_| {
-                AppError::new(AppErrorKind::Validation(
-                    ValidationError::InvalidWalletAddress {
-                        address: source.to_string(),
-                        reason: "invalid source public key".to_string(),
-                    },
-                ))
-            })?
-    };
-
-    if public_key_bytes != expected {
-        return Err(AppError::new(AppErrorKind::Validation(
-            ValidationError::InvalidWalletAddress {
-                address: source.to_string(),
-                reason: "secret seed does not match source account".to_string(),
-            },
-        )));
-    }
-
-    Ok(())
-}
@@ -71,6 +71,16 @@ impl ConversionAuditService {
         self.repo.update_status(audit_id, "executed", None).await
     }
 
+    /// Claim a quoted conversion for execution; false if it was already
+    /// executed, failed or expired
+    pub async fn lock_quote(
+        &self,
+        audit_id: Uuid,
+        transaction_id: Option<Uuid>,
+    ) -> Result<bool, DatabaseError> {
+        self.repo.lock_quote(audit_id, transaction_id).await
+    }
+
     /// Mark a conversion as failed
     pub async fn mark_failed(
         &self,
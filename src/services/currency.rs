@@ -0,0 +1,162 @@
+//! Currency metadata for FX
+//!
+//! Minor-unit precision per currency, rounding of converted amounts, and the
+//! per-pair spread applied on top of mid-market cross rates.
+
+use bigdecimal::{BigDecimal, RoundingMode};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Scale used for intermediate rates (matches NUMERIC(36, 18) columns)
+pub const RATE_SCALE: i64 = 18;
+
+const DEFAULT_PRECISION: i64 = 2;
+const DEFAULT_SPREAD_BPS: u32 = 0;
+const MAX_SPREAD_BPS: u32 = 10_000;
+
+/// Decimal places a currency is settled in. Stellar assets use 7; fiat
+/// follows ISO 4217 minor units, except TZS/UGX which are paid out in whole
+/// units by mobile money rails.
+pub fn precision(currency: &str) -> i64 {
+    match currency.to_uppercase().as_str() {
+        "CNGN" | "USDC" | "XLM" => 7,
+        "TZS" | "UGX" => 0,
+        "NGN" | "KES" | "GHS" | "ZAR" | "USD" => 2,
+        _ => DEFAULT_PRECISION,
+    }
+}
+
+/// Round an amount to the currency's precision. Amounts we credit or pay out
+/// round down so that rounding never gives away value; everything else rounds
+/// half-even.
+pub fn round_amount(amount: &BigDecimal, currency: &str, round_down: bool) -> BigDecimal {
+    let mode = if round_down {
+        RoundingMode::Down
+    } else {
+        RoundingMode::HalfEven
+    };
+    amount.with_scale_round(precision(currency), mode)
+}
+
+/// Round a rate to the scale rates are stored at
+pub fn round_rate(rate: &BigDecimal) -> BigDecimal {
+    rate.with_scale_round(RATE_SCALE, RoundingMode::HalfEven)
+}
+
+/// Spread charged per currency pair, in basis points of the mid rate
+#[derive(Debug, Clone, Default)]
+pub struct SpreadConfig {
+    default_bps: u32,
+    pairs: HashMap<(String, String), u32>,
+}
+
+impl SpreadConfig {
+    pub fn new(default_bps: u32) -> Self {
+        Self {
+            default_bps: default_bps.min(MAX_SPREAD_BPS),
+            pairs: HashMap::new(),
+        }
+    }
+
+    /// Read `FX_DEFAULT_SPREAD_BPS` and `FX_PAIR_SPREADS_BPS`, the latter as
+    /// comma-separated `FROM/TO=bps` entries (e.g. `KES/cNGN=150,USD/cNGN=80`).
+    /// A spread set for `FROM/TO` also applies to `TO/FROM` unless that
+    /// direction has its own entry.
+    pub fn from_env() -> Self {
+        let default_bps = std::env::var("FX_DEFAULT_SPREAD_BPS")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(DEFAULT_SPREAD_BPS);
+        let pairs = std::env::var("FX_PAIR_SPREADS_BPS").unwrap_or_default();
+        Self::parse(default_bps, &pairs)
+    }
+
+    pub fn parse(default_bps: u32, pairs: &str) -> Self {
+        let mut config = Self::new(default_bps);
+        let mut reverse = Vec::new();
+        for entry in pairs.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let Some((pair, bps)) = entry.split_once('=') else {
+                continue;
+            };
+            let (Some((from, to)), Ok(bps)) = (pair.trim().split_once('/'), bps.trim().parse())
+            else {
+                continue;
+            };
+            config = config.with_pair(from.trim(), to.trim(), bps);
+            reverse.push((to.trim().to_string(), from.trim().to_string(), bps));
+        }
+        for (from, to, bps) in reverse {
+            let key = (from.to_uppercase(), to.to_uppercase());
+            config.pairs.entry(key).or_insert(bps.min(MAX_SPREAD_BPS));
+        }
+        config
+    }
+
+    pub fn with_pair(mut self, from: &str, to: &str, bps: u32) -> Self {
+        self.pairs.insert(
+            (from.to_uppercase(), to.to_uppercase()),
+            bps.min(MAX_SPREAD_BPS),
+        );
+        self
+    }
+
+    pub fn spread_bps(&self, from: &str, to: &str) -> u32 {
+        self.pairs
+            .get(&(from.to_uppercase(), to.to_uppercase()))
+            .copied()
+            .unwrap_or(self.default_bps)
+    }
+
+    /// Rate the customer gets for converting `from` into `to`: the mid rate
+    /// less the pair's spread
+    pub fn apply(&self, from: &str, to: &str, mid_rate: &BigDecimal) -> BigDecimal {
+        let bps = self.spread_bps(from, to);
+        if bps == 0 {
+            return mid_rate.clone();
+        }
+        let factor = BigDecimal::from(MAX_SPREAD_BPS - bps) / BigDecimal::from(MAX_SPREAD_BPS);
+        round_rate(&(mid_rate * factor))
+    }
+}
+
+/// Parse a decimal amount string, rejecting non-positive values
+pub fn parse_positive_amount(amount: &str) -> Option<BigDecimal> {
+    BigDecimal::from_str(amount.trim()).ok().filter(|v| *v > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_amount_uses_currency_precision() {
+        let amount = BigDecimal::from_str("1234.5678").unwrap();
+        assert_eq!(round_amount(&amount, "NGN", true).to_string(), "1234.56");
+        assert_eq!(round_amount(&amount, "NGN", false).to_string(), "1234.57");
+        assert_eq!(round_amount(&amount, "UGX", true).to_string(), "1234");
+        assert_eq!(
+            round_amount(&amount, "cNGN", true).to_string(),
+            "1234.5678000"
+        );
+    }
+
+    #[test]
+    fn test_spread_config_parses_pairs_and_reverse() {
+        let config = SpreadConfig::parse(50, "KES/cNGN=150, USD/NGN=80, bad, USD/KES=x");
+        assert_eq!(config.spread_bps("KES", "cNGN"), 150);
+        assert_eq!(config.spread_bps("cngn", "kes"), 150);
+        assert_eq!(config.spread_bps("USD", "NGN"), 80);
+        assert_eq!(config.spread_bps("USD", "KES"), 50);
+    }
+
+    #[test]
+    fn test_spread_reduces_rate() {
+        let config = SpreadConfig::new(0).with_pair("KES", "cNGN", 100);
+        let rate = config.apply("KES", "cNGN", &BigDecimal::from(10));
+        assert_eq!(rate, BigDecimal::from_str("9.9").unwrap());
+        assert_eq!(
+            config.apply("NGN", "cNGN", &BigDecimal::from(1)),
+            BigDecimal::from(1)
+        );
+    }
+}
@@ -0,0 +1,589 @@
+//! Dispute service
+//!
+//! Tracks chargebacks raised against card collections. Provider webhooks and
+//! a periodic poll of each provider's dispute API feed the same `disputes`
+//! rows, linked to the disputed transaction; ops upload evidence and accept
+//! or contest. A lost dispute charges the transaction back, posts a ledger
+//! adjustment for the lost funds and flags the wallet that received the
+//! cNGN, freezing it once it has lost enough disputes.
+
+use crate::database::dispute_repository::{
+    Dispute, DisputeEvidenceFile, DisputeRepository, DisputeUpsert, NewDisputeEvidence,
+};
+use crate::database::ledger_adjustment_repository::{
+    LedgerAdjustment, LedgerAdjustmentRepository, NewLedgerAdjustment,
+};
+use crate::database::repository::Repository;
+use crate::database::transaction_repository::{Transaction, TransactionRepository};
+use crate::database::wallet_risk_repository::{WalletRiskFlag, WalletRiskRepository};
+use crate::error::{AppError, AppErrorKind, DomainError, ValidationError};
+use crate::payments::error::PaymentResult;
+use crate::payments::factory::PaymentProviderFactory;
+use crate::payments::provider::PaymentProvider;
+use crate::payments::types::{DisputeEvidence, DisputeState, Money, ProviderDispute};
+use crate::services::payment_orchestrator::{OrchestrationState, PaymentOrchestrator};
+use bigdecimal::BigDecimal;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Kinds of evidence ops can upload
+const EVIDENCE_KINDS: &[&str] = &[
+    "receipt",
+    "delivery_proof",
+    "customer_communication",
+    "identity",
+    "other",
+];
+
+#[derive(Debug, Clone)]
+pub struct DisputeConfig {
+    /// How often providers' dispute APIs are polled
+    pub poll_interval: Duration,
+    /// How far back each poll asks for disputes
+    pub lookback: Duration,
+    /// Lost disputes after which a wallet is frozen rather than flagged
+    pub freeze_after_losses: i64,
+    /// Open disputes due within this window are reported on every poll
+    pub deadline_warning: Duration,
+}
+
+impl Default for DisputeConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(900),
+            lookback: Duration::from_secs(30 * 24 * 3600),
+            freeze_after_losses: 2,
+            deadline_warning: Duration::from_secs(48 * 3600),
+        }
+    }
+}
+
+impl DisputeConfig {
+    pub fn from_env() -> Self {
+        let mut cfg = Self::default();
+        let var = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<u64>().ok());
+        if let Some(secs) = var("DISPUTE_POLL_INTERVAL_SECONDS").filter(|v| *v > 0) {
+            cfg.poll_interval = Duration::from_secs(secs);
+        }
+        if let Some(days) = var("DISPUTE_POLL_LOOKBACK_DAYS").filter(|v| *v > 0) {
+            cfg.lookback = Duration::from_secs(days * 24 * 3600);
+        }
+        if let Some(losses) = var("DISPUTE_FREEZE_AFTER_LOSSES").filter(|v| *v > 0) {
+            cfg.freeze_after_losses = losses as i64;
+        }
+        if let Some(hours) = var("DISPUTE_DEADLINE_WARNING_HOURS") {
+            cfg.deadline_warning = Duration::from_secs(hours * 3600);
+        }
+        cfg
+    }
+}
+
+/// Status a dispute is stored under for the provider's view of it
+pub fn dispute_status(state: DisputeState) -> &'static str {
+    match state {
+        DisputeState::Open => "open",
+        DisputeState::UnderReview => "under_review",
+        DisputeState::Won => "won",
+        DisputeState::Lost => "lost",
+    }
+}
+
+/// Risk action for a wallet that has now lost `losses` disputes
+pub fn risk_action(losses: i64, freeze_after_losses: i64) -> &'static str {
+    if losses >= freeze_after_losses.max(1) {
+        "freeze"
+    } else {
+        "flag"
+    }
+}
+
+/// Disputes picked up by one poll of the providers
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct DisputeSyncSummary {
+    pub providers: usize,
+    pub recorded: usize,
+    pub failed: usize,
+}
+
+/// A dispute with its evidence and any adjustments posted for it
+#[derive(Debug, Clone, Serialize)]
+pub struct DisputeDetail {
+    #[serde(flatten)]
+    pub dispute: Dispute,
+    pub evidence: Vec<DisputeEvidenceFile>,
+    pub ledger_adjustments: Vec<LedgerAdjustment>,
+}
+
+/// Ops' case for contesting a dispute; the uploaded evidence files go with it
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContestRequest {
+    pub operator: String,
+    pub message: String,
+    pub customer_email: Option<String>,
+    pub customer_name: Option<String>,
+    pub customer_phone: Option<String>,
+    /// What the customer paid for
+    pub service_details: String,
+    pub delivery_date: Option<String>,
+}
+
+pub struct DisputeService {
+    disputes: DisputeRepository,
+    ledger: LedgerAdjustmentRepository,
+    risk: WalletRiskRepository,
+    transactions: TransactionRepository,
+    provider_factory: Arc<PaymentProviderFactory>,
+    orchestrator: Arc<PaymentOrchestrator>,
+    config: DisputeConfig,
+}
+
+impl DisputeService {
+    pub fn new(
+        pool: PgPool,
+        provider_factory: Arc<PaymentProviderFactory>,
+        orchestrator: Arc<PaymentOrchestrator>,
+        config: DisputeConfig,
+    ) -> Self {
+        Self {
+            disputes: DisputeRepository::new(pool.clone()),
+            ledger: LedgerAdjustmentRepository::new(pool.clone()),
+            risk: WalletRiskRepository::new(pool.clone()),
+            transactions: TransactionRepository::new(pool),
+            provider_factory,
+            orchestrator,
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &DisputeConfig {
+        &self.config
+    }
+
+    /// Record a dispute reported by a webhook or poll and bring its
+    /// transaction, the ledger and the wallet's risk flags in line with it.
+    /// Safe to repeat for the same report.
+    pub async fn record(&self, reported: ProviderDispute) -> Result<Dispute, AppError> {
+        let reference = reported
+            .transaction_reference
+            .as_deref()
+            .map(str::trim)
+            .filter(|r| !r.is_empty())
+            .ok_or_else(|| missing_field("transaction_reference"))?;
+        let transaction = self
+            .transactions
+            .find_by_payment_reference(reference)
+            .await?
+            .ok_or_else(|| not_found(reference))?;
+
+        let (amount, currency) = match &reported.amount {
+            Some(money) => (
+                BigDecimal::from_str(money.amount.trim())
+                    .unwrap_or_else(|_| transaction.from_amount.clone()),
+                money.currency.to_uppercase(),
+            ),
+            None => (
+                transaction.from_amount.clone(),
+                transaction.from_currency.clone(),
+            ),
+        };
+        let dispute = self
+            .disputes
+            .upsert(&DisputeUpsert {
+                transaction_id: transaction.transaction_id,
+                provider: reported.provider.as_str().to_string(),
+                provider_dispute_id: reported.dispute_id.clone(),
+                status: dispute_status(reported.state).to_string(),
+                reason: reported.reason.clone(),
+                amount,
+                currency,
+                evidence_due_at: reported.evidence_due_at,
+                provider_data: reported
+                    .provider_data
+                    .clone()
+                    .unwrap_or_else(|| serde_json::json!({})),
+            })
+            .await?;
+
+        self.settle(&dispute, &transaction).await?;
+        Ok(dispute)
+    }
+
+    /// Poll every card provider for recent disputes
+    pub async fn sync(&self) -> DisputeSyncSummary {
+        let since = Utc::now()
+            - chrono::Duration::from_std(self.config.lookback)
+                .unwrap_or_else(|_| chrono::Duration::days(30));
+        let mut summary = DisputeSyncSummary::default();
+
+        for name in self
+            .provider_factory
+            .list_available_providers()
+            .into_iter()
+            .filter(|p| p.supports_disputes())
+        {
+            summary.providers += 1;
+            let reported = match self.provider_factory.get_provider(name.clone()) {
+                Ok(provider) => provider.list_disputes(since).await,
+                Err(e) => Err(e),
+            };
+            let reported = match reported {
+                Ok(reported) => reported,
+                Err(e) => {
+                    warn!(provider = %name, error = %e, "failed to poll provider disputes");
+                    summary.failed += 1;
+                    continue;
+                }
+            };
+            for dispute in reported {
+                let dispute_id = dispute.dispute_id.clone();
+                match self.record(dispute).await {
+                    Ok(_) => summary.recorded += 1,
+                    Err(e) => {
+                        warn!(provider = %name, dispute_id = %dispute_id, error = %e, "failed to record polled dispute");
+                        summary.failed += 1;
+                    }
+                }
+            }
+        }
+
+        summary
+    }
+
+    /// Open disputes whose evidence deadline falls within the warning window
+    pub async fn upcoming_deadlines(&self) -> Result<Vec<Dispute>, AppError> {
+        let deadline = Utc::now()
+            + chrono::Duration::from_std(self.config.deadline_warning)
+                .unwrap_or_else(|_| chrono::Duration::zero());
+        Ok(self.disputes.find_open_due_before(deadline, 100).await?)
+    }
+
+    pub async fn list(&self, status: Option<&str>, limit: i64) -> Result<Vec<Dispute>, AppError> {
+        Ok(self.disputes.list(status, limit).await?)
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<DisputeDetail, AppError> {
+        let dispute = self
+            .disputes
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| not_found(&id.to_string()))?;
+        let evidence = self.disputes.list_evidence(id).await?;
+        let ledger_adjustments = self
+            .ledger
+            .find_by_transaction(dispute.transaction_id)
+            .await?
+            .into_iter()
+            .filter(|a| a.dispute_id == Some(id))
+            .collect();
+        Ok(DisputeDetail {
+            dispute,
+            evidence,
+            ledger_adjustments,
+        })
+    }
+
+    /// Attach an uploaded evidence file to an open dispute
+    pub async fn add_evidence(
+        &self,
+        id: Uuid,
+        evidence: NewDisputeEvidence,
+    ) -> Result<DisputeEvidenceFile, AppError> {
+        if !EVIDENCE_KINDS.contains(&evidence.kind.as_str()) {
+            return Err(AppError::new(AppErrorKind::Validation(
+                ValidationError::OutOfRange {
+                    field: "kind".to_string(),
+                    min: None,
+                    max: None,
+                },
+            )));
+        }
+        required("file_name", &evidence.file_name)?;
+        required("storage_url", &evidence.storage_url)?;
+        required("uploaded_by", &evidence.uploaded_by)?;
+
+        let dispute = self.open_dispute(id).await?;
+        let file = self.disputes.add_evidence(dispute.id, &evidence).await?;
+        info!(dispute_id = %id, kind = %file.kind, file = %file.file_name, "dispute evidence uploaded");
+        Ok(file)
+    }
+
+    /// Concede an open dispute: the provider refunds the customer and the
+    /// loss is posted straight away
+    pub async fn accept(
+        &self,
+        id: Uuid,
+        operator: &str,
+        note: Option<&str>,
+    ) -> Result<Dispute, AppError> {
+        required("operator", operator)?;
+        let dispute = self.open_dispute(id).await?;
+        self.provider(&dispute)?
+            .accept_dispute(
+                &dispute.provider_dispute_id,
+                &disputed_amount(&dispute),
+                note.unwrap_or("Dispute accepted"),
+            )
+            .await?;
+
+        let dispute = self
+            .disputes
+            .record_ops_action(id, "accepted", operator, note, "lost")
+            .await?
+            .ok_or_else(|| closed(&dispute))?;
+        warn!(dispute_id = %id, operator = %operator, "dispute accepted");
+
+        let transaction = self.transaction(&dispute).await?;
+        self.settle(&dispute, &transaction).await?;
+        Ok(dispute)
+    }
+
+    /// Contest an open dispute with the evidence uploaded for it
+    pub async fn contest(&self, id: Uuid, request: ContestRequest) -> Result<Dispute, AppError> {
+        required("operator", &request.operator)?;
+        required("message", &request.message)?;
+        required("service_details", &request.service_details)?;
+        let dispute = self.open_dispute(id).await?;
+        let evidence = self.disputes.list_evidence(id).await?;
+        if evidence.is_empty() {
+            return Err(missing_field("evidence"));
+        }
+
+        self.provider(&dispute)?
+            .contest_dispute(
+                &dispute.provider_dispute_id,
+                &disputed_amount(&dispute),
+                &DisputeEvidence {
+                    message: request.message.clone(),
+                    customer_email: request.customer_email,
+                    customer_name: request.customer_name,
+                    customer_phone: request.customer_phone,
+                    service_details: request.service_details,
+                    delivery_date: request.delivery_date,
+                    files: evidence.into_iter().map(|e| e.file_name).collect(),
+                },
+            )
+            .await?;
+        self.disputes.mark_evidence_submitted(id).await?;
+
+        let dispute = self
+            .disputes
+            .record_ops_action(
+                id,
+                "contested",
+                &request.operator,
+                Some(&request.message),
+                "under_review",
+            )
+            .await?
+            .ok_or_else(|| closed(&dispute))?;
+        info!(dispute_id = %id, operator = %request.operator, "dispute contested");
+        Ok(dispute)
+    }
+
+    pub async fn wallet_risk_flags(
+        &self,
+        wallet_address: &str,
+    ) -> Result<Vec<WalletRiskFlag>, AppError> {
+        Ok(self.risk.find_by_wallet(wallet_address).await?)
+    }
+
+    /// Lift a flag or freeze
+    pub async fn clear_risk_flag(
+        &self,
+        id: Uuid,
+        operator: &str,
+    ) -> Result<WalletRiskFlag, AppError> {
+        required("operator", operator)?;
+        let flag = self
+            .risk
+            .clear(id, operator)
+            .await?
+            .ok_or_else(|| not_found(&id.to_string()))?;
+        info!(flag_id = %id, wallet = %flag.wallet_address, operator = %operator, "wallet risk flag cleared");
+        Ok(flag)
+    }
+
+    /// Move the transaction to match the dispute and, once lost, post the
+    /// loss. Every step is a no-op when already done.
+    async fn settle(&self, dispute: &Dispute, transaction: &Transaction) -> Result<(), AppError> {
+        let reference = transaction
+            .payment_reference
+            .as_deref()
+            .ok_or_else(|| not_found(&transaction.transaction_id.to_string()))?;
+        let current = OrchestrationState::from_db_status(&transaction.status);
+
+        match dispute.status.as_str() {
+            "won" => {
+                if current == Some(OrchestrationState::Disputed) {
+                    self.orchestrator
+                        .handle_dispute_resolved(reference, true)
+                        .await?;
+                }
+            }
+            "lost" => {
+                if current != Some(OrchestrationState::ChargedBack) {
+                    self.orchestrator.handle_dispute_opened(reference).await?;
+                    self.orchestrator
+                        .handle_dispute_resolved(reference, false)
+                        .await?;
+                }
+                self.post_loss(dispute, transaction).await?;
+            }
+            _ => {
+                if current != Some(OrchestrationState::ChargedBack) {
+                    self.orchestrator.handle_dispute_opened(reference).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn post_loss(
+        &self,
+        dispute: &Dispute,
+        transaction: &Transaction,
+    ) -> Result<(), AppError> {
+        let posted = self
+            .ledger
+            .post(&NewLedgerAdjustment {
+                transaction_id: transaction.transaction_id,
+                dispute_id: Some(dispute.id),
+                wallet_address: transaction.wallet_address.clone(),
+                kind: "chargeback".to_string(),
+                amount: -dispute.amount.clone(),
+                currency: dispute.currency.clone(),
+                description: Some(format!(
+                    "{} dispute {} lost",
+                    dispute.provider, dispute.provider_dispute_id
+                )),
+                metadata: serde_json::json!({
+                    "provider": dispute.provider,
+                    "provider_dispute_id": dispute.provider_dispute_id,
+                    "cngn_delivered": transaction.cngn_amount.to_string(),
+                }),
+            })
+            .await?;
+        if let Some(adjustment) = posted {
+            warn!(
+                dispute_id = %dispute.id,
+                transaction_id = %transaction.transaction_id,
+                amount = %adjustment.amount,
+                currency = %adjustment.currency,
+                "chargeback loss posted"
+            );
+        }
+
+        if self.risk.exists_for_dispute(dispute.id).await? {
+            return Ok(());
+        }
+        let losses = self
+            .disputes
+            .count_lost_for_wallet(&transaction.wallet_address)
+            .await?;
+        let action = risk_action(losses, self.config.freeze_after_losses);
+        let flag = self
+            .risk
+            .raise(
+                &transaction.wallet_address,
+                action,
+                &format!("{} lost dispute(s); latest {}", losses, dispute.id),
+                Some(dispute.id),
+            )
+            .await?;
+        warn!(
+            wallet = %flag.wallet_address,
+            action = %flag.action,
+            losses,
+            "wallet flagged after lost dispute"
+        );
+        Ok(())
+    }
+
+    async fn open_dispute(&self, id: Uuid) -> Result<Dispute, AppError> {
+        let dispute = self
+            .disputes
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| not_found(&id.to_string()))?;
+        if dispute.status != "open" {
+            return Err(closed(&dispute));
+        }
+        Ok(dispute)
+    }
+
+    async fn transaction(&self, dispute: &Dispute) -> Result<Transaction, AppError> {
+        let id = dispute.transaction_id.to_string();
+        self.transactions
+            .find_by_id(&id)
+            .await?
+            .ok_or_else(|| not_found(&id))
+    }
+
+    fn provider(&self, dispute: &Dispute) -> PaymentResult<Box<dyn PaymentProvider>> {
+        self.provider_factory
+            .get_provider(dispute.provider.parse()?)
+    }
+}
+
+fn disputed_amount(dispute: &Dispute) -> Money {
+    Money {
+        amount: dispute.amount.to_string(),
+        currency: dispute.currency.clone(),
+    }
+}
+
+fn required(field: &str, value: &str) -> Result<(), ValidationError> {
+    if value.trim().is_empty() {
+        return Err(ValidationError::MissingField {
+            field: field.to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn missing_field(field: &str) -> AppError {
+    AppError::new(AppErrorKind::Validation(ValidationError::MissingField {
+        field: field.to_string(),
+    }))
+}
+
+fn not_found(id: &str) -> AppError {
+    AppError::new(AppErrorKind::Domain(DomainError::TransactionNotFound {
+        transaction_id: id.to_string(),
+    }))
+}
+
+fn closed(dispute: &Dispute) -> AppError {
+    AppError::new(AppErrorKind::Domain(DomainError::DisputeClosed {
+        dispute_id: dispute.id.to_string(),
+        status: dispute.status.clone(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn provider_states_map_to_stored_statuses() {
+        assert_eq!(dispute_status(DisputeState::Open), "open");
+        assert_eq!(dispute_status(DisputeState::UnderReview), "under_review");
+        assert_eq!(dispute_status(DisputeState::Won), "won");
+        assert_eq!(dispute_status(DisputeState::Lost), "lost");
+    }
+
+    #[test]
+    fn wallet_is_frozen_once_losses_reach_threshold() {
+        assert_eq!(risk_action(1, 2), "flag");
+        assert_eq!(risk_action(2, 2), "freeze");
+        assert_eq!(risk_action(3, 2), "freeze");
+        // A threshold of zero still needs one loss, which freezes
+        assert_eq!(risk_action(1, 0), "freeze");
+    }
+}
@@ -0,0 +1,471 @@
+//! Distributed locks and leader election
+//!
+//! Replicas coordinate through named leases with fencing tokens. A lease is
+//! held until it expires and renewed well before then; a replica that dies
+//! stops renewing and another takes over once the lease lapses. Every change
+//! of holder bumps the lock's fencing token, so work tagged with a token can
+//! be told apart from work done under a lease that has since been lost.
+//!
+//! Singleton workers run a [`LeaderElection`] and only do their work while
+//! their [`Leadership`] says they lead. Per-transaction work uses row-level
+//! claims on the transaction itself instead.
+
+use crate::database::distributed_lock_repository::{DistributedLockRepository, LockLease};
+use crate::database::error::DatabaseError;
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+/// Identifies this process to other replicas: `INSTANCE_ID`, or the host
+/// name and process ID
+pub fn instance_id() -> String {
+    match std::env::var("INSTANCE_ID") {
+        Ok(id) if !id.trim().is_empty() => id.trim().to_string(),
+        _ => format!(
+            "{}-{}",
+            std::env::var("HOSTNAME").unwrap_or_else(|_| "aframp".to_string()),
+            std::process::id()
+        ),
+    }
+}
+
+/// Where leases are kept
+#[async_trait]
+pub trait LockStore: Send + Sync {
+    /// Take the lock if it is free or has lapsed. Returns `None` while
+    /// someone holds it.
+    async fn try_acquire(
+        &self,
+        name: &str,
+        holder: &str,
+        ttl: Duration,
+    ) -> Result<Option<LockLease>, DatabaseError>;
+
+    /// Extend a lease. Returns `None` once another holder has taken the lock.
+    async fn renew(
+        &self,
+        lease: &LockLease,
+        ttl: Duration,
+    ) -> Result<Option<LockLease>, DatabaseError>;
+
+    /// Give a lease up early. Returns `false` if it had already been lost.
+    async fn release(&self, lease: &LockLease) -> Result<bool, DatabaseError>;
+}
+
+/// Leases shared between replicas through the `distributed_locks` table
+pub struct PgLockStore {
+    repo: DistributedLockRepository,
+}
+
+impl PgLockStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            repo: DistributedLockRepository::new(pool),
+        }
+    }
+}
+
+#[async_trait]
+impl LockStore for PgLockStore {
+    async fn try_acquire(
+        &self,
+        name: &str,
+        holder: &str,
+        ttl: Duration,
+    ) -> Result<Option<LockLease>, DatabaseError> {
+        self.repo.try_acquire(name, holder, ttl).await
+    }
+
+    async fn renew(
+        &self,
+        lease: &LockLease,
+        ttl: Duration,
+    ) -> Result<Option<LockLease>, DatabaseError> {
+        self.repo.renew(lease, ttl).await
+    }
+
+    async fn release(&self, lease: &LockLease) -> Result<bool, DatabaseError> {
+        self.repo.release(lease).await
+    }
+}
+
+/// Leases held in process memory, for single-instance setups and tests.
+/// Follows the same rules as [`PgLockStore`].
+#[derive(Default)]
+pub struct MemoryLockStore {
+    locks: Mutex<HashMap<String, LockLease>>,
+}
+
+impl MemoryLockStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl LockStore for MemoryLockStore {
+    async fn try_acquire(
+        &self,
+        name: &str,
+        holder: &str,
+        ttl: Duration,
+    ) -> Result<Option<LockLease>, DatabaseError> {
+        let now = Utc::now();
+        let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+        let fencing_token = match locks.get(name) {
+            Some(existing) if existing.expires_at >= now => return Ok(None),
+            Some(existing) => existing.fencing_token + 1,
+            None => 1,
+        };
+        let lease = LockLease {
+            name: name.to_string(),
+            holder: holder.to_string(),
+            fencing_token,
+            acquired_at: now,
+            expires_at: now + chrono::Duration::from_std(ttl).unwrap_or_default(),
+        };
+        locks.insert(name.to_string(), lease.clone());
+        Ok(Some(lease))
+    }
+
+    async fn renew(
+        &self,
+        lease: &LockLease,
+        ttl: Duration,
+    ) -> Result<Option<LockLease>, DatabaseError> {
+        let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+        match locks.get_mut(&lease.name) {
+            Some(current)
+                if current.holder == lease.holder
+                    && current.fencing_token == lease.fencing_token =>
+            {
+                current.expires_at =
+                    Utc::now() + chrono::Duration::from_std(ttl).unwrap_or_default();
+                Ok(Some(current.clone()))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    async fn release(&self, lease: &LockLease) -> Result<bool, DatabaseError> {
+        let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+        match locks.get_mut(&lease.name) {
+            Some(current)
+                if current.holder == lease.holder
+                    && current.fencing_token == lease.fencing_token =>
+            {
+                current.expires_at = Utc::now() - chrono::Duration::milliseconds(1);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LeaderElectionConfig {
+    /// How long leadership lasts without renewal
+    pub lease: Duration,
+    /// How often the leader renews, and followers try to take over
+    pub renew_interval: Duration,
+}
+
+impl Default for LeaderElectionConfig {
+    fn default() -> Self {
+        Self {
+            lease: Duration::from_secs(30),
+            renew_interval: Duration::from_secs(10),
+        }
+    }
+}
+
+impl LeaderElectionConfig {
+    pub fn from_env() -> Self {
+        let mut cfg = Self::default();
+        let var = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<u64>().ok());
+        if let Some(secs) = var("LEADER_LEASE_SECONDS").filter(|v| *v > 0) {
+            cfg.lease = Duration::from_secs(secs);
+        }
+        if let Some(secs) = var("LEADER_RENEW_SECONDS").filter(|v| *v > 0) {
+            cfg.renew_interval = Duration::from_secs(secs);
+        }
+        // Renewing less often than the lease lasts would drop leadership
+        // between renewals
+        if cfg.renew_interval >= cfg.lease {
+            cfg.renew_interval = cfg.lease / 3;
+        }
+        cfg
+    }
+}
+
+/// Campaigns for one named leadership until shutdown, renewing it while held
+pub struct LeaderElection {
+    store: Arc<dyn LockStore>,
+    name: String,
+    holder: String,
+    config: LeaderElectionConfig,
+    state: watch::Sender<Option<LockLease>>,
+}
+
+impl LeaderElection {
+    pub fn new(
+        store: Arc<dyn LockStore>,
+        name: &str,
+        holder: &str,
+        config: LeaderElectionConfig,
+    ) -> Self {
+        let (state, _) = watch::channel(None);
+        Self {
+            store,
+            name: name.to_string(),
+            holder: holder.to_string(),
+            config,
+            state,
+        }
+    }
+
+    /// A view of whether this instance currently leads
+    pub fn leadership(&self) -> Leadership {
+        Leadership {
+            name: self.name.clone(),
+            state: self.state.subscribe(),
+        }
+    }
+
+    /// One round: renew the lease if held, otherwise try to take it
+    pub async fn step(&self) {
+        let current = self.state.borrow().clone();
+        let next = match current {
+            Some(lease) => match self.store.renew(&lease, self.config.lease).await {
+                Ok(Some(renewed)) => Some(renewed),
+                Ok(None) => {
+                    warn!(
+                        lock = %self.name,
+                        holder = %self.holder,
+                        fencing_token = lease.fencing_token,
+                        "leadership lost to another instance"
+                    );
+                    None
+                }
+                Err(e) => {
+                    // Keep the lease; it stops counting once it expires
+                    warn!(lock = %self.name, error = %e, "failed to renew leadership");
+                    Some(lease)
+                }
+            },
+            None => match self
+                .store
+                .try_acquire(&self.name, &self.holder, self.config.lease)
+                .await
+            {
+                Ok(Some(lease)) => {
+                    info!(
+                        lock = %self.name,
+                        holder = %self.holder,
+                        fencing_token = lease.fencing_token,
+                        "leadership acquired"
+                    );
+                    Some(lease)
+                }
+                Ok(None) => None,
+                Err(e) => {
+                    warn!(lock = %self.name, error = %e, "failed to campaign for leadership");
+                    None
+                }
+            },
+        };
+        self.state.send_replace(next);
+    }
+
+    /// Give leadership up so another instance can take over straight away
+    pub async fn resign(&self) {
+        if let Some(lease) = self.state.send_replace(None) {
+            if let Err(e) = self.store.release(&lease).await {
+                warn!(lock = %self.name, error = %e, "failed to release leadership");
+            }
+        }
+    }
+
+    pub async fn run(self, mut shutdown_rx: watch::Receiver<bool>) {
+        info!(
+            lock = %self.name,
+            holder = %self.holder,
+            lease_secs = self.config.lease.as_secs(),
+            "leader election started"
+        );
+
+        loop {
+            self.step().await;
+            tokio::select! {
+                _ = shutdown_rx.changed() => {
+                    if *shutdown_rx.borrow() {
+                        break;
+                    }
+                }
+                _ = tokio::time::sleep(self.config.renew_interval) => {}
+            }
+        }
+
+        self.resign().await;
+        info!(lock = %self.name, "leader election stopped");
+    }
+}
+
+/// Whether this instance leads, as last seen by its [`LeaderElection`]
+#[derive(Debug, Clone)]
+pub struct Leadership {
+    name: String,
+    state: watch::Receiver<Option<LockLease>>,
+}
+
+impl Leadership {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Fencing token of the lease this instance holds, if it holds an
+    /// unexpired one. A lease whose renewals have been failing stops
+    /// counting once it expires.
+    pub fn fencing_token(&self) -> Option<i64> {
+        self.state
+            .borrow()
+            .as_ref()
+            .filter(|lease| lease.expires_at > Utc::now())
+            .map(|lease| lease.fencing_token)
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.fencing_token().is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn election(store: &Arc<MemoryLockStore>, holder: &str, lease_ms: u64) -> LeaderElection {
+        LeaderElection::new(
+            store.clone(),
+            "worker.singleton",
+            holder,
+            LeaderElectionConfig {
+                lease: Duration::from_millis(lease_ms),
+                renew_interval: Duration::from_millis(lease_ms / 3),
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn only_one_of_many_concurrent_instances_acquires() {
+        let store = Arc::new(MemoryLockStore::new());
+        let attempts = (0..16).map(|i| {
+            let store = store.clone();
+            tokio::spawn(async move {
+                store
+                    .try_acquire(
+                        "offramp",
+                        &format!("replica-{}", i),
+                        Duration::from_secs(30),
+                    )
+                    .await
+                    .unwrap()
+            })
+        });
+
+        let mut winners = 0;
+        for attempt in attempts {
+            if attempt.await.unwrap().is_some() {
+                winners += 1;
+            }
+        }
+        assert_eq!(winners, 1);
+    }
+
+    #[tokio::test]
+    async fn leadership_fails_over_with_a_higher_fencing_token() {
+        let store = Arc::new(MemoryLockStore::new());
+        let first = election(&store, "replica-a", 60);
+        let second = election(&store, "replica-b", 60);
+        let (a, b) = (first.leadership(), second.leadership());
+
+        first.step().await;
+        second.step().await;
+        assert!(a.is_leader());
+        assert!(!b.is_leader());
+        let first_token = a.fencing_token().unwrap();
+
+        // Replica A stalls and stops renewing; B takes over once the lease lapses
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        assert!(!a.is_leader());
+        second.step().await;
+        assert!(b.is_leader());
+        assert!(b.fencing_token().unwrap() > first_token);
+
+        // A comes back and finds it has been replaced
+        first.step().await;
+        assert!(!a.is_leader());
+        first.step().await;
+        assert!(!a.is_leader());
+    }
+
+    #[tokio::test]
+    async fn renewals_keep_leadership_past_the_lease() {
+        let store = Arc::new(MemoryLockStore::new());
+        let leader = election(&store, "replica-a", 60);
+        let follower = election(&store, "replica-b", 60);
+        let (a, b) = (leader.leadership(), follower.leadership());
+
+        leader.step().await;
+        let token = a.fencing_token();
+        for _ in 0..5 {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            leader.step().await;
+            follower.step().await;
+        }
+        assert!(a.is_leader());
+        assert!(!b.is_leader());
+        assert_eq!(a.fencing_token(), token);
+    }
+
+    #[tokio::test]
+    async fn resigning_hands_over_immediately() {
+        let store = Arc::new(MemoryLockStore::new());
+        let leader = election(&store, "replica-a", 60_000);
+        let follower = election(&store, "replica-b", 60_000);
+
+        leader.step().await;
+        leader.resign().await;
+        assert!(!leader.leadership().is_leader());
+
+        follower.step().await;
+        assert!(follower.leadership().is_leader());
+        assert_eq!(follower.leadership().fencing_token(), Some(2));
+    }
+
+    #[tokio::test]
+    async fn a_lost_lease_cannot_be_renewed_or_released() {
+        let store = MemoryLockStore::new();
+        let ttl = Duration::from_millis(10);
+        let stale = store
+            .try_acquire("payouts", "a", ttl)
+            .await
+            .unwrap()
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let current = store
+            .try_acquire("payouts", "b", ttl)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(current.fencing_token, stale.fencing_token + 1);
+        assert!(store.renew(&stale, ttl).await.unwrap().is_none());
+        assert!(!store.release(&stale).await.unwrap());
+        assert!(store.renew(&current, ttl).await.unwrap().is_some());
+    }
+}
@@ -7,12 +7,14 @@
 use crate::cache::cache::{Cache, RedisCache};
 use crate::cache::keys::exchange_rate::CurrencyPairKey;
 use crate::database::error::DatabaseError;
-use crate::database::exchange_rate_repository::ExchangeRateRepository;
+use crate::database::exchange_rate_repository::{ExchangeRateHistoryEntry, ExchangeRateRepository};
+use crate::services::currency::{self, SpreadConfig};
 use crate::services::fee_structure::{FeeCalculationInput, FeeStructureService};
 use async_trait::async_trait;
 use bigdecimal::BigDecimal;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
@@ -38,6 +40,16 @@ pub enum ExchangeRateError {
 
     #[error("Invalid amount: {0}")]
     InvalidAmount(String),
+
+    #[error("Invalid history query ({field}): {reason}")]
+    InvalidHistoryQuery { field: String, reason: String },
+
+    #[error("Rate for {from} -> {to} is stale: {reason}")]
+    StaleRate {
+        from: String,
+        to: String,
+        reason: String,
+    },
 }
 
 pub type ExchangeRateResult<T> = Result<T, ExchangeRateError>;
@@ -56,6 +68,12 @@ pub trait RateProvider: Send + Sync {
 
     /// Get provider name
     fn name(&self) -> &str;
+
+    /// Rates that never move (pegs) need no vetting and may be served
+    /// without a stored rate
+    fn is_fixed(&self) -> bool {
+        false
+    }
 }
 
 /// Rate data structure
@@ -92,13 +110,51 @@ pub struct ConversionResult {
     pub from_currency: String,
     pub to_currency: String,
     pub from_amount: String,
+    /// Mid-market rate, triangulated through pivot currencies if needed
     pub base_rate: String,
+    /// Rate applied to the amount: the base rate less the pair's spread
+    #[serde(default)]
+    pub applied_rate: String,
+    #[serde(default)]
+    pub spread_bps: u32,
+    /// Currencies the rate was derived through, e.g. `["KES", "USD", "NGN", "cNGN"]`
+    #[serde(default)]
+    pub rate_path: Vec<String>,
     pub gross_amount: String,
     pub fees: FeeBreakdown,
     pub net_amount: String,
     pub expires_at: DateTime<Utc>,
 }
 
+/// One hop of a cross rate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLeg {
+    pub from_currency: String,
+    pub to_currency: String,
+    pub rate: String,
+    /// Derived from the stored `to -> from` rate
+    pub inverted: bool,
+    pub source: String,
+}
+
+/// Mid-market rate for a pair, direct or derived through pivot currencies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrossRate {
+    pub from_currency: String,
+    pub to_currency: String,
+    pub rate: BigDecimal,
+    pub legs: Vec<RateLeg>,
+}
+
+impl CrossRate {
+    /// Currencies visited from source to target
+    pub fn path(&self) -> Vec<String> {
+        let mut path = vec![self.from_currency.clone()];
+        path.extend(self.legs.iter().map(|leg| leg.to_currency.clone()));
+        path
+    }
+}
+
 /// Fee breakdown
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FeeBreakdown {
@@ -107,6 +163,85 @@ pub struct FeeBreakdown {
     pub total_fees: String,
 }
 
+/// Most candles returned by a single history query
+pub const MAX_HISTORY_CANDLES: i64 = 1000;
+/// Most rate changes returned by a single history query
+pub const MAX_HISTORY_ENTRIES: i64 = 5000;
+
+/// Candle width for rate history aggregation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RateInterval {
+    #[serde(rename = "1m")]
+    OneMinute,
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    #[serde(rename = "15m")]
+    FifteenMinutes,
+    #[serde(rename = "1h")]
+    OneHour,
+    #[serde(rename = "4h")]
+    FourHours,
+    #[serde(rename = "1d")]
+    OneDay,
+}
+
+impl RateInterval {
+    pub fn duration(&self) -> chrono::Duration {
+        match self {
+            RateInterval::OneMinute => chrono::Duration::minutes(1),
+            RateInterval::FiveMinutes => chrono::Duration::minutes(5),
+            RateInterval::FifteenMinutes => chrono::Duration::minutes(15),
+            RateInterval::OneHour => chrono::Duration::hours(1),
+            RateInterval::FourHours => chrono::Duration::hours(4),
+            RateInterval::OneDay => chrono::Duration::days(1),
+        }
+    }
+}
+
+impl FromStr for RateInterval {
+    type Err = ExchangeRateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1m" => Ok(RateInterval::OneMinute),
+            "5m" => Ok(RateInterval::FiveMinutes),
+            "15m" => Ok(RateInterval::FifteenMinutes),
+            "1h" => Ok(RateInterval::OneHour),
+            "4h" => Ok(RateInterval::FourHours),
+            "1d" => Ok(RateInterval::OneDay),
+            other => Err(ExchangeRateError::InvalidHistoryQuery {
+                field: "interval".to_string(),
+                reason: format!(
+                    "unsupported interval '{}', expected one of 1m, 5m, 15m, 1h, 4h, 1d",
+                    other
+                ),
+            }),
+        }
+    }
+}
+
+/// Open/high/low/close of the rate in effect during one interval
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RateCandle {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub open: String,
+    pub high: String,
+    pub low: String,
+    pub close: String,
+    /// Rate changes that took effect inside the interval
+    pub changes: usize,
+}
+
+/// Rates in effect over a history range, oldest first
+#[derive(Debug, Clone)]
+pub struct RateHistory {
+    pub entries: Vec<ExchangeRateHistoryEntry>,
+    /// More than `MAX_HISTORY_ENTRIES` rates were in effect; only the
+    /// oldest are included
+    pub truncated: bool,
+}
+
 /// Exchange rate service configuration
 #[derive(Debug, Clone)]
 pub struct ExchangeRateServiceConfig {
@@ -114,6 +249,12 @@ pub struct ExchangeRateServiceConfig {
     pub rate_expiry_seconds: u64,
     pub enable_validation: bool,
     pub max_rate_deviation: BigDecimal, // Maximum allowed deviation from 1.0 for cNGN
+    /// Currencies cross rates may be triangulated through, in preference order
+    pub pivot_currencies: Vec<String>,
+    pub spreads: SpreadConfig,
+    /// Stored rates older than this are not quoted, whether or not the
+    /// refresher has marked them stale yet
+    pub max_rate_age_seconds: u64,
 }
 
 impl Default for ExchangeRateServiceConfig {
@@ -123,6 +264,40 @@ impl Default for ExchangeRateServiceConfig {
             rate_expiry_seconds: 300,
             enable_validation: true,
             max_rate_deviation: BigDecimal::from_str("0.0001").unwrap(),
+            pivot_currencies: vec!["USD".to_string(), "NGN".to_string()],
+            spreads: SpreadConfig::default(),
+            max_rate_age_seconds: 900,
+        }
+    }
+}
+
+impl ExchangeRateServiceConfig {
+    /// Defaults with pivots from `FX_PIVOT_CURRENCIES` (comma-separated),
+    /// spreads from [`SpreadConfig::from_env`] and the maximum rate age from
+    /// `RATE_STALE_AFTER_SECONDS`, the refresher's staleness window
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let pivot_currencies = std::env::var("FX_PIVOT_CURRENCIES")
+            .ok()
+            .map(|v| {
+                v.split(',')
+                    .map(|c| c.trim().to_string())
+                    .filter(|c| !c.is_empty())
+                    .collect::<Vec<_>>()
+            })
+            .filter(|pivots| !pivots.is_empty())
+            .unwrap_or(defaults.pivot_currencies.clone());
+
+        let max_rate_age_seconds = std::env::var("RATE_STALE_AFTER_SECONDS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(defaults.max_rate_age_seconds);
+
+        Self {
+            pivot_currencies,
+            spreads: SpreadConfig::from_env(),
+            max_rate_age_seconds,
+            ..defaults
         }
     }
 }
@@ -172,25 +347,66 @@ impl ExchangeRateService {
         from_currency: &str,
         to_currency: &str,
     ) -> ExchangeRateResult<BigDecimal> {
-        // Try cache first
-        if let Some(cached_rate) = self.get_cached_rate(from_currency, to_currency).await {
-            debug!("Cache hit for rate: {} -> {}", from_currency, to_currency);
-            return Ok(cached_rate.base_rate);
+        Ok(self.get_rate_data(from_currency, to_currency).await?.base_rate)
+    }
+
+    /// Mid-market rate for any pair: the stored rate, its inverse, or a
+    /// product of rates through up to two pivot currencies (e.g.
+    /// KES -> USD -> NGN -> cNGN). Shorter paths win, then pivot order.
+    pub async fn get_cross_rate(
+        &self,
+        from_currency: &str,
+        to_currency: &str,
+    ) -> ExchangeRateResult<CrossRate> {
+        if from_currency == to_currency {
+            return Ok(CrossRate {
+                from_currency: from_currency.to_string(),
+                to_currency: to_currency.to_string(),
+                rate: BigDecimal::from(1),
+                legs: Vec::new(),
+            });
         }
 
-        // Cache miss - fetch from provider or database
-        let rate_data = self.fetch_rate_data(from_currency, to_currency).await?;
+        let mut legs_seen: HashMap<(String, String), Option<(BigDecimal, RateLeg)>> =
+            HashMap::new();
+        for path in candidate_paths(from_currency, to_currency, &self.config.pivot_currencies) {
+            let mut rate = BigDecimal::from(1);
+            let mut legs = Vec::with_capacity(path.len() - 1);
+            for hop in path.windows(2) {
+                let key = (hop[0].to_string(), hop[1].to_string());
+                let leg = match legs_seen.get(&key) {
+                    Some(leg) => leg.clone(),
+                    None => {
+                        let leg = self.resolve_leg(hop[0], hop[1]).await?;
+                        legs_seen.insert(key, leg.clone());
+                        leg
+                    }
+                };
+                let Some((leg_rate, leg)) = leg else {
+                    break;
+                };
+                rate = currency::round_rate(&(rate * leg_rate));
+                legs.push(leg);
+            }
 
-        // Cache the result
-        if let Some(ref cache) = self.cache {
-            let cache_key = CurrencyPairKey::new(from_currency, to_currency);
-            let ttl = Duration::from_secs(self.config.cache_ttl_seconds);
-            let _ = cache
-                .set(&cache_key.to_string(), &rate_data, Some(ttl))
-                .await;
+            if legs.len() == path.len() - 1 {
+                debug!(
+                    "Cross rate {} -> {} = {} via {:?}",
+                    from_currency, to_currency, rate, path
+                );
+                return Ok(CrossRate {
+                    from_currency: from_currency.to_string(),
+                    to_currency: to_currency.to_string(),
+                    rate,
+                    legs,
+                });
+            }
         }
 
-        Ok(rate_data.base_rate)
+        Err(ExchangeRateError::RateNotFound {
+            from: from_currency.to_string(),
+            to: to_currency.to_string(),
+        })
     }
 
     /// Calculate conversion with fees
@@ -205,16 +421,29 @@ impl ExchangeRateService {
             ));
         }
 
-        // Get exchange rate
-        let rate = self
-            .get_rate(&request.from_currency, &request.to_currency)
+        // Get exchange rate, triangulating if there is no direct rate
+        let cross = self
+            .get_cross_rate(&request.from_currency, &request.to_currency)
             .await?;
+        self.ensure_fresh(&cross).await?;
+        let applied_rate =
+            self.config
+                .spreads
+                .apply(&request.from_currency, &request.to_currency, &cross.rate);
 
-        // Calculate gross amount
-        let gross_amount = &request.amount * &rate;
+        // Calculate gross amount, never crediting more than the target precision allows
+        let gross_amount =
+            currency::round_amount(&(&request.amount * &applied_rate), &request.to_currency, true);
 
         // Calculate fees
         let fees = self.calculate_fees(&request, &gross_amount).await?;
+        let provider_fee = currency::round_amount(&fees.provider_fee, &request.to_currency, false);
+        let platform_fee = currency::round_amount(&fees.platform_fee, &request.to_currency, false);
+        let fees = FeeCalculation {
+            total_fees: &provider_fee + &platform_fee,
+            provider_fee,
+            platform_fee,
+        };
 
         // Calculate net amount (gross - fees)
         let net_amount = &gross_amount - &fees.total_fees;
@@ -227,7 +456,13 @@ impl ExchangeRateService {
             from_currency: request.from_currency.clone(),
             to_currency: request.to_currency.clone(),
             from_amount: request.amount.to_string(),
-            base_rate: rate.to_string(),
+            base_rate: cross.rate.to_string(),
+            applied_rate: applied_rate.to_string(),
+            spread_bps: self
+                .config
+                .spreads
+                .spread_bps(&request.from_currency, &request.to_currency),
+            rate_path: cross.path(),
             gross_amount: gross_amount.to_string(),
             fees: FeeBreakdown {
                 provider_fee: fees.provider_fee.to_string(),
@@ -239,28 +474,92 @@ impl ExchangeRateService {
         })
     }
 
-    /// Get historical rate at specific timestamp
+    /// Get the rate that was in effect at `timestamp`
     pub async fn get_historical_rate(
         &self,
         from_currency: &str,
         to_currency: &str,
-        _timestamp: DateTime<Utc>,
+        timestamp: DateTime<Utc>,
     ) -> ExchangeRateResult<BigDecimal> {
-        // For now, get the most recent rate
-        // TODO: Implement time-based query
-        let rate = self
+        let entry = self
             .repository
-            .get_current_rate(from_currency, to_currency)
+            .rate_as_of(from_currency, to_currency, timestamp)
             .await?
             .ok_or_else(|| ExchangeRateError::RateNotFound {
                 from: from_currency.to_string(),
                 to: to_currency.to_string(),
             })?;
 
-        BigDecimal::from_str(&rate.rate).map_err(|e| ExchangeRateError::InvalidRate(e.to_string()))
+        Ok(entry.rate)
     }
 
-    /// Update exchange rate
+    /// Rates in effect at any point between `start` and `end`, oldest first,
+    /// up to `MAX_HISTORY_ENTRIES` of them
+    pub async fn get_rate_history(
+        &self,
+        from_currency: &str,
+        to_currency: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> ExchangeRateResult<RateHistory> {
+        check_history_range(start, end)?;
+
+        let mut entries = self
+            .repository
+            .list_history(
+                from_currency,
+                to_currency,
+                start,
+                end,
+                MAX_HISTORY_ENTRIES + 1,
+            )
+            .await?;
+        let truncated = entries.len() as i64 > MAX_HISTORY_ENTRIES;
+        entries.truncate(MAX_HISTORY_ENTRIES as usize);
+        Ok(RateHistory { entries, truncated })
+    }
+
+    /// OHLC candles of the rate between `start` and `end`. Aggregated in the
+    /// database, so they cover every rate change however many there are.
+    pub async fn get_rate_candles(
+        &self,
+        from_currency: &str,
+        to_currency: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        interval: RateInterval,
+    ) -> ExchangeRateResult<Vec<RateCandle>> {
+        check_history_range(start, end)?;
+        let buckets = (end - start).num_seconds() / interval.duration().num_seconds();
+        if buckets > MAX_HISTORY_CANDLES {
+            return Err(ExchangeRateError::InvalidHistoryQuery {
+                field: "interval".to_string(),
+                reason: format!(
+                    "range spans {} intervals, at most {} allowed",
+                    buckets, MAX_HISTORY_CANDLES
+                ),
+            });
+        }
+
+        let candles = self
+            .repository
+            .list_history_candles(from_currency, to_currency, start, end, interval.duration())
+            .await?;
+        Ok(candles
+            .into_iter()
+            .map(|c| RateCandle {
+                start: c.start,
+                end: c.end,
+                open: c.open.to_string(),
+                high: c.high.to_string(),
+                low: c.low.to_string(),
+                close: c.close.to_string(),
+                changes: c.changes as usize,
+            })
+            .collect())
+    }
+
+    /// Update exchange rate, appending the change to the rate history
     pub async fn update_rate(
         &self,
         from_currency: &str,
@@ -275,7 +574,7 @@ impl ExchangeRateService {
 
         // Store in database
         self.repository
-            .upsert_rate(from_currency, to_currency, &rate.to_string(), Some(source))
+            .record_rate(from_currency, to_currency, &rate, Some(source))
             .await?;
 
         // Invalidate cache
@@ -292,6 +591,102 @@ impl ExchangeRateService {
         Ok(())
     }
 
+    /// Pairs the configured providers can quote
+    pub fn supported_pairs(&self) -> Vec<(String, String)> {
+        let mut pairs = Vec::new();
+        for provider in &self.providers {
+            for pair in provider.get_supported_pairs() {
+                if !pairs.contains(&pair) {
+                    pairs.push(pair);
+                }
+            }
+        }
+        pairs
+    }
+
+    /// Fetch a pair from the provider chain without storing it or falling
+    /// back to the database, so the caller can vet it before [`Self::update_rate`]
+    pub async fn fetch_from_providers(
+        &self,
+        from_currency: &str,
+        to_currency: &str,
+    ) -> ExchangeRateResult<RateData> {
+        let mut last_error = None;
+        for provider in &self.providers {
+            if !provider.is_healthy().await {
+                continue;
+            }
+            match provider.fetch_rate(from_currency, to_currency).await {
+                Ok(rate_data) => return Ok(rate_data),
+                Err(ExchangeRateError::RateNotFound { .. }) => continue,
+                Err(e) => {
+                    warn!("Provider {} failed to fetch rate: {}", provider.name(), e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| ExchangeRateError::RateNotFound {
+            from: from_currency.to_string(),
+            to: to_currency.to_string(),
+        }))
+    }
+
+    /// The stored rate for a pair and when it was last updated
+    pub async fn stored_rate(
+        &self,
+        from_currency: &str,
+        to_currency: &str,
+    ) -> ExchangeRateResult<Option<(BigDecimal, DateTime<Utc>)>> {
+        match self
+            .repository
+            .get_current_rate(from_currency, to_currency)
+            .await?
+        {
+            Some(rate) => {
+                let value = BigDecimal::from_str(&rate.rate)
+                    .map_err(|e| ExchangeRateError::InvalidRate(e.to_string()))?;
+                Ok(Some((value, rate.updated_at)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Mark a stored pair stale until a new rate is recorded
+    pub async fn mark_stale(
+        &self,
+        from_currency: &str,
+        to_currency: &str,
+        reason: &str,
+    ) -> ExchangeRateResult<bool> {
+        Ok(self
+            .repository
+            .mark_stale(from_currency, to_currency, reason)
+            .await?)
+    }
+
+    /// Fail if any stored rate behind `cross` is marked stale or older than
+    /// `max_rate_age_seconds`. Fixed-peg legs never go stale.
+    pub async fn ensure_fresh(&self, cross: &CrossRate) -> ExchangeRateResult<()> {
+        let max_age = chrono::Duration::seconds(self.config.max_rate_age_seconds as i64);
+        for leg in cross.legs.iter().filter(|leg| leg.source != "fixed_peg") {
+            let (from, to) = if leg.inverted {
+                (&leg.to_currency, &leg.from_currency)
+            } else {
+                (&leg.from_currency, &leg.to_currency)
+            };
+            if let Some(freshness) = self.repository.find_freshness(from, to).await? {
+                if let Some(reason) = freshness.staleness(Utc::now(), max_age) {
+                    return Err(ExchangeRateError::StaleRate {
+                        from: from.clone(),
+                        to: to.clone(),
+                        reason,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Invalidate cached rate
     pub async fn invalidate_cache(
         &self,
@@ -309,6 +704,75 @@ impl ExchangeRateService {
 
     // Private helper methods
 
+    async fn get_rate_data(
+        &self,
+        from_currency: &str,
+        to_currency: &str,
+    ) -> ExchangeRateResult<RateData> {
+        // Try cache first
+        if let Some(cached_rate) = self.get_cached_rate(from_currency, to_currency).await {
+            debug!("Cache hit for rate: {} -> {}", from_currency, to_currency);
+            return Ok(cached_rate);
+        }
+
+        // Cache miss - fetch from provider or database
+        let rate_data = self.fetch_rate_data(from_currency, to_currency).await?;
+
+        // Cache the result
+        if let Some(ref cache) = self.cache {
+            let cache_key = CurrencyPairKey::new(from_currency, to_currency);
+            let ttl = Duration::from_secs(self.config.cache_ttl_seconds);
+            let _ = cache
+                .set(&cache_key.to_string(), &rate_data, Some(ttl))
+                .await;
+        }
+
+        Ok(rate_data)
+    }
+
+    /// Rate for a single hop, from the stored pair or the inverse of the
+    /// opposite pair. `None` if neither is known.
+    async fn resolve_leg(
+        &self,
+        from_currency: &str,
+        to_currency: &str,
+    ) -> ExchangeRateResult<Option<(BigDecimal, RateLeg)>> {
+        match self.get_rate_data(from_currency, to_currency).await {
+            Ok(data) => {
+                return Ok(Some((
+                    data.base_rate.clone(),
+                    RateLeg {
+                        from_currency: from_currency.to_string(),
+                        to_currency: to_currency.to_string(),
+                        rate: data.base_rate.to_string(),
+                        inverted: false,
+                        source: data.source,
+                    },
+                )))
+            }
+            Err(ExchangeRateError::RateNotFound { .. }) => {}
+            Err(e) => return Err(e),
+        }
+
+        match self.get_rate_data(to_currency, from_currency).await {
+            Ok(data) if data.base_rate > 0 => {
+                let rate = currency::round_rate(&(BigDecimal::from(1) / &data.base_rate));
+                Ok(Some((
+                    rate.clone(),
+                    RateLeg {
+                        from_currency: from_currency.to_string(),
+                        to_currency: to_currency.to_string(),
+                        rate: rate.to_string(),
+                        inverted: true,
+                        source: data.source,
+                    },
+                )))
+            }
+            Ok(_) | Err(ExchangeRateError::RateNotFound { .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
     async fn get_cached_rate(&self, from_currency: &str, to_currency: &str) -> Option<RateData> {
         if let Some(ref cache) = self.cache {
             let cache_key = CurrencyPairKey::new(from_currency, to_currency);
@@ -318,57 +782,44 @@ impl ExchangeRateService {
         }
     }
 
+    /// The stored rate for a pair. Only the rate refresher stores rates, so
+    /// every rate served here has passed its circuit breaker; fixed pegs are
+    /// the one thing answered straight from a provider.
     async fn fetch_rate_data(
         &self,
         from_currency: &str,
         to_currency: &str,
     ) -> ExchangeRateResult<RateData> {
-        // Try providers first
-        for provider in &self.providers {
-            if provider.is_healthy().await {
-                match provider.fetch_rate(from_currency, to_currency).await {
-                    Ok(rate_data) => {
-                        // Store in database for historical record
-                        let _ = self
-                            .repository
-                            .upsert_rate(
-                                from_currency,
-                                to_currency,
-                                &rate_data.base_rate.to_string(),
-                                Some(&rate_data.source),
-                            )
-                            .await;
-                        return Ok(rate_data);
-                    }
-                    Err(e) => {
-                        warn!("Provider {} failed to fetch rate: {}", provider.name(), e);
-                        continue;
-                    }
-                }
-            }
-        }
-
-        // Fallback to database
-        let rate = self
+        if let Some(rate) = self
             .repository
             .get_current_rate(from_currency, to_currency)
             .await?
-            .ok_or_else(|| ExchangeRateError::RateNotFound {
-                from: from_currency.to_string(),
-                to: to_currency.to_string(),
-            })?;
+        {
+            let base_rate = BigDecimal::from_str(&rate.rate)
+                .map_err(|e| ExchangeRateError::InvalidRate(e.to_string()))?;
+
+            return Ok(RateData {
+                currency_pair: format!("{}/{}", from_currency, to_currency),
+                base_rate: base_rate.clone(),
+                buy_rate: base_rate.clone(),
+                sell_rate: base_rate.clone(),
+                spread: BigDecimal::from(0),
+                source: rate.source.unwrap_or_else(|| "database".to_string()),
+                last_updated: rate.updated_at,
+            });
+        }
+
+        for provider in self.providers.iter().filter(|p| p.is_fixed()) {
+            match provider.fetch_rate(from_currency, to_currency).await {
+                Ok(rate_data) => return Ok(rate_data),
+                Err(ExchangeRateError::RateNotFound { .. }) => continue,
+                Err(e) => warn!("Provider {} failed to fetch rate: {}", provider.name(), e),
+            }
+        }
 
-        let base_rate = BigDecimal::from_str(&rate.rate)
-            .map_err(|e| ExchangeRateError::InvalidRate(e.to_string()))?;
-
-        Ok(RateData {
-            currency_pair: format!("{}/{}", from_currency, to_currency),
-            base_rate: base_rate.clone(),
-            buy_rate: base_rate.clone(),
-            sell_rate: base_rate.clone(),
-            spread: BigDecimal::from(0),
-            source: rate.source.unwrap_or_else(|| "database".to_string()),
-            last_updated: rate.updated_at,
+        Err(ExchangeRateError::RateNotFound {
+            from: from_currency.to_string(),
+            to: to_currency.to_string(),
         })
     }
 
@@ -464,6 +915,35 @@ impl ExchangeRateService {
     }
 }
 
+/// Paths to try for a cross rate, shortest first: direct, one pivot, then
+/// two distinct pivots
+fn candidate_paths<'a>(from: &'a str, to: &'a str, pivots: &'a [String]) -> Vec<Vec<&'a str>> {
+    let pivots: Vec<&str> = pivots
+        .iter()
+        .map(String::as_str)
+        .filter(|p| *p != from && *p != to)
+        .collect();
+
+    let mut paths = vec![vec![from, to]];
+    paths.extend(pivots.iter().map(|p| vec![from, *p, to]));
+    for first in &pivots {
+        for second in pivots.iter().filter(|p| *p != first) {
+            paths.push(vec![from, *first, *second, to]);
+        }
+    }
+    paths
+}
+
+fn check_history_range(start: DateTime<Utc>, end: DateTime<Utc>) -> ExchangeRateResult<()> {
+    if start >= end {
+        return Err(ExchangeRateError::InvalidHistoryQuery {
+            field: "from".to_string(),
+            reason: "from must be before to".to_string(),
+        });
+    }
+    Ok(())
+}
+
 #[derive(Debug, Clone)]
 struct FeeCalculation {
     provider_fee: BigDecimal,
@@ -474,6 +954,7 @@ struct FeeCalculation {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::database::exchange_rate_repository::RateFreshness;
 
     #[test]
     fn test_conversion_direction() {
@@ -501,4 +982,56 @@ mod tests {
         let result = service.validate_rate("USD", "NGN", &BigDecimal::from(-1));
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_candidate_paths_prefer_fewer_hops() {
+        let pivots = vec!["USD".to_string(), "NGN".to_string()];
+        let paths = candidate_paths("KES", "cNGN", &pivots);
+        assert_eq!(paths[0], vec!["KES", "cNGN"]);
+        assert_eq!(paths[1], vec!["KES", "USD", "cNGN"]);
+        assert_eq!(paths[2], vec!["KES", "NGN", "cNGN"]);
+        assert!(paths.contains(&vec!["KES", "USD", "NGN", "cNGN"]));
+
+        // A pivot equal to an endpoint is skipped
+        let paths = candidate_paths("USD", "cNGN", &pivots);
+        assert_eq!(paths, vec![vec!["USD", "cNGN"], vec!["USD", "NGN", "cNGN"]]);
+    }
+
+    #[test]
+    fn test_rate_interval_parsing() {
+        assert_eq!(
+            "15m".parse::<RateInterval>().unwrap(),
+            RateInterval::FifteenMinutes
+        );
+        assert!("2w".parse::<RateInterval>().is_err());
+    }
+
+    #[test]
+    fn test_rate_staleness_by_marker_and_age() {
+        let now = Utc::now();
+        let max_age = chrono::Duration::minutes(15);
+        let mut freshness = RateFreshness {
+            from_currency: "USD".to_string(),
+            to_currency: "NGN".to_string(),
+            source: Some("test".to_string()),
+            updated_at: now - chrono::Duration::minutes(5),
+            stale_since: None,
+            stale_reason: None,
+        };
+        assert_eq!(freshness.staleness(now, max_age), None);
+
+        // A refresher that stopped running leaves no marker behind
+        freshness.updated_at = now - chrono::Duration::minutes(20);
+        assert!(freshness
+            .staleness(now, max_age)
+            .is_some_and(|reason| reason.contains("1200s ago")));
+
+        freshness.updated_at = now;
+        freshness.stale_since = Some(now);
+        freshness.stale_reason = Some("circuit breaker".to_string());
+        assert_eq!(
+            freshness.staleness(now, max_age),
+            Some("circuit breaker".to_string())
+        );
+    }
 }
@@ -1,4 +1,8 @@
 use crate::database::error::{DatabaseError, DatabaseErrorKind};
+use crate::database::fee_rule_repository::FeeRuleRepository;
+use crate::services::fee_rules::{self, AppliedFeeRule, FeeContext, FeeRuleKind};
+use bigdecimal::Zero;
+use chrono::Datelike;
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use std::collections::HashMap;
@@ -24,6 +28,9 @@ pub struct FeeBreakdown {
     pub net_amount: BigDecimal,
     #[serde(with = "bigdecimal_serde")]
     pub effective_rate: BigDecimal,
+    /// Fee rules that changed the platform fee, in the order applied
+    #[serde(default)]
+    pub applied_rules: Vec<AppliedFeeRule>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -117,10 +124,66 @@ struct FeeConfig {
     platform_fee_percent: Option<BigDecimal>,
 }
 
+/// A fee structure tier that is not (yet) stored in `fee_structures`,
+/// used to simulate proposed pricing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProposedFeeStructure {
+    pub transaction_type: String,
+    #[serde(default)]
+    pub payment_provider: Option<String>,
+    #[serde(default)]
+    pub payment_method: Option<String>,
+    #[serde(default)]
+    pub min_amount: Option<BigDecimal>,
+    #[serde(default)]
+    pub max_amount: Option<BigDecimal>,
+    #[serde(default)]
+    pub provider_fee_percent: Option<BigDecimal>,
+    #[serde(default)]
+    pub provider_fee_flat: Option<BigDecimal>,
+    #[serde(default)]
+    pub provider_fee_cap: Option<BigDecimal>,
+    #[serde(default)]
+    pub platform_fee_percent: Option<BigDecimal>,
+}
+
+impl ProposedFeeStructure {
+    /// Same constraints `fee_structures` enforces
+    pub fn validate(&self) -> Result<(), String> {
+        const TYPES: [&str; 5] = ["onramp", "offramp", "bill_payment", "exchange", "transfer"];
+        if !TYPES.contains(&self.transaction_type.as_str()) {
+            return Err(format!(
+                "unknown transaction_type {}",
+                self.transaction_type
+            ));
+        }
+        if let (Some(min), Some(max)) = (&self.min_amount, &self.max_amount) {
+            if min > max {
+                return Err("min_amount must not exceed max_amount".to_string());
+            }
+        }
+        for (field, percent) in [
+            ("provider_fee_percent", &self.provider_fee_percent),
+            ("platform_fee_percent", &self.platform_fee_percent),
+        ] {
+            if let Some(percent) = percent {
+                if *percent < 0 || *percent > 100 {
+                    return Err(format!("{} must be between 0 and 100", field));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 pub struct FeeCalculationService {
     pool: PgPool,
     cache: Arc<RwLock<HashMap<String, Vec<FeeConfig>>>>,
     xlm_rate_cache: Arc<RwLock<Option<(BigDecimal, chrono::DateTime<chrono::Utc>)>>>,
+    /// Tiers to use instead of `fee_structures`
+    proposed: Option<Vec<FeeConfig>>,
+    /// Skip fee rules and never write calculation logs
+    simulated: bool,
 }
 
 impl FeeCalculationService {
@@ -129,15 +192,85 @@ impl FeeCalculationService {
             pool,
             cache: Arc::new(RwLock::new(HashMap::new())),
             xlm_rate_cache: Arc::new(RwLock::new(None)),
+            proposed: None,
+            simulated: false,
         }
     }
 
+    /// A calculator for what-if analysis: it prices with `structures`
+    /// alone, ignores fee rules and writes nothing
+    pub fn simulated(pool: PgPool, structures: Vec<ProposedFeeStructure>) -> Self {
+        let proposed = structures
+            .into_iter()
+            .map(|s| FeeConfig {
+                id: Uuid::new_v4(),
+                transaction_type: s.transaction_type,
+                payment_provider: s.payment_provider,
+                payment_method: s.payment_method,
+                min_amount: s.min_amount,
+                max_amount: s.max_amount,
+                provider_fee_percent: s.provider_fee_percent,
+                provider_fee_flat: s.provider_fee_flat,
+                provider_fee_cap: s.provider_fee_cap,
+                platform_fee_percent: s.platform_fee_percent,
+            })
+            .collect();
+        Self {
+            proposed: Some(proposed),
+            simulated: true,
+            ..Self::new(pool)
+        }
+    }
+
+    /// Every live `fee_structures` tier, for simulating current pricing
+    /// without a lookup per transaction
+    pub async fn active_structures(&self) -> Result<Vec<ProposedFeeStructure>, DatabaseError> {
+        let configs = self.query_fee_configs(None, None, None).await?;
+        Ok(configs
+            .into_iter()
+            .map(|c| ProposedFeeStructure {
+                transaction_type: c.transaction_type,
+                payment_provider: c.payment_provider,
+                payment_method: c.payment_method,
+                min_amount: c.min_amount,
+                max_amount: c.max_amount,
+                provider_fee_percent: c.provider_fee_percent,
+                provider_fee_flat: c.provider_fee_flat,
+                provider_fee_cap: c.provider_fee_cap,
+                platform_fee_percent: c.platform_fee_percent,
+            })
+            .collect())
+    }
+
+    /// Fees for an anonymous estimate: only rules that need no wallet,
+    /// promo code or partner key apply, and no promo budget is spent. Callers
+    /// pricing for a customer use `calculate_fees_with_context`.
     pub async fn calculate_fees(
         &self,
         transaction_type: &str,
         amount: BigDecimal,
         provider: Option<&str>,
         payment_method: Option<&str>,
+    ) -> Result<FeeBreakdown, DatabaseError> {
+        self.calculate_fees_with_context(
+            transaction_type,
+            amount,
+            provider,
+            payment_method,
+            &FeeContext::default(),
+        )
+        .await
+    }
+
+    /// Calculate fees with promotions, volume discounts and partner pricing
+    /// from `fee_rules` applied to the platform fee
+    pub async fn calculate_fees_with_context(
+        &self,
+        transaction_type: &str,
+        amount: BigDecimal,
+        provider: Option<&str>,
+        payment_method: Option<&str>,
+        context: &FeeContext,
     ) -> Result<FeeBreakdown, DatabaseError> {
         let currency = "NGN".to_string();
 
@@ -160,6 +293,13 @@ impl FeeCalculationService {
             }
         };
 
+        let (platform_fee, applied_rules) = if self.simulated {
+            (platform_fee, Vec::new())
+        } else {
+            self.apply_fee_rules(transaction_type, &amount, platform_fee, context)
+                .await?
+        };
+
         let stellar_fee = self.calculate_stellar_fee().await;
 
         let total = provider_fee
@@ -185,16 +325,82 @@ impl FeeCalculationService {
             total: total.clone(),
             net_amount,
             effective_rate,
+            applied_rules,
         };
 
-        if let Some(config) = fee_config {
-            self.log_calculation(&breakdown, config.id, transaction_type)
-                .await?;
+        if !self.simulated && (fee_config.is_some() || !breakdown.applied_rules.is_empty()) {
+            self.log_calculation(
+                &breakdown,
+                fee_config.map(|config| config.id),
+                transaction_type,
+                context.transaction_id,
+            )
+            .await?;
         }
 
         Ok(breakdown)
     }
 
+    /// Apply the fee rules matching `context` to a platform fee priced
+    /// elsewhere, such as a quote's conversion fees. As with
+    /// `calculate_fees_with_context`, promo budgets are only spent when
+    /// `context.transaction_id` is set.
+    pub async fn apply_rules(
+        &self,
+        transaction_type: &str,
+        amount: &BigDecimal,
+        platform_fee: &BigDecimal,
+        context: &FeeContext,
+    ) -> Result<(BigDecimal, Vec<AppliedFeeRule>), DatabaseError> {
+        if self.simulated || amount.is_zero() {
+            return Ok((platform_fee.clone(), Vec::new()));
+        }
+        let fee = PlatformFee {
+            percent: platform_fee * BigDecimal::from(100) / amount,
+            calculated: platform_fee.clone(),
+        };
+        let (fee, applied) = self
+            .apply_fee_rules(transaction_type, amount, fee, context)
+            .await?;
+        Ok((fee.calculated, applied))
+    }
+
+    /// Spend the promo budget behind rules applied to a quote that is now
+    /// charged to a transaction. Returns false if the promo ran out since.
+    pub async fn charge_quoted_rules(
+        &self,
+        applied: &[AppliedFeeRule],
+    ) -> Result<bool, DatabaseError> {
+        let repo = FeeRuleRepository::new(self.pool.clone());
+        for rule in applied
+            .iter()
+            .filter(|r| r.rule_type == FeeRuleKind::PromoCode)
+        {
+            if !repo.consume_budget(rule.rule_id, &rule.discount).await? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Provider fee alone for `amount` under the tier matching `provider`
+    /// and `payment_method`. Nothing is logged and no rules run; `None` when
+    /// no tier prices the provider.
+    pub async fn provider_fee(
+        &self,
+        transaction_type: &str,
+        amount: &BigDecimal,
+        provider: &str,
+        payment_method: Option<&str>,
+    ) -> Result<Option<ProviderFee>, DatabaseError> {
+        let fee_config = self
+            .find_matching_tier(transaction_type, amount, Some(provider), payment_method)
+            .await?;
+        Ok(fee_config.and_then(|config| {
+            self.calculate_provider_fee(amount, &config, Some(provider), payment_method)
+        }))
+    }
+
     pub async fn estimate_fees(
         &self,
         transaction_type: &str,
@@ -271,6 +477,80 @@ impl FeeCalculationService {
         Ok(None)
     }
 
+    /// Run the fee rules that match the context over the tier's platform
+    /// fee. For charged transactions the promo's budget is spent here; a promo
+    /// whose budget ran out in the meantime is dropped and the rules re-run.
+    async fn apply_fee_rules(
+        &self,
+        transaction_type: &str,
+        amount: &BigDecimal,
+        platform_fee: PlatformFee,
+        context: &FeeContext,
+    ) -> Result<(PlatformFee, Vec<AppliedFeeRule>), DatabaseError> {
+        let repo = FeeRuleRepository::new(self.pool.clone());
+        let partner_key_hash = context
+            .partner_api_key
+            .as_deref()
+            .map(fee_rules::hash_partner_key);
+        let mut rules = repo
+            .list_candidates(
+                transaction_type,
+                context.promo_code.as_deref(),
+                partner_key_hash.as_deref(),
+            )
+            .await?;
+        if rules.is_empty() {
+            return Ok((platform_fee, Vec::new()));
+        }
+
+        let needs_activity = rules.iter().any(|r| {
+            matches!(
+                FeeRuleKind::parse(&r.rule_type),
+                Some(FeeRuleKind::FirstNFree | FeeRuleKind::VolumeDiscount)
+            )
+        });
+        let activity = match (&context.wallet_address, needs_activity) {
+            (Some(wallet), true) => {
+                let month_start = chrono::Utc::now()
+                    .date_naive()
+                    .with_day(1)
+                    .and_then(|d| d.and_hms_opt(0, 0, 0))
+                    .map(|d| d.and_utc())
+                    .unwrap_or_else(chrono::Utc::now);
+                Some(repo.wallet_activity(wallet, month_start).await?)
+            }
+            _ => None,
+        };
+
+        let evaluation = loop {
+            let evaluation =
+                fee_rules::evaluate(&rules, amount, &platform_fee.percent, activity.as_ref());
+            let promo = evaluation
+                .applied
+                .iter()
+                .find(|a| a.rule_type == FeeRuleKind::PromoCode);
+            match (context.transaction_id, promo) {
+                (Some(_), Some(promo)) => {
+                    if repo.consume_budget(promo.rule_id, &promo.discount).await? {
+                        break evaluation;
+                    }
+                    let exhausted = promo.rule_id;
+                    info!(rule_id = %exhausted, "promo budget exhausted, re-evaluating fee rules");
+                    rules.retain(|r| r.id != exhausted);
+                }
+                _ => break evaluation,
+            }
+        };
+
+        Ok((
+            PlatformFee {
+                percent: evaluation.platform_fee_percent,
+                calculated: evaluation.platform_fee,
+            },
+            evaluation.applied,
+        ))
+    }
+
     fn amount_in_range(
         &self,
         amount: &BigDecimal,
@@ -287,13 +567,33 @@ impl FeeCalculationService {
         transaction_type: &str,
         provider: Option<&str>,
         payment_method: Option<&str>,
+    ) -> Result<Vec<FeeConfig>, DatabaseError> {
+        if let Some(proposed) = &self.proposed {
+            return Ok(matching_configs(
+                proposed,
+                transaction_type,
+                provider,
+                payment_method,
+            ));
+        }
+
+        self.query_fee_configs(Some(transaction_type), provider, payment_method)
+            .await
+    }
+
+    /// Active tiers matching the filters; no `transaction_type` loads them all
+    async fn query_fee_configs(
+        &self,
+        transaction_type: Option<&str>,
+        provider: Option<&str>,
+        payment_method: Option<&str>,
     ) -> Result<Vec<FeeConfig>, DatabaseError> {
         let query = r#"
             SELECT id, transaction_type, payment_provider, payment_method,
                    min_amount, max_amount, provider_fee_percent, provider_fee_flat,
                    provider_fee_cap, platform_fee_percent
             FROM fee_structures
-            WHERE transaction_type = $1
+            WHERE ($1::TEXT IS NULL OR transaction_type = $1)
               AND is_active = TRUE
               AND effective_from <= NOW()
               AND (effective_until IS NULL OR effective_until >= NOW())
@@ -426,17 +726,23 @@ impl FeeCalculationService {
     async fn log_calculation(
         &self,
         breakdown: &FeeBreakdown,
-        fee_structure_id: Uuid,
+        fee_structure_id: Option<Uuid>,
         transaction_type: &str,
+        transaction_id: Option<Uuid>,
     ) -> Result<(), DatabaseError> {
         let query = r#"
             INSERT INTO fee_calculation_logs 
             (transaction_type, amount, currency, payment_provider, payment_method,
              fee_structure_id, provider_fee, platform_fee, stellar_fee_xlm, 
-             stellar_fee_ngn, total_fees, net_amount, effective_rate)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+             stellar_fee_ngn, total_fees, net_amount, effective_rate,
+             transaction_id, applied_rule_ids, calculation_metadata)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
         "#;
 
+        let applied_rule_ids: Vec<Uuid> =
+            breakdown.applied_rules.iter().map(|r| r.rule_id).collect();
+        let metadata = serde_json::json!({ "applied_rules": breakdown.applied_rules });
+
         sqlx::query(query)
             .bind(transaction_type)
             .bind(&breakdown.amount)
@@ -457,6 +763,9 @@ impl FeeCalculationService {
             .bind(&breakdown.total)
             .bind(&breakdown.net_amount)
             .bind(&breakdown.effective_rate)
+            .bind(transaction_id)
+            .bind(applied_rule_ids)
+            .bind(metadata)
             .execute(&self.pool)
             .await
             .map_err(DatabaseError::from_sqlx)?;
@@ -471,6 +780,32 @@ impl FeeCalculationService {
     }
 }
 
+/// In-memory equivalent of the tier lookup in `load_fee_configs`
+fn matching_configs(
+    configs: &[FeeConfig],
+    transaction_type: &str,
+    provider: Option<&str>,
+    payment_method: Option<&str>,
+) -> Vec<FeeConfig> {
+    fn matches(filter: Option<&str>, value: &Option<String>) -> bool {
+        match (filter, value) {
+            (Some(filter), Some(value)) => filter == value,
+            _ => true,
+        }
+    }
+
+    let mut matching: Vec<FeeConfig> = configs
+        .iter()
+        .filter(|c| c.transaction_type == transaction_type)
+        .filter(|c| matches(provider, &c.payment_provider))
+        .filter(|c| matches(payment_method, &c.payment_method))
+        .cloned()
+        .collect();
+    // NULL min_amount sorts first, as in the SQL lookup
+    matching.sort_by(|a, b| a.min_amount.cmp(&b.min_amount));
+    matching
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -513,9 +848,37 @@ mod tests {
             total: BigDecimal::from_str("1700").unwrap(),
             net_amount: BigDecimal::from_str("98300").unwrap(),
             effective_rate: BigDecimal::from_str("1.7").unwrap(),
+            applied_rules: Vec::new(),
         };
 
         let json = serde_json::to_string(&breakdown).unwrap();
         assert!(json.contains("flutterwave"));
     }
+
+    #[test]
+    fn test_matching_configs_follows_tier_lookup() {
+        let tier = |provider: Option<&str>, min: Option<&str>| FeeConfig {
+            id: Uuid::new_v4(),
+            transaction_type: "onramp".to_string(),
+            payment_provider: provider.map(str::to_string),
+            payment_method: None,
+            min_amount: min.map(|m| BigDecimal::from_str(m).unwrap()),
+            max_amount: None,
+            provider_fee_percent: None,
+            provider_fee_flat: None,
+            provider_fee_cap: None,
+            platform_fee_percent: None,
+        };
+        let configs = vec![
+            tier(Some("paystack"), Some("50000")),
+            tier(None, Some("1000")),
+            tier(Some("flutterwave"), None),
+        ];
+
+        let matching = matching_configs(&configs, "onramp", Some("paystack"), Some("card"));
+        assert_eq!(matching.len(), 2);
+        assert_eq!(matching[0].min_amount, Some(BigDecimal::from(1000)));
+        assert!(matching_configs(&configs, "offramp", None, None).is_empty());
+        assert_eq!(matching_configs(&configs, "onramp", None, None).len(), 3);
+    }
 }
@@ -0,0 +1,279 @@
+//! Fee rules engine
+//!
+//! Evaluates promotions, first-N-free offers, monthly volume discounts and
+//! partner overrides against the platform fee of a matched fee tier. Rules
+//! run highest priority first; a non-stackable rule only applies on its own.
+
+use crate::database::fee_rule_repository::{FeeRule, WalletFeeActivity};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::types::BigDecimal;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeRuleKind {
+    PromoCode,
+    FirstNFree,
+    VolumeDiscount,
+    PartnerOverride,
+}
+
+impl FeeRuleKind {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "promo_code" => Some(Self::PromoCode),
+            "first_n_free" => Some(Self::FirstNFree),
+            "volume_discount" => Some(Self::VolumeDiscount),
+            "partner_override" => Some(Self::PartnerOverride),
+            _ => None,
+        }
+    }
+}
+
+/// Who is being charged, for rules that depend on it
+#[derive(Debug, Clone, Default)]
+pub struct FeeContext {
+    pub wallet_address: Option<String>,
+    pub promo_code: Option<String>,
+    pub partner_api_key: Option<String>,
+    /// Set when the fee is actually charged; promo budgets are only spent
+    /// for charged transactions, never for estimates
+    pub transaction_id: Option<Uuid>,
+}
+
+/// A rule that changed the platform fee
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppliedFeeRule {
+    pub rule_id: Uuid,
+    pub name: String,
+    pub rule_type: FeeRuleKind,
+    /// Platform fee removed by the rule; negative when a partner override
+    /// raises the fee
+    pub discount: BigDecimal,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleEvaluation {
+    pub platform_fee_percent: BigDecimal,
+    pub platform_fee: BigDecimal,
+    pub applied: Vec<AppliedFeeRule>,
+}
+
+/// Hex SHA-256 of a partner API key, as stored in `fee_rules`
+pub fn hash_partner_key(api_key: &str) -> String {
+    hex::encode(Sha256::digest(api_key.trim().as_bytes()))
+}
+
+/// Apply `rules` (already ordered by priority) to a platform fee of
+/// `platform_fee_percent` on `amount`
+pub fn evaluate(
+    rules: &[FeeRule],
+    amount: &BigDecimal,
+    platform_fee_percent: &BigDecimal,
+    activity: Option<&WalletFeeActivity>,
+) -> RuleEvaluation {
+    let hundred = BigDecimal::from(100);
+    let mut percent = platform_fee_percent.clone();
+    let mut fee = amount * &percent / &hundred;
+    let mut applied: Vec<AppliedFeeRule> = Vec::new();
+
+    for rule in rules {
+        if !applied.is_empty() && !rule.stackable {
+            continue;
+        }
+        let Some(kind) = FeeRuleKind::parse(&rule.rule_type) else {
+            continue;
+        };
+
+        let discount = match kind {
+            FeeRuleKind::PartnerOverride => match &rule.platform_fee_percent {
+                Some(override_percent) => {
+                    let overridden = amount * override_percent / &hundred;
+                    let discount = &fee - &overridden;
+                    percent = override_percent.clone();
+                    discount
+                }
+                None => continue,
+            },
+            FeeRuleKind::PromoCode => {
+                let mut discount = percent_of(&fee, rule.discount_percent.as_ref())
+                    + rule
+                        .discount_flat
+                        .clone()
+                        .unwrap_or_else(|| BigDecimal::from(0));
+                if let Some(budget) = &rule.budget_total {
+                    let remaining = budget - &rule.budget_used;
+                    if discount > remaining {
+                        discount = remaining;
+                    }
+                }
+                discount
+            }
+            FeeRuleKind::FirstNFree => match (activity, rule.free_transactions) {
+                (Some(activity), Some(free))
+                    if activity.completed_transactions < i64::from(free) =>
+                {
+                    fee.clone()
+                }
+                _ => continue,
+            },
+            FeeRuleKind::VolumeDiscount => match (activity, &rule.min_monthly_volume) {
+                (Some(activity), Some(min)) if activity.monthly_volume >= *min => {
+                    percent_of(&fee, rule.discount_percent.as_ref())
+                }
+                _ => continue,
+            },
+        };
+
+        // Discounts never take the fee below zero
+        let discount = if discount > fee {
+            fee.clone()
+        } else {
+            discount
+        };
+        if kind != FeeRuleKind::PartnerOverride && discount <= 0 {
+            continue;
+        }
+
+        fee = &fee - &discount;
+        applied.push(AppliedFeeRule {
+            rule_id: rule.id,
+            name: rule.name.clone(),
+            rule_type: kind,
+            discount,
+        });
+        if !rule.stackable {
+            break;
+        }
+    }
+
+    RuleEvaluation {
+        platform_fee_percent: percent,
+        platform_fee: fee,
+        applied,
+    }
+}
+
+fn percent_of(amount: &BigDecimal, percent: Option<&BigDecimal>) -> BigDecimal {
+    percent
+        .map(|p| amount * p / BigDecimal::from(100))
+        .unwrap_or_else(|| BigDecimal::from(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn rule(rule_type: &str, priority: i32, stackable: bool) -> FeeRule {
+        let now = chrono::Utc::now();
+        FeeRule {
+            id: Uuid::new_v4(),
+            name: rule_type.to_string(),
+            rule_type: rule_type.to_string(),
+            transaction_type: None,
+            priority,
+            stackable,
+            promo_code: None,
+            partner_key_hash: None,
+            free_transactions: None,
+            min_monthly_volume: None,
+            discount_percent: None,
+            discount_flat: None,
+            platform_fee_percent: None,
+            budget_total: None,
+            budget_used: BigDecimal::from(0),
+            starts_at: now,
+            expires_at: None,
+            is_active: true,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn dec(s: &str) -> BigDecimal {
+        BigDecimal::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_stacking_and_priority() {
+        let activity = WalletFeeActivity {
+            completed_transactions: 10,
+            monthly_volume: dec("2000000"),
+        };
+        let mut partner = rule("partner_override", 30, true);
+        partner.platform_fee_percent = Some(dec("0.5"));
+        let mut volume = rule("volume_discount", 20, true);
+        volume.min_monthly_volume = Some(dec("1000000"));
+        volume.discount_percent = Some(dec("20"));
+        let mut promo = rule("promo_code", 10, false);
+        promo.discount_percent = Some(dec("50"));
+
+        // 1% of 100_000 = 1000; partner -> 500; volume 20% -> 400; the
+        // non-stackable promo is skipped because rules already applied
+        let result = evaluate(
+            &[partner, volume, promo],
+            &dec("100000"),
+            &dec("1"),
+            Some(&activity),
+        );
+        assert_eq!(result.platform_fee, dec("400"));
+        assert_eq!(result.platform_fee_percent, dec("0.5"));
+        assert_eq!(result.applied.len(), 2);
+        assert_eq!(result.applied[0].discount, dec("500"));
+        assert_eq!(result.applied[1].rule_type, FeeRuleKind::VolumeDiscount);
+    }
+
+    #[test]
+    fn test_non_stackable_rule_applies_alone() {
+        let activity = WalletFeeActivity {
+            completed_transactions: 2,
+            monthly_volume: dec("0"),
+        };
+        let mut first_free = rule("first_n_free", 20, false);
+        first_free.free_transactions = Some(3);
+        let mut promo = rule("promo_code", 10, true);
+        promo.discount_flat = Some(dec("100"));
+
+        let result = evaluate(
+            &[first_free.clone(), promo.clone()],
+            &dec("50000"),
+            &dec("1"),
+            Some(&activity),
+        );
+        assert_eq!(result.platform_fee, dec("0"));
+        assert_eq!(result.applied.len(), 1);
+
+        // Once the wallet has used its free transactions the promo applies
+        let used = WalletFeeActivity {
+            completed_transactions: 3,
+            monthly_volume: dec("0"),
+        };
+        let result = evaluate(&[first_free, promo], &dec("50000"), &dec("1"), Some(&used));
+        assert_eq!(result.platform_fee, dec("400"));
+        assert_eq!(result.applied[0].rule_type, FeeRuleKind::PromoCode);
+    }
+
+    #[test]
+    fn test_promo_capped_by_remaining_budget() {
+        let mut promo = rule("promo_code", 0, false);
+        promo.discount_percent = Some(dec("100"));
+        promo.budget_total = Some(dec("1000"));
+        promo.budget_used = dec("900");
+
+        let result = evaluate(&[promo.clone()], &dec("50000"), &dec("1"), None);
+        assert_eq!(result.platform_fee, dec("400"));
+
+        promo.budget_used = dec("1000");
+        let result = evaluate(&[promo], &dec("50000"), &dec("1"), None);
+        assert!(result.applied.is_empty());
+        assert_eq!(result.platform_fee, dec("500"));
+    }
+
+    #[test]
+    fn test_hash_partner_key() {
+        assert_eq!(hash_partner_key(" key "), hash_partner_key("key"));
+        assert_eq!(hash_partner_key("key").len(), 64);
+    }
+}
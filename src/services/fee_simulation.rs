@@ -0,0 +1,255 @@
+//! Fee simulation
+//!
+//! Replays recent completed transactions through the fee calculator twice —
+//! once with the live `fee_structures`, once with a proposed set — and
+//! reports the platform revenue delta. Both sets of tiers are loaded once
+//! up front. Nothing is written: live config, fee rules and calculation logs
+//! are left untouched.
+
+use crate::database::transaction_repository::TransactionRepository;
+use crate::error::{AppError, AppErrorKind, ValidationError};
+use crate::services::fee_calculation::{FeeCalculationService, ProposedFeeStructure};
+use serde::{Deserialize, Serialize};
+use sqlx::types::BigDecimal;
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use tracing::info;
+
+const DEFAULT_WINDOW_DAYS: i64 = 30;
+const MAX_WINDOW_DAYS: i64 = 365;
+const MAX_TRANSACTIONS: i64 = 50_000;
+
+/// Upper bounds (NGN) of the default amount bands
+const DEFAULT_AMOUNT_BANDS: [&str; 3] = ["10000", "100000", "1000000"];
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FeeSimulationRequest {
+    /// How many days of history to replay (default 30)
+    #[serde(default)]
+    pub days: Option<i64>,
+    /// The complete proposed set of fee structures
+    pub fee_structures: Vec<ProposedFeeStructure>,
+    /// Ascending amount band boundaries; defaults to 10k / 100k / 1M
+    #[serde(default)]
+    pub amount_bands: Vec<BigDecimal>,
+}
+
+/// Platform revenue under current and proposed pricing for one group
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RevenueDelta {
+    pub key: String,
+    pub transactions: u64,
+    pub current_revenue: BigDecimal,
+    pub proposed_revenue: BigDecimal,
+    pub delta: BigDecimal,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FeeSimulationReport {
+    pub from: chrono::DateTime<chrono::Utc>,
+    pub to: chrono::DateTime<chrono::Utc>,
+    pub transactions: u64,
+    /// The window held more transactions than are replayed in one run
+    pub truncated: bool,
+    pub total: RevenueDelta,
+    pub by_transaction_type: Vec<RevenueDelta>,
+    pub by_provider: Vec<RevenueDelta>,
+    pub by_amount_band: Vec<RevenueDelta>,
+}
+
+/// Running revenue totals per group key
+#[derive(Debug, Default)]
+struct RevenueTally(BTreeMap<String, RevenueDelta>);
+
+impl RevenueTally {
+    fn add(&mut self, key: &str, current: &BigDecimal, proposed: &BigDecimal) {
+        let entry = self
+            .0
+            .entry(key.to_string())
+            .or_insert_with(|| RevenueDelta {
+                key: key.to_string(),
+                transactions: 0,
+                current_revenue: BigDecimal::from(0),
+                proposed_revenue: BigDecimal::from(0),
+                delta: BigDecimal::from(0),
+            });
+        entry.transactions += 1;
+        entry.current_revenue += current;
+        entry.proposed_revenue += proposed;
+        entry.delta = &entry.proposed_revenue - &entry.current_revenue;
+    }
+
+    fn into_rows(self) -> Vec<RevenueDelta> {
+        self.0.into_values().collect()
+    }
+}
+
+/// Label of the band `amount` falls in, e.g. `10000-100000` or `1000000+`
+pub fn amount_band(amount: &BigDecimal, bounds: &[BigDecimal]) -> String {
+    let mut lower = BigDecimal::from(0);
+    for upper in bounds {
+        if amount < upper {
+            return format!("{}-{}", lower, upper);
+        }
+        lower = upper.clone();
+    }
+    format!("{}+", lower)
+}
+
+pub struct FeeSimulationService {
+    pool: PgPool,
+}
+
+impl FeeSimulationService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn simulate(
+        &self,
+        request: FeeSimulationRequest,
+    ) -> Result<FeeSimulationReport, AppError> {
+        let days = request.days.unwrap_or(DEFAULT_WINDOW_DAYS);
+        if !(1..=MAX_WINDOW_DAYS).contains(&days) {
+            return Err(AppError::new(AppErrorKind::Validation(
+                ValidationError::OutOfRange {
+                    field: "days".to_string(),
+                    min: Some("1".to_string()),
+                    max: Some(MAX_WINDOW_DAYS.to_string()),
+                },
+            )));
+        }
+        if request.fee_structures.is_empty() {
+            return Err(AppError::new(AppErrorKind::Validation(
+                ValidationError::MissingField {
+                    field: "fee_structures".to_string(),
+                },
+            )));
+        }
+        for structure in &request.fee_structures {
+            structure.validate().map_err(|reason| {
+                AppError::new(AppErrorKind::Validation(ValidationError::InvalidAmount {
+                    amount: String::new(),
+                    reason,
+                }))
+            })?;
+        }
+
+        let bands: Vec<BigDecimal> = if request.amount_bands.is_empty() {
+            DEFAULT_AMOUNT_BANDS
+                .iter()
+                .map(|b| BigDecimal::from_str(b).expect("valid band"))
+                .collect()
+        } else {
+            request.amount_bands.clone()
+        };
+        if bands.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(AppError::new(AppErrorKind::Validation(
+                ValidationError::InvalidAmount {
+                    amount: String::new(),
+                    reason: "amount_bands must be strictly ascending".to_string(),
+                },
+            )));
+        }
+
+        let to = chrono::Utc::now();
+        let from = to - chrono::Duration::days(days);
+        let mut transactions = TransactionRepository::new(self.pool.clone())
+            .find_completed_since(from, MAX_TRANSACTIONS + 1)
+            .await?;
+        let truncated = transactions.len() as i64 > MAX_TRANSACTIONS;
+        transactions.truncate(MAX_TRANSACTIONS as usize);
+
+        // Both sides price from tiers held in memory, so the replay does no
+        // lookups per transaction
+        let live = FeeCalculationService::new(self.pool.clone())
+            .active_structures()
+            .await?;
+        let current = FeeCalculationService::simulated(self.pool.clone(), live);
+        let proposed = FeeCalculationService::simulated(self.pool.clone(), request.fee_structures);
+
+        let mut total = RevenueTally::default();
+        let mut by_type = RevenueTally::default();
+        let mut by_provider = RevenueTally::default();
+        let mut by_band = RevenueTally::default();
+        for tx in &transactions {
+            let provider = tx.payment_provider.as_deref();
+            let method = tx.payment_method();
+            let before = current
+                .calculate_fees(&tx.r#type, tx.from_amount.clone(), provider, method)
+                .await?;
+            let after = proposed
+                .calculate_fees(&tx.r#type, tx.from_amount.clone(), provider, method)
+                .await?;
+            let (before, after) = (&before.platform.calculated, &after.platform.calculated);
+
+            total.add("total", before, after);
+            by_type.add(&tx.r#type, before, after);
+            by_provider.add(provider.unwrap_or("none"), before, after);
+            by_band.add(&amount_band(&tx.from_amount, &bands), before, after);
+        }
+
+        let total = total.into_rows().pop().unwrap_or_else(|| RevenueDelta {
+            key: "total".to_string(),
+            transactions: 0,
+            current_revenue: BigDecimal::from(0),
+            proposed_revenue: BigDecimal::from(0),
+            delta: BigDecimal::from(0),
+        });
+        info!(
+            days,
+            transactions = total.transactions,
+            delta = %total.delta,
+            "fee simulation finished"
+        );
+
+        Ok(FeeSimulationReport {
+            from,
+            to,
+            transactions: total.transactions,
+            truncated,
+            total,
+            by_transaction_type: by_type.into_rows(),
+            by_provider: by_provider.into_rows(),
+            by_amount_band: by_band.into_rows(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_amount_band_labels() {
+        let bounds: Vec<BigDecimal> = DEFAULT_AMOUNT_BANDS
+            .iter()
+            .map(|b| BigDecimal::from_str(b).unwrap())
+            .collect();
+        assert_eq!(amount_band(&BigDecimal::from(5000), &bounds), "0-10000");
+        assert_eq!(
+            amount_band(&BigDecimal::from(10000), &bounds),
+            "10000-100000"
+        );
+        assert_eq!(
+            amount_band(&BigDecimal::from(2_000_000), &bounds),
+            "1000000+"
+        );
+    }
+
+    #[test]
+    fn test_revenue_tally_accumulates_delta() {
+        let mut tally = RevenueTally::default();
+        tally.add("onramp", &BigDecimal::from(100), &BigDecimal::from(150));
+        tally.add("onramp", &BigDecimal::from(200), &BigDecimal::from(180));
+        tally.add("offramp", &BigDecimal::from(50), &BigDecimal::from(50));
+
+        let rows = tally.into_rows();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].key, "offramp");
+        assert_eq!(rows[1].transactions, 2);
+        assert_eq!(rows[1].current_revenue, BigDecimal::from(300));
+        assert_eq!(rows[1].delta, BigDecimal::from(30));
+    }
+}
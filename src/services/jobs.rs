@@ -0,0 +1,734 @@
+//! Durable job queue
+//!
+//! Background work runs as rows in the `jobs` table rather than in
+//! hand-rolled polling loops. A job has a kind, a typed JSON payload, a
+//! `run_at` time and an attempt budget; runners lease due jobs, keep the
+//! lease alive by heartbeat while a handler runs, and on failure queue the
+//! job again with exponential backoff until it runs out of attempts and is
+//! buried as `dead`. A unique key keeps a piece of work from being queued
+//! twice. Recurring work is declared as a schedule (`@every 10s` or a cron
+//! expression) that enqueues one job per run, never overlapping the last.
+
+use crate::database::job_repository::{
+    Job, JobCount, JobRepository, JobSchedule, NewJob, NewJobSchedule,
+};
+use crate::error::{AppError, AppErrorKind, DomainError, InfrastructureError};
+use crate::services::distributed_lock::instance_id;
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, TimeZone, Timelike, Utc};
+use serde::{de::DeserializeOwned, Serialize};
+use sqlx::PgPool;
+use std::time::Duration;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct JobsConfig {
+    /// Job runners working the queue concurrently
+    pub workers: usize,
+    /// How long an idle runner waits before polling again
+    pub poll_interval: Duration,
+    /// Jobs claimed per poll
+    pub batch_size: i64,
+    /// How long a claimed job is held without a heartbeat before another
+    /// runner may take it
+    pub lease: Duration,
+    /// How often a running job's lease is extended
+    pub heartbeat_interval: Duration,
+    /// Attempts for jobs enqueued without their own budget
+    pub max_attempts: i32,
+    /// Delay after the first failure for jobs enqueued without their own,
+    /// doubled for each further one
+    pub base_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Prefix of the runner IDs recorded on leased jobs
+    pub worker_id: String,
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        Self {
+            workers: 2,
+            poll_interval: Duration::from_secs(1),
+            batch_size: 10,
+            lease: Duration::from_secs(60),
+            heartbeat_interval: Duration::from_secs(20),
+            max_attempts: 5,
+            base_backoff: Duration::from_secs(10),
+            max_backoff: Duration::from_secs(3600),
+            worker_id: instance_id(),
+        }
+    }
+}
+
+impl JobsConfig {
+    pub fn from_env() -> Self {
+        let mut cfg = Self::default();
+        let var = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<u64>().ok());
+        if let Some(workers) = var("JOBS_WORKERS").filter(|v| *v > 0) {
+            cfg.workers = workers as usize;
+        }
+        if let Some(ms) = var("JOBS_POLL_INTERVAL_MS") {
+            cfg.poll_interval = Duration::from_millis(ms);
+        }
+        if let Some(batch_size) = var("JOBS_BATCH_SIZE").filter(|v| *v > 0) {
+            cfg.batch_size = batch_size as i64;
+        }
+        if let Some(secs) = var("JOBS_LEASE_SECONDS").filter(|v| *v > 0) {
+            cfg.lease = Duration::from_secs(secs);
+        }
+        if let Some(secs) = var("JOBS_HEARTBEAT_SECONDS").filter(|v| *v > 0) {
+            cfg.heartbeat_interval = Duration::from_secs(secs);
+        }
+        if let Some(max_attempts) = var("JOBS_MAX_ATTEMPTS").filter(|v| *v > 0) {
+            cfg.max_attempts = max_attempts as i32;
+        }
+        if let Some(secs) = var("JOBS_BASE_BACKOFF_SECONDS") {
+            cfg.base_backoff = Duration::from_secs(secs);
+        }
+        if let Some(secs) = var("JOBS_MAX_BACKOFF_SECONDS") {
+            cfg.max_backoff = Duration::from_secs(secs);
+        }
+        if let Ok(worker_id) = std::env::var("JOBS_WORKER_ID") {
+            if !worker_id.trim().is_empty() {
+                cfg.worker_id = worker_id.trim().to_string();
+            }
+        }
+        cfg
+    }
+}
+
+/// A typed job payload
+pub trait JobPayload: Serialize + DeserializeOwned + Send + Sync {
+    /// Job kind, e.g. `offramp.process`
+    const KIND: &'static str;
+}
+
+/// How a job is enqueued; unset fields fall back to [`JobsConfig`]
+#[derive(Debug, Clone, Default)]
+pub struct JobOptions {
+    pub run_at: Option<DateTime<Utc>>,
+    pub max_attempts: Option<i32>,
+    pub backoff: Option<Duration>,
+    pub unique_key: Option<String>,
+}
+
+impl JobOptions {
+    /// Skip the job while a queued or running job holds `key`
+    pub fn with_unique_key(mut self, key: impl Into<String>) -> Self {
+        self.unique_key = Some(key.into());
+        self
+    }
+
+    pub fn with_run_at(mut self, run_at: DateTime<Utc>) -> Self {
+        self.run_at = Some(run_at);
+        self
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: i32) -> Self {
+        self.max_attempts = Some(max_attempts.max(1));
+        self
+    }
+
+    /// Delay after the first failure, doubled for each further one
+    pub fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = Some(backoff);
+        self
+    }
+}
+
+/// What a handler did with a job
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobOutcome {
+    Done,
+    /// Not ready yet; run again after the delay without using an attempt
+    Snooze(Duration),
+}
+
+/// Runs jobs of one kind. Returning an error uses up the attempt and queues
+/// the job again with backoff, or buries it on its last attempt.
+#[async_trait]
+pub trait JobHandler: Send + Sync {
+    fn kind(&self) -> &'static str;
+
+    async fn handle(&self, job: &Job) -> anyhow::Result<JobOutcome>;
+}
+
+/// Delay before retrying a job that has now failed `attempts` times
+pub fn backoff_delay(attempts: i32, base: Duration, max: Duration) -> Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 20) as u32;
+    base.saturating_mul(2u32.saturating_pow(exponent)).min(max)
+}
+
+// ---------------------------------------------------------------------------
+// Schedules
+// ---------------------------------------------------------------------------
+
+/// When a recurring job runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Schedule {
+    /// `@every 30s`: a fixed delay after each enqueue
+    Every(Duration),
+    /// Five-field cron expression (minute hour day-of-month month
+    /// day-of-week), evaluated in UTC
+    Cron(CronExpr),
+}
+
+impl Schedule {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let spec = spec.trim();
+        match spec {
+            "@hourly" => return Self::parse("0 * * * *"),
+            "@daily" | "@midnight" => return Self::parse("0 0 * * *"),
+            "@weekly" => return Self::parse("0 0 * * 0"),
+            "@monthly" => return Self::parse("0 0 1 * *"),
+            _ => {}
+        }
+        if let Some(every) = spec.strip_prefix("@every") {
+            return parse_every(every.trim()).map(Schedule::Every);
+        }
+        CronExpr::parse(spec).map(Schedule::Cron)
+    }
+
+    /// The first run strictly after `after`
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Schedule::Every(every) => Some(after + chrono::Duration::from_std(*every).ok()?),
+            Schedule::Cron(cron) => cron.next_after(after),
+        }
+    }
+}
+
+fn parse_every(value: &str) -> Result<Duration, String> {
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("missing unit in '@every {}'", value))?;
+    let (count, unit) = value.split_at(split);
+    let count: u64 = count
+        .parse()
+        .map_err(|_| format!("invalid interval '@every {}'", value))?;
+    let secs = match unit {
+        "s" => count,
+        "m" => count * 60,
+        "h" => count * 3600,
+        _ => return Err(format!("unknown unit '{}' in '@every {}'", unit, value)),
+    };
+    if secs == 0 {
+        return Err("interval must be at least one second".to_string());
+    }
+    Ok(Duration::from_secs(secs))
+}
+
+/// A parsed cron expression; each field is a bit set of allowed values
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronExpr {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    /// Day-of-month and day-of-week were both restricted, in which case a
+    /// day matching either runs, as in cron
+    either_day: bool,
+}
+
+impl CronExpr {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = spec.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!("cron expression '{}' must have five fields", spec));
+        }
+        let mut days_of_week = parse_cron_field(fields[4], 0, 7)?;
+        // 7 is another name for Sunday
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            minutes: parse_cron_field(fields[0], 0, 59)?,
+            hours: parse_cron_field(fields[1], 0, 23)?,
+            days_of_month: parse_cron_field(fields[2], 1, 31)?,
+            months: parse_cron_field(fields[3], 1, 12)?,
+            days_of_week,
+            either_day: fields[2] != "*" && fields[4] != "*",
+        })
+    }
+
+    fn day_matches(&self, at: &DateTime<Utc>) -> bool {
+        let dom = self.days_of_month & (1 << at.day()) != 0;
+        let dow = self.days_of_week & (1 << at.weekday().num_days_from_sunday()) != 0;
+        if self.either_day {
+            dom || dow
+        } else {
+            dom && dow
+        }
+    }
+
+    /// The first matching minute strictly after `after`, searching up to
+    /// five years ahead
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut at = after.with_second(0)?.with_nanosecond(0)? + chrono::Duration::minutes(1);
+        let limit = at + chrono::Duration::days(5 * 366);
+
+        while at < limit {
+            if self.months & (1 << at.month()) == 0 {
+                let (year, month) = if at.month() == 12 {
+                    (at.year() + 1, 1)
+                } else {
+                    (at.year(), at.month() + 1)
+                };
+                at = Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).single()?;
+                continue;
+            }
+            if !self.day_matches(&at) {
+                at = at.date_naive().succ_opt()?.and_hms_opt(0, 0, 0)?.and_utc();
+                continue;
+            }
+            if self.hours & (1 << at.hour()) == 0 {
+                at = at.with_minute(0)? + chrono::Duration::hours(1);
+                continue;
+            }
+            if self.minutes & (1 << at.minute()) == 0 {
+                at += chrono::Duration::minutes(1);
+                continue;
+            }
+            return Some(at);
+        }
+        None
+    }
+}
+
+/// Parse one cron field (`*`, `5`, `1-5`, `*/15`, `10-50/10`, or a comma
+/// list of those) into a bit set
+fn parse_cron_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .map_err(|_| format!("invalid step in cron field '{}'", field))?;
+                if step == 0 {
+                    return Err(format!("zero step in cron field '{}'", field));
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (
+                parse_cron_value(start, field)?,
+                parse_cron_value(end, field)?,
+            )
+        } else {
+            let value = parse_cron_value(range, field)?;
+            // `5/10` means from 5 to the end in steps of 10
+            (value, if step > 1 { max } else { value })
+        };
+        if start < min || end > max || start > end {
+            return Err(format!("cron field '{}' is outside {}-{}", field, min, max));
+        }
+        let mut value = start;
+        while value <= end {
+            bits |= 1 << value;
+            value += step;
+        }
+    }
+    Ok(bits)
+}
+
+fn parse_cron_value(value: &str, field: &str) -> Result<u32, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value '{}' in cron field '{}'", value, field))
+}
+
+// ---------------------------------------------------------------------------
+// Queue
+// ---------------------------------------------------------------------------
+
+pub struct JobQueue {
+    repo: JobRepository,
+    config: JobsConfig,
+}
+
+impl JobQueue {
+    pub fn new(pool: PgPool, config: JobsConfig) -> Self {
+        Self {
+            repo: JobRepository::new(pool),
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &JobsConfig {
+        &self.config
+    }
+
+    /// Queue a job. Returns `None` if a queued or running job already holds
+    /// the options' unique key.
+    pub async fn enqueue<J: JobPayload>(
+        &self,
+        job: &J,
+        options: JobOptions,
+    ) -> Result<Option<Job>, AppError> {
+        let payload = serde_json::to_value(job).map_err(|e| {
+            AppError::new(AppErrorKind::Infrastructure(
+                InfrastructureError::Configuration {
+                    message: format!("failed to serialize {} job: {}", J::KIND, e),
+                },
+            ))
+        })?;
+        let new_job = NewJob {
+            kind: J::KIND.to_string(),
+            payload,
+            max_attempts: options.max_attempts.unwrap_or(self.config.max_attempts),
+            backoff_seconds: options
+                .backoff
+                .unwrap_or(self.config.base_backoff)
+                .as_secs()
+                .min(i32::MAX as u64) as i32,
+            run_at: options.run_at.unwrap_or_else(Utc::now),
+            unique_key: options.unique_key,
+            schedule_name: None,
+        };
+        Ok(self.repo.enqueue(&new_job).await?)
+    }
+
+    /// Declare a recurring job. Safe to call on every start: the schedule's
+    /// next run and paused state survive unless its timing changed.
+    pub async fn schedule<J: JobPayload>(
+        &self,
+        name: &str,
+        schedule: &str,
+        job: &J,
+        max_attempts: i32,
+    ) -> Result<JobSchedule, AppError> {
+        let parsed = Schedule::parse(schedule).map_err(|e| invalid_schedule(name, &e))?;
+        let payload =
+            serde_json::to_value(job).map_err(|e| invalid_schedule(name, &e.to_string()))?;
+        // Interval schedules run straight away; cron ones wait for their slot
+        let next_run_at = match parsed {
+            Schedule::Every(_) => Utc::now(),
+            Schedule::Cron(_) => parsed
+                .next_after(Utc::now())
+                .ok_or_else(|| invalid_schedule(name, "never runs"))?,
+        };
+
+        let saved = self
+            .repo
+            .upsert_schedule(&NewJobSchedule {
+                name: name.to_string(),
+                kind: J::KIND.to_string(),
+                payload,
+                schedule: schedule.trim().to_string(),
+                max_attempts: max_attempts.max(1),
+                next_run_at,
+            })
+            .await?;
+        info!(
+            schedule = %saved.name,
+            kind = %saved.kind,
+            spec = %saved.schedule,
+            enabled = saved.enabled,
+            "job schedule registered"
+        );
+        Ok(saved)
+    }
+
+    /// Enqueue a run of every due schedule for the given kinds and move each
+    /// to its next slot. Returns how many jobs were enqueued.
+    pub async fn enqueue_scheduled(&self, kinds: &[String]) -> Result<usize, AppError> {
+        let mut enqueued = 0;
+        let now = Utc::now();
+        for schedule in self.repo.due_schedules(kinds).await? {
+            let next_run_at = match Schedule::parse(&schedule.schedule)
+                .ok()
+                .and_then(|parsed| parsed.next_after(now))
+            {
+                Some(next) => next,
+                None => {
+                    warn!(schedule = %schedule.name, spec = %schedule.schedule, "unusable job schedule; pausing it");
+                    self.repo
+                        .set_schedule_enabled(&schedule.name, false)
+                        .await?;
+                    continue;
+                }
+            };
+            if !self
+                .repo
+                .advance_schedule(&schedule.name, schedule.next_run_at, next_run_at)
+                .await?
+            {
+                continue;
+            }
+
+            let job = self
+                .repo
+                .enqueue(&NewJob {
+                    kind: schedule.kind.clone(),
+                    payload: schedule.payload.clone(),
+                    max_attempts: schedule.max_attempts,
+                    backoff_seconds: self.config.base_backoff.as_secs().min(i32::MAX as u64) as i32,
+                    run_at: now,
+                    unique_key: Some(format!("schedule:{}", schedule.name)),
+                    schedule_name: Some(schedule.name.clone()),
+                })
+                .await?;
+            // The previous run is still queued or running; this slot is skipped
+            if let Some(job) = job {
+                self.repo.set_last_job(&schedule.name, job.id).await?;
+                enqueued += 1;
+            }
+        }
+        Ok(enqueued)
+    }
+
+    /// Lease due jobs of the given kinds to `worker`
+    pub async fn claim(&self, kinds: &[String], worker: &str) -> Result<Vec<Job>, AppError> {
+        Ok(self
+            .repo
+            .claim_due(kinds, worker, self.config.batch_size, self.config.lease)
+            .await?)
+    }
+
+    /// Bury jobs whose lease ran out on their last attempt
+    pub async fn bury_expired(&self) -> Result<u64, AppError> {
+        Ok(self.repo.bury_expired().await?)
+    }
+
+    /// Extend `worker`'s lease on a running job. Returns `false` once the
+    /// lease has been lost.
+    pub async fn heartbeat(&self, job: &Job, worker: &str) -> Result<bool, AppError> {
+        Ok(self
+            .repo
+            .heartbeat(job.id, worker, self.config.lease)
+            .await?)
+    }
+
+    pub async fn complete(&self, job: &Job, worker: &str) -> Result<Option<Job>, AppError> {
+        Ok(self.repo.complete(job.id, worker).await?)
+    }
+
+    /// Record a failed attempt, backing off or burying the job on its last
+    pub async fn fail(
+        &self,
+        job: &Job,
+        worker: &str,
+        error: &str,
+    ) -> Result<Option<Job>, AppError> {
+        let delay = backoff_delay(
+            job.attempts,
+            Duration::from_secs(job.backoff_seconds.max(0) as u64),
+            self.config.max_backoff,
+        );
+        let run_at = Utc::now() + chrono::Duration::from_std(delay).unwrap_or_default();
+        Ok(self
+            .repo
+            .record_failure(job.id, worker, error, run_at, job.is_last_attempt())
+            .await?)
+    }
+
+    pub async fn snooze(
+        &self,
+        job: &Job,
+        worker: &str,
+        delay: Duration,
+    ) -> Result<Option<Job>, AppError> {
+        let run_at = Utc::now() + chrono::Duration::from_std(delay).unwrap_or_default();
+        Ok(self.repo.snooze(job.id, worker, run_at).await?)
+    }
+
+    // -----------------------------------------------------------------------
+    // Admin
+    // -----------------------------------------------------------------------
+
+    pub async fn list(
+        &self,
+        status: Option<&str>,
+        kind: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<Job>, AppError> {
+        Ok(self.repo.list(status, kind, limit).await?)
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<Job, AppError> {
+        self.repo
+            .find(id)
+            .await?
+            .ok_or_else(|| not_found(&id.to_string()))
+    }
+
+    pub async fn counts(&self) -> Result<Vec<JobCount>, AppError> {
+        Ok(self.repo.counts().await?)
+    }
+
+    /// Queue a dead or cancelled job again with a fresh attempt budget
+    pub async fn retry(&self, id: Uuid, operator: &str) -> Result<Job, AppError> {
+        match self.repo.retry(id).await? {
+            Some(job) => {
+                info!(job_id = %id, kind = %job.kind, operator = %operator, "job queued for retry");
+                Ok(job)
+            }
+            None => Err(invalid_state(self.get(id).await?, "retried")),
+        }
+    }
+
+    pub async fn cancel(&self, id: Uuid, operator: &str) -> Result<Job, AppError> {
+        match self.repo.cancel(id).await? {
+            Some(job) => {
+                info!(job_id = %id, kind = %job.kind, operator = %operator, "job cancelled");
+                Ok(job)
+            }
+            None => Err(invalid_state(self.get(id).await?, "cancelled")),
+        }
+    }
+
+    pub async fn schedules(&self) -> Result<Vec<JobSchedule>, AppError> {
+        Ok(self.repo.list_schedules().await?)
+    }
+
+    /// Pause or resume a schedule
+    pub async fn set_schedule_enabled(
+        &self,
+        name: &str,
+        enabled: bool,
+        operator: &str,
+    ) -> Result<JobSchedule, AppError> {
+        let schedule = self
+            .repo
+            .set_schedule_enabled(name, enabled)
+            .await?
+            .ok_or_else(|| not_found(name))?;
+        info!(schedule = %name, enabled, operator = %operator, "job schedule updated");
+        Ok(schedule)
+    }
+
+    /// Make a schedule due now
+    pub async fn trigger_schedule(
+        &self,
+        name: &str,
+        operator: &str,
+    ) -> Result<JobSchedule, AppError> {
+        let schedule = self
+            .repo
+            .trigger_schedule(name)
+            .await?
+            .ok_or_else(|| not_found(name))?;
+        info!(schedule = %name, operator = %operator, "job schedule triggered");
+        Ok(schedule)
+    }
+}
+
+fn invalid_schedule(name: &str, reason: &str) -> AppError {
+    AppError::new(AppErrorKind::Infrastructure(
+        InfrastructureError::Configuration {
+            message: format!("invalid job schedule '{}': {}", name, reason),
+        },
+    ))
+}
+
+fn not_found(id: &str) -> AppError {
+    AppError::new(AppErrorKind::Domain(DomainError::TransactionNotFound {
+        transaction_id: id.to_string(),
+    }))
+}
+
+fn invalid_state(job: Job, action: &str) -> AppError {
+    AppError::new(AppErrorKind::Domain(DomainError::InvalidJobState {
+        job_id: job.id.to_string(),
+        status: job.status,
+        action: action.to_string(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(spec: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(spec)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let base = Duration::from_secs(10);
+        let max = Duration::from_secs(600);
+        assert_eq!(backoff_delay(1, base, max), Duration::from_secs(10));
+        assert_eq!(backoff_delay(2, base, max), Duration::from_secs(20));
+        assert_eq!(backoff_delay(4, base, max), Duration::from_secs(80));
+        assert_eq!(backoff_delay(99, base, max), max);
+    }
+
+    #[test]
+    fn every_schedules_parse() {
+        assert_eq!(
+            Schedule::parse("@every 30s").unwrap(),
+            Schedule::Every(Duration::from_secs(30))
+        );
+        assert_eq!(
+            Schedule::parse("@every 5m").unwrap(),
+            Schedule::Every(Duration::from_secs(300))
+        );
+        assert!(Schedule::parse("@every 0s").is_err());
+        assert!(Schedule::parse("@every 10").is_err());
+        assert!(Schedule::parse("@every 10d").is_err());
+    }
+
+    #[test]
+    fn cron_finds_the_next_matching_minute() {
+        let every_15 = Schedule::parse("*/15 * * * *").unwrap();
+        assert_eq!(
+            every_15.next_after(at("2026-03-13T10:07:30Z")),
+            Some(at("2026-03-13T10:15:00Z"))
+        );
+        // Strictly after: a time on the slot moves to the following one
+        assert_eq!(
+            every_15.next_after(at("2026-03-13T10:15:00Z")),
+            Some(at("2026-03-13T10:30:00Z"))
+        );
+
+        let nightly = Schedule::parse("30 2 * * *").unwrap();
+        assert_eq!(
+            nightly.next_after(at("2026-12-31T03:00:00Z")),
+            Some(at("2027-01-01T02:30:00Z"))
+        );
+    }
+
+    #[test]
+    fn cron_day_fields_follow_cron_rules() {
+        // Weekdays only: Friday 13 March 2026 rolls to Monday
+        let weekdays = Schedule::parse("0 9 * * 1-5").unwrap();
+        assert_eq!(
+            weekdays.next_after(at("2026-03-13T10:00:00Z")),
+            Some(at("2026-03-16T09:00:00Z"))
+        );
+
+        // Both day fields set: the 1st of the month or any Sunday
+        let either = Schedule::parse("0 0 1 * 0").unwrap();
+        assert_eq!(
+            either.next_after(at("2026-03-13T00:00:00Z")),
+            Some(at("2026-03-15T00:00:00Z"))
+        );
+
+        // 7 is Sunday too
+        assert_eq!(
+            Schedule::parse("0 0 * * 7").unwrap(),
+            Schedule::parse("0 0 * * 0").unwrap()
+        );
+    }
+
+    #[test]
+    fn invalid_cron_expressions_are_rejected() {
+        assert!(Schedule::parse("* * * *").is_err());
+        assert!(Schedule::parse("60 * * * *").is_err());
+        assert!(Schedule::parse("*/0 * * * *").is_err());
+        assert!(Schedule::parse("5-1 * * * *").is_err());
+        assert!(Schedule::parse("0 0 31 2 *")
+            .unwrap()
+            .next_after(Utc::now())
+            .is_none());
+    }
+}
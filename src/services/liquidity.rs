@@ -0,0 +1,394 @@
+//! Liquidity service
+//! Tracks the inventory quotes are paid from (hot wallet cNGN and payment
+//! provider float) and reserves it when a quote or payout is issued, so the
+//! same balance is never promised twice.
+
+use crate::chains::stellar::client::StellarClient;
+use crate::chains::stellar::types::extract_cngn_balance;
+use crate::database::liquidity_reservation_repository::{
+    LiquidityReservationRepository, ReserveOutcome,
+};
+use crate::error::{AppError, AppErrorKind, DomainError, ExternalError};
+use crate::payments::factory::PaymentProviderFactory;
+use crate::payments::types::ProviderName;
+use bigdecimal::{BigDecimal, RoundingMode};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+/// Inventory pool for cNGN held in the hot wallet
+pub const CNGN_POOL: &str = "stellar:cngn";
+
+/// What to do with a quote the available inventory cannot cover
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortfallPolicy {
+    /// Refuse the quote with `InsufficientLiquidity`
+    Reject,
+    /// Re-quote for the largest amount the inventory can cover
+    Reprice,
+}
+
+impl ShortfallPolicy {
+    fn from_env_value(value: &str) -> Self {
+        match value.trim().to_lowercase().as_str() {
+            "reprice" => Self::Reprice,
+            _ => Self::Reject,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LiquidityConfig {
+    /// Account cNGN payouts are sent from
+    pub hot_wallet: String,
+    /// XLM the hot wallet must keep for fees; below it no cNGN is available
+    pub min_xlm_balance: BigDecimal,
+    /// How long a fetched balance is reused before asking Horizon or the
+    /// provider again
+    pub balance_cache_ttl: Duration,
+    pub shortfall_policy: ShortfallPolicy,
+}
+
+impl Default for LiquidityConfig {
+    fn default() -> Self {
+        Self {
+            hot_wallet: String::new(),
+            min_xlm_balance: BigDecimal::from(5),
+            balance_cache_ttl: Duration::from_secs(30),
+            shortfall_policy: ShortfallPolicy::Reject,
+        }
+    }
+}
+
+impl LiquidityConfig {
+    pub fn from_env() -> Self {
+        let mut cfg = Self::default();
+        cfg.hot_wallet = std::env::var("LIQUIDITY_HOT_WALLET_ADDRESS")
+            .or_else(|_| std::env::var("CNGN_DISTRIBUTION_ACCOUNT"))
+            .or_else(|_| std::env::var("SYSTEM_WALLET_ADDRESS"))
+            .unwrap_or_default();
+        cfg.min_xlm_balance = std::env::var("LIQUIDITY_MIN_XLM_BALANCE")
+            .ok()
+            .and_then(|v| BigDecimal::from_str(&v).ok())
+            .unwrap_or(cfg.min_xlm_balance);
+        cfg.balance_cache_ttl = Duration::from_secs(
+            std::env::var("LIQUIDITY_BALANCE_CACHE_SECONDS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(cfg.balance_cache_ttl.as_secs()),
+        );
+        if let Ok(policy) = std::env::var("LIQUIDITY_SHORTFALL_POLICY") {
+            cfg.shortfall_policy = ShortfallPolicy::from_env_value(&policy);
+        }
+        cfg
+    }
+}
+
+/// Result of reserving inventory
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reservation {
+    Reserved,
+    /// Inventory could not cover the amount; `available` is what is left
+    Shortfall {
+        available: BigDecimal,
+    },
+}
+
+/// Inventory pool for a provider's float in `currency`
+pub fn provider_pool(provider: &ProviderName, currency: &str) -> String {
+    format!("{}:{}", provider, currency.to_uppercase())
+}
+
+/// Scale `requested` down by `available / needed`, rounding down to `scale`
+/// decimals, so a re-priced quote stays within the inventory.
+pub fn scale_to_available(
+    requested: &BigDecimal,
+    needed: &BigDecimal,
+    available: &BigDecimal,
+    scale: i64,
+) -> Option<BigDecimal> {
+    if *needed <= 0 || *available <= 0 {
+        return None;
+    }
+    let scaled = (requested * available / needed).with_scale_round(scale, RoundingMode::Down);
+    (scaled > 0).then_some(scaled)
+}
+
+pub fn insufficient_liquidity(amount: &BigDecimal) -> AppError {
+    AppError::new(AppErrorKind::Domain(DomainError::InsufficientLiquidity {
+        amount: amount.to_string(),
+    }))
+}
+
+pub struct LiquidityService {
+    repository: LiquidityReservationRepository,
+    stellar_client: StellarClient,
+    cngn_issuer: String,
+    provider_factory: Option<Arc<PaymentProviderFactory>>,
+    config: LiquidityConfig,
+    balances: RwLock<HashMap<String, (BigDecimal, DateTime<Utc>)>>,
+}
+
+impl LiquidityService {
+    pub fn new(
+        pool: PgPool,
+        stellar_client: StellarClient,
+        cngn_issuer: String,
+        config: LiquidityConfig,
+    ) -> Self {
+        Self {
+            repository: LiquidityReservationRepository::new(pool),
+            stellar_client,
+            cngn_issuer,
+            provider_factory: None,
+            config,
+            balances: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Enable provider float checks for NGN payouts
+    pub fn with_provider_factory(mut self, factory: Arc<PaymentProviderFactory>) -> Self {
+        self.provider_factory = Some(factory);
+        self
+    }
+
+    pub fn shortfall_policy(&self) -> ShortfallPolicy {
+        self.config.shortfall_policy
+    }
+
+    /// Spendable cNGN in the hot wallet. Reports nothing when the wallet is
+    /// below its XLM floor, since payouts could not pay their fees.
+    pub async fn cngn_inventory(&self) -> Result<BigDecimal, AppError> {
+        if let Some(balance) = self.cached_balance(CNGN_POOL).await {
+            return Ok(balance);
+        }
+
+        let account = self
+            .stellar_client
+            .get_account(&self.config.hot_wallet)
+            .await?;
+        let xlm = account
+            .balances
+            .iter()
+            .find(|b| b.asset_type == "native")
+            .and_then(|b| BigDecimal::from_str(&b.balance).ok())
+            .unwrap_or_else(|| BigDecimal::from(0));
+        let cngn = if xlm < self.config.min_xlm_balance {
+            warn!(
+                hot_wallet = %self.config.hot_wallet,
+                xlm = %xlm,
+                min_xlm = %self.config.min_xlm_balance,
+                "hot wallet below XLM floor, no cNGN inventory available"
+            );
+            BigDecimal::from(0)
+        } else {
+            extract_cngn_balance(&account.balances, Some(&self.cngn_issuer))
+                .and_then(|s| BigDecimal::from_str(&s).ok())
+                .unwrap_or_else(|| BigDecimal::from(0))
+        };
+
+        self.cache_balance(CNGN_POOL, &cngn).await;
+        Ok(cngn)
+    }
+
+    /// Float the provider holds for payouts in `currency`
+    pub async fn provider_float(
+        &self,
+        provider: &ProviderName,
+        currency: &str,
+    ) -> Result<BigDecimal, AppError> {
+        let pool = provider_pool(provider, currency);
+        if let Some(balance) = self.cached_balance(&pool).await {
+            return Ok(balance);
+        }
+
+        let provider_error = |message: String| {
+            AppError::new(AppErrorKind::External(ExternalError::PaymentProvider {
+                provider: provider.to_string(),
+                message,
+                is_retryable: true,
+            }))
+        };
+        let factory = self
+            .provider_factory
+            .as_ref()
+            .ok_or_else(|| provider_error("provider balances are not configured".to_string()))?;
+        let money = factory
+            .get_provider(provider.clone())
+            .map_err(|e| provider_error(e.to_string()))?
+            .get_balance(currency)
+            .await
+            .map_err(|e| provider_error(e.to_string()))?;
+        let balance = BigDecimal::from_str(&money.amount)
+            .map_err(|e| provider_error(format!("invalid balance {}: {}", money.amount, e)))?;
+
+        self.cache_balance(&pool, &balance).await;
+        Ok(balance)
+    }
+
+    /// Reserve hot wallet cNGN for a quote until `expires_at`
+    pub async fn reserve_cngn(
+        &self,
+        reference: &str,
+        amount: &BigDecimal,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Reservation, AppError> {
+        let balance = self.cngn_inventory().await?;
+        self.reserve(CNGN_POOL, reference, amount, &balance, expires_at)
+            .await
+    }
+
+    /// Reserve provider float for a payout until `expires_at`
+    pub async fn reserve_provider_float(
+        &self,
+        provider: &ProviderName,
+        currency: &str,
+        reference: &str,
+        amount: &BigDecimal,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Reservation, AppError> {
+        let balance = self.provider_float(provider, currency).await?;
+        self.reserve(
+            &provider_pool(provider, currency),
+            reference,
+            amount,
+            &balance,
+            expires_at,
+        )
+        .await
+    }
+
+    /// Hold a used quote's inventory for the transaction paying it out,
+    /// until the payout consumes or releases it under `transaction_reference`
+    /// or `expires_at` passes
+    pub async fn hand_over(
+        &self,
+        reference: &str,
+        transaction_reference: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), AppError> {
+        let moved = self
+            .repository
+            .reassign(reference, transaction_reference, expires_at)
+            .await?;
+        debug!(reference = %reference, transaction = %transaction_reference, moved, "liquidity reservation handed over");
+        Ok(())
+    }
+
+    /// The quote or payout went ahead; its inventory has left the pool
+    pub async fn consume(&self, reference: &str) -> Result<(), AppError> {
+        let settled = self.repository.settle(reference, "consumed").await?;
+        if settled > 0 {
+            // The balance has moved, so the cached figure is now too high
+            self.balances.write().await.clear();
+        }
+        debug!(reference = %reference, settled, "liquidity reservation consumed");
+        Ok(())
+    }
+
+    /// The quote or payout was abandoned; return its inventory to the pool
+    pub async fn release(&self, reference: &str) -> Result<(), AppError> {
+        let settled = self.repository.settle(reference, "released").await?;
+        debug!(reference = %reference, settled, "liquidity reservation released");
+        Ok(())
+    }
+
+    /// Release reservations held by quotes that have expired
+    pub async fn release_expired(&self) -> Result<u64, AppError> {
+        let released = self.repository.release_expired().await?;
+        if released > 0 {
+            info!(released, "released expired liquidity reservations");
+        }
+        Ok(released)
+    }
+
+    async fn reserve(
+        &self,
+        inventory_pool: &str,
+        reference: &str,
+        amount: &BigDecimal,
+        balance: &BigDecimal,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Reservation, AppError> {
+        match self
+            .repository
+            .reserve(inventory_pool, reference, amount, balance, expires_at)
+            .await?
+        {
+            ReserveOutcome::Reserved(_) => Ok(Reservation::Reserved),
+            ReserveOutcome::Insufficient { available } => {
+                warn!(
+                    pool = %inventory_pool,
+                    reference = %reference,
+                    requested = %amount,
+                    available = %available,
+                    "liquidity shortfall"
+                );
+                Ok(Reservation::Shortfall { available })
+            }
+        }
+    }
+
+    async fn cached_balance(&self, inventory_pool: &str) -> Option<BigDecimal> {
+        let ttl = chrono::Duration::from_std(self.config.balance_cache_ttl).ok()?;
+        self.balances
+            .read()
+            .await
+            .get(inventory_pool)
+            .filter(|(_, fetched_at)| Utc::now() - *fetched_at < ttl)
+            .map(|(balance, _)| balance.clone())
+    }
+
+    async fn cache_balance(&self, inventory_pool: &str, balance: &BigDecimal) {
+        self.balances
+            .write()
+            .await
+            .insert(inventory_pool.to_string(), (balance.clone(), Utc::now()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scale_to_available_rounds_down() {
+        let scaled = scale_to_available(
+            &BigDecimal::from(100),
+            &BigDecimal::from(150_000),
+            &BigDecimal::from(100_000),
+            2,
+        );
+        assert_eq!(scaled, Some(BigDecimal::from_str("66.66").unwrap()));
+        assert_eq!(
+            scale_to_available(
+                &BigDecimal::from(100),
+                &BigDecimal::from(150_000),
+                &BigDecimal::from(0),
+                2
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_provider_pool_and_policy() {
+        assert_eq!(
+            provider_pool(&ProviderName::Flutterwave, "ngn"),
+            "flutterwave:NGN"
+        );
+        assert_eq!(
+            ShortfallPolicy::from_env_value("Reprice"),
+            ShortfallPolicy::Reprice
+        );
+        assert_eq!(
+            ShortfallPolicy::from_env_value("anything"),
+            ShortfallPolicy::Reject
+        );
+    }
+}
@@ -2,25 +2,55 @@
 
 pub mod balance;
 #[cfg(feature = "database")]
+pub mod claimable_balance;
+#[cfg(feature = "database")]
 pub mod cngn_payment_builder;
 #[cfg(feature = "database")]
 pub mod cngn_trustline;
 #[cfg(feature = "database")]
 pub mod conversion_audit;
 #[cfg(feature = "database")]
+pub mod currency;
+#[cfg(feature = "database")]
+pub mod dispute;
+#[cfg(feature = "database")]
+pub mod distributed_lock;
+#[cfg(feature = "database")]
 pub mod exchange_rate;
 #[cfg(feature = "database")]
 pub mod fee_calculation;
 #[cfg(feature = "database")]
+pub mod fee_rules;
+#[cfg(feature = "database")]
+pub mod fee_simulation;
+#[cfg(feature = "database")]
 pub mod fee_structure;
 #[cfg(feature = "database")]
-pub mod onramp_quote;
+pub mod jobs;
+#[cfg(feature = "database")]
+pub mod liquidity;
+#[cfg(feature = "database")]
+pub mod quote;
 #[cfg(feature = "database")]
 pub mod payment_orchestrator;
 #[cfg(feature = "database")]
+pub mod payout_review;
+#[cfg(feature = "database")]
+pub mod provider_routing;
+#[cfg(feature = "database")]
 pub mod rate_providers;
 #[cfg(feature = "database")]
-pub mod onramp_quote;
+pub mod rate_sources;
+#[cfg(feature = "database")]
+pub mod refund;
+#[cfg(feature = "database")]
+pub mod suspense_payment;
+#[cfg(feature = "database")]
+pub mod treasury;
+#[cfg(feature = "database")]
+pub mod trustline_operation;
+#[cfg(feature = "database")]
+pub mod trustline_sponsorship;
 pub mod webhook_processor;
 pub mod notification;
 
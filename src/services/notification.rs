@@ -8,6 +8,10 @@ pub enum NotificationType {
     OfframpFailed,
     OfframpRefunded,
     CngnReceived,
+    OfframpAmountMismatch,
+    OnrampRefundInitiated,
+    OnrampRefunded,
+    OnrampRefundFailed,
 }
 
 pub struct NotificationService;
@@ -57,6 +61,37 @@ impl NotificationService {
                     "🔔 NOTIFICATION: cNGN Received - {}", message
                 );
             }
+            NotificationType::OfframpAmountMismatch => {
+                info!(
+                    transaction_id = %tx.transaction_id,
+                    wallet = %tx.wallet_address,
+                    expected = %tx.cngn_amount,
+                    "🔔 NOTIFICATION: Offramp Amount Mismatch - {}", message
+                );
+            }
+            NotificationType::OnrampRefundInitiated => {
+                info!(
+                    transaction_id = %tx.transaction_id,
+                    wallet = %tx.wallet_address,
+                    amount = %tx.from_amount,
+                    currency = %tx.from_currency,
+                    "🔔 NOTIFICATION: Onramp Refund Initiated - {}", message
+                );
+            }
+            NotificationType::OnrampRefunded => {
+                info!(
+                    transaction_id = %tx.transaction_id,
+                    wallet = %tx.wallet_address,
+                    "🔔 NOTIFICATION: Onramp Refunded - {}", message
+                );
+            }
+            NotificationType::OnrampRefundFailed => {
+                error!(
+                    transaction_id = %tx.transaction_id,
+                    wallet = %tx.wallet_address,
+                    "🔔 NOTIFICATION: Onramp Refund Failed - {}", message
+                );
+            }
         }
     }
 }
@@ -8,17 +8,22 @@ use crate::database::repository::Repository;
 use crate::database::transaction_repository::Transaction;
 use crate::database::transaction_repository::TransactionRepository;
 use crate::error::{AppError, AppErrorKind, DomainError, ExternalError, InfrastructureError};
+use crate::payments::circuit_breaker::{CircuitState, ProviderCircuitBreaker};
+use crate::payments::error::PaymentError;
 use crate::payments::provider::PaymentProvider;
 use crate::payments::types::{
     Money, PaymentMethod, PaymentRequest, PaymentResponse, PaymentState, ProviderName,
     StatusRequest, StatusResponse,
 };
+use crate::services::fee_calculation::FeeCalculationService;
+use crate::services::fee_rules::FeeContext;
+use crate::services::provider_routing::{ProviderRoutingEngine, RoutingDecision, RoutingWeights};
 use bigdecimal::BigDecimal;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::RwLock;
 use tracing::{error, info, warn};
 use uuid::Uuid;
@@ -213,6 +218,10 @@ pub enum OrchestrationState {
     RefundInitiated,
     /// Refund completed
     Refunded,
+    /// Customer disputed the collection; funds may be clawed back
+    Disputed,
+    /// Dispute lost and the collection charged back
+    ChargedBack,
 }
 
 impl std::fmt::Display for OrchestrationState {
@@ -226,6 +235,8 @@ impl std::fmt::Display for OrchestrationState {
             OrchestrationState::Failed => write!(f, "failed"),
             OrchestrationState::RefundInitiated => write!(f, "refund_initiated"),
             OrchestrationState::Refunded => write!(f, "refunded"),
+            OrchestrationState::Disputed => write!(f, "disputed"),
+            OrchestrationState::ChargedBack => write!(f, "charged_back"),
         }
     }
 }
@@ -239,13 +250,34 @@ impl OrchestrationState {
                 OrchestrationState::PaymentConfirmed,
                 OrchestrationState::Failed,
             ],
-            OrchestrationState::PaymentConfirmed => vec![OrchestrationState::ProcessingBlockchain],
-            OrchestrationState::ProcessingBlockchain => vec![OrchestrationState::Completed],
+            OrchestrationState::PaymentConfirmed => vec![
+                OrchestrationState::ProcessingBlockchain,
+                OrchestrationState::RefundInitiated,
+                OrchestrationState::Refunded,
+                OrchestrationState::Disputed,
+            ],
+            OrchestrationState::ProcessingBlockchain => vec![
+                OrchestrationState::Completed,
+                OrchestrationState::RefundInitiated,
+                OrchestrationState::Disputed,
+            ],
             OrchestrationState::RefundInitiated => vec![OrchestrationState::Refunded],
+            // Settled, but reversals, provider refunds and disputes can
+            // still arrive afterwards
+            OrchestrationState::Completed => vec![
+                OrchestrationState::RefundInitiated,
+                OrchestrationState::Refunded,
+                OrchestrationState::Disputed,
+            ],
+            OrchestrationState::Disputed => vec![
+                OrchestrationState::Completed,
+                OrchestrationState::Refunded,
+                OrchestrationState::ChargedBack,
+            ],
             // Terminal states - no valid transitions
-            OrchestrationState::Completed => vec![],
             OrchestrationState::Failed => vec![],
             OrchestrationState::Refunded => vec![],
+            OrchestrationState::ChargedBack => vec![],
         }
     }
 
@@ -256,6 +288,7 @@ impl OrchestrationState {
             OrchestrationState::Completed
                 | OrchestrationState::Failed
                 | OrchestrationState::Refunded
+                | OrchestrationState::ChargedBack
         )
     }
 
@@ -277,6 +310,8 @@ impl OrchestrationState {
             "failed" => Some(OrchestrationState::Failed),
             "refund_initiated" => Some(OrchestrationState::RefundInitiated),
             "refunded" => Some(OrchestrationState::Refunded),
+            "disputed" => Some(OrchestrationState::Disputed),
+            "charged_back" => Some(OrchestrationState::ChargedBack),
             _ => None,
         }
     }
@@ -292,6 +327,8 @@ impl OrchestrationState {
             OrchestrationState::Failed => "failed",
             OrchestrationState::RefundInitiated => "refund_initiated",
             OrchestrationState::Refunded => "refunded",
+            OrchestrationState::Disputed => "disputed",
+            OrchestrationState::ChargedBack => "charged_back",
         }
     }
 }
@@ -363,6 +400,7 @@ pub enum SelectionStrategy {
 /// Provider selection context
 #[derive(Debug, Clone)]
 pub struct SelectionContext {
+    pub transaction_type: String, // "onramp" or "offramp"
     pub amount: BigDecimal,
     pub currency: String,
     pub payment_method: PaymentMethod,
@@ -383,6 +421,12 @@ pub struct PaymentInitiationRequest {
     pub callback_url: Option<String>,
     pub idempotency_key: Option<String>,
     pub metadata: Option<serde_json::Value>,
+    /// Transaction record the routing decision and fees are written to
+    pub transaction_id: Option<String>,
+    /// Promo code the customer entered
+    pub promo_code: Option<String>,
+    /// API key of the partner the request came through
+    pub partner_api_key: Option<String>,
 }
 
 // ============================================================================
@@ -516,6 +560,9 @@ pub struct PaymentOrchestrator {
     config: OrchestratorConfig,
     provider_metrics: Arc<RwLock<HashMap<ProviderName, ProviderMetrics>>>,
     round_robin_index: Arc<RwLock<usize>>,
+    routing: Option<Arc<ProviderRoutingEngine>>,
+    circuit_breaker: Option<Arc<ProviderCircuitBreaker>>,
+    fees: Option<Arc<FeeCalculationService>>,
 }
 
 impl PaymentOrchestrator {
@@ -537,9 +584,33 @@ impl PaymentOrchestrator {
             config,
             provider_metrics: Arc::new(RwLock::new(metrics)),
             round_robin_index: Arc::new(RwLock::new(0)),
+            routing: None,
+            circuit_breaker: None,
+            fees: None,
         }
     }
 
+    /// Route cost- and reliability-based selections through persisted fee
+    /// tiers, outcomes and daily caps instead of in-memory metrics
+    pub fn with_routing(mut self, routing: Arc<ProviderRoutingEngine>) -> Self {
+        self.routing = Some(routing);
+        self
+    }
+
+    /// Skip providers whose circuit is open and fail over automatically
+    /// when the selected provider's initiation fails
+    pub fn with_circuit_breaker(mut self, breaker: Arc<ProviderCircuitBreaker>) -> Self {
+        self.circuit_breaker = Some(breaker);
+        self
+    }
+
+    /// Price each initiated onramp's fees for its customer, applying fee
+    /// rules and spending promo budgets against the transaction
+    pub fn with_fee_calculation(mut self, fees: Arc<FeeCalculationService>) -> Self {
+        self.fees = Some(fees);
+        self
+    }
+
     /// Add a provider to the orchestrator
     pub fn add_provider(&mut self, provider: Arc<dyn PaymentProvider>) {
         let name = provider.name();
@@ -558,26 +629,45 @@ impl PaymentOrchestrator {
         &self,
         context: &SelectionContext,
     ) -> OrchestratorResult<ProviderName> {
-        let metrics = self.provider_metrics.read().await;
-
-        // Filter available providers
-        let available_providers: Vec<ProviderName> = self
-            .providers
-            .keys()
-            .filter(|name| {
-                if let Some(m) = metrics.get(*name) {
-                    m.current_health != ProviderHealth::Unhealthy
-                } else {
-                    true
-                }
-            })
-            .cloned()
-            .collect();
+        self.select_provider_with_decision(context)
+            .await
+            .map(|(provider, _)| provider)
+    }
 
+    /// Select a provider, returning the routing engine's decision when it
+    /// made the choice
+    pub async fn select_provider_with_decision(
+        &self,
+        context: &SelectionContext,
+    ) -> OrchestratorResult<(ProviderName, Option<RoutingDecision>)> {
+        let available_providers = self.available_providers().await;
         if available_providers.is_empty() {
             return Err(OrchestratorError::NoProviderAvailable);
         }
 
+        if let Some(routing) = &self.routing {
+            let weights = match context.strategy {
+                SelectionStrategy::CostBased => Some(routing.weights()),
+                SelectionStrategy::ReliabilityBased => Some(RoutingWeights::reliability_first()),
+                _ => None,
+            };
+            if let Some(weights) = weights {
+                let decision = routing
+                    .route(
+                        &context.transaction_type,
+                        &context.amount,
+                        Some(context.payment_method.as_str()),
+                        &available_providers,
+                        &weights,
+                    )
+                    .await
+                    .map_err(|e| OrchestratorError::ProviderSelectionFailed {
+                        reason: e.to_string(),
+                    })?;
+                return Ok((decision.selected.clone(), Some(decision)));
+            }
+        }
+
         // Apply selection strategy
         let selected = match context.strategy {
             SelectionStrategy::Default => {
@@ -604,7 +694,36 @@ impl PaymentOrchestrator {
             "Selected payment provider"
         );
 
-        Ok(selected)
+        Ok((selected, None))
+    }
+
+    /// Providers that are not unhealthy and whose circuit is not open
+    async fn available_providers(&self) -> Vec<ProviderName> {
+        let healthy: Vec<ProviderName> = {
+            let metrics = self.provider_metrics.read().await;
+            self.providers
+                .keys()
+                .filter(|name| {
+                    if let Some(m) = metrics.get(*name) {
+                        m.current_health != ProviderHealth::Unhealthy
+                    } else {
+                        true
+                    }
+                })
+                .cloned()
+                .collect()
+        };
+
+        let Some(breaker) = &self.circuit_breaker else {
+            return healthy;
+        };
+        let mut available = Vec::with_capacity(healthy.len());
+        for name in healthy {
+            if breaker.is_available(&name).await {
+                available.push(name);
+            }
+        }
+        available
     }
 
     /// Select default provider
@@ -895,7 +1014,7 @@ impl PaymentOrchestrator {
         let currency = request.currency.clone();
 
         // Generate or use provided idempotency key
-        let idempotency_key = request.idempotency_key.unwrap_or_else(|| {
+        let idempotency_key = request.idempotency_key.clone().unwrap_or_else(|| {
             self.generate_idempotency_key(
                 "onramp",
                 &request.wallet_address,
@@ -925,6 +1044,7 @@ impl PaymentOrchestrator {
 
         // Create selection context
         let context = SelectionContext {
+            transaction_type: "onramp".to_string(),
             amount: amount.clone(),
             currency: currency.clone(),
             payment_method: request.payment_method.clone(),
@@ -940,7 +1060,13 @@ impl PaymentOrchestrator {
         };
 
         // Select provider
-        let provider_name = self.select_provider(&context).await?;
+        let (provider_name, decision) = self.select_provider_with_decision(&context).await?;
+        if let (Some(decision), Some(transaction_id)) = (&decision, &request.transaction_id) {
+            let routing = serde_json::json!({ "routing": decision.to_metadata() });
+            if let Err(e) = self.transaction_repo.merge_metadata(transaction_id, routing).await {
+                warn!(transaction_id = %transaction_id, error = %e, "Failed to record routing decision");
+            }
+        }
 
         // Get provider
         let provider = self
@@ -966,9 +1092,45 @@ impl PaymentOrchestrator {
         };
 
         // Initiate payment with retry logic
-        let response = self
-            .initiate_with_retry(provider.as_ref(), payment_request)
-            .await?;
+        let started = Instant::now();
+        let result = self
+            .initiate_with_retries(provider.as_ref(), payment_request.clone())
+            .await;
+        if let Some(routing) = &self.routing {
+            let succeeded = matches!(&result, Ok(r) if r.status != PaymentState::Failed);
+            if let Err(e) = routing
+                .record_outcome(&provider_name, succeeded, started.elapsed(), &amount)
+                .await
+            {
+                warn!(provider = %provider_name, error = %e, "Failed to record routing outcome");
+            }
+        }
+
+        // With circuit breaking on, a transient failure moves the payment to
+        // the next provider whose circuit is closed
+        let (provider_name, response) = match result {
+            Ok(response) => (provider_name, response),
+            Err(e) if self.circuit_breaker.is_some() && e.is_retryable() => {
+                warn!(provider = %provider_name, error = %e, "Payment initiation failed, failing over");
+                if let Some(m) = self.provider_metrics.write().await.get_mut(&provider_name) {
+                    m.record_failure();
+                }
+                self.failover_to_alternative(&provider_name, payment_request)
+                    .await
+                    .map_err(|failover_error| match failover_error {
+                        OrchestratorError::AllProvidersFailed { mut errors } => {
+                            errors.insert(0, format!("{}: {}", provider_name, e));
+                            OrchestratorError::AllProvidersFailed { errors }
+                        }
+                        other => other,
+                    })?
+            }
+            Err(e) => {
+                return Err(OrchestratorError::AllProvidersFailed {
+                    errors: vec![e.to_string()],
+                })
+            }
+        };
 
         // Store idempotency key
         let now = SystemTime::now()
@@ -987,6 +1149,10 @@ impl PaymentOrchestrator {
         };
         self.store_idempotency_key(&idempotency_info).await?;
 
+        if response.status != PaymentState::Failed {
+            self.charge_fees(&request, &provider_name).await;
+        }
+
         // Record metrics
         {
             let mut metrics = self.provider_metrics.write().await;
@@ -1010,14 +1176,63 @@ impl PaymentOrchestrator {
         Ok(response)
     }
 
+    /// Price an initiated onramp's fees for its customer and record them on
+    /// the transaction. Fee rules see the wallet, promo code and partner key,
+    /// and a promo's budget is spent against the transaction.
+    async fn charge_fees(&self, request: &PaymentInitiationRequest, provider: &ProviderName) {
+        let (Some(fees), Some(transaction_id)) = (&self.fees, &request.transaction_id) else {
+            return;
+        };
+        let context = FeeContext {
+            wallet_address: Some(request.wallet_address.clone()),
+            promo_code: request.promo_code.clone(),
+            partner_api_key: request.partner_api_key.clone(),
+            transaction_id: Uuid::parse_str(transaction_id).ok(),
+        };
+        let breakdown = match fees
+            .calculate_fees_with_context(
+                "onramp",
+                request.amount.clone(),
+                Some(provider.as_str()),
+                Some(request.payment_method.as_str()),
+                &context,
+            )
+            .await
+        {
+            Ok(breakdown) => breakdown,
+            Err(e) => {
+                warn!(transaction_id = %transaction_id, error = %e, "Failed to calculate fees");
+                return;
+            }
+        };
+        let fees = serde_json::json!({ "fees": breakdown });
+        if let Err(e) = self.transaction_repo.merge_metadata(transaction_id, fees).await {
+            warn!(transaction_id = %transaction_id, error = %e, "Failed to record fees");
+        }
+    }
+
     /// Initiate payment with retry logic
     async fn initiate_with_retry(
         &self,
         provider: &dyn PaymentProvider,
         request: PaymentRequest,
     ) -> OrchestratorResult<PaymentResponse> {
+        self.initiate_with_retries(provider, request)
+            .await
+            .map_err(|e| OrchestratorError::AllProvidersFailed {
+                errors: vec![e.to_string()],
+            })
+    }
+
+    /// Retry loop behind `initiate_with_retry`, keeping the provider error so
+    /// callers can tell whether failing over is worthwhile
+    async fn initiate_with_retries(
+        &self,
+        provider: &dyn PaymentProvider,
+        request: PaymentRequest,
+    ) -> Result<PaymentResponse, PaymentError> {
         let mut attempt = 0;
-        let mut last_error: Option<OrchestratorError> = None;
+        let mut last_error: Option<PaymentError> = None;
 
         while attempt < self.config.max_retry_attempts {
             attempt += 1;
@@ -1027,15 +1242,9 @@ impl PaymentOrchestrator {
                 Err(e) => {
                     if !e.is_retryable() {
                         // Non-retryable error, fail immediately
-                        return Err(OrchestratorError::AllProvidersFailed {
-                            errors: vec![e.to_string()],
-                        });
+                        return Err(e);
                     }
 
-                    last_error = Some(OrchestratorError::AllProvidersFailed {
-                        errors: vec![e.to_string()],
-                    });
-
                     // Calculate delay with exponential backoff
                     if attempt < self.config.max_retry_attempts {
                         let delay = self.calculate_retry_delay(attempt);
@@ -1048,12 +1257,13 @@ impl PaymentOrchestrator {
                         );
                         tokio::time::sleep(Duration::from_secs(delay)).await;
                     }
+                    last_error = Some(e);
                 }
             }
         }
 
-        Err(last_error.unwrap_or(OrchestratorError::AllProvidersFailed {
-            errors: vec!["Max retries exceeded".to_string()],
+        Err(last_error.unwrap_or(PaymentError::NetworkError {
+            message: "Max retries exceeded".to_string(),
         }))
     }
 
@@ -1169,13 +1379,24 @@ impl PaymentOrchestrator {
         failed_provider: ProviderName,
         request: PaymentRequest,
     ) -> OrchestratorResult<PaymentResponse> {
+        self.failover_to_alternative(&failed_provider, request)
+            .await
+            .map(|(_, response)| response)
+    }
+
+    /// Try the other providers in turn, skipping open circuits, and return
+    /// the one that accepted the payment
+    async fn failover_to_alternative(
+        &self,
+        failed_provider: &ProviderName,
+        request: PaymentRequest,
+    ) -> OrchestratorResult<(ProviderName, PaymentResponse)> {
         // Find alternative providers
-        let failed = failed_provider.clone();
         let alternative_providers: Vec<ProviderName> = self
-            .providers
-            .keys()
-            .filter(|name| *name != &failed)
-            .cloned()
+            .available_providers()
+            .await
+            .into_iter()
+            .filter(|name| name != failed_provider)
             .collect();
 
         if alternative_providers.is_empty() {
@@ -1199,7 +1420,7 @@ impl PaymentOrchestrator {
                             to_provider = %provider_name,
                             "Failover successful"
                         );
-                        return Ok(response);
+                        return Ok((provider_name, response));
                     }
                     Err(e) => {
                         errors.push(format!("{}: {}", provider_name, e));
@@ -1304,8 +1525,16 @@ impl PaymentOrchestrator {
         self.provider_metrics.read().await.clone()
     }
 
-    /// Get provider health status
+    /// Get provider health status. An open circuit makes the provider
+    /// unhealthy and a half-open one degraded, whatever its metrics say.
     pub async fn get_provider_health(&self, provider: &ProviderName) -> ProviderHealth {
+        if let Some(breaker) = &self.circuit_breaker {
+            match breaker.state(provider).await {
+                CircuitState::Open => return ProviderHealth::Unhealthy,
+                CircuitState::HalfOpen => return ProviderHealth::Degraded,
+                CircuitState::Closed => {}
+            }
+        }
         let metrics = self.provider_metrics.read().await;
         metrics
             .get(provider)
@@ -1416,6 +1645,107 @@ impl PaymentOrchestrator {
         info!(tx_ref = %transaction_reference, reason = %reason, "Withdrawal failure processed");
         Ok(())
     }
+
+    /// Handle a payout the provider reversed after reporting it delivered:
+    /// the user is owed their cNGN back
+    pub async fn handle_withdrawal_reversal(
+        &self,
+        transaction_reference: &str,
+        reason: &str,
+    ) -> OrchestratorResult<()> {
+        let transaction = self.find_by_reference(transaction_reference).await?;
+        self.transition_state(
+            &transaction.transaction_id.to_string(),
+            OrchestrationState::RefundInitiated,
+            Some(format!("Withdrawal reversed via webhook: {}", reason)),
+        )
+        .await?;
+
+        warn!(tx_ref = %transaction_reference, reason = %reason, "Withdrawal reversal processed");
+        Ok(())
+    }
+
+    /// Handle refund processed webhook. A repeat confirmation of a refund
+    /// already recorded is not an error.
+    pub async fn handle_refund_processed(
+        &self,
+        transaction_reference: &str,
+    ) -> OrchestratorResult<()> {
+        let transaction = self.find_by_reference(transaction_reference).await?;
+        if OrchestrationState::from_db_status(&transaction.status)
+            == Some(OrchestrationState::Refunded)
+        {
+            return Ok(());
+        }
+        self.transition_state(
+            &transaction.transaction_id.to_string(),
+            OrchestrationState::Refunded,
+            Some("Refund confirmed via webhook".to_string()),
+        )
+        .await?;
+
+        info!(tx_ref = %transaction_reference, "Refund processed");
+        Ok(())
+    }
+
+    /// Handle dispute opened webhook. A reminder for a dispute already
+    /// recorded is not an error.
+    pub async fn handle_dispute_opened(
+        &self,
+        transaction_reference: &str,
+    ) -> OrchestratorResult<()> {
+        let transaction = self.find_by_reference(transaction_reference).await?;
+        if OrchestrationState::from_db_status(&transaction.status)
+            == Some(OrchestrationState::Disputed)
+        {
+            return Ok(());
+        }
+        self.transition_state(
+            &transaction.transaction_id.to_string(),
+            OrchestrationState::Disputed,
+            Some("Dispute opened via webhook".to_string()),
+        )
+        .await?;
+
+        warn!(tx_ref = %transaction_reference, "Dispute opened");
+        Ok(())
+    }
+
+    /// Handle dispute resolution webhook: a won dispute settles the
+    /// transaction again, a lost one marks it charged back
+    pub async fn handle_dispute_resolved(
+        &self,
+        transaction_reference: &str,
+        won: bool,
+    ) -> OrchestratorResult<()> {
+        let transaction = self.find_by_reference(transaction_reference).await?;
+        let (target, reason) = if won {
+            (OrchestrationState::Completed, "Dispute won via webhook")
+        } else {
+            (OrchestrationState::ChargedBack, "Dispute lost via webhook")
+        };
+        self.transition_state(
+            &transaction.transaction_id.to_string(),
+            target,
+            Some(reason.to_string()),
+        )
+        .await?;
+
+        info!(tx_ref = %transaction_reference, won, "Dispute resolved");
+        Ok(())
+    }
+
+    async fn find_by_reference(&self, transaction_reference: &str) -> OrchestratorResult<Transaction> {
+        self.transaction_repo
+            .find_by_payment_reference(transaction_reference)
+            .await
+            .map_err(|_| OrchestratorError::TransactionNotFound {
+                transaction_id: transaction_reference.to_string(),
+            })?
+            .ok_or(OrchestratorError::TransactionNotFound {
+                transaction_id: transaction_reference.to_string(),
+            })
+    }
 }
 
 // ============================================================================
@@ -0,0 +1,267 @@
+//! Payout review service
+//!
+//! The offramp processor polls providers for transfers whose webhook never
+//! arrived. Definitive answers settle the payout there; ambiguous ones (a
+//! transfer reversed after it was reported successful, a status we cannot
+//! interpret, a transfer still unconfirmed after the retry timeout) are
+//! escalated here. While a review is open the transaction stays in
+//! `transfer_pending` and polling stops; ops then mark the payout completed,
+//! refund the cNGN, or resume polling.
+
+use crate::database::payout_review_repository::{
+    NewPayoutReview, PayoutReview, PayoutReviewRepository, PayoutSettlement,
+};
+use crate::database::repository::Repository;
+use crate::database::transaction_repository::{Transaction, TransactionRepository};
+use crate::error::{AppError, AppErrorKind, DomainError, ValidationError};
+use crate::services::notification::{NotificationService, NotificationType};
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Why a payout was escalated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayoutReviewReason {
+    /// The provider reversed a transfer it had reported successful
+    ReversedAfterSuccess,
+    /// The provider reported a status we cannot interpret
+    UnknownStatus,
+    /// The transfer was still unconfirmed after the retry timeout
+    StuckPending,
+}
+
+impl PayoutReviewReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PayoutReviewReason::ReversedAfterSuccess => "reversed_after_success",
+            PayoutReviewReason::UnknownStatus => "unknown_status",
+            PayoutReviewReason::StuckPending => "stuck_pending",
+        }
+    }
+}
+
+/// How ops settled a review
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayoutResolution {
+    /// The customer was paid; complete the offramp
+    Completed,
+    /// The customer was not paid; return their cNGN
+    Refunded,
+    /// Keep polling the provider
+    Resumed,
+}
+
+impl PayoutResolution {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PayoutResolution::Completed => "completed",
+            PayoutResolution::Refunded => "refunded",
+            PayoutResolution::Resumed => "resumed",
+        }
+    }
+}
+
+/// A payout the offramp processor could not settle on its own
+#[derive(Debug, Clone)]
+pub struct PayoutEscalation {
+    pub provider: String,
+    pub provider_reference: Option<String>,
+    pub reason: PayoutReviewReason,
+    pub provider_status: Option<String>,
+    pub details: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResolvePayoutReviewRequest {
+    pub operator: String,
+    pub resolution: PayoutResolution,
+    pub note: Option<String>,
+}
+
+pub struct PayoutReviewService {
+    reviews: PayoutReviewRepository,
+    transactions: TransactionRepository,
+    notifications: Arc<NotificationService>,
+}
+
+impl PayoutReviewService {
+    pub fn new(pool: PgPool, notifications: Arc<NotificationService>) -> Self {
+        Self {
+            reviews: PayoutReviewRepository::new(pool.clone()),
+            transactions: TransactionRepository::new(pool),
+            notifications,
+        }
+    }
+
+    /// Queue a payout for review, or return the review already open for it
+    pub async fn escalate(
+        &self,
+        transaction: &Transaction,
+        escalation: PayoutEscalation,
+    ) -> Result<PayoutReview, AppError> {
+        let opened = self
+            .reviews
+            .open(&NewPayoutReview {
+                transaction_id: transaction.transaction_id,
+                provider: escalation.provider,
+                provider_reference: escalation.provider_reference,
+                reason: escalation.reason.as_str().to_string(),
+                provider_status: escalation.provider_status,
+                details: escalation.details,
+            })
+            .await?;
+        match opened {
+            Some(review) => {
+                warn!(
+                    review_id = %review.id,
+                    transaction_id = %transaction.transaction_id,
+                    reason = %review.reason,
+                    "payout escalated for manual review"
+                );
+                Ok(review)
+            }
+            None => self
+                .reviews
+                .find_open(transaction.transaction_id)
+                .await?
+                .ok_or_else(|| not_found(&transaction.transaction_id.to_string())),
+        }
+    }
+
+    pub async fn list(
+        &self,
+        status: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<PayoutReview>, AppError> {
+        Ok(self.reviews.list(status, limit).await?)
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<PayoutReview, AppError> {
+        self.reviews
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| not_found(&id.to_string()))
+    }
+
+    /// Settle an open review and move its payout on accordingly, in one
+    /// database transaction. A payout that has already left
+    /// `transfer_pending` is left as it is.
+    pub async fn resolve(
+        &self,
+        id: Uuid,
+        request: ResolvePayoutReviewRequest,
+    ) -> Result<PayoutReview, AppError> {
+        let operator = request.operator.trim();
+        if operator.is_empty() {
+            return Err(AppError::new(AppErrorKind::Validation(
+                ValidationError::MissingField {
+                    field: "operator".to_string(),
+                },
+            )));
+        }
+        let review = self.get(id).await?;
+        if review.status != "open" {
+            return Err(closed(&review));
+        }
+        let transaction_id = review.transaction_id.to_string();
+        let transaction = self
+            .transactions
+            .find_by_id(&transaction_id)
+            .await?
+            .ok_or_else(|| not_found(&transaction_id))?;
+        let settlement = match request.resolution {
+            PayoutResolution::Completed => PayoutSettlement {
+                next_status: "completed".to_string(),
+                metadata: serde_json::json!({ "payout_review_id": null }),
+            },
+            PayoutResolution::Refunded => PayoutSettlement {
+                next_status: "refund_initiated".to_string(),
+                metadata: serde_json::json!({
+                    "payout_review_id": null,
+                    "failure_reason": format!("Payout review {}: {}", id, review.reason),
+                }),
+            },
+            PayoutResolution::Resumed => PayoutSettlement {
+                next_status: "transfer_pending".to_string(),
+                metadata: serde_json::json!({
+                    "payout_review_id": null,
+                    "status_checks": 0,
+                    "next_status_check_at": null,
+                    "transfer_pending_since": chrono::Utc::now().to_rfc3339(),
+                }),
+            },
+        };
+        let Some((review, settled)) = self
+            .reviews
+            .resolve(
+                id,
+                request.resolution.as_str(),
+                operator,
+                request.note.as_deref(),
+                &settlement,
+            )
+            .await?
+        else {
+            // Another operator resolved it since it was read
+            let current = self.get(id).await?;
+            return Err(closed(&current));
+        };
+        info!(
+            review_id = %id,
+            transaction_id = %transaction_id,
+            resolution = %request.resolution.as_str(),
+            operator = %operator,
+            "payout review resolved"
+        );
+
+        // A late webhook may have settled the payout while it sat in review;
+        // the review is then only closed
+        if !settled {
+            warn!(
+                review_id = %id,
+                transaction_id = %transaction_id,
+                "payout already settled; review closed without changes"
+            );
+            return Ok(review);
+        }
+
+        match request.resolution {
+            PayoutResolution::Completed => {
+                self.notifications
+                    .send_notification(
+                        &transaction,
+                        NotificationType::OfframpCompleted,
+                        "Funds have been sent to your bank account",
+                    )
+                    .await;
+            }
+            PayoutResolution::Refunded => {
+                self.notifications
+                    .send_notification(
+                        &transaction,
+                        NotificationType::OfframpFailed,
+                        "Bank transfer failed, initiating refund",
+                    )
+                    .await;
+            }
+            PayoutResolution::Resumed => {}
+        }
+        Ok(review)
+    }
+}
+
+fn not_found(id: &str) -> AppError {
+    AppError::new(AppErrorKind::Domain(DomainError::TransactionNotFound {
+        transaction_id: id.to_string(),
+    }))
+}
+
+fn closed(review: &PayoutReview) -> AppError {
+    AppError::new(AppErrorKind::Domain(DomainError::PayoutReviewClosed {
+        review_id: review.id.to_string(),
+        status: review.status.clone(),
+    }))
+}
@@ -0,0 +1,476 @@
+//! Provider routing engine
+//! Picks a payment provider by weighing the expected provider fee for the
+//! amount (from `fee_structures`) against persisted success rate and latency,
+//! skipping providers that are disabled or have reached their daily cap. Each
+//! decision records every candidate and why it won or was excluded, so it
+//! can be stored in transaction metadata.
+
+use crate::database::provider_routing_repository::{
+    ProviderRoutingRepository, ProviderRoutingStats,
+};
+use crate::error::{AppError, AppErrorKind, ExternalError};
+use crate::payments::types::ProviderName;
+use crate::services::fee_calculation::FeeCalculationService;
+use bigdecimal::{BigDecimal, ToPrimitive};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Success rate assumed for a provider before it has any history
+const PRIOR_SUCCESS_RATE: f64 = 0.95;
+/// How many observations the prior is worth when smoothing the success rate
+const PRIOR_WEIGHT: f64 = 10.0;
+/// Latency score given to a provider with no recorded latency
+const UNKNOWN_LATENCY_SCORE: f64 = 0.5;
+
+/// Relative importance of each routing factor
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct RoutingWeights {
+    pub cost: f64,
+    pub reliability: f64,
+    pub latency: f64,
+}
+
+impl RoutingWeights {
+    /// Cheapest provider that is not failing
+    pub fn cost_first() -> Self {
+        Self {
+            cost: 0.6,
+            reliability: 0.3,
+            latency: 0.1,
+        }
+    }
+
+    /// Most reliable provider, with cost as a tie-breaker
+    pub fn reliability_first() -> Self {
+        Self {
+            cost: 0.15,
+            reliability: 0.65,
+            latency: 0.2,
+        }
+    }
+
+    /// Price alone
+    pub fn cost_only() -> Self {
+        Self {
+            cost: 1.0,
+            reliability: 0.0,
+            latency: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProviderRoutingConfig {
+    /// Weights used for cost-based selection
+    pub weights: RoutingWeights,
+    /// Days of persisted outcomes that feed success rate and latency
+    pub window_days: i32,
+    /// Outcomes needed before a low success rate excludes a provider
+    pub min_samples: i64,
+    /// Smoothed success rate below which a provider is excluded
+    pub min_success_rate: f64,
+}
+
+impl Default for ProviderRoutingConfig {
+    fn default() -> Self {
+        Self {
+            weights: RoutingWeights::cost_first(),
+            window_days: 7,
+            min_samples: 20,
+            min_success_rate: 0.8,
+        }
+    }
+}
+
+impl ProviderRoutingConfig {
+    pub fn from_env() -> Self {
+        fn env_f64(name: &str, default: f64) -> f64 {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .filter(|v| *v >= 0.0)
+                .unwrap_or(default)
+        }
+
+        let mut cfg = Self::default();
+        cfg.weights = RoutingWeights {
+            cost: env_f64("PROVIDER_ROUTING_COST_WEIGHT", cfg.weights.cost),
+            reliability: env_f64(
+                "PROVIDER_ROUTING_RELIABILITY_WEIGHT",
+                cfg.weights.reliability,
+            ),
+            latency: env_f64("PROVIDER_ROUTING_LATENCY_WEIGHT", cfg.weights.latency),
+        };
+        cfg.window_days = std::env::var("PROVIDER_ROUTING_WINDOW_DAYS")
+            .ok()
+            .and_then(|v| v.parse::<i32>().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(cfg.window_days);
+        cfg.min_samples = std::env::var("PROVIDER_ROUTING_MIN_SAMPLES")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(cfg.min_samples);
+        cfg.min_success_rate =
+            env_f64("PROVIDER_ROUTING_MIN_SUCCESS_RATE", cfg.min_success_rate).min(1.0);
+        cfg
+    }
+}
+
+/// One provider as considered by a routing decision
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RoutingCandidate {
+    pub provider: ProviderName,
+    /// Provider fee for the amount; `None` when no fee tier prices it
+    pub expected_fee: Option<BigDecimal>,
+    /// Success rate smoothed towards the prior for thin history
+    pub success_rate: f64,
+    pub samples: i64,
+    pub avg_latency_ms: Option<f64>,
+    pub volume_today: BigDecimal,
+    pub daily_cap: Option<BigDecimal>,
+    /// Weighted score in 0..=1; `None` for excluded providers
+    pub score: Option<f64>,
+    pub excluded: Option<String>,
+}
+
+impl RoutingCandidate {
+    /// Build a candidate from its fee and persisted stats, applying the
+    /// exclusion rules
+    pub fn new(
+        provider: ProviderName,
+        amount: &BigDecimal,
+        expected_fee: Option<BigDecimal>,
+        stats: Option<&ProviderRoutingStats>,
+        config: &ProviderRoutingConfig,
+    ) -> Self {
+        let (successes, failures, latency_total) = stats
+            .map(|s| (s.success_count, s.failure_count, s.total_latency_ms))
+            .unwrap_or((0, 0, 0));
+        let samples = successes + failures;
+        let success_rate = (successes as f64 + PRIOR_SUCCESS_RATE * PRIOR_WEIGHT)
+            / (samples as f64 + PRIOR_WEIGHT);
+        let avg_latency_ms = (samples > 0).then(|| latency_total as f64 / samples as f64);
+        let volume_today = stats
+            .map(|s| s.volume_today.clone())
+            .unwrap_or_else(|| BigDecimal::from(0));
+        let daily_cap = stats.and_then(|s| s.daily_volume_cap.clone());
+
+        let excluded = if stats.is_some_and(|s| !s.is_enabled) {
+            Some("disabled in provider config".to_string())
+        } else if daily_cap
+            .as_ref()
+            .is_some_and(|cap| &volume_today + amount > *cap)
+        {
+            Some("daily volume cap reached".to_string())
+        } else if samples >= config.min_samples && success_rate < config.min_success_rate {
+            Some(format!(
+                "success rate {:.3} below {:.3}",
+                success_rate, config.min_success_rate
+            ))
+        } else {
+            None
+        };
+
+        Self {
+            provider,
+            expected_fee,
+            success_rate,
+            samples,
+            avg_latency_ms,
+            volume_today,
+            daily_cap,
+            score: None,
+            excluded,
+        }
+    }
+}
+
+/// Why a provider was chosen, for transaction metadata
+#[derive(Debug, Clone, Serialize)]
+pub struct RoutingDecision {
+    pub selected: ProviderName,
+    pub transaction_type: String,
+    pub amount: BigDecimal,
+    pub payment_method: Option<String>,
+    pub weights: RoutingWeights,
+    pub candidates: Vec<RoutingCandidate>,
+    pub decided_at: DateTime<Utc>,
+}
+
+impl RoutingDecision {
+    pub fn to_metadata(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+}
+
+/// Score the eligible candidates against each other and return the index of
+/// the best one. Cost and latency are relative to the best eligible
+/// provider; a provider without a fee tier scores zero on cost.
+pub fn score_candidates(
+    candidates: &mut [RoutingCandidate],
+    weights: &RoutingWeights,
+) -> Option<usize> {
+    let eligible = |c: &RoutingCandidate| c.excluded.is_none();
+    let cheapest = candidates
+        .iter()
+        .filter(|c| eligible(c))
+        .filter_map(|c| c.expected_fee.as_ref().and_then(|f| f.to_f64()))
+        .fold(None, |min: Option<f64>, fee| {
+            Some(min.map_or(fee, |m| m.min(fee)))
+        });
+    let fastest = candidates
+        .iter()
+        .filter(|c| eligible(c))
+        .filter_map(|c| c.avg_latency_ms)
+        .fold(None, |min: Option<f64>, ms| {
+            Some(min.map_or(ms, |m| m.min(ms)))
+        });
+
+    let total_weight = weights.cost + weights.reliability + weights.latency;
+    let mut best: Option<(usize, f64)> = None;
+    for (index, candidate) in candidates.iter_mut().enumerate() {
+        if !eligible(candidate) {
+            continue;
+        }
+        let cost = match (
+            candidate.expected_fee.as_ref().and_then(|f| f.to_f64()),
+            cheapest,
+        ) {
+            (Some(fee), _) if fee <= 0.0 => 1.0,
+            (Some(fee), Some(cheapest)) => cheapest / fee,
+            _ => 0.0,
+        };
+        let latency = match (candidate.avg_latency_ms, fastest) {
+            (Some(ms), _) if ms <= 0.0 => 1.0,
+            (Some(ms), Some(fastest)) => fastest / ms,
+            _ => UNKNOWN_LATENCY_SCORE,
+        };
+        let score = if total_weight > 0.0 {
+            (weights.cost * cost
+                + weights.reliability * candidate.success_rate
+                + weights.latency * latency)
+                / total_weight
+        } else {
+            0.0
+        };
+        candidate.score = Some(score);
+        if best.is_none_or(|(_, top)| score > top) {
+            best = Some((index, score));
+        }
+    }
+    best.map(|(index, _)| index)
+}
+
+pub struct ProviderRoutingEngine {
+    repository: ProviderRoutingRepository,
+    fees: FeeCalculationService,
+    config: ProviderRoutingConfig,
+}
+
+impl ProviderRoutingEngine {
+    pub fn new(pool: PgPool, config: ProviderRoutingConfig) -> Self {
+        Self {
+            repository: ProviderRoutingRepository::new(pool.clone()),
+            fees: FeeCalculationService::new(pool),
+            config,
+        }
+    }
+
+    /// Weights configured for cost-based routing
+    pub fn weights(&self) -> RoutingWeights {
+        self.config.weights
+    }
+
+    /// Choose among `providers` for a `transaction_type` of `amount`
+    pub async fn route(
+        &self,
+        transaction_type: &str,
+        amount: &BigDecimal,
+        payment_method: Option<&str>,
+        providers: &[ProviderName],
+        weights: &RoutingWeights,
+    ) -> Result<RoutingDecision, AppError> {
+        let stats = self.repository.stats(self.config.window_days).await?;
+
+        let mut candidates = Vec::with_capacity(providers.len());
+        for provider in providers {
+            let expected_fee = self
+                .fees
+                .provider_fee(transaction_type, amount, provider.as_str(), payment_method)
+                .await?
+                .map(|fee| fee.calculated);
+            let provider_stats = stats.iter().find(|s| s.provider == provider.as_str());
+            candidates.push(RoutingCandidate::new(
+                provider.clone(),
+                amount,
+                expected_fee,
+                provider_stats,
+                &self.config,
+            ));
+        }
+
+        let Some(selected) = score_candidates(&mut candidates, weights) else {
+            warn!(
+                transaction_type,
+                amount = %amount,
+                "no provider eligible for routing"
+            );
+            return Err(AppError::new(AppErrorKind::External(
+                ExternalError::PaymentProvider {
+                    provider: "all".to_string(),
+                    message: "no provider is enabled, under its daily cap and above the success rate threshold".to_string(),
+                    is_retryable: true,
+                },
+            )));
+        };
+
+        let decision = RoutingDecision {
+            selected: candidates[selected].provider.clone(),
+            transaction_type: transaction_type.to_string(),
+            amount: amount.clone(),
+            payment_method: payment_method.map(str::to_string),
+            weights: *weights,
+            candidates,
+            decided_at: Utc::now(),
+        };
+        info!(
+            provider = %decision.selected,
+            transaction_type,
+            amount = %amount,
+            "routed payment"
+        );
+        Ok(decision)
+    }
+
+    /// Persist the outcome of an initiation routed to `provider`
+    pub async fn record_outcome(
+        &self,
+        provider: &ProviderName,
+        succeeded: bool,
+        latency: Duration,
+        amount: &BigDecimal,
+    ) -> Result<(), AppError> {
+        self.repository
+            .record_outcome(
+                provider.as_str(),
+                succeeded,
+                i64::try_from(latency.as_millis()).unwrap_or(i64::MAX),
+                amount,
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(
+        provider: &str,
+        successes: i64,
+        failures: i64,
+        latency_ms: i64,
+    ) -> ProviderRoutingStats {
+        ProviderRoutingStats {
+            provider: provider.to_string(),
+            is_enabled: true,
+            daily_volume_cap: None,
+            success_count: successes,
+            failure_count: failures,
+            total_latency_ms: latency_ms * (successes + failures),
+            volume_today: BigDecimal::from(0),
+        }
+    }
+
+    fn candidate(
+        provider: ProviderName,
+        fee: i64,
+        stats: Option<&ProviderRoutingStats>,
+    ) -> RoutingCandidate {
+        RoutingCandidate::new(
+            provider,
+            &BigDecimal::from(100_000),
+            Some(BigDecimal::from(fee)),
+            stats,
+            &ProviderRoutingConfig::default(),
+        )
+    }
+
+    #[test]
+    fn test_cost_and_reliability_trade_off() {
+        let paystack = stats("paystack", 60, 40, 800);
+        let flutterwave = stats("flutterwave", 99, 1, 800);
+        let mut candidates = vec![
+            candidate(ProviderName::Paystack, 1400, Some(&paystack)),
+            candidate(ProviderName::Flutterwave, 1500, Some(&flutterwave)),
+        ];
+
+        // Paystack is cheaper but its 60% success rate excludes it
+        let best = score_candidates(&mut candidates, &RoutingWeights::cost_first()).unwrap();
+        assert_eq!(candidates[best].provider, ProviderName::Flutterwave);
+        assert!(candidates[0].excluded.is_some());
+        assert!(candidates[0].score.is_none());
+
+        // With similar reliability the cheaper provider wins on cost
+        let paystack = stats("paystack", 97, 3, 800);
+        let mut candidates = vec![
+            candidate(ProviderName::Paystack, 1400, Some(&paystack)),
+            candidate(ProviderName::Flutterwave, 1500, Some(&flutterwave)),
+        ];
+        let best = score_candidates(&mut candidates, &RoutingWeights::cost_first()).unwrap();
+        assert_eq!(candidates[best].provider, ProviderName::Paystack);
+
+        // ...but not when reliability is what matters
+        let best = score_candidates(&mut candidates, &RoutingWeights::reliability_first()).unwrap();
+        assert_eq!(candidates[best].provider, ProviderName::Flutterwave);
+    }
+
+    #[test]
+    fn test_daily_cap_and_disabled_providers_are_excluded() {
+        let mut capped = stats("paystack", 100, 0, 500);
+        capped.daily_volume_cap = Some(BigDecimal::from(1_000_000));
+        capped.volume_today = BigDecimal::from(950_000);
+        let mut disabled = stats("mpesa", 100, 0, 500);
+        disabled.is_enabled = false;
+
+        let mut candidates = vec![
+            candidate(ProviderName::Paystack, 100, Some(&capped)),
+            candidate(ProviderName::Mpesa, 100, Some(&disabled)),
+            candidate(ProviderName::Flutterwave, 2000, None),
+        ];
+        let best = score_candidates(&mut candidates, &RoutingWeights::cost_only()).unwrap();
+        assert_eq!(candidates[best].provider, ProviderName::Flutterwave);
+        assert_eq!(
+            candidates[0].excluded.as_deref(),
+            Some("daily volume cap reached")
+        );
+        assert_eq!(
+            candidates[1].excluded.as_deref(),
+            Some("disabled in provider config")
+        );
+
+        let mut none = vec![candidates[0].clone()];
+        assert_eq!(
+            score_candidates(&mut none, &RoutingWeights::cost_only()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_thin_history_is_not_excluded() {
+        // Three failures out of three is below min_samples, so the provider
+        // stays eligible with a success rate pulled towards the prior
+        let flaky = stats("paystack", 0, 3, 500);
+        let c = candidate(ProviderName::Paystack, 100, Some(&flaky));
+        assert!(c.excluded.is_none());
+        assert!(c.success_rate > 0.7 && c.success_rate < PRIOR_SUCCESS_RATE);
+
+        let fresh = candidate(ProviderName::Paystack, 100, None);
+        assert_eq!(fresh.success_rate, PRIOR_SUCCESS_RATE);
+        assert_eq!(fresh.avg_latency_ms, None);
+    }
+}
@@ -0,0 +1,1177 @@
+//! Quote Service
+//!
+//! Prices onramp (fiat → cNGN), offramp (cNGN → fiat payout) and bill payment
+//! (cNGN → NGN bill) quotes. A quote fixes either the amount the user sends
+//! or the amount they receive; the rate, fees and expiry are locked in Redis
+//! and recorded as a `quoted` conversion audit, which consuming the quote
+//! moves to `executed`.
+
+use crate::cache::cache::Cache;
+use crate::cache::keys::quote::QuoteKey;
+use crate::cache::RedisCache;
+use crate::chains::stellar::client::StellarClient;
+use crate::chains::stellar::trustline::CngnTrustlineManager;
+use crate::chains::stellar::types::{extract_cngn_balance, is_valid_stellar_address};
+use crate::chains::traits::ChainType;
+use crate::database::wallet_risk_repository::WalletRiskRepository;
+use crate::error::{AppError, AppErrorKind, DomainError, InfrastructureError, ValidationError};
+use crate::payments::types::ProviderName;
+use crate::services::conversion_audit::{ConversionAuditService, ConversionQuoteInput};
+use crate::services::currency;
+use crate::services::exchange_rate::{ConversionDirection, ConversionRequest, ExchangeRateService};
+use crate::services::fee_calculation::FeeCalculationService;
+use crate::services::fee_rules::{AppliedFeeRule, FeeContext};
+use crate::services::fee_structure::{FeeCalculationInput, FeeStructureService};
+use crate::services::liquidity::{self, LiquidityService, Reservation, ShortfallPolicy};
+use bigdecimal::num_traits::Zero;
+use bigdecimal::{BigDecimal, RoundingMode};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+/// Fiat currency quotes use when the request does not say
+const DEFAULT_FIAT_CURRENCY: &str = "NGN";
+
+const CNGN: &str = "cNGN";
+
+/// Quotes stay in Redis this long past expiry so late attempts to use them
+/// are recorded as expired rather than unknown
+const EXPIRED_QUOTE_RETENTION_SECS: u64 = 600;
+
+/// Forward pricings tried when solving for the send amount of a reverse quote
+const MAX_REVERSE_ITERATIONS: usize = 8;
+
+/// Longest a used quote's inventory stays held for its transaction when the
+/// payout never consumes or releases it
+const USED_QUOTE_RESERVATION_HOURS: i64 = 24;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuoteFlow {
+    /// Fiat collected by a payment provider, cNGN sent to the wallet
+    Onramp,
+    /// cNGN received from the wallet, fiat paid out by a payment provider
+    Offramp,
+    /// cNGN received from the wallet, an NGN bill paid
+    BillPayment,
+}
+
+impl QuoteFlow {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuoteFlow::Onramp => "onramp",
+            QuoteFlow::Offramp => "offramp",
+            QuoteFlow::BillPayment => "bill_payment",
+        }
+    }
+
+    fn env_prefix(&self) -> &'static str {
+        match self {
+            QuoteFlow::Onramp => "ONRAMP",
+            QuoteFlow::Offramp => "OFFRAMP",
+            QuoteFlow::BillPayment => "BILL_PAYMENT",
+        }
+    }
+
+    /// Currencies the user sends and receives
+    fn currencies(&self, fiat: &str) -> (String, String) {
+        match self {
+            QuoteFlow::Onramp => (fiat.to_string(), CNGN.to_string()),
+            QuoteFlow::Offramp | QuoteFlow::BillPayment => (CNGN.to_string(), fiat.to_string()),
+        }
+    }
+
+    fn direction(&self) -> ConversionDirection {
+        match self {
+            QuoteFlow::Onramp => ConversionDirection::Buy,
+            QuoteFlow::Offramp | QuoteFlow::BillPayment => ConversionDirection::Sell,
+        }
+    }
+}
+
+impl std::fmt::Display for QuoteFlow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Which amount of the quote the request fixed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuoteSide {
+    /// "I send X"
+    Send,
+    /// "I receive Y"
+    Receive,
+}
+
+/// Amount limits (NGN equivalent of the amount sent) and lifetime of one
+/// flow's quotes
+#[derive(Debug, Clone)]
+pub struct QuoteLimits {
+    pub min_amount_ngn: BigDecimal,
+    pub max_amount_ngn: Option<BigDecimal>,
+    pub ttl: Duration,
+}
+
+impl QuoteLimits {
+    fn from_env(flow: QuoteFlow, defaults: QuoteLimits) -> Self {
+        let var = |name: &str| std::env::var(format!("{}_QUOTE_{}", flow.env_prefix(), name)).ok();
+        let mut limits = defaults;
+        if let Some(min) = var("MIN_NGN").and_then(|v| BigDecimal::from_str(&v).ok()) {
+            limits.min_amount_ngn = min;
+        }
+        if let Some(max) = var("MAX_NGN").and_then(|v| BigDecimal::from_str(&v).ok()) {
+            limits.max_amount_ngn = Some(max).filter(|m| *m > BigDecimal::zero());
+        }
+        if let Some(ttl) = var("TTL_SECONDS")
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|v| *v > 0)
+        {
+            limits.ttl = Duration::from_secs(ttl);
+        }
+        limits
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct QuoteConfig {
+    pub onramp: QuoteLimits,
+    pub offramp: QuoteLimits,
+    pub bill_payment: QuoteLimits,
+    /// Without a liquidity service, compare onramp quotes against the hot
+    /// wallet's cNGN balance
+    pub liquidity_check_enabled: bool,
+}
+
+impl Default for QuoteConfig {
+    fn default() -> Self {
+        Self {
+            onramp: QuoteLimits {
+                min_amount_ngn: BigDecimal::from(1000),
+                max_amount_ngn: None,
+                ttl: Duration::from_secs(180),
+            },
+            offramp: QuoteLimits {
+                min_amount_ngn: BigDecimal::from(1000),
+                max_amount_ngn: None,
+                ttl: Duration::from_secs(120),
+            },
+            bill_payment: QuoteLimits {
+                min_amount_ngn: BigDecimal::from(100),
+                max_amount_ngn: None,
+                ttl: Duration::from_secs(300),
+            },
+            liquidity_check_enabled: true,
+        }
+    }
+}
+
+impl QuoteConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            onramp: QuoteLimits::from_env(QuoteFlow::Onramp, defaults.onramp),
+            offramp: QuoteLimits::from_env(QuoteFlow::Offramp, defaults.offramp),
+            bill_payment: QuoteLimits::from_env(QuoteFlow::BillPayment, defaults.bill_payment),
+            liquidity_check_enabled: std::env::var("ONRAMP_LIQUIDITY_CHECK")
+                .unwrap_or_else(|_| "true".to_string())
+                .to_lowercase()
+                != "false",
+        }
+    }
+
+    pub fn limits(&self, flow: QuoteFlow) -> &QuoteLimits {
+        match flow {
+            QuoteFlow::Onramp => &self.onramp,
+            QuoteFlow::Offramp => &self.offramp,
+            QuoteFlow::BillPayment => &self.bill_payment,
+        }
+    }
+}
+
+/// API request for a quote. Exactly one of `send_amount` and
+/// `receive_amount` is given.
+#[derive(Debug, Clone, Deserialize)]
+pub struct QuoteRequest {
+    pub flow: QuoteFlow,
+    pub wallet_address: String,
+    /// Provider collecting (onramp) or paying out (offramp) the fiat
+    #[serde(default)]
+    pub provider: Option<ProviderName>,
+    #[serde(default)]
+    pub chain: Option<ChainType>,
+    /// Fiat side of the quote (defaults to NGN; bill payments are NGN only)
+    #[serde(default)]
+    pub fiat_currency: Option<String>,
+    /// Decimal amount the user sends
+    #[serde(default)]
+    pub send_amount: Option<String>,
+    /// Decimal amount the user wants to receive
+    #[serde(default)]
+    pub receive_amount: Option<String>,
+    /// Biller the bill payment is for
+    #[serde(default)]
+    pub biller: Option<String>,
+    #[serde(default)]
+    pub promo_code: Option<String>,
+    /// API key of the partner the request came through, from the request
+    /// headers
+    #[serde(skip)]
+    pub partner_api_key: Option<String>,
+}
+
+/// Quote as locked in Redis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredQuote {
+    pub quote_id: String,
+    pub flow: QuoteFlow,
+    pub quoted_side: QuoteSide,
+    pub wallet_address: String,
+    pub provider: Option<ProviderName>,
+    pub chain: ChainType,
+    pub send_currency: String,
+    pub send_amount: String,
+    pub receive_currency: String,
+    pub receive_amount: String,
+    pub rate: String,
+    pub rate_path: Vec<String>,
+    pub platform_fee: String,
+    pub provider_fee: String,
+    pub total_fee: String,
+    /// NGN equivalent of the amount sent, as checked against the limits
+    pub amount_ngn: String,
+    pub biller: Option<String>,
+    /// `conversion_audits` row tracking the quote
+    pub audit_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub status: String,
+    /// Fee rules behind the quoted platform fee; a promo's budget is spent
+    /// when the quote is used
+    #[serde(default)]
+    pub fee_rules: Vec<AppliedFeeRule>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuoteAmount {
+    pub amount: String,
+    pub currency: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuoteFees {
+    pub platform_fee: String,
+    pub provider_fee: String,
+    pub total_fee: String,
+    /// Fees are taken from the amount received
+    pub currency: String,
+    /// Promotions and partner pricing applied to the platform fee
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub applied_rules: Vec<AppliedFeeRule>,
+}
+
+/// API response for a quote
+#[derive(Debug, Clone, Serialize)]
+pub struct QuoteResponse {
+    pub quote_id: String,
+    pub flow: QuoteFlow,
+    pub quoted_side: QuoteSide,
+    pub expires_at: DateTime<Utc>,
+    pub expires_in_seconds: u64,
+    pub send: QuoteAmount,
+    pub receive: QuoteAmount,
+    pub fees: QuoteFees,
+    /// Send currency to receive currency rate applied, spread included
+    pub rate: String,
+    pub rate_path: Vec<String>,
+    pub amount_ngn: String,
+    pub provider: Option<ProviderName>,
+    pub chain: ChainType,
+    /// The wallet must add a cNGN trustline before an onramp can settle
+    pub trustline_required: bool,
+    /// The requested amount exceeded available liquidity and was reduced
+    pub repriced: bool,
+}
+
+/// One forward pricing of a send amount
+#[derive(Debug, Clone)]
+struct Pricing {
+    send_amount: BigDecimal,
+    rate: BigDecimal,
+    rate_path: Vec<String>,
+    platform_fee: BigDecimal,
+    provider_fee: BigDecimal,
+    receive_amount: BigDecimal,
+    applied_rules: Vec<AppliedFeeRule>,
+}
+
+impl Pricing {
+    fn total_fee(&self) -> BigDecimal {
+        &self.platform_fee + &self.provider_fee
+    }
+}
+
+/// Next send amount to try for a reverse quote: cover the remaining shortfall
+/// at `rate`, rounded up to the send currency's precision and always moving
+/// by at least one minor unit
+pub fn reverse_step(
+    send: &BigDecimal,
+    received: &BigDecimal,
+    target: &BigDecimal,
+    rate: &BigDecimal,
+    send_precision: i64,
+) -> BigDecimal {
+    let unit = BigDecimal::new(1.into(), send_precision);
+    if rate.is_zero() {
+        return send + unit;
+    }
+    let extra = ((target - received) / rate).with_scale_round(send_precision, RoundingMode::Up);
+    send + extra.max(unit)
+}
+
+pub struct QuoteService {
+    exchange_rate_service: Arc<ExchangeRateService>,
+    fee_service: Arc<FeeStructureService>,
+    stellar_client: StellarClient,
+    redis_cache: RedisCache,
+    cngn_issuer: String,
+    config: QuoteConfig,
+    liquidity: Option<Arc<LiquidityService>>,
+    audit: Option<Arc<ConversionAuditService>>,
+    wallet_risk: Option<Arc<WalletRiskRepository>>,
+    fee_rules: Option<Arc<FeeCalculationService>>,
+}
+
+impl QuoteService {
+    pub fn new(
+        exchange_rate_service: Arc<ExchangeRateService>,
+        fee_service: Arc<FeeStructureService>,
+        stellar_client: StellarClient,
+        redis_cache: RedisCache,
+        cngn_issuer: String,
+        config: QuoteConfig,
+    ) -> Self {
+        Self {
+            exchange_rate_service,
+            fee_service,
+            stellar_client,
+            redis_cache,
+            cngn_issuer,
+            config,
+            liquidity: None,
+            audit: None,
+            wallet_risk: None,
+            fee_rules: None,
+        }
+    }
+
+    /// Reserve hot wallet cNGN for onramp quotes and provider float for
+    /// offramp quotes instead of only comparing against current balances
+    pub fn with_liquidity(mut self, liquidity: Arc<LiquidityService>) -> Self {
+        self.liquidity = Some(liquidity);
+        self
+    }
+
+    /// Record every quote in `conversion_audits` and lock it there on use
+    pub fn with_audit(mut self, audit: Arc<ConversionAuditService>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Refuse quotes for wallets frozen after lost disputes
+    pub fn with_wallet_risk(mut self, wallet_risk: Arc<WalletRiskRepository>) -> Self {
+        self.wallet_risk = Some(wallet_risk);
+        self
+    }
+
+    /// Apply promotions, volume discounts and partner pricing from
+    /// `fee_rules` to the quoted platform fee
+    pub fn with_fee_rules(mut self, fees: Arc<FeeCalculationService>) -> Self {
+        self.fee_rules = Some(fees);
+        self
+    }
+
+    pub fn config(&self) -> &QuoteConfig {
+        &self.config
+    }
+
+    /// Create a quote
+    pub async fn create_quote(&self, request: QuoteRequest) -> Result<QuoteResponse, AppError> {
+        self.quote(request, false).await
+    }
+
+    /// Use a quote: mark it consumed, lock its conversion audit to
+    /// `transaction_id` and spend the budget of any promo it applied. The
+    /// inventory reserved for it stays held for `transaction_id` until the
+    /// payout consumes or releases it; without a transaction there is no
+    /// payout to wait for and it is consumed now. Returns `None` if the quote
+    /// is unknown, expired or was already used.
+    pub async fn consume_quote(
+        &self,
+        quote_id: &str,
+        transaction_id: Option<Uuid>,
+    ) -> Result<Option<StoredQuote>, AppError> {
+        let cache_key = QuoteKey::new(quote_id).to_string();
+        let Some(mut stored) =
+            <RedisCache as Cache<StoredQuote>>::get(&self.redis_cache, &cache_key)
+                .await
+                .map_err(|e| cache_error("load", e))?
+        else {
+            return Ok(None);
+        };
+        if stored.status != "pending" {
+            return Ok(None);
+        }
+
+        if stored.expires_at <= Utc::now() {
+            stored.status = "expired".to_string();
+            self.store(&cache_key, &stored).await?;
+            if let (Some(audit), Some(audit_id)) = (&self.audit, stored.audit_id) {
+                if let Err(e) = audit.mark_expired(audit_id).await {
+                    warn!(quote_id = %quote_id, error = %e, "failed to mark quote audit expired");
+                }
+            }
+            return Ok(None);
+        }
+
+        // The audit row is the lock; Redis alone cannot stop two instances
+        // consuming the same quote
+        if let (Some(audit), Some(audit_id)) = (&self.audit, stored.audit_id) {
+            if !audit.lock_quote(audit_id, transaction_id).await? {
+                return Ok(None);
+            }
+        }
+
+        stored.status = "consumed".to_string();
+        self.store(&cache_key, &stored).await?;
+        if let Some(liquidity) = &self.liquidity {
+            match transaction_id {
+                Some(transaction_id) => {
+                    let held_until =
+                        Utc::now() + chrono::Duration::hours(USED_QUOTE_RESERVATION_HOURS);
+                    liquidity
+                        .hand_over(quote_id, &transaction_id.to_string(), held_until)
+                        .await?
+                }
+                None => liquidity.consume(quote_id).await?,
+            }
+        }
+        if let (Some(fees), Some(_)) = (&self.fee_rules, transaction_id) {
+            // The quote is locked, so its price stands even if the promo ran
+            // out after it was quoted
+            if !fees.charge_quoted_rules(&stored.fee_rules).await? {
+                warn!(quote_id = %quote_id, "promo budget exhausted since quote; honouring quoted fee");
+            }
+        }
+        Ok(Some(stored))
+    }
+
+    async fn quote(
+        &self,
+        request: QuoteRequest,
+        repriced: bool,
+    ) -> Result<QuoteResponse, AppError> {
+        // 1. Validate wallet, chain and provider
+        let wallet_address = request.wallet_address.trim();
+        if wallet_address.is_empty() {
+            return Err(missing_field("wallet_address"));
+        }
+        let chain = request.chain.unwrap_or(ChainType::Stellar);
+        if chain != ChainType::Stellar {
+            return Err(AppError::new(AppErrorKind::Validation(
+                ValidationError::InvalidWalletAddress {
+                    address: wallet_address.to_string(),
+                    reason: format!("quotes are not available on {}", chain),
+                },
+            )));
+        }
+        if !is_valid_stellar_address(wallet_address) {
+            return Err(AppError::new(AppErrorKind::Validation(
+                ValidationError::InvalidWalletAddress {
+                    address: wallet_address.to_string(),
+                    reason: "Stellar wallet address is invalid or does not exist".to_string(),
+                },
+            )));
+        }
+        if let Some(wallet_risk) = &self.wallet_risk {
+            if let Some(freeze) = wallet_risk.active_freeze(wallet_address).await? {
+                return Err(AppError::new(AppErrorKind::Domain(DomainError::WalletFrozen {
+                    wallet_address: wallet_address.to_string(),
+                    reason: freeze.reason,
+                })));
+            }
+        }
+
+        let flow = request.flow;
+        let fiat_currency = request
+            .fiat_currency
+            .as_deref()
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .unwrap_or(DEFAULT_FIAT_CURRENCY)
+            .to_uppercase();
+        if flow == QuoteFlow::BillPayment && fiat_currency != DEFAULT_FIAT_CURRENCY {
+            return Err(AppError::new(AppErrorKind::Validation(
+                ValidationError::InvalidCurrency {
+                    currency: fiat_currency,
+                    reason: "bill payments are quoted in NGN".to_string(),
+                },
+            )));
+        }
+        let provider = match flow {
+            QuoteFlow::BillPayment => request.provider.clone(),
+            QuoteFlow::Onramp | QuoteFlow::Offramp => {
+                let provider = request
+                    .provider
+                    .clone()
+                    .ok_or_else(|| missing_field("provider"))?;
+                if !provider.supports_currency(&fiat_currency) {
+                    return Err(AppError::new(AppErrorKind::Validation(
+                        ValidationError::InvalidCurrency {
+                            currency: fiat_currency,
+                            reason: format!("{} does not support this currency", provider),
+                        },
+                    )));
+                }
+                Some(provider)
+            }
+        };
+
+        // 2. Refuse to quote on stale rates, then price the fixed side
+        let (send_currency, receive_currency) = flow.currencies(&fiat_currency);
+        let fee_context = FeeContext {
+            wallet_address: Some(wallet_address.to_string()),
+            promo_code: request.promo_code.clone(),
+            partner_api_key: request.partner_api_key.clone(),
+            transaction_id: None,
+        };
+        let cross = self
+            .exchange_rate_service
+            .get_cross_rate(&send_currency, &receive_currency)
+            .await?;
+        self.exchange_rate_service.ensure_fresh(&cross).await?;
+
+        let (quoted_side, pricing) = match (&request.send_amount, &request.receive_amount) {
+            (Some(raw), None) => {
+                let amount = currency::round_amount(&parse_amount(raw)?, &send_currency, true);
+                let pricing = self
+                    .price(
+                        flow,
+                        &send_currency,
+                        &receive_currency,
+                        &amount,
+                        &fee_context,
+                    )
+                    .await?;
+                (QuoteSide::Send, pricing)
+            }
+            (None, Some(raw)) => {
+                let target = currency::round_amount(&parse_amount(raw)?, &receive_currency, true);
+                let pricing = self
+                    .price_for_receive(
+                        flow,
+                        &send_currency,
+                        &receive_currency,
+                        &target,
+                        &fee_context,
+                    )
+                    .await?;
+                (QuoteSide::Receive, pricing)
+            }
+            (Some(_), Some(_)) => {
+                return Err(AppError::new(AppErrorKind::Validation(
+                    ValidationError::InvalidAmount {
+                        amount: String::new(),
+                        reason: "give either send_amount or receive_amount, not both".to_string(),
+                    },
+                )))
+            }
+            (None, None) => return Err(missing_field("send_amount")),
+        };
+        if pricing.receive_amount <= BigDecimal::zero() {
+            return Err(AppError::new(AppErrorKind::Domain(
+                DomainError::InvalidAmount {
+                    amount: pricing.send_amount.to_string(),
+                    reason: "fees exceed the amount".to_string(),
+                },
+            )));
+        }
+
+        // 3. Per-flow limits on the NGN value sent
+        let limits = self.config.limits(flow);
+        let amount_ngn = self
+            .ngn_equivalent(&pricing.send_amount, &send_currency)
+            .await?;
+        if amount_ngn < limits.min_amount_ngn {
+            return Err(AppError::new(AppErrorKind::Domain(
+                DomainError::AmountTooLow {
+                    amount: amount_ngn.to_string(),
+                    minimum: limits.min_amount_ngn.to_string(),
+                },
+            )));
+        }
+        if let Some(max) = limits
+            .max_amount_ngn
+            .as_ref()
+            .filter(|max| amount_ngn > **max)
+        {
+            return Err(AppError::new(AppErrorKind::Validation(
+                ValidationError::OutOfRange {
+                    field: "amount".to_string(),
+                    min: Some(limits.min_amount_ngn.to_string()),
+                    max: Some(max.to_string()),
+                },
+            )));
+        }
+
+        // 4. Onramp wallets need a cNGN trustline to be paid
+        let trustline_required = if flow == QuoteFlow::Onramp {
+            let trustline_status = CngnTrustlineManager::new(self.stellar_client.clone())
+                .check_trustline(wallet_address)
+                .await
+                .map_err(|e| match e {
+                    crate::chains::stellar::errors::StellarError::InvalidAddress { .. } => {
+                        AppError::new(AppErrorKind::Validation(
+                            ValidationError::InvalidWalletAddress {
+                                address: wallet_address.to_string(),
+                                reason: "Stellar wallet address is invalid or does not exist"
+                                    .to_string(),
+                            },
+                        ))
+                    }
+                    _ => AppError::from(e),
+                })?;
+            !trustline_status.has_trustline
+        } else {
+            false
+        };
+
+        // 5. Hold the inventory the quote pays out from for its lifetime
+        let quote_id = format!("q_{}", Uuid::new_v4().simple());
+        let created_at = Utc::now();
+        let expires_at = created_at
+            + chrono::Duration::from_std(limits.ttl).unwrap_or_else(|_| chrono::Duration::zero());
+        let reservation = match (&self.liquidity, flow, &provider) {
+            (Some(liquidity), QuoteFlow::Onramp, _) => Some(
+                liquidity
+                    .reserve_cngn(&quote_id, &pricing.receive_amount, expires_at)
+                    .await?,
+            ),
+            (Some(liquidity), QuoteFlow::Offramp, Some(provider)) => Some(
+                liquidity
+                    .reserve_provider_float(
+                        provider,
+                        &receive_currency,
+                        &quote_id,
+                        &pricing.receive_amount,
+                        expires_at,
+                    )
+                    .await?,
+            ),
+            (None, QuoteFlow::Onramp, _) if self.config.liquidity_check_enabled => {
+                self.check_cngn_liquidity(&pricing.receive_amount).await?;
+                None
+            }
+            _ => None,
+        };
+        if let Some(Reservation::Shortfall { available }) = reservation {
+            let liquidity = self
+                .liquidity
+                .as_ref()
+                .expect("reservation implies liquidity");
+            if repriced || liquidity.shortfall_policy() != ShortfallPolicy::Reprice {
+                return Err(liquidity::insufficient_liquidity(&pricing.receive_amount));
+            }
+            let repriced_request = match quoted_side {
+                QuoteSide::Send => liquidity::scale_to_available(
+                    &pricing.send_amount,
+                    &pricing.receive_amount,
+                    &available,
+                    currency::precision(&send_currency),
+                )
+                .map(|send| QuoteRequest {
+                    send_amount: Some(send.to_string()),
+                    ..request.clone()
+                }),
+                QuoteSide::Receive => {
+                    Some(currency::round_amount(&available, &receive_currency, true))
+                        .filter(|receive| *receive > BigDecimal::zero())
+                        .map(|receive| QuoteRequest {
+                            receive_amount: Some(receive.to_string()),
+                            ..request.clone()
+                        })
+                }
+            };
+            let Some(repriced_request) = repriced_request else {
+                return Err(liquidity::insufficient_liquidity(&pricing.receive_amount));
+            };
+            info!(
+                flow = %flow,
+                requested = %pricing.receive_amount,
+                available = %available,
+                "re-pricing quote to available liquidity"
+            );
+            return Box::pin(self.quote(repriced_request, true)).await;
+        }
+
+        // 6. Record the quote in the conversion audit trail
+        let audit_id = match &self.audit {
+            Some(audit) => {
+                let input = ConversionQuoteInput {
+                    user_id: None,
+                    // Wallets need not be registered to be quoted, so the
+                    // address is kept in metadata rather than the FK column
+                    wallet_address: None,
+                    transaction_id: None,
+                    from_currency: send_currency.clone(),
+                    to_currency: receive_currency.clone(),
+                    from_amount: pricing.send_amount.clone(),
+                    to_amount: pricing.receive_amount.clone(),
+                    rate: pricing.rate.clone(),
+                    fee_amount: pricing.total_fee(),
+                    fee_currency: Some(receive_currency.clone()),
+                    provider: provider.as_ref().map(|p| p.to_string()),
+                    metadata: serde_json::json!({
+                        "quote_id": quote_id,
+                        "flow": flow,
+                        "quoted_side": quoted_side,
+                        "wallet_address": wallet_address,
+                        "chain": chain,
+                        "biller": request.biller,
+                        "expires_at": expires_at,
+                    }),
+                };
+                match audit.create_quote(input).await {
+                    Ok(record) => Some(record.id),
+                    Err(e) => {
+                        self.release_reservation(&quote_id).await;
+                        return Err(e.into());
+                    }
+                }
+            }
+            None => None,
+        };
+
+        // 7. Lock the quote in Redis
+        let stored = StoredQuote {
+            quote_id: quote_id.clone(),
+            flow,
+            quoted_side,
+            wallet_address: wallet_address.to_string(),
+            provider: provider.clone(),
+            chain,
+            send_currency: send_currency.clone(),
+            send_amount: pricing.send_amount.to_string(),
+            receive_currency: receive_currency.clone(),
+            receive_amount: pricing.receive_amount.to_string(),
+            rate: pricing.rate.to_string(),
+            rate_path: pricing.rate_path.clone(),
+            platform_fee: pricing.platform_fee.to_string(),
+            provider_fee: pricing.provider_fee.to_string(),
+            total_fee: pricing.total_fee().to_string(),
+            amount_ngn: amount_ngn.to_string(),
+            biller: request.biller.clone(),
+            audit_id,
+            created_at,
+            expires_at,
+            status: "pending".to_string(),
+            fee_rules: pricing.applied_rules.clone(),
+        };
+        let cache_key = QuoteKey::new(&quote_id).to_string();
+        if let Err(e) = self.store(&cache_key, &stored).await {
+            self.release_reservation(&quote_id).await;
+            if let (Some(audit), Some(audit_id)) = (&self.audit, audit_id) {
+                if let Err(audit_err) = audit
+                    .mark_failed(audit_id, "quote could not be stored")
+                    .await
+                {
+                    warn!(quote_id = %quote_id, error = %audit_err, "failed to mark quote audit failed");
+                }
+            }
+            return Err(e);
+        }
+        debug!(quote_id = %quote_id, flow = %flow, "Stored quote in Redis");
+
+        Ok(QuoteResponse {
+            quote_id,
+            flow,
+            quoted_side,
+            expires_at,
+            expires_in_seconds: limits.ttl.as_secs(),
+            send: QuoteAmount {
+                amount: pricing.send_amount.to_string(),
+                currency: send_currency,
+            },
+            receive: QuoteAmount {
+                amount: pricing.receive_amount.to_string(),
+                currency: receive_currency.clone(),
+            },
+            fees: QuoteFees {
+                platform_fee: pricing.platform_fee.to_string(),
+                provider_fee: pricing.provider_fee.to_string(),
+                total_fee: pricing.total_fee().to_string(),
+                currency: receive_currency,
+                applied_rules: pricing.applied_rules,
+            },
+            rate: pricing.rate.to_string(),
+            rate_path: pricing.rate_path,
+            amount_ngn: amount_ngn.to_string(),
+            provider,
+            chain,
+            trustline_required,
+            repriced,
+        })
+    }
+
+    /// Convert `send_amount` and take the flow's fees, after fee rules, from
+    /// the result
+    async fn price(
+        &self,
+        flow: QuoteFlow,
+        send_currency: &str,
+        receive_currency: &str,
+        send_amount: &BigDecimal,
+        fee_context: &FeeContext,
+    ) -> Result<Pricing, AppError> {
+        let conversion = self
+            .exchange_rate_service
+            .calculate_conversion(ConversionRequest {
+                from_currency: send_currency.to_string(),
+                to_currency: receive_currency.to_string(),
+                amount: send_amount.clone(),
+                direction: flow.direction(),
+            })
+            .await
+            .map_err(AppError::from)?;
+        let gross =
+            BigDecimal::from_str(&conversion.gross_amount).unwrap_or_else(|_| send_amount.clone());
+
+        let platform_fee = BigDecimal::from_str(&conversion.fees.platform_fee)
+            .unwrap_or_else(|_| BigDecimal::zero());
+        let provider_fee = BigDecimal::from_str(&conversion.fees.provider_fee)
+            .unwrap_or_else(|_| BigDecimal::zero());
+        // Without conversion fees configured, fall back to the flow's own fee
+        // types. They apply to the gross amount received.
+        let (platform_fee, provider_fee) = if platform_fee.is_zero() && provider_fee.is_zero() {
+            self.calculate_flow_fees(flow, &gross, receive_currency)
+                .await?
+        } else {
+            (platform_fee, provider_fee)
+        };
+        let (platform_fee, applied_rules) = match &self.fee_rules {
+            Some(fees) => {
+                let (fee, applied) = fees
+                    .apply_rules(flow.as_str(), &gross, &platform_fee, fee_context)
+                    .await?;
+                (
+                    currency::round_amount(&fee, receive_currency, false),
+                    applied,
+                )
+            }
+            None => (platform_fee, Vec::new()),
+        };
+
+        let rate = BigDecimal::from_str(&conversion.applied_rate)
+            .or_else(|_| BigDecimal::from_str(&conversion.base_rate))
+            .unwrap_or_else(|_| BigDecimal::from(1));
+        let receive_amount = currency::round_amount(
+            &(&gross - &platform_fee - &provider_fee),
+            receive_currency,
+            true,
+        );
+
+        Ok(Pricing {
+            send_amount: send_amount.clone(),
+            rate,
+            rate_path: conversion.rate_path,
+            platform_fee,
+            provider_fee,
+            receive_amount,
+            applied_rules,
+        })
+    }
+
+    /// Smallest send amount whose pricing delivers at least `target`. Fees
+    /// can be tiered, so the send amount is found by repricing rather than
+    /// by inverting the rate.
+    async fn price_for_receive(
+        &self,
+        flow: QuoteFlow,
+        send_currency: &str,
+        receive_currency: &str,
+        target: &BigDecimal,
+        fee_context: &FeeContext,
+    ) -> Result<Pricing, AppError> {
+        let send_precision = currency::precision(send_currency);
+        let initial_rate = self
+            .exchange_rate_service
+            .get_cross_rate(send_currency, receive_currency)
+            .await?
+            .rate;
+        let mut send = if initial_rate.is_zero() {
+            target.clone()
+        } else {
+            (target / &initial_rate).with_scale_round(send_precision, RoundingMode::Up)
+        };
+
+        for _ in 0..MAX_REVERSE_ITERATIONS {
+            let pricing = self
+                .price(flow, send_currency, receive_currency, &send, fee_context)
+                .await?;
+            if pricing.receive_amount >= *target {
+                return Ok(pricing);
+            }
+            send = reverse_step(
+                &send,
+                &pricing.receive_amount,
+                target,
+                &pricing.rate,
+                send_precision,
+            );
+        }
+
+        Err(AppError::new(AppErrorKind::Domain(
+            DomainError::InvalidAmount {
+                amount: target.to_string(),
+                reason: "no send amount delivers this receive amount".to_string(),
+            },
+        )))
+    }
+
+    /// NGN value of an amount; cNGN is pegged 1:1 and other currencies go
+    /// through cross rates
+    async fn ngn_equivalent(
+        &self,
+        amount: &BigDecimal,
+        currency_code: &str,
+    ) -> Result<BigDecimal, AppError> {
+        if currency_code.eq_ignore_ascii_case(DEFAULT_FIAT_CURRENCY)
+            || currency_code.eq_ignore_ascii_case(CNGN)
+        {
+            return Ok(amount.clone());
+        }
+        let cross = self
+            .exchange_rate_service
+            .get_cross_rate(currency_code, DEFAULT_FIAT_CURRENCY)
+            .await?;
+        Ok(currency::round_amount(
+            &(amount * cross.rate),
+            DEFAULT_FIAT_CURRENCY,
+            true,
+        ))
+    }
+
+    /// `{flow}_platform` and `{flow}_provider` fees, or a `{flow}` fee split
+    /// 20/80 between platform and provider
+    async fn calculate_flow_fees(
+        &self,
+        flow: QuoteFlow,
+        amount: &BigDecimal,
+        currency_code: &str,
+    ) -> Result<(BigDecimal, BigDecimal), AppError> {
+        let platform_fee = self
+            .fee(&format!("{}_platform", flow), amount, currency_code)
+            .await?;
+        let provider_fee = self
+            .fee(&format!("{}_provider", flow), amount, currency_code)
+            .await?;
+        if !platform_fee.is_zero() || !provider_fee.is_zero() {
+            return Ok((platform_fee, provider_fee));
+        }
+
+        let total_fee = self.fee(flow.as_str(), amount, currency_code).await?;
+        let platform = currency::round_amount(
+            &(&total_fee * BigDecimal::from_str("0.2").unwrap()),
+            currency_code,
+            false,
+        );
+        let provider = &total_fee - &platform;
+        Ok((platform, provider))
+    }
+
+    async fn fee(
+        &self,
+        fee_type: &str,
+        amount: &BigDecimal,
+        currency_code: &str,
+    ) -> Result<BigDecimal, AppError> {
+        let fee = self
+            .fee_service
+            .calculate_fee(FeeCalculationInput {
+                fee_type: fee_type.to_string(),
+                amount: amount.clone(),
+                currency: Some(fee_currency(currency_code).to_string()),
+                at_time: None,
+            })
+            .await
+            .map_err(|e| {
+                AppError::new(AppErrorKind::Infrastructure(
+                    InfrastructureError::Database {
+                        message: e.to_string(),
+                        is_retryable: true,
+                    },
+                ))
+            })?;
+        Ok(fee
+            .map(|r| currency::round_amount(&r.fee, currency_code, false))
+            .unwrap_or_else(BigDecimal::zero))
+    }
+
+    async fn check_cngn_liquidity(&self, amount_cngn: &BigDecimal) -> Result<(), AppError> {
+        let distribution_account = std::env::var("CNGN_DISTRIBUTION_ACCOUNT")
+            .or_else(|_| std::env::var("CNGN_ISSUER_ADDRESS"))
+            .or_else(|_| std::env::var("CNGN_ISSUER_MAINNET"))
+            .unwrap_or_else(|_| self.cngn_issuer.clone());
+
+        let account = self
+            .stellar_client
+            .get_account(&distribution_account)
+            .await
+            .map_err(|e| {
+                info!(
+                    "Liquidity check skipped: could not fetch distribution account: {}",
+                    e
+                );
+                AppError::new(AppErrorKind::External(
+                    crate::error::ExternalError::Blockchain {
+                        message: e.to_string(),
+                        is_retryable: true,
+                    },
+                ))
+            })?;
+
+        let available = extract_cngn_balance(&account.balances, Some(&self.cngn_issuer))
+            .and_then(|s| BigDecimal::from_str(&s).ok())
+            .unwrap_or_else(BigDecimal::zero);
+        if available < *amount_cngn {
+            return Err(liquidity::insufficient_liquidity(amount_cngn));
+        }
+        Ok(())
+    }
+
+    async fn store(&self, cache_key: &str, stored: &StoredQuote) -> Result<(), AppError> {
+        let ttl = (stored.expires_at - Utc::now())
+            .to_std()
+            .unwrap_or_default()
+            + Duration::from_secs(EXPIRED_QUOTE_RETENTION_SECS);
+        self.redis_cache
+            .set(cache_key, stored, Some(ttl))
+            .await
+            .map_err(|e| cache_error("store", e))
+    }
+
+    async fn release_reservation(&self, quote_id: &str) {
+        if let Some(liquidity) = &self.liquidity {
+            if let Err(e) = liquidity.release(quote_id).await {
+                warn!(quote_id = %quote_id, error = %e, "failed to release reservation");
+            }
+        }
+    }
+}
+
+/// Fee structures are configured in NGN for both NGN and cNGN
+fn fee_currency(currency_code: &str) -> &str {
+    if currency_code.eq_ignore_ascii_case(CNGN) {
+        DEFAULT_FIAT_CURRENCY
+    } else {
+        currency_code
+    }
+}
+
+fn parse_amount(raw: &str) -> Result<BigDecimal, ValidationError> {
+    currency::parse_positive_amount(raw).ok_or_else(|| ValidationError::InvalidAmount {
+        amount: raw.to_string(),
+        reason: "amount must be a positive decimal number".to_string(),
+    })
+}
+
+fn missing_field(field: &str) -> AppError {
+    AppError::new(AppErrorKind::Validation(ValidationError::MissingField {
+        field: field.to_string(),
+    }))
+}
+
+fn cache_error(action: &str, e: crate::cache::error::CacheError) -> AppError {
+    AppError::new(AppErrorKind::Infrastructure(InfrastructureError::Cache {
+        message: format!("Failed to {} quote: {}", action, e),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_limits() {
+        let config = QuoteConfig::default();
+        assert_eq!(
+            config.limits(QuoteFlow::Onramp).min_amount_ngn,
+            BigDecimal::from(1000)
+        );
+        assert_eq!(
+            config.limits(QuoteFlow::Onramp).ttl,
+            Duration::from_secs(180)
+        );
+        assert!(config
+            .limits(QuoteFlow::BillPayment)
+            .max_amount_ngn
+            .is_none());
+    }
+
+    #[test]
+    fn test_flow_currencies() {
+        assert_eq!(
+            QuoteFlow::Onramp.currencies("KES"),
+            ("KES".to_string(), "cNGN".to_string())
+        );
+        assert_eq!(
+            QuoteFlow::Offramp.currencies("NGN"),
+            ("cNGN".to_string(), "NGN".to_string())
+        );
+        assert_eq!(
+            QuoteFlow::BillPayment.direction(),
+            ConversionDirection::Sell
+        );
+    }
+
+    #[test]
+    fn test_request_deserializes_shared_enums() {
+        let request: QuoteRequest = serde_json::from_value(serde_json::json!({
+            "flow": "bill_payment",
+            "wallet_address": "GABC",
+            "provider": "paystack",
+            "chain": "stellar",
+            "receive_amount": "5000"
+        }))
+        .unwrap();
+        assert_eq!(request.flow, QuoteFlow::BillPayment);
+        assert_eq!(request.provider, Some(ProviderName::Paystack));
+        assert_eq!(request.chain, Some(ChainType::Stellar));
+        assert!(request.send_amount.is_none());
+    }
+
+    #[test]
+    fn test_reverse_step_covers_shortfall() {
+        let send = BigDecimal::from(1000);
+        let received = BigDecimal::from_str("985.00").unwrap();
+        let target = BigDecimal::from(1000);
+        let rate = BigDecimal::from(1);
+        let next = reverse_step(&send, &received, &target, &rate, 2);
+        assert_eq!(next, BigDecimal::from_str("1015.00").unwrap());
+
+        // Rounding up means a shortfall of a fraction of a unit still moves
+        let next = reverse_step(
+            &send,
+            &BigDecimal::from_str("999.999").unwrap(),
+            &target,
+            &rate,
+            2,
+        );
+        assert_eq!(next, BigDecimal::from_str("1000.01").unwrap());
+    }
+}
@@ -2,15 +2,17 @@
 //!
 //! Implements different rate providers:
 //! - FixedRateProvider: For cNGN 1:1 peg with NGN
-//! - ExternalApiProvider: For future external API integration
+//! - AggregatedRateProvider: Combines several sources (see `rate_sources` for
+//!   the concrete API and Stellar DEX adapters)
 
 use super::exchange_rate::{ExchangeRateError, ExchangeRateResult, RateData, RateProvider};
 use async_trait::async_trait;
 use bigdecimal::BigDecimal;
 use chrono::Utc;
+use futures::future::join_all;
 #[cfg(test)]
 use std::str::FromStr;
-use tracing::debug;
+use tracing::{info, warn};
 
 /// Fixed rate provider for cNGN/NGN 1:1 peg
 pub struct FixedRateProvider {
@@ -75,78 +77,9 @@ impl RateProvider for FixedRateProvider {
     fn name(&self) -> &str {
         "FixedRateProvider"
     }
-}
-
-/// External API provider for fetching rates from external sources
-/// This is a placeholder for future implementation with CoinGecko, Fixer.io, etc.
-pub struct ExternalApiProvider {
-    api_url: String,
-    api_key: Option<String>,
-    supported_pairs: Vec<(String, String)>,
-    timeout_seconds: u64,
-}
 
-impl ExternalApiProvider {
-    pub fn new(api_url: String, api_key: Option<String>) -> Self {
-        Self {
-            api_url,
-            api_key,
-            supported_pairs: Vec::new(),
-            timeout_seconds: 10,
-        }
-    }
-
-    pub fn with_timeout(mut self, timeout_seconds: u64) -> Self {
-        self.timeout_seconds = timeout_seconds;
-        self
-    }
-
-    pub fn add_supported_pair(mut self, from: String, to: String) -> Self {
-        self.supported_pairs.push((from, to));
-        self
-    }
-}
-
-#[async_trait]
-impl RateProvider for ExternalApiProvider {
-    async fn fetch_rate(&self, from: &str, to: &str) -> ExchangeRateResult<RateData> {
-        // Check if this is a supported pair
-        let is_supported = self
-            .supported_pairs
-            .iter()
-            .any(|(f, t)| f == from && t == to);
-
-        if !is_supported {
-            return Err(ExchangeRateError::RateNotFound {
-                from: from.to_string(),
-                to: to.to_string(),
-            });
-        }
-
-        // TODO: Implement actual API call to external service
-        // For now, return an error indicating it's not implemented
-        debug!(
-            "ExternalApiProvider: Would fetch rate from {} for {} -> {}",
-            self.api_url, from, to
-        );
-
-        Err(ExchangeRateError::ProviderError(
-            "External API provider not yet implemented".to_string(),
-        ))
-    }
-
-    fn get_supported_pairs(&self) -> Vec<(String, String)> {
-        self.supported_pairs.clone()
-    }
-
-    async fn is_healthy(&self) -> bool {
-        // TODO: Implement health check by pinging the API
-        // For now, return false since it's not implemented
-        false
-    }
-
-    fn name(&self) -> &str {
-        "ExternalApiProvider"
+    fn is_fixed(&self) -> bool {
+        true
     }
 }
 
@@ -161,6 +94,40 @@ pub enum AggregationStrategy {
     Average,
     Median,
     First,
+    /// Median of the sources within `max_deviation_bps` of the overall
+    /// median; fails unless at least `min_sources` remain
+    OutlierRejecting {
+        max_deviation_bps: u32,
+        min_sources: usize,
+    },
+}
+
+impl AggregationStrategy {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AggregationStrategy::Average => "average",
+            AggregationStrategy::Median => "median",
+            AggregationStrategy::First => "first",
+            AggregationStrategy::OutlierRejecting { .. } => "outlier_rejecting",
+        }
+    }
+}
+
+/// A single source's rate fed into aggregation
+#[derive(Debug, Clone)]
+pub struct SourceQuote {
+    pub source: String,
+    pub rate: BigDecimal,
+}
+
+/// Result of aggregating source quotes
+#[derive(Debug, Clone)]
+pub struct AggregatedRate {
+    pub rate: BigDecimal,
+    /// Sources whose quotes went into `rate`
+    pub contributors: Vec<String>,
+    /// Sources discarded as outliers
+    pub rejected: Vec<String>,
 }
 
 impl AggregatedRateProvider {
@@ -175,6 +142,77 @@ impl AggregatedRateProvider {
         self.providers.push(provider);
         self
     }
+
+    pub fn provider_count(&self) -> usize {
+        self.providers.len()
+    }
+
+    /// Combine source quotes according to the configured strategy
+    pub fn aggregate(&self, quotes: Vec<SourceQuote>) -> ExchangeRateResult<AggregatedRate> {
+        if quotes.is_empty() {
+            return Err(ExchangeRateError::ProviderError(
+                "All providers failed".to_string(),
+            ));
+        }
+
+        let all_sources = || quotes.iter().map(|q| q.source.clone()).collect();
+        match self.aggregation_strategy {
+            AggregationStrategy::First => Ok(AggregatedRate {
+                rate: quotes[0].rate.clone(),
+                contributors: vec![quotes[0].source.clone()],
+                rejected: Vec::new(),
+            }),
+            AggregationStrategy::Average => {
+                let sum: BigDecimal = quotes.iter().map(|q| &q.rate).sum();
+                Ok(AggregatedRate {
+                    rate: sum / BigDecimal::from(quotes.len() as u64),
+                    contributors: all_sources(),
+                    rejected: Vec::new(),
+                })
+            }
+            AggregationStrategy::Median => Ok(AggregatedRate {
+                rate: median(quotes.iter().map(|q| q.rate.clone()).collect()),
+                contributors: all_sources(),
+                rejected: Vec::new(),
+            }),
+            AggregationStrategy::OutlierRejecting {
+                max_deviation_bps,
+                min_sources,
+            } => {
+                let center = median(quotes.iter().map(|q| q.rate.clone()).collect());
+                let limit =
+                    &center * BigDecimal::from(max_deviation_bps) / BigDecimal::from(10_000);
+                let (kept, rejected): (Vec<SourceQuote>, Vec<SourceQuote>) = quotes
+                    .into_iter()
+                    .partition(|q| (&q.rate - &center).abs() <= limit);
+
+                if kept.len() < min_sources.max(1) {
+                    return Err(ExchangeRateError::ProviderError(format!(
+                        "quorum not met: {} of {} required sources within {} bps of the median",
+                        kept.len(),
+                        min_sources,
+                        max_deviation_bps
+                    )));
+                }
+
+                Ok(AggregatedRate {
+                    rate: median(kept.iter().map(|q| q.rate.clone()).collect()),
+                    contributors: kept.into_iter().map(|q| q.source).collect(),
+                    rejected: rejected.into_iter().map(|q| q.source).collect(),
+                })
+            }
+        }
+    }
+}
+
+fn median(mut rates: Vec<BigDecimal>) -> BigDecimal {
+    rates.sort();
+    let mid = rates.len() / 2;
+    if rates.len().is_multiple_of(2) {
+        (&rates[mid - 1] + &rates[mid]) / BigDecimal::from(2)
+    } else {
+        rates[mid].clone()
+    }
 }
 
 #[async_trait]
@@ -186,55 +224,60 @@ impl RateProvider for AggregatedRateProvider {
             ));
         }
 
-        let mut rates = Vec::new();
-        let mut last_error = None;
-
-        // Fetch rates from all providers
-        for provider in &self.providers {
+        // Fetch rates from all healthy providers concurrently
+        let results = join_all(self.providers.iter().map(|provider| async move {
             if provider.is_healthy().await {
-                match provider.fetch_rate(from, to).await {
-                    Ok(rate_data) => rates.push(rate_data),
-                    Err(e) => {
-                        last_error = Some(e);
-                        continue;
-                    }
-                }
+                Some(provider.fetch_rate(from, to).await)
+            } else {
+                None
+            }
+        }))
+        .await;
+
+        let mut quotes = Vec::new();
+        let mut last_error = None;
+        for result in results.into_iter().flatten() {
+            match result {
+                Ok(rate_data) => quotes.push(SourceQuote {
+                    source: rate_data.source,
+                    rate: rate_data.base_rate,
+                }),
+                Err(e) => last_error = Some(e),
             }
         }
 
-        if rates.is_empty() {
+        if quotes.is_empty() {
             return Err(last_error.unwrap_or_else(|| {
                 ExchangeRateError::ProviderError("All providers failed".to_string())
             }));
         }
 
-        // Aggregate rates based on strategy
-        let aggregated_rate = match self.aggregation_strategy {
-            AggregationStrategy::First => rates[0].base_rate.clone(),
-            AggregationStrategy::Average => {
-                let sum: BigDecimal = rates.iter().map(|r| &r.base_rate).sum();
-                sum / BigDecimal::from(rates.len() as u64)
-            }
-            AggregationStrategy::Median => {
-                let mut sorted_rates: Vec<BigDecimal> =
-                    rates.iter().map(|r| r.base_rate.clone()).collect();
-                sorted_rates.sort();
-                let mid = sorted_rates.len() / 2;
-                if sorted_rates.len() % 2 == 0 {
-                    (&sorted_rates[mid - 1] + &sorted_rates[mid]) / BigDecimal::from(2)
-                } else {
-                    sorted_rates[mid].clone()
-                }
-            }
-        };
+        let aggregated = self.aggregate(quotes)?;
+        if !aggregated.rejected.is_empty() {
+            warn!(
+                pair = %format!("{}/{}", from, to),
+                rejected = ?aggregated.rejected,
+                "discarded outlier rate sources"
+            );
+        }
+        info!(
+            pair = %format!("{}/{}", from, to),
+            rate = %aggregated.rate,
+            contributors = ?aggregated.contributors,
+            "aggregated rate"
+        );
 
         Ok(RateData {
             currency_pair: format!("{}/{}", from, to),
-            base_rate: aggregated_rate.clone(),
-            buy_rate: aggregated_rate.clone(),
-            sell_rate: aggregated_rate.clone(),
+            base_rate: aggregated.rate.clone(),
+            buy_rate: aggregated.rate.clone(),
+            sell_rate: aggregated.rate.clone(),
             spread: BigDecimal::from(0),
-            source: format!("aggregated_{:?}", self.aggregation_strategy),
+            source: format!(
+                "aggregated_{}:{}",
+                self.aggregation_strategy.name(),
+                aggregated.contributors.join(",")
+            ),
             last_updated: Utc::now(),
         })
     }
@@ -272,6 +315,7 @@ impl RateProvider for AggregatedRateProvider {
 pub struct MockRateProvider {
     rate: BigDecimal,
     healthy: bool,
+    source: String,
 }
 
 #[cfg(test)]
@@ -280,9 +324,15 @@ impl MockRateProvider {
         Self {
             rate: BigDecimal::from_str(&rate.to_string()).unwrap(),
             healthy: true,
+            source: "mock".to_string(),
         }
     }
 
+    pub fn with_source(mut self, source: &str) -> Self {
+        self.source = source.to_string();
+        self
+    }
+
     pub fn with_health(mut self, healthy: bool) -> Self {
         self.healthy = healthy;
         self
@@ -299,7 +349,7 @@ impl RateProvider for MockRateProvider {
             buy_rate: self.rate.clone(),
             sell_rate: self.rate.clone(),
             spread: BigDecimal::from(0),
-            source: "mock".to_string(),
+            source: self.source.clone(),
             last_updated: Utc::now(),
         })
     }
@@ -374,4 +424,41 @@ mod tests {
         let expected = BigDecimal::from_str("1600").unwrap();
         assert_eq!(rate.base_rate, expected);
     }
+
+    #[tokio::test]
+    async fn test_outlier_rejection_discards_deviating_source() {
+        let aggregated = AggregatedRateProvider::new(AggregationStrategy::OutlierRejecting {
+            max_deviation_bps: 200,
+            min_sources: 2,
+        })
+        .add_provider(Box::new(MockRateProvider::new(1468.0).with_source("a")))
+        .add_provider(Box::new(MockRateProvider::new(1470.0).with_source("b")))
+        .add_provider(Box::new(MockRateProvider::new(1600.0).with_source("c")));
+
+        let rate = aggregated.fetch_rate("USD", "NGN").await.unwrap();
+        assert_eq!(rate.base_rate, BigDecimal::from(1469));
+        assert_eq!(rate.source, "aggregated_outlier_rejecting:a,b");
+    }
+
+    #[test]
+    fn test_outlier_rejection_requires_quorum() {
+        let aggregated = AggregatedRateProvider::new(AggregationStrategy::OutlierRejecting {
+            max_deviation_bps: 100,
+            min_sources: 2,
+        });
+        let quote = |source: &str, rate: i64| SourceQuote {
+            source: source.to_string(),
+            rate: BigDecimal::from(rate),
+        };
+
+        let result =
+            aggregated.aggregate(vec![quote("a", 1400), quote("b", 1500), quote("c", 1600)]);
+        assert!(matches!(result, Err(ExchangeRateError::ProviderError(_))));
+
+        let result = aggregated
+            .aggregate(vec![quote("a", 1500), quote("b", 1505), quote("c", 1700)])
+            .unwrap();
+        assert_eq!(result.contributors, vec!["a", "b"]);
+        assert_eq!(result.rejected, vec!["c"]);
+    }
 }
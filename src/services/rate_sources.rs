@@ -0,0 +1,789 @@
+//! Concrete external rate sources
+//!
+//! Adapters for the FX and crypto rate APIs we pull from, plus a Stellar DEX
+//! source that quotes the orderbook mid-price from Horizon. Each adapter
+//! parses its API's response separately from the HTTP call so the parsing
+//! can be checked against recorded responses. `RateSourcesConfig` combines
+//! the enabled sources into an outlier-rejecting `AggregatedRateProvider`.
+
+use super::exchange_rate::{ExchangeRateError, ExchangeRateResult, RateData, RateProvider};
+use super::rate_providers::{AggregatedRateProvider, AggregationStrategy};
+use crate::chains::stellar::client::StellarClient;
+use crate::chains::stellar::types::{HorizonOrderBook, HorizonOrderBookLevel, StellarAsset};
+use async_trait::async_trait;
+use bigdecimal::{BigDecimal, Zero};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+const DEFAULT_TIMEOUT_SECONDS: u64 = 10;
+const DEFAULT_MAX_DEVIATION_BPS: u32 = 200;
+const DEFAULT_MIN_SOURCES: usize = 2;
+const DEFAULT_MAX_DEX_SPREAD_BPS: u32 = 500;
+const ORDER_BOOK_DEPTH: u32 = 5;
+
+const EXCHANGERATE_API_DEFAULT_URL: &str = "https://open.er-api.com";
+const OPEN_EXCHANGE_RATES_DEFAULT_URL: &str = "https://openexchangerates.org";
+const COINGECKO_DEFAULT_URL: &str = "https://api.coingecko.com";
+
+/// Pairs a source answers for; an unlisted pair is `RateNotFound` so the
+/// aggregator and the exchange rate service move on to other providers
+fn supports(pairs: &[(String, String)], from: &str, to: &str) -> bool {
+    pairs
+        .iter()
+        .any(|(f, t)| f.eq_ignore_ascii_case(from) && t.eq_ignore_ascii_case(to))
+}
+
+fn not_found(from: &str, to: &str) -> ExchangeRateError {
+    ExchangeRateError::RateNotFound {
+        from: from.to_string(),
+        to: to.to_string(),
+    }
+}
+
+fn parse_number(value: &serde_json::Number, source: &str) -> ExchangeRateResult<BigDecimal> {
+    BigDecimal::from_str(&value.to_string()).map_err(|e| {
+        ExchangeRateError::ProviderError(format!("{} returned invalid rate: {}", source, e))
+    })
+}
+
+fn positive(rate: BigDecimal, source: &str) -> ExchangeRateResult<BigDecimal> {
+    if rate > 0 {
+        Ok(rate)
+    } else {
+        Err(ExchangeRateError::InvalidRate(format!(
+            "{} returned non-positive rate {}",
+            source, rate
+        )))
+    }
+}
+
+fn timestamp(unix: i64) -> DateTime<Utc> {
+    Utc.timestamp_opt(unix, 0).single().unwrap_or_else(Utc::now)
+}
+
+fn rate_data(from: &str, to: &str, rate: BigDecimal, source: &str, at: DateTime<Utc>) -> RateData {
+    RateData {
+        currency_pair: format!("{}/{}", from, to),
+        base_rate: rate.clone(),
+        buy_rate: rate.clone(),
+        sell_rate: rate,
+        spread: BigDecimal::zero(),
+        source: source.to_string(),
+        last_updated: at,
+    }
+}
+
+fn http_client(timeout: Duration) -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .user_agent("Aframp-Backend/1.0")
+        .build()
+        .unwrap_or_default()
+}
+
+async fn get_json<T: for<'de> Deserialize<'de>>(
+    request: reqwest::RequestBuilder,
+    source: &str,
+) -> ExchangeRateResult<T> {
+    let response = request.send().await.map_err(|e| {
+        ExchangeRateError::ProviderError(format!("{} request failed: {}", source, e))
+    })?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(ExchangeRateError::ProviderError(format!(
+            "{} returned HTTP {}",
+            source, status
+        )));
+    }
+    response.json::<T>().await.map_err(|e| {
+        ExchangeRateError::ProviderError(format!("{} returned malformed body: {}", source, e))
+    })
+}
+
+// ---------------------------------------------------------------------------
+// ExchangeRate-API (https://www.exchangerate-api.com)
+// ---------------------------------------------------------------------------
+
+/// `GET /v6/latest/{BASE}` (open access) or `/v6/{key}/latest/{BASE}`
+#[derive(Debug, Deserialize)]
+pub struct ExchangeRateApiResponse {
+    pub result: String,
+    #[serde(rename = "error-type")]
+    pub error_type: Option<String>,
+    pub base_code: Option<String>,
+    pub time_last_update_unix: Option<i64>,
+    #[serde(default)]
+    pub rates: HashMap<String, serde_json::Number>,
+}
+
+/// Fiat rates from ExchangeRate-API, quoted with `from` as the base currency
+pub struct ExchangeRateApiProvider {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+    supported_pairs: Vec<(String, String)>,
+}
+
+impl ExchangeRateApiProvider {
+    pub const SOURCE: &'static str = "exchangerate_api";
+
+    pub fn new(base_url: String, api_key: Option<String>, timeout: Duration) -> Self {
+        Self {
+            client: http_client(timeout),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            supported_pairs: Vec::new(),
+        }
+    }
+
+    pub fn with_pairs(mut self, pairs: Vec<(String, String)>) -> Self {
+        self.supported_pairs = pairs;
+        self
+    }
+
+    pub fn parse(
+        response: ExchangeRateApiResponse,
+        from: &str,
+        to: &str,
+    ) -> ExchangeRateResult<RateData> {
+        if response.result != "success" {
+            return Err(ExchangeRateError::ProviderError(format!(
+                "{} error: {}",
+                Self::SOURCE,
+                response.error_type.as_deref().unwrap_or("unknown")
+            )));
+        }
+        if !response
+            .base_code
+            .as_deref()
+            .is_some_and(|base| base.eq_ignore_ascii_case(from))
+        {
+            return Err(ExchangeRateError::ProviderError(format!(
+                "{} answered for base {:?}, expected {}",
+                Self::SOURCE,
+                response.base_code,
+                from
+            )));
+        }
+        let rate = response
+            .rates
+            .get(&to.to_uppercase())
+            .ok_or_else(|| not_found(from, to))?;
+        let rate = positive(parse_number(rate, Self::SOURCE)?, Self::SOURCE)?;
+        let at = response
+            .time_last_update_unix
+            .map(timestamp)
+            .unwrap_or_else(Utc::now);
+        Ok(rate_data(from, to, rate, Self::SOURCE, at))
+    }
+}
+
+#[async_trait]
+impl RateProvider for ExchangeRateApiProvider {
+    async fn fetch_rate(&self, from: &str, to: &str) -> ExchangeRateResult<RateData> {
+        if !supports(&self.supported_pairs, from, to) {
+            return Err(not_found(from, to));
+        }
+        let base = from.to_uppercase();
+        let url = match &self.api_key {
+            Some(key) => format!("{}/v6/{}/latest/{}", self.base_url, key, base),
+            None => format!("{}/v6/latest/{}", self.base_url, base),
+        };
+        debug!(source = Self::SOURCE, from = %from, to = %to, "fetching rate");
+        let response = get_json(self.client.get(url), Self::SOURCE).await?;
+        Self::parse(response, from, to)
+    }
+
+    fn get_supported_pairs(&self) -> Vec<(String, String)> {
+        self.supported_pairs.clone()
+    }
+
+    async fn is_healthy(&self) -> bool {
+        // Request failures surface from fetch_rate and are handled by the caller
+        true
+    }
+
+    fn name(&self) -> &str {
+        "ExchangeRateApiProvider"
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Open Exchange Rates (https://openexchangerates.org)
+// ---------------------------------------------------------------------------
+
+/// `GET /api/latest.json` (app id sent as `Authorization: Token ...`); always
+/// USD-based on the free plan
+#[derive(Debug, Deserialize)]
+pub struct OpenExchangeRatesResponse {
+    pub timestamp: i64,
+    pub base: String,
+    #[serde(default)]
+    pub rates: HashMap<String, serde_json::Number>,
+}
+
+/// Fiat rates from Open Exchange Rates. Non-USD pairs are crossed through the
+/// USD base within the same snapshot.
+pub struct OpenExchangeRatesProvider {
+    client: reqwest::Client,
+    base_url: String,
+    app_id: String,
+    supported_pairs: Vec<(String, String)>,
+}
+
+impl OpenExchangeRatesProvider {
+    pub const SOURCE: &'static str = "open_exchange_rates";
+
+    pub fn new(base_url: String, app_id: String, timeout: Duration) -> Self {
+        Self {
+            client: http_client(timeout),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            app_id,
+            supported_pairs: Vec::new(),
+        }
+    }
+
+    pub fn with_pairs(mut self, pairs: Vec<(String, String)>) -> Self {
+        self.supported_pairs = pairs;
+        self
+    }
+
+    pub fn parse(
+        response: OpenExchangeRatesResponse,
+        from: &str,
+        to: &str,
+    ) -> ExchangeRateResult<RateData> {
+        let per_base = |currency: &str| -> ExchangeRateResult<BigDecimal> {
+            if currency.eq_ignore_ascii_case(&response.base) {
+                return Ok(BigDecimal::from(1));
+            }
+            let rate = response
+                .rates
+                .get(&currency.to_uppercase())
+                .ok_or_else(|| not_found(from, to))?;
+            positive(parse_number(rate, Self::SOURCE)?, Self::SOURCE)
+        };
+        let rate = per_base(to)? / per_base(from)?;
+        Ok(rate_data(
+            from,
+            to,
+            rate,
+            Self::SOURCE,
+            timestamp(response.timestamp),
+        ))
+    }
+}
+
+#[async_trait]
+impl RateProvider for OpenExchangeRatesProvider {
+    async fn fetch_rate(&self, from: &str, to: &str) -> ExchangeRateResult<RateData> {
+        if !supports(&self.supported_pairs, from, to) {
+            return Err(not_found(from, to));
+        }
+        let url = format!("{}/api/latest.json", self.base_url);
+        debug!(source = Self::SOURCE, from = %from, to = %to, "fetching rate");
+        let request = self
+            .client
+            .get(url)
+            .header("Authorization", format!("Token {}", self.app_id));
+        let response = get_json(request, Self::SOURCE).await?;
+        Self::parse(response, from, to)
+    }
+
+    fn get_supported_pairs(&self) -> Vec<(String, String)> {
+        self.supported_pairs.clone()
+    }
+
+    async fn is_healthy(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &str {
+        "OpenExchangeRatesProvider"
+    }
+}
+
+// ---------------------------------------------------------------------------
+// CoinGecko (https://www.coingecko.com/en/api)
+// ---------------------------------------------------------------------------
+
+/// `GET /api/v3/simple/price?ids=...&vs_currencies=...&include_last_updated_at=true`,
+/// keyed by coin id then by lowercase vs-currency
+pub type CoinGeckoSimplePriceResponse = HashMap<String, HashMap<String, serde_json::Number>>;
+
+/// Crypto prices (USDC, XLM, ...) from CoinGecko's simple price endpoint
+pub struct CoinGeckoProvider {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+    supported_pairs: Vec<(String, String)>,
+}
+
+impl CoinGeckoProvider {
+    pub const SOURCE: &'static str = "coingecko";
+
+    pub fn new(base_url: String, api_key: Option<String>, timeout: Duration) -> Self {
+        Self {
+            client: http_client(timeout),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            supported_pairs: Vec::new(),
+        }
+    }
+
+    pub fn with_pairs(mut self, pairs: Vec<(String, String)>) -> Self {
+        self.supported_pairs = pairs;
+        self
+    }
+
+    /// CoinGecko coin id for an asset symbol
+    pub fn coin_id(symbol: &str) -> Option<&'static str> {
+        match symbol.to_uppercase().as_str() {
+            "USDC" => Some("usd-coin"),
+            "USDT" => Some("tether"),
+            "XLM" => Some("stellar"),
+            "BTC" => Some("bitcoin"),
+            "ETH" => Some("ethereum"),
+            _ => None,
+        }
+    }
+
+    pub fn parse(
+        response: CoinGeckoSimplePriceResponse,
+        from: &str,
+        to: &str,
+    ) -> ExchangeRateResult<RateData> {
+        let coin = Self::coin_id(from).ok_or_else(|| not_found(from, to))?;
+        let prices = response.get(coin).ok_or_else(|| not_found(from, to))?;
+        let rate = prices
+            .get(&to.to_lowercase())
+            .ok_or_else(|| not_found(from, to))?;
+        let rate = positive(parse_number(rate, Self::SOURCE)?, Self::SOURCE)?;
+        let at = prices
+            .get("last_updated_at")
+            .and_then(|v| v.as_i64())
+            .map(timestamp)
+            .unwrap_or_else(Utc::now);
+        Ok(rate_data(from, to, rate, Self::SOURCE, at))
+    }
+}
+
+#[async_trait]
+impl RateProvider for CoinGeckoProvider {
+    async fn fetch_rate(&self, from: &str, to: &str) -> ExchangeRateResult<RateData> {
+        if !supports(&self.supported_pairs, from, to) {
+            return Err(not_found(from, to));
+        }
+        let coin = Self::coin_id(from).ok_or_else(|| not_found(from, to))?;
+        let vs_currency = to.to_lowercase();
+        let mut request = self.client.get(format!(
+            "{}/api/v3/simple/price?ids={}&vs_currencies={}&include_last_updated_at=true",
+            self.base_url, coin, vs_currency
+        ));
+        if let Some(key) = &self.api_key {
+            request = request.header("x-cg-demo-api-key", key);
+        }
+        debug!(source = Self::SOURCE, from = %from, to = %to, "fetching rate");
+        let response = get_json(request, Self::SOURCE).await?;
+        Self::parse(response, from, to)
+    }
+
+    fn get_supported_pairs(&self) -> Vec<(String, String)> {
+        self.supported_pairs.clone()
+    }
+
+    async fn is_healthy(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &str {
+        "CoinGeckoProvider"
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Stellar DEX orderbook
+// ---------------------------------------------------------------------------
+
+/// Mid-price of the Stellar DEX orderbook between two configured assets.
+/// Books with a missing side or a spread wider than `max_spread_bps` are
+/// refused rather than quoted.
+pub struct StellarDexRateProvider {
+    client: StellarClient,
+    assets: HashMap<String, StellarAsset>,
+    max_spread_bps: u32,
+}
+
+impl StellarDexRateProvider {
+    pub const SOURCE: &'static str = "stellar_dex";
+
+    pub fn new(client: StellarClient) -> Self {
+        Self {
+            client,
+            assets: HashMap::new(),
+            max_spread_bps: DEFAULT_MAX_DEX_SPREAD_BPS,
+        }
+    }
+
+    /// Quote `asset` under its asset code (`XLM` for native)
+    pub fn with_asset(mut self, asset: StellarAsset) -> Self {
+        self.assets.insert(asset.code().to_uppercase(), asset);
+        self
+    }
+
+    pub fn with_max_spread_bps(mut self, max_spread_bps: u32) -> Self {
+        self.max_spread_bps = max_spread_bps;
+        self
+    }
+
+    /// Mid-price from an order book of `from` priced in `to`
+    pub fn parse(
+        book: &HorizonOrderBook,
+        from: &str,
+        to: &str,
+        max_spread_bps: u32,
+    ) -> ExchangeRateResult<RateData> {
+        let best = |levels: &[HorizonOrderBookLevel]| {
+            levels
+                .first()
+                .and_then(|level| BigDecimal::from_str(&level.price).ok())
+                .filter(|price| *price > 0)
+        };
+        let (Some(bid), Some(ask)) = (best(&book.bids), best(&book.asks)) else {
+            return Err(ExchangeRateError::ProviderError(format!(
+                "{} order book for {}/{} has no liquidity on one side",
+                Self::SOURCE,
+                from,
+                to
+            )));
+        };
+
+        let mid = (&bid + &ask) / BigDecimal::from(2);
+        let spread = &ask - &bid;
+        if &spread * BigDecimal::from(10_000) > &mid * BigDecimal::from(max_spread_bps) {
+            return Err(ExchangeRateError::ProviderError(format!(
+                "{} order book for {}/{} is too wide (bid {}, ask {})",
+                Self::SOURCE,
+                from,
+                to,
+                bid,
+                ask
+            )));
+        }
+
+        Ok(RateData {
+            currency_pair: format!("{}/{}", from, to),
+            base_rate: mid,
+            buy_rate: ask,
+            sell_rate: bid,
+            spread,
+            source: Self::SOURCE.to_string(),
+            last_updated: Utc::now(),
+        })
+    }
+}
+
+#[async_trait]
+impl RateProvider for StellarDexRateProvider {
+    async fn fetch_rate(&self, from: &str, to: &str) -> ExchangeRateResult<RateData> {
+        let (Some(selling), Some(buying)) = (
+            self.assets.get(&from.to_uppercase()),
+            self.assets.get(&to.to_uppercase()),
+        ) else {
+            return Err(not_found(from, to));
+        };
+        debug!(source = Self::SOURCE, from = %from, to = %to, "fetching order book");
+        let book = self
+            .client
+            .get_order_book(selling, buying, ORDER_BOOK_DEPTH)
+            .await
+            .map_err(|e| ExchangeRateError::ProviderError(format!("{}: {}", Self::SOURCE, e)))?;
+        Self::parse(&book, from, to, self.max_spread_bps)
+    }
+
+    fn get_supported_pairs(&self) -> Vec<(String, String)> {
+        let codes: Vec<&StellarAsset> = self.assets.values().collect();
+        codes
+            .iter()
+            .flat_map(|from| {
+                codes
+                    .iter()
+                    .filter(move |to| to.code() != from.code())
+                    .map(move |to| (from.code().to_string(), to.code().to_string()))
+            })
+            .collect()
+    }
+
+    async fn is_healthy(&self) -> bool {
+        self.assets.len() >= 2
+    }
+
+    fn name(&self) -> &str {
+        "StellarDexRateProvider"
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Configuration
+// ---------------------------------------------------------------------------
+
+/// Which external sources feed the aggregated provider, and how they are
+/// combined
+#[derive(Debug, Clone)]
+pub struct RateSourcesConfig {
+    /// Source names from `RATE_SOURCES`: exchangerate_api, open_exchange_rates,
+    /// coingecko, stellar_dex
+    pub sources: Vec<String>,
+    /// Pairs the HTTP sources are queried for
+    pub pairs: Vec<(String, String)>,
+    pub timeout: Duration,
+    pub max_deviation_bps: u32,
+    pub min_sources: usize,
+    pub exchangerate_api_url: String,
+    pub exchangerate_api_key: Option<String>,
+    pub open_exchange_rates_url: String,
+    pub open_exchange_rates_app_id: Option<String>,
+    pub coingecko_url: String,
+    pub coingecko_api_key: Option<String>,
+    pub dex_assets: Vec<StellarAsset>,
+    pub dex_max_spread_bps: u32,
+}
+
+impl RateSourcesConfig {
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        let list = |name: &str| {
+            var(name)
+                .map(|v| {
+                    v.split(',')
+                        .map(|item| item.trim().to_string())
+                        .filter(|item| !item.is_empty())
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default()
+        };
+
+        let dex_assets = list("STELLAR_DEX_RATE_ASSETS")
+            .into_iter()
+            .filter_map(|asset| match StellarAsset::from_str(&asset) {
+                Ok(asset) => Some(asset),
+                Err(e) => {
+                    warn!(asset = %asset, error = %e, "ignoring invalid STELLAR_DEX_RATE_ASSETS entry");
+                    None
+                }
+            })
+            .collect();
+
+        Self {
+            sources: list("RATE_SOURCES")
+                .into_iter()
+                .map(|s| s.to_lowercase())
+                .collect(),
+            pairs: parse_pairs(&list("RATE_SOURCE_PAIRS")),
+            timeout: Duration::from_secs(
+                var("RATE_SOURCE_TIMEOUT_SECONDS")
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(DEFAULT_TIMEOUT_SECONDS),
+            ),
+            max_deviation_bps: var("RATE_AGGREGATION_MAX_DEVIATION_BPS")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_MAX_DEVIATION_BPS),
+            min_sources: var("RATE_AGGREGATION_MIN_SOURCES")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_MIN_SOURCES),
+            exchangerate_api_url: var("EXCHANGERATE_API_URL")
+                .unwrap_or_else(|| EXCHANGERATE_API_DEFAULT_URL.to_string()),
+            exchangerate_api_key: var("EXCHANGERATE_API_KEY"),
+            open_exchange_rates_url: var("OPEN_EXCHANGE_RATES_URL")
+                .unwrap_or_else(|| OPEN_EXCHANGE_RATES_DEFAULT_URL.to_string()),
+            open_exchange_rates_app_id: var("OPEN_EXCHANGE_RATES_APP_ID"),
+            coingecko_url: var("COINGECKO_API_URL")
+                .unwrap_or_else(|| COINGECKO_DEFAULT_URL.to_string()),
+            coingecko_api_key: var("COINGECKO_API_KEY"),
+            dex_assets,
+            dex_max_spread_bps: var("STELLAR_DEX_MAX_SPREAD_BPS")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_MAX_DEX_SPREAD_BPS),
+        }
+    }
+
+    /// Build the aggregated provider, or `None` when no source is enabled.
+    /// The Stellar DEX source is skipped without a Stellar client.
+    pub fn build(&self, stellar_client: Option<StellarClient>) -> Option<AggregatedRateProvider> {
+        let mut provider = AggregatedRateProvider::new(AggregationStrategy::OutlierRejecting {
+            max_deviation_bps: self.max_deviation_bps,
+            min_sources: self.min_sources,
+        });
+
+        for source in &self.sources {
+            provider = match source.as_str() {
+                ExchangeRateApiProvider::SOURCE => provider.add_provider(Box::new(
+                    ExchangeRateApiProvider::new(
+                        self.exchangerate_api_url.clone(),
+                        self.exchangerate_api_key.clone(),
+                        self.timeout,
+                    )
+                    .with_pairs(self.pairs.clone()),
+                )),
+                OpenExchangeRatesProvider::SOURCE => match &self.open_exchange_rates_app_id {
+                    Some(app_id) => provider.add_provider(Box::new(
+                        OpenExchangeRatesProvider::new(
+                            self.open_exchange_rates_url.clone(),
+                            app_id.clone(),
+                            self.timeout,
+                        )
+                        .with_pairs(self.pairs.clone()),
+                    )),
+                    None => {
+                        warn!("open_exchange_rates enabled without OPEN_EXCHANGE_RATES_APP_ID");
+                        provider
+                    }
+                },
+                CoinGeckoProvider::SOURCE => provider.add_provider(Box::new(
+                    CoinGeckoProvider::new(
+                        self.coingecko_url.clone(),
+                        self.coingecko_api_key.clone(),
+                        self.timeout,
+                    )
+                    .with_pairs(self.pairs.clone()),
+                )),
+                StellarDexRateProvider::SOURCE => match &stellar_client {
+                    Some(client) => provider.add_provider(Box::new(
+                        self.dex_assets
+                            .iter()
+                            .cloned()
+                            .fold(StellarDexRateProvider::new(client.clone()), |dex, asset| {
+                                dex.with_asset(asset)
+                            })
+                            .with_max_spread_bps(self.dex_max_spread_bps),
+                    )),
+                    None => {
+                        warn!("stellar_dex rate source enabled without a Stellar client");
+                        provider
+                    }
+                },
+                other => {
+                    warn!(source = %other, "unknown rate source in RATE_SOURCES");
+                    provider
+                }
+            };
+        }
+
+        (provider.provider_count() > 0).then_some(provider)
+    }
+}
+
+/// Parse `FROM/TO` entries, skipping malformed ones
+fn parse_pairs(entries: &[String]) -> Vec<(String, String)> {
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let (from, to) = entry.split_once('/')?;
+            let (from, to) = (from.trim(), to.trim());
+            (!from.is_empty() && !to.is_empty()).then(|| (from.to_string(), to.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXCHANGERATE_API_USD: &str =
+        include_str!("../../tests/fixtures/rates/exchangerate_api_usd.json");
+    const EXCHANGERATE_API_ERROR: &str =
+        include_str!("../../tests/fixtures/rates/exchangerate_api_error.json");
+    const OPEN_EXCHANGE_RATES_LATEST: &str =
+        include_str!("../../tests/fixtures/rates/open_exchange_rates_latest.json");
+    const COINGECKO_SIMPLE_PRICE: &str =
+        include_str!("../../tests/fixtures/rates/coingecko_simple_price.json");
+    const ORDER_BOOK_USDC_CNGN: &str =
+        include_str!("../../tests/fixtures/rates/horizon_order_book_usdc_cngn.json");
+    const ORDER_BOOK_EMPTY: &str =
+        include_str!("../../tests/fixtures/rates/horizon_order_book_empty.json");
+
+    fn decimal(value: &str) -> BigDecimal {
+        BigDecimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn test_exchangerate_api_fixture() {
+        let response = serde_json::from_str(EXCHANGERATE_API_USD).unwrap();
+        let rate = ExchangeRateApiProvider::parse(response, "USD", "NGN").unwrap();
+        assert_eq!(rate.base_rate, decimal("1468.52"));
+        assert_eq!(rate.source, "exchangerate_api");
+        assert_eq!(rate.last_updated.timestamp(), 1760745751);
+
+        let response = serde_json::from_str(EXCHANGERATE_API_USD).unwrap();
+        assert!(matches!(
+            ExchangeRateApiProvider::parse(response, "USD", "XOF"),
+            Err(ExchangeRateError::RateNotFound { .. })
+        ));
+
+        let response = serde_json::from_str(EXCHANGERATE_API_ERROR).unwrap();
+        assert!(matches!(
+            ExchangeRateApiProvider::parse(response, "USD", "NGN"),
+            Err(ExchangeRateError::ProviderError(_))
+        ));
+    }
+
+    #[test]
+    fn test_open_exchange_rates_fixture_crosses_through_base() {
+        let response = serde_json::from_str(OPEN_EXCHANGE_RATES_LATEST).unwrap();
+        let rate = OpenExchangeRatesProvider::parse(response, "USD", "NGN").unwrap();
+        assert_eq!(rate.base_rate, decimal("1469.1"));
+
+        let response = serde_json::from_str(OPEN_EXCHANGE_RATES_LATEST).unwrap();
+        let rate = OpenExchangeRatesProvider::parse(response, "KES", "NGN").unwrap();
+        assert_eq!(
+            rate.base_rate.with_scale(6),
+            (decimal("1469.1") / decimal("129.25")).with_scale(6)
+        );
+    }
+
+    #[test]
+    fn test_coingecko_fixture() {
+        let response = serde_json::from_str(COINGECKO_SIMPLE_PRICE).unwrap();
+        let rate = CoinGeckoProvider::parse(response, "USDC", "NGN").unwrap();
+        assert_eq!(rate.base_rate, decimal("1467.94"));
+        assert_eq!(rate.last_updated.timestamp(), 1760745580);
+
+        let response = serde_json::from_str(COINGECKO_SIMPLE_PRICE).unwrap();
+        assert!(CoinGeckoProvider::parse(response, "XLM", "GHS").is_err());
+    }
+
+    #[test]
+    fn test_stellar_dex_fixture_mid_price() {
+        let book: HorizonOrderBook = serde_json::from_str(ORDER_BOOK_USDC_CNGN).unwrap();
+        let rate = StellarDexRateProvider::parse(&book, "USDC", "cNGN", 500).unwrap();
+        assert_eq!(rate.base_rate, decimal("1468.001"));
+        assert_eq!(rate.sell_rate, decimal("1467.002"));
+        assert_eq!(rate.buy_rate, decimal("1469"));
+
+        // 1.998 wide on ~1468 is ~13.6 bps
+        assert!(StellarDexRateProvider::parse(&book, "USDC", "cNGN", 10).is_err());
+
+        let empty: HorizonOrderBook = serde_json::from_str(ORDER_BOOK_EMPTY).unwrap();
+        assert!(StellarDexRateProvider::parse(&empty, "XLM", "cNGN", 500).is_err());
+    }
+
+    #[test]
+    fn test_parse_pairs_skips_malformed_entries() {
+        let pairs = parse_pairs(&[
+            "USD/NGN".to_string(),
+            "KES".to_string(),
+            " USDC / NGN ".to_string(),
+        ]);
+        assert_eq!(
+            pairs,
+            vec![
+                ("USD".to_string(), "NGN".to_string()),
+                ("USDC".to_string(), "NGN".to_string())
+            ]
+        );
+    }
+}
@@ -0,0 +1,556 @@
+//! Refund service
+//!
+//! Returns fiat to customers whose onramp payment was collected but whose
+//! cNGN cannot be delivered: the wallet has no trustline, the address is
+//! bad, or there is no liquidity. The refund goes back through the provider
+//! that collected the payment, moves the transaction to `refund_initiated`
+//! and then `refunded`, and the customer is notified at each step.
+
+use crate::database::refund_repository::{NewRefund, Refund, RefundRepository};
+use crate::database::repository::Repository;
+use crate::database::transaction_repository::{Transaction, TransactionRepository};
+use crate::error::{AppError, AppErrorKind, DomainError, ValidationError};
+use crate::payments::factory::PaymentProviderFactory;
+use crate::payments::types::{Money, PaymentState, ProviderName, RefundRequest};
+use crate::services::fee_calculation::FeeCalculationService;
+use crate::services::notification::{NotificationService, NotificationType};
+use crate::services::payment_orchestrator::{OrchestrationState, PaymentOrchestrator};
+use bigdecimal::num_traits::Zero;
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct RefundConfig {
+    /// Keep the provider's collection fee back from full refunds, since
+    /// providers do not return it
+    pub withhold_provider_fee: bool,
+}
+
+impl Default for RefundConfig {
+    fn default() -> Self {
+        Self {
+            withhold_provider_fee: true,
+        }
+    }
+}
+
+impl RefundConfig {
+    pub fn from_env() -> Self {
+        let mut cfg = Self::default();
+        if let Ok(value) = std::env::var("REFUND_WITHHOLD_PROVIDER_FEE") {
+            cfg.withhold_provider_fee = value.to_lowercase() != "false";
+        }
+        cfg
+    }
+}
+
+/// Why an onramp's cNGN could not be delivered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefundReason {
+    MissingTrustline,
+    InvalidWallet,
+    InsufficientLiquidity,
+    Other,
+}
+
+impl RefundReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RefundReason::MissingTrustline => "missing_trustline",
+            RefundReason::InvalidWallet => "invalid_wallet",
+            RefundReason::InsufficientLiquidity => "insufficient_liquidity",
+            RefundReason::Other => "other",
+        }
+    }
+
+    fn describe(&self) -> &'static str {
+        match self {
+            RefundReason::MissingTrustline => "your wallet has no cNGN trustline",
+            RefundReason::InvalidWallet => "your wallet address cannot receive cNGN",
+            RefundReason::InsufficientLiquidity => "cNGN liquidity ran out",
+            RefundReason::Other => "your cNGN could not be delivered",
+        }
+    }
+}
+
+/// A request to refund an onramp payment
+#[derive(Debug, Clone, Deserialize)]
+pub struct OnrampRefundRequest {
+    pub reason: RefundReason,
+    /// Decimal amount in the payment currency; the whole payment when absent
+    pub amount: Option<String>,
+    pub requested_by: String,
+    pub note: Option<String>,
+}
+
+/// Stored status of a refund in the provider's `state`
+pub fn refund_status(state: &PaymentState) -> &'static str {
+    match state {
+        PaymentState::Success => "processed",
+        PaymentState::Failed | PaymentState::Cancelled | PaymentState::Reversed => "failed",
+        PaymentState::Processing => "processing",
+        PaymentState::Pending | PaymentState::Unknown => "pending",
+    }
+}
+
+/// Amount to return for a payment of `paid` and the provider fee withheld
+/// from it. An explicit amount is refunded as asked; a full refund keeps the
+/// provider's fee back when `withhold_provider_fee` is set.
+pub fn refund_amounts(
+    paid: &BigDecimal,
+    requested: Option<&BigDecimal>,
+    provider_fee: Option<&BigDecimal>,
+    withhold_provider_fee: bool,
+) -> Result<(BigDecimal, BigDecimal), ValidationError> {
+    let (amount, fee) = match requested {
+        Some(amount) => (amount.clone(), BigDecimal::zero()),
+        None => {
+            let fee = match provider_fee {
+                Some(fee) if withhold_provider_fee && *fee > BigDecimal::zero() => {
+                    fee.clone().min(paid.clone())
+                }
+                _ => BigDecimal::zero(),
+            };
+            (paid - &fee, fee)
+        }
+    };
+    if amount <= BigDecimal::zero() || amount > *paid {
+        return Err(ValidationError::InvalidAmount {
+            amount: amount.to_string(),
+            reason: format!("refund must be more than 0 and at most {}", paid),
+        });
+    }
+    Ok((amount, fee))
+}
+
+/// Metadata keys a submitted cNGN payment's hash may be recorded under
+const SUBMITTED_HASH_KEYS: [&str; 4] = [
+    "submitted_hash",
+    "stellar_tx_hash",
+    "transaction_hash",
+    "hash",
+];
+
+/// Hash of the cNGN payment submitted for a transaction, if any
+pub fn submitted_cngn_hash(transaction: &Transaction) -> Option<&str> {
+    transaction
+        .blockchain_tx_hash
+        .as_deref()
+        .into_iter()
+        .chain(
+            SUBMITTED_HASH_KEYS
+                .iter()
+                .filter_map(|key| transaction.metadata.get(*key).and_then(|v| v.as_str())),
+        )
+        .find(|hash| !hash.is_empty())
+}
+
+pub struct RefundService {
+    refunds: RefundRepository,
+    transactions: TransactionRepository,
+    fees: FeeCalculationService,
+    provider_factory: Arc<PaymentProviderFactory>,
+    orchestrator: Arc<PaymentOrchestrator>,
+    notifications: Arc<NotificationService>,
+    config: RefundConfig,
+}
+
+impl RefundService {
+    pub fn new(
+        pool: PgPool,
+        provider_factory: Arc<PaymentProviderFactory>,
+        orchestrator: Arc<PaymentOrchestrator>,
+        notifications: Arc<NotificationService>,
+        config: RefundConfig,
+    ) -> Self {
+        Self {
+            refunds: RefundRepository::new(pool.clone()),
+            transactions: TransactionRepository::new(pool.clone()),
+            fees: FeeCalculationService::new(pool),
+            provider_factory,
+            orchestrator,
+            notifications,
+            config,
+        }
+    }
+
+    /// Refund an onramp whose cNGN cannot be delivered. Asking again while a
+    /// refund is in flight or done returns that refund; a failed one is
+    /// retried. An onramp whose cNGN payment has been submitted is left to
+    /// the confirmation worker rather than refunded.
+    ///
+    /// The transaction is moved to `refund_initiated` before the provider is
+    /// asked, so the cNGN send cannot go out alongside the refund. If the
+    /// provider refuses, the transaction stays there with a failed refund:
+    /// nothing retries it, and an operator either calls this again or
+    /// settles the payment by hand.
+    pub async fn refund_onramp(
+        &self,
+        transaction_id: Uuid,
+        request: OnrampRefundRequest,
+    ) -> Result<Refund, AppError> {
+        if request.requested_by.trim().is_empty() {
+            return Err(AppError::new(AppErrorKind::Validation(
+                ValidationError::MissingField {
+                    field: "requested_by".to_string(),
+                },
+            )));
+        }
+        let transaction = self.transaction(transaction_id).await?;
+        if let Some(refund) = self.refunds.find_active(transaction_id).await? {
+            return Ok(refund);
+        }
+        let state = OrchestrationState::from_db_status(&transaction.status);
+        let refundable = match state {
+            Some(OrchestrationState::PaymentConfirmed | OrchestrationState::RefundInitiated) => {
+                true
+            }
+            // The cNGN may already be on its way; only a send that was never
+            // submitted can be abandoned for a refund
+            Some(OrchestrationState::ProcessingBlockchain) => {
+                submitted_cngn_hash(&transaction).is_none()
+            }
+            _ => false,
+        };
+        if transaction.r#type != "onramp" || !refundable {
+            return Err(AppError::new(AppErrorKind::Domain(
+                DomainError::RefundNotAllowed {
+                    transaction_id: transaction_id.to_string(),
+                    status: format!("{} {}", transaction.r#type, transaction.status),
+                },
+            )));
+        }
+        let provider_name = transaction
+            .payment_provider
+            .as_deref()
+            .unwrap_or_default()
+            .parse::<ProviderName>()?;
+
+        let requested = request
+            .amount
+            .as_deref()
+            .map(|a| {
+                BigDecimal::from_str(a.trim()).map_err(|_| ValidationError::InvalidAmount {
+                    amount: a.to_string(),
+                    reason: "not a decimal amount".to_string(),
+                })
+            })
+            .transpose()?;
+        let provider_fee = match requested {
+            Some(_) => None,
+            None => self
+                .fees
+                .provider_fee(
+                    "onramp",
+                    &transaction.from_amount,
+                    provider_name.as_str(),
+                    transaction.payment_method(),
+                )
+                .await?
+                .map(|fee| fee.calculated),
+        };
+        let (amount, provider_fee) = refund_amounts(
+            &transaction.from_amount,
+            requested.as_ref(),
+            provider_fee.as_ref(),
+            self.config.withhold_provider_fee,
+        )?;
+
+        // Take the transaction off the cNGN path first; a send that was
+        // submitted since it was read keeps it there
+        let state_change = match state {
+            Some(OrchestrationState::RefundInitiated) => serde_json::json!({}),
+            _ => serde_json::json!({
+                "state_change_reason": format!("Onramp refund: {}", request.reason.as_str()),
+                "previous_state": state.map(|s| s.to_string()),
+                "new_state": OrchestrationState::RefundInitiated.to_string(),
+            }),
+        };
+        if self
+            .transactions
+            .claim_onramp_refund(
+                transaction_id,
+                &transaction.status,
+                &SUBMITTED_HASH_KEYS,
+                state_change,
+            )
+            .await?
+            .is_none()
+        {
+            if let Some(refund) = self.refunds.find_active(transaction_id).await? {
+                return Ok(refund);
+            }
+            let current = self.transaction(transaction_id).await?;
+            return Err(AppError::new(AppErrorKind::Domain(
+                DomainError::RefundNotAllowed {
+                    transaction_id: transaction_id.to_string(),
+                    status: format!("{} {}", current.r#type, current.status),
+                },
+            )));
+        }
+
+        let Some(refund) = self
+            .refunds
+            .create(&NewRefund {
+                transaction_id,
+                provider: provider_name.as_str().to_string(),
+                reason: request.reason.as_str().to_string(),
+                amount: amount.clone(),
+                provider_fee,
+                currency: transaction.from_currency.clone(),
+                requested_by: request.requested_by.trim().to_string(),
+                note: request.note.clone(),
+            })
+            .await?
+        else {
+            // Another request got there first
+            return self
+                .refunds
+                .find_active(transaction_id)
+                .await?
+                .ok_or_else(|| not_found(&transaction_id.to_string()));
+        };
+
+        let provider = self.provider_factory.get_provider(provider_name)?;
+        let full = amount == transaction.from_amount;
+        let response = provider
+            .refund(RefundRequest {
+                transaction_reference: transaction
+                    .payment_reference
+                    .clone()
+                    .unwrap_or_else(|| transaction_id.to_string()),
+                provider_reference: None,
+                amount: (!full).then(|| Money {
+                    amount: amount.to_string(),
+                    currency: transaction.from_currency.clone(),
+                }),
+                reason: Some(request.reason.describe().to_string()),
+            })
+            .await;
+
+        let response = match response {
+            Ok(response) => response,
+            Err(e) => {
+                // The transaction is left in refund_initiated for an
+                // operator to retry or settle
+                warn!(
+                    transaction_id = %transaction_id,
+                    error = %e,
+                    "provider refund failed; transaction needs operator attention"
+                );
+                self.refunds.mark_failed(refund.id, &e.to_string()).await?;
+                self.notifications
+                    .send_notification(
+                        &transaction,
+                        NotificationType::OnrampRefundFailed,
+                        "We could not start your refund and are looking into it",
+                    )
+                    .await;
+                return Err(e.into());
+            }
+        };
+
+        let status = refund_status(&response.status);
+        let refund = self
+            .refunds
+            .mark_submitted(
+                refund.id,
+                status,
+                response.refund_reference.as_deref(),
+                &response
+                    .provider_data
+                    .clone()
+                    .unwrap_or_else(|| serde_json::json!({})),
+            )
+            .await?;
+        info!(
+            transaction_id = %transaction_id,
+            refund_id = %refund.id,
+            amount = %refund.amount,
+            status,
+            "onramp refund submitted"
+        );
+
+        match status {
+            "processed" => self.complete(&transaction).await?,
+            "failed" => {
+                self.notifications
+                    .send_notification(
+                        &transaction,
+                        NotificationType::OnrampRefundFailed,
+                        "Your refund was declined by the payment provider and is being reviewed",
+                    )
+                    .await
+            }
+            _ => {
+                self.notifications
+                    .send_notification(
+                        &transaction,
+                        NotificationType::OnrampRefundInitiated,
+                        &format!(
+                            "We are refunding {} {} because {}",
+                            refund.amount,
+                            refund.currency,
+                            request.reason.describe()
+                        ),
+                    )
+                    .await
+            }
+        }
+        Ok(refund)
+    }
+
+    /// Apply a provider's refund webhook for the payment `transaction_reference`
+    pub async fn handle_provider_update(
+        &self,
+        transaction_reference: &str,
+        processed: bool,
+    ) -> Result<(), AppError> {
+        let transaction = self
+            .transactions
+            .find_by_payment_reference(transaction_reference)
+            .await?
+            .ok_or_else(|| not_found(transaction_reference))?;
+        let Some(refund) = self.refunds.find_active(transaction.transaction_id).await? else {
+            // Refunded outside this service, e.g. from the provider dashboard
+            if processed {
+                self.orchestrator
+                    .handle_refund_processed(transaction_reference)
+                    .await?;
+            }
+            return Ok(());
+        };
+
+        if processed {
+            if self.refunds.mark_processed(refund.id).await?.is_some() {
+                self.complete(&transaction).await?;
+            }
+        } else if self
+            .refunds
+            .mark_failed(refund.id, "Provider reported the refund failed")
+            .await?
+            .is_some()
+        {
+            warn!(transaction_id = %transaction.transaction_id, refund_id = %refund.id, "onramp refund failed");
+            self.notifications
+                .send_notification(
+                    &transaction,
+                    NotificationType::OnrampRefundFailed,
+                    "Your refund did not go through and is being reviewed",
+                )
+                .await;
+        }
+        Ok(())
+    }
+
+    /// Refund attempts for a transaction, oldest first
+    pub async fn list(&self, transaction_id: Uuid) -> Result<Vec<Refund>, AppError> {
+        Ok(self.refunds.find_by_transaction(transaction_id).await?)
+    }
+
+    async fn complete(&self, transaction: &Transaction) -> Result<(), AppError> {
+        let reference = transaction
+            .payment_reference
+            .clone()
+            .unwrap_or_else(|| transaction.transaction_id.to_string());
+        self.orchestrator
+            .handle_refund_processed(&reference)
+            .await?;
+        self.notifications
+            .send_notification(
+                transaction,
+                NotificationType::OnrampRefunded,
+                "Your payment has been refunded",
+            )
+            .await;
+        Ok(())
+    }
+
+    async fn transaction(&self, transaction_id: Uuid) -> Result<Transaction, AppError> {
+        let id = transaction_id.to_string();
+        self.transactions
+            .find_by_id(&id)
+            .await?
+            .ok_or_else(|| not_found(&id))
+    }
+}
+
+fn not_found(id: &str) -> AppError {
+    AppError::new(AppErrorKind::Domain(DomainError::TransactionNotFound {
+        transaction_id: id.to_string(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(value: &str) -> BigDecimal {
+        BigDecimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn full_refund_withholds_provider_fee_when_configured() {
+        let (amount, fee) = refund_amounts(&dec("10000"), None, Some(&dec("150")), true).unwrap();
+        assert_eq!(amount, dec("9850"));
+        assert_eq!(fee, dec("150"));
+
+        let (amount, fee) = refund_amounts(&dec("10000"), None, Some(&dec("150")), false).unwrap();
+        assert_eq!(amount, dec("10000"));
+        assert_eq!(fee, dec("0"));
+    }
+
+    #[test]
+    fn partial_refund_is_bounded_by_payment() {
+        let (amount, fee) =
+            refund_amounts(&dec("10000"), Some(&dec("2500")), Some(&dec("150")), true).unwrap();
+        assert_eq!(amount, dec("2500"));
+        assert_eq!(fee, dec("0"));
+
+        assert!(refund_amounts(&dec("10000"), Some(&dec("10000.01")), None, true).is_err());
+        assert!(refund_amounts(&dec("10000"), Some(&dec("0")), None, true).is_err());
+    }
+
+    #[test]
+    fn submitted_cngn_hash_is_found_on_column_or_metadata() {
+        let mut transaction = Transaction {
+            transaction_id: Uuid::new_v4(),
+            wallet_address: "GABC".to_string(),
+            r#type: "onramp".to_string(),
+            from_currency: "NGN".to_string(),
+            to_currency: "cNGN".to_string(),
+            from_amount: dec("10000"),
+            to_amount: dec("10000"),
+            cngn_amount: dec("10000"),
+            status: "processing".to_string(),
+            payment_provider: Some("paystack".to_string()),
+            payment_reference: None,
+            blockchain_tx_hash: None,
+            error_message: None,
+            metadata: serde_json::json!({ "submitted_hash": "" }),
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        };
+        assert_eq!(submitted_cngn_hash(&transaction), None);
+
+        transaction.metadata = serde_json::json!({ "stellar_tx_hash": "from_metadata" });
+        assert_eq!(submitted_cngn_hash(&transaction), Some("from_metadata"));
+
+        transaction.blockchain_tx_hash = Some("from_column".to_string());
+        assert_eq!(submitted_cngn_hash(&transaction), Some("from_column"));
+    }
+
+    #[test]
+    fn provider_states_map_to_refund_statuses() {
+        assert_eq!(refund_status(&PaymentState::Success), "processed");
+        assert_eq!(refund_status(&PaymentState::Processing), "processing");
+        assert_eq!(refund_status(&PaymentState::Pending), "pending");
+        assert_eq!(refund_status(&PaymentState::Failed), "failed");
+    }
+}
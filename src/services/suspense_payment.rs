@@ -0,0 +1,369 @@
+//! Suspense payment service
+//!
+//! cNGN that reaches the system wallet without a memo, or with a memo naming
+//! no known transaction, is recorded by the transaction monitor as a suspense
+//! payment instead of being dropped. Ops either match it to the transaction
+//! it was meant for, which is then credited as if the memo had been right,
+//! or send it back to the sender from the hot wallet. Whatever is left
+//! unresolved is summarised by age in a weekly report.
+
+use crate::chains::stellar::client::StellarClient;
+use crate::chains::stellar::errors::StellarError;
+use crate::chains::stellar::payment::{CngnMemo, CngnPaymentBuilder};
+use crate::chains::stellar::signer::TransactionSigner;
+use crate::database::repository::Repository;
+use crate::database::suspense_payment_repository::{
+    SuspenseAgingBucket, SuspenseCredit, SuspensePayment, SuspensePaymentRepository,
+};
+use crate::database::transaction_repository::TransactionRepository;
+use crate::error::{AppError, AppErrorKind, DomainError, ValidationError};
+use bigdecimal::Zero;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::types::BigDecimal;
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// How long a refund claimed without a signed payment is left to the
+/// request that claimed it before another may take over
+const REFUND_CLAIM_GRACE: chrono::Duration = chrono::Duration::minutes(5);
+
+/// Why the monitor could not match a payment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuspenseReason {
+    /// The payment carried no memo
+    NoMemo,
+    /// The memo did not name a known transaction
+    UnknownMemo,
+    /// The memo named a transaction no longer waiting for a deposit
+    TransactionClosed,
+}
+
+impl SuspenseReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SuspenseReason::NoMemo => "no_memo",
+            SuspenseReason::UnknownMemo => "unknown_memo",
+            SuspenseReason::TransactionClosed => "transaction_closed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MatchSuspensePaymentRequest {
+    pub operator: String,
+    pub transaction_id: Uuid,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RefundSuspensePaymentRequest {
+    pub operator: String,
+    pub note: Option<String>,
+}
+
+/// Unresolved suspense payments by age
+#[derive(Debug, Clone, Serialize)]
+pub struct SuspenseAgingReport {
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+    pub payments: i64,
+    pub amount: BigDecimal,
+    pub buckets: Vec<SuspenseAgingBucket>,
+}
+
+pub struct SuspensePaymentService {
+    payments: SuspensePaymentRepository,
+    transactions: TransactionRepository,
+    stellar_client: StellarClient,
+    refund_signer: Option<Arc<dyn TransactionSigner>>,
+}
+
+impl SuspensePaymentService {
+    pub fn new(pool: PgPool, stellar_client: StellarClient) -> Self {
+        Self {
+            payments: SuspensePaymentRepository::new(pool.clone()),
+            transactions: TransactionRepository::new(pool),
+            stellar_client,
+            refund_signer: None,
+        }
+    }
+
+    /// Signer for the wallet refunds are sent from
+    pub fn with_refund_signer(mut self, signer: Arc<dyn TransactionSigner>) -> Self {
+        self.refund_signer = Some(signer);
+        self
+    }
+
+    pub async fn list(
+        &self,
+        status: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<SuspensePayment>, AppError> {
+        Ok(self.payments.list(status, limit).await?)
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<SuspensePayment, AppError> {
+        self.payments
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| not_found(&id.to_string()))
+    }
+
+    /// Credit an unresolved payment to a transaction still waiting for
+    /// funds, the way the monitor would have had the memo been right
+    pub async fn match_payment(
+        &self,
+        id: Uuid,
+        request: MatchSuspensePaymentRequest,
+    ) -> Result<SuspensePayment, AppError> {
+        let operator = request.operator.trim();
+        if operator.is_empty() {
+            return Err(missing_operator());
+        }
+        let payment = self.get(id).await?;
+        if payment.status != "unresolved" {
+            return Err(closed(&payment));
+        }
+        let transaction_id = request.transaction_id.to_string();
+        let transaction = self
+            .transactions
+            .find_by_id(&transaction_id)
+            .await?
+            .ok_or_else(|| not_found(&transaction_id))?;
+        let is_top_up = transaction.status == "awaiting_top_up";
+        if !is_top_up && !is_awaiting_deposit(&transaction.status) {
+            return Err(AppError::new(AppErrorKind::Domain(
+                DomainError::TransactionNotAwaitingPayment {
+                    transaction_id,
+                    status: transaction.status,
+                },
+            )));
+        }
+
+        let mut transaction = transaction;
+        if is_top_up {
+            transaction.record_top_up(&payment.stellar_tx_hash);
+        }
+        let mut metadata = transaction.metadata.clone();
+        if !is_top_up {
+            metadata["incoming_hash"] = json!(payment.stellar_tx_hash);
+            metadata["incoming_ledger"] = json!(payment.ledger);
+            metadata["incoming_confirmed_at"] = json!(chrono::Utc::now().to_rfc3339());
+        }
+        metadata["suspense_payment_id"] = json!(id);
+        let next_status = if transaction.r#type == "offramp" {
+            "cngn_received"
+        } else {
+            "completed"
+        };
+
+        let credit = SuspenseCredit {
+            transaction_id: transaction.transaction_id,
+            expected_status: transaction.status.clone(),
+            next_status: next_status.to_string(),
+            metadata,
+            blockchain_tx_hash: (!is_top_up).then(|| payment.stellar_tx_hash.clone()),
+        };
+        let Some(matched) = self
+            .payments
+            .match_to_transaction(id, &credit, operator, request.note.as_deref())
+            .await?
+        else {
+            // Either the payment was resolved or the transaction moved on
+            // since they were read
+            let current = self.get(id).await?;
+            if current.status != "unresolved" {
+                return Err(closed(&current));
+            }
+            return Err(AppError::new(AppErrorKind::Domain(
+                DomainError::TransactionNotAwaitingPayment {
+                    transaction_id,
+                    status: transaction.status,
+                },
+            )));
+        };
+
+        info!(
+            suspense_payment_id = %id,
+            transaction_id = %transaction_id,
+            status = next_status,
+            operator = %operator,
+            "suspense payment matched"
+        );
+        Ok(matched)
+    }
+
+    /// Send an unresolved payment back to its sender. A refund whose earlier
+    /// attempt failed or was interrupted is checked on Horizon first, and
+    /// only rebuilt once the earlier envelope can no longer land.
+    pub async fn refund(
+        &self,
+        id: Uuid,
+        request: RefundSuspensePaymentRequest,
+    ) -> Result<SuspensePayment, AppError> {
+        let operator = request.operator.trim();
+        if operator.is_empty() {
+            return Err(missing_operator());
+        }
+        let payment = self.get(id).await?;
+        let claimed = match payment.status.as_str() {
+            "unresolved" => {
+                self.payments
+                    .start_refund(id, "unresolved", None, operator)
+                    .await?
+            }
+            "refunding" => {
+                if let Some(hash) = payment.refund_tx_hash.as_deref() {
+                    if self.refund_landed(hash).await? {
+                        info!(suspense_payment_id = %id, refund_tx_hash = %hash, "earlier refund found on chain");
+                        return self
+                            .payments
+                            .mark_refunded(id, request.note.as_deref())
+                            .await?
+                            .ok_or_else(|| closed(&payment));
+                    }
+                    if payment
+                        .refund_expires_at
+                        .is_some_and(|expires_at| expires_at > chrono::Utc::now())
+                    {
+                        return Err(closed(&payment));
+                    }
+                } else if chrono::Utc::now() - payment.updated_at < REFUND_CLAIM_GRACE {
+                    // Another request is still building its refund
+                    return Err(closed(&payment));
+                }
+                self.payments
+                    .start_refund(id, "refunding", payment.refund_tx_hash.as_deref(), operator)
+                    .await?
+            }
+            _ => return Err(closed(&payment)),
+        };
+        let payment = claimed.ok_or_else(|| closed(&payment))?;
+
+        let builder = CngnPaymentBuilder::new(self.stellar_client.clone());
+        let signed = match self.sign_refund(&builder, &payment).await {
+            Ok(signed) => signed,
+            Err(e) => {
+                // Nothing was signed, so the payment can simply be retried
+                self.payments.release_refund(id).await?;
+                return Err(e.into());
+            }
+        };
+        let refund_tx_hash = signed.draft.transaction_hash.clone();
+        let expires_at =
+            chrono::Utc::now() + chrono::Duration::seconds(signed.draft.timeout_seconds as i64);
+        self.payments
+            .record_refund_attempt(id, &refund_tx_hash, expires_at)
+            .await?;
+
+        if let Err(e) = builder
+            .submit_signed_payment(&signed.signed_envelope_xdr)
+            .await
+        {
+            warn!(
+                suspense_payment_id = %id,
+                refund_tx_hash = %refund_tx_hash,
+                error = %e,
+                "suspense refund submission failed"
+            );
+            return Err(e.into());
+        }
+
+        let refunded = self
+            .payments
+            .mark_refunded(id, request.note.as_deref())
+            .await?
+            .ok_or_else(|| closed(&payment))?;
+        info!(
+            suspense_payment_id = %id,
+            sender = %refunded.sender,
+            amount = %refunded.amount,
+            refund_tx_hash = %refund_tx_hash,
+            operator = %operator,
+            "suspense payment refunded"
+        );
+        Ok(refunded)
+    }
+
+    /// Unresolved and refunding payments grouped by age
+    pub async fn aging_report(&self) -> Result<SuspenseAgingReport, AppError> {
+        let buckets = self.payments.aging().await?;
+        Ok(SuspenseAgingReport {
+            generated_at: chrono::Utc::now(),
+            payments: buckets.iter().map(|b| b.payments).sum(),
+            amount: buckets
+                .iter()
+                .fold(BigDecimal::zero(), |total, b| total + &b.amount),
+            buckets,
+        })
+    }
+
+    async fn sign_refund(
+        &self,
+        builder: &CngnPaymentBuilder,
+        payment: &SuspensePayment,
+    ) -> Result<crate::chains::stellar::payment::SignedCngnPayment, StellarError> {
+        let signer = self.refund_signer.as_ref().ok_or_else(|| {
+            StellarError::signing_error("no signer configured to refund suspense payments")
+        })?;
+        let memo = format!("REFUND-{}", payment.id);
+        let draft = builder
+            .build_payment(
+                signer.public_key(),
+                &payment.sender,
+                &payment.amount.with_scale(7).to_string(),
+                CngnMemo::Text(memo.chars().take(28).collect()),
+                None,
+            )
+            .await?;
+        builder.sign_payment(draft, signer.as_ref()).await
+    }
+
+    async fn refund_landed(&self, hash: &str) -> Result<bool, StellarError> {
+        match self.stellar_client.get_transaction_by_hash(hash).await {
+            Ok(record) => Ok(record.successful),
+            Err(StellarError::TransactionFailed { .. }) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Statuses in which a transaction is still waiting for its deposit
+pub fn is_awaiting_deposit(status: &str) -> bool {
+    matches!(status, "pending" | "processing" | "pending_payment")
+}
+
+fn missing_operator() -> AppError {
+    AppError::new(AppErrorKind::Validation(ValidationError::MissingField {
+        field: "operator".to_string(),
+    }))
+}
+
+fn not_found(id: &str) -> AppError {
+    AppError::new(AppErrorKind::Domain(DomainError::TransactionNotFound {
+        transaction_id: id.to_string(),
+    }))
+}
+
+fn closed(payment: &SuspensePayment) -> AppError {
+    AppError::new(AppErrorKind::Domain(DomainError::SuspensePaymentClosed {
+        payment_id: payment.id.to_string(),
+        status: payment.status.clone(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_open_deposits_accept_payments() {
+        assert!(is_awaiting_deposit("pending"));
+        assert!(is_awaiting_deposit("pending_payment"));
+        assert!(!is_awaiting_deposit("awaiting_top_up"));
+        assert!(!is_awaiting_deposit("cngn_received"));
+        assert!(!is_awaiting_deposit("completed"));
+    }
+}
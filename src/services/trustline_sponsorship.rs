@@ -0,0 +1,359 @@
+//! Sponsored trustline reserves
+//! Lets new users open a cNGN trustline without holding XLM for the reserve:
+//! the platform sponsor account pays the reserve and the fee, co-signs the
+//! user's envelope, and can revoke the sponsorship when the user leaves.
+//! Sponsorships are tracked per user in `trustline_operations`.
+
+use crate::chains::stellar::client::StellarClient;
+use crate::chains::stellar::errors::StellarError;
+use crate::chains::stellar::multisig;
+use crate::chains::stellar::signer::{
+    ensure_signer_matches_source, sign_envelope, TransactionSigner,
+};
+use crate::chains::stellar::trustline::{CngnTrustlineManager, UnsignedTrustlineTransaction};
+use crate::database::repository::Repository;
+use crate::database::trustline_operation_repository::{
+    NewSponsoredOperation, TrustlineOperation, TrustlineOperationRepository,
+};
+use crate::error::{AppError, AppErrorKind, DomainError, ValidationError};
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Reserves paid per sponsored trustline
+const RESERVES_PER_TRUSTLINE: i32 = 1;
+const DEFAULT_MAX_RESERVES_PER_USER: i64 = 1;
+
+/// Trustline sponsorship configuration
+#[derive(Debug, Clone)]
+pub struct TrustlineSponsorshipConfig {
+    pub enabled: bool,
+    /// Maximum reserves the sponsor pays for a single wallet at any time
+    pub max_reserves_per_user: i64,
+}
+
+impl TrustlineSponsorshipConfig {
+    pub fn from_env() -> Self {
+        Self {
+            enabled: std::env::var("TRUSTLINE_SPONSORSHIP_ENABLED")
+                .map(|v| v.to_lowercase() == "true")
+                .unwrap_or(false),
+            max_reserves_per_user: std::env::var("TRUSTLINE_SPONSORSHIP_MAX_PER_USER")
+                .ok()
+                .and_then(|v| v.parse::<i64>().ok())
+                .unwrap_or(DEFAULT_MAX_RESERVES_PER_USER),
+        }
+    }
+}
+
+/// Service for sponsored trustline creation and revocation
+pub struct TrustlineSponsorshipService {
+    repo: TrustlineOperationRepository,
+    stellar_client: StellarClient,
+    manager: CngnTrustlineManager,
+    sponsor_signer: Arc<dyn TransactionSigner>,
+    config: TrustlineSponsorshipConfig,
+}
+
+impl TrustlineSponsorshipService {
+    pub fn new(
+        repo: TrustlineOperationRepository,
+        stellar_client: StellarClient,
+        sponsor_signer: Arc<dyn TransactionSigner>,
+        config: TrustlineSponsorshipConfig,
+    ) -> Self {
+        Self {
+            repo,
+            manager: CngnTrustlineManager::new(stellar_client.clone()),
+            stellar_client,
+            sponsor_signer,
+            config,
+        }
+    }
+
+    /// Public key of the sponsor account
+    pub fn sponsor(&self) -> &str {
+        self.sponsor_signer.public_key()
+    }
+
+    /// Whether an envelope is sourced from the sponsor account and therefore
+    /// needs the sponsor's co-signature before submission
+    pub fn is_sponsored_envelope(&self, envelope_xdr: &str) -> bool {
+        multisig::envelope_transaction(envelope_xdr)
+            .ok()
+            .and_then(|tx| {
+                ensure_signer_matches_source(
+                    self.sponsor_signer.as_ref(),
+                    &muxed_to_strkey(&tx.source_account),
+                )
+                .ok()
+            })
+            .is_some()
+    }
+
+    /// Build a sponsored trustline draft for `account_id` and log it as a
+    /// pending operation. The user signs the draft and submits it through
+    /// [`Self::submit`]. The sponsor pays the fee, so the default fee is
+    /// always used.
+    pub async fn build(
+        &self,
+        account_id: &str,
+        limit: Option<&str>,
+    ) -> Result<(UnsignedTrustlineTransaction, TrustlineOperation), AppError> {
+        self.ensure_enabled()?;
+        self.ensure_within_limit(account_id).await?;
+
+        let draft = self
+            .manager
+            .build_sponsored_trustline_transaction(account_id, self.sponsor(), limit, None)
+            .await?;
+        let operation = self
+            .repo
+            .create_sponsored_operation(NewSponsoredOperation {
+                wallet_address: draft.account_id.clone(),
+                asset_code: draft.asset_code.clone(),
+                issuer: Some(draft.issuer.clone()),
+                sponsor_account: self.sponsor().to_string(),
+                sponsored_reserves: RESERVES_PER_TRUSTLINE,
+                transaction_hash: Some(draft.transaction_hash.clone()),
+                metadata: serde_json::json!({
+                    "unsigned_envelope_xdr": draft.unsigned_envelope_xdr,
+                    "sequence": draft.sequence,
+                    "fee_stroops": draft.fee_stroops,
+                    "limit": draft.limit,
+                    "sponsored": true
+                }),
+            })
+            .await?;
+
+        info!(
+            account_id = %account_id,
+            sponsor = %self.sponsor(),
+            operation_id = %operation.id,
+            "sponsored trustline draft built"
+        );
+        Ok((draft, operation))
+    }
+
+    /// Co-sign a user-signed sponsored trustline envelope and submit it. The
+    /// envelope must be the draft built for the operation. The operation is
+    /// claimed first, which enforces the per-user limit once more since
+    /// several drafts may have been built before any of them landed.
+    pub async fn submit(
+        &self,
+        operation_id: Uuid,
+        user_signed_envelope_xdr: &str,
+    ) -> Result<serde_json::Value, AppError> {
+        self.ensure_enabled()?;
+        let operation = self.load(operation_id).await?;
+        if operation.sponsor_account.as_deref() != Some(self.sponsor())
+            || operation.operation_type != "create"
+        {
+            return Err(StellarError::signing_error(
+                "operation is not a sponsored trustline creation",
+            )
+            .into());
+        }
+        if operation.status == "completed" || operation.status == "submitting" {
+            return Err(
+                StellarError::transaction_failed("sponsored trustline already submitted").into(),
+            );
+        }
+        let Some(draft_hash) = operation.transaction_hash.as_deref() else {
+            return Err(
+                StellarError::signing_error("sponsored trustline has no recorded draft").into(),
+            );
+        };
+
+        // Verify before claiming so a bad envelope leaves the draft usable
+        let signed = self
+            .manager
+            .cosign_sponsored_trustline(
+                user_signed_envelope_xdr,
+                draft_hash,
+                &operation.wallet_address,
+                self.sponsor_signer.as_ref(),
+            )
+            .await?;
+
+        let Some(operation) = self
+            .repo
+            .claim_sponsored_submission(operation.id, self.config.max_reserves_per_user)
+            .await?
+        else {
+            return Err(self.limit_reached(&operation.wallet_address));
+        };
+
+        match self.manager.submit_signed_trustline_xdr(&signed).await {
+            Ok(response) => {
+                let hash = response
+                    .get("hash")
+                    .and_then(|v| v.as_str())
+                    .or(Some(draft_hash));
+                self.repo
+                    .update_status(operation.id, "completed", hash, None)
+                    .await?;
+                info!(
+                    account_id = %operation.wallet_address,
+                    operation_id = %operation.id,
+                    "sponsored trustline created"
+                );
+                Ok(response)
+            }
+            Err(e) => {
+                self.repo
+                    .update_status(
+                        operation.id,
+                        "failed",
+                        Some(draft_hash),
+                        Some(&e.to_string()),
+                    )
+                    .await?;
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Stop sponsoring the user's cNGN trustline. The revocation is signed by
+    /// the sponsor and submitted directly; the user must hold enough XLM to
+    /// take over the reserve.
+    pub async fn revoke(&self, account_id: &str) -> Result<TrustlineOperation, AppError> {
+        let sponsorship = self
+            .repo
+            .find_active_sponsorship(account_id, self.manager.asset_code())
+            .await?
+            .ok_or_else(|| {
+                AppError::new(AppErrorKind::Validation(
+                    ValidationError::InvalidWalletAddress {
+                        address: account_id.to_string(),
+                        reason: "wallet has no active trustline sponsorship".to_string(),
+                    },
+                ))
+            })?;
+        let sponsor = sponsorship.sponsor_account.as_deref().unwrap_or_default();
+        if sponsor != self.sponsor() {
+            return Err(StellarError::signing_error(format!(
+                "sponsorship is held by {}, not the configured sponsor",
+                sponsor
+            ))
+            .into());
+        }
+
+        let draft = self
+            .manager
+            .build_revoke_trustline_sponsorship(account_id, self.sponsor(), None)
+            .await?;
+        let signed = sign_envelope(
+            self.sponsor_signer.as_ref(),
+            &draft.unsigned_envelope_xdr,
+            self.stellar_client.network().network_passphrase(),
+        )
+        .await?;
+        let result = self
+            .manager
+            .submit_signed_trustline_xdr(&signed.signed_envelope_xdr)
+            .await;
+
+        let metadata = serde_json::json!({
+            "sponsorship_operation_id": sponsorship.id,
+            "sponsor": self.sponsor(),
+        });
+        match result {
+            Ok(response) => {
+                let hash = response
+                    .get("hash")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or(&signed.transaction_hash)
+                    .to_string();
+                self.repo.mark_sponsorship_revoked(sponsorship.id).await?;
+                let operation = self
+                    .repo
+                    .create_operation(
+                        account_id,
+                        &draft.asset_code,
+                        Some(&draft.issuer),
+                        "revoke_sponsorship",
+                        "completed",
+                        Some(&hash),
+                        None,
+                        metadata,
+                    )
+                    .await?;
+                info!(account_id = %account_id, hash = %hash, "trustline sponsorship revoked");
+                Ok(operation)
+            }
+            Err(e) => {
+                warn!(account_id = %account_id, error = %e, "failed to revoke trustline sponsorship");
+                self.repo
+                    .create_operation(
+                        account_id,
+                        &draft.asset_code,
+                        Some(&draft.issuer),
+                        "revoke_sponsorship",
+                        "failed",
+                        None,
+                        Some(&e.to_string()),
+                        metadata,
+                    )
+                    .await?;
+                Err(e.into())
+            }
+        }
+    }
+
+    fn ensure_enabled(&self) -> Result<(), StellarError> {
+        if self.config.enabled {
+            Ok(())
+        } else {
+            Err(StellarError::config_error(
+                "trustline sponsorship is disabled",
+            ))
+        }
+    }
+
+    async fn ensure_within_limit(&self, account_id: &str) -> Result<(), AppError> {
+        let active = self.repo.active_sponsored_reserves(account_id).await?;
+        if active + i64::from(RESERVES_PER_TRUSTLINE) > self.config.max_reserves_per_user {
+            warn!(
+                account_id = %account_id,
+                active_reserves = active,
+                limit = self.config.max_reserves_per_user,
+                "trustline sponsorship limit reached"
+            );
+            return Err(self.limit_reached(account_id));
+        }
+        Ok(())
+    }
+
+    fn limit_reached(&self, account_id: &str) -> AppError {
+        AppError::new(AppErrorKind::Domain(DomainError::TrustlineCreationFailed {
+            wallet_address: account_id.to_string(),
+            reason: format!(
+                "sponsorship limit of {} reserves per user reached",
+                self.config.max_reserves_per_user
+            ),
+        }))
+    }
+
+    async fn load(&self, operation_id: Uuid) -> Result<TrustlineOperation, AppError> {
+        self.repo
+            .find_by_id(&operation_id.to_string())
+            .await?
+            .ok_or_else(|| {
+                AppError::new(AppErrorKind::Domain(DomainError::TransactionNotFound {
+                    transaction_id: operation_id.to_string(),
+                }))
+            })
+    }
+}
+
+fn muxed_to_strkey(account: &stellar_xdr::next::MuxedAccount) -> String {
+    let key = match account {
+        stellar_xdr::next::MuxedAccount::Ed25519(k) => k.0,
+        stellar_xdr::next::MuxedAccount::MuxedEd25519(m) => m.ed25519.0,
+    };
+    stellar_strkey::ed25519::PublicKey(key)
+        .to_string()
+        .as_str()
+        .to_owned()
+}
@@ -32,7 +32,7 @@ mod tests {
         let err = WebhookProcessorError::InvalidSignature;
         assert_eq!(err.to_string(), "Invalid signature");
 
-        let err = WebhookProcessorError::AlreadyProcessed;
-        assert_eq!(err.to_string(), "Already processed");
+        let err = WebhookProcessorError::InvalidPayload("not json".to_string());
+        assert_eq!(err.to_string(), "Invalid payload: not json");
     }
 }
@@ -0,0 +1,117 @@
+use crate::services::claimable_balance::ClaimableBalanceService;
+use crate::services::distributed_lock::Leadership;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+// ---------------------------------------------------------------------------
+// Configuration
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+pub struct ClaimableBalanceReclaimerConfig {
+    /// How often pending claimable balances are checked.
+    pub poll_interval: Duration,
+    /// Maximum number of pending balances examined per cycle.
+    pub batch_size: i64,
+}
+
+impl Default for ClaimableBalanceReclaimerConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(3600),
+            batch_size: 100,
+        }
+    }
+}
+
+impl ClaimableBalanceReclaimerConfig {
+    pub fn from_env() -> Self {
+        let mut cfg = Self::default();
+        cfg.poll_interval = Duration::from_secs(
+            std::env::var("CLAIMABLE_BALANCE_POLL_INTERVAL_SECONDS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(cfg.poll_interval.as_secs()),
+        );
+        cfg.batch_size = std::env::var("CLAIMABLE_BALANCE_BATCH_SIZE")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(cfg.batch_size);
+        cfg
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Worker
+// ---------------------------------------------------------------------------
+
+/// Marks claimed balances and reclaims the ones recipients let expire.
+pub struct ClaimableBalanceReclaimer {
+    service: Arc<ClaimableBalanceService>,
+    config: ClaimableBalanceReclaimerConfig,
+    leadership: Option<Leadership>,
+}
+
+impl ClaimableBalanceReclaimer {
+    pub fn new(
+        service: Arc<ClaimableBalanceService>,
+        config: ClaimableBalanceReclaimerConfig,
+    ) -> Self {
+        Self {
+            service,
+            config,
+            leadership: None,
+        }
+    }
+
+    /// Only sweep while this replica holds `leadership`
+    pub fn with_leadership(mut self, leadership: Leadership) -> Self {
+        self.leadership = Some(leadership);
+        self
+    }
+
+    fn is_leader(&self) -> bool {
+        self.leadership.as_ref().is_none_or(|l| l.is_leader())
+    }
+
+    pub async fn run(self, mut shutdown_rx: watch::Receiver<bool>) {
+        info!(
+            poll_interval_secs = self.config.poll_interval.as_secs(),
+            batch_size = self.config.batch_size,
+            "claimable balance reclaimer started"
+        );
+
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.changed() => {
+                    if *shutdown_rx.borrow() {
+                        info!("claimable balance reclaimer stopping");
+                        break;
+                    }
+                }
+                _ = tokio::time::sleep(self.config.poll_interval) => {
+                    if !self.is_leader() {
+                        continue;
+                    }
+                    match self.service.sweep(self.config.batch_size).await {
+                        Ok(sweep) => {
+                            if sweep.claimed + sweep.reclaimed + sweep.failed > 0 {
+                                info!(
+                                    claimed = sweep.claimed,
+                                    reclaimed = sweep.reclaimed,
+                                    failed = sweep.failed,
+                                    "claimable balance sweep finished"
+                                );
+                            }
+                        }
+                        Err(e) => warn!(error = %e, "claimable balance sweep failed"),
+                    }
+                }
+            }
+        }
+
+        info!("claimable balance reclaimer stopped");
+    }
+}
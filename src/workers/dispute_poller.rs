@@ -0,0 +1,91 @@
+use std::sync::Arc;
+use tokio::sync::watch;
+use tracing::{error, info, warn};
+
+use crate::services::dispute::DisputeService;
+use crate::services::distributed_lock::Leadership;
+
+/// Polls card providers' dispute APIs so disputes whose webhooks never
+/// arrived are still recorded, and reports open disputes nearing their
+/// evidence deadline.
+pub struct DisputePoller {
+    service: Arc<DisputeService>,
+    leadership: Option<Leadership>,
+}
+
+impl DisputePoller {
+    pub fn new(service: Arc<DisputeService>) -> Self {
+        Self {
+            service,
+            leadership: None,
+        }
+    }
+
+    /// Only poll while this replica holds `leadership`
+    pub fn with_leadership(mut self, leadership: Leadership) -> Self {
+        self.leadership = Some(leadership);
+        self
+    }
+
+    fn is_leader(&self) -> bool {
+        self.leadership.as_ref().is_none_or(|l| l.is_leader())
+    }
+
+    pub async fn run(self, mut shutdown_rx: watch::Receiver<bool>) {
+        let poll_interval = self.service.config().poll_interval;
+        info!(
+            poll_interval_secs = poll_interval.as_secs(),
+            "dispute poller started"
+        );
+
+        loop {
+            if !self.is_leader() {
+                tokio::select! {
+                    _ = shutdown_rx.changed() => {
+                        if *shutdown_rx.borrow() {
+                            break;
+                        }
+                    }
+                    _ = tokio::time::sleep(poll_interval) => {}
+                }
+                continue;
+            }
+
+            let summary = self.service.sync().await;
+            if summary.failed > 0 {
+                warn!(
+                    providers = summary.providers,
+                    recorded = summary.recorded,
+                    failed = summary.failed,
+                    "dispute poll finished with problems"
+                );
+            }
+
+            match self.service.upcoming_deadlines().await {
+                Ok(due) => {
+                    for dispute in due {
+                        warn!(
+                            dispute_id = %dispute.id,
+                            provider = %dispute.provider,
+                            transaction_id = %dispute.transaction_id,
+                            evidence_due_at = ?dispute.evidence_due_at,
+                            "open dispute nearing its evidence deadline"
+                        );
+                    }
+                }
+                Err(e) => error!(error = %e, "failed to check dispute deadlines"),
+            }
+
+            tokio::select! {
+                _ = shutdown_rx.changed() => {
+                    if *shutdown_rx.borrow() {
+                        break;
+                    }
+                }
+                _ = tokio::time::sleep(poll_interval) => {}
+            }
+        }
+
+        info!("dispute poller stopped");
+    }
+}
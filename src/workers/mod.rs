@@ -1,3 +1,8 @@
+pub mod claimable_balance_reclaimer;
+pub mod dispute_poller;
+pub mod job_runner;
 pub mod offramp_processor;
+pub mod rate_refresher;
+pub mod suspense_report;
 pub mod transaction_monitor;
-pub mod webhook_retry;
+pub mod webhook_queue;
@@ -0,0 +1,410 @@
+use crate::services::distributed_lock::Leadership;
+use crate::services::exchange_rate::ExchangeRateService;
+use bigdecimal::BigDecimal;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{error, info, warn};
+
+// ---------------------------------------------------------------------------
+// Configuration
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+pub struct RateRefresherConfig {
+    /// How often every pair is refreshed.
+    pub poll_interval: Duration,
+    /// Pairs to refresh; empty means every pair the providers support.
+    pub pairs: Vec<(String, String)>,
+    /// Largest accepted move from the previous rate, in percent. Bigger
+    /// jumps trip the circuit breaker and the pair is marked stale.
+    pub max_jump_percent: BigDecimal,
+    /// A pair whose refreshes keep failing is marked stale once its stored
+    /// rate is older than this.
+    pub stale_after: Duration,
+    /// A tripped pair takes the jumped rate as its new baseline once that
+    /// many refreshes in a row agree on it (half-open). 0 leaves tripped
+    /// pairs stale until a rate within the limit comes back.
+    pub rebaseline_after: u32,
+}
+
+impl Default for RateRefresherConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(60),
+            pairs: Vec::new(),
+            max_jump_percent: BigDecimal::from(5),
+            stale_after: Duration::from_secs(900),
+            rebaseline_after: 5,
+        }
+    }
+}
+
+impl RateRefresherConfig {
+    pub fn from_env() -> Self {
+        let mut cfg = Self::default();
+        cfg.poll_interval = Duration::from_secs(
+            std::env::var("RATE_REFRESH_INTERVAL_SECONDS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(cfg.poll_interval.as_secs()),
+        );
+        cfg.pairs = std::env::var("RATE_REFRESH_PAIRS")
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|pair| {
+                let (from, to) = pair.trim().split_once('/')?;
+                Some((from.trim().to_string(), to.trim().to_string()))
+            })
+            .filter(|(from, to)| !from.is_empty() && !to.is_empty())
+            .collect();
+        cfg.max_jump_percent = std::env::var("RATE_CIRCUIT_BREAKER_MAX_JUMP_PERCENT")
+            .ok()
+            .and_then(|v| BigDecimal::from_str(&v).ok())
+            .unwrap_or(cfg.max_jump_percent);
+        cfg.stale_after = Duration::from_secs(
+            std::env::var("RATE_STALE_AFTER_SECONDS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(cfg.stale_after.as_secs()),
+        );
+        cfg.rebaseline_after = std::env::var("RATE_CIRCUIT_BREAKER_REBASELINE_AFTER")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(cfg.rebaseline_after);
+        cfg
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Worker
+// ---------------------------------------------------------------------------
+
+/// What happened to one pair in a refresh cycle
+#[derive(Debug, Clone, PartialEq)]
+pub enum RefreshOutcome {
+    /// The new rate was recorded and is the baseline from now on; any stale
+    /// marker on the pair is cleared
+    Updated,
+    /// The new rate moved too far from the stored one and was not recorded
+    Tripped {
+        jump_percent: BigDecimal,
+    },
+    /// The jumped rate held for `rebaseline_after` refreshes and was recorded
+    /// as the new baseline, clearing the stale marker
+    Rebaselined {
+        jump_percent: BigDecimal,
+    },
+    /// No provider answered; `marked_stale` once the stored rate aged out
+    Failed {
+        marked_stale: bool,
+    },
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RefreshSummary {
+    pub updated: usize,
+    pub rebaselined: usize,
+    pub tripped: usize,
+    pub failed: usize,
+    pub marked_stale: usize,
+}
+
+/// Refreshes configured pairs from the provider chain on a schedule,
+/// holding back suspicious jumps and marking pairs stale so quotes stop.
+pub struct RateRefresher {
+    service: Arc<ExchangeRateService>,
+    config: RateRefresherConfig,
+    leadership: Option<Leadership>,
+    jumps: Mutex<JumpTracker>,
+}
+
+impl RateRefresher {
+    pub fn new(service: Arc<ExchangeRateService>, config: RateRefresherConfig) -> Self {
+        Self {
+            service,
+            config,
+            leadership: None,
+            jumps: Mutex::new(JumpTracker::default()),
+        }
+    }
+
+    /// Only refresh while this replica holds `leadership`
+    pub fn with_leadership(mut self, leadership: Leadership) -> Self {
+        self.leadership = Some(leadership);
+        self
+    }
+
+    fn is_leader(&self) -> bool {
+        self.leadership.as_ref().is_none_or(|l| l.is_leader())
+    }
+
+    pub async fn run(self, mut shutdown_rx: watch::Receiver<bool>) {
+        let pairs = self.pairs();
+        info!(
+            poll_interval_secs = self.config.poll_interval.as_secs(),
+            pairs = pairs.len(),
+            max_jump_percent = %self.config.max_jump_percent,
+            "rate refresher started"
+        );
+
+        loop {
+            if self.is_leader() {
+                let summary = self.refresh_all(&pairs).await;
+                if summary.rebaselined + summary.tripped + summary.failed > 0 {
+                    warn!(
+                        updated = summary.updated,
+                        rebaselined = summary.rebaselined,
+                        tripped = summary.tripped,
+                        failed = summary.failed,
+                        marked_stale = summary.marked_stale,
+                        "rate refresh finished with problems"
+                    );
+                }
+            }
+
+            tokio::select! {
+                _ = shutdown_rx.changed() => {
+                    if *shutdown_rx.borrow() {
+                        info!("rate refresher stopping");
+                        break;
+                    }
+                }
+                _ = tokio::time::sleep(self.config.poll_interval) => {}
+            }
+        }
+
+        info!("rate refresher stopped");
+    }
+
+    fn pairs(&self) -> Vec<(String, String)> {
+        if self.config.pairs.is_empty() {
+            self.service.supported_pairs()
+        } else {
+            self.config.pairs.clone()
+        }
+    }
+
+    pub async fn refresh_all(&self, pairs: &[(String, String)]) -> RefreshSummary {
+        let mut summary = RefreshSummary::default();
+        for (from, to) in pairs {
+            match self.refresh_pair(from, to).await {
+                RefreshOutcome::Updated => summary.updated += 1,
+                RefreshOutcome::Rebaselined { .. } => summary.rebaselined += 1,
+                RefreshOutcome::Tripped { .. } => {
+                    summary.tripped += 1;
+                    summary.marked_stale += 1;
+                }
+                RefreshOutcome::Failed { marked_stale } => {
+                    summary.failed += 1;
+                    if marked_stale {
+                        summary.marked_stale += 1;
+                    }
+                }
+            }
+        }
+        summary
+    }
+
+    pub async fn refresh_pair(&self, from: &str, to: &str) -> RefreshOutcome {
+        let previous = match self.service.stored_rate(from, to).await {
+            Ok(previous) => previous,
+            Err(e) => {
+                warn!(pair = %format!("{}/{}", from, to), error = %e, "failed to load stored rate");
+                return RefreshOutcome::Failed {
+                    marked_stale: false,
+                };
+            }
+        };
+
+        let fetched = match self.service.fetch_from_providers(from, to).await {
+            Ok(fetched) => fetched,
+            Err(e) => {
+                let aged_out = previous.as_ref().is_some_and(|(_, updated_at)| {
+                    Utc::now()
+                        .signed_duration_since(*updated_at)
+                        .to_std()
+                        .unwrap_or_default()
+                        > self.config.stale_after
+                });
+                let marked_stale = aged_out
+                    && self
+                        .alarm(
+                            from,
+                            to,
+                            &format!("refresh failing past the staleness window: {}", e),
+                        )
+                        .await;
+                if !aged_out {
+                    warn!(pair = %format!("{}/{}", from, to), error = %e, "rate refresh failed");
+                }
+                return RefreshOutcome::Failed { marked_stale };
+            }
+        };
+
+        let pair = (from.to_string(), to.to_string());
+        let mut rebaselined = None;
+        if let Some((previous_rate, _)) = &previous {
+            if let Some(jump_percent) = jump_exceeds(
+                previous_rate,
+                &fetched.base_rate,
+                &self.config.max_jump_percent,
+            ) {
+                let held = self.jumps.lock().unwrap().observe(
+                    pair.clone(),
+                    &fetched.base_rate,
+                    &self.config.max_jump_percent,
+                );
+                if self.config.rebaseline_after > 0 && held >= self.config.rebaseline_after {
+                    warn!(
+                        pair = %format!("{}/{}", from, to),
+                        previous = %previous_rate,
+                        rate = %fetched.base_rate,
+                        refreshes = held,
+                        "circuit breaker: jumped rate held, taking it as the new baseline"
+                    );
+                    rebaselined = Some(jump_percent);
+                } else {
+                    self.alarm(
+                        from,
+                        to,
+                        &format!(
+                            "circuit breaker: {} moved {}% from {} to {} (limit {}%, held {} of {} refreshes)",
+                            fetched.source,
+                            jump_percent,
+                            previous_rate,
+                            fetched.base_rate,
+                            self.config.max_jump_percent,
+                            held,
+                            self.config.rebaseline_after
+                        ),
+                    )
+                    .await;
+                    return RefreshOutcome::Tripped { jump_percent };
+                }
+            }
+        }
+        self.jumps.lock().unwrap().forget(&pair);
+
+        match self
+            .service
+            .update_rate(from, to, fetched.base_rate.clone(), &fetched.source)
+            .await
+        {
+            Ok(()) => match rebaselined {
+                Some(jump_percent) => RefreshOutcome::Rebaselined { jump_percent },
+                None => RefreshOutcome::Updated,
+            },
+            Err(e) => {
+                warn!(pair = %format!("{}/{}", from, to), error = %e, "failed to store refreshed rate");
+                RefreshOutcome::Failed {
+                    marked_stale: false,
+                }
+            }
+        }
+    }
+
+    /// Mark the pair stale and raise the staleness alarm
+    async fn alarm(&self, from: &str, to: &str, reason: &str) -> bool {
+        error!(
+            alarm = "exchange_rate_stale",
+            pair = %format!("{}/{}", from, to),
+            reason = %reason,
+            "exchange rate marked stale"
+        );
+        match self.service.mark_stale(from, to, reason).await {
+            Ok(marked) => marked,
+            Err(e) => {
+                warn!(pair = %format!("{}/{}", from, to), error = %e, "failed to mark rate stale");
+                false
+            }
+        }
+    }
+}
+
+/// Rejected rates per pair, for deciding when a jump is real
+#[derive(Debug, Default)]
+struct JumpTracker {
+    pending: HashMap<(String, String), (BigDecimal, u32)>,
+}
+
+impl JumpTracker {
+    /// Note a rejected rate for `pair` and return how many refreshes in a
+    /// row have now reported it, counting rates within `max_percent` of
+    /// the first one as the same
+    fn observe(
+        &mut self,
+        pair: (String, String),
+        rate: &BigDecimal,
+        max_percent: &BigDecimal,
+    ) -> u32 {
+        let entry = self.pending.entry(pair).or_insert((rate.clone(), 0));
+        if jump_exceeds(&entry.0, rate, max_percent).is_some() {
+            *entry = (rate.clone(), 0);
+        }
+        entry.1 += 1;
+        entry.1
+    }
+
+    fn forget(&mut self, pair: &(String, String)) {
+        self.pending.remove(pair);
+    }
+}
+
+/// Percentage move from `previous` to `current`, if it exceeds `max_percent`
+pub fn jump_exceeds(
+    previous: &BigDecimal,
+    current: &BigDecimal,
+    max_percent: &BigDecimal,
+) -> Option<BigDecimal> {
+    if *previous <= 0 {
+        return None;
+    }
+    let jump = ((current - previous).abs() * BigDecimal::from(100) / previous).round(4);
+    (jump > *max_percent).then_some(jump)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jump_exceeds_threshold() {
+        let max = BigDecimal::from(5);
+        let previous = BigDecimal::from(1500);
+        assert_eq!(jump_exceeds(&previous, &BigDecimal::from(1560), &max), None);
+        assert_eq!(
+            jump_exceeds(&previous, &BigDecimal::from(1650), &max),
+            Some(BigDecimal::from(10))
+        );
+        assert_eq!(
+            jump_exceeds(&previous, &BigDecimal::from(1380), &max),
+            Some(BigDecimal::from(8))
+        );
+        assert_eq!(
+            jump_exceeds(&BigDecimal::from(0), &BigDecimal::from(1), &max),
+            None
+        );
+    }
+
+    #[test]
+    fn test_jump_tracker_counts_refreshes_that_agree() {
+        let max = BigDecimal::from(5);
+        let pair = ("USD".to_string(), "NGN".to_string());
+        let mut jumps = JumpTracker::default();
+        let observe = |jumps: &mut JumpTracker, rate: i64| {
+            jumps.observe(pair.clone(), &BigDecimal::from(rate), &max)
+        };
+        assert_eq!(observe(&mut jumps, 1650), 1);
+        assert_eq!(observe(&mut jumps, 1660), 2);
+        // A rate far from the first rejected one starts the count again
+        assert_eq!(observe(&mut jumps, 1900), 1);
+        assert_eq!(observe(&mut jumps, 1890), 2);
+
+        jumps.forget(&pair);
+        assert_eq!(observe(&mut jumps, 1890), 1);
+    }
+}
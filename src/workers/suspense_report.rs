@@ -0,0 +1,66 @@
+use crate::database::job_repository::Job;
+use crate::services::jobs::{JobHandler, JobOutcome, JobPayload};
+use crate::services::suspense_payment::SuspensePaymentService;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Buckets whose payments have waited long enough to call out
+const OVERDUE_BUCKETS: [&str; 2] = ["7-30d", "30d+"];
+
+/// Payload of the recurring job that reports unresolved suspense payments
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SuspenseAgingReportJob {}
+
+impl JobPayload for SuspenseAgingReportJob {
+    const KIND: &'static str = "suspense.aging_report";
+}
+
+/// Runs `suspense.aging_report`: summarises unresolved suspense payments by
+/// age so ops can see what has been left waiting
+pub struct SuspenseAgingReporter {
+    service: Arc<SuspensePaymentService>,
+}
+
+impl SuspenseAgingReporter {
+    pub fn new(service: Arc<SuspensePaymentService>) -> Self {
+        Self { service }
+    }
+}
+
+#[async_trait]
+impl JobHandler for SuspenseAgingReporter {
+    fn kind(&self) -> &'static str {
+        SuspenseAgingReportJob::KIND
+    }
+
+    async fn handle(&self, _job: &Job) -> anyhow::Result<JobOutcome> {
+        let report = self.service.aging_report().await?;
+        info!(
+            payments = report.payments,
+            amount = %report.amount,
+            "suspense aging report"
+        );
+        for bucket in &report.buckets {
+            if OVERDUE_BUCKETS.contains(&bucket.bucket.as_str()) {
+                warn!(
+                    bucket = %bucket.bucket,
+                    payments = bucket.payments,
+                    amount = %bucket.amount,
+                    oldest_received_at = %bucket.oldest_received_at,
+                    "suspense payments unresolved for over a week"
+                );
+            } else {
+                info!(
+                    bucket = %bucket.bucket,
+                    payments = bucket.payments,
+                    amount = %bucket.amount,
+                    oldest_received_at = %bucket.oldest_received_at,
+                    "suspense payments awaiting resolution"
+                );
+            }
+        }
+        Ok(JobOutcome::Done)
+    }
+}
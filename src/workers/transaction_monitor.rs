@@ -1,11 +1,21 @@
 use crate::chains::stellar::client::{HorizonTransactionRecord, StellarClient};
+use crate::chains::stellar::types::is_payment_operation;
+use crate::database::job_repository::Job;
 use crate::database::repository::Repository;
+use crate::database::suspense_payment_repository::{NewSuspensePayment, SuspensePaymentRepository};
 use crate::database::transaction_repository::TransactionRepository;
 use crate::database::webhook_repository::WebhookRepository;
+use crate::services::distributed_lock::Leadership;
+use crate::services::jobs::{JobHandler, JobOptions, JobOutcome, JobPayload, JobQueue};
+use crate::services::suspense_payment::{is_awaiting_deposit, SuspenseReason};
+use async_trait::async_trait;
+use bigdecimal::{BigDecimal, Zero};
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value as JsonValue};
 use sqlx::PgPool;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use tokio::sync::watch;
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
@@ -15,9 +25,8 @@ use uuid::Uuid;
 
 /// Typed errors produced by the transaction monitor worker.
 ///
-/// These are kept internal to the worker; callers at the `run` level receive
-/// them only through logging — the worker loop never propagates a hard failure
-/// upward so that a single bad transaction cannot crash the whole cycle.
+/// These are kept internal to the worker and surface only through logging, so
+/// that a single bad transaction cannot fail the whole job.
 #[derive(Debug, thiserror::Error)]
 pub enum MonitorError {
     /// A transaction was still pending after the absolute deadline elapsed.
@@ -53,13 +62,15 @@ impl From<anyhow::Error> for MonitorError {
 
 #[derive(Debug, Clone)]
 pub struct TransactionMonitorConfig {
-    /// How often the worker wakes up to poll Stellar.
+    /// How often the `stellar.monitor` job is scheduled, and how long a
+    /// confirmation waits before polling Horizon again for a transaction
+    /// it has not seen yet.
     pub poll_interval: Duration,
     /// Absolute wall-clock deadline from `created_at`; transactions older than
     /// this are abandoned and marked `failed` (regardless of retry count).
     pub pending_timeout: Duration,
-    /// Maximum number of retry attempts before a transaction is permanently
-    /// failed (used in conjunction with the absolute timeout above).
+    /// Retries of a transaction's confirmation job before the transaction is
+    /// permanently failed (used in conjunction with the absolute timeout above).
     pub max_retries: u32,
     /// Maximum number of pending/processing transactions fetched per cycle.
     pub pending_batch_size: i64,
@@ -121,62 +132,84 @@ impl TransactionMonitorConfig {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Jobs
+// ---------------------------------------------------------------------------
+
+/// Payload of the recurring job that finds pending transactions to confirm
+/// and scans the system wallet for incoming payments
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MonitorScanJob {}
+
+impl JobPayload for MonitorScanJob {
+    const KIND: &'static str = "stellar.monitor";
+}
+
+/// Payload of the job that polls Horizon for one pending transaction until
+/// it confirms, fails or times out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfirmTransactionJob {
+    pub transaction_id: String,
+}
+
+impl JobPayload for ConfirmTransactionJob {
+    const KIND: &'static str = "stellar.confirm_transaction";
+}
+
+/// First retry delay for confirmation jobs, doubled for each further one
+const CONFIRMATION_BACKOFF: Duration = Duration::from_secs(10);
+
 // ---------------------------------------------------------------------------
 // Worker
 // ---------------------------------------------------------------------------
 
+/// cNGN paid into the system wallet by one Stellar transaction
+struct IncomingPayment {
+    sender: String,
+    amount: BigDecimal,
+    asset_code: String,
+    asset_issuer: Option<String>,
+}
+
+/// Runs `stellar.monitor`: queues a confirmation job for each pending
+/// transaction and matches incoming system-wallet payments by memo.
 pub struct TransactionMonitorWorker {
     pool: PgPool,
     stellar_client: StellarClient,
+    queue: Arc<JobQueue>,
     config: TransactionMonitorConfig,
-    incoming_cursor: Option<String>,
+    /// Horizon paging cursor for the incoming scan, with the fencing token
+    /// of the leadership term it was advanced under
+    incoming_cursor: Mutex<(Option<i64>, Option<String>)>,
+    leadership: Option<Leadership>,
 }
 
 impl TransactionMonitorWorker {
     pub fn new(
         pool: PgPool,
         stellar_client: StellarClient,
+        queue: Arc<JobQueue>,
         config: TransactionMonitorConfig,
     ) -> Self {
         Self {
             pool,
             stellar_client,
+            queue,
             config,
-            incoming_cursor: None,
+            incoming_cursor: Mutex::new((None, None)),
+            leadership: None,
         }
     }
 
-    pub async fn run(mut self, mut shutdown_rx: watch::Receiver<bool>) {
-        info!(
-            poll_interval_secs = self.config.poll_interval.as_secs(),
-            pending_timeout_secs = self.config.pending_timeout.as_secs(),
-            max_retries = self.config.max_retries,
-            monitoring_window_hours = self.config.monitoring_window_hours,
-            has_system_wallet = self.config.system_wallet_address.is_some(),
-            "stellar transaction monitor worker started"
-        );
-
-        loop {
-            tokio::select! {
-                _ = shutdown_rx.changed() => {
-                    if *shutdown_rx.borrow() {
-                        info!("stellar transaction monitor worker stopping");
-                        break;
-                    }
-                }
-                _ = tokio::time::sleep(self.config.poll_interval) => {
-                    if let Err(e) = self.run_cycle().await {
-                        warn!(error = %e, "transaction monitor cycle failed");
-                    }
-                }
-            }
-        }
-
-        info!("stellar transaction monitor worker stopped");
+    /// Only scan incoming payments while this replica holds `leadership`,
+    /// so one replica pages through the system wallet at a time
+    pub fn with_leadership(mut self, leadership: Leadership) -> Self {
+        self.leadership = Some(leadership);
+        self
     }
 
-    async fn run_cycle(&mut self) -> anyhow::Result<()> {
-        self.process_pending_transactions().await?;
+    async fn run_cycle(&self) -> anyhow::Result<()> {
+        self.enqueue_confirmations().await?;
         self.scan_incoming_transactions().await?;
         Ok(())
     }
@@ -185,7 +218,7 @@ impl TransactionMonitorWorker {
     // Pending-transaction polling
     // -----------------------------------------------------------------------
 
-    async fn process_pending_transactions(&self) -> anyhow::Result<()> {
+    async fn enqueue_confirmations(&self) -> anyhow::Result<()> {
         let tx_repo = TransactionRepository::new(self.pool.clone());
         let pending = tx_repo
             .find_pending_payments_for_monitoring(
@@ -197,197 +230,33 @@ impl TransactionMonitorWorker {
         for tx in pending {
             let tx_id = tx.transaction_id.to_string();
 
-            // ------------------------------------------------------------------
-            // 1. Absolute timeout check (uses created_at so retries don't reset
-            //    the clock).
-            // ------------------------------------------------------------------
-            if is_timed_out(tx.created_at, self.config.pending_timeout) {
-                let elapsed = chrono::Utc::now()
-                    .signed_duration_since(tx.created_at)
-                    .to_std()
-                    .unwrap_or_default();
-                let err = MonitorError::ConfirmationTimeout {
-                    tx_id: tx_id.clone(),
-                    elapsed_secs: elapsed.as_secs(),
-                };
-                warn!(error = %err, "transaction exceeded absolute deadline");
-                self.handle_absolute_timeout(&tx_id, Some(tx.metadata.clone()))
-                    .await?;
-                continue;
-            }
-
-            // ------------------------------------------------------------------
-            // 2. Exponential backoff: skip transactions that have been retried
-            //    but haven't waited long enough since the last attempt.
-            // ------------------------------------------------------------------
-            let retry_count = get_retry_count(Some(&tx.metadata));
-            if retry_count > 0 && !is_ready_for_retry(tx.metadata.get("last_retry_at"), retry_count)
+            // We need a Stellar tx hash to poll Horizon; transactions that
+            // never get one are failed by the absolute timeout.
+            if extract_tx_hash(Some(&tx.metadata)).is_none()
+                && !is_timed_out(tx.created_at, self.config.pending_timeout)
             {
-                continue; // not yet ready; will be picked up in a later cycle
-            }
-
-            // ------------------------------------------------------------------
-            // 3. We need a Stellar tx hash to poll Horizon.
-            // ------------------------------------------------------------------
-            let tx_hash = match extract_tx_hash(Some(&tx.metadata)) {
-                Some(h) => h,
-                None => {
-                    warn!(
-                        transaction_id = %tx_id,
-                        "pending transaction has no stellar hash in metadata"
-                    );
-                    continue;
-                }
-            };
-
-            // ------------------------------------------------------------------
-            // 4. Query Horizon.
-            // ------------------------------------------------------------------
-            match self.stellar_client.get_transaction_by_hash(&tx_hash).await {
-                Ok(record) => {
-                    self.handle_horizon_status(
-                        &tx_id,
-                        record,
-                        Some(tx.metadata.clone()),
-                        retry_count,
-                    )
-                    .await?;
-                }
-                Err(e) => {
-                    let message = e.to_string().to_lowercase();
-                    // Transient states: keep polling without counting a retry.
-                    if message.contains("not found")
-                        || message.contains("network")
-                        || message.contains("timeout")
-                        || message.contains("rate limit")
-                    {
-                        continue;
-                    }
-                    self.fail_or_retry(&tx_id, Some(tx.metadata.clone()), &e.to_string())
-                        .await?;
-                }
+                warn!(
+                    transaction_id = %tx_id,
+                    "pending transaction has no stellar hash in metadata"
+                );
+                continue;
             }
-        }
-
-        Ok(())
-    }
-
-    // -----------------------------------------------------------------------
-    // Status handlers
-    // -----------------------------------------------------------------------
-
-    async fn handle_horizon_status(
-        &self,
-        transaction_id: &str,
-        record: HorizonTransactionRecord,
-        metadata: Option<JsonValue>,
-        attempts: u32,
-    ) -> anyhow::Result<()> {
-        let mut updated = metadata.unwrap_or_else(|| json!({}));
-        merge_status_fields(&mut updated, &record);
 
-        let tx_repo = TransactionRepository::new(self.pool.clone());
-
-        if record.successful {
-            tx_repo
-                .update_status_with_metadata(transaction_id, "completed", updated.clone())
-                .await?;
-
-            // Also write the confirmed hash to the dedicated column.
-            tx_repo
-                .update_blockchain_hash(transaction_id, &record.hash)
-                .await?;
-
-            info!(
-                transaction_id = %transaction_id,
-                tx_hash = %record.hash,
-                ledger = ?record.ledger,
-                attempts = attempts + 1,
-                "transaction confirmed on stellar ledger"
-            );
-
-            self.log_webhook_event(transaction_id, "stellar.transaction.confirmed", updated)
-                .await;
-        } else {
-            let reason = record
-                .result_xdr
-                .as_deref()
-                .unwrap_or("transaction failed on horizon");
-            self.fail_or_retry(transaction_id, Some(updated), reason)
+            // One confirmation job per transaction; retries and backoff
+            // are the job's own
+            self.queue
+                .enqueue(
+                    &ConfirmTransactionJob {
+                        transaction_id: tx_id.clone(),
+                    },
+                    JobOptions::default()
+                        .with_unique_key(format!("{}:{}", ConfirmTransactionJob::KIND, tx_id))
+                        .with_max_attempts(self.config.max_retries as i32 + 1)
+                        .with_backoff(CONFIRMATION_BACKOFF),
+                )
                 .await?;
         }
-        Ok(())
-    }
-
-    /// Called when the absolute `pending_timeout` (measured from `created_at`)
-    /// is exceeded. The transaction is failed immediately without further retries.
-    async fn handle_absolute_timeout(
-        &self,
-        transaction_id: &str,
-        metadata: Option<JsonValue>,
-    ) -> anyhow::Result<()> {
-        let mut updated = metadata.unwrap_or_else(|| json!({}));
-        updated["last_monitor_error"] = json!("absolute pending timeout exceeded");
-        updated["timed_out_at"] = json!(chrono::Utc::now().to_rfc3339());
-
-        let tx_repo = TransactionRepository::new(self.pool.clone());
-        tx_repo
-            .update_status_with_metadata(transaction_id, "failed", updated.clone())
-            .await?;
 
-        self.log_webhook_event(transaction_id, "stellar.transaction.timeout", updated)
-            .await;
-
-        warn!(
-            transaction_id = %transaction_id,
-            "transaction failed: absolute timeout exceeded"
-        );
-        Ok(())
-    }
-
-    async fn fail_or_retry(
-        &self,
-        transaction_id: &str,
-        metadata: Option<JsonValue>,
-        error_message: &str,
-    ) -> anyhow::Result<()> {
-        let retries = next_retry_count(metadata.as_ref());
-        let retryable = is_retryable_error(error_message);
-        let mut updated = metadata.unwrap_or_else(|| json!({}));
-        updated["last_monitor_error"] = json!(error_message);
-        updated["retry_count"] = json!(retries);
-        updated["last_retry_at"] = json!(chrono::Utc::now().to_rfc3339());
-        updated["retryable"] = json!(retryable);
-
-        let tx_repo = TransactionRepository::new(self.pool.clone());
-
-        if retryable && retries <= self.config.max_retries {
-            tx_repo
-                .update_status_with_metadata(transaction_id, "pending", updated.clone())
-                .await?;
-
-            let next_backoff = backoff_delay(retries);
-            warn!(
-                transaction_id = %transaction_id,
-                retry_count = retries,
-                next_retry_after_secs = next_backoff.as_secs(),
-                error = %error_message,
-                "transaction failed with retryable error; scheduled for retry"
-            );
-        } else {
-            tx_repo
-                .update_status_with_metadata(transaction_id, "failed", updated.clone())
-                .await?;
-
-            let err = MonitorError::RetryExceeded {
-                tx_id: transaction_id.to_string(),
-                attempts: retries,
-            };
-            warn!(error = %err, original_error = %error_message, "transaction permanently failed");
-
-            self.log_webhook_event(transaction_id, "stellar.transaction.failed", updated)
-                .await;
-        }
         Ok(())
     }
 
@@ -395,22 +264,38 @@ impl TransactionMonitorWorker {
     // Incoming payment scanning
     // -----------------------------------------------------------------------
 
-    async fn scan_incoming_transactions(&mut self) -> anyhow::Result<()> {
+    async fn scan_incoming_transactions(&self) -> anyhow::Result<()> {
         let system_wallet = match self.config.system_wallet_address.as_deref() {
             Some(addr) => addr,
             None => return Ok(()),
         };
+        let token = match &self.leadership {
+            Some(leadership) => match leadership.fencing_token() {
+                Some(token) => Some(token),
+                None => return Ok(()),
+            },
+            None => None,
+        };
 
+        // A cursor from an earlier term may lag what another leader
+        // scanned since, so each term starts over
+        let cursor = {
+            let saved = self
+                .incoming_cursor
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            if saved.0 == token {
+                saved.1.clone()
+            } else {
+                None
+            }
+        };
         let page = self
             .stellar_client
-            .list_account_transactions(
-                system_wallet,
-                self.config.incoming_limit,
-                self.incoming_cursor.as_deref(),
-            )
+            .list_account_transactions(system_wallet, self.config.incoming_limit, cursor.as_deref())
             .await?;
 
-        let mut newest_cursor = self.incoming_cursor.clone();
+        let mut newest_cursor = cursor;
         for tx in page.records {
             if !tx.successful {
                 continue;
@@ -420,40 +305,69 @@ impl TransactionMonitorWorker {
                 newest_cursor = Some(cursor);
             }
 
+            let payment = match self.incoming_cngn_payment(&tx.hash, system_wallet).await {
+                Ok(Some(payment)) => payment,
+                Ok(None) => continue,
+                Err(e) => {
+                    warn!(hash = %tx.hash, error = %e, "failed to inspect incoming transaction");
+                    continue;
+                }
+            };
+
+            // Payments without a memo cannot be attributed; park them for ops
             let memo = match tx.memo.as_deref() {
                 Some(m) if !m.trim().is_empty() => m,
-                _ => continue,
+                _ => {
+                    self.record_suspense(SuspenseReason::NoMemo, None, &tx, payment)
+                        .await;
+                    continue;
+                }
             };
 
-            let looks_like_incoming = self
-                .is_incoming_cngn_payment(&tx.hash, system_wallet)
-                .await
-                .unwrap_or(false);
-            if !looks_like_incoming {
-                continue;
-            }
-
-            let tx_repo = TransactionRepository::new(self.pool.clone());
             let (tx_id_str, is_offramp) = if memo.starts_with("WD-") {
                 (&memo[3..], true)
             } else {
                 (memo, false)
             };
 
+            // Not a transaction id at all, e.g. an exchange's own reference
+            if Uuid::parse_str(tx_id_str).is_err() {
+                self.record_suspense(SuspenseReason::UnknownMemo, Some(memo), &tx, payment)
+                    .await;
+                continue;
+            }
+
             let tx_repo = TransactionRepository::new(self.pool.clone());
             match tx_repo.find_by_id(tx_id_str).await {
-                Ok(Some(db_tx)) => {
-                    let is_pending = db_tx.status == "pending"
-                        || db_tx.status == "processing"
-                        || db_tx.status == "pending_payment";
-                    if !is_pending {
+                Ok(Some(mut db_tx)) => {
+                    let is_pending = is_awaiting_deposit(&db_tx.status);
+                    let is_top_up = db_tx.status == "awaiting_top_up";
+                    if !is_pending && !is_top_up {
+                        // A rescan returns deposits already credited; anything
+                        // else arrived after the transaction closed
+                        if !db_tx.has_deposit(&tx.hash) {
+                            self.record_suspense(
+                                SuspenseReason::TransactionClosed,
+                                Some(memo),
+                                &tx,
+                                payment,
+                            )
+                            .await;
+                        }
                         continue;
                     }
 
+                    // A rescan can return the original deposit or an
+                    // earlier top-up
+                    if is_top_up && !db_tx.record_top_up(&tx.hash) {
+                        continue;
+                    }
                     let mut metadata = db_tx.metadata.clone();
-                    metadata["incoming_hash"] = json!(tx.hash);
-                    metadata["incoming_ledger"] = json!(tx.ledger);
-                    metadata["incoming_confirmed_at"] = json!(chrono::Utc::now().to_rfc3339());
+                    if !is_top_up {
+                        metadata["incoming_hash"] = json!(tx.hash);
+                        metadata["incoming_ledger"] = json!(tx.ledger);
+                        metadata["incoming_confirmed_at"] = json!(chrono::Utc::now().to_rfc3339());
+                    }
 
                     let next_status = if is_offramp || db_tx.r#type == "offramp" {
                         "cngn_received"
@@ -469,10 +383,13 @@ impl TransactionMonitorWorker {
                         )
                         .await?;
 
-                    // Also persist the confirmed hash to the dedicated column.
-                    tx_repo
-                        .update_blockchain_hash(&db_tx.transaction_id.to_string(), &tx.hash)
-                        .await?;
+                    // Also persist the confirmed hash to the dedicated column;
+                    // it keeps pointing at the original deposit.
+                    if !is_top_up {
+                        tx_repo
+                            .update_blockchain_hash(&db_tx.transaction_id.to_string(), &tx.hash)
+                            .await?;
+                    }
 
                     info!(
                         transaction_id = %db_tx.transaction_id,
@@ -484,15 +401,23 @@ impl TransactionMonitorWorker {
 
                     let event_type = if next_status == "completed" {
                         "stellar.incoming.matched"
+                    } else if is_top_up {
+                        "stellar.offramp.top_up"
                     } else {
                         "stellar.offramp.received"
                     };
 
-                    self.log_webhook_event(&db_tx.transaction_id.to_string(), event_type, metadata)
-                        .await;
+                    log_webhook_event(
+                        &self.pool,
+                        &db_tx.transaction_id.to_string(),
+                        event_type,
+                        metadata,
+                    )
+                    .await;
                 }
                 Ok(_) => {
-                    self.log_unmatched_incoming(memo, &tx).await;
+                    self.record_suspense(SuspenseReason::UnknownMemo, Some(memo), &tx, payment)
+                        .await;
                 }
                 Err(e) => {
                     warn!(
@@ -504,15 +429,19 @@ impl TransactionMonitorWorker {
             }
         }
 
-        self.incoming_cursor = newest_cursor;
+        *self
+            .incoming_cursor
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = (token, newest_cursor);
         Ok(())
     }
 
-    async fn is_incoming_cngn_payment(
+    /// The cNGN paid into the system wallet by a transaction, if any
+    async fn incoming_cngn_payment(
         &self,
         tx_hash: &str,
         system_wallet: &str,
-    ) -> anyhow::Result<bool> {
+    ) -> anyhow::Result<Option<IncomingPayment>> {
         let issuer = std::env::var("CNGN_ISSUER_TESTNET")
             .or_else(|_| std::env::var("CNGN_ISSUER_MAINNET"))
             .unwrap_or_default();
@@ -520,9 +449,10 @@ impl TransactionMonitorWorker {
             .stellar_client
             .get_transaction_operations(tx_hash)
             .await?;
+        let mut incoming: Option<IncomingPayment> = None;
         for op in operations {
             let op_type = op.get("type").and_then(|v| v.as_str()).unwrap_or("");
-            if op_type != "payment" {
+            if !is_payment_operation(op_type) {
                 continue;
             }
 
@@ -532,65 +462,363 @@ impl TransactionMonitorWorker {
                 .get("asset_issuer")
                 .and_then(|v| v.as_str())
                 .unwrap_or("");
-            if destination == system_wallet && asset_code.eq_ignore_ascii_case("cngn") {
-                if issuer.is_empty() || asset_issuer == issuer {
-                    return Ok(true);
+            if destination != system_wallet
+                || !asset_code.eq_ignore_ascii_case("cngn")
+                || (!issuer.is_empty() && asset_issuer != issuer)
+            {
+                continue;
+            }
+
+            let amount = op
+                .get("amount")
+                .and_then(|v| v.as_str())
+                .and_then(|v| BigDecimal::from_str(v).ok())
+                .unwrap_or_default();
+            match incoming.as_mut() {
+                Some(payment) => payment.amount += amount,
+                None => {
+                    incoming = Some(IncomingPayment {
+                        sender: op
+                            .get("from")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or_default()
+                            .to_string(),
+                        amount,
+                        asset_code: asset_code.to_string(),
+                        asset_issuer: Some(asset_issuer.to_string())
+                            .filter(|issuer| !issuer.is_empty()),
+                    })
                 }
             }
         }
-        Ok(false)
+        Ok(incoming.filter(|payment| !payment.amount.is_zero()))
     }
 
-    // -----------------------------------------------------------------------
-    // Webhook helpers
-    // -----------------------------------------------------------------------
-
-    async fn log_webhook_event(&self, transaction_id: &str, event_type: &str, payload: JsonValue) {
-        let parsed_tx_id = Uuid::parse_str(transaction_id).ok();
-        let repo = WebhookRepository::new(self.pool.clone());
-        let event_id = format!("{}:{}", event_type, transaction_id);
-        if let Err(e) = repo
-            .log_event(
-                &event_id,
-                "stellar",
-                event_type,
-                payload,
-                None,
-                parsed_tx_id,
-            )
+    /// Park a payment that matched no transaction in the suspense ledger
+    async fn record_suspense(
+        &self,
+        reason: SuspenseReason,
+        memo: Option<&str>,
+        tx: &HorizonTransactionRecord,
+        payment: IncomingPayment,
+    ) {
+        let received_at = tx
+            .created_at
+            .as_deref()
+            .and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok())
+            .map(|at| at.with_timezone(&chrono::Utc))
+            .unwrap_or_else(chrono::Utc::now);
+        let repo = SuspensePaymentRepository::new(self.pool.clone());
+        match repo
+            .record(&NewSuspensePayment {
+                stellar_tx_hash: tx.hash.clone(),
+                ledger: tx.ledger,
+                sender: payment.sender,
+                amount: payment.amount,
+                asset_code: payment.asset_code,
+                asset_issuer: payment.asset_issuer,
+                memo: memo.map(str::to_string),
+                reason: reason.as_str().to_string(),
+                received_at,
+            })
             .await
         {
-            warn!(
-                transaction_id = %transaction_id,
-                event_type = %event_type,
+            Ok(Some(recorded)) => warn!(
+                suspense_payment_id = %recorded.id,
+                hash = %recorded.stellar_tx_hash,
+                sender = %recorded.sender,
+                amount = %recorded.amount,
+                reason = %recorded.reason,
+                "unmatched incoming cNGN payment recorded in suspense"
+            ),
+            // Already recorded on an earlier scan
+            Ok(None) => {}
+            Err(e) => error!(
+                hash = %tx.hash,
                 error = %e,
-                "failed to write webhook event"
-            );
+                "failed to record unmatched incoming payment"
+            ),
         }
     }
+}
 
-    async fn log_unmatched_incoming(&self, memo: &str, tx: &HorizonTransactionRecord) {
-        let repo = WebhookRepository::new(self.pool.clone());
-        let event_id = format!("unmatched:{}", tx.hash);
-        let payload = json!({
-            "memo": memo,
-            "hash": tx.hash,
-            "ledger": tx.ledger,
-            "created_at": tx.created_at,
-        });
-        if let Err(e) = repo
-            .log_event(
-                &event_id,
-                "stellar",
-                "stellar.incoming.unmatched",
-                payload,
-                None,
-                None,
-            )
-            .await
-        {
-            error!(error = %e, "failed to log unmatched incoming transaction");
+#[async_trait]
+impl JobHandler for TransactionMonitorWorker {
+    fn kind(&self) -> &'static str {
+        MonitorScanJob::KIND
+    }
+
+    async fn handle(&self, _job: &Job) -> anyhow::Result<JobOutcome> {
+        self.run_cycle().await?;
+        Ok(JobOutcome::Done)
+    }
+}
+
+/// Runs `stellar.confirm_transaction`: polls Horizon for one pending
+/// transaction and completes or fails it. Errors worth retrying fail the
+/// attempt so the job backs off; the transaction is failed once the job's
+/// attempts run out or its absolute deadline passes.
+pub struct TransactionConfirmationWorker {
+    pool: PgPool,
+    stellar_client: StellarClient,
+    config: TransactionMonitorConfig,
+}
+
+impl TransactionConfirmationWorker {
+    pub fn new(
+        pool: PgPool,
+        stellar_client: StellarClient,
+        config: TransactionMonitorConfig,
+    ) -> Self {
+        Self {
+            pool,
+            stellar_client,
+            config,
+        }
+    }
+
+    async fn confirm(&self, job: &Job, tx_id: &str) -> anyhow::Result<JobOutcome> {
+        let tx_repo = TransactionRepository::new(self.pool.clone());
+        let tx = match tx_repo.find_by_id(tx_id).await? {
+            Some(tx) => tx,
+            None => {
+                warn!(transaction_id = %tx_id, "transaction to confirm no longer exists");
+                return Ok(JobOutcome::Done);
+            }
+        };
+        // Resolved elsewhere, e.g. by the incoming payment scan
+        if !matches!(
+            tx.status.as_str(),
+            "pending" | "processing" | "pending_payment"
+        ) {
+            return Ok(JobOutcome::Done);
+        }
+
+        // ------------------------------------------------------------------
+        // 1. Absolute timeout check (uses created_at so retries don't reset
+        //    the clock).
+        // ------------------------------------------------------------------
+        if is_timed_out(tx.created_at, self.config.pending_timeout) {
+            let elapsed = chrono::Utc::now()
+                .signed_duration_since(tx.created_at)
+                .to_std()
+                .unwrap_or_default();
+            let err = MonitorError::ConfirmationTimeout {
+                tx_id: tx_id.to_string(),
+                elapsed_secs: elapsed.as_secs(),
+            };
+            warn!(error = %err, "transaction exceeded absolute deadline");
+            self.handle_absolute_timeout(tx_id, Some(tx.metadata.clone()))
+                .await?;
+            return Ok(JobOutcome::Done);
+        }
+
+        // ------------------------------------------------------------------
+        // 2. We need a Stellar tx hash to poll Horizon.
+        // ------------------------------------------------------------------
+        let tx_hash = match extract_tx_hash(Some(&tx.metadata)) {
+            Some(h) => h,
+            None => return Ok(JobOutcome::Snooze(self.config.poll_interval)),
+        };
+
+        // ------------------------------------------------------------------
+        // 3. Query Horizon.
+        // ------------------------------------------------------------------
+        match self.stellar_client.get_transaction_by_hash(&tx_hash).await {
+            Ok(record) => {
+                self.handle_horizon_status(job, tx_id, record, Some(tx.metadata.clone()))
+                    .await
+            }
+            Err(e) => {
+                let message = e.to_string().to_lowercase();
+                // Transient states: keep polling without using an attempt.
+                if message.contains("not found")
+                    || message.contains("network")
+                    || message.contains("timeout")
+                    || message.contains("rate limit")
+                {
+                    return Ok(JobOutcome::Snooze(self.config.poll_interval));
+                }
+                self.fail_or_retry(job, tx_id, Some(tx.metadata.clone()), &e.to_string())
+                    .await
+            }
+        }
+    }
+
+    // -----------------------------------------------------------------------
+    // Status handlers
+    // -----------------------------------------------------------------------
+
+    async fn handle_horizon_status(
+        &self,
+        job: &Job,
+        transaction_id: &str,
+        record: HorizonTransactionRecord,
+        metadata: Option<JsonValue>,
+    ) -> anyhow::Result<JobOutcome> {
+        let mut updated = metadata.unwrap_or_else(|| json!({}));
+        merge_status_fields(&mut updated, &record);
+
+        if !record.successful {
+            let reason = record
+                .result_xdr
+                .as_deref()
+                .unwrap_or("transaction failed on horizon");
+            return self
+                .fail_or_retry(job, transaction_id, Some(updated), reason)
+                .await;
         }
+
+        let tx_repo = TransactionRepository::new(self.pool.clone());
+        tx_repo
+            .update_status_with_metadata(transaction_id, "completed", updated.clone())
+            .await?;
+
+        // Also write the confirmed hash to the dedicated column.
+        tx_repo
+            .update_blockchain_hash(transaction_id, &record.hash)
+            .await?;
+
+        info!(
+            transaction_id = %transaction_id,
+            tx_hash = %record.hash,
+            ledger = ?record.ledger,
+            attempts = job.attempts,
+            "transaction confirmed on stellar ledger"
+        );
+
+        log_webhook_event(
+            &self.pool,
+            transaction_id,
+            "stellar.transaction.confirmed",
+            updated,
+        )
+        .await;
+        Ok(JobOutcome::Done)
+    }
+
+    /// Called when the absolute `pending_timeout` (measured from `created_at`)
+    /// is exceeded. The transaction is failed immediately without further retries.
+    async fn handle_absolute_timeout(
+        &self,
+        transaction_id: &str,
+        metadata: Option<JsonValue>,
+    ) -> anyhow::Result<()> {
+        let mut updated = metadata.unwrap_or_else(|| json!({}));
+        updated["last_monitor_error"] = json!("absolute pending timeout exceeded");
+        updated["timed_out_at"] = json!(chrono::Utc::now().to_rfc3339());
+
+        let tx_repo = TransactionRepository::new(self.pool.clone());
+        tx_repo
+            .update_status_with_metadata(transaction_id, "failed", updated.clone())
+            .await?;
+
+        log_webhook_event(
+            &self.pool,
+            transaction_id,
+            "stellar.transaction.timeout",
+            updated,
+        )
+        .await;
+
+        warn!(
+            transaction_id = %transaction_id,
+            "transaction failed: absolute timeout exceeded"
+        );
+        Ok(())
+    }
+
+    /// Fail the job attempt for a retryable error while attempts remain, so
+    /// the queue backs off; otherwise fail the transaction for good.
+    async fn fail_or_retry(
+        &self,
+        job: &Job,
+        transaction_id: &str,
+        metadata: Option<JsonValue>,
+        error_message: &str,
+    ) -> anyhow::Result<JobOutcome> {
+        let retryable = is_retryable_error(error_message);
+        let mut updated = metadata.unwrap_or_else(|| json!({}));
+        updated["last_monitor_error"] = json!(error_message);
+        updated["monitor_attempts"] = json!(job.attempts);
+        updated["retryable"] = json!(retryable);
+
+        let tx_repo = TransactionRepository::new(self.pool.clone());
+
+        if retryable && !job.is_last_attempt() {
+            tx_repo
+                .update_status_with_metadata(transaction_id, "pending", updated)
+                .await?;
+            return Err(anyhow::anyhow!(
+                "retryable stellar error for transaction {}: {}",
+                transaction_id,
+                error_message
+            ));
+        }
+
+        tx_repo
+            .update_status_with_metadata(transaction_id, "failed", updated.clone())
+            .await?;
+
+        let err = MonitorError::RetryExceeded {
+            tx_id: transaction_id.to_string(),
+            attempts: job.attempts.max(0) as u32,
+        };
+        warn!(error = %err, original_error = %error_message, "transaction permanently failed");
+
+        log_webhook_event(
+            &self.pool,
+            transaction_id,
+            "stellar.transaction.failed",
+            updated,
+        )
+        .await;
+        Ok(JobOutcome::Done)
+    }
+}
+
+#[async_trait]
+impl JobHandler for TransactionConfirmationWorker {
+    fn kind(&self) -> &'static str {
+        ConfirmTransactionJob::KIND
+    }
+
+    async fn handle(&self, job: &Job) -> anyhow::Result<JobOutcome> {
+        let payload: ConfirmTransactionJob = job.payload_as()?;
+        self.confirm(job, &payload.transaction_id).await
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Webhook helpers
+// ---------------------------------------------------------------------------
+
+async fn log_webhook_event(
+    pool: &PgPool,
+    transaction_id: &str,
+    event_type: &str,
+    payload: JsonValue,
+) {
+    let parsed_tx_id = Uuid::parse_str(transaction_id).ok();
+    let repo = WebhookRepository::new(pool.clone());
+    let event_id = format!("{}:{}", event_type, transaction_id);
+    if let Err(e) = repo
+        .log_event(
+            &event_id,
+            "stellar",
+            event_type,
+            payload,
+            None,
+            parsed_tx_id,
+        )
+        .await
+    {
+        warn!(
+            transaction_id = %transaction_id,
+            event_type = %event_type,
+            error = %e,
+            "failed to write webhook event"
+        );
     }
 }
 
@@ -616,62 +844,6 @@ fn extract_tx_hash(metadata: Option<&JsonValue>) -> Option<String> {
     None
 }
 
-/// Return the current retry count stored in metadata (0 if absent).
-fn get_retry_count(metadata: Option<&JsonValue>) -> u32 {
-    metadata
-        .and_then(|m| m.get("retry_count"))
-        .and_then(|v| v.as_u64())
-        .unwrap_or(0) as u32
-}
-
-/// Increment the retry count by 1 (used when scheduling the next retry).
-fn next_retry_count(metadata: Option<&JsonValue>) -> u32 {
-    get_retry_count(metadata) + 1
-}
-
-/// Exponential backoff schedule for retry attempts.
-///
-/// | attempt | delay   |
-/// |---------|---------|
-/// | 0       | 0 s     |
-/// | 1       | 10 s    |
-/// | 2       | 30 s    |
-/// | 3       | 2 min   |
-/// | 4       | 5 min   |
-/// | ≥ 5     | 10 min  |
-pub fn backoff_delay(retry_count: u32) -> Duration {
-    match retry_count {
-        0 => Duration::from_secs(0),
-        1 => Duration::from_secs(10),
-        2 => Duration::from_secs(30),
-        3 => Duration::from_secs(120),
-        4 => Duration::from_secs(300),
-        _ => Duration::from_secs(600),
-    }
-}
-
-/// Returns `true` if enough time has elapsed since `last_retry_at` to allow
-/// the next polling attempt, according to the exponential backoff schedule.
-fn is_ready_for_retry(last_retry_at: Option<&JsonValue>, retry_count: u32) -> bool {
-    let delay = backoff_delay(retry_count);
-    if delay.is_zero() {
-        return true;
-    }
-
-    let last = last_retry_at
-        .and_then(|v| v.as_str())
-        .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
-        .map(|dt| dt.with_timezone(&chrono::Utc));
-
-    match last {
-        None => true, // no prior attempt recorded → eligible immediately
-        Some(last_dt) => {
-            let elapsed = chrono::Utc::now() - last_dt;
-            elapsed.to_std().map(|d| d >= delay).unwrap_or(true)
-        }
-    }
-}
-
 /// Returns `true` when `last_updated` is older than `timeout` (used for the
 /// absolute deadline check against `created_at`).
 fn is_timed_out(last_updated: chrono::DateTime<chrono::Utc>, timeout: Duration) -> bool {
@@ -769,28 +941,6 @@ mod tests {
         assert_eq!(extract_tx_hash(Some(&meta)).as_deref(), Some("fallback"));
     }
 
-    // --- retry counts -------------------------------------------------------
-
-    #[test]
-    fn retry_count_defaults_to_zero() {
-        assert_eq!(get_retry_count(None), 0);
-        let meta = json!({});
-        assert_eq!(get_retry_count(Some(&meta)), 0);
-    }
-
-    #[test]
-    fn retry_count_reads_stored_value() {
-        let meta = json!({ "retry_count": 4 });
-        assert_eq!(get_retry_count(Some(&meta)), 4);
-    }
-
-    #[test]
-    fn next_retry_count_increments() {
-        assert_eq!(next_retry_count(None), 1);
-        let meta = json!({ "retry_count": 4 });
-        assert_eq!(next_retry_count(Some(&meta)), 5);
-    }
-
     // --- timeout detection --------------------------------------------------
 
     #[test]
@@ -803,62 +953,6 @@ mod tests {
         assert!(is_timed_out(old, Duration::from_secs(30)));
     }
 
-    // --- exponential backoff schedule ---------------------------------------
-
-    #[test]
-    fn backoff_delay_schedule_is_correct() {
-        assert_eq!(backoff_delay(0), Duration::from_secs(0));
-        assert_eq!(backoff_delay(1), Duration::from_secs(10));
-        assert_eq!(backoff_delay(2), Duration::from_secs(30));
-        assert_eq!(backoff_delay(3), Duration::from_secs(120));
-        assert_eq!(backoff_delay(4), Duration::from_secs(300));
-        assert_eq!(backoff_delay(5), Duration::from_secs(600));
-        assert_eq!(backoff_delay(99), Duration::from_secs(600)); // capped
-    }
-
-    // --- is_ready_for_retry -------------------------------------------------
-
-    #[test]
-    fn first_attempt_always_ready() {
-        // retry_count == 0 → delay == 0 → always ready
-        assert!(is_ready_for_retry(None, 0));
-        assert!(is_ready_for_retry(Some(&json!("2026-01-01T00:00:00Z")), 0));
-    }
-
-    #[test]
-    fn missing_last_retry_at_is_ready() {
-        // If metadata has a non-zero retry_count but no timestamp, treat as ready.
-        assert!(is_ready_for_retry(None, 3));
-    }
-
-    #[test]
-    fn recent_last_retry_at_is_not_ready() {
-        // retry 1 → 10 s delay; timestamp 2 s ago → not ready
-        let two_seconds_ago = (chrono::Utc::now() - chrono::Duration::seconds(2)).to_rfc3339();
-        let ts = json!(two_seconds_ago);
-        assert!(!is_ready_for_retry(Some(&ts), 1));
-    }
-
-    #[test]
-    fn old_enough_last_retry_at_is_ready() {
-        // retry 1 → 10 s delay; timestamp 15 s ago → ready
-        let fifteen_seconds_ago = (chrono::Utc::now() - chrono::Duration::seconds(15)).to_rfc3339();
-        let ts = json!(fifteen_seconds_ago);
-        assert!(is_ready_for_retry(Some(&ts), 1));
-    }
-
-    #[test]
-    fn retry_2_requires_30s_gap() {
-        let twenty_seconds_ago = (chrono::Utc::now() - chrono::Duration::seconds(20)).to_rfc3339();
-        let ts = json!(twenty_seconds_ago);
-        assert!(!is_ready_for_retry(Some(&ts), 2), "20s < 30s → not ready");
-
-        let thirty_five_seconds_ago =
-            (chrono::Utc::now() - chrono::Duration::seconds(35)).to_rfc3339();
-        let ts2 = json!(thirty_five_seconds_ago);
-        assert!(is_ready_for_retry(Some(&ts2), 2), "35s > 30s → ready");
-    }
-
     // --- merge_status_fields ------------------------------------------------
 
     #[test]
@@ -0,0 +1,70 @@
+use std::sync::Arc;
+use tokio::sync::watch;
+use tracing::{error, info};
+
+use crate::services::distributed_lock::instance_id;
+use crate::services::webhook_processor::WebhookProcessor;
+
+/// Drains the webhook queue. Several workers can run side by side, in this
+/// process or others; claims skip rows another worker holds.
+pub struct WebhookQueueWorker {
+    processor: Arc<WebhookProcessor>,
+    worker_id: usize,
+    /// Names this worker on the leases it takes
+    holder: String,
+}
+
+impl WebhookQueueWorker {
+    pub fn new(processor: Arc<WebhookProcessor>, worker_id: usize) -> Self {
+        Self {
+            processor,
+            worker_id,
+            holder: format!("{}-{}", instance_id(), worker_id),
+        }
+    }
+
+    pub async fn run(self, mut shutdown_rx: watch::Receiver<bool>) {
+        let poll_interval = self.processor.config().poll_interval;
+        info!(
+            worker_id = self.worker_id,
+            poll_interval_ms = poll_interval.as_millis() as u64,
+            "webhook queue worker started"
+        );
+
+        loop {
+            // Keep draining while batches come back full
+            let backlog = match self.processor.process_due(&self.holder).await {
+                Ok(summary) => {
+                    if summary.claimed > 0 {
+                        info!(
+                            worker_id = self.worker_id,
+                            completed = summary.completed,
+                            retrying = summary.retrying,
+                            dead_lettered = summary.dead_lettered,
+                            "processed webhook batch"
+                        );
+                    }
+                    summary.claimed as i64 >= self.processor.config().batch_size
+                }
+                Err(e) => {
+                    error!(worker_id = self.worker_id, error = %e, "failed to drain webhook queue");
+                    false
+                }
+            };
+            if backlog && !*shutdown_rx.borrow() {
+                continue;
+            }
+
+            tokio::select! {
+                _ = shutdown_rx.changed() => {
+                    if *shutdown_rx.borrow() {
+                        break;
+                    }
+                }
+                _ = tokio::time::sleep(poll_interval) => {}
+            }
+        }
+
+        info!(worker_id = self.worker_id, "webhook queue worker stopped");
+    }
+}
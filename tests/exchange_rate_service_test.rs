@@ -17,7 +17,8 @@ mod tests {
     };
     use Bitmesh_backend::database::repository::Repository;
     use Bitmesh_backend::services::exchange_rate::{
-        ConversionDirection, ConversionRequest, ExchangeRateService, ExchangeRateServiceConfig,
+        ConversionDirection, ConversionRequest, ExchangeRateError, ExchangeRateResult,
+        ExchangeRateService, ExchangeRateServiceConfig, RateData, RateProvider,
     };
     use Bitmesh_backend::services::fee_structure::FeeStructureService;
     use Bitmesh_backend::services::rate_providers::FixedRateProvider;
@@ -325,4 +326,147 @@ mod tests {
 
         assert_eq!(historical_rate, rate);
     }
+
+    #[tokio::test]
+    #[ignore] // Requires database
+    async fn test_historical_rate_as_of_previous_rate() {
+        let pool = setup_test_db().await;
+        let repo = ExchangeRateRepository::new(pool.clone());
+        let service = ExchangeRateService::new(repo, ExchangeRateServiceConfig::default());
+
+        service
+            .update_rate("USD", "NGN", BigDecimal::from(1500), "test")
+            .await
+            .unwrap();
+        let between = Utc::now();
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        service
+            .update_rate("USD", "NGN", BigDecimal::from(1520), "test")
+            .await
+            .unwrap();
+
+        // The earlier rate is still reported for the time it was in effect
+        let then = service
+            .get_historical_rate("USD", "NGN", between)
+            .await
+            .unwrap();
+        assert_eq!(then, BigDecimal::from(1500));
+
+        let now = service
+            .get_historical_rate("USD", "NGN", Utc::now())
+            .await
+            .unwrap();
+        assert_eq!(now, BigDecimal::from(1520));
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires database
+    async fn test_stale_rates_are_not_quoted_until_refreshed() {
+        let pool = setup_test_db().await;
+        let service = ExchangeRateService::new(
+            ExchangeRateRepository::new(pool.clone()),
+            ExchangeRateServiceConfig::default(),
+        );
+
+        service
+            .update_rate("GBP", "NGN", BigDecimal::from(2000), "test")
+            .await
+            .unwrap();
+        let cross = service.get_cross_rate("GBP", "NGN").await.unwrap();
+        assert!(service.ensure_fresh(&cross).await.is_ok());
+
+        assert!(service
+            .mark_stale("GBP", "NGN", "circuit breaker")
+            .await
+            .unwrap());
+        assert!(service.ensure_fresh(&cross).await.is_err());
+
+        // Accepting a fresh value clears the marker
+        service
+            .update_rate("GBP", "NGN", BigDecimal::from(2010), "test")
+            .await
+            .unwrap();
+        assert!(service.ensure_fresh(&cross).await.is_ok());
+
+        // A rate nobody refreshed is refused by age alone
+        let strict = ExchangeRateService::new(
+            ExchangeRateRepository::new(pool.clone()),
+            ExchangeRateServiceConfig {
+                max_rate_age_seconds: 0,
+                ..ExchangeRateServiceConfig::default()
+            },
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+        assert!(strict.ensure_fresh(&cross).await.is_err());
+    }
+
+    /// Provider quoting a rate the circuit breaker would reject
+    struct JumpedProvider;
+
+    #[async_trait::async_trait]
+    impl RateProvider for JumpedProvider {
+        async fn fetch_rate(&self, from: &str, to: &str) -> ExchangeRateResult<RateData> {
+            Ok(RateData {
+                currency_pair: format!("{}/{}", from, to),
+                base_rate: BigDecimal::from(300),
+                buy_rate: BigDecimal::from(300),
+                sell_rate: BigDecimal::from(300),
+                spread: BigDecimal::from(0),
+                source: "jumped".to_string(),
+                last_updated: Utc::now(),
+            })
+        }
+
+        fn get_supported_pairs(&self) -> Vec<(String, String)> {
+            vec![("ZAR".to_string(), "NGN".to_string())]
+        }
+
+        async fn is_healthy(&self) -> bool {
+            true
+        }
+
+        fn name(&self) -> &str {
+            "JumpedProvider"
+        }
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires database
+    async fn test_conversions_price_only_stored_rates() {
+        let pool = setup_test_db().await;
+        let service = ExchangeRateService::new(
+            ExchangeRateRepository::new(pool.clone()),
+            ExchangeRateServiceConfig::default(),
+        )
+        .add_provider(Arc::new(JumpedProvider));
+
+        service
+            .update_rate("ZAR", "NGN", BigDecimal::from(90), "test")
+            .await
+            .unwrap();
+        assert_eq!(
+            service.get_rate("ZAR", "NGN").await.unwrap(),
+            BigDecimal::from(90)
+        );
+
+        // A tripped pair stays refused: reading it neither fetches the
+        // jumped rate nor clears the marker
+        service
+            .mark_stale("ZAR", "NGN", "circuit breaker")
+            .await
+            .unwrap();
+        let result = service
+            .calculate_conversion(ConversionRequest {
+                from_currency: "ZAR".to_string(),
+                to_currency: "NGN".to_string(),
+                amount: BigDecimal::from(100),
+                direction: ConversionDirection::Sell,
+            })
+            .await;
+        assert!(matches!(result, Err(ExchangeRateError::StaleRate { .. })));
+        let (stored, _) = service.stored_rate("ZAR", "NGN").await.unwrap().unwrap();
+        assert_eq!(stored, BigDecimal::from(90));
+        let cross = service.get_cross_rate("ZAR", "NGN").await.unwrap();
+        assert!(service.ensure_fresh(&cross).await.is_err());
+    }
 }
@@ -1,6 +1,19 @@
+use async_trait::async_trait;
+use Bitmesh_backend::database::transaction_repository::TransactionRepository;
+use Bitmesh_backend::payments::{
+    PaymentMethod, PaymentProvider, PaymentRequest, PaymentResponse, PaymentResult, PaymentState,
+    ProviderName, StatusRequest, StatusResponse, WebhookEvent, WebhookVerificationResult,
+    WithdrawalRequest, WithdrawalResponse,
+};
 use Bitmesh_backend::services::fee_calculation::{FeeCalculationService, FeeBreakdown};
+use Bitmesh_backend::services::fee_rules::FeeContext;
+use Bitmesh_backend::services::payment_orchestrator::{
+    OrchestratorConfig, PaymentInitiationRequest, PaymentOrchestrator,
+};
 use sqlx::PgPool;
 use std::str::FromStr;
+use std::sync::Arc;
+use uuid::Uuid;
 
 type BigDecimal = sqlx::types::BigDecimal;
 
@@ -343,3 +356,209 @@ async fn test_effective_rate_calculation() {
     assert!(breakdown3.effective_rate >= BigDecimal::from_str("0.3").unwrap());
     assert!(breakdown3.effective_rate <= BigDecimal::from_str("0.5").unwrap());
 }
+
+/// Accepts every payment and leaves it pending
+struct PendingProvider;
+
+#[async_trait]
+impl PaymentProvider for PendingProvider {
+    async fn initiate_payment(&self, request: PaymentRequest) -> PaymentResult<PaymentResponse> {
+        Ok(PaymentResponse {
+            status: PaymentState::Pending,
+            transaction_reference: request.transaction_reference,
+            provider_reference: None,
+            payment_url: None,
+            amount_charged: Some(request.amount),
+            fees_charged: None,
+            provider_data: None,
+        })
+    }
+
+    async fn verify_payment(&self, request: StatusRequest) -> PaymentResult<StatusResponse> {
+        Ok(StatusResponse {
+            status: PaymentState::Pending,
+            transaction_reference: request.transaction_reference,
+            provider_reference: request.provider_reference,
+            amount: None,
+            payment_method: None,
+            timestamp: None,
+            failure_reason: None,
+            provider_data: None,
+        })
+    }
+
+    async fn process_withdrawal(
+        &self,
+        _request: WithdrawalRequest,
+    ) -> PaymentResult<WithdrawalResponse> {
+        unimplemented!("onramp only")
+    }
+
+    async fn get_payment_status(&self, request: StatusRequest) -> PaymentResult<StatusResponse> {
+        self.verify_payment(request).await
+    }
+
+    fn name(&self) -> ProviderName {
+        ProviderName::Flutterwave
+    }
+
+    fn supported_currencies(&self) -> &'static [&'static str] {
+        &["NGN"]
+    }
+
+    fn supported_countries(&self) -> &'static [&'static str] {
+        &["NG"]
+    }
+
+    fn verify_webhook(
+        &self,
+        _payload: &[u8],
+        _signature: &str,
+    ) -> PaymentResult<WebhookVerificationResult> {
+        unimplemented!("onramp only")
+    }
+
+    fn parse_webhook_event(&self, _payload: &[u8]) -> PaymentResult<WebhookEvent> {
+        unimplemented!("onramp only")
+    }
+}
+
+async fn budget_used(pool: &PgPool, rule_id: Uuid) -> BigDecimal {
+    sqlx::query_scalar("SELECT budget_used FROM fee_rules WHERE id = $1")
+        .bind(rule_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_initiated_onramp_spends_promo_budget() {
+    let pool = setup_test_db().await;
+    seed_fee_structures(&pool).await;
+
+    // Waives the whole platform fee, up to ₦60 in total
+    let promo_code = format!("TEST{}", Uuid::new_v4().simple());
+    let rule_id: Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO fee_rules
+        (name, rule_type, transaction_type, priority, promo_code, discount_percent, budget_total)
+        VALUES ('test promo', 'promo_code', 'onramp', 100, $1, 100, 60)
+        RETURNING id
+        "#,
+    )
+    .bind(&promo_code)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    let fees = Arc::new(FeeCalculationService::new(pool.clone()));
+    let amount = BigDecimal::from_str("10000").unwrap();
+    let context = FeeContext {
+        wallet_address: Some("GTESTWALLET".to_string()),
+        promo_code: Some(promo_code.clone()),
+        ..FeeContext::default()
+    };
+
+    // An estimate shows the discount without spending the budget
+    let estimate = fees
+        .calculate_fees_with_context(
+            "onramp",
+            amount.clone(),
+            Some("flutterwave"),
+            Some("card"),
+            &context,
+        )
+        .await
+        .expect("Failed to calculate fees");
+    assert_eq!(estimate.platform.calculated, BigDecimal::from(0));
+    assert_eq!(estimate.applied_rules.len(), 1);
+    assert_eq!(budget_used(&pool, rule_id).await, BigDecimal::from(0));
+
+    let orchestrator = PaymentOrchestrator::new(
+        vec![Arc::new(PendingProvider) as Arc<dyn PaymentProvider>],
+        Arc::new(TransactionRepository::new(pool.clone())),
+        OrchestratorConfig::default(),
+    )
+    .with_fee_calculation(fees);
+    let initiate = |transaction_id: Uuid| PaymentInitiationRequest {
+        wallet_address: "GTESTWALLET".to_string(),
+        amount: amount.clone(),
+        currency: "NGN".to_string(),
+        payment_method: PaymentMethod::Card,
+        customer_email: Some("test@example.com".to_string()),
+        customer_phone: None,
+        callback_url: None,
+        idempotency_key: Some(transaction_id.to_string()),
+        metadata: None,
+        transaction_id: Some(transaction_id.to_string()),
+        promo_code: Some(promo_code.clone()),
+        partner_api_key: None,
+    };
+
+    // Charging the onramp spends the ₦50 platform fee it waived and logs
+    // the rule against the transaction
+    let transaction_id = Uuid::new_v4();
+    orchestrator
+        .initiate_payment(initiate(transaction_id))
+        .await
+        .expect("Failed to initiate payment");
+    assert_eq!(budget_used(&pool, rule_id).await, BigDecimal::from(50));
+    let logged: Vec<Uuid> = sqlx::query_scalar(
+        "SELECT unnest(applied_rule_ids) FROM fee_calculation_logs WHERE transaction_id = $1",
+    )
+    .bind(transaction_id)
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(logged, vec![rule_id]);
+
+    // The next onramp only gets what is left of the budget
+    orchestrator
+        .initiate_payment(initiate(Uuid::new_v4()))
+        .await
+        .expect("Failed to initiate payment");
+    assert_eq!(budget_used(&pool, rule_id).await, BigDecimal::from(60));
+}
+
+#[tokio::test]
+async fn test_simulated_current_pricing_uses_payment_method_tiers() {
+    let pool = setup_test_db().await;
+    seed_fee_structures(&pool).await;
+    sqlx::query("DELETE FROM fee_structures WHERE payment_method = 'ussd'")
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query(
+        r#"
+        INSERT INTO fee_structures 
+        (transaction_type, payment_provider, payment_method, min_amount, max_amount,
+         provider_fee_percent, provider_fee_flat, provider_fee_cap, platform_fee_percent, is_active)
+        VALUES ('onramp', 'flutterwave', 'ussd', 1000, 50000, 1.0, 0, 2000, 0.1, true)
+        "#
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    // Live tiers are read once; the snapshot prices like the live lookup
+    let live = FeeCalculationService::new(pool.clone())
+        .active_structures()
+        .await
+        .expect("Failed to load fee structures");
+    let current = FeeCalculationService::simulated(pool.clone(), live);
+    let amount = BigDecimal::from_str("20000").unwrap();
+
+    // Platform fee: 20,000 × 0.5% = 100 by card, × 0.1% = 20 by USSD
+    for (method, platform_fee) in [("card", "100"), ("ussd", "20")] {
+        let simulated = current
+            .calculate_fees("onramp", amount.clone(), Some("flutterwave"), Some(method))
+            .await
+            .expect("Failed to simulate fees");
+        assert_eq!(simulated.platform.calculated, BigDecimal::from_str(platform_fee).unwrap());
+    }
+
+    sqlx::query("DELETE FROM fee_structures WHERE payment_method = 'ussd'")
+        .execute(&pool)
+        .await
+        .unwrap();
+}
@@ -9,8 +9,8 @@ mod webhook_tests {
         let err = WebhookProcessorError::InvalidSignature;
         assert_eq!(err.to_string(), "Invalid signature");
 
-        let err = WebhookProcessorError::AlreadyProcessed;
-        assert_eq!(err.to_string(), "Already processed");
+        let err = WebhookProcessorError::InvalidPayload("not json".to_string());
+        assert_eq!(err.to_string(), "Invalid payload: not json");
 
         let err = WebhookProcessorError::UnknownProvider("test".to_string());
         assert_eq!(err.to_string(), "Unknown provider: test");